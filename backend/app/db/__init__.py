@@ -1,3 +1,3 @@
-from .database import get_db, init_db, engine, AsyncSessionLocal
+from .database import get_db, init_db, engine, AsyncSessionLocal, get_db_status, queue_write
 
-__all__ = ["get_db", "init_db", "engine", "AsyncSessionLocal"]
+__all__ = ["get_db", "init_db", "engine", "AsyncSessionLocal", "get_db_status", "queue_write"]
@@ -1,7 +1,17 @@
+import asyncio
+import time
+from collections import deque
+from typing import Awaitable, Callable, Deque, List, Optional
+
+from sqlalchemy import event, inspect, text
+from sqlalchemy.exc import OperationalError, SQLAlchemyError
 from sqlalchemy.ext.asyncio import AsyncSession, create_async_engine, async_sessionmaker
 from sqlalchemy.orm import DeclarativeBase
 
 from app.config import settings
+from app.db.encryption import connect_async, engine_dbapi, is_encrypted
+from app.db.migrations import Migration, apply_migrations, check_schema
+from app.errors import CommandError, ErrorCode
 
 
 class Base(DeclarativeBase):
@@ -10,13 +20,41 @@ class Base(DeclarativeBase):
 
 # Create async engine
 db_path = settings.get_database_path()
-DATABASE_URL = f"sqlite+aiosqlite:///{db_path}"
+DATABASE_URL = settings.get_database_url()
+IS_SQLITE = settings.uses_sqlite()
+
+if IS_SQLITE:
+    # Connections go through SQLCipher when it's installed, unlocking encrypted files with the keychain key.
+    # SQLAlchemy caches compiled queries and each connection caches prepared statements, so repeated
+    # queries skip both compiling and SQLite's parsing.
+    engine = create_async_engine(
+        DATABASE_URL,
+        echo=settings.debug,
+        async_creator=lambda: connect_async(db_path, cached_statements=settings.db_statement_cache_size),
+        module=engine_dbapi(),
+        query_cache_size=settings.db_query_cache_size,
+    )
+else:
+    # A server database outlives connections it drops, so each is checked before reuse
+    engine = create_async_engine(
+        DATABASE_URL,
+        echo=settings.debug,
+        pool_pre_ping=True,
+        query_cache_size=settings.db_query_cache_size,
+    )
+
+
+def _configure_connection(dbapi_connection, _):
+    """Apply journal mode and lock wait settings to every new SQLite connection"""
+    cursor = dbapi_connection.cursor()
+    cursor.execute(f"PRAGMA journal_mode={settings.db_journal_mode}")
+    cursor.execute(f"PRAGMA busy_timeout={int(settings.db_busy_timeout_ms)}")
+    cursor.close()
+
+
+if IS_SQLITE:
+    event.listen(engine.sync_engine, "connect", _configure_connection)
 
-engine = create_async_engine(
-    DATABASE_URL,
-    echo=settings.debug,
-    connect_args={"check_same_thread": False},
-)
 
 AsyncSessionLocal = async_sessionmaker(
     engine,
@@ -25,14 +63,257 @@ AsyncSessionLocal = async_sessionmaker(
 )
 
 
-async def init_db():
-    """Initialize database tables"""
+def _add_missing_columns(conn):
+    """Add columns that were introduced after a table was first created"""
+    inspector = inspect(conn)
+    for table in Base.metadata.sorted_tables:
+        if not inspector.has_table(table.name):
+            continue
+
+        existing = {column["name"] for column in inspector.get_columns(table.name)}
+        for column in table.columns:
+            if column.name in existing:
+                continue
+
+            ddl = f"ALTER TABLE {table.name} ADD COLUMN {column.name} {column.type.compile(dialect=conn.dialect)}"
+            if column.server_default is not None:
+                default = column.server_default.arg
+                ddl += f" DEFAULT {default.text if hasattr(default, 'text') else repr(default)}"
+            conn.execute(text(ddl))
+
+
+def _add_missing_indexes(conn):
+    """Create indexes that were introduced after a table was first created"""
+    inspector = inspect(conn)
+    for table in Base.metadata.sorted_tables:
+        if not inspector.has_table(table.name):
+            continue
+
+        existing = {index["name"] for index in inspector.get_indexes(table.name)}
+        for index in table.indexes:
+            if index.name not in existing:
+                index.create(conn)
+
+
+# ============================================
+# Availability and Write Queue
+# ============================================
+
+# Initialization retry delays grow from the initial to the max backoff, in seconds
+INIT_INITIAL_BACKOFF = 1.0
+INIT_MAX_BACKOFF = 30.0
+
+# Writes queued while the database is unavailable beyond this are dropped, oldest first
+MAX_QUEUED_WRITES = 1000
+
+# Operational errors that clear up on their own, so the write is retried rather than dropped
+TRANSIENT_ERRORS = ("locked", "busy", "unable to open", "disk i/o", "disk is full", "readonly")
+
+WriteOperation = Callable[[AsyncSession], Awaitable[None]]
+
+
+class DatabaseState:
+    """Tracks whether the database is usable and holds writes made while it isn't"""
+
+    def __init__(self):
+        self.available = False
+        self.attempts = 0
+        self.error: Optional[str] = None
+        self.initialized_at: Optional[float] = None
+        self.dropped_writes = 0
+        self.pending_writes: Deque[WriteOperation] = deque(maxlen=MAX_QUEUED_WRITES)
+        self._retry_task: Optional[asyncio.Task] = None
+        self._drain_task: Optional[asyncio.Task] = None
+
+
+db_state = DatabaseState()
+
+
+async def _initialize() -> List[Migration]:
+    """Create and migrate the schema, then check it matches the models; returns the migrations applied"""
     async with engine.begin() as conn:
         await conn.run_sync(Base.metadata.create_all)
+        await conn.run_sync(_add_missing_columns)
+        await conn.run_sync(_add_missing_indexes)
+        applied = await conn.run_sync(apply_migrations)
+        await conn.run_sync(check_schema, Base.metadata)
+    return applied
+
+
+async def migrate_only() -> List[Migration]:
+    """Bring the schema up to date without starting the server, for deployments that migrate separately"""
+    try:
+        return await _initialize()
+    finally:
+        await engine.dispose()
+
+
+async def init_db():
+    """Initialize database tables, retrying in the background if the database is unavailable"""
+    if await _try_initialize():
+        return
+    db_state._retry_task = asyncio.create_task(_retry_initialize())
+
+
+async def _try_initialize() -> bool:
+    db_state.attempts += 1
+    try:
+        await _initialize()
+    except (OperationalError, OSError) as e:
+        db_state.available = False
+        db_state.error = str(e)
+        print(f"Database initialization failed (attempt {db_state.attempts}): {e}")
+        return False
+
+    db_state.initialized_at = time.time()
+    await flush_pending_writes()
+    if db_state.pending_writes:
+        _retry_writes_later()
+    else:
+        db_state.available = True
+        db_state.error = None
+    return True
+
+
+async def _retry_initialize():
+    backoff = INIT_INITIAL_BACKOFF
+    while True:
+        await asyncio.sleep(backoff)
+        if await _try_initialize():
+            return
+        backoff = min(backoff * 2, INIT_MAX_BACKOFF)
+
+
+async def queue_write(operation: WriteOperation) -> bool:
+    """
+    Run a write in its own session, or queue it while the database is unavailable
+
+    Returns True if the write ran now. A write that fails because the database
+    is locked or unreachable marks it unavailable and is queued; queued writes
+    are retried in order in the background until they all succeed, which marks
+    the database available again. This is for background records whose
+    callers can't wait, such as AI usage, cached AI results and shell step
+    logs; request handlers get a 503 from get_db instead so the caller can retry.
+    """
+    if db_state.available and not db_state.pending_writes:
+        try:
+            async with AsyncSessionLocal() as session:
+                await operation(session)
+                await session.commit()
+            return True
+        except OperationalError as e:
+            db_state.error = str(e)
+            if not _is_transient(e) and await _database_responds():
+                db_state.dropped_writes += 1
+                return False
+            db_state.available = False
+        except SQLAlchemyError as e:
+            # Such as an integrity error for a row whose parent was deleted; retrying won't help
+            db_state.error = str(e)
+            db_state.dropped_writes += 1
+            return False
+
+    if len(db_state.pending_writes) == MAX_QUEUED_WRITES:
+        db_state.dropped_writes += 1
+    db_state.pending_writes.append(operation)
+    _retry_writes_later()
+    return False
+
+
+def _retry_writes_later() -> None:
+    # Until initialization succeeds, its retry loop flushes the queue instead
+    if db_state.initialized_at is None:
+        return
+    if not db_state._drain_task or db_state._drain_task.done():
+        db_state._drain_task = asyncio.create_task(_drain_pending_writes())
+
+
+async def _drain_pending_writes() -> None:
+    """Retry queued writes with growing delays until the queue is empty, then mark the database available"""
+    backoff = INIT_INITIAL_BACKOFF
+    while True:
+        await asyncio.sleep(backoff)
+        await flush_pending_writes()
+        if not db_state.pending_writes:
+            db_state.available = True
+            db_state.error = None
+            return
+        backoff = min(backoff * 2, INIT_MAX_BACKOFF)
+
+
+def _is_transient(error: OperationalError) -> bool:
+    message = str(error).lower()
+    return any(marker in message for marker in TRANSIENT_ERRORS)
+
+
+async def _database_responds() -> bool:
+    try:
+        async with engine.connect() as conn:
+            await conn.execute(text("SELECT 1"))
+        return True
+    except (OperationalError, OSError):
+        return False
+
+
+async def flush_pending_writes() -> int:
+    """
+    Run queued writes in order, stopping at the first that still can't be written
+
+    A write that fails for any other reason than the database being locked or
+    unreachable won't succeed on a retry either, such as one naming a missing
+    column or a deleted parent row, so it's dropped rather than holding up the
+    writes behind it.
+    """
+    flushed = 0
+    while db_state.pending_writes:
+        operation = db_state.pending_writes[0]
+        try:
+            async with AsyncSessionLocal() as session:
+                await operation(session)
+                await session.commit()
+        except OperationalError as e:
+            db_state.error = str(e)
+            if _is_transient(e) or not await _database_responds():
+                break
+            db_state.dropped_writes += 1
+        except Exception as e:
+            # Anything else would end the drain and leave the database marked unavailable
+            db_state.error = str(e)
+            db_state.dropped_writes += 1
+        else:
+            flushed += 1
+        db_state.pending_writes.popleft()
+    return flushed
+
+
+async def get_db_status() -> dict:
+    """Get database availability, settings, and write queue state"""
+    journal_mode = None
+    if db_state.available and IS_SQLITE:
+        try:
+            async with engine.connect() as conn:
+                journal_mode = (await conn.execute(text("PRAGMA journal_mode"))).scalar()
+        except OperationalError as e:
+            db_state.error = str(e)
+    return {
+        "available": db_state.available,
+        "dialect": engine.dialect.name,
+        "path": str(db_path) if IS_SQLITE else engine.url.render_as_string(hide_password=True),
+        "encrypted": IS_SQLITE and is_encrypted(db_path),
+        "attempts": db_state.attempts,
+        "error": db_state.error,
+        "initialized_at": db_state.initialized_at,
+        "journal_mode": journal_mode,
+        "busy_timeout_ms": settings.db_busy_timeout_ms,
+        "queued_writes": len(db_state.pending_writes),
+        "dropped_writes": db_state.dropped_writes,
+    }
 
 
 async def get_db():
     """Dependency to get database session"""
+    if not db_state.available:
+        raise CommandError(503, ErrorCode.DB_ERROR, f"Database unavailable: {db_state.error}")
     async with AsyncSessionLocal() as session:
         try:
             yield session
@@ -0,0 +1,166 @@
+"""
+Database Encryption - Opens the SQLite database through SQLCipher when it's encrypted
+
+SQLCipher and the OS keychain are optional: install sqlcipher3-binary and keyring
+to encrypt the database. Whether a file is encrypted is read from its header, so
+there's no setting that can disagree with the file itself.
+"""
+import secrets
+import sqlite3
+from pathlib import Path
+from types import SimpleNamespace
+from typing import Any, Optional
+
+import aiosqlite
+
+try:
+    import sqlcipher3.dbapi2 as sqlcipher
+except ImportError:
+    sqlcipher = None
+
+try:
+    import keyring
+    from keyring.errors import KeyringError
+except ImportError:
+    keyring = None
+    KeyringError = Exception
+
+KEYRING_SERVICE = "com.autotest.ai"
+KEYRING_KEY_NAME = "database-key"
+
+# Every plain SQLite file starts with this; SQLCipher files are random from the first byte
+SQLITE_HEADER = b"SQLite format 3\x00"
+
+# Names the aiosqlite adapter reads from its driver, taken from SQLCipher so its errors map like sqlite3's
+DBAPI_NAMES = (
+    "DatabaseError", "Error", "IntegrityError", "NotSupportedError", "OperationalError", "ProgrammingError",
+    "sqlite_version", "sqlite_version_info",
+)
+
+# What either driver raises for a file that isn't a usable database
+DATABASE_ERRORS = (sqlite3.DatabaseError, *((sqlcipher.DatabaseError,) if sqlcipher else ()))
+
+
+class EncryptionError(Exception):
+    """Raised when the database can't be encrypted, decrypted or unlocked"""
+
+
+def is_encrypted(path: Path) -> bool:
+    try:
+        with open(path, "rb") as f:
+            header = f.read(len(SQLITE_HEADER))
+    except OSError:
+        return False
+    return len(header) == len(SQLITE_HEADER) and header != SQLITE_HEADER
+
+
+def encryption_available() -> bool:
+    return sqlcipher is not None and keyring is not None
+
+
+def require_encryption() -> None:
+    if sqlcipher is None:
+        raise EncryptionError("Database encryption needs SQLCipher: pip install sqlcipher3-binary")
+    if keyring is None:
+        raise EncryptionError("Database encryption needs the OS keychain: pip install keyring")
+
+
+def get_key(create: bool = False) -> str:
+    """The database key from the OS keychain as hex, generating one if asked"""
+    require_encryption()
+    try:
+        key = keyring.get_password(KEYRING_SERVICE, KEYRING_KEY_NAME)
+        if not key and create:
+            key = secrets.token_hex(32)
+            keyring.set_password(KEYRING_SERVICE, KEYRING_KEY_NAME, key)
+    except KeyringError as e:
+        raise EncryptionError(f"Can't use the OS keychain: {e}")
+    if not key:
+        raise EncryptionError("The database is encrypted but its key isn't in the OS keychain")
+    return key
+
+
+def has_key() -> bool:
+    try:
+        return bool(keyring and keyring.get_password(KEYRING_SERVICE, KEYRING_KEY_NAME))
+    except KeyringError:
+        return False
+
+
+def _raw_key(key: str) -> str:
+    # A raw 256-bit key, which skips SQLCipher's key derivation
+    return f"x'{key}'"
+
+
+def connect(path: Path, readonly: bool = False, **kwargs: Any):
+    """
+    Open a database file, unlocking it with the keychain key when it's encrypted
+
+    SQLCipher is used whenever it's installed, so its connections can be mixed,
+    for example by the backup API; it opens plain files like SQLite does.
+    """
+    encrypted = is_encrypted(path)
+    if encrypted:
+        require_encryption()
+    module = sqlcipher or sqlite3
+    connection = module.connect(f"file:{path}?mode=ro" if readonly else str(path), uri=readonly, **kwargs)
+    if encrypted:
+        try:
+            connection.execute(f"PRAGMA key = \"{_raw_key(get_key())}\"")
+        except EncryptionError:
+            connection.close()
+            raise
+    return connection
+
+
+async def connect_async(path: Path, **kwargs: Any) -> aiosqlite.Connection:
+    """Open an aiosqlite connection for the engine's pool, passing kwargs such as cached_statements to connect"""
+    def connector():
+        try:
+            return connect(path, check_same_thread=False, **kwargs)
+        except EncryptionError as e:
+            # Raised as the driver's error so the engine reports the database as unavailable
+            raise (sqlcipher or sqlite3).OperationalError(str(e))
+
+    connection = aiosqlite.Connection(connector, iter_chunk_size=64)
+    connection.daemon = True
+    return await connection
+
+
+def engine_dbapi() -> Optional[Any]:
+    """The driver module for the engine: aiosqlite over SQLCipher when it's installed, else the default"""
+    if sqlcipher is None:
+        return None
+    from sqlalchemy.dialects.sqlite.aiosqlite import AsyncAdapt_aiosqlite_dbapi
+
+    driver = SimpleNamespace(connect=aiosqlite.connect, **{name: getattr(sqlcipher, name) for name in DBAPI_NAMES})
+    return AsyncAdapt_aiosqlite_dbapi(driver, sqlcipher)
+
+
+def export_database(source: Path, target: Path, encrypt: bool) -> None:
+    """Copy a database into a new file, encrypted with the keychain key or in plain text"""
+    require_encryption()
+    key = get_key(create=encrypt)
+    connection = connect(source)
+    try:
+        version = connection.execute("PRAGMA user_version").fetchone()[0]
+        connection.execute("ATTACH DATABASE ? AS exported KEY ?", (str(target), _raw_key(key) if encrypt else ""))
+        connection.execute("SELECT sqlcipher_export('exported')")
+        connection.execute(f"PRAGMA exported.user_version = {int(version)}")
+        connection.execute("DETACH DATABASE exported")
+    except sqlcipher.DatabaseError as e:
+        raise EncryptionError(f"Couldn't export the database: {e}")
+    finally:
+        connection.close()
+
+
+def check_integrity(path: Path) -> Optional[str]:
+    """None when a database passes SQLite's integrity check, otherwise the problem"""
+    connection = connect(path, readonly=True)
+    try:
+        result = connection.execute("PRAGMA integrity_check").fetchone()
+    finally:
+        connection.close()
+    if not result or result[0] != "ok":
+        return result[0] if result else "no result"
+    return None
@@ -0,0 +1,175 @@
+"""
+Schema Migrations - Versioned data and schema changes applied once on startup
+
+New tables, columns and indexes are still created from the models on every
+start. Changes that can't be derived from the models, such as backfilling a
+new column from existing rows, are migrations: numbered steps shipped with
+the backend, applied in order, and recorded in schema_migrations so each runs
+once. They also run on fresh databases, so they must work on empty tables.
+
+After migrating, the schema is checked against the models. A database last
+migrated by a newer backend, or one whose tables or columns don't match the
+models, fails startup with a list of the differences instead of erroring on
+the first query that touches them.
+"""
+from collections import defaultdict
+from dataclasses import dataclass
+from datetime import datetime
+from typing import Any, Callable, Dict, List, Optional, Tuple
+
+from sqlalchemy import (
+    Boolean,
+    Column,
+    Connection,
+    Date,
+    DateTime,
+    Integer,
+    MetaData,
+    Numeric,
+    String,
+    Table,
+    func,
+    inspect,
+    select,
+    text,
+)
+
+
+@dataclass(frozen=True)
+class Migration:
+    version: int
+    description: str
+    apply: Callable[[Connection], None]
+
+
+def _baseline(conn: Connection) -> None:
+    """Tables and columns up to this version are created from the models"""
+
+
+def _number_tied_positions(conn: Connection) -> None:
+    """
+    Give rows that share a position distinct ones, in creation order
+
+    Scenario positions were added with a default of 0, so every scenario that
+    existed then is tied with the others in its test case. Each scope with ties
+    is renumbered 1, 2, 3, ... keeping the order rows are listed in.
+    """
+    for table, order_column, scope_column in (
+        ("scenarios", "position", "test_case_id"),
+        ("steps", "step_order", "scenario_id"),
+        ("suite_scenarios", "position", "suite_id"),
+    ):
+        rows = conn.execute(text(
+            f"SELECT id, {scope_column}, {order_column} FROM {table} "
+            f"ORDER BY {scope_column}, {order_column}, created_at, id"
+        )).all()
+        scopes: Dict[Any, List[Tuple[str, float]]] = defaultdict(list)
+        for row_id, scope_id, position in rows:
+            scopes[scope_id].append((row_id, position))
+        for scoped in scopes.values():
+            if len({position for _, position in scoped}) == len(scoped):
+                continue
+            for index, (row_id, _) in enumerate(scoped, start=1):
+                conn.execute(
+                    text(f"UPDATE {table} SET {order_column} = :position WHERE id = :id"),
+                    {"position": float(index), "id": row_id},
+                )
+
+
+MIGRATIONS: List[Migration] = [
+    Migration(1, "Baseline schema created from the models", _baseline),
+    Migration(2, "Number tied scenario, step and suite positions in creation order", _number_tied_positions),
+]
+
+_versions = Table(
+    "schema_migrations",
+    MetaData(),
+    Column("version", Integer, primary_key=True),
+    Column("description", String, nullable=False),
+    Column("applied_at", DateTime, nullable=False),
+)
+
+
+# SQLite keeps fractional values in INTEGER columns, so columns widened to floats need no rebuild there
+_SQLITE_COMPATIBLE = {("number", "integer")}
+
+
+class SchemaDriftError(Exception):
+    """Raised when the database schema doesn't match what this backend expects"""
+
+    def __init__(self, problems: List[str]):
+        self.problems = problems
+        super().__init__("Database schema doesn't match this backend:\n" + "\n".join(f"- {p}" for p in problems))
+
+
+def latest_version() -> int:
+    return MIGRATIONS[-1].version
+
+
+def current_version(conn: Connection) -> int:
+    """The newest migration applied to the database, 0 before any"""
+    _versions.create(conn, checkfirst=True)
+    return conn.execute(select(func.max(_versions.c.version))).scalar() or 0
+
+
+def apply_migrations(conn: Connection) -> List[Migration]:
+    """Apply the migrations the database hasn't had yet, in order, returning them"""
+    version = current_version(conn)
+    applied = []
+    for migration in MIGRATIONS:
+        if migration.version <= version:
+            continue
+        migration.apply(conn)
+        conn.execute(
+            _versions.insert().values(
+                version=migration.version, description=migration.description, applied_at=datetime.utcnow()
+            )
+        )
+        applied.append(migration)
+    return applied
+
+
+def _type_family(column_type) -> Optional[str]:
+    """The kind of value a column type stores, so reflected and declared types can be compared"""
+    for family, types in (
+        ("boolean", (Boolean,)),
+        ("integer", (Integer,)),
+        ("number", (Numeric,)),
+        ("datetime", (DateTime, Date)),
+        ("text", (String,)),
+    ):
+        if isinstance(column_type, types):
+            return family
+    return None
+
+
+def check_schema(conn: Connection, metadata: MetaData) -> None:
+    """Raise SchemaDriftError listing every difference between the database and the models"""
+    problems = []
+    version = current_version(conn)
+    if version > latest_version():
+        problems.append(
+            f"the database is at schema version {version}, newer than the {latest_version()} this backend "
+            "knows; upgrade the backend or restore a backup taken before the upgrade"
+        )
+
+    inspector = inspect(conn)
+    for table in metadata.sorted_tables:
+        if not inspector.has_table(table.name):
+            problems.append(f"table {table.name} is missing")
+            continue
+        existing = {column["name"]: column for column in inspector.get_columns(table.name)}
+        for column in table.columns:
+            reflected = existing.get(column.name)
+            if reflected is None:
+                problems.append(f"column {table.name}.{column.name} is missing")
+                continue
+            expected, found = _type_family(column.type), _type_family(reflected["type"])
+            compatible = conn.dialect.name == "sqlite" and (expected, found) in _SQLITE_COMPATIBLE
+            if expected and found and expected != found and not compatible:
+                problems.append(
+                    f"column {table.name}.{column.name} stores {found} values, but the backend expects {expected}"
+                )
+
+    if problems:
+        raise SchemaDriftError(problems)
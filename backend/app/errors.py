@@ -0,0 +1,106 @@
+"""
+API Errors - Gives every error response a code the frontend can branch on
+
+Error bodies keep FastAPI's "detail" message and add "code" and "details":
+{"detail": "Device offline", "code": "device_unavailable", "details": {...}}
+"""
+from enum import Enum
+from typing import Any, Dict, Optional
+
+from fastapi import FastAPI, HTTPException, Request
+from fastapi.exceptions import RequestValidationError
+from fastapi.responses import JSONResponse
+from sqlalchemy.exc import OperationalError
+from starlette.exceptions import HTTPException as StarletteHTTPException
+
+
+class ErrorCode(str, Enum):
+    NOT_FOUND = "not_found"
+    VALIDATION_ERROR = "validation_error"
+    CONFLICT = "conflict"
+    UNAUTHORIZED = "unauthorized"
+    FORBIDDEN = "forbidden"
+    DEVICE_UNAVAILABLE = "device_unavailable"
+    DEVICE_ERROR = "device_error"
+    TOOL_NOT_FOUND = "tool_not_found"  # adb, xcrun, idb and similar aren't installed
+    AI_PROVIDER_ERROR = "ai_provider_error"
+    INTEGRATION_ERROR = "integration_error"  # Jira, GitHub and other external services
+    DB_ERROR = "db_error"
+    TIMEOUT = "timeout"
+    INTERNAL_ERROR = "internal_error"
+
+
+# The code for errors raised as a plain HTTPException
+STATUS_CODES = {
+    400: ErrorCode.VALIDATION_ERROR,
+    401: ErrorCode.UNAUTHORIZED,
+    403: ErrorCode.FORBIDDEN,
+    404: ErrorCode.NOT_FOUND,
+    409: ErrorCode.CONFLICT,
+    413: ErrorCode.VALIDATION_ERROR,
+    422: ErrorCode.VALIDATION_ERROR,
+    503: ErrorCode.DB_ERROR,
+    504: ErrorCode.TIMEOUT,
+}
+
+
+class CommandError(HTTPException):
+    """An HTTP error with a code and optional structured details for the frontend"""
+
+    def __init__(
+        self,
+        status_code: int,
+        code: ErrorCode,
+        message: str,
+        details: Optional[Dict[str, Any]] = None,
+        headers: Optional[Dict[str, str]] = None,
+    ):
+        super().__init__(status_code=status_code, detail=message, headers=headers)
+        self.code = code
+        self.details = details
+
+
+def conflict_error(message: str, current: Any) -> CommandError:
+    """A 409 for an edit based on an outdated copy, carrying the current copy to merge with"""
+    return CommandError(409, ErrorCode.CONFLICT, message, {"current": current})
+
+
+def error_body(code: ErrorCode, message: Any, details: Any = None) -> Dict[str, Any]:
+    return {"detail": message, "code": code.value, "details": details}
+
+
+def _code_for(exc: StarletteHTTPException) -> ErrorCode:
+    if isinstance(exc, CommandError):
+        return exc.code
+    return STATUS_CODES.get(exc.status_code, ErrorCode.INTERNAL_ERROR)
+
+
+async def _http_error(request: Request, exc: StarletteHTTPException) -> JSONResponse:
+    return JSONResponse(
+        error_body(_code_for(exc), exc.detail, getattr(exc, "details", None)),
+        status_code=exc.status_code,
+        headers=getattr(exc, "headers", None),
+    )
+
+
+async def _validation_error(request: Request, exc: RequestValidationError) -> JSONResponse:
+    errors = exc.errors()
+    message = "; ".join(f"{'.'.join(str(part) for part in e['loc'])}: {e['msg']}" for e in errors)
+    # Pydantic's error contexts can hold exceptions, which aren't JSON
+    details = {"errors": [{key: e[key] for key in ("loc", "msg", "type")} for e in errors]}
+    return JSONResponse(error_body(ErrorCode.VALIDATION_ERROR, message, details), status_code=422)
+
+
+async def _database_error(request: Request, exc: OperationalError) -> JSONResponse:
+    return JSONResponse(error_body(ErrorCode.DB_ERROR, f"Database error: {exc.orig}"), status_code=503)
+
+
+async def _unhandled_error(request: Request, exc: Exception) -> JSONResponse:
+    return JSONResponse(error_body(ErrorCode.INTERNAL_ERROR, str(exc) or type(exc).__name__), status_code=500)
+
+
+def register_error_handlers(app: FastAPI) -> None:
+    app.add_exception_handler(StarletteHTTPException, _http_error)
+    app.add_exception_handler(RequestValidationError, _validation_error)
+    app.add_exception_handler(OperationalError, _database_error)
+    app.add_exception_handler(Exception, _unhandled_error)
@@ -1,11 +1,29 @@
 import platform
 from contextlib import asynccontextmanager
 
-from fastapi import FastAPI
+from fastapi import FastAPI, Request
 from fastapi.middleware.cors import CORSMiddleware
+from fastapi.routing import APIRoute
 
 from app.config import settings
-from app.db import init_db
+from app.db import engine, get_db_status, init_db
+from app.errors import register_error_handlers
+from app.services.agent_worker import agent_worker
+from app.services.agents import job_lease_monitor
+from app.services.ai_jobs import stop_polling as stop_ai_job_polling
+from app.services.backup import snapshot_scheduler
+from app.services.email_reports import schedule_failure_mailer
+from app.services.github_webhooks import check_reporter
+from app.services.health_monitor import health_monitor
+from app.services.runner_events import runner_events
+from app.services.schedules import run_scheduler
+from app.services.run_lifecycle import recover_orphaned_runs
+from app.services.secret_masking import refresh_secrets
+from app.services.service_manager import service_manager
+from app.services.ordering import order_rebalancer
+from app.services.storage import retention_cleaner
+from app.services.tools import refresh_tool_overrides
+from app.services.tracing import refresh_tracing, tracer
 from app.routers import (
     projects_router,
     test_cases_router,
@@ -18,6 +36,36 @@ from app.routers import (
     mobile_router,
     integrations_router,
     test_runner_router,
+    executions_router,
+    settings_router,
+    events_router,
+    backups_router,
+    elements_router,
+    suites_router,
+    known_issues_router,
+    control_router,
+    plugins_router,
+    shell_router,
+    remote_devices_router,
+    baselines_router,
+    queue_router,
+    agents_router,
+    attachments_router,
+    schedules_router,
+    preferences_router,
+    doctor_router,
+    screen_maps_router,
+    stress_tests_router,
+    browsers_router,
+    custom_fields_router,
+    saved_filters_router,
+    quick_actions_router,
+    drafts_router,
+    test_selection_router,
+    webhooks_router,
+    storage_router,
+    debugging_router,
+    synonyms_router,
 )
 
 
@@ -26,19 +74,74 @@ async def lifespan(app: FastAPI):
     """Application lifespan handler"""
     # Startup
     print(f"Starting {settings.app_name} v{settings.app_version}")
-    print(f"Database: {settings.get_database_path()}")
+    print(f"Database: {engine.url.render_as_string(hide_password=True)}")
     await init_db()
+    await refresh_tool_overrides()
+    await refresh_tracing()
+    await refresh_secrets()
+    await recover_orphaned_runs()
+    health_monitor.start()
+    snapshot_scheduler.start()
+    retention_cleaner.start()
+    order_rebalancer.start()
+    agent_worker.start()
+    job_lease_monitor.start()
+    run_scheduler.start()
+    tracer.start()
+    schedule_failure_mailer.start()
+    check_reporter.start()
     yield
     # Shutdown
     print("Shutting down...")
-
-
+    await schedule_failure_mailer.stop()
+    await check_reporter.stop()
+    await tracer.stop()
+    await run_scheduler.stop()
+    await job_lease_monitor.stop()
+    await agent_worker.stop()
+    await stop_ai_job_polling()
+    await order_rebalancer.stop()
+    await retention_cleaner.stop()
+    await snapshot_scheduler.stop()
+    await health_monitor.stop()
+    await runner_events.close()
+    await service_manager.stop_all()
+
+
+def operation_id(route: APIRoute) -> str:
+    """Name operations by router tag and handler, like test_cases_list_test_cases, so generated clients stay stable"""
+    if not route.tags:
+        return route.name
+    return f"{str(route.tags[0]).replace('-', '_')}_{route.name}"
+
+
+# The contract is served as Swagger UI at /docs and as /openapi.json; `python run.py --export-openapi` writes it out
 app = FastAPI(
     title=settings.app_name,
     version=settings.app_version,
+    description="The desktop app's local API. Routes are under /api; events stream from /api/events/ws.",
     lifespan=lifespan,
+    generate_unique_id_function=operation_id,
 )
 
+register_error_handlers(app)
+
+
+@app.middleware("http")
+async def trace_requests(request: Request, call_next):
+    """Time each API request as a span when tracing is on"""
+    with tracer.span(f"{request.method} {request.url.path}", "request", method=request.method) as span:
+        response = await call_next(request)
+        if span:
+            route = request.scope.get("route")
+            # Named by route template so spans for different IDs group together
+            span.name = f"{request.method} {getattr(route, 'path', request.url.path)}"
+            span.set(path=request.url.path, status_code=response.status_code)
+            if response.status_code >= 500:
+                span.fail(f"HTTP {response.status_code}")
+        return response
+
+
 # CORS middleware
 app.add_middleware(
     CORSMiddleware,
@@ -60,6 +163,36 @@ app.include_router(ai_router, prefix="/api")
 app.include_router(mobile_router, prefix="/api")
 app.include_router(integrations_router, prefix="/api")
 app.include_router(test_runner_router, prefix="/api")
+app.include_router(executions_router, prefix="/api")
+app.include_router(settings_router, prefix="/api")
+app.include_router(events_router, prefix="/api")
+app.include_router(backups_router, prefix="/api")
+app.include_router(elements_router, prefix="/api")
+app.include_router(suites_router, prefix="/api")
+app.include_router(known_issues_router, prefix="/api")
+app.include_router(control_router, prefix="/api")
+app.include_router(plugins_router, prefix="/api")
+app.include_router(shell_router, prefix="/api")
+app.include_router(remote_devices_router, prefix="/api")
+app.include_router(baselines_router, prefix="/api")
+app.include_router(queue_router, prefix="/api")
+app.include_router(agents_router, prefix="/api")
+app.include_router(attachments_router, prefix="/api")
+app.include_router(schedules_router, prefix="/api")
+app.include_router(preferences_router, prefix="/api")
+app.include_router(doctor_router, prefix="/api")
+app.include_router(screen_maps_router, prefix="/api")
+app.include_router(stress_tests_router, prefix="/api")
+app.include_router(browsers_router, prefix="/api")
+app.include_router(custom_fields_router, prefix="/api")
+app.include_router(saved_filters_router, prefix="/api")
+app.include_router(quick_actions_router, prefix="/api")
+app.include_router(drafts_router, prefix="/api")
+app.include_router(test_selection_router, prefix="/api")
+app.include_router(webhooks_router, prefix="/api")
+app.include_router(storage_router, prefix="/api")
+app.include_router(debugging_router, prefix="/api")
+app.include_router(synonyms_router, prefix="/api")
 
 
 @app.get("/health")
@@ -89,6 +222,12 @@ async def get_platform():
     return platform.system().lower()
 
 
+@app.get("/api/db-status")
+async def db_status():
+    """Get database availability and queued write count"""
+    return await get_db_status()
+
+
 @app.get("/api/db-path")
 async def get_db_path():
     """Get database path"""
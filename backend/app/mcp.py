@@ -0,0 +1,39 @@
+"""
+MCP stdio server - Lets desktop AI agents drive devices and edit scenarios
+
+Configure the agent to launch `python -m app.mcp` from the backend directory.
+"""
+import asyncio
+import json
+import sys
+
+from app.db import init_db
+from app.services.mcp_server import PARSE_ERROR, handle_message
+
+
+async def serve() -> None:
+    # stdout carries the protocol, so anything else printed goes to stderr
+    protocol_out = sys.stdout
+    sys.stdout = sys.stderr
+    await init_db()
+
+    loop = asyncio.get_running_loop()
+    while True:
+        line = await loop.run_in_executor(None, sys.stdin.readline)
+        if not line:
+            break
+        if not line.strip():
+            continue
+        try:
+            message = json.loads(line)
+        except ValueError:
+            response = {"jsonrpc": "2.0", "id": None, "error": {"code": PARSE_ERROR, "message": "Parse error"}}
+        else:
+            response = await handle_message(message)
+        if response is not None:
+            protocol_out.write(json.dumps(response) + "\n")
+            protocol_out.flush()
+
+
+if __name__ == "__main__":
+    asyncio.run(serve())
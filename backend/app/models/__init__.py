@@ -16,15 +16,97 @@ from .scenario import (
     ScenarioResponse,
     ScenarioWithSteps,
 )
-from .step import Step, StepCreate, StepUpdate, StepResponse, StepConfig
+from .step import Step, StepCreate, StepUpdate, StepResponse, StepConfig, Assertion
 from .test_run import (
     TestRun,
     TestRunCreate,
     TestRunUpdate,
     TestRunResponse,
     TestRunSummary,
+    StatusCounts,
+    CategoryBreakdown,
+    TestCaseOutcome,
+    RunBreakdown,
+    StepDelta,
+    ScenarioDelta,
+    RunComparison,
 )
-from .step_result import StepResult, StepResultCreate, StepResultResponse
+from .step_result import StepResult, StepResultCreate, StepResultResponse, DurationPoint, SlowStep
+from .app_setting import AppSetting
+from .ai_usage import AiUsage, ModelUsage, AiUsageSummary
+from .ai_cache import AiCacheEntry
+from .element import (
+    Element,
+    ElementCreate,
+    ElementUpdate,
+    ElementResponse,
+    ElementUsage,
+)
+from .scenario_dependency import ScenarioDependency, ScenarioDependencyCreate, ScenarioDependencyResponse
+from .suite import (
+    Suite,
+    SuiteScenario,
+    SuiteCreate,
+    SuiteUpdate,
+    SuiteResponse,
+    SuiteWithScenarios,
+)
+from .known_issue import KnownIssue, KnownIssueCreate, KnownIssueUpdate, KnownIssueResponse
+from .run_log import RunLog, RunLogCreate, RunLogResponse, RunLogPage
+from .remote_device import RemoteDevice, RemoteDeviceCreate, RemoteDeviceUpdate, RemoteDeviceResponse
+from .baseline import ScenarioBaseline, ScenarioBaselineCreate, ScenarioBaselineResponse, BaselineComparison
+from .runner_agent import RunnerAgent, AgentRegistration, RunnerAgentResponse
+from .attachment import Attachment, AttachmentResponse
+from .schedule import (
+    RunSchedule,
+    RunScheduleCreate,
+    RunScheduleUpdate,
+    RunScheduleResponse,
+    UpcomingRun,
+)
+from .run_transition import RunTransition, RunTransitionResponse
+from .screen_map import (
+    AppScreen,
+    ScreenTransition,
+    ScreenElement,
+    AppScreenResponse,
+    ScreenTransitionResponse,
+    ScreenMap,
+    CrawlRequest,
+    CrawlStatus,
+    ScreenCoverage,
+    AppCoverage,
+)
+from .stress_crash import StressCrash, StressCrashResponse, StressTestRequest
+from .project import Viewport
+from .test_run import ViewportBreakdown
+from .custom_field import (
+    CustomField,
+    CustomFieldValue,
+    CustomFieldCreate,
+    CustomFieldUpdate,
+    CustomFieldResponse,
+    CustomFieldValues,
+)
+from .saved_filter import (
+    SavedFilter,
+    FilterCondition,
+    SavedFilterCreate,
+    SavedFilterUpdate,
+    SavedFilterResponse,
+)
+from .stat_counter import StatCounter
+from .draft import Draft, DraftSave, DraftResponse
+from .generated_test import (
+    GeneratedTestCode,
+    GeneratedTest,
+    GenerateTestsResponse,
+    SaveGeneratedTests,
+    GeneratedTestCodeResponse,
+)
+from .file_test_mapping import FileTestMapping, FileTestMappingCreate, FileTestMappingResponse
+from .webhook_run import WebhookRun, WebhookRunResponse
+from .synonym import SynonymGroup, SynonymGroupCreate, SynonymGroupUpdate, SynonymGroupResponse
 
 __all__ = [
     "Project",
@@ -49,12 +131,113 @@ __all__ = [
     "StepUpdate",
     "StepResponse",
     "StepConfig",
+    "Assertion",
     "TestRun",
     "TestRunCreate",
     "TestRunUpdate",
     "TestRunResponse",
     "TestRunSummary",
+    "StatusCounts",
+    "CategoryBreakdown",
+    "TestCaseOutcome",
+    "RunBreakdown",
+    "StepDelta",
+    "ScenarioDelta",
+    "RunComparison",
     "StepResult",
     "StepResultCreate",
     "StepResultResponse",
+    "AppSetting",
+    "AiUsage",
+    "ModelUsage",
+    "AiUsageSummary",
+    "AiCacheEntry",
+    "Element",
+    "ElementCreate",
+    "ElementUpdate",
+    "ElementResponse",
+    "ElementUsage",
+    "ScenarioDependency",
+    "ScenarioDependencyCreate",
+    "ScenarioDependencyResponse",
+    "Suite",
+    "SuiteScenario",
+    "SuiteCreate",
+    "SuiteUpdate",
+    "SuiteResponse",
+    "SuiteWithScenarios",
+    "KnownIssue",
+    "KnownIssueCreate",
+    "KnownIssueUpdate",
+    "KnownIssueResponse",
+    "RunLog",
+    "RunLogCreate",
+    "RunLogResponse",
+    "RunLogPage",
+    "RemoteDevice",
+    "RemoteDeviceCreate",
+    "RemoteDeviceUpdate",
+    "RemoteDeviceResponse",
+    "ScenarioBaseline",
+    "ScenarioBaselineCreate",
+    "ScenarioBaselineResponse",
+    "BaselineComparison",
+    "RunnerAgent",
+    "AgentRegistration",
+    "RunnerAgentResponse",
+    "Attachment",
+    "AttachmentResponse",
+    "RunSchedule",
+    "RunScheduleCreate",
+    "RunScheduleUpdate",
+    "RunScheduleResponse",
+    "UpcomingRun",
+    "RunTransition",
+    "RunTransitionResponse",
+    "AppScreen",
+    "ScreenTransition",
+    "ScreenElement",
+    "AppScreenResponse",
+    "ScreenTransitionResponse",
+    "ScreenMap",
+    "CrawlRequest",
+    "CrawlStatus",
+    "ScreenCoverage",
+    "AppCoverage",
+    "StressCrash",
+    "StressCrashResponse",
+    "StressTestRequest",
+    "Viewport",
+    "ViewportBreakdown",
+    "CustomField",
+    "CustomFieldValue",
+    "CustomFieldCreate",
+    "CustomFieldUpdate",
+    "CustomFieldResponse",
+    "CustomFieldValues",
+    "SavedFilter",
+    "FilterCondition",
+    "SavedFilterCreate",
+    "SavedFilterUpdate",
+    "SavedFilterResponse",
+    "DurationPoint",
+    "SlowStep",
+    "StatCounter",
+    "Draft",
+    "DraftSave",
+    "DraftResponse",
+    "GeneratedTestCode",
+    "GeneratedTest",
+    "GenerateTestsResponse",
+    "SaveGeneratedTests",
+    "GeneratedTestCodeResponse",
+    "FileTestMapping",
+    "FileTestMappingCreate",
+    "FileTestMappingResponse",
+    "WebhookRun",
+    "WebhookRunResponse",
+    "SynonymGroup",
+    "SynonymGroupCreate",
+    "SynonymGroupUpdate",
+    "SynonymGroupResponse",
 ]
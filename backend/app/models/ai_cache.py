@@ -0,0 +1,19 @@
+from datetime import datetime
+
+from sqlalchemy import String, DateTime, Integer, Text
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class AiCacheEntry(Base):
+    """Cached AI result keyed by a hash of the request inputs"""
+
+    __tablename__ = "ai_cache"
+
+    key: Mapped[str] = mapped_column(String, primary_key=True)
+    command: Mapped[str] = mapped_column(String, nullable=False)
+    result: Mapped[str] = mapped_column(Text, nullable=False)
+    hit_count: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    expires_at: Mapped[datetime] = mapped_column(DateTime, nullable=False, index=True)
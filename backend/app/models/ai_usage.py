@@ -0,0 +1,50 @@
+import uuid
+from datetime import datetime
+from typing import List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Integer, Boolean
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class AiUsage(Base):
+    """AI request usage database model"""
+
+    __tablename__ = "ai_usage"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    command: Mapped[str] = mapped_column(String, nullable=False)
+    provider: Mapped[str] = mapped_column(String, nullable=False)
+    model: Mapped[str] = mapped_column(String, nullable=False)
+    input_tokens: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
+    output_tokens: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
+    latency_ms: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
+    success: Mapped[bool] = mapped_column(Boolean, nullable=False, default=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, index=True)
+
+
+class ModelUsage(BaseModel):
+    """Usage totals for a single model"""
+
+    model: str
+    requests: int
+    input_tokens: int
+    output_tokens: int
+    estimated_cost_usd: Optional[float]
+
+
+class AiUsageSummary(BaseModel):
+    """Usage totals for a period"""
+
+    period: str
+    since: Optional[datetime]
+    total_requests: int
+    failed_requests: int
+    input_tokens: int
+    output_tokens: int
+    estimated_cost_usd: float
+    by_model: List[ModelUsage]
+    monthly_cap_usd: Optional[float]
+    month_to_date_cost_usd: float
@@ -0,0 +1,51 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Integer, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class Attachment(Base):
+    """Run evidence file, such as a screenshot or video, kept in attachment storage"""
+
+    __tablename__ = "attachments"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    test_run_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    step_result_id: Mapped[Optional[str]] = mapped_column(
+        String, ForeignKey("step_results.id", ondelete="CASCADE"), nullable=True
+    )
+    # 'screenshot' | 'video' | 'trace' | 'log' | 'crash' | 'other'
+    kind: Mapped[str] = mapped_column(String, nullable=False)
+    filename: Mapped[str] = mapped_column(String, nullable=False)
+    content_type: Mapped[str] = mapped_column(String, nullable=False)
+    size_bytes: Mapped[int] = mapped_column(Integer, nullable=False)
+    sha256: Mapped[str] = mapped_column(String, nullable=False)
+    storage: Mapped[str] = mapped_column(String, nullable=False)  # 'local' | 's3', where it was stored
+    storage_key: Mapped[str] = mapped_column(String, nullable=False)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class AttachmentResponse(BaseModel):
+    """Schema for attachment response"""
+
+    id: str
+    test_run_id: str
+    step_result_id: Optional[str]
+    kind: str
+    filename: str
+    content_type: str
+    size_bytes: int
+    sha256: str
+    storage: str
+    created_at: datetime
+    download_url: Optional[str] = None  # Signed, expires after attachments.link_ttl_seconds
+
+    class Config:
+        from_attributes = True
@@ -0,0 +1,55 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+from .test_run import RunComparison
+
+
+class ScenarioBaseline(Base):
+    """A test run approved as the golden result for a scenario; superseded rows are its history"""
+
+    __tablename__ = "scenario_baselines"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    scenario_id: Mapped[str] = mapped_column(
+        String, ForeignKey("scenarios.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    test_run_id: Mapped[str] = mapped_column(String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=False)
+    note: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    # Set when a newer run is approved or the baseline is cleared
+    superseded_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class ScenarioBaselineCreate(BaseModel):
+    """Schema for approving a test run as a scenario's baseline"""
+
+    test_run_id: str
+    note: Optional[str] = None
+
+
+class ScenarioBaselineResponse(BaseModel):
+    """Schema for scenario baseline response"""
+
+    id: str
+    scenario_id: str
+    test_run_id: str
+    note: Optional[str]
+    superseded_at: Optional[datetime]
+    created_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class BaselineComparison(BaseModel):
+    """A run's results for one scenario compared against that scenario's baseline"""
+
+    scenario_id: str
+    baseline: ScenarioBaselineResponse
+    comparison: RunComparison
@@ -0,0 +1,92 @@
+import json
+import uuid
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, Boolean, ForeignKey, Text, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class CustomField(Base):
+    """Extra metadata a project tracks on its test cases or on itself, like component, sprint or risk level"""
+
+    __tablename__ = "custom_fields"
+    __table_args__ = (UniqueConstraint("project_id", "entity", "name"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
+    entity: Mapped[str] = mapped_column(String, nullable=False, default="test_case")  # 'test_case' | 'project'
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    # 'text' | 'number' | 'boolean' | 'date' | 'select' | 'multi_select'
+    field_type: Mapped[str] = mapped_column(String, nullable=False, default="text")
+    allowed_values: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")  # JSON list
+    required: Mapped[bool] = mapped_column(Boolean, nullable=False, default=False, server_default="0")
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class CustomFieldValue(Base):
+    """A custom field's value on one test case or project, stored as JSON"""
+
+    __tablename__ = "custom_field_values"
+    __table_args__ = (UniqueConstraint("field_id", "entity_id"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    field_id: Mapped[str] = mapped_column(String, ForeignKey("custom_fields.id", ondelete="CASCADE"), nullable=False)
+    entity_id: Mapped[str] = mapped_column(String, nullable=False, index=True)  # Test case or project ID
+    value: Mapped[str] = mapped_column(Text, nullable=False)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class CustomFieldCreate(BaseModel):
+    """Schema for creating a custom field"""
+
+    project_id: str
+    entity: str = "test_case"
+    name: str
+    field_type: str = "text"
+    allowed_values: List[str] = []
+    required: bool = False
+
+
+class CustomFieldUpdate(BaseModel):
+    """Schema for updating a custom field; its entity and type can't change once values exist"""
+
+    name: Optional[str] = None
+    allowed_values: Optional[List[str]] = None
+    required: Optional[bool] = None
+
+
+class CustomFieldResponse(BaseModel):
+    """Schema for custom field response"""
+
+    id: str
+    project_id: str
+    entity: str
+    name: str
+    field_type: str
+    allowed_values: List[str] = []
+    required: bool
+    created_at: datetime
+    updated_at: datetime
+
+    @field_validator("allowed_values", mode="before")
+    @classmethod
+    def parse_allowed_values(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "[]")
+        return v
+
+    class Config:
+        from_attributes = True
+
+
+class CustomFieldValues(BaseModel):
+    """A test case's or project's custom field values by field name"""
+
+    entity_id: str
+    values: Dict[str, Any] = {}
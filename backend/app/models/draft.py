@@ -0,0 +1,57 @@
+import json
+import uuid
+from datetime import datetime
+from typing import Any, Dict, Optional
+
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, Text, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class Draft(Base):
+    """Unsaved edits to a scenario, step or test case, autosaved so a crash doesn't lose them"""
+
+    __tablename__ = "drafts"
+    __table_args__ = (UniqueConstraint("entity", "entity_id"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    entity: Mapped[str] = mapped_column(String, nullable=False)  # 'scenario' | 'step' | 'test_case'
+    entity_id: Mapped[str] = mapped_column(String, nullable=False)
+    content: Mapped[str] = mapped_column(Text, nullable=False, default="{}", server_default="{}")  # JSON object
+    # The saved entity's updated_at when editing began, to notice edits saved elsewhere since
+    base_updated_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class DraftSave(BaseModel):
+    """Schema for autosaving a draft"""
+
+    content: Dict[str, Any]
+    base_updated_at: Optional[datetime] = None
+
+
+class DraftResponse(BaseModel):
+    """Schema for draft response"""
+
+    id: str
+    entity: str
+    entity_id: str
+    content: Dict[str, Any] = {}
+    base_updated_at: Optional[datetime]
+    created_at: datetime
+    updated_at: datetime
+    conflict: bool = False  # The entity was saved after the draft's base version
+    current_updated_at: Optional[datetime] = None
+
+    @field_validator("content", mode="before")
+    @classmethod
+    def parse_content(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "{}")
+        return v
+
+    class Config:
+        from_attributes = True
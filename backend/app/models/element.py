@@ -0,0 +1,103 @@
+import json
+import uuid
+from datetime import datetime
+from typing import List, Optional
+
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, ForeignKey, Integer, Text, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class Element(Base):
+    """Reusable UI element with per-platform locators, referenced from steps by name"""
+
+    __tablename__ = "elements"
+    __table_args__ = (UniqueConstraint("project_id", "name"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    description: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    css_selector: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    xpath: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    # JSON lists of the iframes to enter and shadow hosts to pierce before matching css_selector
+    frame_path: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    shadow_path: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    resource_id: Mapped[Optional[str]] = mapped_column(String, nullable=True)  # Android
+    accessibility_id: Mapped[Optional[str]] = mapped_column(String, nullable=True)  # iOS
+    x: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)  # Coordinate fallback
+    y: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class ElementCreate(BaseModel):
+    """Schema for creating an element"""
+
+    project_id: str
+    name: str
+    description: Optional[str] = None
+    css_selector: Optional[str] = None
+    xpath: Optional[str] = None
+    frame_path: List[str] = []
+    shadow_path: List[str] = []
+    resource_id: Optional[str] = None
+    accessibility_id: Optional[str] = None
+    x: Optional[int] = None
+    y: Optional[int] = None
+
+
+class ElementUpdate(BaseModel):
+    """Schema for updating an element"""
+
+    name: Optional[str] = None
+    description: Optional[str] = None
+    css_selector: Optional[str] = None
+    xpath: Optional[str] = None
+    frame_path: Optional[List[str]] = None
+    shadow_path: Optional[List[str]] = None
+    resource_id: Optional[str] = None
+    accessibility_id: Optional[str] = None
+    x: Optional[int] = None
+    y: Optional[int] = None
+
+
+class ElementResponse(BaseModel):
+    """Schema for element response"""
+
+    id: str
+    project_id: str
+    name: str
+    description: Optional[str]
+    css_selector: Optional[str]
+    xpath: Optional[str]
+    frame_path: List[str] = []
+    shadow_path: List[str] = []
+    resource_id: Optional[str]
+    accessibility_id: Optional[str]
+    x: Optional[int]
+    y: Optional[int]
+    created_at: datetime
+    updated_at: datetime
+
+    @field_validator("frame_path", "shadow_path", mode="before")
+    @classmethod
+    def parse_path(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "[]")
+        return v
+
+    class Config:
+        from_attributes = True
+
+
+class ElementUsage(BaseModel):
+    """A step that references an element"""
+
+    step_id: str
+    step_label: str
+    scenario_id: str
+    scenario_name: str
+    test_case_id: str
@@ -0,0 +1,50 @@
+import uuid
+from datetime import datetime
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, ForeignKey, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class FileTestMapping(Base):
+    """Source files whose changes a test case covers, for picking the tests to run for a diff"""
+
+    __tablename__ = "file_test_mappings"
+    __table_args__ = (UniqueConstraint("test_case_id", "path_pattern"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(
+        String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    test_case_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_cases.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    # A repository path, a directory such as src/auth/, or a glob such as src/**/*.tsx
+    path_pattern: Mapped[str] = mapped_column(String, nullable=False)
+    source: Mapped[str] = mapped_column(String, nullable=False, default="manual")  # 'manual' | 'ai'
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class FileTestMappingCreate(BaseModel):
+    """Schema for mapping source files to a test case"""
+
+    test_case_id: str
+    path_pattern: str
+
+
+class FileTestMappingResponse(BaseModel):
+    """Schema for file to test mapping response"""
+
+    id: str
+    project_id: str
+    test_case_id: str
+    path_pattern: str
+    source: str
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
@@ -0,0 +1,73 @@
+import uuid
+from datetime import datetime
+from typing import List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Text, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class GeneratedTestCode(Base):
+    """Test code the AI generated for a test case, with the source it was generated from"""
+
+    __tablename__ = "generated_test_code"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    test_case_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_cases.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    code: Mapped[str] = mapped_column(Text, nullable=False)
+    test_type: Mapped[Optional[str]] = mapped_column(String, nullable=True)  # unit | integration | e2e ...
+    language: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    framework: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    # What the tests were generated from: the code snippet and a commit, file or URL it came from
+    source_code: Mapped[Optional[str]] = mapped_column(Text, nullable=True)
+    source_ref: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class GeneratedTest(BaseModel):
+    """A test returned by the AI agent's test generation"""
+
+    name: str
+    description: str
+    code: str
+    test_type: str
+
+
+class GenerateTestsResponse(BaseModel):
+    """Schema for the AI agent's test generation response"""
+
+    tests: List[GeneratedTest]
+    coverage_estimate: str
+
+
+class SaveGeneratedTests(BaseModel):
+    """Schema for saving generated tests as test cases of a project"""
+
+    response: GenerateTestsResponse
+    language: Optional[str] = None
+    framework: Optional[str] = None
+    source_code: Optional[str] = None
+    source_ref: Optional[str] = None  # e.g. a commit SHA or path@commit
+
+
+class GeneratedTestCodeResponse(BaseModel):
+    """Schema for generated test code response"""
+
+    id: str
+    test_case_id: str
+    code: str
+    test_type: Optional[str]
+    language: Optional[str]
+    framework: Optional[str]
+    source_code: Optional[str]
+    source_ref: Optional[str]
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
@@ -0,0 +1,65 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Boolean, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class KnownIssue(Base):
+    """Tracked issue marking a test case's failures as expected while it is active"""
+
+    __tablename__ = "known_issues"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
+    test_case_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_cases.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    tracker: Mapped[str] = mapped_column(String, nullable=False)  # 'jira' | 'github'
+    issue_key: Mapped[str] = mapped_column(String, nullable=False)  # PROJ-123 or owner/repo#123
+    issue_url: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    reason: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    active: Mapped[bool] = mapped_column(Boolean, nullable=False, default=True, server_default="1")
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class KnownIssueCreate(BaseModel):
+    """Schema for creating a known issue"""
+
+    test_case_id: str
+    tracker: str
+    issue_key: str
+    issue_url: Optional[str] = None
+    reason: Optional[str] = None
+
+
+class KnownIssueUpdate(BaseModel):
+    """Schema for updating a known issue"""
+
+    issue_key: Optional[str] = None
+    issue_url: Optional[str] = None
+    reason: Optional[str] = None
+    active: Optional[bool] = None
+
+
+class KnownIssueResponse(BaseModel):
+    """Schema for known issue response"""
+
+    id: str
+    project_id: str
+    test_case_id: str
+    tracker: str
+    issue_key: str
+    issue_url: Optional[str]
+    reason: Optional[str]
+    active: bool
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
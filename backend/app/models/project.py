@@ -1,14 +1,29 @@
+import json
 import uuid
 from datetime import datetime
-from typing import Optional
+from typing import Dict, List, Optional
 
-from pydantic import BaseModel, Field
-from sqlalchemy import String, DateTime
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, Boolean, Text
 from sqlalchemy.orm import Mapped, mapped_column
 
 from app.db.database import Base
 
 
+class Viewport(BaseModel):
+    """A screen size web scenarios can be run at"""
+
+    name: str
+    width: int
+    height: int
+    device_scale_factor: float = 1.0
+    is_mobile: bool = False
+    has_touch: bool = False
+    user_agent: Optional[str] = None
+    # Playwright device profile, e.g. 'iPhone 13'; width and height still apply
+    device: Optional[str] = None
+
+
 class Project(Base):
     """Project database model"""
 
@@ -20,6 +35,19 @@ class Project(Base):
     app_url: Mapped[str] = mapped_column(String, nullable=False)
     repo_url: Mapped[Optional[str]] = mapped_column(String, nullable=True)
     project_type: Mapped[str] = mapped_column(String, nullable=False, default="web")
+    # Resolve elements from UI dumps only, never calling AI services
+    ai_free: Mapped[bool] = mapped_column(Boolean, nullable=False, default=False, server_default="0")
+    # JSON object of environment variables passed to shell steps
+    env_vars: Mapped[str] = mapped_column(Text, nullable=False, default="{}", server_default="{}")
+    # JSON list of env_vars names whose values are masked in logs, results and AI requests
+    secret_env_vars: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    # Browser web runs use unless a run picks one: 'chromium' | 'chrome' | 'edge' | 'firefox' | 'safari'
+    default_browser: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    browser_headless: Mapped[bool] = mapped_column(Boolean, nullable=False, default=True, server_default="1")
+    # JSON list of Viewports that web runs can be repeated across
+    viewports: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    # Set once the default synonym groups have been added to the project's dictionary
+    synonyms_seeded: Mapped[bool] = mapped_column(Boolean, nullable=False, default=False, server_default="0")
     created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
     updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
 
@@ -32,6 +60,12 @@ class ProjectCreate(BaseModel):
     app_url: str
     repo_url: Optional[str] = None
     project_type: str = "web"
+    ai_free: bool = False
+    env_vars: Dict[str, str] = {}
+    secret_env_vars: List[str] = []
+    default_browser: Optional[str] = None
+    browser_headless: bool = True
+    viewports: List[Viewport] = []
 
 
 class ProjectUpdate(BaseModel):
@@ -42,6 +76,12 @@ class ProjectUpdate(BaseModel):
     app_url: Optional[str] = None
     repo_url: Optional[str] = None
     project_type: Optional[str] = None
+    ai_free: Optional[bool] = None
+    env_vars: Optional[Dict[str, str]] = None
+    secret_env_vars: Optional[List[str]] = None
+    default_browser: Optional[str] = None
+    browser_headless: Optional[bool] = None
+    viewports: Optional[List[Viewport]] = None
 
 
 class ProjectResponse(BaseModel):
@@ -53,8 +93,28 @@ class ProjectResponse(BaseModel):
     app_url: str
     repo_url: Optional[str]
     project_type: str
+    ai_free: bool = False
+    env_vars: Dict[str, str] = {}
+    secret_env_vars: List[str] = []
+    default_browser: Optional[str] = None
+    browser_headless: bool = True
+    viewports: List[Viewport] = []
     created_at: datetime
     updated_at: datetime
 
+    @field_validator("env_vars", mode="before")
+    @classmethod
+    def parse_env_vars(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "{}")
+        return v
+
+    @field_validator("secret_env_vars", "viewports", mode="before")
+    @classmethod
+    def parse_secret_env_vars(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "[]")
+        return v
+
     class Config:
         from_attributes = True
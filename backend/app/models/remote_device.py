@@ -0,0 +1,60 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Integer
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class RemoteDevice(Base):
+    """Android device reached over Wi-Fi ADB, remembered so it can be reconnected"""
+
+    __tablename__ = "remote_devices"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    host: Mapped[str] = mapped_column(String, nullable=False)
+    port: Mapped[int] = mapped_column(Integer, nullable=False, default=5555)
+    last_connected_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+    @property
+    def address(self) -> str:
+        return f"{self.host}:{self.port}"
+
+
+class RemoteDeviceCreate(BaseModel):
+    """Schema for saving a remote device endpoint"""
+
+    name: Optional[str] = None
+    host: str
+    port: int = 5555
+
+
+class RemoteDeviceUpdate(BaseModel):
+    """Schema for updating a remote device endpoint"""
+
+    name: Optional[str] = None
+    host: Optional[str] = None
+    port: Optional[int] = None
+
+
+class RemoteDeviceResponse(BaseModel):
+    """Schema for remote device response"""
+
+    id: str
+    name: str
+    host: str
+    port: int
+    address: str
+    connected: bool = False
+    last_connected_at: Optional[datetime]
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
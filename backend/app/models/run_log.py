@@ -0,0 +1,58 @@
+import uuid
+from datetime import datetime
+from typing import List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Integer, Text, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class RunLog(Base):
+    """Structured console line written while a test run executes"""
+
+    __tablename__ = "run_logs"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    test_run_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    level: Mapped[str] = mapped_column(String, nullable=False, default="info")
+    source: Mapped[str] = mapped_column(String, nullable=False, default="executor")
+    step_index: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    message: Mapped[str] = mapped_column(Text, nullable=False)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class RunLogCreate(BaseModel):
+    """Schema for writing a run log line"""
+
+    level: str = "info"  # 'debug' | 'info' | 'warning' | 'error'
+    source: str = "executor"
+    step_index: Optional[int] = None
+    message: str
+
+
+class RunLogResponse(BaseModel):
+    """Schema for run log response"""
+
+    id: str
+    test_run_id: str
+    level: str
+    source: str
+    step_index: Optional[int]
+    message: str
+    created_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class RunLogPage(BaseModel):
+    """One page of a run's log lines, oldest first"""
+
+    items: List[RunLogResponse]
+    total: int
+    offset: int
+    limit: int
@@ -0,0 +1,38 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class RunTransition(Base):
+    """A test run's change from one lifecycle state to another"""
+
+    __tablename__ = "run_transitions"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    test_run_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    from_status: Mapped[str] = mapped_column(String, nullable=False)
+    to_status: Mapped[str] = mapped_column(String, nullable=False)
+    reason: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class RunTransitionResponse(BaseModel):
+    """Schema for run transition response"""
+
+    id: str
+    test_run_id: str
+    from_status: str
+    to_status: str
+    reason: Optional[str]
+    created_at: datetime
+
+    class Config:
+        from_attributes = True
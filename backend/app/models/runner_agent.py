@@ -0,0 +1,62 @@
+import uuid
+import json
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, Text
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class RunnerAgent(Base):
+    """Another machine running this app in agent mode, which takes execution jobs from this one"""
+
+    __tablename__ = "runner_agents"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    hostname: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    version: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    # Devices and browsers the agent last advertised, as JSON lists
+    devices: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    browsers: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    last_seen_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class AgentRegistration(BaseModel):
+    """Schema an agent sends when it registers or heartbeats"""
+
+    name: str
+    hostname: Optional[str] = None
+    version: Optional[str] = None
+    devices: List[Dict[str, Any]] = []  # DeviceInfo fields: id, name, status, platform, device_kind
+    browsers: List[str] = []
+    job_ids: List[str] = []  # Jobs the agent is running, which renews their leases
+
+
+class RunnerAgentResponse(BaseModel):
+    """Schema for runner agent response"""
+
+    id: str
+    name: str
+    hostname: Optional[str]
+    version: Optional[str]
+    devices: List[Dict[str, Any]] = []
+    browsers: List[str] = []
+    online: bool = False
+    active_jobs: int = 0
+    last_seen_at: Optional[datetime]
+    created_at: datetime
+
+    @field_validator("devices", "browsers", mode="before")
+    @classmethod
+    def parse_json_list(cls, v):
+        if isinstance(v, str):
+            return json.loads(v)
+        return v
+
+    class Config:
+        from_attributes = True
@@ -0,0 +1,76 @@
+import json
+import uuid
+from datetime import datetime
+from typing import Any, List, Optional
+
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, ForeignKey, Text, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class SavedFilter(Base):
+    """A named test case filter shared across a project, like failed critical checkout tests"""
+
+    __tablename__ = "saved_filters"
+    __table_args__ = (UniqueConstraint("project_id", "name"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    description: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    conditions: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")  # JSON list
+    match: Mapped[str] = mapped_column(String, nullable=False, default="all", server_default="all")  # 'all' | 'any'
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class FilterCondition(BaseModel):
+    """One test case condition; custom fields are named custom:<field name>"""
+
+    field: str  # status, priority, category, test_type, name, description or custom:<name>
+    op: str = "eq"  # 'eq' | 'ne' | 'in' | 'contains'
+    value: Any = None
+
+
+class SavedFilterCreate(BaseModel):
+    """Schema for saving a filter"""
+
+    project_id: str
+    name: str
+    description: Optional[str] = None
+    conditions: List[FilterCondition] = []
+    match: str = "all"
+
+
+class SavedFilterUpdate(BaseModel):
+    """Schema for updating a saved filter"""
+
+    name: Optional[str] = None
+    description: Optional[str] = None
+    conditions: Optional[List[FilterCondition]] = None
+    match: Optional[str] = None
+
+
+class SavedFilterResponse(BaseModel):
+    """Schema for saved filter response"""
+
+    id: str
+    project_id: str
+    name: str
+    description: Optional[str]
+    conditions: List[FilterCondition] = []
+    match: str
+    created_at: datetime
+    updated_at: datetime
+
+    @field_validator("conditions", mode="before")
+    @classmethod
+    def parse_conditions(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "[]")
+        return v
+
+    class Config:
+        from_attributes = True
@@ -3,7 +3,7 @@ from datetime import datetime
 from typing import Optional, List, TYPE_CHECKING
 
 from pydantic import BaseModel
-from sqlalchemy import String, DateTime, ForeignKey
+from sqlalchemy import String, DateTime, Float, ForeignKey, Integer, Index
 from sqlalchemy.orm import Mapped, mapped_column
 
 from app.db.database import Base
@@ -16,12 +16,19 @@ class Scenario(Base):
     """Scenario database model"""
 
     __tablename__ = "scenarios"
+    __table_args__ = (Index("ix_scenarios_test_case_position", "test_case_id", "position"),)
 
     id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
     test_case_id: Mapped[str] = mapped_column(String, ForeignKey("test_cases.id", ondelete="CASCADE"), nullable=False)
     name: Mapped[str] = mapped_column(String, nullable=False)
     description: Mapped[Optional[str]] = mapped_column(String, nullable=True)
     target_url: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    position: Mapped[float] = mapped_column(Float, nullable=False, default=0.0, server_default="0")
+    # Longest a run of the scenario should take, checked against its steps' total duration
+    max_duration_ms: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    budget_action: Mapped[Optional[str]] = mapped_column(String, nullable=True)  # Empty uses executor.budget_action
+    # 'always' | 'on_failure' | 'never'; empty uses executor.screenshot_policy
+    screenshot_policy: Mapped[Optional[str]] = mapped_column(String, nullable=True)
     created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
     updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
 
@@ -33,6 +40,9 @@ class ScenarioCreate(BaseModel):
     name: str
     description: Optional[str] = None
     target_url: Optional[str] = None
+    max_duration_ms: Optional[int] = None
+    budget_action: Optional[str] = None
+    screenshot_policy: Optional[str] = None
 
 
 class ScenarioUpdate(BaseModel):
@@ -41,6 +51,10 @@ class ScenarioUpdate(BaseModel):
     name: Optional[str] = None
     description: Optional[str] = None
     target_url: Optional[str] = None
+    max_duration_ms: Optional[int] = None
+    budget_action: Optional[str] = None
+    screenshot_policy: Optional[str] = None
+    expected_updated_at: Optional[datetime] = None
 
 
 class ScenarioResponse(BaseModel):
@@ -51,6 +65,10 @@ class ScenarioResponse(BaseModel):
     name: str
     description: Optional[str]
     target_url: Optional[str]
+    position: float = 0.0
+    max_duration_ms: Optional[int] = None
+    budget_action: Optional[str] = None
+    screenshot_policy: Optional[str] = None
     created_at: datetime
     updated_at: datetime
 
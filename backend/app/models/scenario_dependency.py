@@ -0,0 +1,38 @@
+import uuid
+from datetime import datetime
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, ForeignKey, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class ScenarioDependency(Base):
+    """A scenario that must pass before another scenario can run"""
+
+    __tablename__ = "scenario_dependencies"
+    __table_args__ = (UniqueConstraint("scenario_id", "depends_on_id"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    scenario_id: Mapped[str] = mapped_column(String, ForeignKey("scenarios.id", ondelete="CASCADE"), nullable=False, index=True)
+    depends_on_id: Mapped[str] = mapped_column(String, ForeignKey("scenarios.id", ondelete="CASCADE"), nullable=False)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class ScenarioDependencyCreate(BaseModel):
+    """Schema for adding a scenario dependency"""
+
+    depends_on_id: str
+
+
+class ScenarioDependencyResponse(BaseModel):
+    """Schema for scenario dependency response"""
+
+    id: str
+    scenario_id: str
+    depends_on_id: str
+    created_at: datetime
+
+    class Config:
+        from_attributes = True
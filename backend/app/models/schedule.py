@@ -0,0 +1,121 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Boolean, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class RunSchedule(Base):
+    """Cron schedule that runs a suite locally or on a runner agent"""
+
+    __tablename__ = "run_schedules"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(
+        String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    suite_id: Mapped[str] = mapped_column(String, ForeignKey("suites.id", ondelete="CASCADE"), nullable=False)
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    cron: Mapped[str] = mapped_column(String, nullable=False)
+    timezone: Mapped[str] = mapped_column(String, nullable=False, default="UTC", server_default="UTC")
+    enabled: Mapped[bool] = mapped_column(Boolean, nullable=False, default=True, server_default="1")
+    target: Mapped[str] = mapped_column(String, nullable=False, default="device")  # 'device' | 'browser'
+    platform: Mapped[Optional[str]] = mapped_column(String, nullable=True)  # 'android' | 'ios'
+    device_id: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    framework: Mapped[str] = mapped_column(String, nullable=False, default="playwright")
+    browser: Mapped[str] = mapped_column(String, nullable=False, default="chromium")
+    headless: Mapped[bool] = mapped_column(Boolean, nullable=False, default=True)
+    self_heal: Mapped[bool] = mapped_column(Boolean, nullable=False, default=True)
+    priority: Mapped[str] = mapped_column(String, nullable=False, default="normal")
+    # Dispatch to a runner agent instead of this machine; agent_id picks one, empty picks any
+    on_agent: Mapped[bool] = mapped_column(Boolean, nullable=False, default=False, server_default="0")
+    agent_id: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    next_run_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)  # UTC
+    last_run_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
+    last_test_run_id: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    last_error: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class RunScheduleCreate(BaseModel):
+    """Schema for creating a schedule"""
+
+    suite_id: str
+    name: str
+    cron: str
+    timezone: str = "UTC"
+    enabled: bool = True
+    target: str = "device"
+    platform: Optional[str] = None
+    device_id: Optional[str] = None
+    framework: str = "playwright"
+    browser: str = "chromium"
+    headless: bool = True
+    self_heal: bool = True
+    priority: str = "normal"
+    on_agent: bool = False
+    agent_id: Optional[str] = None
+
+
+class RunScheduleUpdate(BaseModel):
+    """Schema for updating a schedule"""
+
+    name: Optional[str] = None
+    cron: Optional[str] = None
+    timezone: Optional[str] = None
+    enabled: Optional[bool] = None
+    target: Optional[str] = None
+    platform: Optional[str] = None
+    device_id: Optional[str] = None
+    framework: Optional[str] = None
+    browser: Optional[str] = None
+    headless: Optional[bool] = None
+    self_heal: Optional[bool] = None
+    priority: Optional[str] = None
+    on_agent: Optional[bool] = None
+    agent_id: Optional[str] = None
+
+
+class RunScheduleResponse(BaseModel):
+    """Schema for schedule response"""
+
+    id: str
+    project_id: str
+    suite_id: str
+    name: str
+    cron: str
+    timezone: str
+    enabled: bool
+    target: str
+    platform: Optional[str]
+    device_id: Optional[str]
+    framework: str
+    browser: str
+    headless: bool
+    self_heal: bool
+    priority: str
+    on_agent: bool
+    agent_id: Optional[str]
+    next_run_at: Optional[datetime]
+    last_run_at: Optional[datetime]
+    last_test_run_id: Optional[str]
+    last_error: Optional[str]
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class UpcomingRun(BaseModel):
+    """A future run of a schedule"""
+
+    schedule_id: str
+    name: str
+    suite_id: str
+    run_at: datetime  # UTC
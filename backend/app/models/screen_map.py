@@ -0,0 +1,148 @@
+import uuid
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, ForeignKey, Integer, Text, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class AppScreen(Base):
+    """A distinct app screen found by crawling, keyed by its layout fingerprint"""
+
+    __tablename__ = "app_screens"
+    __table_args__ = (UniqueConstraint("project_id", "fingerprint"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(
+        String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    fingerprint: Mapped[str] = mapped_column(String, nullable=False)
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    activity: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    depth: Mapped[int] = mapped_column(Integer, nullable=False, default=0)  # Taps from the crawl's first screen
+    screenshot_path: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    ui_dump: Mapped[Optional[str]] = mapped_column(Text, nullable=True)
+    # JSON list of the screen's clickable elements, each a descriptor with its index and bounds
+    elements: Mapped[str] = mapped_column(Text, nullable=False, default="[]")
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class ScreenTransition(Base):
+    """Tapping an element on one screen leads to another"""
+
+    __tablename__ = "screen_transitions"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(
+        String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    from_screen_id: Mapped[str] = mapped_column(
+        String, ForeignKey("app_screens.id", ondelete="CASCADE"), nullable=False
+    )
+    to_screen_id: Mapped[str] = mapped_column(
+        String, ForeignKey("app_screens.id", ondelete="CASCADE"), nullable=False
+    )
+    element: Mapped[str] = mapped_column(Text, nullable=False)  # JSON descriptor and index of the tapped element
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class ScreenElement(BaseModel):
+    """A clickable element on a crawled screen"""
+
+    resource_id: Optional[str] = None
+    text: Optional[str] = None
+    content_desc: Optional[str] = None
+    class_name: Optional[str] = None
+    index: int = 0  # Among the screen's elements matching the same descriptor
+    bounds: List[int] = []  # left, top, right, bottom in device pixels
+
+
+class AppScreenResponse(BaseModel):
+    """Schema for a crawled screen, without its UI dump"""
+
+    id: str
+    project_id: str
+    fingerprint: str
+    name: str
+    activity: Optional[str]
+    depth: int
+    has_screenshot: bool
+    elements: List[ScreenElement]
+    created_at: datetime
+    updated_at: datetime
+
+
+class ScreenTransitionResponse(BaseModel):
+    """Schema for screen transition response"""
+
+    id: str
+    from_screen_id: str
+    to_screen_id: str
+    element: Dict[str, Any]
+    created_at: datetime
+
+
+class ScreenMap(BaseModel):
+    """A project's screen-flow graph"""
+
+    project_id: str
+    screens: List[AppScreenResponse]
+    transitions: List[ScreenTransitionResponse]
+
+
+class CrawlRequest(BaseModel):
+    """Schema for starting a crawl from the device's current screen"""
+
+    device_id: str
+    package_name: Optional[str] = None  # Relaunched to get back to a screen; empty uses the first screen's app
+    launch: bool = False  # Launch package_name before crawling instead of starting where the device is
+    max_depth: int = 3
+    max_steps: int = 50  # Taps before the crawl stops
+    max_screens: int = 30
+
+
+class CrawlStatus(BaseModel):
+    """State of a project's crawl"""
+
+    project_id: str
+    device_id: str
+    state: str  # 'running' | 'completed' | 'stopped' | 'failed'
+    steps: int
+    screens: int  # Screens seen in this crawl
+    new_screens: int  # Screens the project's map didn't have yet
+    transitions: int
+    error: Optional[str] = None
+    started_at: datetime
+    finished_at: Optional[datetime] = None
+
+
+class ScreenCoverage(BaseModel):
+    """How much of one crawled screen the project's scenarios exercise"""
+
+    screen_id: str
+    name: str
+    # A step finds an element on it, or a tested element leads to it
+    covered: bool
+    elements_total: int
+    elements_tested: int
+    coverage_pct: float
+    untested_elements: List[ScreenElement]
+    scenario_ids: List[str]  # Scenarios with a step acting on the screen
+
+
+class AppCoverage(BaseModel):
+    """Coverage of a project's screen-flow map by its scenarios"""
+
+    project_id: str
+    screens_total: int
+    screens_covered: int
+    elements_total: int
+    elements_tested: int
+    coverage_pct: float  # Tested elements out of every interactive element on the map
+    # Steps that only tap coordinates, which can't be tied to an element
+    unlocated_steps: int
+    screens: List[ScreenCoverage]
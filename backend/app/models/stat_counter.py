@@ -0,0 +1,16 @@
+from sqlalchemy import String, Integer
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class StatCounter(Base):
+    """Running count, and total where one is kept, of a project's rows with one value of a metric"""
+
+    __tablename__ = "stat_counters"
+
+    project_id: Mapped[str] = mapped_column(String, primary_key=True)
+    metric: Mapped[str] = mapped_column(String, primary_key=True)  # e.g. test_case_status
+    key: Mapped[str] = mapped_column(String, primary_key=True)  # The counted value, e.g. failed
+    count: Mapped[int] = mapped_column(Integer, nullable=False, default=0, server_default="0")
+    total: Mapped[int] = mapped_column(Integer, nullable=False, default=0, server_default="0")
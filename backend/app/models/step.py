@@ -1,10 +1,10 @@
 import uuid
 import json
 from datetime import datetime
-from typing import Optional, Dict, Any
+from typing import Optional, Dict, Any, List
 
 from pydantic import BaseModel, field_validator
-from sqlalchemy import String, DateTime, Integer, ForeignKey, Text
+from sqlalchemy import Boolean, String, DateTime, Float, ForeignKey, Text, Index
 from sqlalchemy.orm import Mapped, mapped_column
 
 from app.db.database import Base
@@ -14,17 +14,39 @@ class Step(Base):
     """Step database model"""
 
     __tablename__ = "steps"
+    __table_args__ = (Index("ix_steps_scenario_order", "scenario_id", "step_order"),)
 
     id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
     scenario_id: Mapped[str] = mapped_column(String, ForeignKey("scenarios.id", ondelete="CASCADE"), nullable=False)
-    step_order: Mapped[int] = mapped_column(Integer, nullable=False)
+    # Fractional so a step can be moved between two others by rewriting only itself
+    step_order: Mapped[float] = mapped_column(Float, nullable=False)
     step_type: Mapped[str] = mapped_column(String, nullable=False)
     label: Mapped[str] = mapped_column(String, nullable=False)
     config: Mapped[str] = mapped_column(Text, nullable=False, default="{}")
+    # Debug runs pause before this step
+    breakpoint: Mapped[bool] = mapped_column(Boolean, nullable=False, default=False, server_default="0")
     created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
     updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
 
 
+class Assertion(BaseModel):
+    """A check a step makes after it acts, or the whole of a verify step"""
+
+    # 'element_text' | 'element_attribute' | 'screen_text' | 'variable' | 'api_response'
+    target: str = "element_text"
+    # 'equals' | 'not_equals' | 'contains' | 'regex' | 'greater_than' | 'less_than' | 'exists' | 'not_exists'
+    operator: str = "equals"
+    expected: Optional[str] = None
+    selector: Optional[str] = None  # Element targets
+    element_description: Optional[str] = None  # Element targets without a selector
+    attribute: Optional[str] = None  # element_attribute, e.g. 'checked' or 'href'
+    name: Optional[str] = None  # variable
+    url: Optional[str] = None  # api_response
+    method: str = "GET"  # api_response
+    path: Optional[str] = None  # api_response: 'status', or a dotted JSON path such as 'data.items.0.id'
+    soft: Optional[bool] = None  # Overrides the step's soft setting
+
+
 class StepConfig(BaseModel):
     """Configuration for a step"""
 
@@ -34,6 +56,31 @@ class StepConfig(BaseModel):
     timeout: Optional[int] = None
     expected: Optional[str] = None
     operator: Optional[str] = None
+    # 'absolute' pixels (the default) or 'normalized' 0-1 fractions of the screen
+    coordinate_mode: Optional[str] = None
+    # Screen size the coordinates were recorded on, used to scale them to other devices
+    reference_width: Optional[int] = None
+    reference_height: Optional[int] = None
+    assertions: Optional[List[Assertion]] = None
+    # Record failed assertions without stopping the run
+    soft: Optional[bool] = None
+    # How long a tap or swipe waits for its element to appear, overriding executor.implicit_wait_ms
+    wait_timeout: Optional[int] = None
+    # How Android input steps type: 'auto' | 'input_text' | 'adb_keyboard' | 'clipboard'
+    input_method: Optional[str] = None
+    # Element descriptor a tap_element step matches against the UI dump when it runs
+    resource_id: Optional[str] = None
+    text: Optional[str] = None
+    content_desc: Optional[str] = None
+    class_name: Optional[str] = None
+    # Which match to tap when the descriptor matches several elements
+    index: Optional[int] = None
+    # Web: iframe selectors to enter, outermost first, then shadow hosts to pierce, before matching selector
+    frame_path: Optional[List[str]] = None
+    shadow_path: Optional[List[str]] = None
+    # Longest the step should take; going over warns or fails it per budget_action or executor.budget_action
+    max_duration_ms: Optional[int] = None
+    budget_action: Optional[str] = None  # 'warn' | 'fail'
 
     class Config:
         extra = "allow"
@@ -43,7 +90,7 @@ class StepCreate(BaseModel):
     """Schema for creating a step"""
 
     scenario_id: str
-    step_order: int
+    step_order: float
     step_type: str
     label: str
     config: Optional[StepConfig] = None
@@ -52,10 +99,11 @@ class StepCreate(BaseModel):
 class StepUpdate(BaseModel):
     """Schema for updating a step"""
 
-    step_order: Optional[int] = None
+    step_order: Optional[float] = None
     step_type: Optional[str] = None
     label: Optional[str] = None
     config: Optional[StepConfig] = None
+    expected_updated_at: Optional[datetime] = None
 
 
 class StepResponse(BaseModel):
@@ -63,10 +111,11 @@ class StepResponse(BaseModel):
 
     id: str
     scenario_id: str
-    step_order: int
+    step_order: float
     step_type: str
     label: str
     config: StepConfig
+    breakpoint: bool = False
     created_at: datetime
     updated_at: datetime
 
@@ -1,9 +1,10 @@
 import uuid
+import json
 from datetime import datetime
-from typing import Optional
+from typing import Optional, Dict, Any, List
 
-from pydantic import BaseModel
-from sqlalchemy import String, DateTime, Integer, ForeignKey
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, Integer, ForeignKey, Boolean, Text, Index
 from sqlalchemy.orm import Mapped, mapped_column
 
 from app.db.database import Base
@@ -13,6 +14,10 @@ class StepResult(Base):
     """Step result database model"""
 
     __tablename__ = "step_results"
+    __table_args__ = (
+        Index("ix_step_results_run_created", "test_run_id", "created_at"),
+        Index("ix_step_results_step_created", "step_id", "created_at"),
+    )
 
     id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
     test_run_id: Mapped[str] = mapped_column(String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=False)
@@ -22,6 +27,15 @@ class StepResult(Base):
     duration_ms: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
     error_message: Mapped[Optional[str]] = mapped_column(String, nullable=True)
     screenshot_path: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    # Size of the stored screenshot, and of the PNG it was compressed from
+    screenshot_bytes: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    screenshot_original_bytes: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    healed: Mapped[bool] = mapped_column(Boolean, nullable=False, default=False, server_default="0")
+    healed_locator: Mapped[Optional[str]] = mapped_column(Text, nullable=True)
+    # Structured output such as accessibility violations, as JSON
+    details: Mapped[Optional[str]] = mapped_column(Text, nullable=True)
+    # Name of the project viewport a web run used, for runs repeated across viewports
+    viewport: Mapped[Optional[str]] = mapped_column(String, nullable=True)
     created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
 
 
@@ -35,6 +49,12 @@ class StepResultCreate(BaseModel):
     duration_ms: Optional[int] = None
     error_message: Optional[str] = None
     screenshot_path: Optional[str] = None
+    screenshot_bytes: Optional[int] = None
+    screenshot_original_bytes: Optional[int] = None
+    healed: bool = False
+    healed_locator: Optional[Dict[str, Any]] = None
+    details: Optional[Dict[str, Any]] = None
+    viewport: Optional[str] = None
 
 
 class StepResultResponse(BaseModel):
@@ -48,7 +68,41 @@ class StepResultResponse(BaseModel):
     duration_ms: Optional[int]
     error_message: Optional[str]
     screenshot_path: Optional[str]
+    screenshot_bytes: Optional[int] = None
+    healed: bool = False
+    healed_locator: Optional[Dict[str, Any]] = None
+    details: Optional[Dict[str, Any]] = None
+    viewport: Optional[str] = None
     created_at: datetime
 
+    @field_validator("healed_locator", "details", mode="before")
+    @classmethod
+    def parse_healed_locator(cls, v):
+        if isinstance(v, str):
+            return json.loads(v)
+        return v
+
     class Config:
         from_attributes = True
+
+
+class DurationPoint(BaseModel):
+    day: str  # YYYY-MM-DD
+    avg_ms: int
+    runs: int
+
+
+class SlowStep(BaseModel):
+    """A step's timings across recent runs, with its daily average for trends"""
+
+    step_id: str
+    label: str
+    step_type: str
+    scenario_id: str
+    scenario_name: str
+    avg_ms: int
+    max_ms: int
+    runs: int
+    max_duration_ms: Optional[int] = None  # The step's budget
+    over_budget: int = 0  # Runs that went over the budget
+    trend: List[DurationPoint] = []
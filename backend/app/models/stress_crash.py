@@ -0,0 +1,60 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, ForeignKey, Integer, Text
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class StressCrash(Base):
+    """A crash or ANR found by a stress test, with what's needed to reproduce it"""
+
+    __tablename__ = "stress_crashes"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    test_run_id: Mapped[str] = mapped_column(
+        String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    kind: Mapped[str] = mapped_column(String, nullable=False)  # 'crash' | 'anr'
+    process: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    message: Mapped[str] = mapped_column(String, nullable=False)
+    stack_trace: Mapped[Optional[str]] = mapped_column(Text, nullable=True)
+    seed: Mapped[int] = mapped_column(Integer, nullable=False)
+    # Events sent before the crash was seen, None when the tool doesn't report it
+    event_index: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    reproduce_command: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class StressCrashResponse(BaseModel):
+    """Schema for stress crash response"""
+
+    id: str
+    test_run_id: str
+    kind: str
+    process: Optional[str]
+    message: str
+    stack_trace: Optional[str]
+    seed: int
+    event_index: Optional[int]
+    reproduce_command: Optional[str]
+    created_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class StressTestRequest(BaseModel):
+    """Schema for starting a stress test on a device"""
+
+    project_id: str
+    device_id: str
+    platform: str  # 'android' | 'ios'
+    package_name: str  # Package or bundle ID of the app under test
+    events: int = 500
+    seed: Optional[int] = None  # Random when not set; reuse a crash's seed to reproduce it
+    throttle_ms: int = 100  # Pause between events
+    priority: str = "normal"  # 'high' | 'normal' | 'low'
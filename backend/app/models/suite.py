@@ -0,0 +1,72 @@
+import uuid
+from datetime import datetime
+from typing import List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Float, ForeignKey, UniqueConstraint
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+from .scenario import ScenarioResponse
+
+
+class Suite(Base):
+    """Named, ordered group of scenarios that may span test cases"""
+
+    __tablename__ = "suites"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
+    name: Mapped[str] = mapped_column(String, nullable=False)
+    description: Mapped[Optional[str]] = mapped_column(String, nullable=True)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class SuiteScenario(Base):
+    """Membership of a scenario in a suite, ordered by position"""
+
+    __tablename__ = "suite_scenarios"
+    __table_args__ = (UniqueConstraint("suite_id", "scenario_id"),)
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    suite_id: Mapped[str] = mapped_column(String, ForeignKey("suites.id", ondelete="CASCADE"), nullable=False, index=True)
+    scenario_id: Mapped[str] = mapped_column(String, ForeignKey("scenarios.id", ondelete="CASCADE"), nullable=False)
+    position: Mapped[float] = mapped_column(Float, nullable=False)
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+
+
+class SuiteCreate(BaseModel):
+    """Schema for creating a suite"""
+
+    project_id: str
+    name: str
+    description: Optional[str] = None
+    scenario_ids: List[str] = []
+
+
+class SuiteUpdate(BaseModel):
+    """Schema for updating a suite"""
+
+    name: Optional[str] = None
+    description: Optional[str] = None
+
+
+class SuiteResponse(BaseModel):
+    """Schema for suite response"""
+
+    id: str
+    project_id: str
+    name: str
+    description: Optional[str]
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
+
+
+class SuiteWithScenarios(SuiteResponse):
+    """Suite with its scenarios in run order"""
+
+    scenarios: List[ScenarioResponse] = []
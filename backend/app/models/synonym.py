@@ -0,0 +1,62 @@
+import json
+import uuid
+from datetime import datetime
+from typing import List, Optional
+
+from pydantic import BaseModel, field_validator
+from sqlalchemy import String, DateTime, ForeignKey, Text
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class SynonymGroup(Base):
+    """Words and phrases the offline element matcher treats as meaning the same thing in a project"""
+
+    __tablename__ = "synonym_groups"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(
+        String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    language: Mapped[str] = mapped_column(String, nullable=False, default="en", server_default="en")  # e.g. en, pt-BR
+    # JSON list of lowercase terms
+    terms: Mapped[str] = mapped_column(Text, nullable=False, default="[]", server_default="[]")
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class SynonymGroupCreate(BaseModel):
+    """Schema for creating a synonym group"""
+
+    project_id: str
+    language: str = "en"
+    terms: List[str]
+
+
+class SynonymGroupUpdate(BaseModel):
+    """Schema for updating a synonym group"""
+
+    language: Optional[str] = None
+    terms: Optional[List[str]] = None
+
+
+class SynonymGroupResponse(BaseModel):
+    """Schema for synonym group response"""
+
+    id: str
+    project_id: str
+    language: str
+    terms: List[str]
+    created_at: datetime
+    updated_at: datetime
+
+    @field_validator("terms", mode="before")
+    @classmethod
+    def parse_terms(cls, v):
+        if isinstance(v, str):
+            return json.loads(v or "[]")
+        return v
+
+    class Config:
+        from_attributes = True
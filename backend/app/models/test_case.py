@@ -3,7 +3,7 @@ from datetime import datetime
 from typing import Optional, List
 
 from pydantic import BaseModel
-from sqlalchemy import String, DateTime, ForeignKey
+from sqlalchemy import String, DateTime, ForeignKey, Index
 from sqlalchemy.orm import Mapped, mapped_column
 
 from app.db.database import Base
@@ -13,6 +13,10 @@ class TestCase(Base):
     """Test case database model"""
 
     __tablename__ = "test_cases"
+    __table_args__ = (
+        Index("ix_test_cases_project_status", "project_id", "status"),
+        Index("ix_test_cases_project_updated", "project_id", "updated_at"),
+    )
 
     id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
     project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
@@ -46,6 +50,7 @@ class TestCaseUpdate(BaseModel):
     priority: Optional[str] = None
     test_type: Optional[str] = None
     status: Optional[str] = None
+    expected_updated_at: Optional[datetime] = None  # Fails with a conflict if the row changed since
 
 
 class TestCaseFilter(BaseModel):
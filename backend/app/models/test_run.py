@@ -1,11 +1,12 @@
 import uuid
 from datetime import datetime
-from typing import Optional
+from typing import List, Optional
 
 from pydantic import BaseModel
-from sqlalchemy import String, DateTime, Integer, ForeignKey
+from sqlalchemy import String, DateTime, Integer, ForeignKey, Index
 from sqlalchemy.orm import Mapped, mapped_column
 
+from app.config import settings
 from app.db.database import Base
 
 
@@ -13,6 +14,10 @@ class TestRun(Base):
     """Test run database model"""
 
     __tablename__ = "test_runs"
+    __table_args__ = (
+        Index("ix_test_runs_project_created", "project_id", "created_at"),
+        Index("ix_test_runs_project_status", "project_id", "status"),
+    )
 
     id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
     project_id: Mapped[str] = mapped_column(String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False)
@@ -22,8 +27,16 @@ class TestRun(Base):
     passed: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
     failed: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
     skipped: Mapped[int] = mapped_column(Integer, nullable=False, default=0)
+    # Failures on test cases with an active known issue; included in failed
+    expected_failed: Mapped[int] = mapped_column(Integer, nullable=False, default=0, server_default="0")
     started_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
     completed_at: Mapped[Optional[datetime]] = mapped_column(DateTime, nullable=True)
+    # The run whose failed scenarios this run retries
+    rerun_of: Mapped[Optional[str]] = mapped_column(
+        String, ForeignKey("test_runs.id", ondelete="SET NULL"), nullable=True, index=True
+    )
+    # The backend instance that runs it, so a restart of one sharing the database only aborts its own runs
+    owner_instance: Mapped[Optional[str]] = mapped_column(String, nullable=True, default=settings.get_instance_id)
     created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
 
 
@@ -57,8 +70,10 @@ class TestRunResponse(BaseModel):
     passed: int
     failed: int
     skipped: int
+    expected_failed: int
     started_at: Optional[datetime]
     completed_at: Optional[datetime]
+    rerun_of: Optional[str] = None
     created_at: datetime
 
     class Config:
@@ -72,3 +87,88 @@ class TestRunSummary(BaseModel):
     passed_runs: int
     failed_runs: int
     avg_duration_ms: Optional[float]
+
+
+class StatusCounts(BaseModel):
+    passed: int = 0
+    failed: int = 0
+    expected_failed: int = 0
+    skipped: int = 0
+
+
+class CategoryBreakdown(StatusCounts):
+    category: str
+
+
+class ViewportBreakdown(StatusCounts):
+    viewport: str
+    screenshot_ids: List[str] = []  # Screenshot attachments taken at this viewport
+
+
+class TestCaseOutcome(StatusCounts):
+    """How one test case fared within a test run"""
+
+    test_case_id: str
+    name: str
+    category: Optional[str]
+    status: str
+    known_issue_id: Optional[str] = None
+    issue_key: Optional[str] = None
+    issue_url: Optional[str] = None
+
+
+class RunBreakdown(BaseModel):
+    """Detailed per-category and per-test-case results of a test run"""
+
+    run: TestRunResponse
+    totals: StatusCounts
+    healed_steps: int
+    by_category: List[CategoryBreakdown]
+    test_cases: List[TestCaseOutcome]
+    by_viewport: List[ViewportBreakdown] = []
+
+
+class StepDelta(BaseModel):
+    """How one step's result changed between two runs"""
+
+    step_id: str
+    label: str
+    step_type: str
+    status_a: Optional[str]  # None when the step didn't run in that run
+    status_b: Optional[str]
+    # 'newly_failing' | 'newly_passing' | 'status_changed' | 'unchanged' | 'added' | 'removed'
+    change: str
+    duration_a_ms: Optional[int]
+    duration_b_ms: Optional[int]
+    duration_regression: bool = False
+    screenshot_a: Optional[str] = None
+    screenshot_b: Optional[str] = None
+    # Fraction of pixels that differ, None when either run has no screenshot
+    screenshot_diff: Optional[float] = None
+    screenshot_changed: bool = False
+
+
+class ScenarioDelta(BaseModel):
+    """How one scenario's outcome changed between two runs, with its steps"""
+
+    scenario_id: str
+    name: str
+    status_a: Optional[str]
+    status_b: Optional[str]
+    change: str
+    duration_a_ms: Optional[int]
+    duration_b_ms: Optional[int]
+    duration_regression: bool = False
+    steps: List[StepDelta]
+
+
+class RunComparison(BaseModel):
+    """Differences between an earlier run (a) and a later run (b)"""
+
+    run_a: TestRunResponse
+    run_b: TestRunResponse
+    newly_failing: int
+    newly_passing: int
+    duration_regressions: int
+    changed_screenshots: int
+    scenarios: List[ScenarioDelta]
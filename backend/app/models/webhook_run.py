@@ -0,0 +1,52 @@
+import uuid
+from datetime import datetime
+from typing import Optional
+
+from pydantic import BaseModel
+from sqlalchemy import String, DateTime, Integer, ForeignKey
+from sqlalchemy.orm import Mapped, mapped_column
+
+from app.db.database import Base
+
+
+class WebhookRun(Base):
+    """Test run started by a GitHub push or pull request webhook, with the check run reporting it"""
+
+    __tablename__ = "webhook_runs"
+
+    id: Mapped[str] = mapped_column(String, primary_key=True, default=lambda: str(uuid.uuid4()))
+    project_id: Mapped[str] = mapped_column(
+        String, ForeignKey("projects.id", ondelete="CASCADE"), nullable=False, index=True
+    )
+    test_run_id: Mapped[Optional[str]] = mapped_column(
+        String, ForeignKey("test_runs.id", ondelete="CASCADE"), nullable=True, index=True
+    )  # Empty when no mapped scenarios were affected
+    event: Mapped[str] = mapped_column(String, nullable=False)  # 'push' | 'pull_request'
+    repository: Mapped[str] = mapped_column(String, nullable=False)  # owner/repo
+    base_sha: Mapped[str] = mapped_column(String, nullable=False)
+    head_sha: Mapped[str] = mapped_column(String, nullable=False)
+    pull_number: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    check_run_id: Mapped[Optional[int]] = mapped_column(Integer, nullable=True)
+    error: Mapped[Optional[str]] = mapped_column(String, nullable=True)  # Why the run or check couldn't start
+    created_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow)
+    updated_at: Mapped[datetime] = mapped_column(DateTime, default=datetime.utcnow, onupdate=datetime.utcnow)
+
+
+class WebhookRunResponse(BaseModel):
+    """Schema for webhook run response"""
+
+    id: str
+    project_id: str
+    test_run_id: Optional[str]
+    event: str
+    repository: str
+    base_sha: str
+    head_sha: str
+    pull_number: Optional[int]
+    check_run_id: Optional[int]
+    error: Optional[str]
+    created_at: datetime
+    updated_at: datetime
+
+    class Config:
+        from_attributes = True
@@ -9,6 +9,36 @@ from .ai import router as ai_router
 from .mobile import router as mobile_router
 from .integrations import router as integrations_router
 from .test_runner import router as test_runner_router
+from .executions import router as executions_router
+from .settings import router as settings_router
+from .events import router as events_router
+from .backups import router as backups_router
+from .elements import router as elements_router
+from .suites import router as suites_router
+from .known_issues import router as known_issues_router
+from .control import router as control_router
+from .plugins import router as plugins_router
+from .shell import router as shell_router
+from .remote_devices import router as remote_devices_router
+from .baselines import router as baselines_router
+from .queue import router as queue_router
+from .agents import router as agents_router
+from .attachments import router as attachments_router
+from .schedules import router as schedules_router
+from .preferences import router as preferences_router
+from .doctor import router as doctor_router
+from .screen_maps import router as screen_maps_router
+from .stress_tests import router as stress_tests_router
+from .browsers import router as browsers_router
+from .custom_fields import router as custom_fields_router
+from .saved_filters import router as saved_filters_router
+from .quick_actions import router as quick_actions_router
+from .drafts import router as drafts_router
+from .test_selection import router as test_selection_router
+from .webhooks import router as webhooks_router
+from .storage import router as storage_router
+from .debugging import router as debugging_router
+from .synonyms import router as synonyms_router
 
 __all__ = [
     "projects_router",
@@ -22,4 +52,34 @@ __all__ = [
     "mobile_router",
     "integrations_router",
     "test_runner_router",
+    "executions_router",
+    "settings_router",
+    "events_router",
+    "backups_router",
+    "elements_router",
+    "suites_router",
+    "known_issues_router",
+    "control_router",
+    "plugins_router",
+    "shell_router",
+    "remote_devices_router",
+    "baselines_router",
+    "queue_router",
+    "agents_router",
+    "attachments_router",
+    "schedules_router",
+    "preferences_router",
+    "doctor_router",
+    "screen_maps_router",
+    "stress_tests_router",
+    "browsers_router",
+    "custom_fields_router",
+    "saved_filters_router",
+    "quick_actions_router",
+    "drafts_router",
+    "test_selection_router",
+    "webhooks_router",
+    "storage_router",
+    "debugging_router",
+    "synonyms_router",
 ]
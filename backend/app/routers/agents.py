@@ -0,0 +1,183 @@
+import uuid
+from typing import Any, Dict, List, Optional
+
+from fastapi import APIRouter, Body, Depends, HTTPException, Query, Response
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import AgentRegistration, RunnerAgent, RunnerAgentResponse, Scenario, TestCase, TestRun, TestRunResponse
+from app.services.agents import (
+    AgentError,
+    JobReportError,
+    agent_response,
+    choose_agent,
+    dispatch_run,
+    get_agent_token,
+    next_job,
+    record_job_result,
+    register_agent,
+    require_agent_token,
+    rotate_agent_token,
+)
+from app.services.app_settings import get_setting
+from app.services.control_api import require_main_process
+from app.services.dependencies import DependencyError, execution_order, load_prerequisites
+from app.services.test_runner import TestFramework
+
+router = APIRouter(prefix="/agents", tags=["agents"])
+
+# Endpoints agents call; each needs "Authorization: Bearer <agent token>"
+protected = APIRouter(dependencies=[Depends(require_agent_token)])
+
+
+class AgentTokenResponse(BaseModel):
+    enabled: bool
+    token: str
+
+
+class AgentRunRequest(BaseModel):
+    """Schema for running scenarios on a runner agent"""
+
+    scenario_ids: List[str]
+    target: str = "device"  # 'device' | 'browser'
+    platform: Optional[str] = None  # 'android' | 'ios'
+    device_id: Optional[str] = None  # Any ready device of the platform when empty
+    agent_id: Optional[str] = None  # The least busy matching agent when empty
+    framework: TestFramework = TestFramework.PLAYWRIGHT
+    browser: str = "chromium"
+    headless: bool = True
+    self_heal: bool = True
+    name: Optional[str] = None
+
+
+class AgentRunResponse(BaseModel):
+    test_run: TestRunResponse
+    agent_id: str
+    order: List[str]
+
+
+async def get_agent_or_404(db: AsyncSession, agent_id: str) -> RunnerAgent:
+    agent = await db.get(RunnerAgent, agent_id)
+    if not agent:
+        raise HTTPException(status_code=404, detail="Agent not found")
+    return agent
+
+
+@router.get("", response_model=List[RunnerAgentResponse])
+async def list_agents(db: AsyncSession = Depends(get_db)):
+    """List registered agents with what they last advertised"""
+    result = await db.execute(select(RunnerAgent).order_by(RunnerAgent.name))
+    return [agent_response(agent) for agent in result.scalars().all()]
+
+
+@router.get("/token", response_model=AgentTokenResponse, dependencies=[Depends(require_main_process)])
+async def read_agent_token(db: AsyncSession = Depends(get_db)):
+    """Get the token to configure on agent machines; only the desktop app's main process may"""
+    return AgentTokenResponse(enabled=await get_setting(db, "agents.enabled"), token=get_agent_token())
+
+
+@router.post("/token/rotate", response_model=AgentTokenResponse, dependencies=[Depends(require_main_process)])
+async def rotate_token(db: AsyncSession = Depends(get_db)):
+    """Issue a new agent token; agents need the new one to reconnect"""
+    return AgentTokenResponse(enabled=await get_setting(db, "agents.enabled"), token=rotate_agent_token())
+
+
+@router.delete("/{agent_id}")
+async def delete_agent(agent_id: str, db: AsyncSession = Depends(get_db)):
+    """Forget an agent; it registers again the next time it connects"""
+    agent = await get_agent_or_404(db, agent_id)
+    await db.delete(agent)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.post("/run", response_model=AgentRunResponse)
+async def run_on_agent(data: AgentRunRequest, db: AsyncSession = Depends(get_db)):
+    """Run scenarios in dependency order on an agent that has the requested device or browser"""
+    if not data.scenario_ids:
+        raise HTTPException(status_code=400, detail="No scenarios to run")
+    if data.target not in ("device", "browser"):
+        raise HTTPException(status_code=400, detail=f"Unknown target: {data.target}")
+    if data.target == "device" and data.platform not in ("android", "ios"):
+        raise HTTPException(status_code=400, detail="Platform must be android or ios for device runs")
+    try:
+        order = await execution_order(db, data.scenario_ids)
+    except DependencyError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    try:
+        agent = await choose_agent(
+            db,
+            platform=data.platform,
+            device_id=data.device_id,
+            browser=data.browser if data.target == "browser" else None,
+            agent_id=data.agent_id,
+        )
+    except AgentError as e:
+        raise HTTPException(status_code=409, detail=str(e))
+
+    first = await db.get(Scenario, order[0])
+    test_case = await db.get(TestCase, first.test_case_id)
+    test_run = TestRun(
+        id=str(uuid.uuid4()),
+        project_id=test_case.project_id,
+        name=data.name or (first.name if len(order) == 1 else f"{len(order)} scenarios"),
+    )
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    options = data.model_dump(include={"target", "platform", "device_id", "browser", "headless", "self_heal"})
+    options["framework"] = data.framework.value
+    try:
+        await dispatch_run(db, agent, test_run, order, options, await load_prerequisites(db))
+    except AgentError as e:
+        await db.delete(test_run)
+        await db.commit()
+        raise HTTPException(status_code=400, detail=str(e))
+    return AgentRunResponse(test_run=test_run, agent_id=agent.id, order=order)
+
+
+@protected.post("/register", response_model=RunnerAgentResponse)
+async def register(
+    data: AgentRegistration, agent_id: Optional[str] = Query(None), db: AsyncSession = Depends(get_db)
+):
+    """Register an agent, or refresh the record of one reconnecting with its ID"""
+    return agent_response(await register_agent(db, data, agent_id))
+
+
+@protected.post("/{agent_id}/heartbeat", response_model=RunnerAgentResponse)
+async def heartbeat(agent_id: str, data: AgentRegistration, db: AsyncSession = Depends(get_db)):
+    """Record that an agent is alive, with its current devices and browsers"""
+    await get_agent_or_404(db, agent_id)
+    return agent_response(await register_agent(db, data, agent_id))
+
+
+@protected.get("/{agent_id}/jobs/next")
+async def get_next_job(
+    agent_id: str, wait: float = Query(25, ge=0, le=60), db: AsyncSession = Depends(get_db)
+):
+    """Wait for the agent's next job, answering 204 when none arrives in time"""
+    await get_agent_or_404(db, agent_id)
+    job = await next_job(db, agent_id, wait)
+    if not job:
+        return Response(status_code=204)
+    return job.payload
+
+
+@protected.post("/{agent_id}/jobs/{job_id}/result", response_model=TestRunResponse)
+async def report_job_result(
+    agent_id: str, job_id: str, result: Dict[str, Any] = Body(...), db: AsyncSession = Depends(get_db)
+):
+    """Store the results of a job an agent finished"""
+    try:
+        return await record_job_result(db, agent_id, job_id, result)
+    except JobReportError as e:
+        raise HTTPException(status_code=422, detail=str(e))
+    except AgentError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+
+
+router.include_router(protected)
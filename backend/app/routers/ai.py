@@ -1,10 +1,42 @@
+import time
+import uuid
 from typing import Optional, List, Any
 
 import httpx
-from fastapi import APIRouter, HTTPException
+from fastapi import APIRouter, Depends, HTTPException
 from pydantic import BaseModel
-
-from app.config import settings
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.errors import CommandError, ErrorCode
+from app.models import (
+    AiUsageSummary,
+    GenerateTestsResponse,
+    Project,
+    Scenario,
+    ScenarioWithSteps,
+    StepConfig,
+    StepCreate,
+    StepResponse,
+    TestCase,
+)
+from app.services.ai_cache import cache_key, clear_cache, get_cached_result, store_cached_result
+from app.services.ai_client import AiClientError, ask_ai_json, get_provider, load_ai_options
+from app.services.ai_jobs import (
+    AiJobError,
+    AiJobNotFound,
+    AiJobResponse,
+    cancel_job,
+    get_job,
+    get_job_result,
+    submit_generation_job,
+)
+from app.services.ai_usage import PERIODS, get_usage_summary, record_usage
+from app.services.secret_masking import mask_data
+from app.services.service_urls import get_service_url
+from app.services.tracing import tracer
+from .steps import add_steps
 
 router = APIRouter(prefix="/ai", tags=["ai"])
 
@@ -47,18 +79,6 @@ class GenerateTestsRequest(BaseModel):
     requirements: Optional[List[str]] = None
 
 
-class GeneratedTest(BaseModel):
-    name: str
-    description: str
-    code: str
-    test_type: str
-
-
-class GenerateTestsResponse(BaseModel):
-    tests: List[GeneratedTest]
-    coverage_estimate: str
-
-
 class ParseRequirementsRequest(BaseModel):
     requirements: str
     format: Optional[str] = None
@@ -122,6 +142,9 @@ class AiWebElementLocation(BaseModel):
     found: bool
     selector: str
     xpath: Optional[str] = None
+    # Set when the element is inside iframes or shadow roots, outermost first
+    frame_path: List[str] = []
+    shadow_path: List[str] = []
     element_type: str
     confidence: float
     description: str
@@ -149,65 +172,156 @@ class SuggestWebStepRequest(BaseModel):
     page_html: Optional[str] = None
 
 
+class GenerateScenarioRequest(BaseModel):
+    test_case_id: str
+    description: str
+    name: Optional[str] = None
+
+
+WEB_STEP_TYPES = {
+    "navigate": "open a URL (config: url)",
+    "click": "click an element (config: selector, element_description)",
+    "type": "type into a field (config: selector, value, element_description)",
+    "verify": "assert an element or URL (config: selector or expected, element_description)",
+    "wait": "pause (config: timeout in ms)",
+}
+
+MOBILE_STEP_TYPES = {
+    "launch": "launch the app (config: packageName)",
+    "tap": "tap an element (config: element_description)",
+    "tap_element": "tap an element found when the step runs (config: resource_id, text or content_desc, "
+    "element_description)",
+    "input": "type text into the focused field (config: value, element_description)",
+    "swipe": "swipe the screen (config: element_description describing the direction)",
+    "back": "press the back button",
+    "home": "press the home button",
+    "wait": "pause (config: timeout in ms)",
+}
+
+
 # ============================================
 # AI Agent Endpoints
 # ============================================
 
 
+async def call_ai_agent(command: str, path: str, payload: dict, timeout: float) -> Any:
+    """Forward a request to the AI agent service, recording its usage"""
+    payload = mask_data(payload)
+    start_time = time.time()
+    try:
+        with tracer.span(command, "ai", provider="ai-agent", path=path):
+            async with httpx.AsyncClient(timeout=timeout) as client:
+                response = await client.post(f"{await get_service_url('ai_agent')}{path}", json=payload)
+                response.raise_for_status()
+                data = response.json()
+    except httpx.HTTPError as e:
+        await record_usage(
+            command, "ai-agent", "ai-agent", 0, 0, int((time.time() - start_time) * 1000), success=False
+        )
+        raise CommandError(502, ErrorCode.AI_PROVIDER_ERROR, f"AI service error: {str(e)}")
+
+    # The agent reports token usage when its provider exposes it
+    usage = data.get("usage") if isinstance(data, dict) else None
+    usage = usage if isinstance(usage, dict) else {}
+    await record_usage(
+        command,
+        "ai-agent",
+        (data.get("model") if isinstance(data, dict) else None) or "ai-agent",
+        usage.get("input_tokens", 0),
+        usage.get("output_tokens", 0),
+        int((time.time() - start_time) * 1000),
+    )
+    return data
+
+
 @router.get("/available")
 async def check_ai_available():
     """Check if AI agent service is available"""
     try:
         async with httpx.AsyncClient(timeout=5.0) as client:
-            response = await client.get(f"{settings.ai_agent_url}/health")
+            response = await client.get(f"{await get_service_url('ai_agent')}/health")
             return {"available": response.status_code == 200}
     except Exception:
         return {"available": False}
 
 
+@router.get("/provider")
+async def get_ai_provider():
+    """Get the AI provider used for direct AI calls"""
+    options = await load_ai_options()
+    try:
+        provider = get_provider(options)
+    except AiClientError as e:
+        return {"provider": options.provider, "model": None, "configured": False, "error": str(e)}
+    return {"provider": provider.name, "model": provider.model, "configured": True, "error": None}
+
+
+@router.get("/usage", response_model=AiUsageSummary)
+async def get_ai_usage_summary(period: str = "month", db: AsyncSession = Depends(get_db)):
+    """Get AI request counts and estimated cost per model for a period"""
+    if period not in PERIODS:
+        raise HTTPException(
+            status_code=400, detail=f"Invalid period, expected one of: {', '.join(PERIODS)}"
+        )
+    return await get_usage_summary(db, period)
+
+
 @router.post("/analyze-code", response_model=AnalyzeCodeResponse)
 async def analyze_code(request: AnalyzeCodeRequest):
     """Analyze code using AI"""
-    try:
-        async with httpx.AsyncClient(timeout=30.0) as client:
-            response = await client.post(
-                f"{settings.ai_agent_url}/analyze-code",
-                json=request.model_dump(),
-            )
-            response.raise_for_status()
-            return response.json()
-    except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"AI service error: {str(e)}")
+    return await call_ai_agent("analyze_code", "/analyze-code", request.model_dump(), 30.0)
 
 
 @router.post("/generate-tests", response_model=GenerateTestsResponse)
 async def generate_tests(request: GenerateTestsRequest):
     """Generate tests using AI"""
+    return await call_ai_agent("generate_tests", "/generate-tests", request.model_dump(), 60.0)
+
+
+@router.post("/generate-tests/jobs", response_model=AiJobResponse)
+async def submit_generate_tests_job(request: GenerateTestsRequest):
+    """Submit test generation as a background job, for codebases too big to finish within a request"""
     try:
-        async with httpx.AsyncClient(timeout=60.0) as client:
-            response = await client.post(
-                f"{settings.ai_agent_url}/generate-tests",
-                json=request.model_dump(),
-            )
-            response.raise_for_status()
-            return response.json()
-    except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"AI service error: {str(e)}")
+        return await submit_generation_job(request.model_dump())
+    except AiJobError as e:
+        raise CommandError(502, ErrorCode.AI_PROVIDER_ERROR, str(e))
+
+
+@router.get("/jobs/{job_id}", response_model=AiJobResponse)
+async def get_ai_job(job_id: str):
+    """Get the status and progress of an AI job"""
+    try:
+        return get_job(job_id)
+    except AiJobNotFound as e:
+        raise HTTPException(status_code=404, detail=str(e))
+
+
+@router.get("/jobs/{job_id}/result", response_model=GenerateTestsResponse)
+async def get_ai_job_result(job_id: str):
+    """Get the generated tests of a completed AI job"""
+    try:
+        return await get_job_result(job_id)
+    except AiJobNotFound as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    except AiJobError as e:
+        raise CommandError(409, ErrorCode.CONFLICT, str(e))
+
+
+@router.delete("/jobs/{job_id}", response_model=AiJobResponse)
+async def cancel_ai_job(job_id: str):
+    """Cancel a running AI job"""
+    try:
+        return await cancel_job(job_id)
+    except AiJobNotFound as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    except AiJobError as e:
+        raise CommandError(502, ErrorCode.AI_PROVIDER_ERROR, str(e))
 
 
 @router.post("/parse-requirements", response_model=ParseRequirementsResponse)
 async def parse_requirements(request: ParseRequirementsRequest):
     """Parse requirements into test cases using AI"""
-    try:
-        async with httpx.AsyncClient(timeout=60.0) as client:
-            response = await client.post(
-                f"{settings.ai_agent_url}/parse-requirements",
-                json=request.model_dump(),
-            )
-            response.raise_for_status()
-            return response.json()
-    except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"AI service error: {str(e)}")
+    return await call_ai_agent("parse_requirements", "/parse-requirements", request.model_dump(), 60.0)
 
 
 # ============================================
@@ -218,43 +332,125 @@ async def parse_requirements(request: ParseRequirementsRequest):
 @router.post("/web/analyze", response_model=AiWebAnalysisResult)
 async def analyze_web_page(request: AnalyzeWebPageRequest):
     """Analyze a web page screenshot using AI"""
-    try:
-        async with httpx.AsyncClient(timeout=60.0) as client:
-            response = await client.post(
-                f"{settings.ai_agent_url}/web/analyze",
-                json=request.model_dump(),
-            )
-            response.raise_for_status()
-            return response.json()
-    except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"AI service error: {str(e)}")
+    return await call_ai_agent("analyze_web_page", "/web/analyze", request.model_dump(), 60.0)
 
 
 @router.post("/web/find-element", response_model=AiWebElementLocation)
 async def find_web_element(request: FindWebElementRequest):
-    """Find a web element using AI"""
-    try:
-        async with httpx.AsyncClient(timeout=30.0) as client:
-            response = await client.post(
-                f"{settings.ai_agent_url}/web/find-element",
-                json=request.model_dump(),
-            )
-            response.raise_for_status()
-            return response.json()
-    except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"AI service error: {str(e)}")
+    """Find a web element using AI, reusing cached results for identical screens"""
+    key = cache_key(
+        "find_web_element",
+        request.screenshot_base64,
+        request.page_html,
+        request.element_description,
+    )
+    cached = await get_cached_result(key)
+    if cached is not None:
+        return cached
+
+    result = await call_ai_agent("find_web_element", "/web/find-element", request.model_dump(), 30.0)
+    # Misses aren't cached so a retry can still succeed
+    if result.get("found"):
+        await store_cached_result(key, "find_web_element", result)
+    return result
+
+
+@router.delete("/cache")
+async def clear_ai_cache(expired_only: bool = False):
+    """Clear cached AI element lookups"""
+    return {"cleared": await clear_cache(expired_only)}
 
 
 @router.post("/web/suggest-step", response_model=AiWebSuggestedStep)
 async def suggest_web_step(request: SuggestWebStepRequest):
     """Get AI-suggested next step for web testing"""
+    return await call_ai_agent("suggest_web_step", "/web/suggest-step", request.model_dump(), 30.0)
+
+
+# ============================================
+# AI Scenario Authoring Endpoints
+# ============================================
+
+
+@router.post("/generate-scenario", response_model=ScenarioWithSteps)
+async def generate_scenario_from_text(
+    request: GenerateScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Turn a plain-English flow into a new scenario with ordered steps"""
+    result = await db.execute(select(TestCase).where(TestCase.id == request.test_case_id))
+    test_case = result.scalar_one_or_none()
+    if not test_case:
+        raise HTTPException(status_code=404, detail="Test case not found")
+    project = await db.get(Project, test_case.project_id)
+
+    is_web = project.project_type == "web"
+    step_types = WEB_STEP_TYPES if is_web else MOBILE_STEP_TYPES
+    step_type_lines = "\n".join(f"- {name}: {usage}" for name, usage in step_types.items())
+
+    prompt = f"""You are a QA automation engineer. Convert this test flow into ordered test steps.
+
+Application: {project.name} ({project.project_type}) at {project.app_url}
+Test case: {test_case.name}
+Flow: {request.description}
+
+Allowed step types:
+{step_type_lines}
+
+Locators are unknown, so leave "selector" empty and always describe the target element
+in "element_description" so it can be located later.
+
+Respond with JSON only:
+{{"name": "short scenario name", "steps": [{{"step_type": "...", "label": "...", "config": {{}}}}]}}"""
+
     try:
-        async with httpx.AsyncClient(timeout=30.0) as client:
-            response = await client.post(
-                f"{settings.ai_agent_url}/web/suggest-step",
-                json=request.model_dump(),
+        generated = await ask_ai_json(prompt, "generate_scenario_from_text")
+    except AiClientError as e:
+        raise CommandError(502, ErrorCode.AI_PROVIDER_ERROR, str(e))
+
+    if isinstance(generated, list):
+        generated = {"steps": generated}
+    generated_steps = [
+        s for s in generated.get("steps", []) if s.get("step_type") in step_types
+    ]
+    if not generated_steps:
+        raise CommandError(502, ErrorCode.AI_PROVIDER_ERROR, "AI did not return any usable steps")
+
+    scenario = Scenario(
+        id=str(uuid.uuid4()),
+        test_case_id=test_case.id,
+        name=request.name or generated.get("name") or test_case.name,
+        description=request.description,
+        target_url=project.app_url if is_web else None,
+    )
+    db.add(scenario)
+    await db.flush()
+
+    steps = add_steps(
+        db,
+        [
+            StepCreate(
+                scenario_id=scenario.id,
+                step_order=order,
+                step_type=s["step_type"],
+                label=s.get("label") or s["step_type"],
+                config=StepConfig(**(s.get("config") or {})),
             )
-            response.raise_for_status()
-            return response.json()
-    except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"AI service error: {str(e)}")
+            for order, s in enumerate(generated_steps)
+        ],
+    )
+
+    await db.commit()
+    await db.refresh(scenario)
+    for step in steps:
+        await db.refresh(step)
+
+    return ScenarioWithSteps(
+        id=scenario.id,
+        test_case_id=scenario.test_case_id,
+        name=scenario.name,
+        description=scenario.description,
+        target_url=scenario.target_url,
+        created_at=scenario.created_at,
+        updated_at=scenario.updated_at,
+        steps=[StepResponse.model_validate(step) for step in steps],
+    )
@@ -0,0 +1,105 @@
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, File, Form, HTTPException, Query, UploadFile
+from fastapi.responses import FileResponse
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import Attachment, AttachmentResponse
+from app.services.app_settings import get_all_settings, get_setting
+from app.services.attachments import (
+    AttachmentError,
+    attachment_response,
+    delete_attachment,
+    get_storage,
+    store_attachment,
+    verify_download,
+)
+
+router = APIRouter(prefix="/attachments", tags=["attachments"])
+
+UPLOAD_CHUNK_BYTES = 1024 * 1024
+
+
+async def get_attachment_or_404(db: AsyncSession, attachment_id: str) -> Attachment:
+    attachment = await db.get(Attachment, attachment_id)
+    if not attachment:
+        raise HTTPException(status_code=404, detail="Attachment not found")
+    return attachment
+
+
+@router.post("", response_model=AttachmentResponse)
+async def upload_attachment(
+    file: UploadFile = File(...),
+    test_run_id: str = Form(...),
+    step_result_id: Optional[str] = Form(None),
+    kind: str = Form("other"),
+    db: AsyncSession = Depends(get_db),
+):
+    """Upload a screenshot, video or other evidence for a test run or one of its step results"""
+    max_bytes = await get_setting(db, "attachments.max_upload_mb") * 1024 * 1024
+    data = bytearray()
+    while chunk := await file.read(UPLOAD_CHUNK_BYTES):
+        data.extend(chunk)
+        if max_bytes and len(data) > max_bytes:
+            raise HTTPException(status_code=413, detail="Attachment is larger than attachments.max_upload_mb")
+    try:
+        attachment = await store_attachment(
+            db, test_run_id, file.filename, file.content_type, bytes(data), kind, step_result_id
+        )
+        return attachment_response(attachment, await get_all_settings(db))
+    except AttachmentError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.get("/test-run/{test_run_id}", response_model=List[AttachmentResponse])
+async def list_attachments(
+    test_run_id: str,
+    step_result_id: Optional[str] = Query(None),
+    db: AsyncSession = Depends(get_db),
+):
+    """List a test run's attachments with signed download links"""
+    query = select(Attachment).where(Attachment.test_run_id == test_run_id)
+    if step_result_id:
+        query = query.where(Attachment.step_result_id == step_result_id)
+    result = await db.execute(query.order_by(Attachment.created_at))
+    values = await get_all_settings(db)
+    return [attachment_response(attachment, values) for attachment in result.scalars().all()]
+
+
+@router.get("/{attachment_id}", response_model=AttachmentResponse)
+async def get_attachment(attachment_id: str, db: AsyncSession = Depends(get_db)):
+    """Get an attachment with a fresh signed download link"""
+    attachment = await get_attachment_or_404(db, attachment_id)
+    return attachment_response(attachment, await get_all_settings(db))
+
+
+@router.get("/{attachment_id}/download")
+async def download_attachment(
+    attachment_id: str,
+    expires: int = Query(...),
+    signature: str = Query(...),
+    db: AsyncSession = Depends(get_db),
+):
+    """Download a locally stored attachment using a signed link"""
+    if not verify_download(attachment_id, expires, signature):
+        raise HTTPException(status_code=403, detail="Download link is invalid or has expired")
+    attachment = await get_attachment_or_404(db, attachment_id)
+    if attachment.storage != "local":
+        raise HTTPException(status_code=400, detail="Attachment is in object storage; use its signed URL")
+    path = get_storage(await get_all_settings(db), "local").path(attachment.storage_key)
+    if not path.is_file():
+        raise HTTPException(status_code=404, detail="Attachment file is missing")
+    return FileResponse(path, media_type=attachment.content_type, filename=attachment.filename)
+
+
+@router.delete("/{attachment_id}")
+async def remove_attachment(attachment_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete an attachment and its stored file"""
+    attachment = await get_attachment_or_404(db, attachment_id)
+    try:
+        await delete_attachment(db, attachment)
+    except AttachmentError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"status": "deleted"}
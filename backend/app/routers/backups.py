@@ -0,0 +1,94 @@
+from typing import List
+
+from fastapi import APIRouter, HTTPException
+from pydantic import BaseModel
+
+from app.services.backup import (
+    BackupError,
+    backup_database,
+    create_snapshot,
+    list_snapshots,
+    restore_database,
+)
+from app.db.encryption import EncryptionError
+from app.services.database_encryption import (
+    EncryptionStatus,
+    encryption_status,
+    migrate_to_encrypted,
+    migrate_to_plain,
+)
+
+router = APIRouter(prefix="/database", tags=["database"])
+
+
+class BackupRequest(BaseModel):
+    path: str
+
+
+class SnapshotInfo(BaseModel):
+    path: str
+    size_bytes: int
+    created_at: float
+
+
+@router.post("/backup")
+async def create_backup(request: BackupRequest):
+    """Back up the database to a new file"""
+    try:
+        target = await backup_database(request.path)
+    except BackupError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"path": str(target)}
+
+
+@router.post("/restore")
+async def restore_backup(request: BackupRequest):
+    """Restore the database from a backup file"""
+    try:
+        safety_snapshot = await restore_database(request.path)
+    except BackupError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"status": "restored", "previous_snapshot": str(safety_snapshot)}
+
+
+@router.get("/snapshots", response_model=List[SnapshotInfo])
+async def get_snapshots():
+    """List database snapshots, newest first"""
+    return [
+        SnapshotInfo(path=str(path), size_bytes=path.stat().st_size, created_at=path.stat().st_mtime)
+        for path in list_snapshots()
+    ]
+
+
+@router.post("/snapshots")
+async def take_snapshot():
+    """Take a database snapshot now"""
+    try:
+        target = await create_snapshot(reason="manual")
+    except BackupError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"path": str(target)}
+
+
+@router.get("/encryption", response_model=EncryptionStatus)
+async def get_encryption_status():
+    """Whether the database is encrypted and whether encryption can be turned on"""
+    return encryption_status()
+
+
+@router.post("/encryption/migrate-to-encrypted", response_model=EncryptionStatus)
+async def encrypt_database():
+    """Encrypt the database with SQLCipher, keeping the key in the OS keychain"""
+    try:
+        return await migrate_to_encrypted()
+    except EncryptionError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.post("/encryption/migrate-to-plain", response_model=EncryptionStatus)
+async def decrypt_database():
+    """Decrypt the database back to plain SQLite"""
+    try:
+        return await migrate_to_plain()
+    except EncryptionError as e:
+        raise HTTPException(status_code=400, detail=str(e))
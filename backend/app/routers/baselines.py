@@ -0,0 +1,77 @@
+from typing import List
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    BaselineComparison,
+    Scenario,
+    ScenarioBaselineCreate,
+    ScenarioBaselineResponse,
+    TestRun,
+)
+from app.services.baselines import (
+    BaselineError,
+    approve_baseline,
+    baseline_history,
+    clear_baseline,
+    compare_with_baselines,
+    current_baseline,
+)
+
+router = APIRouter(prefix="/baselines", tags=["baselines"])
+
+
+async def _get_scenario_or_404(db: AsyncSession, scenario_id: str) -> Scenario:
+    scenario = await db.get(Scenario, scenario_id)
+    if not scenario:
+        raise HTTPException(status_code=404, detail="Scenario not found")
+    return scenario
+
+
+@router.get("/scenario/{scenario_id}", response_model=ScenarioBaselineResponse)
+async def get_scenario_baseline(scenario_id: str, db: AsyncSession = Depends(get_db)):
+    """Get the run a scenario is currently compared against"""
+    await _get_scenario_or_404(db, scenario_id)
+    baseline = await current_baseline(db, scenario_id)
+    if not baseline:
+        raise HTTPException(status_code=404, detail="Scenario has no baseline")
+    return baseline
+
+
+@router.get("/scenario/{scenario_id}/history", response_model=List[ScenarioBaselineResponse])
+async def get_scenario_baseline_history(scenario_id: str, db: AsyncSession = Depends(get_db)):
+    """List a scenario's baselines, newest first"""
+    await _get_scenario_or_404(db, scenario_id)
+    return await baseline_history(db, scenario_id)
+
+
+@router.post("/scenario/{scenario_id}", response_model=ScenarioBaselineResponse)
+async def approve_scenario_baseline(
+    scenario_id: str, data: ScenarioBaselineCreate, db: AsyncSession = Depends(get_db)
+):
+    """Approve a test run as the scenario's new baseline"""
+    await _get_scenario_or_404(db, scenario_id)
+    try:
+        return await approve_baseline(db, scenario_id, data.test_run_id, data.note)
+    except BaselineError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.delete("/scenario/{scenario_id}")
+async def clear_scenario_baseline(scenario_id: str, db: AsyncSession = Depends(get_db)):
+    """Stop comparing a scenario's runs against a baseline"""
+    await _get_scenario_or_404(db, scenario_id)
+    if not await clear_baseline(db, scenario_id):
+        raise HTTPException(status_code=404, detail="Scenario has no baseline")
+    return {"status": "deleted"}
+
+
+@router.get("/compare/{test_run_id}", response_model=List[BaselineComparison])
+async def compare_run_with_baselines(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Compare each scenario in a test run against its baseline"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return await compare_with_baselines(db, test_run)
@@ -0,0 +1,22 @@
+from typing import List
+
+from fastapi import APIRouter, HTTPException
+
+from app.services.browsers import BrowserError, BrowserInfo, detect_browsers, install_managed_chromium
+
+router = APIRouter(prefix="/browsers", tags=["browsers"])
+
+
+@router.get("", response_model=List[BrowserInfo])
+async def list_browsers():
+    """List the browsers installed for web tests, with their paths and versions"""
+    return await detect_browsers()
+
+
+@router.post("/install", response_model=BrowserInfo)
+async def install_chromium():
+    """Download a managed Chromium for web tests, for machines without a usable browser"""
+    try:
+        return await install_managed_chromium()
+    except BrowserError as e:
+        raise HTTPException(status_code=500, detail=str(e))
@@ -0,0 +1,108 @@
+from typing import Any, Optional
+
+from fastapi import APIRouter, Body, Depends, HTTPException, Query, Response
+from pydantic import BaseModel
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import RunBreakdown, RunLogPage, TestRun, TestRunResponse
+from app.routers.executions import ExecuteScenarioRequest, cancel_execution, execute_scenario
+from app.routers.suites import RunSuiteRequest, run_suite
+from app.services.app_settings import get_setting
+from app.services.control_api import (
+    get_control_token,
+    require_control_token,
+    require_main_process,
+    rotate_control_token,
+)
+from app.services.mcp_server import handle_message
+from app.services.run_logs import query_run_logs
+from app.services.run_status import get_run_breakdown
+
+router = APIRouter(prefix="/control", tags=["control"])
+
+# Endpoints for external tools; each call needs "Authorization: Bearer <token>"
+protected = APIRouter(dependencies=[Depends(require_control_token)])
+
+
+class ControlTokenResponse(BaseModel):
+    enabled: bool
+    token: str
+
+
+@router.get("/token", response_model=ControlTokenResponse, dependencies=[Depends(require_main_process)])
+async def read_control_token(db: AsyncSession = Depends(get_db)):
+    """Get the control API token to configure an external tool; only the desktop app's main process may"""
+    return ControlTokenResponse(enabled=await get_setting(db, "control_api.enabled"), token=get_control_token())
+
+
+@router.post("/token/rotate", response_model=ControlTokenResponse, dependencies=[Depends(require_main_process)])
+async def rotate_token(db: AsyncSession = Depends(get_db)):
+    """Issue a new control API token, revoking the current one; only the desktop app's main process may"""
+    return ControlTokenResponse(enabled=await get_setting(db, "control_api.enabled"), token=rotate_control_token())
+
+
+@protected.post("/runs/scenario/{scenario_id}", response_model=TestRunResponse)
+async def control_run_scenario(
+    scenario_id: str, data: ExecuteScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Trigger a scenario run on a device"""
+    return await execute_scenario(scenario_id, data, db)
+
+
+@protected.post("/runs/suite/{suite_id}", response_model=TestRunResponse)
+async def control_run_suite(suite_id: str, data: RunSuiteRequest, db: AsyncSession = Depends(get_db)):
+    """Trigger a suite run on a device or in a browser"""
+    return await run_suite(suite_id, data, db)
+
+
+@protected.get("/runs/{test_run_id}", response_model=TestRunResponse)
+async def control_run_status(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a run's status and counts"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return test_run
+
+
+@protected.get("/runs/{test_run_id}/report", response_model=RunBreakdown)
+async def control_run_report(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a run's detailed results"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return await get_run_breakdown(db, test_run)
+
+
+@protected.get("/runs/{test_run_id}/logs", response_model=RunLogPage)
+async def control_run_logs(
+    test_run_id: str,
+    level: Optional[str] = Query(None),
+    offset: int = Query(0, ge=0),
+    limit: int = Query(200, ge=1, le=1000),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get a page of a run's console log"""
+    try:
+        return await query_run_logs(db, test_run_id, level=level, offset=offset, limit=limit)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@protected.post("/runs/{test_run_id}/cancel")
+async def control_cancel_run(test_run_id: str):
+    """Cancel a running scenario or suite run"""
+    return await cancel_execution(test_run_id)
+
+
+@protected.post("/mcp")
+async def control_mcp(message: Any = Body(...)):
+    """Model Context Protocol endpoint for agents that connect over HTTP"""
+    if isinstance(message, list):
+        responses = [r for r in [await handle_message(m) for m in message] if r is not None]
+        return responses or Response(status_code=202)
+    response = await handle_message(message)
+    return response if response is not None else Response(status_code=202)
+
+
+router.include_router(protected)
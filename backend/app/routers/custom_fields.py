@@ -0,0 +1,157 @@
+import json
+from typing import Any, Dict, List
+
+from fastapi import APIRouter, Depends, HTTPException, Query
+from sqlalchemy import delete, select
+from sqlalchemy.exc import IntegrityError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    CustomField,
+    CustomFieldCreate,
+    CustomFieldUpdate,
+    CustomFieldResponse,
+    CustomFieldValue,
+    CustomFieldValues,
+    Project,
+    TestCase,
+)
+from app.services.custom_fields import (
+    CustomFieldError,
+    check_field,
+    coerce_value,
+    get_values,
+    list_fields,
+    set_values,
+)
+
+router = APIRouter(prefix="/custom-fields", tags=["custom-fields"])
+
+
+async def get_custom_field_or_404(db: AsyncSession, field_id: str) -> CustomField:
+    field = await db.get(CustomField, field_id)
+    if not field:
+        raise HTTPException(status_code=404, detail="Custom field not found")
+    return field
+
+
+async def entity_project_id(db: AsyncSession, entity: str, entity_id: str) -> str:
+    """The project whose fields apply to a test case or project"""
+    if entity == "project":
+        if not await db.get(Project, entity_id):
+            raise HTTPException(status_code=404, detail="Project not found")
+        return entity_id
+    if entity == "test_case":
+        test_case = await db.get(TestCase, entity_id)
+        if not test_case:
+            raise HTTPException(status_code=404, detail="Test case not found")
+        return test_case.project_id
+    raise HTTPException(status_code=400, detail="Entity must be test_case or project")
+
+
+@router.post("", response_model=CustomFieldResponse)
+async def create_custom_field(data: CustomFieldCreate, db: AsyncSession = Depends(get_db)):
+    """Define a custom field on a project's test cases or on the project itself"""
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    try:
+        check_field(data.entity, data.field_type, data.allowed_values)
+    except CustomFieldError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    field = CustomField(
+        project_id=data.project_id,
+        entity=data.entity,
+        name=data.name.strip(),
+        field_type=data.field_type,
+        allowed_values=json.dumps(data.allowed_values),
+        required=data.required,
+    )
+    db.add(field)
+    try:
+        await db.commit()
+    except IntegrityError:
+        await db.rollback()
+        raise HTTPException(status_code=400, detail=f"The project already has a {data.name} field")
+    await db.refresh(field)
+    return field
+
+
+@router.get("/project/{project_id}", response_model=List[CustomFieldResponse])
+async def list_custom_fields(
+    project_id: str, entity: str = Query("test_case"), db: AsyncSession = Depends(get_db)
+):
+    """List a project's custom fields for test cases or for the project"""
+    return await list_fields(db, project_id, entity)
+
+
+@router.get("/project/{project_id}/values", response_model=List[CustomFieldValues])
+async def list_project_test_case_values(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Get the custom field values of every test case in a project"""
+    result = await db.execute(select(TestCase.id).where(TestCase.project_id == project_id))
+    values = await get_values(db, project_id, "test_case", result.scalars().all())
+    return [CustomFieldValues(entity_id=entity_id, values=fields) for entity_id, fields in values.items()]
+
+
+@router.put("/{field_id}", response_model=CustomFieldResponse)
+async def update_custom_field(field_id: str, data: CustomFieldUpdate, db: AsyncSession = Depends(get_db)):
+    """Rename a custom field or change its allowed values; existing values must still be allowed"""
+    field = await get_custom_field_or_404(db, field_id)
+    update_data = data.model_dump(exclude_unset=True)
+    if "allowed_values" in update_data:
+        try:
+            check_field(field.entity, field.field_type, update_data["allowed_values"])
+        except CustomFieldError as e:
+            raise HTTPException(status_code=400, detail=str(e))
+        field.allowed_values = json.dumps(update_data.pop("allowed_values"))
+        result = await db.execute(select(CustomFieldValue.value).where(CustomFieldValue.field_id == field.id))
+        for value in result.scalars().all():
+            try:
+                coerce_value(field, json.loads(value))
+            except CustomFieldError as e:
+                raise HTTPException(status_code=400, detail=f"Values in use would no longer be allowed: {e}")
+    if update_data.get("name") is not None:
+        update_data["name"] = update_data["name"].strip()
+    for key, value in update_data.items():
+        setattr(field, key, value)
+
+    try:
+        await db.commit()
+    except IntegrityError:
+        await db.rollback()
+        raise HTTPException(status_code=400, detail=f"The project already has a {data.name} field")
+    await db.refresh(field)
+    return field
+
+
+@router.delete("/{field_id}")
+async def delete_custom_field(field_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a custom field and its values"""
+    field = await get_custom_field_or_404(db, field_id)
+    await db.execute(delete(CustomFieldValue).where(CustomFieldValue.field_id == field.id))
+    await db.delete(field)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.get("/values/{entity}/{entity_id}", response_model=CustomFieldValues)
+async def get_custom_field_values(entity: str, entity_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a test case's or project's custom field values by field name"""
+    project_id = await entity_project_id(db, entity, entity_id)
+    values = await get_values(db, project_id, entity, [entity_id])
+    return CustomFieldValues(entity_id=entity_id, values=values[entity_id])
+
+
+@router.put("/values/{entity}/{entity_id}", response_model=CustomFieldValues)
+async def set_custom_field_values(
+    entity: str, entity_id: str, data: Dict[str, Any], db: AsyncSession = Depends(get_db)
+):
+    """Set some of a test case's or project's custom field values; empty values clear a field"""
+    project_id = await entity_project_id(db, entity, entity_id)
+    try:
+        values = await set_values(db, project_id, entity, entity_id, data)
+    except CustomFieldError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    await db.commit()
+    return CustomFieldValues(entity_id=entity_id, values=values)
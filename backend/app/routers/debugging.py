@@ -0,0 +1,106 @@
+from typing import Any, Dict, Optional
+
+from fastapi import APIRouter, HTTPException
+from pydantic import BaseModel
+
+from app.services.debugging import DebugError, DebugState, debug_sessions
+from app.services.executor import StepExecutionError
+
+router = APIRouter(prefix="/debug", tags=["debug"])
+
+
+class ContinueRequest(BaseModel):
+    """Schema for resuming or stepping over, optionally with an edited config for the paused step"""
+
+    config: Optional[Dict[str, Any]] = None
+
+
+class TryStepRequest(BaseModel):
+    """Schema for running a step on the paused device"""
+
+    step_type: str
+    config: Dict[str, Any] = {}
+
+
+class TryStepResponse(BaseModel):
+    status: str
+    duration_ms: int
+    error_message: Optional[str] = None
+    details: Optional[Dict[str, Any]] = None
+
+
+class DeviceSnapshot(BaseModel):
+    screenshot: str  # Base64 PNG
+    ui_dump: str
+
+
+def _session_error(e: Exception) -> HTTPException:
+    if isinstance(e, KeyError):
+        return HTTPException(status_code=404, detail="No debug run in progress for this test run")
+    return HTTPException(status_code=409, detail=str(e))
+
+
+@router.get("/{test_run_id}", response_model=DebugState)
+async def get_debug_state(test_run_id: str):
+    """Get where a debug run is paused"""
+    try:
+        return debug_sessions.get(test_run_id).state()
+    except KeyError as e:
+        raise _session_error(e)
+
+
+@router.get("/{test_run_id}/device", response_model=DeviceSnapshot)
+async def inspect_device(test_run_id: str):
+    """Get the paused device's live screenshot and UI dump"""
+    try:
+        screenshot, ui_dump = await debug_sessions.inspect(test_run_id)
+    except (KeyError, DebugError) as e:
+        raise _session_error(e)
+    except StepExecutionError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return DeviceSnapshot(screenshot=screenshot, ui_dump=ui_dump)
+
+
+@router.post("/{test_run_id}/try-step", response_model=TryStepResponse)
+async def try_step(test_run_id: str, data: TryStepRequest):
+    """Run a step on the paused device without recording it, to try out a config"""
+    try:
+        outcome = await debug_sessions.try_step(test_run_id, data.step_type, data.config)
+    except (KeyError, DebugError) as e:
+        raise _session_error(e)
+    return TryStepResponse(
+        status=outcome.status,
+        duration_ms=outcome.duration_ms,
+        error_message=outcome.error_message,
+        details=outcome.details,
+    )
+
+
+@router.post("/{test_run_id}/resume", response_model=DebugState)
+async def resume_run(test_run_id: str, data: ContinueRequest):
+    """Run the paused step and carry on to the next breakpoint"""
+    try:
+        debug_sessions.resume(test_run_id, data.config)
+        return debug_sessions.get(test_run_id).state()
+    except (KeyError, DebugError) as e:
+        raise _session_error(e)
+
+
+@router.post("/{test_run_id}/step-over", response_model=DebugState)
+async def step_over(test_run_id: str, data: ContinueRequest):
+    """Run the paused step and pause again before the next one"""
+    try:
+        debug_sessions.step_over(test_run_id, data.config)
+        return debug_sessions.get(test_run_id).state()
+    except (KeyError, DebugError) as e:
+        raise _session_error(e)
+
+
+@router.post("/{test_run_id}/abort")
+async def abort_debug_run(test_run_id: str):
+    """Cancel a debug run"""
+    try:
+        await debug_sessions.abort(test_run_id)
+    except KeyError as e:
+        raise _session_error(e)
+    return {"status": "cancelling"}
@@ -0,0 +1,52 @@
+from datetime import datetime, timedelta
+from typing import List, Optional
+
+from fastapi import APIRouter, Query
+
+from app.services.crash_logs import DeviceCrash, list_crashes_since
+from app.services.doctor import DoctorReport, run_environment_doctor
+from app.services.tools import ToolInfo, detect_tools
+from app.services.tracing import TraceSpan, tracer
+
+router = APIRouter(prefix="/doctor", tags=["doctor"])
+
+
+@router.get("", response_model=DoctorReport)
+async def get_environment_report():
+    """Check this machine for the tools, API keys, ports and storage the app needs"""
+    return await run_environment_doctor()
+
+
+@router.get("/tools", response_model=List[ToolInfo])
+async def get_tools():
+    """Report where adb, xcrun, node and the other tools were found, with their versions"""
+    return await detect_tools()
+
+
+@router.get("/traces", response_model=List[TraceSpan])
+async def get_recent_traces(
+    limit: int = Query(200, ge=1, le=2000),
+    kind: Optional[str] = None,
+    min_duration_ms: float = 0,
+):
+    """Get the newest recorded trace spans, newest first; empty unless tracing.enabled is on"""
+    return tracer.recent(limit, kind, min_duration_ms)
+
+
+@router.delete("/traces")
+async def clear_recent_traces():
+    """Forget the trace spans kept in memory; the trace file is kept"""
+    tracer.clear()
+    return {"status": "deleted"}
+
+
+@router.get("/crashes", response_model=List[DeviceCrash])
+async def get_device_crashes(
+    device_id: str,
+    platform: str = "android",
+    since: Optional[datetime] = None,
+):
+    """Get the crash reports a device wrote since a UTC time, by default in the last hour"""
+    if since and since.tzinfo:
+        since = (since - since.utcoffset()).replace(tzinfo=None)
+    return await list_crashes_since(since or datetime.utcnow() - timedelta(hours=1), device_id, platform)
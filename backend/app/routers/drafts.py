@@ -0,0 +1,46 @@
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException, Query
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import DraftResponse, DraftSave
+from app.services.drafts import DraftError, DraftNotFound, discard_draft, get_draft, list_drafts, save_draft
+
+router = APIRouter(prefix="/drafts", tags=["drafts"])
+
+
+@router.get("", response_model=List[DraftResponse])
+async def list_all_drafts(entity: Optional[str] = Query(None), db: AsyncSession = Depends(get_db)):
+    """List drafts to recover, newest first"""
+    return await list_drafts(db, entity)
+
+
+@router.get("/{entity}/{entity_id}", response_model=DraftResponse)
+async def get_entity_draft(entity: str, entity_id: str, db: AsyncSession = Depends(get_db)):
+    """Get an entity's draft; conflict is set when the entity was saved after the draft's base version"""
+    try:
+        return await get_draft(db, entity, entity_id)
+    except DraftNotFound as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    except DraftError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.put("/{entity}/{entity_id}", response_model=DraftResponse)
+async def autosave_draft(entity: str, entity_id: str, data: DraftSave, db: AsyncSession = Depends(get_db)):
+    """Autosave an entity's unsaved edits"""
+    try:
+        return await save_draft(db, entity, entity_id, data)
+    except DraftNotFound as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    except DraftError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.delete("/{entity}/{entity_id}")
+async def discard_entity_draft(entity: str, entity_id: str, db: AsyncSession = Depends(get_db)):
+    """Discard an entity's draft, after its edits are saved or abandoned"""
+    if not await discard_draft(db, entity, entity_id):
+        raise HTTPException(status_code=404, detail="No draft for this entity")
+    return {"status": "discarded"}
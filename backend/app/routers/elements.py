@@ -0,0 +1,123 @@
+import json
+from typing import List
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    Element,
+    ElementCreate,
+    ElementUpdate,
+    ElementResponse,
+    ElementUsage,
+    Project,
+)
+from app.services.elements import find_element_usages, rename_element_references
+
+router = APIRouter(prefix="/elements", tags=["elements"])
+
+
+async def get_element_or_404(db: AsyncSession, element_id: str) -> Element:
+    result = await db.execute(select(Element).where(Element.id == element_id))
+    element = result.scalar_one_or_none()
+    if not element:
+        raise HTTPException(status_code=404, detail="Element not found")
+    return element
+
+
+async def ensure_name_available(db: AsyncSession, project_id: str, name: str) -> None:
+    result = await db.execute(
+        select(Element.id).where(Element.project_id == project_id, Element.name == name)
+    )
+    if result.scalar_one_or_none():
+        raise HTTPException(status_code=409, detail=f"Element '{name}' already exists")
+
+
+@router.post("", response_model=ElementResponse)
+async def create_element(data: ElementCreate, db: AsyncSession = Depends(get_db)):
+    """Create a new element"""
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    await ensure_name_available(db, data.project_id, data.name)
+
+    values = data.model_dump()
+    element = Element(
+        **{**values, "frame_path": json.dumps(data.frame_path), "shadow_path": json.dumps(data.shadow_path)}
+    )
+    db.add(element)
+    await db.commit()
+    await db.refresh(element)
+    return element
+
+
+@router.get("/project/{project_id}", response_model=List[ElementResponse])
+async def list_elements_by_project(project_id: str, db: AsyncSession = Depends(get_db)):
+    """List all elements for a project"""
+    result = await db.execute(
+        select(Element).where(Element.project_id == project_id).order_by(Element.name)
+    )
+    return result.scalars().all()
+
+
+@router.get("/{element_id}", response_model=ElementResponse)
+async def get_element(element_id: str, db: AsyncSession = Depends(get_db)):
+    """Get an element by ID"""
+    return await get_element_or_404(db, element_id)
+
+
+@router.put("/{element_id}", response_model=ElementResponse)
+async def update_element(
+    element_id: str, data: ElementUpdate, db: AsyncSession = Depends(get_db)
+):
+    """Update an element; renaming it updates the steps that reference it"""
+    element = await get_element_or_404(db, element_id)
+
+    update_data = data.model_dump(exclude_unset=True)
+    new_name = update_data.get("name")
+    if new_name and new_name != element.name:
+        await ensure_name_available(db, element.project_id, new_name)
+        await rename_element_references(db, element, new_name)
+
+    for key in ("frame_path", "shadow_path"):
+        if key in update_data:
+            update_data[key] = json.dumps(update_data[key] or [])
+    for key, value in update_data.items():
+        setattr(element, key, value)
+
+    await db.commit()
+    await db.refresh(element)
+    return element
+
+
+@router.delete("/{element_id}")
+async def delete_element(element_id: str, force: bool = False, db: AsyncSession = Depends(get_db)):
+    """Delete an element, refusing while steps still reference it unless forced"""
+    element = await get_element_or_404(db, element_id)
+
+    usages = await find_element_usages(db, element)
+    if usages and not force:
+        raise HTTPException(
+            status_code=409, detail=f"Element is used by {len(usages)} step(s)"
+        )
+
+    await db.delete(element)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.get("/{element_id}/usages", response_model=List[ElementUsage])
+async def get_element_usages(element_id: str, db: AsyncSession = Depends(get_db)):
+    """List the steps that reference an element"""
+    element = await get_element_or_404(db, element_id)
+    return [
+        ElementUsage(
+            step_id=step.id,
+            step_label=step.label,
+            scenario_id=scenario.id,
+            scenario_name=scenario.name,
+            test_case_id=scenario.test_case_id,
+        )
+        for step, scenario in await find_element_usages(db, element)
+    ]
@@ -0,0 +1,68 @@
+from typing import Any, Dict, List, Optional
+
+from fastapi import APIRouter, HTTPException, Query, WebSocket, WebSocketDisconnect
+from pydantic import BaseModel
+
+from app.services.db_changes import DB_CHANGED_EVENT
+from app.services.events import event_bus
+
+router = APIRouter(prefix="/events", tags=["events"])
+
+
+class WindowResponse(BaseModel):
+    """Schema for a window connected to the event stream"""
+    window_id: str
+    filters: Dict[str, str]
+
+
+class WindowEvent(BaseModel):
+    """Schema for an event sent to one window"""
+    event: str
+    payload: Any = None
+
+
+@router.websocket("/ws")
+async def events_websocket(
+    websocket: WebSocket,
+    db_data: bool = Query(False),
+    window_id: Optional[str] = Query(None),
+    test_run_id: Optional[str] = Query(None),
+    device_id: Optional[str] = Query(None),
+):
+    """
+    Stream backend events to the client
+
+    Windows pass their window_id to receive events sent to them, and
+    test_run_id or device_id to hear only about that run or device.
+    db:changed events carry row data only when db_data is set.
+    """
+    await websocket.accept()
+    queue = event_bus.subscribe(window_id, {"test_run_id": test_run_id, "device_id": device_id})
+    try:
+        while True:
+            message = await queue.get()
+            if message["event"] == DB_CHANGED_EVENT and not db_data:
+                message = {**message, "payload": {**message["payload"], "data": None}}
+            await websocket.send_json(message)
+    except WebSocketDisconnect:
+        pass
+    finally:
+        event_bus.unsubscribe(queue)
+
+
+@router.get("/windows", response_model=List[WindowResponse])
+async def list_windows():
+    """List the windows connected to the event stream"""
+    return [
+        WindowResponse(window_id=subscription.window_id, filters=subscription.filters)
+        for subscription in event_bus.windows()
+    ]
+
+
+@router.post("/windows/{window_id}")
+async def send_window_event(window_id: str, data: WindowEvent):
+    """Send an event to one window, e.g. to show a step in a detached run viewer"""
+    if not any(subscription.window_id == window_id for subscription in event_bus.windows()):
+        raise HTTPException(status_code=404, detail="Window not connected")
+    event_bus.publish(data.event, data.payload, window_id=window_id)
+    return {"status": "sent"}
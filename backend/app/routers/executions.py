@@ -0,0 +1,296 @@
+import uuid
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    Project,
+    Scenario,
+    Step,
+    StepResponse,
+    StepResult,
+    TestCase,
+    TestRun,
+    TestRunResponse,
+)
+from app.routers.queue import check_priority
+from app.services.browsers import BrowserError, select_viewports
+from app.services.dependencies import DependencyError, execution_order, load_prerequisites
+from app.services.executor import (
+    ScenarioExecutor,
+    StepExecutionError,
+    cancel_scenario_run,
+    start_executor_run,
+    start_scenario_run,
+    start_scenarios_run,
+)
+from app.services.reruns import LatestScenarioStatus, RerunError, RerunRequest, latest_status, rerun_failures
+from app.services.test_runner import TestFramework
+from app.services.web_executor import WebScenarioExecutor
+
+router = APIRouter(prefix="/executions", tags=["executions"])
+
+
+class ExecuteScenarioRequest(BaseModel):
+    """Schema for running a scenario on a mobile device"""
+
+    device_id: str
+    platform: str  # 'android' | 'ios'
+    test_run_id: Optional[str] = None
+    self_heal: bool = True
+    priority: str = "normal"  # 'high' | 'normal' | 'low'
+    debug: bool = False  # Pause at step breakpoints
+
+
+class ExecuteWebScenarioRequest(BaseModel):
+    """Schema for running a scenario in a browser, optionally across the project's viewports"""
+
+    framework: TestFramework = TestFramework.PLAYWRIGHT
+    browser: Optional[str] = None  # Empty uses the project's default browser
+    headless: Optional[bool] = None
+    viewports: Optional[List[str]] = None  # Empty runs every project viewport, None runs once
+    priority: str = "normal"  # 'high' | 'normal' | 'low'
+
+
+class ExecuteOrderedRequest(BaseModel):
+    """Schema for running several scenarios in dependency order as one test run"""
+
+    scenario_ids: List[str]
+    device_id: str
+    platform: str  # 'android' | 'ios'
+    name: Optional[str] = None
+    self_heal: bool = True
+    priority: str = "normal"  # 'high' | 'normal' | 'low'
+    debug: bool = False  # Pause at step breakpoints
+
+
+class OrderedExecutionResponse(BaseModel):
+    test_run: TestRunResponse
+    order: List[str]
+
+
+class ScenarioOutcome(BaseModel):
+    """Dependency-aware result of one scenario within a test run"""
+
+    scenario_id: str
+    name: str
+    status: str  # 'passed' | 'failed' | 'skipped'
+    passed: int
+    failed: int
+    skipped: int
+    skip_reason: Optional[str] = None
+
+
+class ResolveLocatorsRequest(BaseModel):
+    """Schema for resolving a scenario's locators on a mobile device"""
+
+    device_id: str
+    platform: str  # 'android' | 'ios'
+
+
+class ResolveLocatorsResponse(BaseModel):
+    """Schema for the result of resolving a scenario's locators"""
+
+    resolved_step_ids: List[str]
+    unresolved_step_ids: List[str]
+    screens: int
+    steps: List[StepResponse]
+
+
+@router.post("/scenario/{scenario_id}", response_model=TestRunResponse)
+async def execute_scenario(
+    scenario_id: str, data: ExecuteScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Queue a scenario to run on a device in the background"""
+    check_priority(data.priority)
+    result = await db.execute(select(Scenario).where(Scenario.id == scenario_id))
+    scenario = result.scalar_one_or_none()
+    if not scenario:
+        raise HTTPException(status_code=404, detail="Scenario not found")
+
+    test_case = await db.get(TestCase, scenario.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+
+    if data.test_run_id:
+        result = await db.execute(select(TestRun).where(TestRun.id == data.test_run_id))
+        test_run = result.scalar_one_or_none()
+        if not test_run:
+            raise HTTPException(status_code=404, detail="Test run not found")
+    else:
+        test_run = TestRun(
+            id=str(uuid.uuid4()),
+            project_id=test_case.project_id,
+            name=scenario.name,
+        )
+        db.add(test_run)
+        await db.commit()
+        await db.refresh(test_run)
+
+    start_scenario_run(
+        scenario_id,
+        test_run.id,
+        data.device_id,
+        data.platform,
+        self_heal=data.self_heal,
+        ai_free=project.ai_free if project else False,
+        priority=data.priority,
+        debug=data.debug,
+    )
+    return test_run
+
+
+@router.post("/web/scenario/{scenario_id}", response_model=TestRunResponse)
+async def execute_web_scenario(
+    scenario_id: str, data: ExecuteWebScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Queue a scenario to run in a browser, once per selected viewport, as one test run"""
+    check_priority(data.priority)
+    scenario = await db.get(Scenario, scenario_id)
+    if not scenario:
+        raise HTTPException(status_code=404, detail="Scenario not found")
+    test_case = await db.get(TestCase, scenario.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+    try:
+        select_viewports(project, data.viewports)
+    except BrowserError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    test_run = TestRun(id=str(uuid.uuid4()), project_id=project.id, name=scenario.name)
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    executor = WebScenarioExecutor(data.framework, data.browser, data.headless, data.viewports)
+    start_executor_run(executor, [scenario_id], test_run.id, priority=data.priority)
+    return test_run
+
+
+@router.post("/ordered", response_model=OrderedExecutionResponse)
+async def execute_scenarios_in_order(data: ExecuteOrderedRequest, db: AsyncSession = Depends(get_db)):
+    """Run scenarios and their prerequisites in dependency order, skipping dependents of failures"""
+    if not data.scenario_ids:
+        raise HTTPException(status_code=400, detail="No scenarios to run")
+    check_priority(data.priority)
+    try:
+        order = await execution_order(db, data.scenario_ids)
+    except DependencyError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    first = await db.get(Scenario, order[0])
+    test_case = await db.get(TestCase, first.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+
+    test_run = TestRun(
+        id=str(uuid.uuid4()),
+        project_id=project.id,
+        name=data.name or f"{len(order)} scenarios",
+    )
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    start_scenarios_run(
+        order,
+        test_run.id,
+        data.device_id,
+        data.platform,
+        self_heal=data.self_heal,
+        ai_free=project.ai_free,
+        prerequisites=await load_prerequisites(db),
+        priority=data.priority,
+        debug=data.debug,
+    )
+    return OrderedExecutionResponse(test_run=test_run, order=order)
+
+
+@router.get("/{test_run_id}/scenarios", response_model=List[ScenarioOutcome])
+async def get_scenario_outcomes(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get each scenario's outcome within a test run"""
+    result = await db.execute(
+        select(StepResult, Scenario)
+        .join(Step, StepResult.step_id == Step.id)
+        .join(Scenario, Step.scenario_id == Scenario.id)
+        .where(StepResult.test_run_id == test_run_id)
+        .order_by(StepResult.created_at)
+    )
+
+    outcomes = {}
+    for step_result, scenario in result.all():
+        outcome = outcomes.setdefault(
+            scenario.id,
+            ScenarioOutcome(
+                scenario_id=scenario.id, name=scenario.name, status="passed", passed=0, failed=0, skipped=0
+            ),
+        )
+        if step_result.status == "passed":
+            outcome.passed += 1
+        elif step_result.status == "skipped":
+            outcome.skipped += 1
+            outcome.skip_reason = step_result.error_message
+        else:
+            outcome.failed += 1
+
+    for outcome in outcomes.values():
+        if outcome.failed:
+            outcome.status = "failed"
+        elif outcome.skipped and not outcome.passed:
+            outcome.status = "skipped"
+    return list(outcomes.values())
+
+
+@router.post("/{test_run_id}/rerun-failures", response_model=TestRunResponse)
+async def rerun_failed_scenarios(test_run_id: str, data: RerunRequest, db: AsyncSession = Depends(get_db)):
+    """Queue a new test run of only the scenarios that failed in a finished run"""
+    check_priority(data.priority)
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    try:
+        return await rerun_failures(db, test_run_id, data)
+    except RerunError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.get("/{test_run_id}/latest-status", response_model=List[LatestScenarioStatus])
+async def get_latest_status(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get each scenario's latest outcome across a test run and its reruns"""
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return await latest_status(db, test_run_id)
+
+
+@router.post("/{test_run_id}/cancel")
+async def cancel_execution(test_run_id: str):
+    """Cancel a scenario run that is queued or executing in the background"""
+    if not await cancel_scenario_run(test_run_id):
+        raise HTTPException(status_code=404, detail="No active run for this test run")
+    return {"status": "cancelling"}
+
+
+@router.post("/scenario/{scenario_id}/resolve-locators", response_model=ResolveLocatorsResponse)
+async def resolve_scenario_locators(
+    scenario_id: str, data: ResolveLocatorsRequest, db: AsyncSession = Depends(get_db)
+):
+    """Resolve element descriptions to coordinates with one AI call per screen"""
+    result = await db.execute(select(Scenario).where(Scenario.id == scenario_id))
+    if not result.scalar_one_or_none():
+        raise HTTPException(status_code=404, detail="Scenario not found")
+
+    executor = ScenarioExecutor(data.device_id, data.platform)
+    try:
+        resolution = await executor.resolve_locators(scenario_id)
+    except StepExecutionError as e:
+        raise HTTPException(status_code=500, detail=str(e))
+
+    steps_result = await db.execute(
+        select(Step).where(Step.scenario_id == scenario_id).order_by(Step.step_order)
+    )
+    return ResolveLocatorsResponse(
+        resolved_step_ids=resolution.resolved_step_ids,
+        unresolved_step_ids=resolution.unresolved_step_ids,
+        screens=resolution.screens,
+        steps=[StepResponse.model_validate(step) for step in steps_result.scalars().all()],
+    )
@@ -1,9 +1,11 @@
 from typing import List, Optional
 
 import httpx
-from fastapi import APIRouter, HTTPException
+from fastapi import APIRouter
 from pydantic import BaseModel
 
+from app.errors import CommandError, ErrorCode
+
 router = APIRouter(prefix="/integrations", tags=["integrations"])
 
 
@@ -116,7 +118,7 @@ async def get_jira_issue(issue_key: str, credentials: JiraCredentials):
                 labels=data["fields"].get("labels", []),
             )
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"Jira API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"Jira API error: {str(e)}")
 
 
 @router.post("/jira/issue", response_model=JiraIssue)
@@ -155,7 +157,7 @@ async def create_jira_issue(request: CreateJiraIssueRequest):
             # Fetch the created issue
             return await get_jira_issue(data["key"], request.credentials)
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"Jira API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"Jira API error: {str(e)}")
 
 
 @router.post("/jira/search")
@@ -189,7 +191,7 @@ async def search_jira_issues(request: SearchJiraRequest):
 
             return {"issues": issues, "total": data.get("total", 0)}
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"Jira API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"Jira API error: {str(e)}")
 
 
 # ============================================
@@ -223,7 +225,7 @@ async def get_github_issue(issue_number: int, credentials: GitHubCredentials):
                 html_url=data["html_url"],
             )
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"GitHub API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"GitHub API error: {str(e)}")
 
 
 @router.post("/github/issue", response_model=GitHubIssue)
@@ -262,7 +264,7 @@ async def create_github_issue(request: CreateGitHubIssueRequest):
                 html_url=data["html_url"],
             )
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"GitHub API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"GitHub API error: {str(e)}")
 
 
 @router.post("/github/issues", response_model=List[GitHubIssue])
@@ -304,7 +306,7 @@ async def list_github_issues(
                 if "pull_request" not in item  # Exclude PRs
             ]
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"GitHub API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"GitHub API error: {str(e)}")
 
 
 @router.post("/github/pr/{pr_number}", response_model=GitHubPullRequest)
@@ -334,4 +336,4 @@ async def get_github_pull_request(pr_number: int, credentials: GitHubCredentials
                 merged=data.get("merged", False),
             )
     except httpx.HTTPError as e:
-        raise HTTPException(status_code=502, detail=f"GitHub API error: {str(e)}")
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, f"GitHub API error: {str(e)}")
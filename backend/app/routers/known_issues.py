@@ -0,0 +1,105 @@
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.config import settings
+from app.db import get_db
+from app.models import (
+    KnownIssue,
+    KnownIssueCreate,
+    KnownIssueUpdate,
+    KnownIssueResponse,
+    TestCase,
+)
+
+router = APIRouter(prefix="/known-issues", tags=["known-issues"])
+
+TRACKERS = ("jira", "github")
+
+
+def issue_url_for(tracker: str, issue_key: str) -> Optional[str]:
+    """Build a link to a Jira key (PROJ-123) or GitHub reference (owner/repo#123)"""
+    if tracker == "jira" and settings.jira_base_url:
+        return f"{settings.jira_base_url.rstrip('/')}/browse/{issue_key}"
+    if tracker == "github" and "#" in issue_key:
+        repo, number = issue_key.split("#", 1)
+        return f"https://github.com/{repo}/issues/{number}"
+    return None
+
+
+async def get_known_issue_or_404(db: AsyncSession, issue_id: str) -> KnownIssue:
+    issue = await db.get(KnownIssue, issue_id)
+    if not issue:
+        raise HTTPException(status_code=404, detail="Known issue not found")
+    return issue
+
+
+@router.post("", response_model=KnownIssueResponse)
+async def create_known_issue(data: KnownIssueCreate, db: AsyncSession = Depends(get_db)):
+    """Mark a test case's failures as expected until the linked issue is resolved"""
+    if data.tracker not in TRACKERS:
+        raise HTTPException(status_code=400, detail=f"Tracker must be one of: {', '.join(TRACKERS)}")
+    test_case = await db.get(TestCase, data.test_case_id)
+    if not test_case:
+        raise HTTPException(status_code=404, detail="Test case not found")
+
+    issue = KnownIssue(
+        project_id=test_case.project_id,
+        test_case_id=data.test_case_id,
+        tracker=data.tracker,
+        issue_key=data.issue_key,
+        issue_url=data.issue_url or issue_url_for(data.tracker, data.issue_key),
+        reason=data.reason,
+    )
+    db.add(issue)
+    await db.commit()
+    await db.refresh(issue)
+    return issue
+
+
+@router.get("/project/{project_id}", response_model=List[KnownIssueResponse])
+async def list_known_issues(project_id: str, active_only: bool = False, db: AsyncSession = Depends(get_db)):
+    """List known issues for a project"""
+    query = select(KnownIssue).where(KnownIssue.project_id == project_id)
+    if active_only:
+        query = query.where(KnownIssue.active.is_(True))
+    result = await db.execute(query.order_by(KnownIssue.created_at.desc()))
+    return result.scalars().all()
+
+
+@router.get("/test-case/{test_case_id}", response_model=List[KnownIssueResponse])
+async def list_known_issues_by_test_case(test_case_id: str, db: AsyncSession = Depends(get_db)):
+    """List known issues for a test case"""
+    result = await db.execute(
+        select(KnownIssue)
+        .where(KnownIssue.test_case_id == test_case_id)
+        .order_by(KnownIssue.created_at.desc())
+    )
+    return result.scalars().all()
+
+
+@router.put("/{issue_id}", response_model=KnownIssueResponse)
+async def update_known_issue(issue_id: str, data: KnownIssueUpdate, db: AsyncSession = Depends(get_db)):
+    """Update a known issue; deactivate it once the issue is fixed"""
+    issue = await get_known_issue_or_404(db, issue_id)
+
+    update_data = data.model_dump(exclude_unset=True)
+    for key, value in update_data.items():
+        setattr(issue, key, value)
+    if "issue_key" in update_data and "issue_url" not in update_data:
+        issue.issue_url = issue_url_for(issue.tracker, issue.issue_key)
+
+    await db.commit()
+    await db.refresh(issue)
+    return issue
+
+
+@router.delete("/{issue_id}")
+async def delete_known_issue(issue_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a known issue"""
+    issue = await get_known_issue_or_404(db, issue_id)
+    await db.delete(issue)
+    await db.commit()
+    return {"status": "deleted"}
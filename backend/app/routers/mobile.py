@@ -3,11 +3,23 @@ import asyncio
 import base64
 import tempfile
 import os
-from typing import List, Optional
+import re
+import shlex
+import shutil
+import sys
+from typing import Any, Dict, List, Optional, Set, Tuple
 from pathlib import Path
 
-from fastapi import APIRouter, HTTPException
+from fastapi import APIRouter, Depends, HTTPException
 from pydantic import BaseModel
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.errors import CommandError, ErrorCode
+from app.services.tools import find_tool, tool_command
+from app.services.synonyms import load_synonyms
+from app.services.tracing import tracer
+from app.services.ui_dump import find_element_from_ui_dump, node_at, parse_ui_dump, suggest_locator
 
 router = APIRouter(prefix="/mobile", tags=["mobile"])
 
@@ -22,6 +34,7 @@ class DeviceInfo(BaseModel):
     name: str
     status: str
     platform: str  # 'android' | 'ios'
+    device_kind: str = "physical"  # 'physical' | 'emulator' | 'simulator'
 
 
 class ScreenshotResponse(BaseModel):
@@ -43,6 +56,8 @@ class SwipeRequest(BaseModel):
 
 class InputTextRequest(BaseModel):
     text: str
+    # Android: 'auto' | 'input_text' | 'adb_keyboard' | 'clipboard'; auto uses input_text for ASCII
+    method: str = "auto"
 
 
 class KeyEventRequest(BaseModel):
@@ -53,33 +68,431 @@ class AppRequest(BaseModel):
     package: str  # package name for Android, bundle ID for iOS
 
 
+class StartStreamRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    fps: float = 5.0
+
+
+class AvdInfo(BaseModel):
+    name: str
+    running: bool
+    device_id: Optional[str] = None  # emulator-<port> while running
+
+
+class AvdCreateRequest(BaseModel):
+    name: str
+    system_image: str  # e.g. 'system-images;android-34;google_apis;x86_64'
+    device: Optional[str] = None  # Hardware profile such as 'pixel_7'
+    force: bool = False  # Overwrite an existing AVD with the same name
+
+
+class AvdStartRequest(BaseModel):
+    port: Optional[int] = None  # Even console port, picked automatically when omitted
+    headless: bool = True
+    wipe_data: bool = False
+    cold_boot: bool = False
+    gpu: Optional[str] = None  # e.g. 'swiftshader_indirect' for machines without a GPU
+    extra_args: List[str] = []
+    wait_for_boot: bool = True
+    boot_timeout: float = 300.0
+
+
+class IosCreateDeviceRequest(BaseModel):
+    name: str
+    device_type: str  # Identifier or name, e.g. 'iPhone 15'
+    runtime: str  # Identifier or name, e.g. 'com.apple.CoreSimulator.SimRuntime.iOS-17-5'
+
+
+class InstallAppRequest(BaseModel):
+    path: str  # Path to a simulator .app bundle
+
+
+class StatusBarRequest(BaseModel):
+    """Status bar overrides; fields left unset keep their current value"""
+
+    time: Optional[str] = None  # e.g. '9:41'
+    data_network: Optional[str] = None  # 'hide' | 'wifi' | '3g' | '4g' | 'lte' | '5g' ...
+    wifi_mode: Optional[str] = None  # 'searching' | 'failed' | 'active'
+    wifi_bars: Optional[int] = None  # 0-3
+    cellular_mode: Optional[str] = None  # 'notSupported' | 'searching' | 'failed' | 'active'
+    cellular_bars: Optional[int] = None  # 0-4
+    operator_name: Optional[str] = None
+    battery_state: Optional[str] = None  # 'charging' | 'charged' | 'discharging'
+    battery_level: Optional[int] = None  # 0-100
+
+
+class DeepLinkRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    url: str
+    package: Optional[str] = None  # Android package to open the link in
+
+
+class PermissionRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    package: str  # package name for Android, bundle ID for iOS
+    permission: str  # e.g. 'CAMERA' on Android, 'photos' on iOS
+    grant: bool = True
+
+
+class NetworkRequest(BaseModel):
+    platform: str  # 'android' only; simulators use the host's network
+    airplane_mode: Optional[bool] = None
+    wifi: Optional[bool] = None
+    mobile_data: Optional[bool] = None
+
+
+class LocaleRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    locale: str  # e.g. 'de-DE'
+
+
+class DeviceTimeRequest(BaseModel):
+    platform: str  # 'android' only; simulators use the host's clock
+    time: Optional[str] = None  # ISO time
+    auto: bool = False  # Go back to network-provided time
+
+
+class PushNotificationRequest(BaseModel):
+    bundle_id: str
+    payload: Dict  # APNs payload with an 'aps' key
+
+
+class BiometricRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    match: bool = True
+    kind: str = "face"  # iOS only: 'face' | 'touch'
+    finger_id: int = 1  # Android only: the enrolled fingerprint
+
+
+class RoutePoint(BaseModel):
+    latitude: float
+    longitude: float
+    altitude: Optional[float] = None  # Android only
+    delay_ms: Optional[int] = None  # Wait before moving to the next point
+
+
+class LocationRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    latitude: Optional[float] = None
+    longitude: Optional[float] = None
+    altitude: Optional[float] = None
+    route: Optional[List[RoutePoint]] = None  # Played back in order instead of a single point
+    interval_ms: int = 1000
+
+
+class SensorRequest(BaseModel):
+    platform: str  # 'android' only
+    sensor: str  # e.g. 'acceleration', 'gyroscope', 'light', 'proximity'
+    values: List[float]
+
+
+class FileTransferRequest(BaseModel):
+    local_path: str
+    remote_path: str
+
+
+class MediaRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    paths: List[str]  # Photos or videos on this machine
+
+
+class ClipboardRequest(BaseModel):
+    platform: str  # 'android' | 'ios'
+    text: str
+
+
+class AdbConnectRequest(BaseModel):
+    address: str  # host:port, port defaults to 5555
+
+
+class AdbPairRequest(BaseModel):
+    host: str
+    port: int  # Pairing port shown under Wireless debugging, not the connect port
+    code: str  # Six-digit pairing code
+
+
+class OfflineElementLocation(BaseModel):
+    found: bool
+    x: Optional[int] = None
+    y: Optional[int] = None
+    confidence: float
+    resource_id: Optional[str] = None
+    text: Optional[str] = None
+    content_desc: Optional[str] = None
+    class_name: Optional[str] = None
+
+
+class InspectedElement(BaseModel):
+    """The UI dump node under a point of the screen, with step config fields that find it"""
+
+    found: bool
+    resource_id: Optional[str] = None
+    text: Optional[str] = None
+    content_desc: Optional[str] = None
+    class_name: Optional[str] = None
+    clickable: bool = False
+    bounds: Optional[List[int]] = None  # [left, top, right, bottom] in device pixels
+    locator: Dict[str, Any] = {}
+
+
+# ============================================
+# Device Processes
+# ============================================
+
+# Default limit for a single device command, in seconds
+DEVICE_COMMAND_TIMEOUT = 30.0
+
+# Device command processes still running, keyed by device ID
+_device_processes: Dict[str, Set[asyncio.subprocess.Process]] = {}
+_cancelled_processes: Set[asyncio.subprocess.Process] = set()
+
+
+def _kill(process: asyncio.subprocess.Process) -> None:
+    if process.returncode is None:
+        try:
+            process.kill()
+        except ProcessLookupError:
+            pass
+
+
+async def run_device_process(
+    cmd: List[str],
+    device_id: Optional[str] = None,
+    timeout: float = DEVICE_COMMAND_TIMEOUT,
+    input: Optional[bytes] = None,
+) -> Tuple[int, bytes, bytes]:
+    """
+    Run a device command without blocking, returning (returncode, stdout, stderr)
+
+    The process is killed if it exceeds the timeout, if the calling task is
+    cancelled, or if the device's operations are cancelled.
+    """
+    # Named by tool and subcommand so spans group by operation, not by arguments
+    name = " ".join([Path(cmd[0]).name] + [arg for arg in cmd[1:3] if not arg.startswith("-")][:1])
+    with tracer.span(name, "device", device_id=device_id) as span:
+        returncode, stdout, stderr = await _run_device_process(cmd, device_id, timeout, input)
+        if span:
+            span.set(returncode=returncode)
+            if returncode != 0:
+                span.fail(stderr.decode(errors="replace").strip()[:500] or f"Exited with code {returncode}")
+        return returncode, stdout, stderr
+
+
+async def _run_device_process(
+    cmd: List[str], device_id: Optional[str], timeout: float, input: Optional[bytes]
+) -> Tuple[int, bytes, bytes]:
+    process = await asyncio.create_subprocess_exec(
+        *cmd,
+        stdin=asyncio.subprocess.PIPE if input is not None else None,
+        stdout=asyncio.subprocess.PIPE,
+        stderr=asyncio.subprocess.PIPE,
+    )
+    key = device_id or ""
+    _device_processes.setdefault(key, set()).add(process)
+    try:
+        stdout, stderr = await asyncio.wait_for(process.communicate(input), timeout)
+    except asyncio.TimeoutError:
+        _kill(process)
+        await process.wait()
+        raise CommandError(
+            504, ErrorCode.TIMEOUT, f"{cmd[0]} timed out after {timeout:g}s: {' '.join(cmd[1:])}"
+        )
+    except asyncio.CancelledError:
+        _kill(process)
+        raise
+    finally:
+        _device_processes[key].discard(process)
+        if not _device_processes[key]:
+            del _device_processes[key]
+
+    if process in _cancelled_processes:
+        _cancelled_processes.discard(process)
+        raise HTTPException(status_code=409, detail="Device operation cancelled")
+    return process.returncode, stdout, stderr
+
+
+def cancel_device_processes(device_id: str) -> int:
+    """Kill every running command for a device, returning how many were killed"""
+    processes = [p for p in _device_processes.get(device_id, set()) if p.returncode is None]
+    for process in processes:
+        _cancelled_processes.add(process)
+        _kill(process)
+    return len(processes)
+
+
+@router.post("/{device_id}/cancel")
+async def cancel_device_operation(device_id: str):
+    """Cancel all running commands and scenario runs on a device"""
+    from app.services.executor import cancel_device_runs
+
+    runs = await cancel_device_runs(device_id)
+    return {"cancelled_commands": cancel_device_processes(device_id), "cancelled_runs": runs}
+
+
+async def _apply_device_state(action: str, platform: str, device_id: str, config: Dict) -> Dict:
+    from app.services.device_state import STATE_STEP_TYPES, DeviceStateError
+
+    if platform not in ("android", "ios"):
+        raise HTTPException(status_code=400, detail="Platform must be android or ios")
+    try:
+        commands = STATE_STEP_TYPES[action](platform, device_id, config)
+    except DeviceStateError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    run = run_xcrun_command if platform == "ios" else run_adb_command
+    for command in commands:
+        await run(command.args, device_id, input=command.input)
+        if command.delay:
+            await asyncio.sleep(command.delay)
+    return {"status": "ok"}
+
+
+@router.post("/{device_id}/deeplink")
+async def open_deep_link(device_id: str, request: DeepLinkRequest):
+    """Open a URL or deep link, waiting for the activity to start on Android"""
+    config = {"url": request.url, "packageName": request.package}
+    return await _apply_device_state("deeplink", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/permission")
+async def set_app_permission(device_id: str, request: PermissionRequest):
+    """Grant or revoke an app permission"""
+    config = {"packageName": request.package, "permission": request.permission, "grant": request.grant}
+    return await _apply_device_state("permission", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/network")
+async def set_network_state(device_id: str, request: NetworkRequest):
+    """Toggle airplane mode, Wi-Fi or mobile data"""
+    config = request.model_dump(include={"airplane_mode", "wifi", "mobile_data"})
+    return await _apply_device_state("network", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/locale")
+async def set_device_locale(device_id: str, request: LocaleRequest):
+    """Change the system language and region"""
+    return await _apply_device_state("locale", request.platform, device_id, {"locale": request.locale})
+
+
+@router.post("/{device_id}/time")
+async def set_device_time(device_id: str, request: DeviceTimeRequest):
+    """Set the device clock, or return it to automatic time"""
+    config = {"time": request.time, "auto": request.auto}
+    return await _apply_device_state("set_time", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/biometric")
+async def simulate_biometric(device_id: str, request: BiometricRequest):
+    """Simulate a matching or failed biometric authentication"""
+    config = request.model_dump(include={"match", "kind", "finger_id"})
+    return await _apply_device_state("biometric", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/location")
+async def set_mock_location(device_id: str, request: LocationRequest):
+    """Set a mock GPS location, or play back a route and return once it finishes"""
+    config = request.model_dump(exclude={"platform"}, exclude_none=True)
+    return await _apply_device_state("set_location", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/sensor")
+async def set_sensor_values(device_id: str, request: SensorRequest):
+    """Set an emulator sensor's values"""
+    config = {"sensor": request.sensor, "values": request.values}
+    return await _apply_device_state("sensor", request.platform, device_id, config)
+
+
+@router.post("/{device_id}/media")
+async def add_media(device_id: str, request: MediaRequest):
+    """Add photos or videos to the device's gallery"""
+    return await _apply_device_state("add_media", request.platform, device_id, {"paths": request.paths})
+
+
+@router.post("/{device_id}/clipboard")
+async def set_clipboard(device_id: str, request: ClipboardRequest):
+    """Put text on the device clipboard"""
+    return await _apply_device_state("set_clipboard", request.platform, device_id, {"text": request.text})
+
+
+@router.get("/{device_id}/clipboard")
+async def get_clipboard(device_id: str, platform: str):
+    """Read text from the device clipboard"""
+    if platform == "ios":
+        return {"text": await run_xcrun_command(["pbpaste", device_id], device_id)}
+    output = await run_adb_command(["shell", "am", "broadcast", "-a", "clipper.get"], device_id)
+    # Clipper replies with: Broadcast completed: result=-1, data="<text>"
+    if 'data="' not in output:
+        raise HTTPException(status_code=500, detail="Clipboard unavailable; install and start the Clipper app")
+    return {"text": output.split('data="', 1)[1].rsplit('"', 1)[0]}
+
+
+@router.post("/{device_id}/stream/start")
+async def start_screen_stream(device_id: str, request: StartStreamRequest):
+    """Start streaming the device screen as 'device:frame' events"""
+    from app.services.screen_stream import start_stream
+
+    try:
+        stream = await start_stream(device_id, request.platform, request.fps)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"status": "streaming", "device_id": device_id, "fps": stream.fps}
+
+
+@router.post("/{device_id}/stream/stop")
+async def stop_screen_stream(device_id: str):
+    """Stop streaming the device screen"""
+    from app.services.screen_stream import stop_stream
+
+    if not await stop_stream(device_id):
+        raise HTTPException(status_code=404, detail="No active stream for this device")
+    return {"status": "stopped"}
+
+
+@router.get("/{device_id}/stream")
+async def get_screen_stream_status(device_id: str):
+    """Get the state of a device's screen stream"""
+    from app.services.screen_stream import get_stream
+
+    stream = get_stream(device_id)
+    if not stream:
+        return {"streaming": False}
+    return {"streaming": True, "platform": stream.platform, "fps": stream.fps, "frames": stream.frames}
+
+
 # ============================================
 # Android (ADB) Commands
 # ============================================
 
+# adb's messages for a device it can't reach, as opposed to a command that failed on the device
+ADB_UNAVAILABLE_MESSAGES = ("not found", "offline", "unauthorized", "no devices", "device still authorizing")
+
 
-async def run_adb_command(args: List[str], device_id: Optional[str] = None) -> str:
+async def run_adb_command(
+    args: List[str],
+    device_id: Optional[str] = None,
+    timeout: float = DEVICE_COMMAND_TIMEOUT,
+    input: Optional[bytes] = None,
+) -> str:
     """Run an ADB command"""
-    cmd = ["adb"]
+    cmd = [tool_command("adb")]
     if device_id:
         cmd.extend(["-s", device_id])
     cmd.extend(args)
 
     try:
-        process = await asyncio.create_subprocess_exec(
-            *cmd,
-            stdout=asyncio.subprocess.PIPE,
-            stderr=asyncio.subprocess.PIPE,
+        returncode, stdout, stderr = await run_device_process(cmd, device_id, timeout, input)
+    except FileNotFoundError:
+        raise CommandError(
+            500, ErrorCode.TOOL_NOT_FOUND, "adb not found in tools.adb_path, PATH, ANDROID_HOME or ANDROID_SDK_ROOT"
         )
-        stdout, stderr = await process.communicate()
 
-        if process.returncode != 0:
-            raise HTTPException(
-                status_code=500, detail=f"ADB error: {stderr.decode()}"
-            )
-        return stdout.decode()
-    except FileNotFoundError:
-        raise HTTPException(status_code=500, detail="ADB not found in PATH")
+    if returncode != 0:
+        message = stderr.decode()
+        unavailable = any(text in message for text in ADB_UNAVAILABLE_MESSAGES)
+        raise CommandError(
+            500, ErrorCode.DEVICE_UNAVAILABLE if unavailable else ErrorCode.DEVICE_ERROR, f"ADB error: {message}",
+            {"device_id": device_id} if device_id else None,
+        )
+    return stdout.decode()
 
 
 @router.get("/android/devices", response_model=List[DeviceInfo])
@@ -107,12 +520,47 @@ async def list_android_devices():
                     name=name or device_id,
                     status=status,
                     platform="android",
+                    device_kind="emulator" if device_id.startswith("emulator-") else "physical",
                 )
             )
 
     return devices
 
 
+def _adb_address(address: str) -> str:
+    address = address.strip()
+    if not address or any(c.isspace() for c in address):
+        raise HTTPException(status_code=400, detail="Address must be host or host:port")
+    return address if ":" in address.rsplit("]", 1)[-1] else f"{address}:5555"
+
+
+@router.post("/android/connect")
+async def adb_connect(request: AdbConnectRequest):
+    """Attach a device over the network with adb connect"""
+    address = _adb_address(request.address)
+    output = (await run_adb_command(["connect", address])).strip()
+    # adb exits successfully even when the connection fails
+    if not output.startswith(("connected to", "already connected to")):
+        raise CommandError(502, ErrorCode.DEVICE_UNAVAILABLE, output or f"Couldn't connect to {address}")
+    return {"status": "ok", "device_id": address, "message": output}
+
+
+@router.post("/android/disconnect")
+async def adb_disconnect(request: AdbConnectRequest):
+    """Detach a network device"""
+    await run_adb_command(["disconnect", _adb_address(request.address)])
+    return {"status": "ok"}
+
+
+@router.post("/android/pair")
+async def adb_pair(request: AdbPairRequest):
+    """Pair with an Android 11+ device using its Wireless debugging pairing code"""
+    output = (await run_adb_command(["pair", f"{request.host}:{request.port}", request.code])).strip()
+    if "Successfully paired" not in output:
+        raise CommandError(502, ErrorCode.DEVICE_UNAVAILABLE, output or "Pairing failed")
+    return {"status": "ok", "message": output}
+
+
 @router.get("/android/{device_id}/screenshot", response_model=ScreenshotResponse)
 async def android_screenshot(device_id: str):
     """Take a screenshot from an Android device"""
@@ -136,6 +584,25 @@ async def android_screenshot(device_id: str):
             os.unlink(temp_path)
 
 
+@router.post("/android/{device_id}/files/push")
+async def android_push_file(device_id: str, request: FileTransferRequest):
+    """Copy a file from this machine to the device"""
+    if not Path(request.local_path).expanduser().exists():
+        raise HTTPException(status_code=404, detail=f"File not found: {request.local_path}")
+    local_path = str(Path(request.local_path).expanduser())
+    await run_adb_command(["push", local_path, request.remote_path], device_id, timeout=300.0)
+    return {"status": "ok"}
+
+
+@router.post("/android/{device_id}/files/pull")
+async def android_pull_file(device_id: str, request: FileTransferRequest):
+    """Copy a file from the device to this machine"""
+    local_path = Path(request.local_path).expanduser()
+    local_path.parent.mkdir(parents=True, exist_ok=True)
+    await run_adb_command(["pull", request.remote_path, str(local_path)], device_id, timeout=300.0)
+    return {"status": "ok", "path": str(local_path)}
+
+
 @router.post("/android/{device_id}/tap")
 async def android_tap(device_id: str, request: TapRequest):
     """Tap on the screen"""
@@ -164,12 +631,96 @@ async def android_swipe(device_id: str, request: SwipeRequest):
     return {"status": "ok"}
 
 
+# Characters the device's shell would interpret in `input text`
+ADB_TEXT_SPECIAL = set("\\'\"`$&|;<>()[]{}*?~#!")
+
+
+def escape_adb_text(text: str) -> str:
+    """
+    Escape text for `adb shell input text`
+
+    adb joins its arguments and the device's sh parses them again, so the
+    escaping is the same on macOS, Linux and Windows hosts. Spaces become %s,
+    which input text turns back into spaces.
+    """
+    return "".join("%s" if c == " " else f"\\{c}" if c in ADB_TEXT_SPECIAL else c for c in text)
+
+
+# ADBKeyboard (com.android.adbkeyboard) is an IME that types whatever text it is broadcast
+ADB_KEYBOARD_PACKAGE = "com.android.adbkeyboard"
+ADB_KEYBOARD_IME = f"{ADB_KEYBOARD_PACKAGE}/.AdbIME"
+CLIPPER_PACKAGE = "ca.zgrs.clipper"
+KEYCODE_PASTE = 279
+
+TEXT_INPUT_METHODS = ("auto", "input_text", "adb_keyboard", "clipboard")
+
+
+async def _android_package_installed(device_id: str, package: str) -> bool:
+    output = await run_adb_command(["shell", "pm", "list", "packages", package], device_id)
+    return f"package:{package}" in output.split()
+
+
+async def _type_with_adb_keyboard(device_id: str, text: str) -> None:
+    """Switch to ADBKeyboard for the broadcast, then back to the keyboard that was in use"""
+    previous = (await run_adb_command(
+        ["shell", "settings", "get", "secure", "default_input_method"], device_id
+    )).strip()
+    if previous != ADB_KEYBOARD_IME:
+        await run_adb_command(["shell", "ime", "enable", ADB_KEYBOARD_IME], device_id)
+        await run_adb_command(["shell", "ime", "set", ADB_KEYBOARD_IME], device_id)
+    try:
+        message = base64.b64encode(text.encode("utf-8")).decode()
+        await run_adb_command(["shell", "am", "broadcast", "-a", "ADB_INPUT_B64", "--es", "msg", message], device_id)
+    finally:
+        if previous and previous != "null" and previous != ADB_KEYBOARD_IME:
+            await run_adb_command(["shell", "ime", "set", previous], device_id)
+
+
+async def _type_with_clipboard(device_id: str, text: str) -> None:
+    """Put the text on the clipboard through the Clipper app, then paste it"""
+    await run_adb_command(
+        ["shell", "am", "broadcast", "-a", "clipper.set", "-e", "text", shlex.quote(text)], device_id
+    )
+    await run_adb_command(["shell", "input", "keyevent", str(KEYCODE_PASTE)], device_id)
+
+
+async def android_type_text(device_id: str, text: str, method: str = "auto") -> str:
+    """
+    Type text into the focused field, returning the method used
+
+    `input text` only handles ASCII, so in auto mode other text goes through
+    ADBKeyboard when it's installed, and otherwise through the clipboard.
+    """
+    if method not in TEXT_INPUT_METHODS:
+        raise HTTPException(status_code=400, detail=f"Input method must be one of: {', '.join(TEXT_INPUT_METHODS)}")
+    if method == "auto":
+        if text.isascii():
+            method = "input_text"
+        elif await _android_package_installed(device_id, ADB_KEYBOARD_PACKAGE):
+            method = "adb_keyboard"
+        elif await _android_package_installed(device_id, CLIPPER_PACKAGE):
+            method = "clipboard"
+        else:
+            raise HTTPException(
+                status_code=400,
+                detail=f"Typing non-ASCII text needs ADBKeyboard ({ADB_KEYBOARD_PACKAGE}) "
+                       f"or Clipper ({CLIPPER_PACKAGE}) installed on the device",
+            )
+
+    if method == "adb_keyboard":
+        await _type_with_adb_keyboard(device_id, text)
+    elif method == "clipboard":
+        await _type_with_clipboard(device_id, text)
+    else:
+        await run_adb_command(["shell", "input", "text", escape_adb_text(text)], device_id)
+    return method
+
+
 @router.post("/android/{device_id}/input")
 async def android_input_text(device_id: str, request: InputTextRequest):
-    """Input text"""
-    # Escape special characters
-    text = request.text.replace(" ", "%s").replace("&", "\\&")
-    await run_adb_command(["shell", "input", "text", text], device_id)
+    """Input text, using ADBKeyboard or the clipboard for text `input text` can't type"""
+    method = await android_type_text(device_id, request.text, request.method)
+    return {"status": "ok", "method": method}
     return {"status": "ok"}
 
 
@@ -220,35 +771,275 @@ async def android_dump_ui(device_id: str):
     return {"xml": output}
 
 
+@router.get("/android/{device_id}/find-element", response_model=OfflineElementLocation)
+async def android_find_element_offline(
+    device_id: str,
+    description: str,
+    project_id: Optional[str] = None,
+    language: Optional[str] = None,
+    db: AsyncSession = Depends(get_db),
+):
+    """Find an element from the UI dump using text, resource-id, and synonym matching (no AI)"""
+    # Without a project the default synonyms apply
+    synonyms = await load_synonyms(db, project_id, language) if project_id else None
+    dump = await android_dump_ui(device_id)
+    match = find_element_from_ui_dump(parse_ui_dump(dump["xml"]), description, synonyms=synonyms)
+    if not match:
+        return OfflineElementLocation(found=False, confidence=0.0)
+
+    node, score = match
+    x, y = node.center
+    return OfflineElementLocation(
+        found=True,
+        x=x,
+        y=y,
+        confidence=round(score, 2),
+        resource_id=node.resource_id or None,
+        text=node.text or None,
+        content_desc=node.content_desc or None,
+        class_name=node.class_name or None,
+    )
+
+
+@router.get("/android/{device_id}/inspect", response_model=InspectedElement)
+async def android_inspect_at(device_id: str, x: int, y: int):
+    """Find the element at a point of the screenshot, in device pixels, and suggest its locator"""
+    dump = await android_dump_ui(device_id)
+    nodes = parse_ui_dump(dump["xml"])
+    node = node_at(nodes, x, y)
+    if not node:
+        return InspectedElement(found=False)
+    return InspectedElement(
+        found=True,
+        resource_id=node.resource_id or None,
+        text=node.text or None,
+        content_desc=node.content_desc or None,
+        class_name=node.class_name or None,
+        clickable=node.clickable,
+        bounds=[node.left, node.top, node.right, node.bottom],
+        locator=suggest_locator(nodes, node),
+    )
+
+
 # ============================================
-# iOS (Simulator) Commands
+# Android Emulators
 # ============================================
 
+# Console ports the emulator accepts; each instance takes an even port and the next odd one
+EMULATOR_PORTS = range(5554, 5684, 2)
+
+BOOT_POLL_INTERVAL = 2.0
+
+
+def _require_android_tool(name: str) -> str:
+    path = find_tool(name)
+    if not path:
+        raise CommandError(
+            500, ErrorCode.TOOL_NOT_FOUND,
+            f"{name} not found in tools.{name}_path, PATH, ANDROID_HOME or ANDROID_SDK_ROOT",
+        )
+    return path
 
-async def run_xcrun_command(args: List[str]) -> str:
-    """Run an xcrun simctl command"""
-    cmd = ["xcrun", "simctl"] + args
 
+async def run_android_tool(
+    name: str, args: List[str], timeout: float = DEVICE_COMMAND_TIMEOUT, input: Optional[bytes] = None
+) -> str:
+    """Run an Android SDK tool such as emulator or avdmanager"""
+    returncode, stdout, stderr = await run_device_process(
+        [_require_android_tool(name)] + args, timeout=timeout, input=input
+    )
+    if returncode != 0:
+        message = stderr.decode(errors="replace").strip() or stdout.decode(errors="replace").strip()
+        raise CommandError(500, ErrorCode.DEVICE_ERROR, f"{name} error: {message}")
+    return stdout.decode(errors="replace")
+
+
+async def _running_emulators() -> Dict[str, str]:
+    """Map the AVD name of each running emulator to its device ID"""
+    running = {}
+    for device in await list_android_devices():
+        if not device.id.startswith("emulator-") or device.status == "offline":
+            continue
+        try:
+            output = await run_adb_command(["emu", "avd", "name"], device.id, timeout=10.0)
+        except HTTPException:
+            continue
+        name = output.strip().splitlines()[0].strip() if output.strip() else ""
+        if name:
+            running[name] = device.id
+    return running
+
+
+def _avd_dir(name: str) -> Path:
+    """Locate an AVD's data directory from its .ini file"""
+    avd_home = Path(os.environ.get("ANDROID_AVD_HOME") or Path.home() / ".android" / "avd")
+    ini = avd_home / f"{name}.ini"
+    if ini.is_file():
+        for line in ini.read_text(encoding="utf-8", errors="replace").splitlines():
+            if line.startswith("path="):
+                return Path(line[len("path="):].strip())
+    return avd_home / f"{name}.avd"
+
+
+@router.get("/android/avds", response_model=List[AvdInfo])
+async def avd_list():
+    """List Android virtual devices and which of them are running"""
+    output = await run_android_tool("emulator", ["-list-avds"])
     try:
-        process = await asyncio.create_subprocess_exec(
-            *cmd,
-            stdout=asyncio.subprocess.PIPE,
-            stderr=asyncio.subprocess.PIPE,
+        running = await _running_emulators()
+    except HTTPException:
+        running = {}  # adb isn't installed, so nothing can be running
+    names = [line.strip() for line in output.splitlines() if line.strip() and not line.startswith("INFO")]
+    return [AvdInfo(name=name, running=name in running, device_id=running.get(name)) for name in names]
+
+
+@router.post("/android/avds", response_model=AvdInfo)
+async def avd_create(request: AvdCreateRequest):
+    """Create an AVD from an installed system image"""
+    args = ["create", "avd", "-n", request.name, "-k", request.system_image]
+    if request.device:
+        args += ["-d", request.device]
+    if request.force:
+        args.append("--force")
+    # avdmanager asks whether to create a custom hardware profile
+    await run_android_tool("avdmanager", args, timeout=120.0, input=b"no\n")
+    return AvdInfo(name=request.name, running=False)
+
+
+@router.post("/android/avds/{name}/start")
+async def avd_start(name: str, options: AvdStartRequest):
+    """Boot an AVD, returning its device ID once Android has finished booting"""
+    running = await _running_emulators()
+    if name in running:
+        raise HTTPException(status_code=409, detail=f"{name} is already running as {running[name]}")
+
+    taken = {device.id for device in await list_android_devices()}
+    if options.port is not None:
+        if options.port % 2 or options.port not in EMULATOR_PORTS:
+            raise HTTPException(status_code=400, detail="Port must be an even number from 5554 to 5682")
+        port = options.port
+    else:
+        port = next((p for p in EMULATOR_PORTS if f"emulator-{p}" not in taken), None)
+        if port is None:
+            raise HTTPException(status_code=409, detail="No free emulator port")
+    device_id = f"emulator-{port}"
+    if device_id in taken:
+        raise HTTPException(status_code=409, detail=f"Port {port} is already used by a running emulator")
+
+    args = [_require_android_tool("emulator"), "-avd", name, "-port", str(port)]
+    if options.headless:
+        args += ["-no-window", "-no-audio", "-no-boot-anim"]
+    if options.wipe_data:
+        args.append("-wipe-data")
+    if options.cold_boot:
+        args.append("-no-snapshot-load")
+    if options.gpu:
+        args += ["-gpu", options.gpu]
+    args += options.extra_args
+
+    # The emulator runs until stopped, so it isn't tracked as a device command
+    process = await asyncio.create_subprocess_exec(
+        *args,
+        stdin=asyncio.subprocess.DEVNULL,
+        stdout=asyncio.subprocess.DEVNULL,
+        stderr=asyncio.subprocess.DEVNULL,
+        start_new_session=os.name != "nt",
+    )
+    await asyncio.sleep(1.0)
+    if process.returncode is not None:
+        raise HTTPException(status_code=500, detail=f"Emulator exited with code {process.returncode}")
+
+    if options.wait_for_boot:
+        await wait_for_boot_completed(device_id, options.boot_timeout)
+    return {"status": "ok", "device_id": device_id, "booted": options.wait_for_boot}
+
+
+@router.post("/android/avds/{name}/wipe")
+async def avd_wipe_data(name: str):
+    """Reset an AVD to factory state by deleting its user data and snapshots"""
+    if name in await _running_emulators():
+        raise HTTPException(status_code=409, detail=f"Stop {name} before wiping its data")
+    avd_dir = _avd_dir(name)
+    if not avd_dir.is_dir():
+        raise HTTPException(status_code=404, detail=f"AVD not found: {name}")
+
+    removed = []
+    for path in list(avd_dir.glob("userdata-qemu.img*")) + list(avd_dir.glob("cache.img*")):
+        path.unlink()
+        removed.append(path.name)
+    if (avd_dir / "snapshots").is_dir():
+        shutil.rmtree(avd_dir / "snapshots")
+        removed.append("snapshots")
+    return {"status": "ok", "removed": removed}
+
+
+@router.post("/android/{device_id}/emulator/stop")
+async def avd_stop(device_id: str):
+    """Shut down a running emulator"""
+    if not device_id.startswith("emulator-"):
+        raise HTTPException(status_code=400, detail="Only emulators can be stopped")
+    await run_adb_command(["emu", "kill"], device_id)
+    return {"status": "ok"}
+
+
+@router.post("/android/{device_id}/wait-for-boot")
+async def wait_for_boot_completed(device_id: str, timeout: float = 300.0):
+    """Wait until a device reports sys.boot_completed, then unlock its screen"""
+    loop = asyncio.get_running_loop()
+    deadline = loop.time() + timeout
+    await run_adb_command(["wait-for-device"], device_id, timeout=timeout)
+    while True:
+        try:
+            output = await run_adb_command(["shell", "getprop", "sys.boot_completed"], device_id, timeout=10.0)
+            if output.strip() == "1":
+                break
+        except HTTPException:
+            pass  # adbd restarts while the system boots
+        if loop.time() >= deadline:
+            raise CommandError(504, ErrorCode.TIMEOUT, f"{device_id} didn't finish booting in {timeout:g}s")
+        await asyncio.sleep(BOOT_POLL_INTERVAL)
+
+    # Dismiss the keyguard so the first step sees the launcher
+    await run_adb_command(["shell", "input", "keyevent", "82"], device_id)
+    return {"status": "ok", "device_id": device_id}
+
+
+# ============================================
+# iOS (Simulator) Commands
+# ============================================
+
+
+def require_macos() -> None:
+    """Xcode tools only exist on macOS; fail with a clear error elsewhere"""
+    if sys.platform != "darwin":
+        raise HTTPException(
+            status_code=400, detail=f"iOS simulators need macOS with Xcode, but this machine runs {sys.platform}"
         )
-        stdout, stderr = await process.communicate()
 
-        if process.returncode != 0:
-            raise HTTPException(
-                status_code=500, detail=f"xcrun error: {stderr.decode()}"
-            )
-        return stdout.decode()
+
+async def run_xcrun_command(
+    args: List[str],
+    device_id: Optional[str] = None,
+    timeout: float = DEVICE_COMMAND_TIMEOUT,
+    input: Optional[bytes] = None,
+) -> str:
+    """Run an xcrun simctl command"""
+    require_macos()
+    cmd = [tool_command("xcrun"), "simctl"] + args
+
+    try:
+        returncode, stdout, stderr = await run_device_process(cmd, device_id, timeout, input)
     except FileNotFoundError:
-        raise HTTPException(status_code=500, detail="xcrun not found (requires Xcode)")
+        raise CommandError(500, ErrorCode.TOOL_NOT_FOUND, "xcrun not found (requires Xcode)")
+
+    if returncode != 0:
+        raise CommandError(500, ErrorCode.DEVICE_ERROR, f"xcrun error: {stderr.decode()}")
+    return stdout.decode()
 
 
 @router.get("/ios/devices", response_model=List[DeviceInfo])
 async def list_ios_devices():
-    """List iOS simulators"""
+    """List iOS simulators and connected physical devices"""
     import json
 
     output = await run_xcrun_command(["list", "devices", "-j"])
@@ -263,34 +1054,148 @@ async def list_ios_devices():
                     name=device["name"],
                     status=device["state"].lower(),
                     platform="ios",
+                    device_kind="simulator",
                 )
             )
 
+    devices.extend(await list_ios_physical_devices())
     return devices
 
 
 @router.post("/ios/{device_id}/boot")
 async def ios_boot_simulator(device_id: str):
     """Boot an iOS simulator"""
-    await run_xcrun_command(["boot", device_id])
+    await run_xcrun_command(["boot", device_id], device_id)
     return {"status": "ok"}
 
 
 @router.post("/ios/{device_id}/shutdown")
 async def ios_shutdown_simulator(device_id: str):
     """Shutdown an iOS simulator"""
-    await run_xcrun_command(["shutdown", device_id])
+    await run_xcrun_command(["shutdown", device_id], device_id)
     return {"status": "ok"}
 
 
+@router.get("/ios/device-types")
+async def ios_list_device_types():
+    """List simulator device types that can be created"""
+    import json
+
+    data = json.loads(await run_xcrun_command(["list", "devicetypes", "-j"]))
+    return [
+        {"identifier": t["identifier"], "name": t["name"]} for t in data.get("devicetypes", [])
+    ]
+
+
+@router.get("/ios/runtimes")
+async def ios_list_runtimes():
+    """List installed simulator runtimes"""
+    import json
+
+    data = json.loads(await run_xcrun_command(["list", "runtimes", "-j"]))
+    return [
+        {"identifier": r["identifier"], "name": r["name"], "available": r.get("isAvailable", False)}
+        for r in data.get("runtimes", [])
+    ]
+
+
+@router.post("/ios/devices", response_model=DeviceInfo)
+async def ios_create_device(request: IosCreateDeviceRequest):
+    """Create a simulator, returning its UDID as the device ID"""
+    output = await run_xcrun_command(["create", request.name, request.device_type, request.runtime])
+    return DeviceInfo(id=output.strip(), name=request.name, status="shutdown", platform="ios")
+
+
+@router.post("/ios/{device_id}/erase")
+async def ios_erase_device(device_id: str):
+    """Erase a shut down simulator's contents and settings"""
+    await run_xcrun_command(["erase", device_id], device_id)
+    return {"status": "ok"}
+
+
+@router.delete("/ios/{device_id}")
+async def ios_delete_device(device_id: str):
+    """Delete a simulator"""
+    await run_xcrun_command(["delete", device_id], device_id)
+    return {"status": "ok"}
+
+
+@router.post("/ios/{device_id}/install")
+async def ios_install_app(device_id: str, request: InstallAppRequest):
+    """Install a .app bundle on a booted simulator, or a signed .app or .ipa on a device"""
+    app_path = Path(request.path).expanduser()
+    if is_physical_ios(device_id):
+        if not app_path.exists() or app_path.suffix not in (".app", ".ipa"):
+            raise HTTPException(status_code=400, detail=f"Not a .app or .ipa: {request.path}")
+        await run_devicectl(["device", "install", "app", "--device", device_id, str(app_path)], device_id, 300.0)
+        return {"status": "ok"}
+    if not app_path.is_dir() or app_path.suffix != ".app":
+        raise HTTPException(status_code=400, detail=f"Not a .app bundle: {request.path}")
+    # Large bundles can take a while to copy
+    await run_xcrun_command(["install", device_id, str(app_path)], device_id, timeout=300.0)
+    return {"status": "ok"}
+
+
+@router.post("/ios/{device_id}/uninstall")
+async def ios_uninstall_app(device_id: str, request: AppRequest):
+    """Uninstall an app by bundle ID"""
+    if is_physical_ios(device_id):
+        await run_devicectl(["device", "uninstall", "app", "--device", device_id, request.package], device_id)
+        return {"status": "ok"}
+    await run_xcrun_command(["uninstall", device_id, request.package], device_id)
+    return {"status": "ok"}
+
+
+@router.post("/ios/{device_id}/status-bar")
+async def ios_set_status_bar(device_id: str, request: StatusBarRequest):
+    """Override the simulator's status bar so screenshots are reproducible"""
+    flags = {
+        "time": "--time",
+        "data_network": "--dataNetwork",
+        "wifi_mode": "--wifiMode",
+        "wifi_bars": "--wifiBars",
+        "cellular_mode": "--cellularMode",
+        "cellular_bars": "--cellularBars",
+        "operator_name": "--operatorName",
+        "battery_state": "--batteryState",
+        "battery_level": "--batteryLevel",
+    }
+    args = []
+    for field, flag in flags.items():
+        value = getattr(request, field)
+        if value is not None:
+            args += [flag, str(value)]
+    if not args:
+        raise HTTPException(status_code=400, detail="No status bar overrides given")
+    await run_xcrun_command(["status_bar", device_id, "override"] + args, device_id)
+    return {"status": "ok"}
+
+
+@router.delete("/ios/{device_id}/status-bar")
+async def ios_clear_status_bar(device_id: str):
+    """Remove status bar overrides"""
+    await run_xcrun_command(["status_bar", device_id, "clear"], device_id)
+    return {"status": "ok"}
+
+
+@router.post("/ios/{device_id}/push")
+async def ios_push_notification(device_id: str, request: PushNotificationRequest):
+    """Deliver a simulated push notification to an app"""
+    config = {"packageName": request.bundle_id, "payload": request.payload}
+    return await _apply_device_state("push_notification", "ios", device_id, config)
+
+
 @router.get("/ios/{device_id}/screenshot", response_model=ScreenshotResponse)
 async def ios_screenshot(device_id: str):
-    """Take a screenshot from an iOS simulator"""
+    """Take a screenshot from an iOS simulator or device"""
     with tempfile.NamedTemporaryFile(suffix=".png", delete=False) as f:
         temp_path = f.name
 
     try:
-        await run_xcrun_command(["io", device_id, "screenshot", temp_path])
+        if is_physical_ios(device_id):
+            await _physical_screenshot(device_id, temp_path)
+        else:
+            await run_xcrun_command(["io", device_id, "screenshot", temp_path], device_id)
 
         with open(temp_path, "rb") as f:
             screenshot_data = base64.b64encode(f.read()).decode()
@@ -301,10 +1206,46 @@ async def ios_screenshot(device_id: str):
             os.unlink(temp_path)
 
 
+# Screen scale (pixels per point) of each simulator, from idb describe
+_idb_scales: Dict[str, float] = {}
+
+
+async def run_idb_command(args: List[str], device_id: str) -> str:
+    """Run an idb command against a simulator"""
+    require_macos()
+    cmd = [tool_command("idb")] + args + ["--udid", device_id]
+    try:
+        returncode, stdout, stderr = await run_device_process(cmd, device_id)
+    except FileNotFoundError:
+        raise CommandError(500, ErrorCode.TOOL_NOT_FOUND, "idb not found in PATH")
+    if returncode != 0:
+        raise CommandError(500, ErrorCode.DEVICE_ERROR, f"idb error: {stderr.decode()}")
+    return stdout.decode()
+
+
+async def _idb_points(device_id: str, *pixels: int) -> List[str]:
+    """Convert screenshot pixel coordinates to the points idb expects"""
+    import json
+
+    if device_id not in _idb_scales:
+        description = json.loads(await run_idb_command(["describe", "--json"], device_id))
+        dimensions = description.get("screen_dimensions") or {}
+        _idb_scales[device_id] = float(dimensions.get("density") or 1.0)
+    scale = _idb_scales[device_id]
+    return [str(round(value / scale)) for value in pixels]
+
+
 @router.post("/ios/{device_id}/tap")
 async def ios_tap(device_id: str, request: TapRequest):
-    """Tap on the iOS simulator screen"""
-    # Use AppleScript to send click events
+    """Tap on the iOS simulator screen at screenshot pixel coordinates"""
+    if is_physical_ios(device_id):
+        raise HTTPException(status_code=400, detail=PHYSICAL_INPUT_UNSUPPORTED)
+    if find_tool("idb"):
+        # idb injects touches straight into the simulator, whatever its window state
+        await run_idb_command(["ui", "tap"] + await _idb_points(device_id, request.x, request.y), device_id)
+        return {"status": "ok"}
+    # Fallback: AppleScript clicks on the Simulator window, which needs it frontmost
+    require_macos()
     script = f'''
     tell application "Simulator"
         activate
@@ -313,31 +1254,160 @@ async def ios_tap(device_id: str, request: TapRequest):
         click at {{{request.x}, {request.y}}}
     end tell
     '''
-    process = await asyncio.create_subprocess_exec(
-        "osascript", "-e", script,
-        stdout=asyncio.subprocess.PIPE,
-        stderr=asyncio.subprocess.PIPE,
-    )
-    await process.communicate()
+    returncode, _, stderr = await run_device_process(["osascript", "-e", script], device_id)
+    if returncode != 0:
+        raise HTTPException(status_code=500, detail=f"Tap failed: {stderr.decode()}")
+    return {"status": "ok"}
+
+
+@router.post("/ios/{device_id}/swipe")
+async def ios_swipe(device_id: str, request: SwipeRequest):
+    """Swipe on the iOS simulator screen (requires idb)"""
+    if is_physical_ios(device_id):
+        raise HTTPException(status_code=400, detail=PHYSICAL_INPUT_UNSUPPORTED)
+    if not find_tool("idb"):
+        raise CommandError(500, ErrorCode.TOOL_NOT_FOUND, "Swiping on iOS simulators requires idb (pip install fb-idb)")
+    points = await _idb_points(device_id, request.start_x, request.start_y, request.end_x, request.end_y)
+    duration = f"{request.duration_ms / 1000:g}"
+    await run_idb_command(["ui", "swipe"] + points + ["--duration", duration], device_id)
     return {"status": "ok"}
 
 
 @router.post("/ios/{device_id}/input")
 async def ios_input_text(device_id: str, request: InputTextRequest):
     """Input text on iOS simulator"""
-    await run_xcrun_command(["io", device_id, "type", request.text])
+    if is_physical_ios(device_id):
+        raise HTTPException(status_code=400, detail=PHYSICAL_INPUT_UNSUPPORTED)
+    if find_tool("idb"):
+        await run_idb_command(["ui", "text", request.text], device_id)
+        return {"status": "ok"}
+    await run_xcrun_command(["io", device_id, "type", request.text], device_id)
     return {"status": "ok"}
 
 
 @router.post("/ios/{device_id}/launch")
 async def ios_launch_app(device_id: str, request: AppRequest):
-    """Launch an app on iOS simulator"""
-    await run_xcrun_command(["launch", device_id, request.package])
+    """Launch an app on iOS simulator or device"""
+    if is_physical_ios(device_id):
+        await run_devicectl(
+            ["device", "process", "launch", "--device", device_id, "--terminate-existing", request.package],
+            device_id,
+        )
+        return {"status": "ok"}
+    await run_xcrun_command(["launch", device_id, request.package], device_id)
     return {"status": "ok"}
 
 
 @router.post("/ios/{device_id}/terminate")
 async def ios_terminate_app(device_id: str, request: AppRequest):
-    """Terminate an app on iOS simulator"""
-    await run_xcrun_command(["terminate", device_id, request.package])
+    """Terminate an app on iOS simulator or device"""
+    if is_physical_ios(device_id):
+        await _terminate_physical_app(device_id, request.package)
+        return {"status": "ok"}
+    await run_xcrun_command(["terminate", device_id, request.package], device_id)
     return {"status": "ok"}
+
+
+# ============================================
+# iOS (Physical Device) Commands
+# ============================================
+
+# Simulators use standard UUIDs; devices use 40-hex or 8-16 hex UDIDs
+_SIMULATOR_UDID = re.compile(r"^[0-9A-Fa-f]{8}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{4}-[0-9A-Fa-f]{12}$")
+
+PHYSICAL_INPUT_UNSUPPORTED = (
+    "Taps and typing on physical iOS devices need an XCUITest driver such as WebDriverAgent"
+)
+
+
+def is_physical_ios(device_id: str) -> bool:
+    return not _SIMULATOR_UDID.match(device_id)
+
+
+async def run_devicectl(
+    args: List[str], device_id: Optional[str] = None, timeout: float = DEVICE_COMMAND_TIMEOUT
+) -> Dict:
+    """Run an xcrun devicectl command (Xcode 15+), returning the result from its JSON output"""
+    import json
+
+    require_macos()
+    with tempfile.TemporaryDirectory() as tmp:
+        output_path = Path(tmp) / "result.json"
+        cmd = [tool_command("xcrun"), "devicectl"] + args + ["--json-output", str(output_path), "--quiet"]
+        try:
+            returncode, stdout, stderr = await run_device_process(cmd, device_id, timeout)
+        except FileNotFoundError:
+            raise CommandError(500, ErrorCode.TOOL_NOT_FOUND, "xcrun not found (requires Xcode)")
+        if returncode != 0:
+            message = stderr.decode(errors="replace").strip() or stdout.decode(errors="replace").strip()
+            raise CommandError(500, ErrorCode.DEVICE_ERROR, f"devicectl error: {message}")
+        if not output_path.exists():
+            return {}
+        return json.loads(output_path.read_text(encoding="utf-8")).get("result", {})
+
+
+async def _list_libimobiledevice_devices() -> List[DeviceInfo]:
+    """Fallback for Xcode versions without devicectl"""
+    if not shutil.which("idevice_id"):
+        return []
+    returncode, stdout, _ = await run_device_process(["idevice_id", "-l"])
+    devices = []
+    for udid in stdout.decode().split() if returncode == 0 else []:
+        name = udid
+        if shutil.which("ideviceinfo"):
+            code, out, _ = await run_device_process(["ideviceinfo", "-u", udid, "-k", "DeviceName"], udid)
+            name = out.decode().strip() if code == 0 and out.strip() else udid
+        devices.append(DeviceInfo(id=udid, name=name, status="connected", platform="ios"))
+    return devices
+
+
+async def list_ios_physical_devices() -> List[DeviceInfo]:
+    """List iPhones and iPads paired with this Mac, by hardware UDID"""
+    try:
+        result = await run_devicectl(["list", "devices"])
+    except HTTPException:
+        return await _list_libimobiledevice_devices()
+
+    devices = []
+    for device in result.get("devices", []):
+        hardware = device.get("hardwareProperties", {})
+        if hardware.get("reality") != "physical" or not hardware.get("udid"):
+            continue
+        tunnel = device.get("connectionProperties", {}).get("tunnelState", "")
+        devices.append(
+            DeviceInfo(
+                id=hardware["udid"],
+                name=device.get("deviceProperties", {}).get("name") or hardware["udid"],
+                status="connected" if tunnel == "connected" else tunnel or "unavailable",
+                platform="ios",
+            )
+        )
+    return devices
+
+
+async def _physical_screenshot(device_id: str, path: str) -> None:
+    # devicectl can't capture the screen, so this goes through libimobiledevice
+    if not shutil.which("idevicescreenshot"):
+        raise CommandError(
+            500, ErrorCode.TOOL_NOT_FOUND, "idevicescreenshot not found (brew install libimobiledevice)"
+        )
+    returncode, _, stderr = await run_device_process(["idevicescreenshot", "-u", device_id, path], device_id)
+    if returncode != 0:
+        raise CommandError(500, ErrorCode.DEVICE_ERROR, f"idevicescreenshot error: {stderr.decode()}")
+
+
+async def _terminate_physical_app(device_id: str, bundle_id: str) -> None:
+    """Find the app's process from its install location and terminate it"""
+    apps = await run_devicectl(
+        ["device", "info", "apps", "--device", device_id, "--bundle-id", bundle_id], device_id
+    )
+    app_url = next((app.get("url") for app in apps.get("apps", []) if app.get("url")), None)
+    if not app_url:
+        raise HTTPException(status_code=404, detail=f"App not installed: {bundle_id}")
+    processes = await run_devicectl(["device", "info", "processes", "--device", device_id], device_id)
+    for process in processes.get("runningProcesses", []):
+        if str(process.get("executable", "")).startswith(app_url):
+            await run_devicectl(
+                ["device", "process", "terminate", "--device", device_id, "--pid", str(process["processIdentifier"])],
+                device_id,
+            )
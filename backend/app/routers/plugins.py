@@ -0,0 +1,48 @@
+from typing import List, Optional
+
+from fastapi import APIRouter
+from pydantic import BaseModel
+
+from app.services.plugins import Plugin, plugin_registry, plugins_dir
+
+router = APIRouter(prefix="/plugins", tags=["plugins"])
+
+
+class PluginInfo(BaseModel):
+    name: str
+    kind: str
+    version: str
+    description: str
+    step_types: List[str]
+    path: str
+    error: Optional[str]
+
+
+def plugin_info(plugin: Plugin) -> PluginInfo:
+    return PluginInfo(
+        name=plugin.name,
+        kind=plugin.kind,
+        version=plugin.version,
+        description=plugin.description,
+        step_types=plugin.step_types,
+        path=str(plugin.path),
+        error=plugin.error,
+    )
+
+
+@router.get("", response_model=List[PluginInfo])
+async def list_plugins():
+    """List discovered plugins, including ones whose manifest is invalid"""
+    return [plugin_info(p) for p in plugin_registry.list()]
+
+
+@router.post("/reload", response_model=List[PluginInfo])
+async def reload_plugins():
+    """Rescan the plugins directory"""
+    return [plugin_info(p) for p in plugin_registry.discover()]
+
+
+@router.get("/directory")
+async def get_plugins_directory():
+    """Get the directory plugins are discovered from"""
+    return {"path": str(plugins_dir())}
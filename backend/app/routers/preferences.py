@@ -0,0 +1,33 @@
+from fastapi import APIRouter, HTTPException
+
+from app.services.preferences import (
+    Preferences,
+    PreferencesError,
+    PreferencesUpdate,
+    load_preferences,
+    reset_preferences,
+    update_preferences,
+)
+
+router = APIRouter(prefix="/preferences", tags=["preferences"])
+
+
+@router.get("", response_model=Preferences)
+async def get_preferences():
+    """Get the desktop app preferences"""
+    return load_preferences()
+
+
+@router.put("", response_model=Preferences)
+async def set_preferences(data: PreferencesUpdate):
+    """Update preferences; a new data directory is used after the app restarts"""
+    try:
+        return update_preferences(data)
+    except PreferencesError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.delete("", response_model=Preferences)
+async def clear_preferences():
+    """Reset preferences to their defaults"""
+    return reset_preferences()
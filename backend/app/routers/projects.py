@@ -1,3 +1,4 @@
+import json
 import uuid
 from typing import List, Optional
 
@@ -8,7 +9,12 @@ from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.db import get_db
-from app.models import Project, ProjectCreate, ProjectUpdate, ProjectResponse
+from app.models import Project, ProjectCreate, ProjectUpdate, ProjectResponse, TestCaseStats, TestRunSummary
+from app.services.browsers import BrowserError, check_browser, check_viewports
+from app.services.duplication import copy_project
+from app.services.repo_analysis import RepoAnalysis, RepoAnalysisError, analyze_repo
+from app.services.secret_masking import SecretError, mark_variable_secret, refresh_secrets
+from app.services.stat_counters import rebuild_stats, test_case_stats, test_run_summary
 
 router = APIRouter(prefix="/projects", tags=["projects"])
 
@@ -20,6 +26,17 @@ class ConnectRequest(BaseModel):
     project_type: str = "web"
 
 
+class VariableSecretRequest(BaseModel):
+    """Schema for marking a project variable as secret"""
+    secret: bool = True
+
+
+class ProjectStats(BaseModel):
+    """Schema for a project's rebuilt stats"""
+    test_cases: TestCaseStats
+    test_runs: TestRunSummary
+
+
 class ConnectResponse(BaseModel):
     """Schema for connect response"""
     project: ProjectResponse
@@ -30,6 +47,12 @@ class ConnectResponse(BaseModel):
 @router.post("", response_model=ProjectResponse)
 async def create_project(data: ProjectCreate, db: AsyncSession = Depends(get_db)):
     """Create a new project"""
+    try:
+        if data.default_browser:
+            check_browser(data.default_browser)
+        check_viewports(data.viewports)
+    except BrowserError as e:
+        raise HTTPException(status_code=400, detail=str(e))
     project = Project(
         id=str(uuid.uuid4()),
         name=data.name,
@@ -37,10 +60,17 @@ async def create_project(data: ProjectCreate, db: AsyncSession = Depends(get_db)
         app_url=data.app_url,
         repo_url=data.repo_url,
         project_type=data.project_type,
+        ai_free=data.ai_free,
+        env_vars=json.dumps(data.env_vars),
+        secret_env_vars=json.dumps(data.secret_env_vars),
+        default_browser=data.default_browser,
+        browser_headless=data.browser_headless,
+        viewports=json.dumps([viewport.model_dump() for viewport in data.viewports]),
     )
     db.add(project)
     await db.commit()
     await db.refresh(project)
+    await refresh_secrets()
     return project
 
 
@@ -120,14 +150,78 @@ async def update_project(
         raise HTTPException(status_code=404, detail="Project not found")
 
     update_data = data.model_dump(exclude_unset=True)
+    try:
+        if update_data.get("default_browser"):
+            check_browser(update_data["default_browser"])
+        if data.viewports:
+            check_viewports(data.viewports)
+    except BrowserError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    if "browser_headless" in update_data and update_data["browser_headless"] is None:
+        del update_data["browser_headless"]
+    if "env_vars" in update_data:
+        update_data["env_vars"] = json.dumps(update_data["env_vars"] or {})
+    if "secret_env_vars" in update_data:
+        update_data["secret_env_vars"] = json.dumps(update_data["secret_env_vars"] or [])
+    if "viewports" in update_data:
+        update_data["viewports"] = json.dumps(update_data["viewports"] or [])
     for key, value in update_data.items():
         setattr(project, key, value)
 
     await db.commit()
     await db.refresh(project)
+    await refresh_secrets()
     return project
 
 
+@router.put("/{project_id}/variables/{name}/secret", response_model=ProjectResponse)
+async def set_variable_secret(
+    project_id: str, name: str, data: VariableSecretRequest, db: AsyncSession = Depends(get_db)
+):
+    """Mark a project variable as secret so its value is masked in logs, results and AI requests"""
+    project = await db.get(Project, project_id)
+    if not project:
+        raise HTTPException(status_code=404, detail="Project not found")
+    try:
+        return await mark_variable_secret(db, project, name, data.secret)
+    except SecretError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.post("/{project_id}/duplicate", response_model=ProjectResponse)
+async def duplicate_project(
+    project_id: str,
+    new_name: Optional[str] = None,
+    include_runs: bool = False,
+    db: AsyncSession = Depends(get_db),
+):
+    """Duplicate a project with all test cases, scenarios, steps, and optionally test runs"""
+    result = await db.execute(select(Project).where(Project.id == project_id))
+    project = result.scalar_one_or_none()
+    if not project:
+        raise HTTPException(status_code=404, detail="Project not found")
+
+    new_project = await copy_project(
+        db, project, name=new_name or f"{project.name} (Copy)", include_runs=include_runs
+    )
+    await db.commit()
+    await db.refresh(new_project)
+    return new_project
+
+
+@router.post("/{project_id}/stats/rebuild", response_model=ProjectStats)
+async def rebuild_project_stats(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Recount a project's stat counters from its test cases and runs"""
+    if not await db.get(Project, project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    await rebuild_stats(db, project_id)
+    await db.commit()
+    return ProjectStats(
+        test_cases=await test_case_stats(db, project_id),
+        test_runs=await test_run_summary(db, project_id),
+    )
+
+
 @router.delete("/{project_id}")
 async def delete_project(project_id: str, db: AsyncSession = Depends(get_db)):
     """Delete a project"""
@@ -138,9 +232,35 @@ async def delete_project(project_id: str, db: AsyncSession = Depends(get_db)):
 
     await db.delete(project)
     await db.commit()
+    await refresh_secrets()
     return {"status": "deleted"}
 
 
+class RepoAnalysisRequest(BaseModel):
+    """Schema for analyzing a project's repository"""
+
+    branch: Optional[str] = None  # The default branch when unset
+    use_ai: bool = True  # Ask the AI agent for suggestions on each file with surfaces
+    max_surfaces: int = 200
+
+
+@router.post("/{project_id}/repo-analysis", response_model=RepoAnalysis)
+async def analyze_project_repo(
+    project_id: str, data: Optional[RepoAnalysisRequest] = None, db: AsyncSession = Depends(get_db)
+):
+    """Clone the project's repo_url and propose a test case for each route, component and screen found"""
+    project = await db.get(Project, project_id)
+    if not project:
+        raise HTTPException(status_code=404, detail="Project not found")
+    if not project.repo_url:
+        raise HTTPException(status_code=400, detail="Set the project's repository URL first")
+    data = data or RepoAnalysisRequest()
+    try:
+        return await analyze_repo(project.repo_url, data.branch, data.use_ai, data.max_surfaces)
+    except RepoAnalysisError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
 @router.get("/search/{query}", response_model=List[ProjectResponse])
 async def search_projects(query: str, db: AsyncSession = Depends(get_db)):
     """Search projects by name or description"""
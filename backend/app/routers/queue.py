@@ -0,0 +1,86 @@
+from datetime import datetime
+from typing import List, Optional
+
+from fastapi import APIRouter, HTTPException
+from pydantic import BaseModel
+
+from app.services.run_queue import PRIORITIES, QueueError, run_queue
+
+router = APIRouter(prefix="/queue", tags=["queue"])
+
+
+class QueueEntry(BaseModel):
+    """A test run waiting for or using device or browser capacity"""
+
+    test_run_id: str
+    device_id: str
+    platform: str
+    priority: str
+    state: str  # 'running' | 'queued'
+    position: Optional[int]  # Among queued runs, starting at 0
+    queued_at: datetime
+    started_at: Optional[datetime]
+
+
+class MoveRequest(BaseModel):
+    position: int
+
+
+class PriorityRequest(BaseModel):
+    priority: str  # 'high' | 'normal' | 'low'
+
+
+def check_priority(priority: str) -> None:
+    if priority not in PRIORITIES:
+        raise HTTPException(status_code=400, detail=f"Priority must be one of: {', '.join(PRIORITIES)}")
+
+
+@router.get("", response_model=List[QueueEntry])
+async def get_local_queue():
+    """List running test runs, then queued ones in the order they'll start"""
+    entries = []
+    position = 0
+    for run in run_queue.list():
+        running = run_queue.running(run.test_run_id) is not None
+        entries.append(QueueEntry(
+            test_run_id=run.test_run_id,
+            device_id=run.device_id,
+            platform=run.platform,
+            priority=run.priority,
+            state="running" if running else "queued",
+            position=None if running else position,
+            queued_at=run.queued_at,
+            started_at=run.started_at,
+        ))
+        if not running:
+            position += 1
+    return entries
+
+
+@router.post("/{test_run_id}/move")
+async def move_queued_run(test_run_id: str, data: MoveRequest):
+    """Move a queued run to another position in the queue"""
+    try:
+        run_queue.move(test_run_id, data.position)
+    except QueueError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    return {"status": "moved"}
+
+
+@router.post("/{test_run_id}/priority")
+async def set_queued_run_priority(test_run_id: str, data: PriorityRequest):
+    """Change a queued run's priority, placing it after runs of the same priority"""
+    check_priority(data.priority)
+    try:
+        run_queue.set_priority(test_run_id, data.priority)
+    except QueueError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    return {"status": "updated"}
+
+
+@router.post("/{test_run_id}/cancel")
+async def cancel_queued_run(test_run_id: str):
+    """Cancel a queued or running test run"""
+    if not await run_queue.cancel(test_run_id):
+        raise HTTPException(status_code=404, detail="Test run is not queued or running")
+    return {"status": "cancelling"}
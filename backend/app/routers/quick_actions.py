@@ -0,0 +1,43 @@
+from typing import Any, Dict, List
+
+from fastapi import APIRouter, Body, Depends, HTTPException
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.services.app_settings import get_setting
+from app.services.quick_actions import (
+    QuickActionError,
+    QuickActionResponse,
+    QuickActionResult,
+    invoke_quick_action,
+    list_quick_actions,
+)
+
+router = APIRouter(prefix="/quick-actions", tags=["quick-actions"])
+
+
+@router.get("", response_model=List[QuickActionResponse])
+async def get_quick_actions():
+    """List the actions the command palette can run"""
+    return list_quick_actions()
+
+
+@router.get("/shortcuts", response_model=Dict[str, str])
+async def get_global_shortcuts(db: AsyncSession = Depends(get_db)):
+    """Shortcuts the desktop app registers system-wide, by action id; empty unless shortcuts.global is on"""
+    if not await get_setting(db, "shortcuts.global"):
+        return {}
+    return {action.id: action.shortcut for action in list_quick_actions() if action.shortcut}
+
+
+@router.post("/{action_id}", response_model=QuickActionResult)
+async def run_quick_action(
+    action_id: str, args: Dict[str, Any] = Body(default_factory=dict), db: AsyncSession = Depends(get_db)
+):
+    """Run a quick action with optional arguments"""
+    try:
+        return await invoke_quick_action(db, action_id, args)
+    except KeyError:
+        raise HTTPException(status_code=404, detail="Quick action not found")
+    except QuickActionError as e:
+        raise HTTPException(status_code=400, detail=str(e))
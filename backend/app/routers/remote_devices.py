@@ -0,0 +1,104 @@
+from datetime import datetime
+from typing import List
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import RemoteDevice, RemoteDeviceCreate, RemoteDeviceResponse, RemoteDeviceUpdate
+from app.routers.mobile import AdbConnectRequest, adb_connect, list_android_devices
+
+router = APIRouter(prefix="/remote-devices", tags=["remote-devices"])
+
+
+async def get_remote_device_or_404(db: AsyncSession, remote_device_id: str) -> RemoteDevice:
+    device = await db.get(RemoteDevice, remote_device_id)
+    if not device:
+        raise HTTPException(status_code=404, detail="Remote device not found")
+    return device
+
+
+async def _connected_addresses() -> set:
+    try:
+        return {d.id for d in await list_android_devices() if d.status == "device"}
+    except HTTPException:
+        return set()  # adb isn't installed
+
+
+def _response(device: RemoteDevice, connected: set) -> RemoteDeviceResponse:
+    response = RemoteDeviceResponse.model_validate(device)
+    response.connected = device.address in connected
+    return response
+
+
+@router.get("", response_model=List[RemoteDeviceResponse])
+async def list_remote_devices(db: AsyncSession = Depends(get_db)):
+    """List saved network devices and whether adb currently sees them"""
+    result = await db.execute(select(RemoteDevice).order_by(RemoteDevice.name))
+    connected = await _connected_addresses()
+    return [_response(device, connected) for device in result.scalars().all()]
+
+
+@router.post("", response_model=RemoteDeviceResponse)
+async def create_remote_device(data: RemoteDeviceCreate, db: AsyncSession = Depends(get_db)):
+    """Save a network device endpoint, such as a lab phone"""
+    existing = await db.execute(
+        select(RemoteDevice).where(RemoteDevice.host == data.host, RemoteDevice.port == data.port)
+    )
+    if existing.scalars().first():
+        raise HTTPException(status_code=409, detail=f"{data.host}:{data.port} is already saved")
+    device = RemoteDevice(name=data.name or f"{data.host}:{data.port}", host=data.host, port=data.port)
+    db.add(device)
+    await db.commit()
+    await db.refresh(device)
+    return _response(device, await _connected_addresses())
+
+
+@router.put("/{remote_device_id}", response_model=RemoteDeviceResponse)
+async def update_remote_device(
+    remote_device_id: str, data: RemoteDeviceUpdate, db: AsyncSession = Depends(get_db)
+):
+    """Rename or re-address a saved network device"""
+    device = await get_remote_device_or_404(db, remote_device_id)
+    for field, value in data.model_dump(exclude_unset=True).items():
+        setattr(device, field, value)
+    await db.commit()
+    await db.refresh(device)
+    return _response(device, await _connected_addresses())
+
+
+@router.delete("/{remote_device_id}")
+async def delete_remote_device(remote_device_id: str, db: AsyncSession = Depends(get_db)):
+    """Forget a saved network device"""
+    device = await get_remote_device_or_404(db, remote_device_id)
+    await db.delete(device)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.post("/{remote_device_id}/connect", response_model=RemoteDeviceResponse)
+async def connect_remote_device(remote_device_id: str, db: AsyncSession = Depends(get_db)):
+    """Attach a saved network device"""
+    device = await get_remote_device_or_404(db, remote_device_id)
+    await adb_connect(AdbConnectRequest(address=device.address))
+    device.last_connected_at = datetime.utcnow()
+    await db.commit()
+    await db.refresh(device)
+    return _response(device, {device.address})
+
+
+@router.post("/connect-all")
+async def connect_all_remote_devices(db: AsyncSession = Depends(get_db)):
+    """Try to attach every saved network device, reporting each outcome"""
+    result = await db.execute(select(RemoteDevice).order_by(RemoteDevice.name))
+    outcomes = []
+    for device in result.scalars().all():
+        try:
+            await adb_connect(AdbConnectRequest(address=device.address))
+            device.last_connected_at = datetime.utcnow()
+            outcomes.append({"id": device.id, "address": device.address, "connected": True})
+        except HTTPException as e:
+            outcomes.append({"id": device.id, "address": device.address, "connected": False, "error": e.detail})
+    await db.commit()
+    return outcomes
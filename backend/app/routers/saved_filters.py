@@ -0,0 +1,118 @@
+import json
+from typing import List
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy import select
+from sqlalchemy.exc import IntegrityError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    FilterCondition,
+    Project,
+    SavedFilter,
+    SavedFilterCreate,
+    SavedFilterUpdate,
+    SavedFilterResponse,
+    TestCaseResponse,
+)
+from app.services.saved_filters import SavedFilterError, filter_query
+
+router = APIRouter(prefix="/saved-filters", tags=["saved-filters"])
+
+
+async def get_saved_filter_or_404(db: AsyncSession, filter_id: str) -> SavedFilter:
+    saved_filter = await db.get(SavedFilter, filter_id)
+    if not saved_filter:
+        raise HTTPException(status_code=404, detail="Saved filter not found")
+    return saved_filter
+
+
+async def check_filter(db: AsyncSession, project_id: str, conditions: List[FilterCondition], match: str) -> None:
+    """Compile a filter once so invalid ones are rejected when saved rather than when used"""
+    try:
+        await filter_query(db, project_id, conditions, match)
+    except SavedFilterError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.post("", response_model=SavedFilterResponse)
+async def save_filter(data: SavedFilterCreate, db: AsyncSession = Depends(get_db)):
+    """Save a named test case filter for everyone working on a project"""
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    await check_filter(db, data.project_id, data.conditions, data.match)
+
+    saved_filter = SavedFilter(
+        project_id=data.project_id,
+        name=data.name.strip(),
+        description=data.description,
+        conditions=json.dumps([condition.model_dump() for condition in data.conditions]),
+        match=data.match,
+    )
+    db.add(saved_filter)
+    try:
+        await db.commit()
+    except IntegrityError:
+        await db.rollback()
+        raise HTTPException(status_code=400, detail=f"The project already has a {data.name} filter")
+    await db.refresh(saved_filter)
+    return saved_filter
+
+
+@router.get("/project/{project_id}", response_model=List[SavedFilterResponse])
+async def list_filters(project_id: str, db: AsyncSession = Depends(get_db)):
+    """List a project's saved filters"""
+    result = await db.execute(
+        select(SavedFilter).where(SavedFilter.project_id == project_id).order_by(SavedFilter.name)
+    )
+    return result.scalars().all()
+
+
+@router.get("/{filter_id}/test-cases", response_model=List[TestCaseResponse])
+async def list_test_cases_by_filter(filter_id: str, db: AsyncSession = Depends(get_db)):
+    """List the test cases a saved filter currently matches"""
+    saved_filter = await get_saved_filter_or_404(db, filter_id)
+    conditions = [FilterCondition.model_validate(item) for item in json.loads(saved_filter.conditions)]
+    try:
+        query = await filter_query(db, saved_filter.project_id, conditions, saved_filter.match)
+    except SavedFilterError as e:
+        # A custom field the filter uses may have been deleted since it was saved
+        raise HTTPException(status_code=400, detail=str(e))
+    result = await db.execute(query)
+    return result.scalars().all()
+
+
+@router.put("/{filter_id}", response_model=SavedFilterResponse)
+async def update_filter(filter_id: str, data: SavedFilterUpdate, db: AsyncSession = Depends(get_db)):
+    """Update a saved filter"""
+    saved_filter = await get_saved_filter_or_404(db, filter_id)
+    update_data = data.model_dump(exclude_unset=True)
+    conditions = data.conditions if data.conditions is not None else [
+        FilterCondition.model_validate(item) for item in json.loads(saved_filter.conditions)
+    ]
+    await check_filter(db, saved_filter.project_id, conditions, data.match or saved_filter.match)
+
+    if "conditions" in update_data:
+        update_data["conditions"] = json.dumps([condition.model_dump() for condition in conditions])
+    if update_data.get("name") is not None:
+        update_data["name"] = update_data["name"].strip()
+    for key, value in update_data.items():
+        setattr(saved_filter, key, value)
+
+    try:
+        await db.commit()
+    except IntegrityError:
+        await db.rollback()
+        raise HTTPException(status_code=400, detail=f"The project already has a {data.name} filter")
+    await db.refresh(saved_filter)
+    return saved_filter
+
+
+@router.delete("/{filter_id}")
+async def delete_filter(filter_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a saved filter"""
+    saved_filter = await get_saved_filter_or_404(db, filter_id)
+    await db.delete(saved_filter)
+    await db.commit()
+    return {"status": "deleted"}
@@ -1,33 +1,88 @@
 import uuid
-from typing import List
+from typing import List, Optional
 
 from fastapi import APIRouter, Depends, HTTPException
-from sqlalchemy import select
+from pydantic import BaseModel
+from sqlalchemy import delete, select, func
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.db import get_db
+from app.errors import conflict_error
 from app.models import (
     Scenario,
     ScenarioCreate,
     ScenarioUpdate,
     ScenarioResponse,
     ScenarioWithSteps,
+    ScenarioDependency,
+    ScenarioDependencyCreate,
+    ScenarioDependencyResponse,
     Step,
     StepResponse,
+    SuiteScenario,
 )
+from app.services.budgets import BudgetError, check_budget
+from app.services.codegen import TARGETS, CodegenError, generate_spec
+from app.services.dependencies import DependencyError, validate_dependency
+from app.services.duplication import copy_scenario
+from app.services.ordering import move_after
+from app.services.scenario_validation import ScenarioValidation, ScenarioValidationError, validate_scenario
+from app.services.screenshots import check_policy
+from app.services.versioning import StaleWriteError, claim_version
 
 router = APIRouter(prefix="/scenarios", tags=["scenarios"])
 
 
+class GeneratedSpecResponse(BaseModel):
+    filename: str
+    target: str
+    language: str
+    content: str
+
+
+class MoveScenarioRequest(BaseModel):
+    """Schema for moving a scenario; a missing after_scenario_id moves it to the start"""
+
+    after_scenario_id: Optional[str] = None
+
+
+async def next_scenario_position(db: AsyncSession, test_case_id: str) -> float:
+    """Position after the last scenario of a test case"""
+    result = await db.execute(
+        select(func.max(Scenario.position)).where(Scenario.test_case_id == test_case_id)
+    )
+    return (result.scalar() or 0.0) + 1.0
+
+
+def check_scenario_budget_settings(max_duration_ms: Optional[int], budget_action: Optional[str]) -> None:
+    try:
+        check_budget(max_duration_ms, budget_action)
+    except BudgetError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+def check_screenshot_policy(policy: Optional[str]) -> None:
+    try:
+        check_policy(policy)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
 @router.post("", response_model=ScenarioResponse)
 async def create_scenario(data: ScenarioCreate, db: AsyncSession = Depends(get_db)):
     """Create a new scenario"""
+    check_scenario_budget_settings(data.max_duration_ms, data.budget_action)
+    check_screenshot_policy(data.screenshot_policy)
     scenario = Scenario(
         id=str(uuid.uuid4()),
         test_case_id=data.test_case_id,
         name=data.name,
         description=data.description,
         target_url=data.target_url,
+        position=await next_scenario_position(db, data.test_case_id),
+        max_duration_ms=data.max_duration_ms,
+        budget_action=data.budget_action,
+        screenshot_policy=data.screenshot_policy,
     )
     db.add(scenario)
     await db.commit()
@@ -41,11 +96,17 @@ async def list_scenarios_by_test_case(test_case_id: str, db: AsyncSession = Depe
     result = await db.execute(
         select(Scenario)
         .where(Scenario.test_case_id == test_case_id)
-        .order_by(Scenario.created_at)
+        .order_by(Scenario.position, Scenario.created_at)
     )
     return result.scalars().all()
 
 
+@router.get("/export-targets", response_model=List[str])
+async def list_export_targets():
+    """List the frameworks scenarios can be exported to"""
+    return list(TARGETS)
+
+
 @router.get("/{scenario_id}", response_model=ScenarioResponse)
 async def get_scenario(scenario_id: str, db: AsyncSession = Depends(get_db)):
     """Get a scenario by ID"""
@@ -75,6 +136,9 @@ async def get_scenario_with_steps(scenario_id: str, db: AsyncSession = Depends(g
         name=scenario.name,
         description=scenario.description,
         target_url=scenario.target_url,
+        max_duration_ms=scenario.max_duration_ms,
+        budget_action=scenario.budget_action,
+        screenshot_policy=scenario.screenshot_policy,
         created_at=scenario.created_at,
         updated_at=scenario.updated_at,
         steps=[StepResponse.model_validate(s) for s in steps],
@@ -92,6 +156,13 @@ async def update_scenario(
         raise HTTPException(status_code=404, detail="Scenario not found")
 
     update_data = data.model_dump(exclude_unset=True)
+    check_scenario_budget_settings(update_data.get("max_duration_ms"), update_data.get("budget_action"))
+    check_screenshot_policy(update_data.get("screenshot_policy"))
+    try:
+        await claim_version(db, scenario, update_data.pop("expected_updated_at", None))
+    except StaleWriteError:
+        current = ScenarioResponse.model_validate(scenario).model_dump(mode="json")
+        raise conflict_error("The scenario was changed since it was loaded", current)
     for key, value in update_data.items():
         setattr(scenario, key, value)
 
@@ -108,6 +179,13 @@ async def delete_scenario(scenario_id: str, db: AsyncSession = Depends(get_db)):
     if not scenario:
         raise HTTPException(status_code=404, detail="Scenario not found")
 
+    await db.execute(
+        delete(ScenarioDependency).where(
+            (ScenarioDependency.scenario_id == scenario_id)
+            | (ScenarioDependency.depends_on_id == scenario_id)
+        )
+    )
+    await db.execute(delete(SuiteScenario).where(SuiteScenario.scenario_id == scenario_id))
     await db.delete(scenario)
     await db.commit()
     return {"status": "deleted"}
@@ -123,34 +201,121 @@ async def duplicate_scenario(
     if not scenario:
         raise HTTPException(status_code=404, detail="Scenario not found")
 
-    # Create new scenario
-    new_scenario = Scenario(
-        id=str(uuid.uuid4()),
-        test_case_id=scenario.test_case_id,
-        name=new_name or f"{scenario.name} (Copy)",
-        description=scenario.description,
-        target_url=scenario.target_url,
+    new_scenario = await copy_scenario(
+        db, scenario, scenario.test_case_id, name=new_name or f"{scenario.name} (Copy)"
     )
-    db.add(new_scenario)
-    await db.flush()
+    new_scenario.position = await next_scenario_position(db, scenario.test_case_id)
 
-    # Duplicate steps
-    steps_result = await db.execute(
-        select(Step).where(Step.scenario_id == scenario_id).order_by(Step.step_order)
+    await db.commit()
+    await db.refresh(new_scenario)
+    return new_scenario
+
+
+@router.get("/{scenario_id}/export/{target}", response_model=GeneratedSpecResponse)
+async def export_scenario(
+    scenario_id: str, target: str, platform: str = "android", db: AsyncSession = Depends(get_db)
+):
+    """Generate a standalone test file for a scenario; platform picks Android or iOS for Appium"""
+    scenario = await db.get(Scenario, scenario_id)
+    if not scenario:
+        raise HTTPException(status_code=404, detail="Scenario not found")
+    try:
+        spec = await generate_spec(db, scenario, target, platform)
+    except CodegenError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return GeneratedSpecResponse(**vars(spec))
+
+
+@router.get("/{scenario_id}/validate", response_model=ScenarioValidation)
+async def validate_scenario_steps(scenario_id: str, db: AsyncSession = Depends(get_db)):
+    """Check a scenario's steps without running them, for the editor to show inline"""
+    try:
+        return await validate_scenario(db, scenario_id)
+    except ScenarioValidationError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+
+
+@router.post("/{scenario_id}/move", response_model=ScenarioResponse)
+async def move_scenario(
+    scenario_id: str, data: MoveScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Move a scenario directly after another one in its test case"""
+    result = await db.execute(select(Scenario).where(Scenario.id == scenario_id))
+    scenario = result.scalar_one_or_none()
+    if not scenario:
+        raise HTTPException(status_code=404, detail="Scenario not found")
+
+    try:
+        await move_after(
+            db,
+            Scenario.position,
+            Scenario.test_case_id == scenario.test_case_id,
+            scenario,
+            data.after_scenario_id,
+        )
+    except ValueError:
+        raise HTTPException(status_code=404, detail="Target scenario not found in this test case")
+
+    await db.commit()
+    await db.refresh(scenario)
+    return scenario
+
+
+@router.get("/{scenario_id}/dependencies", response_model=List[ScenarioDependencyResponse])
+async def list_scenario_dependencies(scenario_id: str, db: AsyncSession = Depends(get_db)):
+    """List the scenarios a scenario depends on"""
+    result = await db.execute(
+        select(ScenarioDependency).where(ScenarioDependency.scenario_id == scenario_id)
     )
-    steps = steps_result.scalars().all()
+    return result.scalars().all()
 
-    for step in steps:
-        new_step = Step(
-            id=str(uuid.uuid4()),
-            scenario_id=new_scenario.id,
-            step_order=step.step_order,
-            step_type=step.step_type,
-            label=step.label,
-            config=step.config,
+
+@router.post("/{scenario_id}/dependencies", response_model=ScenarioDependencyResponse)
+async def add_scenario_dependency(
+    scenario_id: str, data: ScenarioDependencyCreate, db: AsyncSession = Depends(get_db)
+):
+    """Make a scenario depend on another scenario passing first"""
+    for required_id in (scenario_id, data.depends_on_id):
+        if not await db.get(Scenario, required_id):
+            raise HTTPException(status_code=404, detail="Scenario not found")
+
+    result = await db.execute(
+        select(ScenarioDependency).where(
+            ScenarioDependency.scenario_id == scenario_id,
+            ScenarioDependency.depends_on_id == data.depends_on_id,
         )
-        db.add(new_step)
+    )
+    existing = result.scalar_one_or_none()
+    if existing:
+        return existing
 
+    try:
+        await validate_dependency(db, scenario_id, data.depends_on_id)
+    except DependencyError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    dependency = ScenarioDependency(scenario_id=scenario_id, depends_on_id=data.depends_on_id)
+    db.add(dependency)
     await db.commit()
-    await db.refresh(new_scenario)
-    return new_scenario
+    await db.refresh(dependency)
+    return dependency
+
+
+@router.delete("/{scenario_id}/dependencies/{depends_on_id}")
+async def remove_scenario_dependency(
+    scenario_id: str, depends_on_id: str, db: AsyncSession = Depends(get_db)
+):
+    """Remove a scenario dependency"""
+    result = await db.execute(
+        select(ScenarioDependency).where(
+            ScenarioDependency.scenario_id == scenario_id,
+            ScenarioDependency.depends_on_id == depends_on_id,
+        )
+    )
+    dependency = result.scalar_one_or_none()
+    if not dependency:
+        raise HTTPException(status_code=404, detail="Dependency not found")
+
+    await db.delete(dependency)
+    await db.commit()
+    return {"status": "deleted"}
@@ -0,0 +1,110 @@
+from datetime import datetime
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException, Query
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    RunSchedule,
+    RunScheduleCreate,
+    RunScheduleUpdate,
+    RunScheduleResponse,
+    Suite,
+    TestRunResponse,
+    UpcomingRun,
+)
+from app.services.schedules import ScheduleError, next_run_time, trigger_schedule, upcoming_runs, validate_schedule
+
+router = APIRouter(prefix="/schedules", tags=["schedules"])
+
+
+async def get_schedule_or_404(db: AsyncSession, schedule_id: str) -> RunSchedule:
+    schedule = await db.get(RunSchedule, schedule_id)
+    if not schedule:
+        raise HTTPException(status_code=404, detail="Schedule not found")
+    return schedule
+
+
+def check_schedule(schedule: RunSchedule) -> None:
+    """Validate a schedule and work out when it next runs"""
+    try:
+        validate_schedule(schedule)
+        schedule.next_run_at = next_run_time(schedule.cron, schedule.timezone, datetime.utcnow())
+    except ScheduleError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.post("", response_model=RunScheduleResponse)
+async def create_schedule(data: RunScheduleCreate, db: AsyncSession = Depends(get_db)):
+    """Create a schedule that runs a suite"""
+    suite = await db.get(Suite, data.suite_id)
+    if not suite:
+        raise HTTPException(status_code=404, detail="Suite not found")
+    schedule = RunSchedule(project_id=suite.project_id, **data.model_dump())
+    check_schedule(schedule)
+    db.add(schedule)
+    await db.commit()
+    await db.refresh(schedule)
+    return schedule
+
+
+@router.get("/project/{project_id}", response_model=List[RunScheduleResponse])
+async def list_schedules(project_id: str, db: AsyncSession = Depends(get_db)):
+    """List a project's schedules"""
+    result = await db.execute(
+        select(RunSchedule).where(RunSchedule.project_id == project_id).order_by(RunSchedule.name)
+    )
+    return result.scalars().all()
+
+
+@router.get("/upcoming", response_model=List[UpcomingRun])
+async def list_upcoming_runs(
+    project_id: Optional[str] = Query(None),
+    limit: int = Query(20, ge=1, le=200),
+    db: AsyncSession = Depends(get_db),
+):
+    """List the next scheduled runs, soonest first"""
+    query = select(RunSchedule).where(RunSchedule.enabled.is_(True))
+    if project_id:
+        query = query.where(RunSchedule.project_id == project_id)
+    result = await db.execute(query)
+    return upcoming_runs(result.scalars().all(), limit)
+
+
+@router.get("/{schedule_id}", response_model=RunScheduleResponse)
+async def get_schedule(schedule_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a schedule by ID"""
+    return await get_schedule_or_404(db, schedule_id)
+
+
+@router.put("/{schedule_id}", response_model=RunScheduleResponse)
+async def update_schedule(schedule_id: str, data: RunScheduleUpdate, db: AsyncSession = Depends(get_db)):
+    """Update a schedule, recalculating its next run"""
+    schedule = await get_schedule_or_404(db, schedule_id)
+    for key, value in data.model_dump(exclude_unset=True).items():
+        setattr(schedule, key, value)
+    check_schedule(schedule)
+    await db.commit()
+    await db.refresh(schedule)
+    return schedule
+
+
+@router.delete("/{schedule_id}")
+async def delete_schedule(schedule_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a schedule"""
+    schedule = await get_schedule_or_404(db, schedule_id)
+    await db.delete(schedule)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.post("/{schedule_id}/run", response_model=TestRunResponse)
+async def run_schedule_now(schedule_id: str, db: AsyncSession = Depends(get_db)):
+    """Run a schedule's suite now without changing its next run"""
+    schedule = await get_schedule_or_404(db, schedule_id)
+    try:
+        return await trigger_schedule(db, schedule)
+    except ScheduleError as e:
+        raise HTTPException(status_code=400, detail=str(e))
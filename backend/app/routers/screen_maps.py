@@ -0,0 +1,93 @@
+from pathlib import Path
+
+from fastapi import APIRouter, Depends, HTTPException
+from fastapi.responses import FileResponse
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import AppCoverage, AppScreen, CrawlRequest, CrawlStatus, Project, ScreenMap
+from app.services.app_coverage import CoverageError, get_app_coverage
+from app.services.app_crawler import (
+    CrawlError,
+    delete_screen_map,
+    get_crawl_status,
+    get_screen_map,
+    start_crawl,
+    stop_crawl,
+)
+
+router = APIRouter(prefix="/screen-maps", tags=["screen-maps"])
+
+
+@router.get("/{project_id}", response_model=ScreenMap)
+async def read_screen_map(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a project's crawled screens and the taps between them"""
+    if not await db.get(Project, project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    return await get_screen_map(db, project_id)
+
+
+@router.get("/{project_id}/coverage", response_model=AppCoverage)
+async def read_app_coverage(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Get which crawled screens and elements the project's scenarios exercise"""
+    try:
+        return await get_app_coverage(db, project_id)
+    except CoverageError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+
+
+@router.delete("/{project_id}")
+async def remove_screen_map(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a project's crawled screens, transitions and screenshots"""
+    try:
+        await delete_screen_map(db, project_id)
+    except CrawlError as e:
+        raise HTTPException(status_code=409, detail=str(e))
+    return {"status": "deleted"}
+
+
+@router.get("/screens/{screen_id}/screenshot")
+async def get_screen_screenshot(screen_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a crawled screen's screenshot as PNG"""
+    screen = await db.get(AppScreen, screen_id)
+    if not screen:
+        raise HTTPException(status_code=404, detail="Screen not found")
+    if not screen.screenshot_path or not Path(screen.screenshot_path).is_file():
+        raise HTTPException(status_code=404, detail="Screenshot file is missing")
+    return FileResponse(screen.screenshot_path, media_type="image/png")
+
+
+@router.get("/screens/{screen_id}/ui-dump")
+async def get_screen_ui_dump(screen_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a crawled screen's UI hierarchy dump"""
+    screen = await db.get(AppScreen, screen_id)
+    if not screen:
+        raise HTTPException(status_code=404, detail="Screen not found")
+    return {"xml": screen.ui_dump or ""}
+
+
+@router.post("/{project_id}/crawl", response_model=CrawlStatus)
+async def start_project_crawl(project_id: str, data: CrawlRequest, db: AsyncSession = Depends(get_db)):
+    """Start crawling the app from the device's current screen, reporting progress as 'crawl:*' events"""
+    try:
+        return await start_crawl(db, project_id, data)
+    except CrawlError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.get("/{project_id}/crawl", response_model=CrawlStatus)
+async def read_crawl_status(project_id: str):
+    """Get the state of a project's current or last crawl"""
+    status = get_crawl_status(project_id)
+    if not status:
+        raise HTTPException(status_code=404, detail="No crawl for this project")
+    return status
+
+
+@router.post("/{project_id}/crawl/stop", response_model=CrawlStatus)
+async def stop_project_crawl(project_id: str):
+    """Stop a project's crawl, keeping the screens found so far"""
+    status = await stop_crawl(project_id)
+    if not status:
+        raise HTTPException(status_code=404, detail="No crawl for this project")
+    return status
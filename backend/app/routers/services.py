@@ -3,10 +3,11 @@ from typing import List, Optional
 from datetime import datetime
 
 import httpx
-from fastapi import APIRouter
+from fastapi import APIRouter, HTTPException
 from pydantic import BaseModel
 
-from app.config import settings
+from app.services.service_urls import get_service_urls as resolve_service_urls
+from app.services.service_manager import ManagedService, ServiceError, service_manager
 
 router = APIRouter(prefix="/services", tags=["services"])
 
@@ -20,6 +21,18 @@ class ServiceHealth(BaseModel):
     checked_at: float
 
 
+class ServiceState(BaseModel):
+    name: str
+    status: str
+    command: str
+    pid: Optional[int] = None
+    uptime_seconds: Optional[float] = None
+    restarts: int
+    last_exit_code: Optional[int] = None
+    error: Optional[str] = None
+    log_path: str
+
+
 class ServiceUrls(BaseModel):
     ai_agent: str
     test_runner: str
@@ -69,10 +82,7 @@ async def check_service(name: str, url: str) -> ServiceHealth:
 @router.get("/health/{service_name}", response_model=ServiceHealth)
 async def check_service_health(service_name: str):
     """Check health of a specific service"""
-    service_urls = {
-        "ai_agent": settings.ai_agent_url,
-        "test_runner": settings.test_runner_url,
-    }
+    service_urls = await resolve_service_urls()
 
     if service_name not in service_urls:
         return ServiceHealth(
@@ -88,13 +98,8 @@ async def check_service_health(service_name: str):
 @router.get("/health", response_model=List[ServiceHealth])
 async def check_all_services_health():
     """Check health of all services"""
-    services = [
-        ("ai_agent", settings.ai_agent_url),
-        ("test_runner", settings.test_runner_url),
-    ]
-
     results = []
-    for name, url in services:
+    for name, url in (await resolve_service_urls()).items():
         health = await check_service(name, url)
         results.append(health)
 
@@ -104,7 +109,74 @@ async def check_all_services_health():
 @router.get("/urls", response_model=ServiceUrls)
 async def get_service_urls():
     """Get service URLs"""
-    return ServiceUrls(
-        ai_agent=settings.ai_agent_url,
-        test_runner=settings.test_runner_url,
+    return ServiceUrls(**await resolve_service_urls())
+
+
+# ============================================
+# Service Lifecycle
+# ============================================
+
+
+def to_service_state(service: ManagedService) -> ServiceState:
+    return ServiceState(
+        name=service.name,
+        status=service.status,
+        command=service.command,
+        pid=service.pid,
+        uptime_seconds=service.uptime_seconds,
+        restarts=service.restarts,
+        last_exit_code=service.last_exit_code,
+        error=service.error,
+        log_path=str(service.log_path),
     )
+
+
+def get_managed_service(service_name: str) -> ManagedService:
+    try:
+        return service_manager.get(service_name)
+    except ServiceError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+
+
+@router.get("/state", response_model=List[ServiceState])
+async def list_service_states():
+    """Get process state of all managed services"""
+    return [to_service_state(service) for service in service_manager.all()]
+
+
+@router.post("/{service_name}/start", response_model=ServiceState)
+async def start_service(service_name: str):
+    """Start a managed service as a child process"""
+    service = get_managed_service(service_name)
+    try:
+        await service.start()
+    except ServiceError as e:
+        raise HTTPException(status_code=500, detail=str(e))
+    return to_service_state(service)
+
+
+@router.post("/{service_name}/stop", response_model=ServiceState)
+async def stop_service(service_name: str):
+    """Stop a managed service"""
+    service = get_managed_service(service_name)
+    await service.stop()
+    return to_service_state(service)
+
+
+@router.post("/{service_name}/restart", response_model=ServiceState)
+async def restart_service(service_name: str):
+    """Restart a managed service"""
+    service = get_managed_service(service_name)
+    try:
+        await service.restart()
+    except ServiceError as e:
+        raise HTTPException(status_code=500, detail=str(e))
+    return to_service_state(service)
+
+
+@router.get("/monitor")
+async def get_monitored_statuses():
+    """Get the last status the background health monitor reported for each service"""
+    from app.services.health_monitor import health_monitor
+
+    return health_monitor.statuses
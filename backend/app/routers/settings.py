@@ -0,0 +1,57 @@
+from typing import Any, Dict
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.services.app_settings import (
+    SettingError,
+    get_all_settings,
+    get_setting,
+    reset_setting,
+    update_settings,
+)
+from app.services.tools import set_tool_overrides
+from app.services.tracing import tracer
+
+router = APIRouter(prefix="/settings", tags=["settings"])
+
+
+@router.get("")
+async def list_settings(db: AsyncSession = Depends(get_db)) -> Dict[str, Any]:
+    """Get all settings with defaults applied"""
+    return await get_all_settings(db)
+
+
+@router.put("")
+async def set_settings(values: Dict[str, Any], db: AsyncSession = Depends(get_db)) -> Dict[str, Any]:
+    """Update one or more settings"""
+    try:
+        updated = await update_settings(db, values)
+    except SettingError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    set_tool_overrides(updated)
+    tracer.configure(updated)
+    return updated
+
+
+@router.get("/{key}")
+async def read_setting(key: str, db: AsyncSession = Depends(get_db)):
+    """Get a single setting"""
+    try:
+        return {"key": key, "value": await get_setting(db, key)}
+    except SettingError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+
+
+@router.delete("/{key}")
+async def delete_setting(key: str, db: AsyncSession = Depends(get_db)):
+    """Reset a setting to its default"""
+    try:
+        value = await reset_setting(db, key)
+    except SettingError as e:
+        raise HTTPException(status_code=404, detail=str(e))
+    values = await get_all_settings(db)
+    set_tool_overrides(values)
+    tracer.configure(values)
+    return {"key": key, "value": value}
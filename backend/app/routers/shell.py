@@ -0,0 +1,35 @@
+from typing import List, Optional
+
+from fastapi import APIRouter, HTTPException
+from pydantic import BaseModel
+
+from app.services.shell_steps import list_confirmations, resolve_confirmation
+
+router = APIRouter(prefix="/shell", tags=["shell"])
+
+
+class ShellConfirmation(BaseModel):
+    id: str
+    command: str
+    test_run_id: Optional[str]
+
+
+class ConfirmShellRequest(BaseModel):
+    approved: bool
+
+
+@router.get("/confirmations", response_model=List[ShellConfirmation])
+async def get_shell_confirmations():
+    """List shell commands waiting for approval because they aren't allowlisted"""
+    return [
+        ShellConfirmation(id=p.id, command=p.command, test_run_id=p.test_run_id)
+        for p in list_confirmations()
+    ]
+
+
+@router.post("/confirmations/{confirmation_id}")
+async def confirm_shell_command(confirmation_id: str, data: ConfirmShellRequest):
+    """Approve or deny a waiting shell command"""
+    if not resolve_confirmation(confirmation_id, data.approved):
+        raise HTTPException(status_code=404, detail="No command is waiting for this confirmation")
+    return {"success": True, "approved": data.approved}
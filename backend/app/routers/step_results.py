@@ -1,35 +1,45 @@
-import uuid
+import json
 from typing import List
 
-from fastapi import APIRouter, Depends
+from fastapi import APIRouter, Depends, HTTPException, Query
+from fastapi.responses import FileResponse
 from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.db import get_db
-from app.models import StepResult, StepResultCreate, StepResultResponse
+from app.models import SlowStep, Step, StepResponse, StepResult, StepResultCreate, StepResultResponse
+from app.services.budgets import slowest_steps
+from app.services.result_writer import insert_step_results, step_result_row
+from app.services.screenshots import ScreenshotStats, media_type, screenshot_stats, stored_screenshot
 
 router = APIRouter(prefix="/step-results", tags=["step-results"])
 
+MAX_BULK_RESULTS = 1000
+
 
 @router.post("", response_model=StepResultResponse)
 async def create_step_result(data: StepResultCreate, db: AsyncSession = Depends(get_db)):
     """Create a new step result"""
-    step_result = StepResult(
-        id=str(uuid.uuid4()),
-        test_run_id=data.test_run_id,
-        step_id=data.step_id,
-        test_case_id=data.test_case_id,
-        status=data.status,
-        duration_ms=data.duration_ms,
-        error_message=data.error_message,
-        screenshot_path=data.screenshot_path,
-    )
+    step_result = StepResult(**step_result_row(data))
     db.add(step_result)
     await db.commit()
     await db.refresh(step_result)
     return step_result
 
 
+@router.post("/bulk", response_model=List[StepResultResponse])
+async def bulk_create_step_results(results: List[StepResultCreate], db: AsyncSession = Depends(get_db)):
+    """Create several step results with one multi-row insert, in the order given"""
+    if len(results) > MAX_BULK_RESULTS:
+        raise HTTPException(status_code=400, detail=f"Send at most {MAX_BULK_RESULTS} results at a time")
+    rows = [step_result_row(data) for data in results]
+    await insert_step_results(db, rows)
+    await db.commit()
+    created = await db.execute(select(StepResult).where(StepResult.id.in_([row["id"] for row in rows])))
+    by_id = {step_result.id: step_result for step_result in created.scalars().all()}
+    return [by_id[row["id"]] for row in rows]
+
+
 @router.get("/test-run/{test_run_id}", response_model=List[StepResultResponse])
 async def list_step_results(test_run_id: str, db: AsyncSession = Depends(get_db)):
     """List all step results for a test run"""
@@ -39,3 +49,60 @@ async def list_step_results(test_run_id: str, db: AsyncSession = Depends(get_db)
         .order_by(StepResult.created_at)
     )
     return result.scalars().all()
+
+
+@router.get("/project/{project_id}/slowest", response_model=List[SlowStep])
+async def list_slowest_steps(
+    project_id: str,
+    days: int = Query(30, ge=1, le=365),
+    limit: int = Query(10, ge=1, le=100),
+    db: AsyncSession = Depends(get_db),
+):
+    """List a project's slowest steps over recent runs, with each one's daily average duration"""
+    return await slowest_steps(db, project_id, days, limit)
+
+
+@router.get("/project/{project_id}/screenshot-stats", response_model=ScreenshotStats)
+async def get_screenshot_stats(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Get the space a project's step screenshots use and how much compression saved"""
+    return await screenshot_stats(db, project_id)
+
+
+@router.get("/{step_result_id}/screenshot")
+async def get_step_screenshot(step_result_id: str, db: AsyncSession = Depends(get_db)):
+    """Get the screenshot captured after a step"""
+    step_result = await db.get(StepResult, step_result_id)
+    if not step_result:
+        raise HTTPException(status_code=404, detail="Step result not found")
+    path = stored_screenshot(step_result.screenshot_path)
+    if not path:
+        raise HTTPException(status_code=404, detail="Screenshot file is missing")
+    return FileResponse(path, media_type=media_type(path.name))
+
+
+@router.post("/{step_result_id}/accept-heal", response_model=StepResponse)
+async def accept_healed_locator(step_result_id: str, db: AsyncSession = Depends(get_db)):
+    """Apply a healed locator suggestion to the step definition"""
+    result = await db.execute(select(StepResult).where(StepResult.id == step_result_id))
+    step_result = result.scalar_one_or_none()
+    if not step_result:
+        raise HTTPException(status_code=404, detail="Step result not found")
+    if not step_result.healed or not step_result.healed_locator:
+        raise HTTPException(status_code=400, detail="Step result has no healed locator")
+
+    result = await db.execute(select(Step).where(Step.id == step_result.step_id))
+    step = result.scalar_one_or_none()
+    if not step:
+        raise HTTPException(status_code=404, detail="Step not found")
+
+    config = json.loads(step.config or "{}")
+    healed_locator = json.loads(step_result.healed_locator)
+    # Coordinates replace the selector they were healed from
+    if "selector" not in healed_locator:
+        config.pop("selector", None)
+    config.update(healed_locator)
+    step.config = json.dumps(config)
+
+    await db.commit()
+    await db.refresh(step)
+    return step
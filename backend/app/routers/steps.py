@@ -1,17 +1,62 @@
 import uuid
 import json
-from typing import List
+from datetime import datetime
+from typing import Any, Dict, List, Optional
 
 from fastapi import APIRouter, Depends, HTTPException
-from sqlalchemy import select
+from pydantic import BaseModel
+from sqlalchemy import case, delete, insert, select, update
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.db import get_db
-from app.models import Step, StepCreate, StepUpdate, StepResponse, StepConfig
+from app.errors import conflict_error
+from app.models import Scenario, Step, StepCreate, StepUpdate, StepResponse, StepConfig, StepResult, TestCase
+from app.services.coordinates import normalize_config
+from app.services.healing import heal_locator
+from app.services.ordering import move_after
+from app.services.single_step import SingleStepRequest, SingleStepResult, execute_single_step
+from app.services.versioning import StaleWriteError, claim_version
 
 router = APIRouter(prefix="/steps", tags=["steps"])
 
 
+class HealStepRequest(BaseModel):
+    """Schema for healing a step locator that failed at runtime"""
+
+    screenshot_base64: str
+    page_html: Optional[str] = None
+
+
+class HealStepResponse(BaseModel):
+    healed: bool
+    locator: Optional[Dict[str, Any]] = None
+
+
+class MoveStepRequest(BaseModel):
+    """Schema for moving a step; a missing after_step_id moves it to the start"""
+
+    after_step_id: Optional[str] = None
+
+
+class BreakpointRequest(BaseModel):
+    enabled: bool
+
+
+class NormalizeCoordinatesRequest(BaseModel):
+    """
+    Schema for converting stored pixel coordinates to screen fractions
+
+    Steps without a recorded reference resolution are assumed to have been
+    recorded at width x height, which defaults to the 1080x1920 screen AI
+    suggestions have assumed.
+    """
+
+    scenario_id: Optional[str] = None
+    project_id: Optional[str] = None
+    width: int = 1080
+    height: int = 1920
+
+
 @router.post("", response_model=StepResponse)
 async def create_step(data: StepCreate, db: AsyncSession = Depends(get_db)):
     """Create a new step"""
@@ -60,6 +105,11 @@ async def update_step(
         raise HTTPException(status_code=404, detail="Step not found")
 
     update_data = data.model_dump(exclude_unset=True)
+    try:
+        await claim_version(db, step, update_data.pop("expected_updated_at", None))
+    except StaleWriteError:
+        current = StepResponse.model_validate(step).model_dump(mode="json")
+        raise conflict_error("The step was changed since it was loaded", current)
     if "config" in update_data and update_data["config"]:
         update_data["config"] = json.dumps(update_data["config"])
 
@@ -73,20 +123,68 @@ async def update_step(
 
 @router.put("/{step_id}/config", response_model=StepResponse)
 async def update_step_config(
-    step_id: str, config: StepConfig, db: AsyncSession = Depends(get_db)
+    step_id: str,
+    config: StepConfig,
+    expected_updated_at: Optional[datetime] = None,
+    db: AsyncSession = Depends(get_db),
 ):
-    """Update just the config of a step"""
+    """Update just the config of a step, refusing if it changed since expected_updated_at"""
     result = await db.execute(select(Step).where(Step.id == step_id))
     step = result.scalar_one_or_none()
     if not step:
         raise HTTPException(status_code=404, detail="Step not found")
 
+    try:
+        await claim_version(db, step, expected_updated_at)
+    except StaleWriteError:
+        current = StepResponse.model_validate(step).model_dump(mode="json")
+        raise conflict_error("The step was changed since it was loaded", current)
     step.config = json.dumps(config.model_dump())
     await db.commit()
     await db.refresh(step)
     return step
 
 
+@router.post("/{step_id}/try", response_model=SingleStepResult)
+async def try_step(step_id: str, data: SingleStepRequest, db: AsyncSession = Depends(get_db)):
+    """Run just this step on a device or in a browser and return its outcome, without a test run"""
+    if not await db.get(Step, step_id):
+        raise HTTPException(status_code=404, detail="Step not found")
+    try:
+        return await execute_single_step(db, step_id, data)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.put("/{step_id}/breakpoint", response_model=StepResponse)
+async def set_breakpoint(step_id: str, data: BreakpointRequest, db: AsyncSession = Depends(get_db)):
+    """Set or clear a step's breakpoint, where debug runs pause"""
+    step = await db.get(Step, step_id)
+    if not step:
+        raise HTTPException(status_code=404, detail="Step not found")
+
+    # A breakpoint isn't an edit, so it keeps updated_at and doesn't stale open editors
+    await db.execute(
+        update(Step).where(Step.id == step_id).values(breakpoint=data.enabled, updated_at=Step.updated_at)
+    )
+    await db.commit()
+    await db.refresh(step)
+    return step
+
+
+@router.delete("/bulk")
+async def bulk_delete_steps(step_ids: List[str], db: AsyncSession = Depends(get_db)):
+    """Delete multiple steps and their results in one transaction"""
+    if not step_ids:
+        return {"deleted": 0}
+
+    # SQLite doesn't enforce ON DELETE CASCADE here, so remove dependent results explicitly
+    await db.execute(delete(StepResult).where(StepResult.step_id.in_(step_ids)))
+    result = await db.execute(delete(Step).where(Step.id.in_(step_ids)))
+    await db.commit()
+    return {"deleted": result.rowcount}
+
+
 @router.delete("/{step_id}")
 async def delete_step(step_id: str, db: AsyncSession = Depends(get_db)):
     """Delete a step"""
@@ -105,21 +203,69 @@ async def reorder_steps(
     scenario_id: str, step_ids: List[str], db: AsyncSession = Depends(get_db)
 ):
     """Reorder steps in a scenario"""
-    for order, step_id in enumerate(step_ids):
-        result = await db.execute(
-            select(Step).where(Step.id == step_id, Step.scenario_id == scenario_id)
+    if step_ids:
+        await db.execute(
+            update(Step)
+            .where(Step.scenario_id == scenario_id, Step.id.in_(step_ids))
+            .values(
+                step_order=case(
+                    {step_id: order for order, step_id in enumerate(step_ids)}, value=Step.id
+                )
+            )
+            .execution_options(synchronize_session=False)
         )
-        step = result.scalar_one_or_none()
-        if step:
-            step.step_order = order
-
     await db.commit()
     return {"status": "reordered"}
 
 
-@router.post("/bulk", response_model=List[StepResponse])
-async def bulk_create_steps(steps: List[StepCreate], db: AsyncSession = Depends(get_db)):
-    """Create multiple steps at once"""
+@router.post("/normalize-coordinates")
+async def normalize_step_coordinates(data: NormalizeCoordinatesRequest, db: AsyncSession = Depends(get_db)):
+    """Migrate steps with absolute coordinates to the normalized mode"""
+    if data.width <= 0 or data.height <= 0:
+        raise HTTPException(status_code=400, detail="Width and height must be positive")
+    query = select(Step)
+    if data.scenario_id:
+        query = query.where(Step.scenario_id == data.scenario_id)
+    if data.project_id:
+        query = (
+            query.join(Scenario, Step.scenario_id == Scenario.id)
+            .join(TestCase, Scenario.test_case_id == TestCase.id)
+            .where(TestCase.project_id == data.project_id)
+        )
+    result = await db.execute(query)
+
+    updated = 0
+    for step in result.scalars().all():
+        normalized = normalize_config(json.loads(step.config or "{}"), data.width, data.height)
+        if normalized:
+            step.config = json.dumps(normalized)
+            updated += 1
+    await db.commit()
+    return {"updated": updated}
+
+
+@router.post("/{step_id}/move", response_model=StepResponse)
+async def move_step(step_id: str, data: MoveStepRequest, db: AsyncSession = Depends(get_db)):
+    """Move a step directly after another step, rewriting only the moved step"""
+    result = await db.execute(select(Step).where(Step.id == step_id))
+    step = result.scalar_one_or_none()
+    if not step:
+        raise HTTPException(status_code=404, detail="Step not found")
+
+    try:
+        await move_after(
+            db, Step.step_order, Step.scenario_id == step.scenario_id, step, data.after_step_id
+        )
+    except ValueError:
+        raise HTTPException(status_code=404, detail="Target step not found in this scenario")
+
+    await db.commit()
+    await db.refresh(step)
+    return step
+
+
+def add_steps(db: AsyncSession, steps: List[StepCreate]) -> List[Step]:
+    """Add steps to the session without committing"""
     created_steps = []
     for data in steps:
         config_json = json.dumps(data.config.model_dump() if data.config else {})
@@ -133,23 +279,50 @@ async def bulk_create_steps(steps: List[StepCreate], db: AsyncSession = Depends(
         )
         db.add(step)
         created_steps.append(step)
-
-    await db.commit()
-    for step in created_steps:
-        await db.refresh(step)
     return created_steps
 
 
-@router.delete("/bulk")
-async def bulk_delete_steps(step_ids: List[str], db: AsyncSession = Depends(get_db)):
-    """Delete multiple steps at once"""
-    deleted_count = 0
-    for step_id in step_ids:
-        result = await db.execute(select(Step).where(Step.id == step_id))
-        step = result.scalar_one_or_none()
-        if step:
-            await db.delete(step)
-            deleted_count += 1
+@router.post("/bulk", response_model=List[StepResponse])
+async def bulk_create_steps(steps: List[StepCreate], db: AsyncSession = Depends(get_db)):
+    """Create multiple steps at once in a single multi-row insert"""
+    if not steps:
+        return []
 
+    rows = [
+        {
+            "id": str(uuid.uuid4()),
+            "scenario_id": data.scenario_id,
+            "step_order": data.step_order,
+            "step_type": data.step_type,
+            "label": data.label,
+            "config": json.dumps(data.config.model_dump() if data.config else {}),
+        }
+        for data in steps
+    ]
+    await db.execute(insert(Step).values(rows))
     await db.commit()
-    return {"deleted": deleted_count}
+
+    ids = [row["id"] for row in rows]
+    result = await db.execute(select(Step).where(Step.id.in_(ids)))
+    created = {step.id: step for step in result.scalars().all()}
+    return [created[step_id] for step_id in ids]
+
+
+@router.post("/{step_id}/heal", response_model=HealStepResponse)
+async def heal_step_locator(
+    step_id: str, request: HealStepRequest, db: AsyncSession = Depends(get_db)
+):
+    """Find a replacement locator for a step using its element description"""
+    result = await db.execute(select(Step).where(Step.id == step_id))
+    step = result.scalar_one_or_none()
+    if not step:
+        raise HTTPException(status_code=404, detail="Step not found")
+
+    config = json.loads(step.config or "{}")
+    if not config.get("element_description"):
+        raise HTTPException(status_code=400, detail="Step has no element description to heal from")
+
+    locator = await heal_locator(
+        step.step_type, config, request.screenshot_base64, request.page_html
+    )
+    return HealStepResponse(healed=locator is not None, locator=locator)
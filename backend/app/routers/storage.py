@@ -0,0 +1,27 @@
+from typing import Optional
+
+from fastapi import APIRouter, Depends, Query
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.services.storage import CleanupResult, StorageUsage, apply_retention, get_storage_usage
+
+router = APIRouter(prefix="/storage", tags=["storage"])
+
+
+@router.get("/usage", response_model=StorageUsage)
+async def storage_usage(project_id: Optional[str] = Query(None), db: AsyncSession = Depends(get_db)):
+    """Get disk usage per project, and per run when a project is given"""
+    return await get_storage_usage(db, project_id)
+
+
+@router.get("/cleanup/preview", response_model=CleanupResult)
+async def preview_cleanup(db: AsyncSession = Depends(get_db)):
+    """List the runs the retention rules would delete, without deleting them"""
+    return await apply_retention(db, dry_run=True)
+
+
+@router.post("/cleanup", response_model=CleanupResult)
+async def run_cleanup(db: AsyncSession = Depends(get_db)):
+    """Delete the runs outside the retention rules with their artifacts"""
+    return await apply_retention(db, dry_run=False)
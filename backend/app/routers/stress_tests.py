@@ -0,0 +1,31 @@
+from typing import List
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import StressCrash, StressCrashResponse, StressTestRequest, TestRun, TestRunResponse
+from app.services.stress_testing import StressTestError, start_stress_test
+
+router = APIRouter(prefix="/stress-tests", tags=["stress-tests"])
+
+
+@router.post("", response_model=TestRunResponse)
+async def create_stress_test(data: StressTestRequest, db: AsyncSession = Depends(get_db)):
+    """Queue a stress test that sends random input to an app, reporting crashes as 'stress:finished'"""
+    try:
+        return await start_stress_test(db, data)
+    except StressTestError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.get("/{test_run_id}/crashes", response_model=List[StressCrashResponse])
+async def list_stress_crashes(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get the crashes and ANRs a stress test found, each with the seed that reproduces it"""
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    result = await db.execute(
+        select(StressCrash).where(StressCrash.test_run_id == test_run_id).order_by(StressCrash.created_at)
+    )
+    return result.scalars().all()
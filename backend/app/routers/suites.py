@@ -0,0 +1,284 @@
+import uuid
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException
+from pydantic import BaseModel
+from sqlalchemy import delete, func, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    Project,
+    Scenario,
+    ScenarioResponse,
+    Suite,
+    SuiteCreate,
+    SuiteUpdate,
+    SuiteResponse,
+    SuiteScenario,
+    SuiteWithScenarios,
+    TestRun,
+    TestRunResponse,
+)
+from app.routers.queue import check_priority
+from app.services.browsers import BrowserError, select_viewports
+from app.services.dependencies import load_prerequisites
+from app.services.executor import ScenarioExecutor, start_executor_run
+from app.services.ordering import move_after
+from app.services.test_runner import TestFramework
+from app.services.web_executor import WebScenarioExecutor
+
+router = APIRouter(prefix="/suites", tags=["suites"])
+
+
+class AddSuiteScenarioRequest(BaseModel):
+    """Schema for adding a scenario to a suite; a missing after_scenario_id appends it"""
+
+    scenario_id: str
+    after_scenario_id: Optional[str] = None
+
+
+class MoveSuiteScenarioRequest(BaseModel):
+    """Schema for moving a scenario within a suite; a missing after_scenario_id moves it to the start"""
+
+    after_scenario_id: Optional[str] = None
+
+
+class RunSuiteRequest(BaseModel):
+    """Schema for running a suite on a mobile device or in a browser"""
+
+    target: str = "device"  # 'device' | 'browser'
+    device_id: Optional[str] = None
+    platform: Optional[str] = None  # 'android' | 'ios'
+    framework: TestFramework = TestFramework.PLAYWRIGHT
+    browser: Optional[str] = None  # Empty uses the project's default browser
+    headless: Optional[bool] = None
+    # Project viewports to repeat browser runs across; empty runs them all, None runs once
+    viewports: Optional[List[str]] = None
+    self_heal: bool = True
+    priority: str = "normal"  # 'high' | 'normal' | 'low'
+
+
+async def get_suite_or_404(db: AsyncSession, suite_id: str) -> Suite:
+    suite = await db.get(Suite, suite_id)
+    if not suite:
+        raise HTTPException(status_code=404, detail="Suite not found")
+    return suite
+
+
+async def get_membership(db: AsyncSession, suite_id: str, scenario_id: str) -> Optional[SuiteScenario]:
+    result = await db.execute(
+        select(SuiteScenario).where(
+            SuiteScenario.suite_id == suite_id, SuiteScenario.scenario_id == scenario_id
+        )
+    )
+    return result.scalar_one_or_none()
+
+
+async def suite_scenario_ids(db: AsyncSession, suite_id: str) -> List[str]:
+    """IDs of a suite's scenarios in run order"""
+    result = await db.execute(
+        select(SuiteScenario.scenario_id)
+        .where(SuiteScenario.suite_id == suite_id)
+        .order_by(SuiteScenario.position, SuiteScenario.created_at)
+    )
+    return list(result.scalars().all())
+
+
+async def next_suite_position(db: AsyncSession, suite_id: str) -> float:
+    """Position after the last scenario of a suite"""
+    result = await db.execute(
+        select(func.max(SuiteScenario.position)).where(SuiteScenario.suite_id == suite_id)
+    )
+    return (result.scalar() or 0.0) + 1.0
+
+
+@router.post("", response_model=SuiteResponse)
+async def create_suite(data: SuiteCreate, db: AsyncSession = Depends(get_db)):
+    """Create a new suite with an optional initial list of scenarios"""
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+
+    scenario_ids = list(dict.fromkeys(data.scenario_ids))
+    if scenario_ids:
+        result = await db.execute(select(Scenario.id).where(Scenario.id.in_(scenario_ids)))
+        missing = set(scenario_ids) - set(result.scalars().all())
+        if missing:
+            raise HTTPException(status_code=404, detail=f"Scenarios not found: {', '.join(sorted(missing))}")
+
+    suite = Suite(
+        id=str(uuid.uuid4()),
+        project_id=data.project_id,
+        name=data.name,
+        description=data.description,
+    )
+    db.add(suite)
+    for position, scenario_id in enumerate(scenario_ids, start=1):
+        db.add(SuiteScenario(suite_id=suite.id, scenario_id=scenario_id, position=float(position)))
+    await db.commit()
+    await db.refresh(suite)
+    return suite
+
+
+@router.get("/project/{project_id}", response_model=List[SuiteResponse])
+async def list_suites_by_project(project_id: str, db: AsyncSession = Depends(get_db)):
+    """List all suites for a project"""
+    result = await db.execute(
+        select(Suite).where(Suite.project_id == project_id).order_by(Suite.created_at.desc())
+    )
+    return result.scalars().all()
+
+
+@router.get("/{suite_id}", response_model=SuiteWithScenarios)
+async def get_suite(suite_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a suite with its scenarios in run order"""
+    suite = await get_suite_or_404(db, suite_id)
+    result = await db.execute(
+        select(Scenario)
+        .join(SuiteScenario, SuiteScenario.scenario_id == Scenario.id)
+        .where(SuiteScenario.suite_id == suite_id)
+        .order_by(SuiteScenario.position, SuiteScenario.created_at)
+    )
+    return SuiteWithScenarios(
+        id=suite.id,
+        project_id=suite.project_id,
+        name=suite.name,
+        description=suite.description,
+        created_at=suite.created_at,
+        updated_at=suite.updated_at,
+        scenarios=[ScenarioResponse.model_validate(s) for s in result.scalars().all()],
+    )
+
+
+@router.put("/{suite_id}", response_model=SuiteResponse)
+async def update_suite(suite_id: str, data: SuiteUpdate, db: AsyncSession = Depends(get_db)):
+    """Update a suite"""
+    suite = await get_suite_or_404(db, suite_id)
+
+    update_data = data.model_dump(exclude_unset=True)
+    for key, value in update_data.items():
+        setattr(suite, key, value)
+
+    await db.commit()
+    await db.refresh(suite)
+    return suite
+
+
+@router.delete("/{suite_id}")
+async def delete_suite(suite_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a suite; its scenarios are left untouched"""
+    suite = await get_suite_or_404(db, suite_id)
+    await db.execute(delete(SuiteScenario).where(SuiteScenario.suite_id == suite_id))
+    await db.delete(suite)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.post("/{suite_id}/scenarios", response_model=SuiteResponse)
+async def add_suite_scenario(
+    suite_id: str, data: AddSuiteScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Add a scenario to a suite"""
+    suite = await get_suite_or_404(db, suite_id)
+    if not await db.get(Scenario, data.scenario_id):
+        raise HTTPException(status_code=404, detail="Scenario not found")
+    if await get_membership(db, suite_id, data.scenario_id):
+        raise HTTPException(status_code=400, detail="Scenario is already in this suite")
+
+    membership = SuiteScenario(
+        suite_id=suite_id,
+        scenario_id=data.scenario_id,
+        position=await next_suite_position(db, suite_id),
+    )
+    db.add(membership)
+    if data.after_scenario_id:
+        await move_suite_membership(db, suite_id, membership, data.after_scenario_id)
+
+    await db.commit()
+    await db.refresh(suite)
+    return suite
+
+
+@router.delete("/{suite_id}/scenarios/{scenario_id}")
+async def remove_suite_scenario(suite_id: str, scenario_id: str, db: AsyncSession = Depends(get_db)):
+    """Remove a scenario from a suite"""
+    membership = await get_membership(db, suite_id, scenario_id)
+    if not membership:
+        raise HTTPException(status_code=404, detail="Scenario is not in this suite")
+    await db.delete(membership)
+    await db.commit()
+    return {"status": "deleted"}
+
+
+@router.post("/{suite_id}/scenarios/{scenario_id}/move", response_model=SuiteResponse)
+async def move_suite_scenario(
+    suite_id: str, scenario_id: str, data: MoveSuiteScenarioRequest, db: AsyncSession = Depends(get_db)
+):
+    """Move a scenario directly after another one in a suite"""
+    suite = await get_suite_or_404(db, suite_id)
+    membership = await get_membership(db, suite_id, scenario_id)
+    if not membership:
+        raise HTTPException(status_code=404, detail="Scenario is not in this suite")
+
+    await move_suite_membership(db, suite_id, membership, data.after_scenario_id)
+    await db.commit()
+    await db.refresh(suite)
+    return suite
+
+
+async def move_suite_membership(
+    db: AsyncSession, suite_id: str, membership: SuiteScenario, after_scenario_id: Optional[str]
+) -> None:
+    after_id = None
+    if after_scenario_id:
+        after = await get_membership(db, suite_id, after_scenario_id)
+        if not after:
+            raise HTTPException(status_code=404, detail="Target scenario not found in this suite")
+        after_id = after.id
+    await db.flush()
+    await move_after(db, SuiteScenario.position, SuiteScenario.suite_id == suite_id, membership, after_id)
+
+
+@router.post("/{suite_id}/run", response_model=TestRunResponse)
+async def run_suite(suite_id: str, data: RunSuiteRequest, db: AsyncSession = Depends(get_db)):
+    """Run a suite's scenarios in order as one aggregated test run"""
+    suite = await get_suite_or_404(db, suite_id)
+    scenario_ids = await suite_scenario_ids(db, suite_id)
+    if not scenario_ids:
+        raise HTTPException(status_code=400, detail="Suite has no scenarios")
+    project = await db.get(Project, suite.project_id)
+    check_priority(data.priority)
+
+    if data.target == "browser":
+        try:
+            select_viewports(project, data.viewports)
+        except BrowserError as e:
+            raise HTTPException(status_code=400, detail=str(e))
+        executor = WebScenarioExecutor(data.framework, data.browser, data.headless, data.viewports)
+    elif data.target == "device":
+        if not data.device_id or data.platform not in ("android", "ios"):
+            raise HTTPException(status_code=400, detail="device_id and platform are required for device runs")
+        executor = ScenarioExecutor(
+            data.device_id, data.platform, self_heal=data.self_heal, ai_free=project.ai_free
+        )
+    else:
+        raise HTTPException(status_code=400, detail=f"Unknown target: {data.target}")
+
+    # Only prerequisites that run earlier in the suite can gate a scenario
+    prerequisites = await load_prerequisites(db)
+    suite_prerequisites = {
+        scenario_id: [p for p in prerequisites.get(scenario_id, []) if p in scenario_ids[:index]]
+        for index, scenario_id in enumerate(scenario_ids)
+    }
+
+    test_run = TestRun(
+        id=str(uuid.uuid4()),
+        project_id=suite.project_id,
+        name=suite.name,
+    )
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    start_executor_run(executor, scenario_ids, test_run.id, suite_prerequisites, data.priority)
+    return test_run
@@ -0,0 +1,88 @@
+import json
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import (
+    Project,
+    SynonymGroup,
+    SynonymGroupCreate,
+    SynonymGroupUpdate,
+    SynonymGroupResponse,
+)
+from app.services.synonyms import SynonymError, check_language, clean_terms, list_groups, seed_defaults
+
+router = APIRouter(prefix="/synonyms", tags=["synonyms"])
+
+
+async def get_group_or_404(db: AsyncSession, group_id: str) -> SynonymGroup:
+    group = await db.get(SynonymGroup, group_id)
+    if not group:
+        raise HTTPException(status_code=404, detail="Synonym group not found")
+    return group
+
+
+@router.post("", response_model=SynonymGroupResponse)
+async def create_synonym_group(data: SynonymGroupCreate, db: AsyncSession = Depends(get_db)):
+    """Add a group of terms to a project's synonym dictionary"""
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    try:
+        check_language(data.language)
+        terms = clean_terms(data.terms)
+    except SynonymError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    group = SynonymGroup(project_id=data.project_id, language=data.language, terms=json.dumps(terms))
+    db.add(group)
+    await db.commit()
+    await db.refresh(group)
+    return group
+
+
+@router.get("/project/{project_id}", response_model=List[SynonymGroupResponse])
+async def list_synonym_groups(project_id: str, language: Optional[str] = None, db: AsyncSession = Depends(get_db)):
+    """List a project's synonym groups, optionally of one language"""
+    if not await db.get(Project, project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    return await list_groups(db, project_id, language)
+
+
+@router.post("/project/{project_id}/defaults", response_model=List[SynonymGroupResponse])
+async def restore_default_synonyms(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Add back any default groups missing from a project's dictionary"""
+    project = await db.get(Project, project_id)
+    if not project:
+        raise HTTPException(status_code=404, detail="Project not found")
+    await seed_defaults(db, project)
+    await db.commit()
+    return await list_groups(db, project_id)
+
+
+@router.put("/{group_id}", response_model=SynonymGroupResponse)
+async def update_synonym_group(group_id: str, data: SynonymGroupUpdate, db: AsyncSession = Depends(get_db)):
+    """Update a synonym group's language or terms"""
+    group = await get_group_or_404(db, group_id)
+    try:
+        if data.language is not None:
+            check_language(data.language)
+            group.language = data.language
+        if data.terms is not None:
+            group.terms = json.dumps(clean_terms(data.terms))
+    except SynonymError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+    await db.commit()
+    await db.refresh(group)
+    return group
+
+
+@router.delete("/{group_id}")
+async def delete_synonym_group(group_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a synonym group"""
+    group = await get_group_or_404(db, group_id)
+    await db.delete(group)
+    await db.commit()
+    return {"status": "deleted"}
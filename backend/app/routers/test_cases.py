@@ -1,24 +1,67 @@
+import json
 import uuid
 from typing import List, Optional
 
-from fastapi import APIRouter, Depends, HTTPException, Query
-from sqlalchemy import select, func
+from fastapi import APIRouter, Depends, File, Form, HTTPException, Query, UploadFile
+from fastapi.responses import Response
+from pydantic import BaseModel
+from sqlalchemy import select, or_, update
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.db import get_db
+from app.errors import conflict_error
 from app.models import (
+    GeneratedTestCodeResponse,
+    Project,
+    SaveGeneratedTests,
     TestCase,
     TestCaseCreate,
     TestCaseUpdate,
     TestCaseResponse,
     TestCaseStats,
-    CategoryCount,
-    PriorityCount,
 )
+from app.services.custom_fields import (
+    CustomFieldError,
+    copy_values,
+    delete_values,
+    filter_clauses,
+    move_values,
+    parse_filters,
+    search_clause,
+)
+from app.services.duplication import copy_test_case
+from app.services.generated_tests import GeneratedTestsError, list_generated_code, save_generated_tests
+from app.services.stat_counters import test_case_stats
+from app.services.test_case_csv import CsvImportError, CsvImportResult, export_test_cases_csv, import_test_cases_csv
+from app.services.versioning import StaleWriteError, claim_version
 
 router = APIRouter(prefix="/test-cases", tags=["test-cases"])
 
 
+class BulkStatusUpdate(BaseModel):
+    """Schema for setting the status of several test cases"""
+
+    test_case_ids: List[str]
+    status: str
+
+
+class BulkMove(BaseModel):
+    """Schema for moving several test cases to another project"""
+
+    test_case_ids: List[str]
+    project_id: str
+
+
+async def ensure_test_cases_exist(db: AsyncSession, test_case_ids: List[str]) -> None:
+    """Raise 404 if any of the test cases is missing, so bulk changes are all-or-nothing"""
+    result = await db.execute(select(TestCase.id).where(TestCase.id.in_(test_case_ids)))
+    missing = set(test_case_ids) - set(result.scalars().all())
+    if missing:
+        raise HTTPException(
+            status_code=404, detail=f"Test cases not found: {', '.join(sorted(missing))}"
+        )
+
+
 @router.post("", response_model=TestCaseResponse)
 async def create_test_case(data: TestCaseCreate, db: AsyncSession = Depends(get_db)):
     """Create a new test case"""
@@ -44,9 +87,16 @@ async def list_test_cases(
     priority: Optional[str] = Query(None),
     status: Optional[str] = Query(None),
     test_type: Optional[str] = Query(None),
+    search: Optional[str] = Query(None),
+    field: List[str] = Query([]),
     db: AsyncSession = Depends(get_db),
 ):
-    """List test cases with optional filters"""
+    """
+    List test cases with optional filters
+
+    search matches names, descriptions and custom field values. Each field
+    parameter filters on a custom field, as in field=Component=Checkout.
+    """
     query = select(TestCase)
 
     if project_id:
@@ -59,6 +109,17 @@ async def list_test_cases(
         query = query.where(TestCase.status == status)
     if test_type:
         query = query.where(TestCase.test_type == test_type)
+    if search:
+        pattern = f"%{search}%"
+        query = query.where(
+            or_(TestCase.name.ilike(pattern), TestCase.description.ilike(pattern), search_clause(TestCase.id, search))
+        )
+    if field:
+        try:
+            clauses = await filter_clauses(db, TestCase.id, "test_case", parse_filters(field), project_id)
+        except CustomFieldError as e:
+            raise HTTPException(status_code=400, detail=str(e))
+        query = query.where(*clauses)
 
     query = query.order_by(TestCase.updated_at.desc())
     result = await db.execute(query)
@@ -76,65 +137,93 @@ async def list_test_cases_by_project(project_id: str, db: AsyncSession = Depends
     return result.scalars().all()
 
 
+@router.get("/project/{project_id}/csv")
+async def export_test_cases(project_id: str, db: AsyncSession = Depends(get_db)):
+    """Export a project's test cases, with a column per custom field, as CSV"""
+    if not await db.get(Project, project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    return Response(
+        await export_test_cases_csv(db, project_id),
+        media_type="text/csv",
+        headers={"Content-Disposition": f'attachment; filename="test-cases-{project_id}.csv"'},
+    )
+
+
+@router.post("/project/{project_id}/csv", response_model=CsvImportResult)
+async def import_test_cases(
+    project_id: str,
+    file: UploadFile = File(...),
+    mapping: Optional[str] = Form(None),
+    db: AsyncSession = Depends(get_db),
+):
+    """
+    Import test cases from CSV, updating ones with the same name
+
+    mapping is a JSON object from CSV headers to a column, a custom field
+    (custom:Name) or "" to skip the header.
+    """
+    if not await db.get(Project, project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    try:
+        content = (await file.read()).decode("utf-8")
+        return await import_test_cases_csv(db, project_id, content, json.loads(mapping) if mapping else None)
+    except (UnicodeDecodeError, ValueError):
+        raise HTTPException(status_code=400, detail="Upload a UTF-8 CSV file with a JSON mapping")
+    except CsvImportError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.post("/project/{project_id}/generated", response_model=List[TestCaseResponse])
+async def save_generated(project_id: str, data: SaveGeneratedTests, db: AsyncSession = Depends(get_db)):
+    """Save AI-generated tests as test cases in the "AI-generated" category, with their code and source"""
+    if not await db.get(Project, project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    try:
+        return await save_generated_tests(db, project_id, data)
+    except GeneratedTestsError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
 @router.get("/stats/{project_id}", response_model=TestCaseStats)
 async def get_test_case_stats(project_id: str, db: AsyncSession = Depends(get_db)):
-    """Get statistics for test cases in a project"""
-    # Total counts by status
-    total_result = await db.execute(
-        select(func.count()).where(TestCase.project_id == project_id)
-    )
-    total = total_result.scalar() or 0
+    """Get statistics for test cases in a project, from its stat counters"""
+    return await test_case_stats(db, project_id)
 
-    passed_result = await db.execute(
-        select(func.count()).where(
-            TestCase.project_id == project_id, TestCase.status == "success"
-        )
-    )
-    passed = passed_result.scalar() or 0
 
-    failed_result = await db.execute(
-        select(func.count()).where(
-            TestCase.project_id == project_id, TestCase.status == "failed"
-        )
+@router.patch("/bulk/status")
+async def bulk_update_test_case_status(data: BulkStatusUpdate, db: AsyncSession = Depends(get_db)):
+    """Set the status of several test cases in one transaction"""
+    await ensure_test_cases_exist(db, data.test_case_ids)
+    result = await db.execute(
+        update(TestCase)
+        .where(TestCase.id.in_(data.test_case_ids))
+        .values(status=data.status)
+        .execution_options(synchronize_session=False)
     )
-    failed = failed_result.scalar() or 0
+    await db.commit()
+    return {"updated": result.rowcount}
 
-    pending_result = await db.execute(
-        select(func.count()).where(
-            TestCase.project_id == project_id, TestCase.status == "pending"
-        )
-    )
-    pending = pending_result.scalar() or 0
 
-    # By category
-    category_result = await db.execute(
-        select(TestCase.category, func.count().label("count"))
-        .where(TestCase.project_id == project_id, TestCase.category.isnot(None))
-        .group_by(TestCase.category)
-    )
-    by_category = [
-        CategoryCount(category=row[0] or "Uncategorized", count=row[1])
-        for row in category_result.all()
-    ]
-
-    # By priority
-    priority_result = await db.execute(
-        select(TestCase.priority, func.count().label("count"))
-        .where(TestCase.project_id == project_id)
-        .group_by(TestCase.priority)
-    )
-    by_priority = [
-        PriorityCount(priority=row[0], count=row[1]) for row in priority_result.all()
-    ]
-
-    return TestCaseStats(
-        total=total,
-        passed=passed,
-        failed=failed,
-        pending=pending,
-        by_category=by_category,
-        by_priority=by_priority,
+@router.post("/bulk/move")
+async def bulk_move_test_cases(data: BulkMove, db: AsyncSession = Depends(get_db)):
+    """
+    Move several test cases, with their scenarios and steps, to another project
+
+    Custom field values move to the new project's fields of the same name.
+    """
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    await ensure_test_cases_exist(db, data.test_case_ids)
+    moving = await db.execute(select(TestCase).where(TestCase.id.in_(data.test_case_ids)))
+    await move_values(db, list(moving.scalars().all()), data.project_id)
+    result = await db.execute(
+        update(TestCase)
+        .where(TestCase.id.in_(data.test_case_ids))
+        .values(project_id=data.project_id)
+        .execution_options(synchronize_session=False)
     )
+    await db.commit()
+    return {"moved": result.rowcount}
 
 
 @router.get("/{test_case_id}", response_model=TestCaseResponse)
@@ -158,6 +247,11 @@ async def update_test_case(
         raise HTTPException(status_code=404, detail="Test case not found")
 
     update_data = data.model_dump(exclude_unset=True)
+    try:
+        await claim_version(db, test_case, update_data.pop("expected_updated_at", None))
+    except StaleWriteError:
+        current = TestCaseResponse.model_validate(test_case).model_dump(mode="json")
+        raise conflict_error("The test case was changed since it was loaded", current)
     for key, value in update_data.items():
         setattr(test_case, key, value)
 
@@ -166,6 +260,14 @@ async def update_test_case(
     return test_case
 
 
+@router.get("/{test_case_id}/generated-code", response_model=List[GeneratedTestCodeResponse])
+async def get_generated_code(test_case_id: str, db: AsyncSession = Depends(get_db)):
+    """Get the AI-generated code of a test case, newest first"""
+    if not await db.get(TestCase, test_case_id):
+        raise HTTPException(status_code=404, detail="Test case not found")
+    return await list_generated_code(db, test_case_id)
+
+
 @router.patch("/{test_case_id}/status")
 async def update_test_case_status(
     test_case_id: str, status: str, db: AsyncSession = Depends(get_db)
@@ -181,6 +283,25 @@ async def update_test_case_status(
     return {"status": "updated"}
 
 
+@router.post("/{test_case_id}/duplicate", response_model=TestCaseResponse)
+async def duplicate_test_case(
+    test_case_id: str, new_name: Optional[str] = None, db: AsyncSession = Depends(get_db)
+):
+    """Duplicate a test case with all its scenarios and steps"""
+    result = await db.execute(select(TestCase).where(TestCase.id == test_case_id))
+    test_case = result.scalar_one_or_none()
+    if not test_case:
+        raise HTTPException(status_code=404, detail="Test case not found")
+
+    new_test_case = await copy_test_case(
+        db, test_case, test_case.project_id, name=new_name or f"{test_case.name} (Copy)"
+    )
+    await copy_values(db, test_case.project_id, test_case.id, test_case.project_id, new_test_case.id)
+    await db.commit()
+    await db.refresh(new_test_case)
+    return new_test_case
+
+
 @router.delete("/{test_case_id}")
 async def delete_test_case(test_case_id: str, db: AsyncSession = Depends(get_db)):
     """Delete a test case"""
@@ -189,6 +310,7 @@ async def delete_test_case(test_case_id: str, db: AsyncSession = Depends(get_db)
     if not test_case:
         raise HTTPException(status_code=404, detail="Test case not found")
 
+    await delete_values(db, [test_case.id])
     await db.delete(test_case)
     await db.commit()
     return {"status": "deleted"}
@@ -5,7 +5,10 @@ from fastapi import APIRouter, HTTPException
 from pydantic import BaseModel
 from typing import List, Optional, Dict, Any
 
+from ..services.browsers import CYPRESS_BROWSERS, PLAYWRIGHT_BROWSERS
+from ..services.runner_events import runner_events
 from ..services.test_runner import test_runner, TestFramework
+from ..services.tools import find_tool
 
 router = APIRouter(prefix="/test-runner", tags=["test-runner"])
 
@@ -53,7 +56,7 @@ async def run_test_steps(request: RunStepsRequest):
     steps_dict = [step.model_dump() for step in request.steps]
 
     if request.framework == TestFramework.CYPRESS:
-        browser = request.browser if request.browser in ["chrome", "firefox", "electron"] else "chrome"
+        browser = request.browser if request.browser in CYPRESS_BROWSERS else "chrome"
         result = await test_runner.run_steps_as_cypress(
             steps=steps_dict,
             base_url=request.base_url,
@@ -61,7 +64,7 @@ async def run_test_steps(request: RunStepsRequest):
             headless=request.headless
         )
     else:
-        browser = request.browser if request.browser in ["chromium", "firefox", "webkit"] else "chromium"
+        browser = request.browser if request.browser in PLAYWRIGHT_BROWSERS else "chromium"
         result = await test_runner.run_steps_as_playwright(
             steps=steps_dict,
             base_url=request.base_url,
@@ -80,7 +83,7 @@ async def run_test_spec(request: RunSpecRequest):
     Executes the provided spec content directly.
     """
     if request.framework == TestFramework.CYPRESS:
-        browser = request.browser if request.browser in ["chrome", "firefox", "electron"] else "chrome"
+        browser = request.browser if request.browser in CYPRESS_BROWSERS else "chrome"
         result = await test_runner.run_cypress(
             spec_content=request.spec_content,
             base_url=request.base_url,
@@ -89,7 +92,7 @@ async def run_test_spec(request: RunSpecRequest):
             timeout=request.timeout
         )
     else:
-        browser = request.browser if request.browser in ["chromium", "firefox", "webkit"] else "chromium"
+        browser = request.browser if request.browser in PLAYWRIGHT_BROWSERS else "chromium"
         result = await test_runner.run_playwright(
             spec_content=request.spec_content,
             base_url=request.base_url,
@@ -104,12 +107,35 @@ async def run_test_spec(request: RunSpecRequest):
 @router.get("/health")
 async def health_check():
     """Check if test runner is available"""
-    import shutil
-
-    npx_available = shutil.which("npx") is not None
+    npx_available = find_tool("npx") is not None
 
     return {
         "status": "ok" if npx_available else "degraded",
         "npx_available": npx_available,
         "message": "Ready to run tests" if npx_available else "npx not found - install Node.js"
     }
+
+
+@router.post("/executions/{execution_id}/subscribe")
+async def subscribe_to_execution(execution_id: str):
+    """Relay an execution's events to the event bus as 'test_runner:event'"""
+    await runner_events.subscribe(execution_id)
+    return {"status": "subscribed", "executions": sorted(runner_events.executions)}
+
+
+@router.delete("/executions/{execution_id}/subscribe")
+async def unsubscribe_from_execution(execution_id: str):
+    """Stop relaying an execution's events"""
+    if not await runner_events.unsubscribe(execution_id):
+        raise HTTPException(status_code=404, detail="Not subscribed to this execution")
+    return {"status": "unsubscribed", "executions": sorted(runner_events.executions)}
+
+
+@router.get("/events/status")
+async def get_runner_events_status():
+    """Get the state of the shared test runner event connection"""
+    return {
+        "connected": runner_events.connected,
+        "executions": sorted(runner_events.executions),
+        "reconnects": runner_events.reconnects,
+    }
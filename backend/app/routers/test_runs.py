@@ -1,23 +1,68 @@
+import tempfile
 import uuid
-from datetime import datetime
+from pathlib import Path
 from typing import List, Optional
 
 from fastapi import APIRouter, Depends, HTTPException, Query
-from sqlalchemy import select, func
+from fastapi.responses import HTMLResponse, Response
+from pydantic import BaseModel
+from sqlalchemy import select
 from sqlalchemy.ext.asyncio import AsyncSession
 
 from app.db import get_db
+from app.errors import CommandError, ErrorCode
 from app.models import (
+    Project,
     TestRun,
     TestRunCreate,
     TestRunUpdate,
     TestRunResponse,
     TestRunSummary,
+    RunBreakdown,
+    RunComparison,
+    RunLogCreate,
+    RunLogPage,
+    RunLogResponse,
+    RunTransitionResponse,
 )
+from app.services.email_reports import EmailDeliveryError, EmailError, email_run_report, parse_recipients
+from app.services.report_import import ReportImportError, import_external_results
+from app.services.run_comparison import (
+    DEFAULT_DURATION_THRESHOLD_PCT,
+    DEFAULT_MIN_DURATION_DELTA_MS,
+    DEFAULT_SCREENSHOT_THRESHOLD,
+    compare_runs,
+)
+from app.services.run_lifecycle import (
+    RunTransitionError,
+    complete_run,
+    list_transitions,
+    start_run,
+    transition_run,
+)
+from app.services.run_logs import add_run_logs, query_run_logs
+from app.services.run_reports import ReportExportError, export_run_pdf, render_html_report, render_junit_xml
+from app.services.run_status import get_run_breakdown
+from app.services.stat_counters import test_run_summary
 
 router = APIRouter(prefix="/test-runs", tags=["test-runs"])
 
 
+class ImportResultsRequest(BaseModel):
+    """Schema for importing a report produced by an external test runner"""
+
+    project_id: str
+    run_name: str
+    format: str  # 'playwright' | 'cypress' (mochawesome)
+    path: str
+
+
+class EmailReportRequest(BaseModel):
+    """Schema for emailing a run's report"""
+
+    recipients: List[str]
+
+
 @router.post("", response_model=TestRunResponse)
 async def create_test_run(data: TestRunCreate, db: AsyncSession = Depends(get_db)):
     """Create a new test run"""
@@ -32,6 +77,17 @@ async def create_test_run(data: TestRunCreate, db: AsyncSession = Depends(get_db
     return test_run
 
 
+@router.post("/import", response_model=TestRunResponse)
+async def import_test_run(data: ImportResultsRequest, db: AsyncSession = Depends(get_db)):
+    """Import a Playwright JSON or Cypress mochawesome report as a completed test run"""
+    if not await db.get(Project, data.project_id):
+        raise HTTPException(status_code=404, detail="Project not found")
+    try:
+        return await import_external_results(db, data.project_id, data.run_name, data.format, data.path)
+    except ReportImportError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
 @router.get("/project/{project_id}", response_model=List[TestRunResponse])
 async def list_test_runs(
     project_id: str,
@@ -53,38 +109,26 @@ async def list_test_runs(
 
 @router.get("/summary/{project_id}", response_model=TestRunSummary)
 async def get_test_run_summary(project_id: str, db: AsyncSession = Depends(get_db)):
-    """Get summary statistics for test runs in a project"""
-    total_result = await db.execute(
-        select(func.count()).where(TestRun.project_id == project_id)
-    )
-    total = total_result.scalar() or 0
-
-    passed_result = await db.execute(
-        select(func.count()).where(
-            TestRun.project_id == project_id, TestRun.status == "passed"
-        )
-    )
-    passed = passed_result.scalar() or 0
-
-    failed_result = await db.execute(
-        select(func.count()).where(
-            TestRun.project_id == project_id, TestRun.status == "failed"
-        )
-    )
-    failed = failed_result.scalar() or 0
+    """Get summary statistics for test runs in a project, from its stat counters"""
+    return await test_run_summary(db, project_id)
 
-    avg_result = await db.execute(
-        select(func.avg(TestRun.duration_ms)).where(
-            TestRun.project_id == project_id, TestRun.duration_ms.isnot(None)
-        )
-    )
-    avg_duration = avg_result.scalar()
 
-    return TestRunSummary(
-        total_runs=total,
-        passed_runs=passed,
-        failed_runs=failed,
-        avg_duration_ms=avg_duration,
+@router.get("/compare", response_model=RunComparison)
+async def compare_test_runs(
+    run_a: str = Query(..., description="The earlier run"),
+    run_b: str = Query(..., description="The later run"),
+    duration_threshold_pct: float = Query(DEFAULT_DURATION_THRESHOLD_PCT, ge=0),
+    min_duration_delta_ms: int = Query(DEFAULT_MIN_DURATION_DELTA_MS, ge=0),
+    screenshot_threshold: float = Query(DEFAULT_SCREENSHOT_THRESHOLD, ge=0, le=1),
+    db: AsyncSession = Depends(get_db),
+):
+    """Diff two test runs: newly failing and passing steps, slower steps and changed screenshots"""
+    first = await db.get(TestRun, run_a)
+    second = await db.get(TestRun, run_b)
+    if not first or not second:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return await compare_runs(
+        db, first, second, duration_threshold_pct, min_duration_delta_ms, screenshot_threshold
     )
 
 
@@ -98,6 +142,100 @@ async def get_test_run(test_run_id: str, db: AsyncSession = Depends(get_db)):
     return test_run
 
 
+@router.get("/{test_run_id}/breakdown", response_model=RunBreakdown)
+async def get_test_run_breakdown(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a test run's results by status, category and test case, with known issues"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return await get_run_breakdown(db, test_run)
+
+
+@router.get("/{test_run_id}/report", response_class=HTMLResponse)
+async def get_test_run_report(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a test run's standalone HTML report"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return HTMLResponse(await render_html_report(db, test_run))
+
+
+@router.get("/{test_run_id}/report.pdf")
+async def get_test_run_pdf(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a test run's report as a PDF, printed by a local Chromium-based browser"""
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    with tempfile.TemporaryDirectory() as tmp:
+        try:
+            path = await export_run_pdf(db, test_run_id, Path(tmp) / "report.pdf")
+        except ReportExportError as e:
+            raise HTTPException(status_code=400, detail=str(e))
+        content = path.read_bytes()
+    return Response(
+        content,
+        media_type="application/pdf",
+        headers={"Content-Disposition": f'attachment; filename="report-{test_run_id}.pdf"'},
+    )
+
+
+@router.get("/{test_run_id}/junit")
+async def get_test_run_junit(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a test run's results as JUnit XML"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return Response(
+        await render_junit_xml(db, test_run),
+        media_type="application/xml",
+        headers={"Content-Disposition": f'attachment; filename="junit-{test_run.id}.xml"'},
+    )
+
+
+@router.post("/{test_run_id}/email")
+async def email_test_run_report(test_run_id: str, data: EmailReportRequest, db: AsyncSession = Depends(get_db)):
+    """Email a test run's HTML report with its JUnit XML attached, using the email.* SMTP settings"""
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    try:
+        await email_run_report(db, test_run_id, parse_recipients(",".join(data.recipients)))
+    except EmailDeliveryError as e:
+        raise CommandError(502, ErrorCode.INTEGRATION_ERROR, str(e))
+    except EmailError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+    return {"status": "sent", "recipients": len(data.recipients)}
+
+
+@router.post("/{test_run_id}/logs", response_model=List[RunLogResponse])
+async def write_test_run_logs(
+    test_run_id: str, entries: List[RunLogCreate], db: AsyncSession = Depends(get_db)
+):
+    """Append console log lines to a test run, such as from a remote executor"""
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    try:
+        return await add_run_logs(db, test_run_id, entries)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.get("/{test_run_id}/logs", response_model=RunLogPage)
+async def get_test_run_logs(
+    test_run_id: str,
+    level: Optional[str] = Query(None),
+    source: Optional[str] = Query(None),
+    step_index: Optional[int] = Query(None),
+    search: Optional[str] = Query(None),
+    offset: int = Query(0, ge=0),
+    limit: int = Query(200, ge=1, le=1000),
+    db: AsyncSession = Depends(get_db),
+):
+    """Get a page of a test run's console log lines"""
+    try:
+        return await query_run_logs(db, test_run_id, level, source, step_index, search, offset, limit)
+    except ValueError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
 @router.put("/{test_run_id}", response_model=TestRunResponse)
 async def update_test_run(
     test_run_id: str, data: TestRunUpdate, db: AsyncSession = Depends(get_db)
@@ -109,9 +247,15 @@ async def update_test_run(
         raise HTTPException(status_code=404, detail="Test run not found")
 
     update_data = data.model_dump(exclude_unset=True)
+    status = update_data.pop("status", None)
     for key, value in update_data.items():
         setattr(test_run, key, value)
 
+    if status and status != test_run.status:
+        try:
+            return await transition_run(db, test_run, status, "Updated through the API")
+        except RunTransitionError as e:
+            raise CommandError(409, ErrorCode.CONFLICT, str(e), {"current": e.current, "target": e.target})
     await db.commit()
     await db.refresh(test_run)
     return test_run
@@ -125,11 +269,10 @@ async def start_test_run(test_run_id: str, db: AsyncSession = Depends(get_db)):
     if not test_run:
         raise HTTPException(status_code=404, detail="Test run not found")
 
-    test_run.status = "running"
-    test_run.started_at = datetime.utcnow()
-    await db.commit()
-    await db.refresh(test_run)
-    return test_run
+    try:
+        return await start_run(db, test_run)
+    except RunTransitionError as e:
+        raise CommandError(409, ErrorCode.CONFLICT, str(e), {"current": e.current, "target": e.target})
 
 
 @router.post("/{test_run_id}/complete", response_model=TestRunResponse)
@@ -146,20 +289,18 @@ async def complete_test_run(
     if not test_run:
         raise HTTPException(status_code=404, detail="Test run not found")
 
-    test_run.status = "passed" if failed == 0 else "failed"
-    test_run.passed = passed
-    test_run.failed = failed
-    test_run.skipped = skipped
-    test_run.completed_at = datetime.utcnow()
+    try:
+        return await complete_run(db, test_run, passed, failed, skipped)
+    except RunTransitionError as e:
+        raise CommandError(409, ErrorCode.CONFLICT, str(e), {"current": e.current, "target": e.target})
 
-    if test_run.started_at:
-        test_run.duration_ms = int(
-            (test_run.completed_at - test_run.started_at).total_seconds() * 1000
-        )
 
-    await db.commit()
-    await db.refresh(test_run)
-    return test_run
+@router.get("/{test_run_id}/transitions", response_model=List[RunTransitionResponse])
+async def get_test_run_transitions(test_run_id: str, db: AsyncSession = Depends(get_db)):
+    """Get a test run's lifecycle transitions, oldest first"""
+    if not await db.get(TestRun, test_run_id):
+        raise HTTPException(status_code=404, detail="Test run not found")
+    return await list_transitions(db, test_run_id)
 
 
 @router.delete("/{test_run_id}")
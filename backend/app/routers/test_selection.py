@@ -0,0 +1,98 @@
+from typing import List, Optional
+
+from fastapi import APIRouter, Depends, HTTPException
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.exc import IntegrityError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import FileTestMapping, FileTestMappingCreate, FileTestMappingResponse, Project, TestCase
+from app.services.test_selection import TestSelection, TestSelectionError, suggest_mappings, suggest_tests_for_diff
+
+router = APIRouter(prefix="/test-selection", tags=["test-selection"])
+
+
+class DiffSelectionRequest(BaseModel):
+    """Schema for picking the tests to run for a diff"""
+
+    project_id: str
+    base: str  # Commit, branch or tag the changes are compared against
+    head: str
+    github_token: Optional[str] = None  # Uses GITHUB_TOKEN when unset
+
+
+class SuggestMappingsRequest(BaseModel):
+    """Schema for asking AI to map changed files to test cases"""
+
+    project_id: str
+    files: List[str]
+
+
+async def get_project_or_404(db: AsyncSession, project_id: str) -> Project:
+    project = await db.get(Project, project_id)
+    if not project:
+        raise HTTPException(status_code=404, detail="Project not found")
+    return project
+
+
+@router.post("/diff", response_model=TestSelection)
+async def select_tests_for_diff(data: DiffSelectionRequest, db: AsyncSession = Depends(get_db)):
+    """Get the scenarios covering the files changed between base and head, highest scoring first"""
+    await get_project_or_404(db, data.project_id)
+    try:
+        return await suggest_tests_for_diff(db, data.project_id, data.base, data.head, data.github_token)
+    except TestSelectionError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.get("/mappings", response_model=List[FileTestMappingResponse])
+async def list_mappings(project_id: str, test_case_id: Optional[str] = None, db: AsyncSession = Depends(get_db)):
+    """List a project's file to test mappings, optionally only a test case's"""
+    query = select(FileTestMapping).where(FileTestMapping.project_id == project_id)
+    if test_case_id:
+        query = query.where(FileTestMapping.test_case_id == test_case_id)
+    result = await db.execute(query.order_by(FileTestMapping.path_pattern))
+    return result.scalars().all()
+
+
+@router.post("/mappings", response_model=FileTestMappingResponse)
+async def create_mapping(data: FileTestMappingCreate, db: AsyncSession = Depends(get_db)):
+    """Map a file, directory or glob to the test case covering it"""
+    test_case = await db.get(TestCase, data.test_case_id)
+    if not test_case:
+        raise HTTPException(status_code=404, detail="Test case not found")
+    path_pattern = data.path_pattern.strip().lstrip("/")
+    if not path_pattern:
+        raise HTTPException(status_code=400, detail="Enter a file, directory or glob")
+
+    mapping = FileTestMapping(project_id=test_case.project_id, test_case_id=test_case.id, path_pattern=path_pattern)
+    db.add(mapping)
+    try:
+        await db.commit()
+    except IntegrityError:
+        await db.rollback()
+        raise HTTPException(status_code=400, detail=f"{path_pattern} is already mapped to this test case")
+    await db.refresh(mapping)
+    return mapping
+
+
+@router.post("/mappings/suggest", response_model=List[FileTestMappingResponse])
+async def suggest_file_mappings(data: SuggestMappingsRequest, db: AsyncSession = Depends(get_db)):
+    """Ask AI which test cases cover the given files, saving its answers as mappings"""
+    await get_project_or_404(db, data.project_id)
+    try:
+        return await suggest_mappings(db, data.project_id, data.files)
+    except TestSelectionError as e:
+        raise HTTPException(status_code=400, detail=str(e))
+
+
+@router.delete("/mappings/{mapping_id}")
+async def delete_mapping(mapping_id: str, db: AsyncSession = Depends(get_db)):
+    """Delete a file to test mapping"""
+    mapping = await db.get(FileTestMapping, mapping_id)
+    if not mapping:
+        raise HTTPException(status_code=404, detail="Mapping not found")
+    await db.delete(mapping)
+    await db.commit()
+    return {"status": "deleted"}
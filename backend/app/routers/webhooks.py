@@ -0,0 +1,44 @@
+import json
+from typing import List
+
+from fastapi import APIRouter, Depends, Header, HTTPException, Request
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from app.db import get_db
+from app.models import WebhookRun, WebhookRunResponse
+from app.services.github_webhooks import WebhookError, handle_github_event, verify_signature
+
+router = APIRouter(prefix="/webhooks", tags=["webhooks"])
+
+
+@router.post("/github", response_model=List[WebhookRunResponse])
+async def receive_github_webhook(
+    request: Request,
+    x_github_event: str = Header(...),
+    x_hub_signature_256: str = Header(None),
+    db: AsyncSession = Depends(get_db),
+):
+    """Run the tests affected by a GitHub push or pull request delivery"""
+    body = await request.body()
+    try:
+        verify_signature(body, x_hub_signature_256)
+    except WebhookError as e:
+        raise HTTPException(status_code=403, detail=str(e))
+    try:
+        payload = json.loads(body)
+    except ValueError:
+        raise HTTPException(status_code=400, detail="Webhook body must be JSON")
+    return await handle_github_event(db, x_github_event, payload)
+
+
+@router.get("/runs", response_model=List[WebhookRunResponse])
+async def list_webhook_runs(project_id: str, limit: int = 50, db: AsyncSession = Depends(get_db)):
+    """List a project's webhook-triggered runs, newest first"""
+    result = await db.execute(
+        select(WebhookRun)
+        .where(WebhookRun.project_id == project_id)
+        .order_by(WebhookRun.created_at.desc())
+        .limit(limit)
+    )
+    return result.scalars().all()
@@ -0,0 +1,206 @@
+"""
+Accessibility Audit - Checks Android UI dumps and web pages for accessibility violations
+"""
+import io
+import json
+from dataclasses import asdict, dataclass
+from typing import Any, Dict, List, Optional, Tuple
+
+from .ui_dump import UiNode
+
+ACCESSIBILITY_STEP_TYPE = "accessibility_check"
+
+# Ordered from least to most severe, matching axe-core impact levels
+SEVERITIES = ("minor", "moderate", "serious", "critical")
+DEFAULT_FAIL_ON = "serious"
+
+# Android's recommended minimum touch target, in dp
+MIN_TOUCH_TARGET_DP = 48
+
+# WCAG AA contrast ratio for normal text
+MIN_CONTRAST_RATIO = 4.5
+
+AXE_SCRIPT_URL = "https://cdnjs.cloudflare.com/ajax/libs/axe-core/4.10.2/axe.min.js"
+
+# Prefix of the line a generated spec prints with each check's violations
+AXE_OUTPUT_MARKER = "AUTOTEST_AXE:"
+
+# Widgets users interact with that need a spoken label
+_LABELLED_CLASSES = ("ImageButton", "ImageView", "Button", "CheckBox", "Switch", "EditText")
+
+
+@dataclass
+class Violation:
+    rule: str
+    severity: str  # One of SEVERITIES
+    message: str
+    target: str = ""  # Resource id, text or CSS selector of the offending element
+    bounds: Optional[List[int]] = None  # [left, top, right, bottom] on Android
+
+
+def _describe(node: UiNode) -> str:
+    return node.resource_id or node.text or node.class_name
+
+
+def _relative_luminance(rgb: Tuple[int, int, int]) -> float:
+    def channel(value: int) -> float:
+        c = value / 255
+        return c / 12.92 if c <= 0.03928 else ((c + 0.055) / 1.055) ** 2.4
+
+    r, g, b = (channel(v) for v in rgb)
+    return 0.2126 * r + 0.7152 * g + 0.0722 * b
+
+
+def contrast_ratio(first: Tuple[int, int, int], second: Tuple[int, int, int]) -> float:
+    lighter, darker = sorted((_relative_luminance(first), _relative_luminance(second)), reverse=True)
+    return (lighter + 0.05) / (darker + 0.05)
+
+
+def _text_contrast(image: Any, node: UiNode) -> Optional[float]:
+    """Estimate text contrast from the two dominant colors inside the node's bounds"""
+    if node.right - node.left < 4 or node.bottom - node.top < 4:
+        return None
+    crop = image.crop((node.left, node.top, node.right, node.bottom)).convert("RGB")
+    colors = crop.quantize(colors=2).convert("RGB").getcolors()
+    if not colors or len(colors) < 2:
+        return None
+    return contrast_ratio(colors[0][1], colors[1][1])
+
+
+def audit_android(
+    nodes: List[UiNode], screenshot_png: Optional[bytes] = None, density_dpi: int = 160
+) -> List[Violation]:
+    """
+    Check a UI dump for missing labels, small touch targets and low-contrast text
+
+    Contrast is only estimated, from the screenshot's dominant colors behind each
+    text node, so those findings are reported as hints at moderate severity.
+    """
+    violations: List[Violation] = []
+    min_target = MIN_TOUCH_TARGET_DP * density_dpi / 160
+
+    image = None
+    if screenshot_png:
+        from PIL import Image
+
+        image = Image.open(io.BytesIO(screenshot_png))
+
+    for node in nodes:
+        bounds = [node.left, node.top, node.right, node.bottom]
+        labelled = node.text.strip() or node.content_desc.strip()
+        if not labelled and (node.clickable or node.class_name.endswith(_LABELLED_CLASSES)):
+            if node.class_name.endswith("ImageView") and not node.clickable:
+                violations.append(Violation(
+                    "image-label", "moderate", "Image has no content description", _describe(node), bounds
+                ))
+            else:
+                violations.append(Violation(
+                    "missing-label", "serious", "Interactive element has no text or content description",
+                    _describe(node), bounds,
+                ))
+
+        if node.clickable:
+            width, height = node.right - node.left, node.bottom - node.top
+            if 0 < width < min_target or 0 < height < min_target:
+                violations.append(Violation(
+                    "touch-target", "moderate",
+                    f"Touch target is {width}x{height}px, below {MIN_TOUCH_TARGET_DP}dp ({min_target:.0f}px)",
+                    _describe(node), bounds,
+                ))
+
+        if image is not None and node.text.strip():
+            ratio = _text_contrast(image, node)
+            if ratio is not None and ratio < MIN_CONTRAST_RATIO:
+                violations.append(Violation(
+                    "color-contrast", "moderate",
+                    f"Text contrast looks like {ratio:.1f}:1, below {MIN_CONTRAST_RATIO}:1",
+                    _describe(node), bounds,
+                ))
+    return violations
+
+
+def evaluate_violations(violations: List[Violation], config: Dict[str, Any]) -> Tuple[bool, Dict[str, Any]]:
+    """
+    Decide whether violations fail the step, returning (passed, details)
+
+    Violations at or above the step's fail_on severity fail it; the rest are
+    kept as warnings. Rules listed in ignore_rules are dropped.
+    """
+    fail_on = config.get("fail_on") or DEFAULT_FAIL_ON
+    if fail_on not in SEVERITIES:
+        fail_on = DEFAULT_FAIL_ON
+    ignored = set(config.get("ignore_rules") or [])
+    kept = [v for v in violations if v.rule not in ignored]
+    failing = [v for v in kept if SEVERITIES.index(v.severity) >= SEVERITIES.index(fail_on)]
+    details = {
+        "accessibility": {
+            "fail_on": fail_on,
+            "failures": len(failing),
+            "warnings": len(kept) - len(failing),
+            "violations": [asdict(v) for v in kept],
+        }
+    }
+    return not failing, details
+
+
+def failure_message(details: Dict[str, Any]) -> str:
+    audit = details["accessibility"]
+    threshold = SEVERITIES.index(audit["fail_on"])
+    rules = sorted({v["rule"] for v in audit["violations"] if SEVERITIES.index(v["severity"]) >= threshold})
+    return f"{audit['failures']} accessibility violations at {audit['fail_on']} or above: {', '.join(rules)}"
+
+
+# ============================================
+# Web (axe-core)
+# ============================================
+
+_AXE_MAP = (
+    "r.violations.map(v => ({ rule: v.id, severity: v.impact || 'minor', message: v.help, "
+    "target: v.nodes.map(n => n.target.join(' ')).join(', ') }))"
+)
+
+
+def axe_playwright_lines() -> List[str]:
+    return [
+        f"  await page.addScriptTag({{ url: '{AXE_SCRIPT_URL}' }});",
+        f"  console.log('{AXE_OUTPUT_MARKER}' + JSON.stringify(await page.evaluate(async () => {{",
+        "    const r = await window.axe.run(document);",
+        f"    return {_AXE_MAP};",
+        "  })));",
+    ]
+
+
+def axe_cypress_lines() -> List[str]:
+    # Browser console output doesn't reach Cypress's stdout, so results go through a task
+    return [
+        "    cy.window().then((win) => new Cypress.Promise((resolve, reject) => {",
+        "      const script = win.document.createElement('script');",
+        f"      script.src = '{AXE_SCRIPT_URL}';",
+        "      script.onload = () => win.axe.run(win.document).then(resolve, reject);",
+        "      script.onerror = () => reject(new Error('Failed to load axe-core'));",
+        "      win.document.head.appendChild(script);",
+        "    })).then((r) => cy.task('log', "
+        f"'{AXE_OUTPUT_MARKER}' + JSON.stringify({_AXE_MAP})));",
+    ]
+
+
+def parse_axe_output(output: str) -> List[List[Violation]]:
+    """Read each check's violations from a spec's output, in step order"""
+    checks = []
+    for line in output.splitlines():
+        if AXE_OUTPUT_MARKER not in line:
+            continue
+        try:
+            items = json.loads(line.split(AXE_OUTPUT_MARKER, 1)[1])
+        except ValueError:
+            continue
+        checks.append([
+            Violation(
+                rule=str(item.get("rule", "")),
+                severity=item.get("severity") if item.get("severity") in SEVERITIES else "minor",
+                message=str(item.get("message", "")),
+                target=str(item.get("target", "")),
+            )
+            for item in items if isinstance(item, dict)
+        ])
+    return checks
@@ -0,0 +1,236 @@
+"""
+Agent Worker - Agent mode: takes execution jobs from another instance and reports results back
+"""
+import asyncio
+import platform
+import time
+from typing import Any, Dict, List, Optional
+
+import httpx
+from fastapi import HTTPException
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from .app_settings import get_all_settings
+from .executor import ScenarioExecutor
+from .secret_masking import job_secrets, mask_data
+from .test_runner import TestFramework, test_runner
+from .web_executor import judge_spec, runner_step
+
+HEARTBEAT_SECONDS = 15
+# How long the orchestrator holds a job request open before answering with no job
+POLL_WAIT_SECONDS = 25
+RETRY_SECONDS = 10
+# How often a disabled agent checks whether agent mode was turned on
+IDLE_CHECK_SECONDS = 30
+
+
+def _mobile():
+    # Imported lazily since the mobile router imports the services package
+    from ..routers import mobile
+    return mobile
+
+
+async def attached_devices() -> List[Dict[str, Any]]:
+    mobile = _mobile()
+    devices = []
+    for lister in (mobile.list_android_devices, mobile.list_ios_devices):
+        try:
+            devices.extend(device.model_dump() for device in await lister())
+        except HTTPException:
+            pass  # adb or xcrun isn't installed
+    return devices
+
+
+class AgentWorker:
+    """Registers with the orchestrator in agent.orchestrator_url and runs the jobs it hands out"""
+
+    def __init__(self):
+        self.agent_id: Optional[str] = None
+        # Sent with each heartbeat so the orchestrator knows the job arrived and is still running
+        self.job_id: Optional[str] = None
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            async with AsyncSessionLocal() as db:
+                values = await get_all_settings(db)
+            url = values["agent.orchestrator_url"].rstrip("/")
+            if not url:
+                await asyncio.sleep(IDLE_CHECK_SECONDS)
+                continue
+            headers = {"Authorization": f"Bearer {values['agent.token']}"}
+            try:
+                async with httpx.AsyncClient(base_url=f"{url}/api/agents", headers=headers) as client:
+                    await self._register(client, values)
+                    heartbeat = asyncio.create_task(self._heartbeat(client, values))
+                    try:
+                        await self._poll(client)
+                    finally:
+                        heartbeat.cancel()
+            except (httpx.HTTPError, ValueError) as e:
+                # Keep trying through network errors and orchestrator restarts
+                print(f"Agent error: {e}")
+                await asyncio.sleep(RETRY_SECONDS)
+
+    async def _registration(self, values: Dict[str, Any]) -> Dict[str, Any]:
+        browsers = [b.strip() for b in values["agent.browsers"].split(",") if b.strip()]
+        return {
+            "name": values["agent.name"] or platform.node(),
+            "hostname": platform.node(),
+            "version": settings.app_version,
+            "devices": await attached_devices(),
+            "browsers": browsers,
+            "job_ids": [self.job_id] if self.job_id else [],
+        }
+
+    async def _register(self, client: httpx.AsyncClient, values: Dict[str, Any]) -> None:
+        params = {"agent_id": self.agent_id} if self.agent_id else {}
+        response = await client.post("/register", params=params, json=await self._registration(values))
+        response.raise_for_status()
+        self.agent_id = response.json()["id"]
+
+    async def _heartbeat(self, client: httpx.AsyncClient, values: Dict[str, Any]) -> None:
+        while True:
+            await asyncio.sleep(HEARTBEAT_SECONDS)
+            try:
+                response = await client.post(f"/{self.agent_id}/heartbeat", json=await self._registration(values))
+                response.raise_for_status()
+            except httpx.HTTPError as e:
+                print(f"Agent heartbeat failed: {e}")
+
+    async def _poll(self, client: httpx.AsyncClient) -> None:
+        while True:
+            response = await client.get(
+                f"/{self.agent_id}/jobs/next",
+                params={"wait": POLL_WAIT_SECONDS},
+                timeout=POLL_WAIT_SECONDS + 10,
+            )
+            response.raise_for_status()
+            if response.status_code == 204:
+                continue
+            job = response.json()
+            self.job_id = job["job_id"]
+            try:
+                result = await run_job(job)
+                response = await client.post(f"/{self.agent_id}/jobs/{job['job_id']}/result", json=result)
+                response.raise_for_status()
+            finally:
+                self.job_id = None
+
+
+# ============================================
+# Jobs
+# ============================================
+
+async def run_job(job: Dict[str, Any]) -> Dict[str, Any]:
+    """Run a job's scenarios, returning the step results and log lines to report"""
+    steps: List[Dict[str, Any]] = []
+    logs: List[Dict[str, Any]] = []
+    with job_secrets(job.get("secrets") or []):
+        try:
+            if job["target"] == "browser":
+                await _run_browser_job(job, steps, logs)
+            else:
+                await _run_device_job(job, steps, logs)
+        except Exception as e:
+            return mask_data({"steps": steps, "logs": logs, "error": f"Agent failed while running the job: {e}"})
+        return mask_data({"steps": steps, "logs": logs})
+
+
+def _skip_all(scenario: Dict[str, Any], steps: List[Dict[str, Any]], reason: str) -> None:
+    for step in scenario["steps"]:
+        steps.append({"step_id": step["id"], "status": "skipped", "duration_ms": 0, "error_message": reason})
+
+
+async def _run_device_job(job: Dict[str, Any], steps: List[Dict[str, Any]], logs: List[Dict[str, Any]]) -> None:
+    executor = ScenarioExecutor(job["device_id"], job["platform"], self_heal=job["self_heal"], ai_free=job["ai_free"])
+    executor.env = job.get("env") or {}
+    outcomes: Dict[str, str] = {}
+    for scenario in job["scenarios"]:
+        if any(outcomes.get(p) != "passed" for p in scenario.get("prerequisites", [])):
+            reason = "Skipped: prerequisite did not pass"
+            logs.append({"level": "warning", "message": f"{scenario['name']}: {reason}"})
+            _skip_all(scenario, steps, reason)
+            outcomes[scenario["id"]] = "skipped"
+            continue
+
+        logs.append({"level": "info", "message": f"Running scenario {scenario['name']}"})
+        failed = stopped = False
+        for index, step in enumerate(scenario["steps"]):
+            if stopped:
+                steps.append({
+                    "step_id": step["id"], "status": "skipped", "duration_ms": 0,
+                    "error_message": "Skipped: an earlier step failed",
+                })
+                continue
+            logs.append({"level": "info", "message": f"Step {index + 1}: {step['step_type']}", "step_index": index})
+            outcome = await executor.execute_step(step["step_type"], step["config"])
+            steps.append({
+                "step_id": step["id"],
+                "status": outcome.status,
+                "duration_ms": outcome.duration_ms,
+                "error_message": outcome.error_message,
+                "healed": outcome.healed,
+                "healed_locator": outcome.healed_locator,
+                "details": outcome.details,
+            })
+            if outcome.status != "passed":
+                failed = True
+                stopped = not outcome.soft
+                logs.append({
+                    "level": "error", "message": outcome.error_message or "Step failed", "step_index": index
+                })
+        outcomes[scenario["id"]] = "failed" if failed else "passed"
+
+
+async def _run_browser_job(job: Dict[str, Any], steps: List[Dict[str, Any]], logs: List[Dict[str, Any]]) -> None:
+    framework = TestFramework(job.get("framework") or TestFramework.PLAYWRIGHT.value)
+    browser = job.get("browser") or "chromium"
+    outcomes: Dict[str, str] = {}
+    for scenario in job["scenarios"]:
+        if any(outcomes.get(p) != "passed" for p in scenario.get("prerequisites", [])):
+            _skip_all(scenario, steps, "Skipped: prerequisite did not pass")
+            outcomes[scenario["id"]] = "skipped"
+            continue
+        if not scenario["steps"]:
+            outcomes[scenario["id"]] = "passed"
+            continue
+
+        runner_steps = [runner_step(step["step_type"], step["config"]) for step in scenario["steps"]]
+        logs.append({"level": "info", "message": f"Running {scenario['name']} with {framework.value} in {browser}"})
+        started = time.time()
+        if framework == TestFramework.CYPRESS:
+            result = await test_runner.run_steps_as_cypress(
+                runner_steps, scenario["base_url"], browser, job.get("headless", True)
+            )
+        else:
+            result = await test_runner.run_steps_as_playwright(
+                runner_steps, scenario["base_url"], browser, job.get("headless", True)
+            )
+        duration_ms = int((time.time() - started) * 1000) // len(scenario["steps"])
+        judged = judge_spec(result, [(step["step_type"], step["config"]) for step in scenario["steps"]])
+        for step, (status, error, details) in zip(scenario["steps"], judged):
+            steps.append({
+                "step_id": step["id"],
+                "status": status,
+                "duration_ms": 0 if status == "skipped" else duration_ms,
+                "error_message": error,
+                "details": details,
+            })
+        failed = any(status == "failed" for status, _, _ in judged)
+        outcomes[scenario["id"]] = "failed" if failed else "passed"
+
+
+# Singleton instance
+agent_worker = AgentWorker()
@@ -0,0 +1,424 @@
+"""
+Runner Agents - Schedules test runs on remote machines running in agent mode
+"""
+import asyncio
+import hmac
+import json
+import secrets
+import uuid
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from fastapi import Depends, Header, HTTPException
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import AsyncSessionLocal, get_db
+from ..models import (
+    AgentRegistration,
+    Project,
+    RunLogCreate,
+    RunnerAgent,
+    RunnerAgentResponse,
+    Scenario,
+    Step,
+    StepResult,
+    TestRun,
+)
+from .app_settings import get_setting
+from .elements import apply_element, load_elements
+from .events import event_bus
+from .run_logs import LOG_LEVELS, add_run_logs
+from .run_lifecycle import RunTransitionError, check_transition, complete_run, fail_run, start_run
+from .secret_masking import project_secret_values
+from .shell_steps import SHELL_STEP_TYPE
+
+# Agents that haven't sent a heartbeat for this long are treated as offline
+AGENT_TIMEOUT = timedelta(seconds=60)
+
+# A job handed to an agent is requeued, or its run failed, when no heartbeat lists it for this long
+JOB_LEASE = timedelta(seconds=45)
+
+# Requeues of a job no agent confirmed receiving before its run is failed
+MAX_DELIVERY_ATTEMPTS = 3
+
+# How often assigned jobs are checked for expired leases, in seconds
+LEASE_CHECK_SECONDS = 15
+
+# Device statuses that can take a run: adb's "device", booted simulators and connected iPhones
+READY_STATUSES = ("device", "booted", "connected")
+
+
+class AgentError(Exception):
+    """Raised when no agent can take a run or a job result doesn't match its job"""
+
+
+class JobReportError(AgentError):
+    """Raised for job results that aren't shaped like an agent's report"""
+
+
+# ============================================
+# Authentication
+# ============================================
+
+def _token_path() -> Path:
+    return settings.get_database_path().parent / "agent_token"
+
+
+def get_agent_token() -> str:
+    """Read the token agents register with, creating one on first use"""
+    path = _token_path()
+    if path.exists():
+        token = path.read_text(encoding="utf-8").strip()
+        if token:
+            return token
+    return rotate_agent_token()
+
+
+def rotate_agent_token() -> str:
+    """Replace the agent token; agents using the old one are refused until reconfigured"""
+    token = secrets.token_urlsafe(32)
+    path = _token_path()
+    path.write_text(token, encoding="utf-8")
+    try:
+        path.chmod(0o600)
+    except OSError:
+        pass  # Not supported on every platform
+    return token
+
+
+async def require_agent_token(
+    authorization: Optional[str] = Header(None), db: AsyncSession = Depends(get_db)
+) -> None:
+    """Reject agent calls while agents are disabled or without the bearer token"""
+    if not await get_setting(db, "agents.enabled"):
+        raise HTTPException(status_code=403, detail="Runner agents are disabled")
+    scheme, _, token = (authorization or "").partition(" ")
+    if scheme.lower() != "bearer" or not hmac.compare_digest(token.strip(), get_agent_token()):
+        raise HTTPException(status_code=401, detail="Invalid agent token", headers={"WWW-Authenticate": "Bearer"})
+
+
+# ============================================
+# Jobs
+# ============================================
+
+@dataclass
+class AgentJob:
+    id: str
+    agent_id: str
+    test_run_id: str
+    payload: Dict[str, Any]
+    state: str = "queued"  # 'queued' | 'assigned'
+    created_at: datetime = field(default_factory=datetime.utcnow)
+    # When the job was handed out or a heartbeat last listed it, and whether one ever has
+    leased_at: Optional[datetime] = None
+    confirmed: bool = False
+    deliveries: int = 0
+
+
+_queues: Dict[str, "asyncio.Queue[AgentJob]"] = {}
+_jobs: Dict[str, AgentJob] = {}
+
+
+def _queue(agent_id: str) -> "asyncio.Queue[AgentJob]":
+    return _queues.setdefault(agent_id, asyncio.Queue())
+
+
+def active_jobs(agent_id: str) -> int:
+    return sum(1 for job in _jobs.values() if job.agent_id == agent_id)
+
+
+def is_online(agent: RunnerAgent) -> bool:
+    return agent.last_seen_at is not None and datetime.utcnow() - agent.last_seen_at < AGENT_TIMEOUT
+
+
+def agent_response(agent: RunnerAgent) -> RunnerAgentResponse:
+    response = RunnerAgentResponse.model_validate(agent)
+    response.online = is_online(agent)
+    response.active_jobs = active_jobs(agent.id)
+    return response
+
+
+async def register_agent(db: AsyncSession, data: AgentRegistration, agent_id: Optional[str] = None) -> RunnerAgent:
+    """Create or refresh an agent's record; agents re-register with their ID after a restart"""
+    agent = await db.get(RunnerAgent, agent_id) if agent_id else None
+    if not agent:
+        agent = RunnerAgent(name=data.name)
+        db.add(agent)
+    agent.name = data.name
+    agent.hostname = data.hostname
+    agent.version = data.version
+    agent.devices = json.dumps(data.devices)
+    agent.browsers = json.dumps(data.browsers)
+    agent.last_seen_at = datetime.utcnow()
+    await db.commit()
+    await db.refresh(agent)
+    for job in _jobs.values():
+        if job.agent_id == agent.id and job.state == "assigned" and job.id in data.job_ids:
+            job.leased_at, job.confirmed = agent.last_seen_at, True
+    event_bus.publish("agent:updated", {"agent_id": agent.id, "online": True})
+    return agent
+
+
+async def choose_agent(
+    db: AsyncSession,
+    platform: Optional[str] = None,
+    device_id: Optional[str] = None,
+    browser: Optional[str] = None,
+    agent_id: Optional[str] = None,
+) -> RunnerAgent:
+    """Pick the online agent with the fewest active jobs that has the device or browser"""
+    result = await db.execute(select(RunnerAgent))
+    candidates = []
+    for agent in result.scalars().all():
+        if not is_online(agent) or (agent_id and agent.id != agent_id):
+            continue
+        if browser:
+            if browser in json.loads(agent.browsers or "[]"):
+                candidates.append(agent)
+            continue
+        devices = [d for d in json.loads(agent.devices or "[]") if d.get("platform") == platform]
+        if device_id:
+            devices = [d for d in devices if d.get("id") == device_id]
+        if any(d.get("status") in READY_STATUSES for d in devices):
+            candidates.append(agent)
+    if not candidates:
+        wanted = browser or (f"device {device_id}" if device_id else f"an {platform} device")
+        raise AgentError(f"No online agent has {wanted}")
+    return min(candidates, key=lambda agent: active_jobs(agent.id))
+
+
+def _pick_device(agent: RunnerAgent, platform: str) -> str:
+    devices = json.loads(agent.devices or "[]")
+    for device in devices:
+        if device.get("platform") == platform and device.get("status") in READY_STATUSES:
+            return device["id"]
+    raise AgentError(f"Agent {agent.name} has no {platform} device")
+
+
+async def dispatch_run(
+    db: AsyncSession,
+    agent: RunnerAgent,
+    test_run: TestRun,
+    scenario_ids: List[str],
+    options: Dict[str, Any],
+    prerequisites: Optional[Dict[str, List[str]]] = None,
+) -> AgentJob:
+    """
+    Queue a run's scenarios for an agent, with everything it needs to run them
+
+    Steps are sent with element references already resolved, so agents don't
+    need this machine's database. Browser runs can't include shell steps.
+    """
+    project = await db.get(Project, test_run.project_id)
+    elements = await load_elements(db, project.id)
+    target = options.get("target", "device")
+    platform = "web" if target == "browser" else options["platform"]
+    payload: Dict[str, Any] = {
+        "test_run_id": test_run.id,
+        "target": target,
+        "platform": platform,
+        "device_id": None if target == "browser" else options.get("device_id") or _pick_device(agent, platform),
+        "framework": options.get("framework"),
+        "browser": options.get("browser"),
+        "headless": options.get("headless", True),
+        "self_heal": options.get("self_heal", True),
+        "ai_free": project.ai_free,
+        "env": json.loads(project.env_vars or "{}"),
+        # Masked by the agent too, since it calls the AI and reports logs itself
+        "secrets": sorted(project_secret_values(project)),
+        "scenarios": [],
+    }
+    for scenario_id in scenario_ids:
+        scenario = await db.get(Scenario, scenario_id)
+        steps = (
+            await db.execute(select(Step).where(Step.scenario_id == scenario_id).order_by(Step.step_order))
+        ).scalars().all()
+        if target == "browser" and any(step.step_type == SHELL_STEP_TYPE for step in steps):
+            raise AgentError(f"Scenario {scenario.name} has shell steps, which agents only run on devices")
+        payload["scenarios"].append({
+            "id": scenario.id,
+            "name": scenario.name,
+            "base_url": scenario.target_url or project.app_url,
+            "prerequisites": (prerequisites or {}).get(scenario_id, []),
+            "steps": [
+                {
+                    "id": step.id,
+                    "step_type": step.step_type,
+                    "config": apply_element(json.loads(step.config or "{}"), elements, platform),
+                }
+                for step in steps
+            ],
+        })
+
+    job = AgentJob(id=str(uuid.uuid4()), agent_id=agent.id, test_run_id=test_run.id, payload=payload)
+    job.payload["job_id"] = job.id
+    _jobs[job.id] = job
+    await _queue(agent.id).put(job)
+    event_bus.publish("agent:job_queued", {"agent_id": agent.id, "job_id": job.id, "test_run_id": test_run.id})
+    return job
+
+
+async def next_job(db: AsyncSession, agent_id: str, wait_seconds: float) -> Optional[AgentJob]:
+    """
+    Wait for the agent's next job, marking its test run as running once handed over
+
+    The job stays leased to the agent until it reports the result. Its
+    heartbeats list the jobs it's running; one that isn't listed in time never
+    arrived and is handed out again.
+    """
+    try:
+        job = await asyncio.wait_for(_queue(agent_id).get(), wait_seconds)
+    except asyncio.TimeoutError:
+        return None
+    job.state = "assigned"
+    job.leased_at = datetime.utcnow()
+    job.deliveries += 1
+    test_run = await db.get(TestRun, job.test_run_id)
+    if test_run and test_run.status == "pending":
+        try:
+            await start_run(db, test_run)
+        except RunTransitionError:
+            pass  # Cancelled while queued; the agent's result is rejected when it reports
+    return job
+
+
+async def expire_job_leases(db: AsyncSession) -> int:
+    """Requeue jobs their agent never confirmed receiving and fail runs whose agent went quiet"""
+    expired = [
+        job for job in _jobs.values()
+        if job.state == "assigned" and job.leased_at and datetime.utcnow() - job.leased_at > JOB_LEASE
+    ]
+    for job in expired:
+        agent = await db.get(RunnerAgent, job.agent_id)
+        if not job.confirmed and agent and is_online(agent) and job.deliveries < MAX_DELIVERY_ATTEMPTS:
+            job.state, job.leased_at = "queued", None
+            await _queue(job.agent_id).put(job)
+            event_bus.publish("agent:job_requeued", {"agent_id": job.agent_id, "job_id": job.id})
+            continue
+
+        _jobs.pop(job.id, None)
+        test_run = await db.get(TestRun, job.test_run_id)
+        if test_run and test_run.status in ("pending", "running"):
+            reason = "The agent stopped responding" if job.confirmed else "The agent never received the job"
+            await add_run_logs(db, test_run.id, [RunLogCreate(level="error", source="agent", message=reason)])
+            try:
+                await fail_run(db, test_run, reason)
+            except RunTransitionError:
+                pass  # Finished or cancelled meanwhile
+        event_bus.publish("agent:job_lost", {"agent_id": job.agent_id, "job_id": job.id})
+    return len(expired)
+
+
+class JobLeaseMonitor:
+    """Checks assigned agent jobs for expired leases every LEASE_CHECK_SECONDS"""
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            await asyncio.sleep(LEASE_CHECK_SECONDS)
+            try:
+                async with AsyncSessionLocal() as db:
+                    await expire_job_leases(db)
+            except Exception as e:
+                event_bus.publish("agent:lease_check_failed", {"error": str(e)})
+
+
+def _check_report(result: Dict[str, Any]) -> None:
+    for key in ("steps", "logs"):
+        items = result.get(key, [])
+        if not isinstance(items, list) or not all(isinstance(item, dict) for item in items):
+            raise JobReportError(f"'{key}' must be a list of objects")
+    for line in result.get("logs", []):
+        if line.get("level", "info") not in LOG_LEVELS:
+            raise JobReportError(f"Log level must be one of: {', '.join(LOG_LEVELS)}")
+
+
+async def record_job_result(db: AsyncSession, agent_id: str, job_id: str, result: Dict[str, Any]) -> TestRun:
+    """Store the step results and log lines an agent reports for a finished job"""
+    job = _jobs.get(job_id)
+    if not job or job.agent_id != agent_id:
+        raise AgentError("Unknown job")
+    _check_report(result)
+    test_run = await db.get(TestRun, job.test_run_id)
+    if not test_run:
+        _jobs.pop(job_id, None)
+        raise AgentError("The job's test run no longer exists")
+    try:
+        check_transition(test_run.status, "passed")
+    except RunTransitionError as e:
+        _jobs.pop(job_id, None)
+        raise AgentError(str(e))
+
+    test_cases = {
+        scenario["id"]: (await db.get(Scenario, scenario["id"])).test_case_id
+        for scenario in job.payload["scenarios"]
+    }
+    step_scenarios = {
+        step["id"]: scenario["id"] for scenario in job.payload["scenarios"] for step in scenario["steps"]
+    }
+    counts = {"passed": 0, "failed": 0, "skipped": 0}
+    for item in result.get("steps", []):
+        scenario_id = step_scenarios.get(item.get("step_id"))
+        if not scenario_id:
+            continue
+        status = item.get("status", "failed")
+        counts["passed" if status == "passed" else "skipped" if status == "skipped" else "failed"] += 1
+        db.add(StepResult(
+            test_run_id=test_run.id,
+            step_id=item["step_id"],
+            test_case_id=test_cases[scenario_id],
+            status=status,
+            duration_ms=item.get("duration_ms"),
+            error_message=item.get("error_message"),
+            healed=bool(item.get("healed")),
+            healed_locator=json.dumps(item["healed_locator"]) if item.get("healed_locator") else None,
+            details=json.dumps(item["details"]) if item.get("details") else None,
+        ))
+
+    agent = await db.get(RunnerAgent, agent_id)
+    logs = [RunLogCreate(level="info", source="agent", message=f"Ran on agent {agent.name if agent else agent_id}")]
+    logs += [
+        RunLogCreate(
+            level=line.get("level", "info"),
+            source="agent",
+            step_index=line.get("step_index"),
+            message=str(line.get("message", "")),
+        )
+        for line in result.get("logs", [])
+    ]
+    await add_run_logs(db, test_run.id, logs)
+
+    if result.get("error"):
+        await add_run_logs(db, test_run.id, [RunLogCreate(level="error", source="agent", message=result["error"])])
+    _jobs.pop(job_id, None)
+    try:
+        # The agent crashed part way when it reports an error, so the run failed whatever its steps say
+        await complete_run(
+            db, test_run, counts["passed"], counts["failed"], counts["skipped"],
+            reason="Agent error" if result.get("error") else None,
+            force_failed=bool(result.get("error")),
+        )
+    except RunTransitionError as e:
+        raise AgentError(str(e))
+    event_bus.publish("agent:job_completed", {"agent_id": agent_id, "job_id": job_id, "test_run_id": test_run.id})
+    return test_run
+
+
+# Singleton instance
+job_lease_monitor = JobLeaseMonitor()
@@ -0,0 +1,70 @@
+"""
+AI Cache Service - Content-addressed cache for AI element lookups
+"""
+import hashlib
+import json
+from datetime import datetime, timedelta
+from typing import Any, Optional
+
+from sqlalchemy import delete
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal, queue_write
+from ..models import AiCacheEntry
+from .app_settings import get_setting
+
+
+def cache_key(command: str, *parts: Optional[str]) -> str:
+    """Hash the inputs that determine an AI result (screenshot, UI dump, description...)"""
+    digest = hashlib.sha256(command.encode())
+    for part in parts:
+        digest.update(b"\0")
+        digest.update((part or "").encode())
+    return digest.hexdigest()
+
+
+async def get_cached_result(key: str) -> Optional[Any]:
+    """Return a cached result if present and not expired"""
+    async with AsyncSessionLocal() as db:
+        entry = await db.get(AiCacheEntry, key)
+        if not entry:
+            return None
+        if entry.expires_at <= datetime.utcnow():
+            await db.delete(entry)
+            await db.commit()
+            return None
+
+        entry.hit_count += 1
+        await db.commit()
+        return json.loads(entry.result)
+
+
+async def store_cached_result(key: str, command: str, result: Any) -> None:
+    """Cache a result for the configured TTL (a TTL of 0 disables caching)"""
+
+    async def write(db: AsyncSession) -> None:
+        ttl_hours = await get_setting(db, "ai.cache_ttl_hours")
+        if ttl_hours <= 0:
+            return
+
+        entry = await db.get(AiCacheEntry, key)
+        if not entry:
+            entry = AiCacheEntry(key=key, command=command, hit_count=0)
+            db.add(entry)
+        entry.result = json.dumps(result)
+        entry.created_at = datetime.utcnow()
+        entry.expires_at = datetime.utcnow() + timedelta(hours=ttl_hours)
+
+    # The result is already in hand, so a busy database delays caching it rather than failing the lookup
+    await queue_write(write)
+
+
+async def clear_cache(expired_only: bool = False) -> int:
+    """Delete cached results, returning the number removed"""
+    async with AsyncSessionLocal() as db:
+        query = delete(AiCacheEntry)
+        if expired_only:
+            query = query.where(AiCacheEntry.expires_at <= datetime.utcnow())
+        result = await db.execute(query)
+        await db.commit()
+        return result.rowcount
@@ -0,0 +1,326 @@
+"""
+AI Client - Direct calls to an AI provider for features that don't go through the AI agent
+"""
+import json
+import re
+import time
+from abc import ABC, abstractmethod
+from dataclasses import dataclass
+from typing import Any, Dict, Optional
+
+import httpx
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from .ai_usage import record_usage
+from .app_settings import get_all_settings
+from .secret_masking import mask_secrets
+from .tracing import tracer
+
+ANTHROPIC_API_URL = "https://api.anthropic.com/v1"
+ANTHROPIC_VERSION = "2023-06-01"
+CLAUDE_MODEL = "claude-sonnet-4-20250514"
+
+OPENAI_API_URL = "https://api.openai.com/v1"
+OPENAI_MODEL = "gpt-4o"
+
+LOCAL_AI_URL = "http://127.0.0.1:11434/v1"  # Ollama's OpenAI-compatible endpoint
+LOCAL_AI_MODEL = "llava"
+
+
+class AiClientError(Exception):
+    """Raised when the AI provider can't be reached or returns an unusable response"""
+
+
+def extract_json(text: str) -> Any:
+    """Extract a JSON value from a model response that may wrap it in prose or code fences"""
+    fenced = re.search(r"```(?:json)?\s*(.*?)```", text, re.DOTALL)
+    if fenced:
+        text = fenced.group(1)
+
+    starts = [i for i in (text.find("{"), text.find("[")) if i != -1]
+    if not starts:
+        raise AiClientError("AI response did not contain JSON")
+    start = min(starts)
+    end = max(text.rfind("}"), text.rfind("]"))
+
+    try:
+        return json.loads(text[start:end + 1])
+    except json.JSONDecodeError as e:
+        raise AiClientError(f"Failed to parse AI response: {e}")
+
+
+@dataclass
+class AiOptions:
+    """Provider selection and generation parameters for an AI call"""
+
+    provider: str
+    model: str
+    max_tokens: int
+    temperature: float
+    base_url: str
+
+
+async def load_ai_options(purpose: str = "general") -> AiOptions:
+    """
+    Load AI options from app settings
+
+    The "element" purpose uses the ai.element_model setting when set, so
+    element lookups can run on a cheaper model.
+    """
+    async with AsyncSessionLocal() as db:
+        values = await get_all_settings(db)
+
+    model = values["ai.model"]
+    if purpose == "element" and values["ai.element_model"]:
+        model = values["ai.element_model"]
+
+    return AiOptions(
+        provider=values["ai.provider"],
+        model=model,
+        max_tokens=values["ai.max_tokens"],
+        temperature=values["ai.temperature"],
+        base_url=values["ai.base_url"],
+    )
+
+
+@dataclass
+class AiResponse:
+    """Response text with the token usage reported by the provider"""
+
+    text: str
+    input_tokens: int = 0
+    output_tokens: int = 0
+
+
+class AiProvider(ABC):
+    """A chat model that accepts a text prompt with an optional screenshot"""
+
+    name: str
+
+    def __init__(self, model: str, base_url: str):
+        self.model = model
+        self.base_url = base_url.rstrip("/")
+
+    @abstractmethod
+    async def complete(
+        self,
+        prompt: str,
+        image_base64: Optional[str] = None,
+        max_tokens: int = 4096,
+        temperature: float = 0.0,
+    ) -> AiResponse:
+        """Send a prompt and return the response text"""
+
+    async def _post(self, url: str, headers: Dict[str, str], payload: Dict[str, Any]) -> Dict[str, Any]:
+        try:
+            async with httpx.AsyncClient(timeout=120.0) as client:
+                response = await client.post(url, headers=headers, json=payload)
+                response.raise_for_status()
+                return response.json()
+        except httpx.HTTPError as e:
+            raise AiClientError(f"{self.name} API error: {str(e)}")
+
+
+class AnthropicProvider(AiProvider):
+    name = "anthropic"
+
+    def __init__(self, api_key: str, model: str, base_url: str = ANTHROPIC_API_URL):
+        super().__init__(model, base_url)
+        if not api_key:
+            raise AiClientError("ANTHROPIC_API_KEY is not configured")
+        self.api_key = api_key
+
+    async def complete(
+        self,
+        prompt: str,
+        image_base64: Optional[str] = None,
+        max_tokens: int = 4096,
+        temperature: float = 0.0,
+    ) -> AiResponse:
+        content = []
+        if image_base64:
+            content.append(
+                {
+                    "type": "image",
+                    "source": {"type": "base64", "media_type": "image/png", "data": image_base64},
+                }
+            )
+        content.append({"type": "text", "text": prompt})
+
+        data = await self._post(
+            f"{self.base_url}/messages",
+            {
+                "x-api-key": self.api_key,
+                "anthropic-version": ANTHROPIC_VERSION,
+                "content-type": "application/json",
+            },
+            {
+                "model": self.model,
+                "max_tokens": max_tokens,
+                "temperature": temperature,
+                "messages": [{"role": "user", "content": content}],
+            },
+        )
+        usage = data.get("usage") or {}
+        return AiResponse(
+            text="".join(
+                block.get("text", "") for block in data.get("content", []) if block.get("type") == "text"
+            ),
+            input_tokens=usage.get("input_tokens", 0),
+            output_tokens=usage.get("output_tokens", 0),
+        )
+
+
+class OpenAIProvider(AiProvider):
+    name = "openai"
+
+    def __init__(self, api_key: str, model: str, base_url: str = OPENAI_API_URL):
+        super().__init__(model, base_url)
+        self.api_key = api_key
+
+    def _url(self) -> str:
+        return f"{self.base_url}/chat/completions"
+
+    def _headers(self) -> Dict[str, str]:
+        headers = {"content-type": "application/json"}
+        if self.api_key:
+            headers["authorization"] = f"Bearer {self.api_key}"
+        return headers
+
+    async def complete(
+        self,
+        prompt: str,
+        image_base64: Optional[str] = None,
+        max_tokens: int = 4096,
+        temperature: float = 0.0,
+    ) -> AiResponse:
+        content = [{"type": "text", "text": prompt}]
+        if image_base64:
+            content.append(
+                {"type": "image_url", "image_url": {"url": f"data:image/png;base64,{image_base64}"}}
+            )
+
+        data = await self._post(
+            self._url(),
+            self._headers(),
+            {
+                "model": self.model,
+                "max_tokens": max_tokens,
+                "temperature": temperature,
+                "messages": [{"role": "user", "content": content}],
+            },
+        )
+        choices = data.get("choices") or []
+        if not choices:
+            raise AiClientError(f"{self.name} returned no choices")
+        usage = data.get("usage") or {}
+        return AiResponse(
+            text=choices[0].get("message", {}).get("content") or "",
+            input_tokens=usage.get("prompt_tokens", 0),
+            output_tokens=usage.get("completion_tokens", 0),
+        )
+
+
+class LocalProvider(OpenAIProvider):
+    """OpenAI-compatible local server such as Ollama or LM Studio"""
+
+    name = "local"
+
+    def __init__(self, model: str, base_url: str = LOCAL_AI_URL):
+        super().__init__("", model, base_url)
+
+
+class AzureOpenAIProvider(OpenAIProvider):
+    name = "azure"
+
+    def __init__(self, api_key: str, endpoint: str, deployment: str, api_version: str):
+        if not (api_key and endpoint and deployment):
+            raise AiClientError("Azure OpenAI endpoint, deployment, and API key must be configured")
+        super().__init__(api_key, deployment, endpoint)
+        self.api_version = api_version
+
+    def _url(self) -> str:
+        return (
+            f"{self.base_url}/openai/deployments/{self.model}/chat/completions"
+            f"?api-version={self.api_version}"
+        )
+
+    def _headers(self) -> Dict[str, str]:
+        return {"content-type": "application/json", "api-key": self.api_key}
+
+
+def get_provider(options: AiOptions) -> AiProvider:
+    """Build the provider selected in the AI options"""
+    provider = options.provider.lower()
+
+    if provider == "anthropic":
+        return AnthropicProvider(
+            settings.anthropic_api_key,
+            options.model or CLAUDE_MODEL,
+            options.base_url or ANTHROPIC_API_URL,
+        )
+    if provider == "openai":
+        if not settings.openai_api_key:
+            raise AiClientError("OPENAI_API_KEY is not configured")
+        return OpenAIProvider(
+            settings.openai_api_key,
+            options.model or OPENAI_MODEL,
+            options.base_url or settings.openai_base_url or OPENAI_API_URL,
+        )
+    if provider == "local":
+        return LocalProvider(
+            options.model or LOCAL_AI_MODEL,
+            options.base_url or settings.local_ai_url or LOCAL_AI_URL,
+        )
+    if provider == "azure":
+        return AzureOpenAIProvider(
+            settings.azure_openai_api_key,
+            options.base_url or settings.azure_openai_endpoint,
+            options.model or settings.azure_openai_deployment,
+            settings.azure_openai_api_version,
+        )
+
+    raise AiClientError(f"Unknown AI provider: {options.provider}")
+
+
+async def ask_ai_json(
+    prompt: str,
+    command: str,
+    image_base64: Optional[str] = None,
+    purpose: str = "general",
+    max_tokens: Optional[int] = None,
+) -> Any:
+    """
+    Send a prompt (optionally with a PNG screenshot) to the configured provider
+
+    Every request is recorded in AI usage under the given command name.
+    Secret variable values are masked out of the prompt.
+    """
+    prompt = mask_secrets(prompt)
+    options = await load_ai_options(purpose)
+    provider = get_provider(options)
+
+    start_time = time.time()
+    try:
+        with tracer.span(command, "ai", provider=provider.name, model=provider.model, image=bool(image_base64)) as span:
+            response = await provider.complete(
+                prompt, image_base64, max_tokens or options.max_tokens, options.temperature
+            )
+            if span:
+                span.set(input_tokens=response.input_tokens, output_tokens=response.output_tokens)
+    except AiClientError:
+        await record_usage(
+            command, provider.name, provider.model, 0, 0, int((time.time() - start_time) * 1000), success=False
+        )
+        raise
+
+    await record_usage(
+        command,
+        provider.name,
+        provider.model,
+        response.input_tokens,
+        response.output_tokens,
+        int((time.time() - start_time) * 1000),
+    )
+    return extract_json(response.text)
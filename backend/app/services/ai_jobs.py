@@ -0,0 +1,181 @@
+"""
+AI Jobs - Long-running AI agent requests submitted as jobs and polled to completion
+
+Generating tests for a large codebase can take longer than any request
+timeout, so the AI agent also accepts them as jobs:
+
+    POST   /jobs/generate-tests   -> {"job_id"}
+    GET    /jobs/{job_id}         -> {"status", "progress", "message", "error"}
+    GET    /jobs/{job_id}/result  -> the same body as POST /generate-tests
+    DELETE /jobs/{job_id}         -> cancels the job
+
+Each submitted job is polled in the background and its progress published
+as 'ai:job_progress' events until it finishes, so the UI doesn't have to poll.
+"""
+import asyncio
+import time
+from dataclasses import dataclass, field
+from typing import Any, Dict, Optional
+
+import httpx
+from pydantic import BaseModel
+
+from .ai_usage import record_usage
+from .events import event_bus
+from .secret_masking import mask_data
+from .service_urls import get_service_url
+
+POLL_INTERVAL_SECONDS = 2.0
+
+# Polling gives up on a job the agent hasn't finished in this long
+MAX_JOB_SECONDS = 60 * 60
+
+FINISHED_STATUSES = ("completed", "failed", "cancelled")
+
+
+class AiJobError(Exception):
+    """Raised when the AI agent can't be reached or rejects a job request"""
+
+
+class AiJobNotFound(Exception):
+    """Raised for job ids that weren't submitted through this backend"""
+
+
+class AiJobResponse(BaseModel):
+    """Schema for a submitted AI job's state"""
+    job_id: str
+    command: str
+    status: str  # queued | running | completed | failed | cancelled
+    progress: float = 0.0  # 0 to 1, as reported by the agent
+    message: Optional[str] = None
+    error: Optional[str] = None
+    submitted_at: float
+
+
+@dataclass
+class AiJob:
+    job_id: str
+    command: str
+    status: str = "queued"
+    progress: float = 0.0
+    message: Optional[str] = None
+    error: Optional[str] = None
+    submitted_at: float = field(default_factory=time.time)
+    usage_recorded: bool = False
+    _task: Optional[asyncio.Task] = None
+
+    def describe(self) -> AiJobResponse:
+        return AiJobResponse(
+            job_id=self.job_id,
+            command=self.command,
+            status=self.status,
+            progress=self.progress,
+            message=self.message,
+            error=self.error,
+            submitted_at=self.submitted_at,
+        )
+
+    @property
+    def finished(self) -> bool:
+        return self.status in FINISHED_STATUSES
+
+
+# Jobs submitted since startup, keyed by the agent's job id
+_jobs: Dict[str, AiJob] = {}
+
+
+async def _agent_request(method: str, path: str, payload: Optional[dict] = None, timeout: float = 30.0) -> Any:
+    try:
+        async with httpx.AsyncClient(timeout=timeout) as client:
+            response = await client.request(method, f"{await get_service_url('ai_agent')}{path}", json=payload)
+            response.raise_for_status()
+            return response.json() if response.content else None
+    except httpx.HTTPError as e:
+        raise AiJobError(f"AI service error: {str(e)}")
+
+
+def _get(job_id: str) -> AiJob:
+    job = _jobs.get(job_id)
+    if not job:
+        raise AiJobNotFound(f"AI job {job_id} not found")
+    return job
+
+
+def _apply_status(job: AiJob, data: Dict[str, Any]) -> None:
+    job.status = data.get("status") or job.status
+    job.progress = float(data.get("progress") or (1.0 if job.status == "completed" else job.progress))
+    job.message = data.get("message")
+    job.error = data.get("error")
+    event_bus.publish("ai:job_progress", job.describe().model_dump())
+
+
+async def _poll(job: AiJob) -> None:
+    deadline = job.submitted_at + MAX_JOB_SECONDS
+    while not job.finished:
+        await asyncio.sleep(POLL_INTERVAL_SECONDS)
+        if time.time() > deadline:
+            _apply_status(job, {"status": "failed", "error": "Timed out waiting for the AI agent"})
+            break
+        try:
+            _apply_status(job, await _agent_request("GET", f"/jobs/{job.job_id}"))
+        except AiJobError as e:
+            # The agent may be restarting; keep polling until the deadline
+            job.message = str(e)
+
+
+async def submit_generation_job(request: Dict[str, Any]) -> AiJobResponse:
+    """Submit a test generation request to the AI agent as a job and start following its progress"""
+    data = await _agent_request("POST", "/jobs/generate-tests", mask_data(request))
+    job_id = (data or {}).get("job_id")
+    if not job_id:
+        raise AiJobError("AI service did not return a job id")
+    job = AiJob(job_id=job_id, command="generate_tests")
+    _jobs[job_id] = job
+    job._task = asyncio.create_task(_poll(job))
+    return job.describe()
+
+
+def get_job(job_id: str) -> AiJobResponse:
+    return _get(job_id).describe()
+
+
+async def get_job_result(job_id: str) -> Any:
+    """
+    Fetch a completed job's result
+
+    The agent's token usage is recorded the first time the result is fetched.
+    """
+    job = _get(job_id)
+    if job.status != "completed":
+        raise AiJobError(f"AI job {job_id} is {job.status}, not completed")
+    data = await _agent_request("GET", f"/jobs/{job_id}/result")
+    if isinstance(data, dict) and not job.usage_recorded:
+        usage = data.get("usage") if isinstance(data.get("usage"), dict) else {}
+        await record_usage(
+            job.command,
+            "ai-agent",
+            data.get("model") or "ai-agent",
+            usage.get("input_tokens", 0),
+            usage.get("output_tokens", 0),
+            int((time.time() - job.submitted_at) * 1000),
+        )
+        job.usage_recorded = True
+    return data
+
+
+async def cancel_job(job_id: str) -> AiJobResponse:
+    """Ask the agent to cancel a job and stop following it"""
+    job = _get(job_id)
+    if not job.finished:
+        await _agent_request("DELETE", f"/jobs/{job_id}")
+        if job._task:
+            job._task.cancel()
+        _apply_status(job, {"status": "cancelled", "progress": job.progress})
+    return job.describe()
+
+
+async def stop_polling() -> None:
+    """Stop following every job, on shutdown"""
+    for job in _jobs.values():
+        if job._task and not job._task.done():
+            job._task.cancel()
@@ -0,0 +1,170 @@
+"""
+AI Usage Service - Records AI requests and estimates their cost
+"""
+from datetime import datetime, timedelta
+from typing import Dict, Optional
+
+from sqlalchemy import func, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import queue_write
+from ..models import AiUsage, AiUsageSummary, ModelUsage
+from .app_settings import get_setting
+from .events import event_bus
+
+# USD per million tokens as (input, output), matched by the longest model name prefix
+MODEL_PRICING: Dict[str, tuple] = {
+    "claude-opus-4": (15.0, 75.0),
+    "claude-sonnet-4": (3.0, 15.0),
+    "claude-3-7-sonnet": (3.0, 15.0),
+    "claude-3-5-sonnet": (3.0, 15.0),
+    "claude-3-5-haiku": (0.8, 4.0),
+    "claude-3-haiku": (0.25, 1.25),
+    "gpt-4o-mini": (0.15, 0.6),
+    "gpt-4o": (2.5, 10.0),
+    "gpt-4.1-mini": (0.4, 1.6),
+    "gpt-4.1": (2.0, 8.0),
+}
+
+PERIODS: Dict[str, Optional[timedelta]] = {
+    "day": timedelta(days=1),
+    "week": timedelta(days=7),
+    "month": timedelta(days=30),
+    "all": None,
+}
+
+# Month ("YYYY-MM") for which the soft cap warning was already sent
+_cap_warned_month: Optional[str] = None
+
+
+def estimate_cost(provider: str, model: str, input_tokens: int, output_tokens: int) -> Optional[float]:
+    """Estimate the cost of a request in USD, or None if the model's pricing is unknown"""
+    if provider == "local":
+        return 0.0
+    for prefix in sorted(MODEL_PRICING, key=len, reverse=True):
+        if model.startswith(prefix):
+            input_price, output_price = MODEL_PRICING[prefix]
+            return (input_tokens * input_price + output_tokens * output_price) / 1_000_000
+    return None
+
+
+async def record_usage(
+    command: str,
+    provider: str,
+    model: str,
+    input_tokens: int,
+    output_tokens: int,
+    latency_ms: int,
+    success: bool = True,
+) -> None:
+    """Store a usage record and warn if the monthly soft cap is exceeded"""
+    usage = AiUsage(
+        command=command,
+        provider=provider,
+        model=model,
+        input_tokens=input_tokens,
+        output_tokens=output_tokens,
+        latency_ms=latency_ms,
+        success=success,
+    )
+
+    async def write(db: AsyncSession) -> None:
+        db.add(usage)
+        await db.flush()
+        await _check_monthly_cap(db)
+
+    # Usage is queued rather than lost if the database is briefly unavailable
+    await queue_write(write)
+
+
+async def _month_to_date_cost(db: AsyncSession) -> float:
+    month_start = datetime.utcnow().replace(day=1, hour=0, minute=0, second=0, microsecond=0)
+    result = await db.execute(
+        select(
+            AiUsage.provider,
+            AiUsage.model,
+            func.sum(AiUsage.input_tokens),
+            func.sum(AiUsage.output_tokens),
+        )
+        .where(AiUsage.created_at >= month_start)
+        .group_by(AiUsage.provider, AiUsage.model)
+    )
+    return sum(
+        estimate_cost(provider, model, input_tokens or 0, output_tokens or 0) or 0.0
+        for provider, model, input_tokens, output_tokens in result.all()
+    )
+
+
+async def _check_monthly_cap(db: AsyncSession) -> None:
+    global _cap_warned_month
+
+    cap = await get_setting(db, "ai.monthly_cost_cap_usd")
+    month = datetime.utcnow().strftime("%Y-%m")
+    if not cap or _cap_warned_month == month:
+        return
+
+    cost = await _month_to_date_cost(db)
+    if cost > cap:
+        _cap_warned_month = month
+        event_bus.publish(
+            "ai:usage_cap_exceeded",
+            {"month": month, "cost_usd": round(cost, 4), "cap_usd": cap},
+        )
+
+
+async def get_usage_summary(db: AsyncSession, period: str) -> AiUsageSummary:
+    """Summarize usage per model over a period ('day', 'week', 'month', or 'all')"""
+    since = datetime.utcnow() - PERIODS[period] if PERIODS[period] else None
+
+    query = select(
+        AiUsage.provider,
+        AiUsage.model,
+        func.count(),
+        func.sum(AiUsage.input_tokens),
+        func.sum(AiUsage.output_tokens),
+    ).group_by(AiUsage.provider, AiUsage.model)
+    if since:
+        query = query.where(AiUsage.created_at >= since)
+    result = await db.execute(query)
+
+    by_model: Dict[str, ModelUsage] = {}
+    for provider, model, requests, input_tokens, output_tokens in result.all():
+        input_tokens = input_tokens or 0
+        output_tokens = output_tokens or 0
+        cost = estimate_cost(provider, model, input_tokens, output_tokens)
+        usage = by_model.get(model)
+        if usage:
+            usage.requests += requests
+            usage.input_tokens += input_tokens
+            usage.output_tokens += output_tokens
+            if cost is not None:
+                usage.estimated_cost_usd = (usage.estimated_cost_usd or 0.0) + cost
+        else:
+            by_model[model] = ModelUsage(
+                model=model,
+                requests=requests,
+                input_tokens=input_tokens,
+                output_tokens=output_tokens,
+                estimated_cost_usd=cost,
+            )
+
+    failed_query = select(func.count()).where(AiUsage.success.is_(False))
+    if since:
+        failed_query = failed_query.where(AiUsage.created_at >= since)
+    failed = (await db.execute(failed_query)).scalar() or 0
+
+    cap = await get_setting(db, "ai.monthly_cost_cap_usd")
+    models = sorted(by_model.values(), key=lambda m: m.requests, reverse=True)
+
+    return AiUsageSummary(
+        period=period,
+        since=since,
+        total_requests=sum(m.requests for m in models),
+        failed_requests=failed,
+        input_tokens=sum(m.input_tokens for m in models),
+        output_tokens=sum(m.output_tokens for m in models),
+        estimated_cost_usd=sum(m.estimated_cost_usd or 0.0 for m in models),
+        by_model=models,
+        monthly_cap_usd=cap or None,
+        month_to_date_cost_usd=await _month_to_date_cost(db),
+    )
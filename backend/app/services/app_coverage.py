@@ -0,0 +1,164 @@
+"""
+App Coverage - Which crawled screens and elements a project's scenarios exercise
+
+Steps don't record which screen they ran on, so a step counts on every crawled
+screen where its locator matches an element. Matches are made against each
+screen's stored UI dump the same way the executor finds elements. Screen maps
+come from Android crawls, so named elements use their Android locators.
+"""
+import json
+from typing import Any, Dict, List, Optional, Set, Tuple
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import (
+    AppCoverage,
+    AppScreen,
+    Project,
+    Scenario,
+    ScreenCoverage,
+    ScreenElement,
+    ScreenTransition,
+    Step,
+    TestCase,
+)
+from .elements import apply_element, load_elements
+from .synonyms import load_synonyms
+from .ui_dump import (
+    UiNode,
+    element_descriptor,
+    find_element_from_ui_dump,
+    find_node_by_descriptor,
+    find_node_by_selector,
+    parse_ui_dump,
+)
+
+
+class CoverageError(Exception):
+    """Raised when a project's coverage can't be computed"""
+
+
+def _located_node(nodes: List[UiNode], config: Dict[str, Any], synonyms: List[Set[str]]) -> Optional[UiNode]:
+    """The node a step acts on, found like the executor finds it"""
+    descriptor = element_descriptor(config)
+    if descriptor:
+        return find_node_by_descriptor(nodes, descriptor, int(config.get("index") or 0))
+    if config.get("selector"):
+        return find_node_by_selector(nodes, config["selector"])
+    if config.get("element_description"):
+        found = find_element_from_ui_dump(nodes, config["element_description"], synonyms=synonyms)
+        return found[0] if found else None
+    return None
+
+
+def _element_for(node: UiNode, elements: List[ScreenElement]) -> Optional[int]:
+    """The smallest clickable element containing a node, as an index into elements"""
+    x, y = node.center
+    best: Optional[Tuple[int, int]] = None
+    for position, element in enumerate(elements):
+        left, top, right, bottom = element.bounds
+        if left <= x < right and top <= y < bottom:
+            area = (right - left) * (bottom - top)
+            if best is None or area < best[1]:
+                best = (position, area)
+    return best[0] if best else None
+
+
+def has_locator(config: Dict[str, Any]) -> bool:
+    return bool(element_descriptor(config) or config.get("selector") or config.get("element_description"))
+
+
+def screen_coverage(
+    screen: AppScreen,
+    steps: List[Tuple[Step, Dict[str, Any]]],
+    synonyms: List[Set[str]],
+) -> Tuple[Set[int], Set[str]]:
+    """The positions of a screen's tested elements and the scenarios that touch it"""
+    nodes = parse_ui_dump(screen.ui_dump or "")
+    elements = [ScreenElement(**element) for element in json.loads(screen.elements)]
+    tested: Set[int] = set()
+    scenario_ids: Set[str] = set()
+    for step, config in steps:
+        node = _located_node(nodes, config, synonyms)
+        if not node:
+            continue
+        scenario_ids.add(step.scenario_id)
+        position = _element_for(node, elements)
+        if position is not None:
+            tested.add(position)
+    return tested, scenario_ids
+
+
+def _element_key(element: Dict[str, Any]) -> tuple:
+    return tuple(element.get(key) for key in ("resource_id", "text", "content_desc", "class_name", "index"))
+
+
+async def get_app_coverage(db: AsyncSession, project_id: str) -> AppCoverage:
+    """Per-screen coverage of the project's screen-flow map, with the elements no step exercises"""
+    project = await db.get(Project, project_id)
+    if not project:
+        raise CoverageError("Project not found")
+
+    named_elements = await load_elements(db, project_id)
+    synonyms = await load_synonyms(db, project_id)
+    result = await db.execute(
+        select(Step)
+        .join(Scenario, Step.scenario_id == Scenario.id)
+        .join(TestCase, Scenario.test_case_id == TestCase.id)
+        .where(TestCase.project_id == project_id)
+    )
+    steps: List[Tuple[Step, Dict[str, Any]]] = []
+    unlocated = 0
+    for step in result.scalars().all():
+        try:
+            config = apply_element(json.loads(step.config or "{}"), named_elements, "android")
+        except ValueError:
+            continue
+        if has_locator(config):
+            steps.append((step, config))
+        elif step.step_type in ("tap", "swipe") and config.get("x") is not None:
+            unlocated += 1
+
+    screens = (await db.execute(
+        select(AppScreen).where(AppScreen.project_id == project_id).order_by(AppScreen.depth, AppScreen.created_at)
+    )).scalars().all()
+    transitions = (await db.execute(
+        select(ScreenTransition).where(ScreenTransition.project_id == project_id)
+    )).scalars().all()
+
+    coverages: Dict[str, ScreenCoverage] = {}
+    tested_keys: Dict[str, Set[tuple]] = {}
+    for screen in screens:
+        elements = [ScreenElement(**element) for element in json.loads(screen.elements)]
+        tested, scenario_ids = screen_coverage(screen, steps, synonyms)
+        tested_keys[screen.id] = {_element_key(elements[position].model_dump()) for position in tested}
+        coverages[screen.id] = ScreenCoverage(
+            screen_id=screen.id,
+            name=screen.name,
+            covered=bool(scenario_ids),
+            elements_total=len(elements),
+            elements_tested=len(tested),
+            coverage_pct=round(100 * len(tested) / len(elements), 1) if elements else 0.0,
+            untested_elements=[element for position, element in enumerate(elements) if position not in tested],
+            scenario_ids=sorted(scenario_ids),
+        )
+
+    # A screen is reached by tapping a tested element that leads to it
+    for transition in transitions:
+        source = tested_keys.get(transition.from_screen_id, set())
+        if transition.to_screen_id in coverages and _element_key(json.loads(transition.element)) in source:
+            coverages[transition.to_screen_id].covered = True
+
+    elements_total = sum(coverage.elements_total for coverage in coverages.values())
+    elements_tested = sum(coverage.elements_tested for coverage in coverages.values())
+    return AppCoverage(
+        project_id=project_id,
+        screens_total=len(coverages),
+        screens_covered=sum(1 for coverage in coverages.values() if coverage.covered),
+        elements_total=elements_total,
+        elements_tested=elements_tested,
+        coverage_pct=round(100 * elements_tested / elements_total, 1) if elements_total else 0.0,
+        unlocated_steps=unlocated,
+        screens=list(coverages.values()),
+    )
@@ -0,0 +1,398 @@
+"""
+App Crawler - Explores a mobile app by tapping its clickable elements to build a screen-flow map
+
+Starting from the device's current screen, each screen's clickable elements are
+tapped breadth first, within depth, step and screen limits. Every screen is
+captured once with a screenshot and UI dump, and every tap that changes the
+screen is stored as a transition, giving a per-project graph of the app.
+Crawling needs a UI dump, so it runs on Android only.
+"""
+import asyncio
+import base64
+import json
+import shutil
+from collections import deque
+from dataclasses import dataclass, field
+from datetime import datetime
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from sqlalchemy import delete, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from ..models import (
+    AppScreen,
+    AppScreenResponse,
+    CrawlRequest,
+    CrawlStatus,
+    Project,
+    ScreenElement,
+    ScreenMap,
+    ScreenTransition,
+    ScreenTransitionResponse,
+)
+from .events import event_bus
+from .executor import ScenarioExecutor, StepExecutionError
+from .ui_dump import UiNode, find_node_by_descriptor, layout_fingerprint, parse_ui_dump
+from .waits import resumed_activity
+
+# How long the app gets to settle after a tap before the screen is captured
+SETTLE_SECONDS = 1.0
+
+# How long a replayed tap waits for its element while getting back to a screen
+REPLAY_WAIT_MS = 2000
+
+MAX_DEPTH = 10
+MAX_STEPS = 500
+MAX_SCREENS = 200
+
+
+class CrawlError(Exception):
+    """Raised when a crawl can't be started or a map can't be read"""
+
+
+@dataclass
+class CapturedScreen:
+    fingerprint: str
+    screenshot: str
+    ui_dump: str
+    nodes: List[UiNode]
+    activity: Optional[str]
+
+    @property
+    def package(self) -> Optional[str]:
+        return self.nodes[0].attributes.get("package") if self.nodes else None
+
+
+@dataclass
+class CrawlTarget:
+    """A screen waiting to be explored, with the taps that lead to it from the first screen"""
+
+    screen_id: str
+    fingerprint: str
+    depth: int
+    path: List[Dict[str, Any]] = field(default_factory=list)
+
+
+def screenshots_dir(project_id: str) -> Path:
+    return settings.get_data_dir() / "screen_maps" / project_id
+
+
+def clickable_elements(nodes: List[UiNode]) -> List[ScreenElement]:
+    """A screen's tappable elements, each described so tap_element can find it again"""
+    elements = []
+    for node in nodes:
+        if not node.clickable or node.attributes.get("enabled") == "false":
+            continue
+        if node.right <= node.left or node.bottom <= node.top:
+            continue
+        descriptor = {
+            "resource_id": node.resource_id,
+            "text": node.text,
+            "content_desc": node.content_desc,
+            "class_name": node.class_name,
+        }
+        descriptor = {key: value for key, value in descriptor.items() if value}
+        index = 0
+        while find_node_by_descriptor(nodes, descriptor, index) is not node:
+            index += 1
+        elements.append(ScreenElement(
+            **descriptor, index=index, bounds=[node.left, node.top, node.right, node.bottom]
+        ))
+    return elements
+
+
+def element_key(element: Dict[str, Any]) -> str:
+    return json.dumps({key: element.get(key) for key in (
+        "resource_id", "text", "content_desc", "class_name", "index"
+    )}, sort_keys=True)
+
+
+def screen_name(activity: Optional[str], nodes: List[UiNode]) -> str:
+    """A readable name from the screen's activity and its first text"""
+    short = activity.rsplit(".", 1)[-1].rsplit("/", 1)[-1] if activity else ""
+    title = next((node.text.strip() for node in nodes if node.text.strip()), "")[:40]
+    return " - ".join(part for part in (short, title) if part) or "Screen"
+
+
+# ============================================
+# Crawler
+# ============================================
+
+
+class AppCrawler:
+    """Crawls one project's app on one Android device"""
+
+    def __init__(self, project_id: str, request: CrawlRequest):
+        self.project_id = project_id
+        self.request = request
+        self.package = request.package_name
+        self.status = CrawlStatus(
+            project_id=project_id,
+            device_id=request.device_id,
+            state="running",
+            steps=0,
+            screens=0,
+            new_screens=0,
+            transitions=0,
+            started_at=datetime.utcnow(),
+        )
+        self._executor = ScenarioExecutor(request.device_id, "android", self_heal=False, ai_free=True)
+        self._executor.implicit_wait_ms = REPLAY_WAIT_MS
+        self._seen: Dict[str, str] = {}  # Fingerprint to screen ID, for screens seen in this crawl
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task and not self._task.done():
+            self._task.cancel()
+            try:
+                await self._task
+            except asyncio.CancelledError:
+                pass
+
+    async def _run(self) -> None:
+        try:
+            async with AsyncSessionLocal() as db:
+                await self._crawl(db)
+            self.status.state = "completed"
+        except asyncio.CancelledError:
+            self.status.state = "stopped"
+            raise
+        except Exception as e:
+            self.status.state = "failed"
+            self.status.error = str(e)
+        finally:
+            self.status.finished_at = datetime.utcnow()
+            event_bus.publish("crawl:finished", self.status.model_dump(mode="json"))
+
+    async def _crawl(self, db: AsyncSession) -> None:
+        if self.request.launch:
+            await self._launch()
+        first = await self._capture()
+        self.package = self.package or first.package
+        root = CrawlTarget(await self._record_screen(db, first, 0), first.fingerprint, 0)
+        frontier = deque([root])
+
+        while frontier:
+            target = frontier.popleft()
+            if target.depth >= self.request.max_depth:
+                continue
+            screen = await db.get(AppScreen, target.screen_id)
+            for element in json.loads(screen.elements):
+                if self._budget_spent():
+                    return
+                if not await self._go_to(target):
+                    break  # The screen can't be reached again, so its other elements are skipped
+                try:
+                    await self._tap(element)
+                except StepExecutionError:
+                    continue
+                captured = await self._capture()
+                if captured.package and self.package and captured.package != self.package:
+                    continue  # Left the app; _go_to brings it back for the next element
+
+                is_new = captured.fingerprint not in self._seen
+                screen_id = await self._record_screen(db, captured, target.depth + 1)
+                if screen_id == target.screen_id:
+                    continue
+                await self._record_transition(db, target.screen_id, screen_id, element)
+                if is_new:
+                    frontier.append(CrawlTarget(
+                        screen_id, captured.fingerprint, target.depth + 1, target.path + [element]
+                    ))
+
+    def _budget_spent(self) -> bool:
+        return self.status.steps >= self.request.max_steps or self.status.screens >= self.request.max_screens
+
+    # ============================================
+    # Device
+    # ============================================
+
+    async def _capture(self) -> CapturedScreen:
+        screenshot = await self._executor._screenshot()
+        ui_dump = await self._executor._dump_ui()
+        nodes = parse_ui_dump(ui_dump)
+        dumpsys = await self._executor._device_command(["shell", "dumpsys", "activity", "activities"])
+        return CapturedScreen(layout_fingerprint(nodes), screenshot, ui_dump, nodes, resumed_activity(dumpsys))
+
+    async def _tap(self, element: Dict[str, Any]) -> None:
+        config = {key: element.get(key) for key in ("resource_id", "text", "content_desc", "class_name", "index")}
+        await self._executor._perform("tap_element", config)
+        self.status.steps += 1
+        await asyncio.sleep(SETTLE_SECONDS)
+
+    async def _launch(self) -> None:
+        if not self.package:
+            raise CrawlError("Set package_name to launch the app")
+        await self._executor._device_command(["shell", "am", "force-stop", self.package])
+        await self._executor._perform("launch", {"packageName": self.package})
+        await asyncio.sleep(SETTLE_SECONDS * 2)
+
+    async def _current_fingerprint(self) -> str:
+        return layout_fingerprint(parse_ui_dump(await self._executor._dump_ui()))
+
+    async def _go_to(self, target: CrawlTarget) -> bool:
+        """Get back to a screen: it may already be showing, one back press away, or reached again from launch"""
+        if await self._current_fingerprint() == target.fingerprint:
+            return True
+        await self._executor._perform("back", {})
+        await asyncio.sleep(SETTLE_SECONDS)
+        if await self._current_fingerprint() == target.fingerprint:
+            return True
+        if not self.package:
+            return False
+        await self._launch()
+        for element in target.path:
+            try:
+                await self._tap(element)
+            except StepExecutionError:
+                return False
+        return await self._current_fingerprint() == target.fingerprint
+
+    # ============================================
+    # Map storage
+    # ============================================
+
+    async def _record_screen(self, db: AsyncSession, captured: CapturedScreen, depth: int) -> str:
+        """Store a screen the first time it's seen, returning its ID"""
+        if captured.fingerprint in self._seen:
+            return self._seen[captured.fingerprint]
+
+        result = await db.execute(select(AppScreen).where(
+            AppScreen.project_id == self.project_id, AppScreen.fingerprint == captured.fingerprint
+        ))
+        screen = result.scalar_one_or_none()
+        if not screen:
+            screen = AppScreen(project_id=self.project_id, fingerprint=captured.fingerprint, depth=depth)
+            db.add(screen)
+            self.status.new_screens += 1
+        screen.name = screen_name(captured.activity, captured.nodes)
+        screen.activity = captured.activity
+        screen.depth = min(screen.depth, depth)
+        screen.ui_dump = captured.ui_dump
+        screen.elements = json.dumps([e.model_dump() for e in clickable_elements(captured.nodes)])
+        await db.flush()
+
+        directory = screenshots_dir(self.project_id)
+        directory.mkdir(parents=True, exist_ok=True)
+        path = directory / f"{screen.id}.png"
+        path.write_bytes(base64.b64decode(captured.screenshot))
+        screen.screenshot_path = str(path)
+        await db.commit()
+
+        self._seen[captured.fingerprint] = screen.id
+        self.status.screens += 1
+        event_bus.publish("crawl:screen", {
+            "project_id": self.project_id, "screen_id": screen.id, "name": screen.name, "depth": depth,
+            "steps": self.status.steps, "screens": self.status.screens,
+        })
+        return screen.id
+
+    async def _record_transition(self, db: AsyncSession, from_id: str, to_id: str, element: Dict[str, Any]) -> None:
+        result = await db.execute(select(ScreenTransition).where(
+            ScreenTransition.from_screen_id == from_id, ScreenTransition.to_screen_id == to_id
+        ))
+        if any(element_key(json.loads(t.element)) == element_key(element) for t in result.scalars().all()):
+            return
+        db.add(ScreenTransition(
+            project_id=self.project_id, from_screen_id=from_id, to_screen_id=to_id, element=json.dumps(element)
+        ))
+        await db.commit()
+        self.status.transitions += 1
+
+
+# One crawl per project
+_crawls: Dict[str, AppCrawler] = {}
+
+
+async def start_crawl(db: AsyncSession, project_id: str, request: CrawlRequest) -> CrawlStatus:
+    project = await db.get(Project, project_id)
+    if not project:
+        raise CrawlError("Project not found")
+    if project.project_type in ("web", "ios"):
+        raise CrawlError("Crawling needs an Android UI dump, so it only runs on Android projects")
+    if not 1 <= request.max_depth <= MAX_DEPTH:
+        raise CrawlError(f"max_depth must be between 1 and {MAX_DEPTH}")
+    if not 1 <= request.max_steps <= MAX_STEPS:
+        raise CrawlError(f"max_steps must be between 1 and {MAX_STEPS}")
+    if not 1 <= request.max_screens <= MAX_SCREENS:
+        raise CrawlError(f"max_screens must be between 1 and {MAX_SCREENS}")
+    running = _crawls.get(project_id)
+    if running and running.status.state == "running":
+        raise CrawlError("A crawl is already running for this project")
+
+    crawler = AppCrawler(project_id, request)
+    _crawls[project_id] = crawler
+    crawler.start()
+    return crawler.status
+
+
+async def stop_crawl(project_id: str) -> Optional[CrawlStatus]:
+    crawler = _crawls.get(project_id)
+    if not crawler:
+        return None
+    await crawler.stop()
+    return crawler.status
+
+
+def get_crawl_status(project_id: str) -> Optional[CrawlStatus]:
+    crawler = _crawls.get(project_id)
+    return crawler.status if crawler else None
+
+
+# ============================================
+# Screen maps
+# ============================================
+
+
+def screen_response(screen: AppScreen) -> AppScreenResponse:
+    return AppScreenResponse(
+        id=screen.id,
+        project_id=screen.project_id,
+        fingerprint=screen.fingerprint,
+        name=screen.name,
+        activity=screen.activity,
+        depth=screen.depth,
+        has_screenshot=bool(screen.screenshot_path and Path(screen.screenshot_path).exists()),
+        elements=[ScreenElement(**element) for element in json.loads(screen.elements)],
+        created_at=screen.created_at,
+        updated_at=screen.updated_at,
+    )
+
+
+async def get_screen_map(db: AsyncSession, project_id: str) -> ScreenMap:
+    screens = await db.execute(
+        select(AppScreen).where(AppScreen.project_id == project_id).order_by(AppScreen.depth, AppScreen.created_at)
+    )
+    transitions = await db.execute(
+        select(ScreenTransition).where(ScreenTransition.project_id == project_id).order_by(ScreenTransition.created_at)
+    )
+    return ScreenMap(
+        project_id=project_id,
+        screens=[screen_response(screen) for screen in screens.scalars().all()],
+        transitions=[
+            ScreenTransitionResponse(
+                id=t.id,
+                from_screen_id=t.from_screen_id,
+                to_screen_id=t.to_screen_id,
+                element=json.loads(t.element),
+                created_at=t.created_at,
+            )
+            for t in transitions.scalars().all()
+        ],
+    )
+
+
+async def delete_screen_map(db: AsyncSession, project_id: str) -> None:
+    running = _crawls.get(project_id)
+    if running and running.status.state == "running":
+        raise CrawlError("Stop the project's crawl before deleting its map")
+    await db.execute(delete(ScreenTransition).where(ScreenTransition.project_id == project_id))
+    await db.execute(delete(AppScreen).where(AppScreen.project_id == project_id))
+    await db.commit()
+    shutil.rmtree(screenshots_dir(project_id), ignore_errors=True)
@@ -0,0 +1,157 @@
+"""
+App Settings Service - Persistent user-configurable settings with defaults
+"""
+import json
+from typing import Any, Dict
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..models import AppSetting
+
+# Every known setting with its default; the default's type is the setting's type
+SETTING_DEFAULTS: Dict[str, Any] = {
+    "ai.provider": settings.ai_provider,
+    "ai.model": settings.ai_model,
+    "ai.element_model": "",  # Cheaper model for element finding, empty uses ai.model
+    "ai.max_tokens": 4096,
+    "ai.temperature": 0.0,
+    "ai.base_url": "",
+    "ai.monthly_cost_cap_usd": 0.0,  # Soft cap that triggers a warning event, 0 disables it
+    "ai.cache_ttl_hours": 24,  # Lifetime of cached element lookups, 0 disables the cache
+    "services.ai_agent_url": "",  # Empty uses AI_AGENT_URL or the local default
+    "services.test_runner_url": "",  # Empty uses TEST_RUNNER_URL or the local default
+    "services.health_check_interval_seconds": 15,
+    "services.health_failure_threshold": 3,  # Consecutive failed checks before a service is reported down
+    "backup.daily_snapshots": False,
+    "backup.snapshot_retention": 7,  # Newest snapshots to keep, 0 keeps all
+    "retention.keep_runs": 0,  # Newest test runs kept per project with their artifacts, 0 keeps all
+    "retention.keep_failed_days": 30,  # Failed runs past keep_runs are still kept until they're this old
+    "retention.auto_cleanup": False,  # Deletes runs outside the retention rules once a day
+    "control_api.enabled": False,  # Lets external tools drive the app with the control token
+    "shell.enabled": False,  # Allows scenarios to run shell steps on this machine
+    "shell.allowed_commands": "",  # Comma-separated names run from PATH or exact paths, "*" allows any
+    "shell.confirm_unlisted": True,  # Ask before running commands not in shell.allowed_commands
+    "queue.max_runs_per_device": 1,  # Runs at once on one device or simulator, 0 is unlimited
+    "queue.max_mobile_runs": 0,  # Device runs at once across all devices, 0 is unlimited
+    "queue.max_web_runs": 3,  # Browser runs at once, 0 is unlimited
+    "agents.enabled": False,  # Lets agent machines register and take runs using the agent token
+    "agent.orchestrator_url": "",  # Instance this machine takes runs from as an agent, empty disables agent mode
+    "agent.token": "",  # The orchestrator's agent token
+    "agent.name": "",  # Empty uses the hostname
+    "agent.browsers": "chromium",  # Comma-separated browsers this agent offers for web runs
+    "attachments.storage": "local",  # 'local' keeps files in the data directory, 's3' uses object storage
+    "attachments.s3_endpoint_url": "",  # S3-compatible endpoint, e.g. https://s3.eu-west-1.amazonaws.com
+    "attachments.s3_bucket": "",
+    "attachments.s3_region": "us-east-1",
+    "attachments.max_upload_mb": 200,
+    "attachments.link_ttl_seconds": 3600,  # How long signed download links stay valid
+    "tools.adb_path": "",  # Empty finds each tool on PATH, then in the Android SDK
+    "tools.emulator_path": "",
+    "tools.avdmanager_path": "",
+    "tools.xcrun_path": "",
+    "tools.idb_path": "",
+    "tools.node_path": "",
+    "tools.npx_path": "",
+    "tools.git_path": "",
+    "executor.implicit_wait_ms": 5000,  # How long mobile steps wait for their element to appear, 0 looks once
+    "executor.collect_crash_logs": True,  # Attach crash reports the device wrote during each mobile run
+    "executor.budget_action": "warn",  # 'warn' or 'fail' steps and scenarios that go over their time budget
+    "executor.result_batch_size": 20,  # Step results buffered before they're written in one insert
+    "executor.result_flush_ms": 1000,  # Oldest a buffered step result gets before a flush, 0 writes each one
+    "executor.screenshot_policy": "on_failure",  # 'always', 'on_failure' or 'never' capture a screenshot per step
+    "screenshots.format": "webp",  # 'webp', 'jpeg' or 'png' for stored step screenshots
+    "screenshots.quality": 80,  # 1-100, for WebP and JPEG
+    "screenshots.max_width": 1080,  # Wider screenshots are downscaled to this, 0 keeps their size
+    "webhooks.target": "browser",  # Where runs started by GitHub webhooks execute: 'browser' or 'device'
+    "webhooks.platform": "android",
+    "webhooks.device_id": "",
+    "webhooks.browser": "chromium",
+    "webhooks.framework": "playwright",
+    "webhooks.report_checks": True,  # Reports webhook runs on the commit with a GitHub check run
+    "shortcuts.global": False,  # Registers quick action shortcuts system-wide when the desktop app starts
+    "tracing.enabled": False,  # Records timings of API requests, device commands and AI calls
+    "tracing.otlp_url": "",  # OTLP/HTTP collector to export traces to, e.g. http://localhost:4318
+    "tracing.max_file_mb": 10,  # Size of the local trace file before it rotates
+    "email.smtp_host": "",  # SMTP server for emailed run reports; the password is SMTP_PASSWORD
+    "email.smtp_port": 587,
+    "email.smtp_security": "starttls",  # 'starttls' | 'ssl' | 'none'
+    "email.smtp_username": "",  # Empty sends without logging in
+    "email.from_address": "",
+    "email.schedule_failure_recipients": "",  # Comma-separated addresses sent failed scheduled runs, empty disables
+}
+
+
+class SettingError(Exception):
+    """Raised for unknown setting keys or values of the wrong type"""
+
+
+def _coerce(key: str, value: Any) -> Any:
+    default = SETTING_DEFAULTS[key]
+    if isinstance(default, bool):
+        if not isinstance(value, bool):
+            raise SettingError(f"Setting {key} must be a boolean")
+        return value
+    if isinstance(default, int):
+        if isinstance(value, bool) or not isinstance(value, int):
+            raise SettingError(f"Setting {key} must be an integer")
+        return value
+    if isinstance(default, float):
+        if isinstance(value, bool) or not isinstance(value, (int, float)):
+            raise SettingError(f"Setting {key} must be a number")
+        return float(value)
+    if isinstance(default, str):
+        if not isinstance(value, str):
+            raise SettingError(f"Setting {key} must be a string")
+        if key.endswith("_url") and value and not value.startswith(("http://", "https://")):
+            raise SettingError(f"Setting {key} must be an http:// or https:// URL")
+        return value
+    return value
+
+
+async def get_all_settings(db: AsyncSession) -> Dict[str, Any]:
+    """Get all settings, falling back to defaults for values that were never set"""
+    values = dict(SETTING_DEFAULTS)
+    result = await db.execute(select(AppSetting))
+    for row in result.scalars().all():
+        if row.key in SETTING_DEFAULTS:
+            values[row.key] = json.loads(row.value)
+    return values
+
+
+async def get_setting(db: AsyncSession, key: str) -> Any:
+    """Get a single setting value"""
+    if key not in SETTING_DEFAULTS:
+        raise SettingError(f"Unknown setting: {key}")
+    row = await db.get(AppSetting, key)
+    return json.loads(row.value) if row else SETTING_DEFAULTS[key]
+
+
+async def update_settings(db: AsyncSession, values: Dict[str, Any]) -> Dict[str, Any]:
+    """Validate and persist setting values, returning all settings"""
+    for key in values:
+        if key not in SETTING_DEFAULTS:
+            raise SettingError(f"Unknown setting: {key}")
+    coerced = {key: _coerce(key, value) for key, value in values.items()}
+
+    for key, value in coerced.items():
+        row = await db.get(AppSetting, key)
+        if row:
+            row.value = json.dumps(value)
+        else:
+            db.add(AppSetting(key=key, value=json.dumps(value)))
+    await db.commit()
+
+    return await get_all_settings(db)
+
+
+async def reset_setting(db: AsyncSession, key: str) -> Any:
+    """Remove a stored value so the setting reverts to its default"""
+    if key not in SETTING_DEFAULTS:
+        raise SettingError(f"Unknown setting: {key}")
+    row = await db.get(AppSetting, key)
+    if row:
+        await db.delete(row)
+        await db.commit()
+    return SETTING_DEFAULTS[key]
@@ -0,0 +1,402 @@
+"""
+Assertions - Evaluates step assertions against devices, variables and APIs
+"""
+import json
+import re
+from dataclasses import asdict, dataclass
+from typing import Any, Awaitable, Callable, Dict, List, Optional, Set, Tuple
+
+import httpx
+
+from .ai_client import AiClientError, ask_ai_json
+from .ui_dump import UiNode, find_element_from_ui_dump, find_node_by_selector, parse_ui_dump
+
+VERIFY_STEP_TYPE = "verify"
+
+ASSERTION_TARGETS = ("element_text", "element_attribute", "screen_text", "variable", "api_response")
+ASSERTION_OPERATORS = (
+    "equals", "not_equals", "contains", "regex", "greater_than", "less_than", "exists", "not_exists"
+)
+
+API_TIMEOUT = 30.0
+
+
+class AssertionTargetError(Exception):
+    """Raised when an assertion's actual value can't be read"""
+
+
+@dataclass
+class AssertionResult:
+    target: str
+    operator: str
+    expected: Optional[str]
+    actual: Optional[str]
+    passed: bool
+    soft: bool
+    message: str
+
+
+def step_assertions(config: Dict[str, Any]) -> List[Dict[str, Any]]:
+    """A step's assertions, including the single expected/operator pair older steps use"""
+    if config.get("assertions"):
+        return [a for a in config["assertions"] if isinstance(a, dict)]
+    if config.get("expected") is None and config.get("operator") not in ("exists", "not_exists"):
+        return []
+    has_element = config.get("selector") or config.get("element_description")
+    return [{
+        "target": "element_text" if has_element else "screen_text",
+        "operator": config.get("operator") or ("contains" if not has_element else "equals"),
+        "expected": config.get("expected"),
+        "selector": config.get("selector"),
+        "element_description": config.get("element_description"),
+    }]
+
+
+def compare(operator: str, actual: Optional[str], expected: Optional[str]) -> Tuple[bool, str]:
+    """Apply an operator, returning (passed, explanation)"""
+    if operator == "exists":
+        return actual is not None, "found" if actual is not None else "not found"
+    if operator == "not_exists":
+        return actual is None, "not found" if actual is None else "found"
+    if actual is None:
+        return False, "not found"
+    if expected is None:
+        return False, f"'{operator}' needs an expected value"
+
+    if operator == "equals":
+        return actual.strip() == expected.strip(), f"got {actual!r}"
+    if operator == "not_equals":
+        return actual.strip() != expected.strip(), f"got {actual!r}"
+    if operator == "contains":
+        return expected in actual, f"got {actual[:200]!r}"
+    if operator == "regex":
+        try:
+            return re.search(expected, actual) is not None, f"got {actual[:200]!r}"
+        except re.error as e:
+            return False, f"invalid regex: {e}"
+    if operator in ("greater_than", "less_than"):
+        try:
+            number, limit = float(actual), float(expected)
+        except ValueError:
+            return False, f"{actual!r} or {expected!r} is not a number"
+        passed = number > limit if operator == "greater_than" else number < limit
+        return passed, f"got {number:g}"
+    return False, f"unknown operator '{operator}'"
+
+
+def _json_path(value: Any, path: str) -> Any:
+    for key in path.split("."):
+        if isinstance(value, list) and key.isdigit() and int(key) < len(value):
+            value = value[int(key)]
+        elif isinstance(value, dict) and key in value:
+            value = value[key]
+        else:
+            return None
+    return value
+
+
+def _as_text(value: Any) -> Optional[str]:
+    if value is None:
+        return None
+    if isinstance(value, (dict, list, bool)):
+        return json.dumps(value)
+    return str(value)
+
+
+async def api_value(assertion: Dict[str, Any]) -> Optional[str]:
+    """Call an API and read its status, or a dotted path from its JSON body"""
+    if not assertion.get("url"):
+        raise AssertionTargetError("api_response assertions need a url")
+    try:
+        async with httpx.AsyncClient(timeout=API_TIMEOUT) as client:
+            response = await client.request(assertion.get("method") or "GET", assertion["url"])
+    except httpx.HTTPError as e:
+        raise AssertionTargetError(f"Request to {assertion['url']} failed: {e}")
+    path = assertion.get("path") or "status"
+    if path == "status":
+        return str(response.status_code)
+    if path == "body":
+        return response.text
+    try:
+        body = response.json()
+    except ValueError:
+        raise AssertionTargetError(f"Response from {assertion['url']} is not JSON")
+    return _as_text(_json_path(body, path))
+
+
+class DeviceValueReader:
+    """Reads assertion values from a mobile device, caching the UI dump and screen text per step"""
+
+    def __init__(
+        self,
+        platform: str,
+        dump_ui: Callable[[], Awaitable[str]],
+        screenshot: Callable[[], Awaitable[str]],
+        variables: Dict[str, Any],
+        ai_free: bool = False,
+        synonyms: Optional[List[Set[str]]] = None,
+    ):
+        self.platform = platform
+        self._dump_ui = dump_ui
+        self._screenshot = screenshot
+        self.variables = variables
+        self.ai_free = ai_free
+        self.synonyms = synonyms
+        self._nodes: Optional[List[UiNode]] = None
+        self._screen_text: Optional[str] = None
+
+    async def _ui_nodes(self) -> List[UiNode]:
+        if self.platform != "android":
+            raise AssertionTargetError("Element assertions need an Android UI dump")
+        if self._nodes is None:
+            self._nodes = parse_ui_dump(await self._dump_ui())
+        return self._nodes
+
+    async def _element(self, assertion: Dict[str, Any]) -> Optional[UiNode]:
+        nodes = await self._ui_nodes()
+        if assertion.get("selector"):
+            return find_node_by_selector(nodes, assertion["selector"])
+        if assertion.get("element_description"):
+            match = find_element_from_ui_dump(nodes, assertion["element_description"], synonyms=self.synonyms)
+            return match[0] if match else None
+        raise AssertionTargetError("Element assertions need a selector or element_description")
+
+    async def screen_text(self) -> str:
+        """All visible text, from the UI dump on Android or read from a screenshot by the AI"""
+        if self._screen_text is not None:
+            return self._screen_text
+        if self.platform == "android":
+            nodes = await self._ui_nodes()
+            self._screen_text = "\n".join(
+                part for node in nodes for part in (node.text, node.content_desc) if part
+            )
+            return self._screen_text
+        if self.ai_free:
+            raise AssertionTargetError("Reading iOS screen text needs AI, which is off for this project")
+        try:
+            result = await ask_ai_json(
+                'Transcribe all text visible in this screenshot. Respond with JSON only: {"text": "..."}',
+                "screen_text_ocr",
+                image_base64=await self._screenshot(),
+            )
+        except AiClientError as e:
+            raise AssertionTargetError(f"Couldn't read screen text: {e}")
+        self._screen_text = str(result.get("text", "")) if isinstance(result, dict) else ""
+        return self._screen_text
+
+    async def value(self, assertion: Dict[str, Any]) -> Optional[str]:
+        target = assertion.get("target") or "element_text"
+        if target == "element_text":
+            node = await self._element(assertion)
+            return (node.text or node.content_desc) if node else None
+        if target == "element_attribute":
+            if not assertion.get("attribute"):
+                raise AssertionTargetError("element_attribute assertions need an attribute")
+            node = await self._element(assertion)
+            return node.attributes.get(assertion["attribute"]) if node else None
+        if target == "screen_text":
+            return await self.screen_text()
+        if target == "variable":
+            return _as_text(self.variables.get(assertion.get("name") or ""))
+        if target == "api_response":
+            return await api_value(assertion)
+        raise AssertionTargetError(f"Unknown assertion target '{target}'")
+
+
+async def run_assertions(
+    assertions: List[Dict[str, Any]], reader: DeviceValueReader, soft: bool = False
+) -> List[AssertionResult]:
+    """Evaluate every assertion, so one failure doesn't hide the others"""
+    results = []
+    for assertion in assertions:
+        target = assertion.get("target") or "element_text"
+        operator = assertion.get("operator") or "equals"
+        expected = _as_text(assertion.get("expected"))
+        is_soft = soft if assertion.get("soft") is None else bool(assertion["soft"])
+        try:
+            actual = await reader.value(assertion)
+        except AssertionTargetError as e:
+            results.append(AssertionResult(target, operator, expected, None, False, is_soft, str(e)))
+            continue
+
+        if target == "screen_text" and operator in ("exists", "not_exists") and expected is not None:
+            # Whether the expected text is on screen, rather than whether there is any text
+            actual = expected if expected in actual else None
+        passed, explanation = compare(operator, actual, expected)
+        message = f"{target} {operator}" + (f" {expected!r}" if expected is not None else "") + f": {explanation}"
+        results.append(AssertionResult(target, operator, expected, actual, passed, is_soft, message))
+    return results
+
+
+def summarize(results: List[AssertionResult]) -> Tuple[str, Optional[str], Dict[str, Any]]:
+    """
+    Reduce assertion results to (outcome, error message, details)
+
+    The outcome is 'passed', 'soft_failed' when only soft assertions failed, or 'failed'.
+    """
+    failures = [r for r in results if not r.passed]
+    details = {"assertions": [asdict(r) for r in results]}
+    if not failures:
+        return "passed", None, details
+    message = "; ".join(r.message for r in failures)
+    if all(r.soft for r in failures):
+        return "soft_failed", f"Soft assertion failed: {message}", details
+    return "failed", f"Assertion failed: {message}", details
+
+
+# ============================================
+# Browser Specs
+# ============================================
+
+def _js(value: Any) -> str:
+    return json.dumps("" if value is None else str(value))
+
+
+def playwright_assertion_lines(assertion: Dict[str, Any], soft: bool = False) -> List[str]:
+    """Playwright expectations for an assertion; expect.soft keeps the test going"""
+    expect = "expect.soft" if (soft if assertion.get("soft") is None else assertion["soft"]) else "expect"
+    target = assertion.get("target") or "element_text"
+    operator = assertion.get("operator") or "equals"
+    expected = assertion.get("expected")
+
+    if target == "api_response":
+        path = assertion.get("path") or "status"
+        lines = [
+            "  {",
+            f"    const res = await page.request.fetch({_js(assertion.get('url'))}, "
+            f"{{ method: {_js(assertion.get('method') or 'GET')} }});",
+        ]
+        if path == "status":
+            lines.append("    const actual = String(res.status());")
+        elif path == "body":
+            lines.append("    const actual = await res.text();")
+        else:
+            lines.append(
+                f"    const found = {_js(path)}.split('.').reduce((o, k) => (o == null ? undefined : o[k]), "
+                "await res.json());"
+            )
+            lines.append("    const actual = found === undefined ? null : "
+                         "(typeof found === 'object' ? JSON.stringify(found) : String(found));")
+        return lines + [f"    {line}" for line in _playwright_value_checks(expect, operator, expected)] + ["  }"]
+
+    if target == "variable":
+        return [f"  // Variable assertions aren't available in browser runs: {assertion.get('name')}"]
+
+    locator = "page.locator('body')" if target == "screen_text" else f"page.locator({_js(assertion.get('selector'))})"
+    if target == "element_attribute":
+        attribute = _js(assertion.get("attribute"))
+        if operator == "exists":
+            return [f"  await {expect}({locator}).toHaveAttribute({attribute}, /.*/);"]
+        if operator == "not_exists":
+            return [f"  await {expect}({locator}).not.toHaveAttribute({attribute}, /.*/);"]
+        value = f"await {locator}.getAttribute({attribute})"
+    else:
+        if target == "screen_text" and operator in ("exists", "contains"):
+            return [f"  await {expect}({locator}).toContainText({_js(expected)});"]
+        if target == "screen_text" and operator == "not_exists":
+            return [f"  await {expect}({locator}).not.toContainText({_js(expected)});"]
+        if operator == "exists":
+            return [f"  await {expect}({locator}).toBeVisible();"]
+        if operator == "not_exists":
+            return [f"  await {expect}({locator}).toHaveCount(0);"]
+        if operator == "equals":
+            return [f"  await {expect}({locator}).toHaveText({_js(expected)});"]
+        if operator == "contains":
+            return [f"  await {expect}({locator}).toContainText({_js(expected)});"]
+        value = f"await {locator}.textContent()"
+    return [
+        "  {",
+        f"    const actual = {value};",
+        *[f"    {line}" for line in _playwright_value_checks(expect, operator, expected)],
+        "  }",
+    ]
+
+
+def _playwright_value_checks(expect: str, operator: str, expected: Any) -> List[str]:
+    """Checks on a string-or-null `actual` variable"""
+    if operator == "exists":
+        return [f"{expect}(actual).not.toBeNull();"]
+    if operator == "not_exists":
+        return [f"{expect}(actual).toBeNull();"]
+    if operator == "equals":
+        return [f"{expect}((actual || '').trim()).toBe({_js(expected)}.trim());"]
+    if operator == "not_equals":
+        return [f"{expect}((actual || '').trim()).not.toBe({_js(expected)}.trim());"]
+    if operator == "contains":
+        return [f"{expect}(actual || '').toContain({_js(expected)});"]
+    if operator == "regex":
+        return [f"{expect}(actual || '').toMatch(new RegExp({_js(expected)}));"]
+    if operator == "greater_than":
+        return [f"{expect}(parseFloat(actual)).toBeGreaterThan({float(expected or 0)});"]
+    if operator == "less_than":
+        return [f"{expect}(parseFloat(actual)).toBeLessThan({float(expected or 0)});"]
+    return [f"// Unknown operator: {operator}"]
+
+
+def cypress_assertion_lines(assertion: Dict[str, Any]) -> List[str]:
+    """Cypress assertions; Cypress has no soft assertions, so every failure stops the test"""
+    target = assertion.get("target") or "element_text"
+    operator = assertion.get("operator") or "equals"
+    expected = assertion.get("expected")
+
+    if target == "variable":
+        return [f"    // Variable assertions aren't available in browser runs: {assertion.get('name')}"]
+    if target == "api_response":
+        path = assertion.get("path") or "status"
+        if path == "status":
+            value = "String(res.status)"
+        elif path == "body":
+            value = "typeof res.body === 'string' ? res.body : JSON.stringify(res.body)"
+        else:
+            value = (
+                f"(() => {{ const v = {_js(path)}.split('.').reduce((o, k) => (o == null ? undefined : o[k]), "
+                "res.body); return v === undefined ? null : "
+                "(typeof v === 'object' ? JSON.stringify(v) : String(v)); })()"
+            )
+        return [
+            f"    cy.request({{ url: {_js(assertion.get('url'))}, method: {_js(assertion.get('method') or 'GET')}, "
+            "failOnStatusCode: false }).then((res) => {",
+            f"      const actual = {value};",
+            *[f"      {line}" for line in _cypress_value_checks(operator, expected)],
+            "    });",
+        ]
+
+    selector = "body" if target == "screen_text" else assertion.get("selector")
+    if target == "screen_text" and operator in ("exists", "contains"):
+        return [f"    cy.get('body').should('contain.text', {_js(expected)});"]
+    if target == "screen_text" and operator == "not_exists":
+        return [f"    cy.get('body').should('not.contain.text', {_js(expected)});"]
+    if operator == "not_exists" and target == "element_text":
+        return [f"    cy.get({_js(selector)}).should('not.exist');"]
+    if operator == "exists" and target == "element_text":
+        return [f"    cy.get({_js(selector)}).should('exist');"]
+    if target == "element_attribute":
+        value = f"$el.attr({_js(assertion.get('attribute'))})"
+    else:
+        value = "$el.text()"
+    return [
+        f"    cy.get({_js(selector)}).then(($el) => {{",
+        f"      const actual = {value} ?? null;",
+        *[f"      {line}" for line in _cypress_value_checks(operator, expected)],
+        "    });",
+    ]
+
+
+def _cypress_value_checks(operator: str, expected: Any) -> List[str]:
+    if operator == "exists":
+        return ["expect(actual).not.to.equal(null);"]
+    if operator == "not_exists":
+        return ["expect(actual).to.equal(null);"]
+    if operator == "equals":
+        return [f"expect((actual || '').trim()).to.equal({_js(expected)}.trim());"]
+    if operator == "not_equals":
+        return [f"expect((actual || '').trim()).not.to.equal({_js(expected)}.trim());"]
+    if operator == "contains":
+        return [f"expect(actual || '').to.include({_js(expected)});"]
+    if operator == "regex":
+        return [f"expect(actual || '').to.match(new RegExp({_js(expected)}));"]
+    if operator == "greater_than":
+        return [f"expect(parseFloat(actual)).to.be.greaterThan({float(expected or 0)});"]
+    if operator == "less_than":
+        return [f"expect(parseFloat(actual)).to.be.lessThan({float(expected or 0)});"]
+    return [f"// Unknown operator: {operator}"]
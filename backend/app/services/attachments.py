@@ -0,0 +1,294 @@
+"""
+Attachments - Stores run evidence on local disk or in S3-compatible object storage
+"""
+import asyncio
+import hashlib
+import hmac
+import re
+import secrets
+import time
+import uuid
+from datetime import datetime
+from pathlib import Path
+from typing import Any, Dict, Optional, Tuple
+from urllib.parse import quote, urlsplit
+
+import httpx
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..models import Attachment, AttachmentResponse, StepResult, TestRun
+from .app_settings import get_all_settings
+from .events import event_bus
+
+ATTACHMENT_KINDS = ("screenshot", "video", "trace", "log", "crash", "other")
+STORAGE_BACKENDS = ("local", "s3")
+
+
+class AttachmentError(Exception):
+    """Raised for invalid uploads and unusable storage configuration"""
+
+
+def _data_dir() -> Path:
+    return settings.get_database_path().parent
+
+
+def safe_filename(filename: str) -> str:
+    """Strip directories and characters that aren't safe in storage keys"""
+    name = re.sub(r"[^A-Za-z0-9._-]+", "_", Path(filename or "").name).strip("._")
+    return name or "attachment"
+
+
+# ============================================
+# Storage Backends
+# ============================================
+
+class LocalStorage:
+    """Keeps attachments under the data directory; downloads go through the API"""
+
+    name = "local"
+
+    def __init__(self, root: Path):
+        self.root = root
+
+    def path(self, key: str) -> Path:
+        path = (self.root / key).resolve()
+        if self.root.resolve() not in path.parents:
+            raise AttachmentError("Invalid storage key")
+        return path
+
+    async def put(self, key: str, data: bytes, content_type: str) -> None:
+        path = self.path(key)
+        path.parent.mkdir(parents=True, exist_ok=True)
+        await asyncio.to_thread(path.write_bytes, data)
+
+    async def delete(self, key: str) -> None:
+        self.path(key).unlink(missing_ok=True)
+
+
+def _hmac(key: bytes, message: str) -> bytes:
+    return hmac.new(key, message.encode("utf-8"), hashlib.sha256).digest()
+
+
+def _quote(value: str, safe: str = "-_.~") -> str:
+    return quote(value, safe=safe)
+
+
+class S3Storage:
+    """
+    S3-compatible object storage, signed with AWS Signature Version 4
+
+    Objects are addressed path-style ({endpoint}/{bucket}/{key}), which
+    MinIO, R2 and other S3-compatible services accept as well as AWS.
+    """
+
+    name = "s3"
+
+    def __init__(self, endpoint_url: str, bucket: str, region: str, access_key: str, secret_key: str):
+        self.endpoint = urlsplit(endpoint_url.rstrip("/"))
+        self.bucket = bucket
+        self.region = region
+        self.access_key = access_key
+        self.secret_key = secret_key
+
+    def _path(self, key: str) -> str:
+        return _quote(f"{self.endpoint.path}/{self.bucket}/{key}", safe="/-_.~")
+
+    def _signature(
+        self, method: str, path: str, query: Dict[str, str], headers: Dict[str, str], payload_hash: str, now: datetime
+    ) -> Tuple[str, str]:
+        """Return the signed header names and the request signature"""
+        date = now.strftime("%Y%m%d")
+        signed_headers = ";".join(sorted(headers))
+        canonical_request = "\n".join([
+            method,
+            path,
+            "&".join(f"{_quote(k)}={_quote(v)}" for k, v in sorted(query.items())),
+            "".join(f"{name}:{headers[name].strip()}\n" for name in sorted(headers)),
+            signed_headers,
+            payload_hash,
+        ])
+        string_to_sign = "\n".join([
+            "AWS4-HMAC-SHA256",
+            now.strftime("%Y%m%dT%H%M%SZ"),
+            f"{date}/{self.region}/s3/aws4_request",
+            hashlib.sha256(canonical_request.encode("utf-8")).hexdigest(),
+        ])
+        key = _hmac(f"AWS4{self.secret_key}".encode("utf-8"), date)
+        for part in (self.region, "s3", "aws4_request"):
+            key = _hmac(key, part)
+        return signed_headers, hmac.new(key, string_to_sign.encode("utf-8"), hashlib.sha256).hexdigest()
+
+    async def _request(self, method: str, key: str, data: bytes = b"", content_type: Optional[str] = None) -> None:
+        now = datetime.utcnow()
+        path = self._path(key)
+        payload_hash = hashlib.sha256(data).hexdigest()
+        headers = {
+            "host": self.endpoint.netloc,
+            "x-amz-content-sha256": payload_hash,
+            "x-amz-date": now.strftime("%Y%m%dT%H%M%SZ"),
+        }
+        if content_type:
+            headers["content-type"] = content_type
+        signed_headers, signature = self._signature(method, path, {}, headers, payload_hash, now)
+        headers["authorization"] = (
+            f"AWS4-HMAC-SHA256 Credential={self.access_key}/{now.strftime('%Y%m%d')}/{self.region}/s3/aws4_request, "
+            f"SignedHeaders={signed_headers}, Signature={signature}"
+        )
+        url = f"{self.endpoint.scheme}://{self.endpoint.netloc}{path}"
+        async with httpx.AsyncClient(timeout=60.0) as client:
+            response = await client.request(method, url, content=data, headers=headers)
+        if response.status_code >= 300 and not (method == "DELETE" and response.status_code == 404):
+            raise AttachmentError(f"Object storage returned {response.status_code}: {response.text[:200]}")
+
+    async def put(self, key: str, data: bytes, content_type: str) -> None:
+        await self._request("PUT", key, data, content_type)
+
+    async def delete(self, key: str) -> None:
+        await self._request("DELETE", key)
+
+    def presigned_url(self, key: str, expires_seconds: int, filename: str) -> str:
+        now = datetime.utcnow()
+        path = self._path(key)
+        query = {
+            "X-Amz-Algorithm": "AWS4-HMAC-SHA256",
+            "X-Amz-Credential": f"{self.access_key}/{now.strftime('%Y%m%d')}/{self.region}/s3/aws4_request",
+            "X-Amz-Date": now.strftime("%Y%m%dT%H%M%SZ"),
+            "X-Amz-Expires": str(min(expires_seconds, 604800)),  # S3's limit is 7 days
+            "X-Amz-SignedHeaders": "host",
+            "response-content-disposition": f'attachment; filename="{filename}"',
+        }
+        _, signature = self._signature("GET", path, query, {"host": self.endpoint.netloc}, "UNSIGNED-PAYLOAD", now)
+        query["X-Amz-Signature"] = signature
+        encoded = "&".join(f"{_quote(k)}={_quote(v)}" for k, v in sorted(query.items()))
+        return f"{self.endpoint.scheme}://{self.endpoint.netloc}{path}?{encoded}"
+
+
+def get_storage(values: Dict[str, Any], backend: Optional[str] = None):
+    """Build a storage backend, by default the one attachments.storage selects"""
+    backend = backend or values["attachments.storage"]
+    if backend == "local":
+        return LocalStorage(_data_dir() / "attachments")
+    if backend == "s3":
+        if not values["attachments.s3_endpoint_url"] or not values["attachments.s3_bucket"]:
+            raise AttachmentError("S3 storage needs attachments.s3_endpoint_url and attachments.s3_bucket")
+        if not settings.s3_access_key_id or not settings.s3_secret_access_key:
+            raise AttachmentError("S3 storage needs S3_ACCESS_KEY_ID and S3_SECRET_ACCESS_KEY")
+        return S3Storage(
+            values["attachments.s3_endpoint_url"],
+            values["attachments.s3_bucket"],
+            values["attachments.s3_region"],
+            settings.s3_access_key_id,
+            settings.s3_secret_access_key,
+        )
+    raise AttachmentError(f"Storage must be one of: {', '.join(STORAGE_BACKENDS)}")
+
+
+# ============================================
+# Signed Downloads
+# ============================================
+
+def _secret_path() -> Path:
+    return _data_dir() / "attachment_secret"
+
+
+def _signing_secret() -> bytes:
+    """Read the key local download links are signed with, creating one on first use"""
+    path = _secret_path()
+    if path.exists():
+        secret = path.read_text(encoding="utf-8").strip()
+        if secret:
+            return secret.encode("utf-8")
+    secret = secrets.token_urlsafe(32)
+    path.write_text(secret, encoding="utf-8")
+    try:
+        path.chmod(0o600)
+    except OSError:
+        pass  # Not supported on every platform
+    return secret.encode("utf-8")
+
+
+def sign_download(attachment_id: str, expires: int) -> str:
+    return hmac.new(_signing_secret(), f"{attachment_id}:{expires}".encode("utf-8"), hashlib.sha256).hexdigest()
+
+
+def verify_download(attachment_id: str, expires: int, signature: str) -> bool:
+    return expires >= time.time() and hmac.compare_digest(sign_download(attachment_id, expires), signature)
+
+
+def download_url(attachment: Attachment, values: Dict[str, Any]) -> str:
+    """Signed URL to download an attachment; S3 objects are fetched straight from the bucket"""
+    ttl = values["attachments.link_ttl_seconds"]
+    if attachment.storage == "s3":
+        return get_storage(values, "s3").presigned_url(attachment.storage_key, ttl, attachment.filename)
+    expires = int(time.time()) + ttl
+    signature = sign_download(attachment.id, expires)
+    return f"/api/attachments/{attachment.id}/download?expires={expires}&signature={signature}"
+
+
+def attachment_response(attachment: Attachment, values: Dict[str, Any]) -> AttachmentResponse:
+    response = AttachmentResponse.model_validate(attachment)
+    try:
+        response.download_url = download_url(attachment, values)
+    except AttachmentError:
+        pass  # S3 settings were removed after upload; the metadata is still useful
+    return response
+
+
+# ============================================
+# Upload and Delete
+# ============================================
+
+async def store_attachment(
+    db: AsyncSession,
+    test_run_id: str,
+    filename: str,
+    content_type: Optional[str],
+    data: bytes,
+    kind: str = "other",
+    step_result_id: Optional[str] = None,
+) -> Attachment:
+    """Save a file in the configured storage and record it against a run or step result"""
+    if kind not in ATTACHMENT_KINDS:
+        raise AttachmentError(f"Kind must be one of: {', '.join(ATTACHMENT_KINDS)}")
+    if not await db.get(TestRun, test_run_id):
+        raise AttachmentError("Test run not found")
+    if step_result_id:
+        step_result = await db.get(StepResult, step_result_id)
+        if not step_result or step_result.test_run_id != test_run_id:
+            raise AttachmentError("Step result not found in this test run")
+
+    values = await get_all_settings(db)
+    storage = get_storage(values)
+    attachment_id = str(uuid.uuid4())
+    name = safe_filename(filename)
+    key = f"{test_run_id}/{attachment_id}/{name}"
+    await storage.put(key, data, content_type or "application/octet-stream")
+
+    attachment = Attachment(
+        id=attachment_id,
+        test_run_id=test_run_id,
+        step_result_id=step_result_id,
+        kind=kind,
+        filename=name,
+        content_type=content_type or "application/octet-stream",
+        size_bytes=len(data),
+        sha256=hashlib.sha256(data).hexdigest(),
+        storage=storage.name,
+        storage_key=key,
+    )
+    db.add(attachment)
+    await db.commit()
+    await db.refresh(attachment)
+    event_bus.publish("attachment:added", {
+        "attachment_id": attachment.id, "test_run_id": test_run_id, "step_result_id": step_result_id, "kind": kind
+    })
+    return attachment
+
+
+async def delete_attachment(db: AsyncSession, attachment: Attachment) -> None:
+    """Remove the stored file, then its row"""
+    values = await get_all_settings(db)
+    await get_storage(values, attachment.storage).delete(attachment.storage_key)
+    await db.delete(attachment)
+    await db.commit()
@@ -0,0 +1,193 @@
+"""
+Backup Service - Database backups, restores, and scheduled snapshots
+"""
+import asyncio
+import time
+import uuid
+from datetime import datetime
+from pathlib import Path
+from typing import List, Optional
+
+from sqlalchemy import text
+
+from ..db import AsyncSessionLocal, engine, init_db
+from ..db.database import IS_SQLITE, db_path
+from ..db.encryption import (
+    DATABASE_ERRORS,
+    EncryptionError,
+    check_integrity,
+    connect,
+    export_database,
+    is_encrypted,
+)
+from .app_settings import get_all_settings
+from .events import event_bus
+
+SNAPSHOT_PREFIX = "autotest-snapshot-"
+SNAPSHOT_INTERVAL_SECONDS = 24 * 60 * 60
+
+# How often the scheduler checks whether a snapshot is due, in seconds
+SCHEDULER_POLL_SECONDS = 60 * 60
+
+
+class BackupError(Exception):
+    """Raised when a backup or restore can't be completed"""
+
+
+def snapshot_dir() -> Path:
+    directory = db_path.parent / "backups"
+    directory.mkdir(parents=True, exist_ok=True)
+    return directory
+
+
+def _require_sqlite() -> None:
+    if not IS_SQLITE:
+        raise BackupError("Backups are only for the SQLite database; back a server database up with its own tools")
+
+
+async def backup_database(path: str) -> Path:
+    """Write a consistent copy of the database to a new file using VACUUM INTO"""
+    _require_sqlite()
+    target = Path(path).expanduser().resolve()
+    if target.exists():
+        raise BackupError(f"Backup target already exists: {target}")
+    if not target.parent.is_dir():
+        raise BackupError(f"Backup directory does not exist: {target.parent}")
+
+    async with engine.connect() as conn:
+        # VACUUM can't run inside a transaction
+        conn = await conn.execution_options(isolation_level="AUTOCOMMIT")
+        await conn.execute(text("VACUUM INTO :path"), {"path": str(target)})
+
+    event_bus.publish("db:backup_created", {"path": str(target), "size_bytes": target.stat().st_size})
+    return target
+
+
+def _check_integrity(path: Path) -> None:
+    try:
+        problem = check_integrity(path)
+    except EncryptionError as e:
+        raise BackupError(str(e))
+    except DATABASE_ERRORS as e:
+        raise BackupError(f"Not a valid database: {e}")
+    if problem:
+        raise BackupError(f"Integrity check failed: {problem}")
+
+
+def _copy_database(source: Path, target: Path) -> None:
+    """
+    Copy a backup over the database with SQLite's backup API
+
+    The backup API can't change encryption, so a backup that's encrypted
+    differently from the database is first exported to match it.
+    """
+    converted = None
+    if is_encrypted(source) != is_encrypted(target):
+        converted = target.with_name(f"{target.name}.restore-{uuid.uuid4().hex[:8]}")
+        try:
+            export_database(source, converted, encrypt=is_encrypted(target))
+        except EncryptionError as e:
+            raise BackupError(str(e))
+        source = converted
+    try:
+        src = connect(source, readonly=True)
+        dst = connect(target)
+        try:
+            src.backup(dst)
+        finally:
+            src.close()
+            dst.close()
+    finally:
+        if converted:
+            converted.unlink(missing_ok=True)
+
+
+async def restore_database(path: str) -> Path:
+    """
+    Replace the database with a backup after checking its integrity
+
+    The current database is snapshotted first so the restore can be undone.
+    Tables and columns added since the backup was taken are migrated in.
+    """
+    _require_sqlite()
+    source = Path(path).expanduser().resolve()
+    if not source.is_file():
+        raise BackupError(f"Backup not found: {source}")
+    await asyncio.to_thread(_check_integrity, source)
+
+    safety_snapshot = await create_snapshot(reason="pre-restore")
+
+    await engine.dispose()
+    await asyncio.to_thread(_copy_database, source, db_path)
+    await engine.dispose()
+    await init_db()
+
+    event_bus.publish(
+        "db:restored", {"path": str(source), "previous_snapshot": str(safety_snapshot)}
+    )
+    return safety_snapshot
+
+
+async def create_snapshot(reason: str = "scheduled") -> Path:
+    """Back up the database into the snapshot directory and rotate old snapshots"""
+    timestamp = datetime.utcnow().strftime("%Y%m%d-%H%M%S")
+    target = snapshot_dir() / f"{SNAPSHOT_PREFIX}{timestamp}-{reason}.db"
+    await backup_database(str(target))
+
+    async with AsyncSessionLocal() as db:
+        retention = (await get_all_settings(db))["backup.snapshot_retention"]
+    rotate_snapshots(retention)
+    return target
+
+
+def list_snapshots() -> List[Path]:
+    """List snapshots, newest first"""
+    return sorted(snapshot_dir().glob(f"{SNAPSHOT_PREFIX}*.db"), reverse=True)
+
+
+def rotate_snapshots(keep: int) -> int:
+    """Delete all but the newest snapshots, returning how many were deleted"""
+    if keep <= 0:
+        return 0
+    stale = list_snapshots()[keep:]
+    for snapshot in stale:
+        snapshot.unlink(missing_ok=True)
+    return len(stale)
+
+
+class SnapshotScheduler:
+    """Takes a daily snapshot while the backup.daily_snapshots setting is on"""
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            try:
+                async with AsyncSessionLocal() as db:
+                    enabled = (await get_all_settings(db))["backup.daily_snapshots"]
+                if enabled and IS_SQLITE and self._snapshot_due():
+                    await create_snapshot()
+            except Exception as e:
+                event_bus.publish("db:snapshot_failed", {"error": str(e)})
+            await asyncio.sleep(SCHEDULER_POLL_SECONDS)
+
+    def _snapshot_due(self) -> bool:
+        snapshots = list_snapshots()
+        if not snapshots:
+            return True
+        return time.time() - snapshots[0].stat().st_mtime >= SNAPSHOT_INTERVAL_SECONDS
+
+
+# Singleton instance
+snapshot_scheduler = SnapshotScheduler()
@@ -0,0 +1,111 @@
+"""
+Baselines - Golden runs that later runs of a scenario are compared against
+"""
+from datetime import datetime
+from typing import List, Optional
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import (
+    BaselineComparison,
+    ScenarioBaseline,
+    ScenarioBaselineResponse,
+    Step,
+    StepResult,
+    TestRun,
+)
+from .events import event_bus
+from .run_comparison import compare_runs
+
+
+class BaselineError(Exception):
+    """Raised when a run can't become a scenario's baseline"""
+
+
+async def current_baseline(db: AsyncSession, scenario_id: str) -> Optional[ScenarioBaseline]:
+    result = await db.execute(
+        select(ScenarioBaseline).where(
+            ScenarioBaseline.scenario_id == scenario_id, ScenarioBaseline.superseded_at.is_(None)
+        )
+    )
+    return result.scalar_one_or_none()
+
+
+async def _supersede(db: AsyncSession, scenario_id: str) -> Optional[ScenarioBaseline]:
+    baseline = await current_baseline(db, scenario_id)
+    if baseline:
+        baseline.superseded_at = datetime.utcnow()
+    return baseline
+
+
+async def approve_baseline(
+    db: AsyncSession, scenario_id: str, test_run_id: str, note: Optional[str] = None
+) -> ScenarioBaseline:
+    """Promote a run to a scenario's baseline, keeping the previous one in its history"""
+    if not await db.get(TestRun, test_run_id):
+        raise BaselineError("Test run not found")
+    ran = await db.execute(
+        select(StepResult.id)
+        .join(Step, Step.id == StepResult.step_id)
+        .where(StepResult.test_run_id == test_run_id, Step.scenario_id == scenario_id)
+        .limit(1)
+    )
+    if ran.scalar_one_or_none() is None:
+        raise BaselineError("The test run has no results for this scenario")
+
+    previous = await _supersede(db, scenario_id)
+    baseline = ScenarioBaseline(scenario_id=scenario_id, test_run_id=test_run_id, note=note)
+    db.add(baseline)
+    await db.commit()
+    await db.refresh(baseline)
+    event_bus.publish("baseline:changed", {
+        "scenario_id": scenario_id,
+        "test_run_id": test_run_id,
+        "previous_test_run_id": previous.test_run_id if previous else None,
+    })
+    return baseline
+
+
+async def clear_baseline(db: AsyncSession, scenario_id: str) -> bool:
+    """Stop comparing a scenario against a baseline, returning False if it had none"""
+    previous = await _supersede(db, scenario_id)
+    if not previous:
+        return False
+    await db.commit()
+    event_bus.publish("baseline:changed", {
+        "scenario_id": scenario_id, "test_run_id": None, "previous_test_run_id": previous.test_run_id
+    })
+    return True
+
+
+async def baseline_history(db: AsyncSession, scenario_id: str) -> List[ScenarioBaseline]:
+    result = await db.execute(
+        select(ScenarioBaseline)
+        .where(ScenarioBaseline.scenario_id == scenario_id)
+        .order_by(ScenarioBaseline.created_at.desc())
+    )
+    return result.scalars().all()
+
+
+async def compare_with_baselines(db: AsyncSession, test_run: TestRun) -> List[BaselineComparison]:
+    """Compare each scenario in a run against its baseline, skipping scenarios without one"""
+    result = await db.execute(
+        select(Step.scenario_id)
+        .join(StepResult, StepResult.step_id == Step.id)
+        .where(StepResult.test_run_id == test_run.id)
+        .distinct()
+    )
+    comparisons = []
+    for scenario_id in result.scalars().all():
+        baseline = await current_baseline(db, scenario_id)
+        if not baseline or baseline.test_run_id == test_run.id:
+            continue
+        baseline_run = await db.get(TestRun, baseline.test_run_id)
+        comparison = await compare_runs(db, baseline_run, test_run, scenario_ids={scenario_id})
+        comparisons.append(BaselineComparison(
+            scenario_id=scenario_id,
+            baseline=ScenarioBaselineResponse.model_validate(baseline),
+            comparison=comparison,
+        ))
+    return comparisons
@@ -0,0 +1,159 @@
+"""
+Browser State - Steps that set cookies, web storage and auth tokens in browser runs
+
+Storage and tokens set before the first navigation are injected as each page
+loads, so a scenario can start signed in without walking through its login
+form. Once set they're reapplied on every later page load of the run.
+"""
+import json
+from typing import Any, Dict, List
+from urllib.parse import urlparse
+
+BROWSER_STATE_STEP_TYPES = ("set_cookie", "clear_cookies", "set_storage", "clear_storage", "set_auth_token")
+
+STORAGES = ("local", "session")
+TOKEN_TARGETS = ("header", "cookie", "local", "session")
+SAME_SITE = ("Strict", "Lax", "None")
+
+DEFAULT_TOKEN_KEYS = {"header": "Authorization", "cookie": "auth_token", "local": "auth_token", "session": "auth_token"}
+
+
+class BrowserStateError(Exception):
+    """Raised for browser state steps with missing or invalid settings"""
+
+
+def _storage(config: Dict[str, Any]) -> str:
+    storage = config.get("storage") or "local"
+    if storage not in STORAGES:
+        raise BrowserStateError(f"storage must be one of: {', '.join(STORAGES)}")
+    return storage
+
+
+def _cleared_storages(config: Dict[str, Any]) -> tuple:
+    """Both storages unless the step names one"""
+    return (_storage(config),) if config.get("storage") else STORAGES
+
+
+def check_state_step(step_type: str, config: Dict[str, Any]) -> None:
+    """Raise BrowserStateError when a step is missing what it needs"""
+    if step_type == "set_cookie":
+        if not config.get("name"):
+            raise BrowserStateError("Set the cookie's name")
+        if config.get("same_site") and config["same_site"] not in SAME_SITE:
+            raise BrowserStateError(f"same_site must be one of: {', '.join(SAME_SITE)}")
+    elif step_type == "set_storage":
+        _storage(config)
+        if not config.get("key"):
+            raise BrowserStateError("Set the storage key")
+    elif step_type == "clear_storage":
+        _cleared_storages(config)
+    elif step_type == "set_auth_token":
+        if not config.get("token") and not config.get("value"):
+            raise BrowserStateError("Set the token")
+        if (config.get("target") or "header") not in TOKEN_TARGETS:
+            raise BrowserStateError(f"target must be one of: {', '.join(TOKEN_TARGETS)}")
+
+
+def _token(config: Dict[str, Any]) -> tuple:
+    """(target, key, value) for an auth token; headers default to a Bearer prefix"""
+    target = config.get("target") or "header"
+    key = config.get("key") or DEFAULT_TOKEN_KEYS[target]
+    token = config.get("token") or config.get("value") or ""
+    prefix = config.get("prefix", "Bearer " if target == "header" else "")
+    return target, key, f"{prefix}{token}"
+
+
+def _cookie(config: Dict[str, Any], base_url: str) -> Dict[str, Any]:
+    cookie: Dict[str, Any] = {"name": config["name"], "value": str(config.get("value") or "")}
+    if config.get("domain"):
+        cookie["domain"] = config["domain"]
+        cookie["path"] = config.get("path") or "/"
+    else:
+        cookie["url"] = config.get("url") or base_url
+    for key, option in (("http_only", "httpOnly"), ("secure", "secure"), ("same_site", "sameSite")):
+        if config.get(key) is not None:
+            cookie[option] = config[key]
+    if config.get("expires"):
+        cookie["expires"] = int(config["expires"])  # Unix seconds
+    return cookie
+
+
+# ============================================
+# Browser Specs
+# ============================================
+
+
+def _playwright_storage_lines(storage: str, key: str, value: str) -> List[str]:
+    args = json.dumps([f"{storage}Storage", key, value])
+    script = "([store, key, value]) => window[store].setItem(key, value)"
+    return [
+        f"await page.addInitScript({script}, {args});",
+        f"if (page.url() !== 'about:blank') await page.evaluate({script}, {args});",
+    ]
+
+
+def playwright_state_lines(step_type: str, config: Dict[str, Any], base_url: str) -> List[str]:
+    check_state_step(step_type, config)
+    if step_type == "set_cookie":
+        return [f"await page.context().addCookies([{json.dumps(_cookie(config, base_url))}]);"]
+    if step_type == "clear_cookies":
+        name = f"{{ name: {json.dumps(config['name'])} }}" if config.get("name") else ""
+        return [f"await page.context().clearCookies({name});"]
+    if step_type == "set_storage":
+        return _playwright_storage_lines(_storage(config), config["key"], str(config.get("value") or ""))
+    if step_type == "clear_storage":
+        stores = _cleared_storages(config)
+        key = json.dumps(config.get("key"))
+        return [
+            "if (page.url() !== 'about:blank') await page.evaluate(([stores, key]) => stores.forEach((store) => "
+            "key ? window[store].removeItem(key) : window[store].clear()), "
+            f"[{json.dumps([f'{store}Storage' for store in stores])}, {key}]);"
+        ]
+    if step_type == "set_auth_token":
+        target, key, value = _token(config)
+        if target == "header":
+            return [f"await page.setExtraHTTPHeaders({{ {json.dumps(key)}: {json.dumps(value)} }});"]
+        if target == "cookie":
+            return playwright_state_lines("set_cookie", {"name": key, "value": value}, base_url)
+        return _playwright_storage_lines(target, key, value)
+    return [f"// {step_type} isn't available in browser runs"]
+
+
+def _cypress_storage_lines(storage: str, key: str, value: str) -> List[str]:
+    set_item = f"win.{storage}Storage.setItem({json.dumps(key)}, {json.dumps(value)})"
+    return [
+        f"Cypress.on('window:before:load', (win) => {{ {set_item}; }});",
+        f"cy.window().then((win) => {{ {set_item}; }});",
+    ]
+
+
+def cypress_state_lines(step_type: str, config: Dict[str, Any], base_url: str) -> List[str]:
+    check_state_step(step_type, config)
+    if step_type == "set_cookie":
+        cookie = _cookie(config, base_url)
+        options = {key: value for key, value in cookie.items() if key not in ("name", "value", "url")}
+        if "url" in cookie:
+            # Before the first visit, cy.setCookie would use the runner's own domain
+            options["domain"] = urlparse(cookie["url"]).hostname
+        if "expires" in options:
+            options["expiry"] = options.pop("expires")
+        if options.get("sameSite"):
+            options["sameSite"] = {"None": "no_restriction"}.get(options["sameSite"], options["sameSite"].lower())
+        return [f"cy.setCookie({json.dumps(cookie['name'])}, {json.dumps(cookie['value'])}, {json.dumps(options)});"]
+    if step_type == "clear_cookies":
+        return [f"cy.clearCookie({json.dumps(config['name'])});"] if config.get("name") else ["cy.clearCookies();"]
+    if step_type == "set_storage":
+        return _cypress_storage_lines(_storage(config), config["key"], str(config.get("value") or ""))
+    if step_type == "clear_storage":
+        stores = _cleared_storages(config)
+        key = config.get("key")
+        action = f"removeItem({json.dumps(key)})" if key else "clear()"
+        return [f"cy.window().then((win) => {{ {' '.join(f'win.{s}Storage.{action};' for s in stores)} }});"]
+    if step_type == "set_auth_token":
+        target, key, value = _token(config)
+        if target == "header":
+            return [f"cy.intercept('**', (req) => {{ req.headers[{json.dumps(key)}] = {json.dumps(value)}; }});"]
+        if target == "cookie":
+            return cypress_state_lines("set_cookie", {"name": key, "value": value}, base_url)
+        return _cypress_storage_lines(target, key, value)
+    return [f"// {step_type} isn't available in browser runs"]
@@ -0,0 +1,128 @@
+"""
+Browser Tabs - Steps that open, switch between and close browser tabs and popup windows
+
+Playwright treats tabs and popups alike as pages of the run's browser
+context. switch_tab waits for a page matching its index, title or URL
+pattern, so it also picks up popups a click opened, like OAuth sign-in
+windows. Every later step acts on the tab a step switched to. Cypress runs a
+scenario in a single tab and can't follow them.
+"""
+import json
+import re
+from typing import Any, Dict, List, Optional
+
+TAB_STEP_TYPES = ("open_tab", "switch_tab", "close_tab")
+
+TAB_OUTPUT_MARKER = "[autotest:tab]"
+
+DEFAULT_TAB_TIMEOUT_MS = 10000
+
+# Polls for a tab matching a step, newest first; indexes count from the oldest tab, or the newest if negative
+FIND_TAB_JS = (
+    "async (context, { index, title, url, timeout }) => {"
+    " const deadline = Date.now() + timeout;"
+    " for (;;) {"
+    " const pages = context.pages();"
+    " if (index !== null) { if (pages.at(index)) return pages.at(index); }"
+    " else for (const candidate of [...pages].reverse()) {"
+    " if (url && new RegExp(url).test(candidate.url())) return candidate;"
+    " if (title && (await candidate.title().catch(() => '')).includes(title)) return candidate; }"
+    " if (Date.now() > deadline) throw new Error(`No tab matching ${JSON.stringify({ index, title, url })}`);"
+    " await new Promise((resolve) => setTimeout(resolve, 250)); } }"
+)
+
+
+class BrowserTabError(Exception):
+    """Raised for tab steps with missing or invalid settings"""
+
+
+def _index(config: Dict[str, Any]) -> Optional[int]:
+    if config.get("index") is None or config.get("index") == "":
+        return None
+    try:
+        return int(config["index"])
+    except (TypeError, ValueError):
+        raise BrowserTabError("index must be a whole number")
+
+
+def check_tab_step(step_type: str, config: Dict[str, Any]) -> None:
+    """Raise BrowserTabError when a step is missing what it needs"""
+    if step_type != "switch_tab":
+        return
+    if _index(config) is None and not config.get("title") and not config.get("url_pattern"):
+        raise BrowserTabError("Set the tab's index, title or URL pattern")
+    if config.get("url_pattern"):
+        try:
+            re.compile(config["url_pattern"])
+        except re.error as e:
+            raise BrowserTabError(f"Invalid URL pattern: {e}")
+
+
+def parse_tab_output(output: str) -> List[Dict[str, Any]]:
+    """The tab each tab step left active, read from a spec's output in step order"""
+    tabs = []
+    for line in output.splitlines():
+        if TAB_OUTPUT_MARKER not in line:
+            continue
+        try:
+            tabs.append(json.loads(line.split(TAB_OUTPUT_MARKER, 1)[1]))
+        except ValueError:
+            continue
+    return tabs
+
+
+# ============================================
+# Browser Specs
+# ============================================
+
+
+def playwright_tab_prelude() -> List[str]:
+    return [f"const findTab = {FIND_TAB_JS};"]
+
+
+def _report_line() -> str:
+    return (
+        f"console.log('{TAB_OUTPUT_MARKER}' + JSON.stringify({{ index: page.context().pages().indexOf(page), "
+        "count: page.context().pages().length, url: page.url(), title: await page.title() }));"
+    )
+
+
+def playwright_tab_lines(step_type: str, config: Dict[str, Any], report: bool = True) -> List[str]:
+    """Lines that leave `page` on the step's tab, reassigning the spec's page parameter; report logs the tab"""
+    check_tab_step(step_type, config)
+    if step_type == "open_tab":
+        lines = ["page = await page.context().newPage();"]
+        if config.get("url"):
+            lines.append(f"await page.goto({json.dumps(config['url'])});")
+    elif step_type == "switch_tab":
+        match = {
+            "index": _index(config),
+            "title": config.get("title") or None,
+            "url": config.get("url_pattern") or None,
+            "timeout": int(config.get("timeout") or DEFAULT_TAB_TIMEOUT_MS),
+        }
+        lines = [
+            f"page = await findTab(page.context(), {json.dumps(match)});",
+            "await page.bringToFront();",
+            "await page.waitForLoadState();",
+        ]
+    elif step_type == "close_tab":
+        # The newest remaining tab becomes active, usually the opener when a popup closes
+        lines = [
+            "{",
+            "  const context = page.context();",
+            "  await page.close();",
+            "  page = context.pages().at(-1);",
+            "  if (!page) throw new Error('close_tab closed the last tab');",
+            "  await page.bringToFront();",
+            "}",
+        ]
+    else:
+        return [f"// {step_type} isn't available in browser runs"]
+    return lines + [_report_line()] if report else lines
+
+
+def cypress_tab_lines(step_type: str, config: Dict[str, Any]) -> List[str]:
+    check_tab_step(step_type, config)
+    message = f"Cypress runs in a single tab and can't {step_type}; run this scenario with Playwright"
+    return [f"cy.then(() => {{ throw new Error({json.dumps(message)}); }});"]
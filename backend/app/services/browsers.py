@@ -0,0 +1,301 @@
+"""
+Browsers - Finds the browsers installed for web tests and installs a managed Chromium when there are none
+
+Browsers are named the way users pick them: chromium, chrome, edge, firefox
+and safari. Playwright and Cypress each get the name or channel they expect.
+The managed Chromium is Playwright's build, installed under the data
+directory so it never touches the user's own Playwright cache.
+"""
+import asyncio
+import json
+import os
+import platform
+import plistlib
+import shutil
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Dict, List, Optional, Tuple
+
+from pydantic import BaseModel
+
+from ..config import settings
+from ..models import Project, Viewport
+from .tools import VERSION_TIMEOUT, tool_command
+
+BROWSERS = ("chromium", "chrome", "edge", "firefox", "safari")
+
+INSTALL_TIMEOUT = 600.0
+
+
+class BrowserError(Exception):
+    """Raised for unknown browsers, unsupported framework pairings and failed installs"""
+
+
+@dataclass
+class BrowserSpec:
+    commands: List[str] = field(default_factory=list)  # Names looked up on PATH
+    # Install locations by platform.system(); Windows paths may use environment variables
+    paths: Dict[str, List[str]] = field(default_factory=dict)
+
+
+BROWSER_SPECS: Dict[str, BrowserSpec] = {
+    "chromium": BrowserSpec(
+        ["chromium", "chromium-browser"],
+        {"Darwin": ["/Applications/Chromium.app/Contents/MacOS/Chromium"]},
+    ),
+    "chrome": BrowserSpec(
+        ["google-chrome", "google-chrome-stable", "chrome"],
+        {
+            "Darwin": ["/Applications/Google Chrome.app/Contents/MacOS/Google Chrome"],
+            "Windows": [
+                r"%PROGRAMFILES%\Google\Chrome\Application\chrome.exe",
+                r"%PROGRAMFILES(X86)%\Google\Chrome\Application\chrome.exe",
+                r"%LOCALAPPDATA%\Google\Chrome\Application\chrome.exe",
+            ],
+        },
+    ),
+    "edge": BrowserSpec(
+        ["microsoft-edge", "microsoft-edge-stable"],
+        {
+            "Darwin": ["/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge"],
+            "Windows": [
+                r"%PROGRAMFILES(X86)%\Microsoft\Edge\Application\msedge.exe",
+                r"%PROGRAMFILES%\Microsoft\Edge\Application\msedge.exe",
+            ],
+        },
+    ),
+    "firefox": BrowserSpec(
+        ["firefox"],
+        {
+            "Darwin": ["/Applications/Firefox.app/Contents/MacOS/firefox"],
+            "Windows": [
+                r"%PROGRAMFILES%\Mozilla Firefox\firefox.exe",
+                r"%PROGRAMFILES(X86)%\Mozilla Firefox\firefox.exe",
+            ],
+        },
+    ),
+    "safari": BrowserSpec([], {"Darwin": ["/Applications/Safari.app/Contents/MacOS/Safari"]}),
+}
+
+# Browsers Cypress can launch by name
+CYPRESS_BROWSERS = ("chrome", "chromium", "edge", "firefox", "electron")
+
+# Playwright's browser and channel for each browser
+PLAYWRIGHT_BROWSERS: Dict[str, Tuple[str, Optional[str]]] = {
+    "chromium": ("chromium", None),
+    "chrome": ("chromium", "chrome"),
+    "edge": ("chromium", "msedge"),
+    "firefox": ("firefox", None),
+    "safari": ("webkit", None),
+    "webkit": ("webkit", None),
+}
+
+
+class BrowserInfo(BaseModel):
+    name: str
+    path: str
+    version: Optional[str] = None
+    managed: bool = False  # The Chromium this app installed
+
+
+def managed_browsers_dir() -> Path:
+    return settings.get_database_path().parent / "browsers"
+
+
+def playwright_cache_dirs() -> List[Path]:
+    """Where Playwright keeps its browsers by default on each platform"""
+    home = Path.home()
+    dirs = [home / ".cache" / "ms-playwright", home / "Library" / "Caches" / "ms-playwright"]
+    if os.environ.get("LOCALAPPDATA"):
+        dirs.append(Path(os.environ["LOCALAPPDATA"]) / "ms-playwright")
+    if os.environ.get("PLAYWRIGHT_BROWSERS_PATH"):
+        dirs.insert(0, Path(os.environ["PLAYWRIGHT_BROWSERS_PATH"]))
+    return dirs
+
+
+def _playwright_chromium(root: Path) -> Optional[Path]:
+    """The Chromium executable in a Playwright browsers directory"""
+    patterns = [
+        "chromium-*/chrome-linux/chrome",
+        "chromium-*/chrome-win/chrome.exe",
+        "chromium-*/chrome-mac/Chromium.app/Contents/MacOS/Chromium",
+        "chromium-*/chrome-mac/Google Chrome for Testing.app/Contents/MacOS/Google Chrome for Testing",
+    ]
+    for pattern in patterns:
+        found = sorted(root.glob(pattern), reverse=True)
+        if found:
+            return found[0]
+    return None
+
+
+def managed_chromium() -> Optional[Path]:
+    root = managed_browsers_dir()
+    return _playwright_chromium(root) if root.is_dir() else None
+
+
+def _installed_path(name: str) -> Optional[str]:
+    spec = BROWSER_SPECS[name]
+    for command in spec.commands:
+        found = shutil.which(command)
+        if found:
+            return found
+    for path in spec.paths.get(platform.system(), []):
+        expanded = Path(os.path.expandvars(path))
+        if expanded.is_file():
+            return str(expanded)
+    return None
+
+
+async def _browser_version(name: str, path: str) -> Optional[str]:
+    if name == "safari":
+        try:
+            with open(Path(path).parents[1] / "Info.plist", "rb") as file:
+                return f"Safari {plistlib.load(file).get('CFBundleShortVersionString')}"
+        except (OSError, plistlib.InvalidFileException):
+            return None
+    if os.name == "nt":
+        return None  # Windows browsers open a window instead of printing --version
+    try:
+        process = await asyncio.create_subprocess_exec(
+            path, "--version", stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.DEVNULL
+        )
+        stdout, _ = await asyncio.wait_for(process.communicate(), VERSION_TIMEOUT)
+    except (OSError, asyncio.TimeoutError):
+        return None
+    return stdout.decode(errors="replace").strip() or None
+
+
+async def detect_browsers() -> List[BrowserInfo]:
+    """Browsers installed on this machine, then the managed Chromium if it's been installed"""
+    found = [(name, path) for name in BROWSER_SPECS if (path := _installed_path(name))]
+    browsers = [
+        BrowserInfo(name=name, path=path, version=version)
+        for (name, path), version in zip(found, await asyncio.gather(*(_browser_version(n, p) for n, p in found)))
+    ]
+    managed = managed_chromium()
+    if managed:
+        version = await _browser_version("chromium", str(managed))
+        browsers.append(BrowserInfo(name="chromium", path=str(managed), version=version, managed=True))
+    return browsers
+
+
+def chromium_executable() -> Optional[str]:
+    """A Chromium-based browser to print pages with, preferring an installed one over the managed Chromium"""
+    for name in ("chrome", "chromium", "edge"):
+        path = _installed_path(name)
+        if path:
+            return path
+    managed = managed_chromium()
+    return str(managed) if managed else None
+
+
+def check_browser(name: str) -> str:
+    if name not in BROWSERS:
+        raise BrowserError(f"Browser must be one of: {', '.join(BROWSERS)}")
+    return name
+
+
+def check_viewports(viewports: List[Viewport]) -> None:
+    names = [viewport.name.strip() for viewport in viewports]
+    if not all(names) or len(set(names)) != len(names):
+        raise BrowserError("Viewports need unique, non-empty names")
+    for viewport in viewports:
+        if viewport.width <= 0 or viewport.height <= 0 or viewport.device_scale_factor <= 0:
+            raise BrowserError(f"Viewport {viewport.name} needs a positive width, height and scale factor")
+
+
+def select_viewports(project: Project, names: Optional[List[str]]) -> List[Optional[Viewport]]:
+    """
+    The viewports a web run repeats across
+
+    None runs once at the framework's default size, an empty list runs every
+    viewport the project defines, and names pick some of them.
+    """
+    if names is None:
+        return [None]
+    defined = {viewport.name: viewport for viewport in map(Viewport.model_validate, json.loads(project.viewports))}
+    if not names:
+        if not defined:
+            raise BrowserError("The project has no viewports")
+        return list(defined.values())
+    unknown = [name for name in names if name not in defined]
+    if unknown:
+        raise BrowserError(f"Unknown viewports: {', '.join(unknown)}")
+    return [defined[name] for name in names]
+
+
+# ============================================
+# Managed Chromium
+# ============================================
+
+_install_lock = asyncio.Lock()
+
+
+async def install_managed_chromium() -> BrowserInfo:
+    """Download Playwright's Chromium into the data directory"""
+    async with _install_lock:
+        if not managed_chromium():
+            managed_browsers_dir().mkdir(parents=True, exist_ok=True)
+            try:
+                process = await asyncio.create_subprocess_exec(
+                    tool_command("npx"), "--yes", "playwright", "install", "chromium",
+                    stdout=asyncio.subprocess.PIPE,
+                    stderr=asyncio.subprocess.STDOUT,
+                    env={**os.environ, "PLAYWRIGHT_BROWSERS_PATH": str(managed_browsers_dir())},
+                )
+                stdout, _ = await asyncio.wait_for(process.communicate(), INSTALL_TIMEOUT)
+            except FileNotFoundError:
+                raise BrowserError("npx not found; install Node.js to download Chromium")
+            except asyncio.TimeoutError:
+                process.kill()
+                raise BrowserError("Chromium download timed out")
+            if process.returncode != 0:
+                raise BrowserError(f"Chromium download failed: {stdout.decode(errors='replace')[-500:]}")
+    path = managed_chromium()
+    if not path:
+        raise BrowserError("Chromium was downloaded but its executable wasn't found")
+    version = await _browser_version("chromium", str(path))
+    return BrowserInfo(name="chromium", path=str(path), version=version, managed=True)
+
+
+async def ensure_browser(name: str) -> None:
+    """Install the managed Chromium for a Chromium run when neither Playwright nor the system has one"""
+    if name != "chromium" or _installed_path("chromium") or managed_chromium():
+        return
+    if any(_playwright_chromium(root) for root in playwright_cache_dirs() if root.is_dir()):
+        return
+    await install_managed_chromium()
+
+
+# ============================================
+# Framework Names
+# ============================================
+
+
+def resolve_browser(project: Project, browser: Optional[str], headless: Optional[bool]) -> Tuple[str, bool]:
+    """The browser and headless flag for a run: what was asked for, else the project's defaults"""
+    browser = browser or project.default_browser or "chromium"
+    return browser, project.browser_headless if headless is None else headless
+
+
+def playwright_browser(name: str) -> Tuple[str, Optional[str]]:
+    """Playwright's browserName and channel for a browser"""
+    return PLAYWRIGHT_BROWSERS.get(name, (name, None))
+
+
+def cypress_browser(name: str) -> str:
+    """Cypress's --browser value; Chromium without a system install runs the managed one by path"""
+    if name == "safari":
+        raise BrowserError("Cypress can't run Safari; use Playwright, which runs it as WebKit")
+    if name == "chromium" and not _installed_path("chromium"):
+        managed = managed_chromium()
+        if managed:
+            return str(managed)
+    return name
+
+
+def browser_env(name: str) -> Dict[str, str]:
+    """Environment for a Playwright run that uses the managed Chromium"""
+    if name == "chromium" and managed_chromium():
+        return {"PLAYWRIGHT_BROWSERS_PATH": str(managed_browsers_dir())}
+    return {}
@@ -0,0 +1,186 @@
+"""
+Budgets - Time budgets for steps and scenarios, and the slowest steps over time
+
+A step's budget is its max_duration_ms config; a scenario's is the total of
+its step durations in one run, checked per viewport for web runs. Going
+over a budget either warns, leaving the step passed with the overrun in its
+details, or fails the step, as the step or scenario's budget_action or the
+executor.budget_action setting says.
+"""
+import json
+from collections import defaultdict
+from datetime import datetime, timedelta
+from typing import Any, Dict, List, Optional, Tuple
+
+from sqlalchemy import and_, func, or_, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import DurationPoint, Scenario, SlowStep, Step, StepResult, TestCase
+
+BUDGET_ACTIONS = ("warn", "fail")
+
+
+class BudgetError(Exception):
+    """Raised for budgets that aren't positive or actions that don't exist"""
+
+
+def check_budget(max_duration_ms: Any, action: Optional[str]) -> None:
+    if max_duration_ms is not None:
+        if isinstance(max_duration_ms, bool) or not isinstance(max_duration_ms, int) or max_duration_ms <= 0:
+            raise BudgetError("max_duration_ms must be a positive number of milliseconds")
+    if action and action not in BUDGET_ACTIONS:
+        raise BudgetError(f"budget_action must be one of: {', '.join(BUDGET_ACTIONS)}")
+
+
+def overrun(duration_ms: Optional[int], max_duration_ms: Optional[int], action: str) -> Optional[Dict[str, Any]]:
+    """The budget details of a duration that went over its budget, or None when it didn't"""
+    if not max_duration_ms or duration_ms is None or duration_ms <= max_duration_ms:
+        return None
+    return {
+        "max_duration_ms": max_duration_ms,
+        "duration_ms": duration_ms,
+        "over_ms": duration_ms - max_duration_ms,
+        "action": action,
+    }
+
+
+def step_budget(step: Step) -> Optional[int]:
+    budget = json.loads(step.config or "{}").get("max_duration_ms")
+    return budget if isinstance(budget, int) and budget > 0 else None
+
+
+def budget_message(subject: str, budget: Dict[str, Any]) -> str:
+    return f"{subject} took {budget['duration_ms']} ms, over its {budget['max_duration_ms']} ms budget"
+
+
+def apply_step_budget(outcome: Any, config: Dict[str, Any], default_action: str) -> Optional[Dict[str, Any]]:
+    """Record a passed step's overrun in its outcome's details, failing it when the budget says to"""
+    max_duration_ms = config.get("max_duration_ms")
+    if outcome.status != "passed" or not isinstance(max_duration_ms, int):
+        return None
+    budget = overrun(outcome.duration_ms, max_duration_ms, config.get("budget_action") or default_action)
+    if budget:
+        outcome.details = {**(outcome.details or {}), "budget": budget}
+        if budget["action"] == "fail":
+            outcome.status = "failed"
+            outcome.error_message = budget_message("Step", budget)
+    return budget
+
+
+async def check_scenario_budget(
+    db: AsyncSession, scenario: Scenario, test_run_id: str, default_action: str
+) -> Tuple[List[str], int]:
+    """
+    Compare a scenario's step durations in a run with its budget, without committing
+
+    Returns a message per overrun and how many passed steps were failed for
+    going over. A failing overrun fails the scenario's last passed step.
+    """
+    if not scenario.max_duration_ms:
+        return [], 0
+    action = scenario.budget_action or default_action
+    result = await db.execute(
+        select(StepResult)
+        .join(Step, StepResult.step_id == Step.id)
+        .where(StepResult.test_run_id == test_run_id, Step.scenario_id == scenario.id)
+        .order_by(StepResult.created_at)
+    )
+    by_viewport: Dict[Optional[str], List[StepResult]] = defaultdict(list)
+    for step_result in result.scalars().all():
+        by_viewport[step_result.viewport].append(step_result)
+
+    messages, failed = [], 0
+    for viewport, results in by_viewport.items():
+        budget = overrun(sum(r.duration_ms or 0 for r in results), scenario.max_duration_ms, action)
+        if not budget:
+            continue
+        subject = f"{scenario.name} at {viewport}" if viewport else scenario.name
+        messages.append(budget_message(subject, budget))
+        passed = [r for r in results if r.status == "passed"]
+        if action == "fail" and passed:
+            passed[-1].status = "failed"
+            passed[-1].error_message = budget_message(subject, budget)
+            failed += 1
+    return messages, failed
+
+
+# ============================================
+# Slowest Steps
+# ============================================
+
+
+async def slowest_steps(db: AsyncSession, project_id: str, days: int = 30, limit: int = 10) -> List[SlowStep]:
+    """A project's steps with the highest average duration over recent runs, with their daily trend"""
+    since = datetime.utcnow() - timedelta(days=days)
+    ranked = await db.execute(
+        select(
+            Step,
+            Scenario.name,
+            func.avg(StepResult.duration_ms),
+            func.max(StepResult.duration_ms),
+            func.count(StepResult.id),
+        )
+        .join(StepResult, StepResult.step_id == Step.id)
+        .join(Scenario, Step.scenario_id == Scenario.id)
+        .join(TestCase, Scenario.test_case_id == TestCase.id)
+        .where(
+            TestCase.project_id == project_id,
+            StepResult.created_at >= since,
+            StepResult.status != "skipped",
+            StepResult.duration_ms.is_not(None),
+        )
+        .group_by(Step.id)
+        .order_by(func.avg(StepResult.duration_ms).desc())
+        .limit(limit)
+    )
+    rows = ranked.all()
+    if not rows:
+        return []
+
+    day = func.date(StepResult.created_at)
+    daily = await db.execute(
+        select(StepResult.step_id, day, func.avg(StepResult.duration_ms), func.count(StepResult.id))
+        .where(
+            StepResult.step_id.in_([step.id for step, *_ in rows]),
+            StepResult.created_at >= since,
+            StepResult.status != "skipped",
+            StepResult.duration_ms.is_not(None),
+        )
+        .group_by(StepResult.step_id, day)
+        .order_by(day)
+    )
+    trends: Dict[str, List[DurationPoint]] = defaultdict(list)
+    for step_id, point_day, avg_ms, runs in daily.all():
+        trends[step_id].append(DurationPoint(day=str(point_day), avg_ms=int(avg_ms), runs=runs))
+
+    budgets = {step.id: step_budget(step) for step, *_ in rows}
+    over_budget: Dict[str, int] = defaultdict(int)
+    over = [
+        and_(StepResult.step_id == step_id, StepResult.duration_ms > budget)
+        for step_id, budget in budgets.items()
+        if budget
+    ]
+    if over:
+        counted = await db.execute(
+            select(StepResult.step_id, func.count(StepResult.id))
+            .where(StepResult.created_at >= since, or_(*over))
+            .group_by(StepResult.step_id)
+        )
+        over_budget.update(dict(counted.all()))
+
+    return [
+        SlowStep(
+            step_id=step.id,
+            label=step.label,
+            step_type=step.step_type,
+            scenario_id=step.scenario_id,
+            scenario_name=scenario_name,
+            avg_ms=int(avg_ms),
+            max_ms=int(max_ms),
+            runs=runs,
+            max_duration_ms=budgets[step.id],
+            over_budget=over_budget[step.id],
+            trend=trends[step.id],
+        )
+        for step, scenario_name, avg_ms, max_ms, runs in rows
+    ]
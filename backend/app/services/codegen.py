@@ -0,0 +1,624 @@
+"""
+Code Generation - Exports scenarios as standalone web and mobile test files without the test runner service
+"""
+import json
+import re
+from dataclasses import dataclass
+from typing import Any, Callable, Dict, List, Optional
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Element, Project, Scenario, Step, TestCase
+from .browser_state import BROWSER_STATE_STEP_TYPES, BrowserStateError, cypress_state_lines, playwright_state_lines
+from .browser_tabs import (
+    TAB_STEP_TYPES,
+    BrowserTabError,
+    cypress_tab_lines,
+    playwright_tab_lines,
+    playwright_tab_prelude,
+)
+from .browsers import playwright_browser
+from .coordinates import to_device_point
+from .elements import ELEMENT_KEY, apply_element, load_elements
+from .file_steps import FileStepError, fixture_names, resolve_fixture
+from .web_context import cypress_context_lines, in_context, playwright_context_lines
+
+
+class CodegenError(Exception):
+    """Raised for unknown export targets"""
+
+
+@dataclass
+class GeneratedSpec:
+    filename: str
+    target: str
+    language: str
+    content: str
+
+
+@dataclass
+class SpecContext:
+    """What a generator needs to render one scenario"""
+
+    test_case: TestCase
+    scenario: Scenario
+    steps: List[Step]
+    elements: Dict[str, Element]
+    base_url: str
+    platform: str = "web"
+    browser: Optional[str] = None  # The project's default browser for web targets
+    headless: bool = True
+
+
+def _js(value: Any) -> str:
+    """Render a value as a JavaScript/TypeScript literal"""
+    return json.dumps(value, ensure_ascii=False)
+
+
+def _slug(name: str) -> str:
+    return re.sub(r"[^a-z0-9]+", "-", name.lower()).strip("-") or "scenario"
+
+
+def _url(config: Dict[str, Any], base_url: str) -> str:
+    url = config.get("url") or base_url
+    if url.startswith("/") and base_url:
+        url = base_url.rstrip("/") + url
+    return url
+
+
+def _playwright_locator(element: Element) -> Optional[str]:
+    if element.css_selector:
+        return element.css_selector
+    if element.xpath:
+        return f"xpath={element.xpath}"
+    return None
+
+
+def _cypress_locator(element: Element) -> Optional[str]:
+    # cy.get only understands CSS selectors
+    return element.css_selector
+
+
+def _element_table(ctx: SpecContext, used: List[str], locator: Callable[[Element], Optional[str]]) -> List[str]:
+    """Declare the referenced elements once so locators can be maintained in one place"""
+    rows = [f"  {_js(name)}: {_js(locator(ctx.elements[name]))}," for name in used]
+    return ["const elements = {", *rows, "};", ""] if rows else []
+
+
+def _used_elements(ctx: SpecContext) -> List[str]:
+    names = []
+    for step in ctx.steps:
+        name = json.loads(step.config or "{}").get(ELEMENT_KEY)
+        if name in ctx.elements and name not in names:
+            names.append(name)
+    return names
+
+
+def _selector_expr(config: Dict[str, Any], ctx: SpecContext) -> Optional[str]:
+    """Reference the element table for repository elements, otherwise inline the selector"""
+    name = config.get(ELEMENT_KEY)
+    if name in ctx.elements:
+        return f"elements[{_js(name)}]"
+    return _js(config["selector"]) if config.get("selector") else None
+
+
+def _fixture_paths(config: Dict[str, Any]) -> List[str]:
+    """Upload fixtures by absolute path when they exist here, else as named in the step"""
+    paths = []
+    for name in fixture_names(config):
+        try:
+            paths.append(str(resolve_fixture(name)))
+        except FileStepError:
+            paths.append(name)
+    return paths
+
+
+def _playwright_step(step: Step, config: Dict[str, Any], ctx: SpecContext) -> List[str]:
+    sel = _selector_expr(config, ctx)
+    value = _js(config.get("value") or "")
+    kind = step.step_type
+    if in_context(kind, config):
+        try:
+            return playwright_context_lines(kind, config)
+        except FileStepError as e:
+            return [f"// TODO: {e}"]
+    if kind == "navigate":
+        return [f"await page.goto({_js(_url(config, ctx.base_url))});"]
+    if kind == "click" and sel:
+        return [f"await page.click({sel});"]
+    if kind in ("type", "input") and sel:
+        return [f"await page.fill({sel}, {value});"]
+    if kind == "hover" and sel:
+        return [f"await page.hover({sel});"]
+    if kind == "select" and sel:
+        return [f"await page.selectOption({sel}, {value});"]
+    if kind == "upload_file" and sel:
+        return [f"await page.setInputFiles({sel}, {_js(_fixture_paths(config))});"]
+    if kind == "wait":
+        return [f"await page.waitForTimeout({int(config.get('timeout') or config.get('duration') or 1000)});"]
+    if kind == "scroll":
+        if sel:
+            return [f"await page.locator({sel}).scrollIntoViewIfNeeded();"]
+        return [f"await page.mouse.wheel(0, {int(config.get('value') or 500)});"]
+    if kind == "screenshot":
+        return [f"await page.screenshot({{ path: {_js(_slug(step.label) + '.png')} }});"]
+    if kind == "verify":
+        lines = []
+        if sel:
+            lines.append(f"await expect(page.locator({sel})).toBeVisible();")
+        if config.get("expected"):
+            expected = _js(config["expected"])
+            operator = config.get("operator") or "not.include"
+            if operator == "include":
+                lines.append(f"expect(page.url()).toContain({expected});")
+            elif operator == "equal":
+                lines.append(f"expect(page.url()).toBe({expected});")
+            else:
+                lines.append(f"expect(page.url()).not.toContain({expected});")
+        return lines
+    if kind in BROWSER_STATE_STEP_TYPES:
+        try:
+            return playwright_state_lines(kind, config, ctx.base_url)
+        except BrowserStateError as e:
+            return [f"// TODO: {e}"]
+    if kind in TAB_STEP_TYPES:
+        try:
+            return playwright_tab_lines(kind, config, report=False)
+        except BrowserTabError as e:
+            return [f"// TODO: {e}"]
+    return [f"// TODO: '{kind}' steps can't be exported to Playwright"]
+
+
+def _cypress_step(step: Step, config: Dict[str, Any], ctx: SpecContext) -> List[str]:
+    sel = _selector_expr(config, ctx)
+    value = _js(config.get("value") or "")
+    kind = step.step_type
+    if in_context(kind, config):
+        try:
+            return cypress_context_lines(kind, config)
+        except FileStepError as e:
+            return [f"// TODO: {e}"]
+    if kind == "navigate":
+        return [f"cy.visit({_js(_url(config, ctx.base_url))});"]
+    if kind == "click" and sel:
+        return [f"cy.get({sel}).click();"]
+    if kind in ("type", "input") and sel:
+        return [f"cy.get({sel}).clear().type({value});"]
+    if kind == "hover" and sel:
+        return [f"cy.get({sel}).trigger('mouseover');"]
+    if kind == "select" and sel:
+        return [f"cy.get({sel}).select({value});"]
+    if kind == "upload_file" and sel:
+        return [f"cy.get({sel}).selectFile({_js(_fixture_paths(config))}, {{ force: true }});"]
+    if kind == "wait":
+        return [f"cy.wait({int(config.get('timeout') or config.get('duration') or 1000)});"]
+    if kind == "scroll":
+        return [f"cy.get({sel}).scrollIntoView();"] if sel else ["cy.scrollTo('bottom');"]
+    if kind == "screenshot":
+        return [f"cy.screenshot({_js(_slug(step.label))});"]
+    if kind == "verify":
+        lines = []
+        if sel:
+            lines.append(f"cy.get({sel}).should('be.visible');")
+        if config.get("expected"):
+            operator = config.get("operator") or "not.include"
+            lines.append(f"cy.url().should({_js(operator)}, {_js(config['expected'])});")
+        return lines
+    if kind in BROWSER_STATE_STEP_TYPES:
+        try:
+            return cypress_state_lines(kind, config, ctx.base_url)
+        except BrowserStateError as e:
+            return [f"// TODO: {e}"]
+    if kind in TAB_STEP_TYPES:
+        try:
+            return cypress_tab_lines(kind, config)
+        except BrowserTabError as e:
+            return [f"// TODO: {e}"]
+    return [f"// TODO: '{kind}' steps can't be exported to Cypress"]
+
+
+def _render_steps(ctx: SpecContext, render: Callable, indent: str, comment: str = "//") -> List[str]:
+    lines = []
+    for step in ctx.steps:
+        config = json.loads(step.config or "{}")
+        lines.append(f"{indent}{comment} {' '.join(step.label.split())}")
+        lines.extend(f"{indent}{line}" for line in render(step, config, ctx))
+    return lines
+
+
+def playwright_spec(ctx: SpecContext) -> str:
+    """Render a scenario as a Playwright Test TypeScript file"""
+    lines = ["import { test, expect } from '@playwright/test';", ""]
+    if ctx.browser:
+        browser_name, channel = playwright_browser(ctx.browser)
+        options = [f"browserName: {_js(browser_name)}"] + ([f"channel: {_js(channel)}"] if channel else [])
+        lines += [f"test.use({{ {', '.join(options)}, headless: {str(ctx.headless).lower()} }});", ""]
+    lines += _element_table(ctx, _used_elements(ctx), _playwright_locator)
+    tab_prelude = playwright_tab_prelude() if any(step.step_type == "switch_tab" for step in ctx.steps) else []
+    lines += [
+        f"test.describe({_js(ctx.test_case.name)}, () => {{",
+        f"  test({_js(ctx.scenario.name)}, async ({{ page }}) => {{",
+        *(f"    {line}" for line in tab_prelude),
+        *_render_steps(ctx, _playwright_step, "    "),
+        "  });",
+        "});",
+    ]
+    return "\n".join(lines) + "\n"
+
+
+def cypress_spec(ctx: SpecContext) -> str:
+    """Render a scenario as a Cypress JavaScript spec"""
+    lines = []
+    if ctx.browser and ctx.browser != "safari":
+        headed = "" if ctx.headless else " --headed"
+        lines += [f"// Run with: npx cypress run --browser {ctx.browser}{headed}", ""]
+    lines += _element_table(ctx, _used_elements(ctx), _cypress_locator)
+    lines += [
+        f"describe({_js(ctx.test_case.name)}, () => {{",
+        f"  it({_js(ctx.scenario.name)}, () => {{",
+        *_render_steps(ctx, _cypress_step, "    "),
+        "  });",
+        "});",
+    ]
+    return "\n".join(lines) + "\n"
+
+
+# ============================================
+# Mobile Targets
+# ============================================
+
+# Seconds to wait for an element before a verify step fails
+MOBILE_VERIFY_TIMEOUT = 5
+
+
+def _camel(name: str) -> str:
+    return "".join(part.capitalize() for part in _slug(name).split("-"))
+
+
+def _kotlin(value: str) -> str:
+    return _js(value).replace("$", "\\$")
+
+
+def _mobile_target(config: Dict[str, Any], ctx: SpecContext) -> Optional[tuple]:
+    """
+    Resolve a step's target as ('id' | 'accessibility_id' | 'text', value) or ('point', (x, y))
+
+    Repository elements use the platform's locator. Plain Android selectors are
+    treated as resource IDs when they look like one and as visible text otherwise.
+    """
+    from_element = config.get(ELEMENT_KEY) in ctx.elements
+    config = apply_element(config, ctx.elements, ctx.platform)
+    selector = config.get("selector")
+    if selector:
+        if ctx.platform == "ios":
+            return ("accessibility_id", selector)
+        if from_element or ":id/" in selector:
+            return ("id", selector)
+        return ("text", selector)
+    # tap_element descriptors, most specific attribute first
+    if config.get("resource_id"):
+        return ("id", config["resource_id"])
+    if config.get("content_desc"):
+        return ("accessibility_id", config["content_desc"])
+    if config.get("text"):
+        return ("text", config["text"])
+    if config.get("x") is not None and config.get("y") is not None:
+        point = _reference_point(config, "x", "y")
+        return ("point", point) if point else None
+    return None
+
+
+def _reference_point(config: Dict[str, Any], x_key: str, y_key: str) -> Optional[tuple]:
+    """Pixel coordinates on the screen the step was recorded on, since exported code can't measure it"""
+    reference = (config.get("reference_width"), config.get("reference_height"))
+    try:
+        return to_device_point(config, x_key, y_key, reference if all(reference) else None)
+    except ValueError:
+        return None  # Normalized without a reference resolution
+
+
+def _swipe_args(config: Dict[str, Any], ctx: SpecContext) -> Optional[tuple]:
+    start = _mobile_target(config, ctx)
+    if not start or start[0] != "point" or config.get("x2") is None or config.get("y2") is None:
+        return None
+    end = _reference_point(config, "x2", "y2")
+    if not end:
+        return None
+    return (*start[1], *end, int(config.get("duration") or 300))
+
+
+def _wait_ms(config: Dict[str, Any]) -> int:
+    return int(config.get("timeout") or config.get("duration") or 1000)
+
+
+def _appium_python_step(step: Step, config: Dict[str, Any], ctx: SpecContext) -> List[str]:
+    target = _mobile_target(config, ctx)
+    find = None
+    if target and target[0] != "point":
+        by = {"id": "AppiumBy.ID", "accessibility_id": "AppiumBy.ACCESSIBILITY_ID"}.get(target[0])
+        if by:
+            find = f"driver.find_element({by}, {_js(target[1])})"
+        else:
+            find = f"driver.find_element(AppiumBy.ANDROID_UIAUTOMATOR, {_js(f'new UiSelector().text({_js(target[1])})')})"
+    kind = step.step_type
+    if kind in ("tap", "tap_element") and find:
+        return [f"{find}.click()"]
+    if kind in ("tap", "tap_element") and target:
+        return [f"driver.tap([{target[1]}])"]
+    if kind in ("input", "type"):
+        value = _js(config.get("value") or "")
+        return [f"{find}.send_keys({value})"] if find else [f"driver.switch_to.active_element.send_keys({value})"]
+    if kind == "swipe" and _swipe_args(config, ctx):
+        return [f"driver.swipe({', '.join(map(str, _swipe_args(config, ctx)))})"]
+    if kind == "back":
+        return ["driver.back()"]
+    if kind == "home":
+        if ctx.platform == "ios":
+            return ['driver.execute_script("mobile: pressButton", {"name": "home"})']
+        return ["driver.press_keycode(3)"]
+    if kind == "launch" and config.get("packageName"):
+        return [f"driver.activate_app({_js(config['packageName'])})"]
+    if kind == "wait":
+        return [f"time.sleep({_wait_ms(config) / 1000:g})"]
+    if kind == "verify" and find:
+        return [f"assert {find}.is_displayed()"]
+    if kind == "screenshot":
+        return [f"driver.save_screenshot({_js(_slug(step.label) + '.png')})"]
+    return [f"# TODO: '{kind}' step has no target to export"]
+
+
+def appium_python_spec(ctx: SpecContext) -> str:
+    """Render a scenario as a pytest Appium script"""
+    options = "XCUITestOptions" if ctx.platform == "ios" else "UiAutomator2Options"
+    module = "ios" if ctx.platform == "ios" else "android"
+    lines = [
+        "import time",
+        "",
+        "from appium import webdriver",
+        f"from appium.options.{module} import {options}",
+        "from appium.webdriver.common.appiumby import AppiumBy",
+        "",
+        'APPIUM_SERVER = "http://127.0.0.1:4723"',
+        "",
+        "",
+        f"def test_{_slug(ctx.scenario.name).replace('-', '_')}():",
+        f"    {_js(ctx.test_case.name + ': ' + ctx.scenario.name)}",
+        f"    options = {options}()",
+        "    # TODO: set options.app or the app package/bundle to test",
+        "    driver = webdriver.Remote(APPIUM_SERVER, options=options)",
+        "    try:",
+        *_render_steps(ctx, _appium_python_step, "        ", "#"),
+        "    finally:",
+        "        driver.quit()",
+    ]
+    return "\n".join(lines) + "\n"
+
+
+def _wdio_selector(target: tuple) -> str:
+    kind, value = target
+    if kind == "accessibility_id":
+        return _js(f"~{value}")
+    if kind == "id":
+        return _js(f"android=new UiSelector().resourceId({_js(value)})")
+    return _js(f"android=new UiSelector().text({_js(value)})")
+
+
+def _wdio_step(step: Step, config: Dict[str, Any], ctx: SpecContext) -> List[str]:
+    target = _mobile_target(config, ctx)
+    el = f"$({_wdio_selector(target)})" if target and target[0] != "point" else None
+    kind = step.step_type
+    if kind in ("tap", "tap_element") and el:
+        return [f"await {el}.click();"]
+    if kind in ("tap", "tap_element") and target:
+        x, y = target[1]
+        return [f"await driver.action('pointer').move({{ x: {x}, y: {y} }}).down().up().perform();"]
+    if kind in ("input", "type"):
+        value = _js(config.get("value") or "")
+        return [f"await {el}.setValue({value});"] if el else [f"await driver.keys({value});"]
+    if kind == "swipe" and _swipe_args(config, ctx):
+        x, y, x2, y2, duration = _swipe_args(config, ctx)
+        return [
+            f"await driver.action('pointer').move({{ x: {x}, y: {y} }}).down()"
+            f".move({{ duration: {duration}, x: {x2}, y: {y2} }}).up().perform();"
+        ]
+    if kind == "back":
+        return ["await driver.back();"]
+    if kind == "home":
+        if ctx.platform == "ios":
+            return ["await driver.execute('mobile: pressButton', { name: 'home' });"]
+        return ["await driver.pressKeyCode(3);"]
+    if kind == "launch" and config.get("packageName"):
+        return [f"await driver.activateApp({_js(config['packageName'])});"]
+    if kind == "wait":
+        return [f"await driver.pause({_wait_ms(config)});"]
+    if kind == "verify" and el:
+        return [f"await expect({el}).toBeDisplayed();"]
+    if kind == "screenshot":
+        return [f"await driver.saveScreenshot({_js('./' + _slug(step.label) + '.png')});"]
+    return [f"// TODO: '{kind}' step has no target to export"]
+
+
+def appium_wdio_spec(ctx: SpecContext) -> str:
+    """Render a scenario as a WebdriverIO Appium spec"""
+    lines = [
+        f"describe({_js(ctx.test_case.name)}, () => {{",
+        f"  it({_js(ctx.scenario.name)}, async () => {{",
+        *_render_steps(ctx, _wdio_step, "    "),
+        "  });",
+        "});",
+    ]
+    return "\n".join(lines) + "\n"
+
+
+def _espresso_step(step: Step, config: Dict[str, Any], ctx: SpecContext) -> List[str]:
+    target = _mobile_target(config, ctx)
+    view = None
+    if target and target[0] == "id":
+        view = f"onView(withResourceName({_kotlin(target[1].split(':id/')[-1])}))"
+    elif target and target[0] == "accessibility_id":
+        view = f"onView(withContentDescription({_kotlin(target[1])}))"
+    elif target and target[0] != "point":
+        view = f"onView(withText({_kotlin(target[1])}))"
+    kind = step.step_type
+    if kind in ("tap", "tap_element") and view:
+        return [f"{view}.perform(click())"]
+    if kind in ("tap", "tap_element") and target:
+        return [f"device.click{target[1]}"]
+    if kind in ("input", "type"):
+        # Without a target, type into whichever view has focus
+        view = view or "onView(hasFocus())"
+        return [f"{view}.perform(replaceText({_kotlin(config.get('value') or '')}), closeSoftKeyboard())"]
+    if kind == "swipe" and _swipe_args(config, ctx):
+        x, y, x2, y2, duration = _swipe_args(config, ctx)
+        # UiDevice swipes move in steps of about 5ms
+        return [f"device.swipe({x}, {y}, {x2}, {y2}, {max(1, duration // 5)})"]
+    if kind == "back":
+        return ["pressBack()"]
+    if kind == "home":
+        return ["device.pressHome()"]
+    if kind == "launch" and config.get("packageName"):
+        return [f"// TODO: launch {config['packageName']} with an ActivityScenarioRule"]
+    if kind == "wait":
+        return [f"Thread.sleep({_wait_ms(config)})"]
+    if kind == "verify" and view:
+        return [f"{view}.check(matches(isDisplayed()))"]
+    if kind == "screenshot":
+        return [f"device.takeScreenshot(File(context.filesDir, {_kotlin(_slug(step.label) + '.png')}))"]
+    return [f"// TODO: '{kind}' step has no target to export"]
+
+
+def espresso_spec(ctx: SpecContext) -> str:
+    """Render a scenario as a skeleton Espresso test in Kotlin"""
+    lines = [
+        "package com.example.tests // TODO: use your test package",
+        "",
+        "import androidx.test.espresso.Espresso.onView",
+        "import androidx.test.espresso.Espresso.pressBack",
+        "import androidx.test.espresso.action.ViewActions.*",
+        "import androidx.test.espresso.assertion.ViewAssertions.matches",
+        "import androidx.test.espresso.matcher.ViewMatchers.*",
+        "import androidx.test.ext.junit.runners.AndroidJUnit4",
+        "import androidx.test.platform.app.InstrumentationRegistry",
+        "import androidx.test.uiautomator.UiDevice",
+        "import java.io.File",
+        "import org.junit.Test",
+        "import org.junit.runner.RunWith",
+        "",
+        f"// {ctx.test_case.name}: {ctx.scenario.name}",
+        "@RunWith(AndroidJUnit4::class)",
+        f"class {_camel(ctx.scenario.name)}Test {{",
+        "    private val instrumentation = InstrumentationRegistry.getInstrumentation()",
+        "    private val device = UiDevice.getInstance(instrumentation)",
+        "    private val context = instrumentation.targetContext",
+        "",
+        "    @Test",
+        f"    fun {_camel(ctx.scenario.name)[:1].lower() + _camel(ctx.scenario.name)[1:]}() {{",
+        *_render_steps(ctx, _espresso_step, "        "),
+        "    }",
+        "}",
+    ]
+    return "\n".join(lines) + "\n"
+
+
+def _xcuitest_step(step: Step, config: Dict[str, Any], ctx: SpecContext) -> List[str]:
+    target = _mobile_target(config, ctx)
+    el = f"app.descendants(matching: .any)[{_js(target[1])}]" if target and target[0] != "point" else None
+    kind = step.step_type
+    if kind in ("tap", "tap_element") and el:
+        return [f"{el}.tap()"]
+    if kind in ("tap", "tap_element") and target:
+        x, y = target[1]
+        return [f"origin.withOffset(CGVector(dx: {x}, dy: {y})).tap()"]
+    if kind in ("input", "type"):
+        value = _js(config.get("value") or "")
+        return [f"{el}.tap()", f"{el}.typeText({value})"] if el else [f"app.typeText({value})"]
+    if kind == "swipe" and _swipe_args(config, ctx):
+        x, y, x2, y2, duration = _swipe_args(config, ctx)
+        return [
+            f"origin.withOffset(CGVector(dx: {x}, dy: {y})).press(forDuration: {duration / 1000:g}, "
+            f"thenDragTo: origin.withOffset(CGVector(dx: {x2}, dy: {y2})))"
+        ]
+    if kind == "back":
+        return ["// iOS has no back button; tap the navigation bar's back button instead"]
+    if kind == "home":
+        return ["XCUIDevice.shared.press(.home)"]
+    if kind == "launch" and config.get("packageName"):
+        return [f"XCUIApplication(bundleIdentifier: {_js(config['packageName'])}).launch()"]
+    if kind == "wait":
+        return [f"Thread.sleep(forTimeInterval: {_wait_ms(config) / 1000:g})"]
+    if kind == "verify" and el:
+        return [f"XCTAssertTrue({el}.waitForExistence(timeout: {MOBILE_VERIFY_TIMEOUT}))"]
+    if kind == "screenshot":
+        return ["add(XCTAttachment(screenshot: app.screenshot()))"]
+    return [f"// TODO: '{kind}' step has no target to export"]
+
+
+def xcuitest_spec(ctx: SpecContext) -> str:
+    """Render a scenario as a skeleton XCUITest case in Swift"""
+    lines = [
+        "import XCTest",
+        "",
+        f"// {ctx.test_case.name}: {ctx.scenario.name}",
+        f"final class {_camel(ctx.scenario.name)}Tests: XCTestCase {{",
+        f"    func test{_camel(ctx.scenario.name)}() throws {{",
+        "        let app = XCUIApplication()",
+        "        app.launch()",
+        "        let origin = app.coordinate(withNormalizedOffset: CGVector(dx: 0, dy: 0))",
+        "",
+        *_render_steps(ctx, _xcuitest_step, "        "),
+        "    }",
+        "}",
+    ]
+    return "\n".join(lines) + "\n"
+
+
+# Export targets: (renderer, filename for a scenario name, language, fixed platform)
+TARGETS: Dict[str, tuple] = {
+    "playwright": (playwright_spec, lambda name: _slug(name) + ".spec.ts", "typescript", "web"),
+    "cypress": (cypress_spec, lambda name: _slug(name) + ".cy.js", "javascript", "web"),
+    "appium-python": (
+        appium_python_spec, lambda name: f"test_{_slug(name).replace('-', '_')}.py", "python", None
+    ),
+    "appium-webdriverio": (appium_wdio_spec, lambda name: _slug(name) + ".e2e.js", "javascript", None),
+    "espresso": (espresso_spec, lambda name: f"{_camel(name)}Test.kt", "kotlin", "android"),
+    "xcuitest": (xcuitest_spec, lambda name: f"{_camel(name)}Tests.swift", "swift", "ios"),
+}
+
+
+async def generate_spec(
+    db: AsyncSession, scenario: Scenario, target: str, platform: str = "android"
+) -> GeneratedSpec:
+    """
+    Generate a test file for a scenario from its steps and the project's element repository
+
+    Appium targets use the given mobile platform; other targets imply their own.
+    """
+    if target not in TARGETS:
+        raise CodegenError(f"Target must be one of: {', '.join(TARGETS)}")
+    render, filename, language, fixed_platform = TARGETS[target]
+    if not fixed_platform and platform not in ("android", "ios"):
+        raise CodegenError("Platform must be android or ios")
+
+    test_case = await db.get(TestCase, scenario.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+    result = await db.execute(
+        select(Step).where(Step.scenario_id == scenario.id).order_by(Step.step_order)
+    )
+    ctx = SpecContext(
+        test_case=test_case,
+        scenario=scenario,
+        steps=list(result.scalars().all()),
+        elements=await load_elements(db, project.id),
+        base_url=scenario.target_url or project.app_url or "",
+        platform=fixed_platform or platform,
+        browser=project.default_browser,
+        headless=project.browser_headless,
+    )
+
+    return GeneratedSpec(
+        filename=filename(scenario.name),
+        target=target,
+        language=language,
+        content=render(ctx),
+    )
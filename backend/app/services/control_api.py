@@ -0,0 +1,70 @@
+"""
+Control API - Opt-in, token-protected access for external tools and IDE plugins
+
+The token is kept in the data directory, readable only by the user, and is
+handed out over HTTP only to the Electron main process, which proves itself
+with the secret it launched the backend with; the app shows it through IPC.
+
+The token decides who may use the /control routes, but it is not a security
+boundary against other processes on the machine: the app's own /api routes,
+which the control routes mirror, take no token because the app's UI calls
+them. The backend only listens on 127.0.0.1, so anything that can reach it
+is already running as a local user.
+"""
+import hmac
+import secrets
+from pathlib import Path
+from typing import Optional
+
+from fastapi import Depends, Header, HTTPException
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import get_db
+from .app_settings import get_setting
+
+
+def _token_path() -> Path:
+    return settings.get_database_path().parent / "control_token"
+
+
+def get_control_token() -> str:
+    """Read the control API token, creating one on first use"""
+    path = _token_path()
+    if path.exists():
+        token = path.read_text(encoding="utf-8").strip()
+        if token:
+            return token
+    return rotate_control_token()
+
+
+def rotate_control_token() -> str:
+    """Replace the control API token, invalidating the old one"""
+    token = secrets.token_urlsafe(32)
+    path = _token_path()
+    path.write_text(token, encoding="utf-8")
+    try:
+        path.chmod(0o600)
+    except OSError:
+        pass  # Not supported on every platform
+    return token
+
+
+def require_main_process(x_main_process_secret: Optional[str] = Header(None)) -> None:
+    """Reject calls that don't come from the Electron main process that launched the backend"""
+    expected = settings.main_process_secret
+    if not expected or not hmac.compare_digest(x_main_process_secret or "", expected):
+        raise HTTPException(status_code=403, detail="Only the desktop app can read or rotate this token")
+
+
+async def require_control_token(
+    authorization: Optional[str] = Header(None), db: AsyncSession = Depends(get_db)
+) -> None:
+    """Reject control API calls while it's disabled or without the bearer token"""
+    if not await get_setting(db, "control_api.enabled"):
+        raise HTTPException(status_code=403, detail="Control API is disabled")
+    scheme, _, token = (authorization or "").partition(" ")
+    if scheme.lower() != "bearer" or not hmac.compare_digest(token.strip(), get_control_token()):
+        raise HTTPException(
+            status_code=401, detail="Invalid control API token", headers={"WWW-Authenticate": "Bearer"}
+        )
@@ -0,0 +1,72 @@
+"""
+Coordinates - Converts step coordinates between devices with different screen sizes
+"""
+import base64
+import struct
+from typing import Any, Dict, Optional, Tuple
+
+# coordinate_mode values: pixels on a reference screen, or 0-1 fractions of the screen
+ABSOLUTE = "absolute"
+NORMALIZED = "normalized"
+COORDINATE_MODES = (ABSOLUTE, NORMALIZED)
+
+# Coordinate pairs a step config may hold
+POINT_KEYS = (("x", "y"), ("x2", "y2"))
+
+# Config overrides for coordinates that are already in the current device's pixels
+DEVICE_PIXELS = {"coordinate_mode": ABSOLUTE, "reference_width": None, "reference_height": None}
+
+
+def png_size(image_base64: str) -> Optional[Tuple[int, int]]:
+    """Read the width and height from a base64 PNG's header"""
+    try:
+        header = base64.b64decode(image_base64[:44])
+    except ValueError:
+        return None
+    if len(header) < 24 or header[:8] != b"\x89PNG\r\n\x1a\n":
+        return None
+    return struct.unpack(">II", header[16:24])
+
+
+def to_device_point(
+    config: Dict[str, Any], x_key: str, y_key: str, screen: Optional[Tuple[int, int]]
+) -> Tuple[int, int]:
+    """
+    Convert a stored point to pixels on the target screen
+
+    Normalized points are fractions of the screen. Absolute points recorded on a
+    screen of a different size are scaled from their reference resolution; without
+    a reference or a known screen size they're used as is.
+    """
+    x, y = float(config[x_key]), float(config[y_key])
+    if config.get("coordinate_mode") == NORMALIZED:
+        if not screen:
+            raise ValueError("Screen size is needed for normalized coordinates")
+        return round(x * screen[0]), round(y * screen[1])
+    reference = (config.get("reference_width"), config.get("reference_height"))
+    if screen and all(reference) and tuple(reference) != tuple(screen):
+        return round(x * screen[0] / reference[0]), round(y * screen[1] / reference[1])
+    return round(x), round(y)
+
+
+def normalize_config(config: Dict[str, Any], width: int, height: int) -> Optional[Dict[str, Any]]:
+    """
+    Convert a config's absolute points to normalized fractions
+
+    Points with a reference resolution use it; others are assumed to have been
+    recorded at width x height. Returns None when there's nothing to convert.
+    """
+    if config.get("coordinate_mode") == NORMALIZED:
+        return None
+    if not any(config.get(xk) is not None and config.get(yk) is not None for xk, yk in POINT_KEYS):
+        return None
+    ref_width = config.get("reference_width") or width
+    ref_height = config.get("reference_height") or height
+    normalized = {
+        **config, "coordinate_mode": NORMALIZED, "reference_width": ref_width, "reference_height": ref_height
+    }
+    for x_key, y_key in POINT_KEYS:
+        if config.get(x_key) is not None and config.get(y_key) is not None:
+            normalized[x_key] = round(float(config[x_key]) / ref_width, 4)
+            normalized[y_key] = round(float(config[y_key]) / ref_height, 4)
+    return normalized
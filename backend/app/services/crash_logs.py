@@ -0,0 +1,239 @@
+"""
+Crash Logs - Finds the crash reports a device wrote and attaches them to runs
+
+Android keeps app crashes, ANRs and native tombstones in its dropbox, which
+`dumpsys dropbox --print` reads without root; /data/tombstones is read as well
+on devices that allow it. iOS simulators write .ips reports to the host's
+DiagnosticReports folder, with the simulator's UDID in the crashed app's path.
+"""
+import asyncio
+import json
+import re
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import List, Optional
+
+from fastapi import HTTPException
+from pydantic import BaseModel
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import RunLogCreate, TestRun
+from ..routers.mobile import is_physical_ios, run_adb_command
+from .app_settings import get_setting
+from .attachments import AttachmentError, store_attachment
+from .run_logs import add_run_logs
+
+IOS_CRASH_REPORTS = Path.home() / "Library" / "Logs" / "DiagnosticReports"
+
+# Dropbox tags with app and native crashes; `dumpsys dropbox` matches one tag per call
+DROPBOX_TAGS = {
+    "data_app_crash": "crash",
+    "system_app_crash": "crash",
+    "data_app_anr": "anr",
+    "system_app_anr": "anr",
+    "data_app_native_crash": "native_crash",
+    "SYSTEM_TOMBSTONE": "native_crash",
+}
+TOMBSTONE_DIR = "/data/tombstones"
+
+# Reports bigger than this are cut short before they're stored
+MAX_REPORT_CHARS = 512 * 1024
+
+_DROPBOX_SEPARATOR = re.compile(r"^={40}$", re.M)
+_DROPBOX_HEADER = re.compile(r"^(\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}) (\S+) \(")
+_TOMBSTONE_LINE = re.compile(r"(\d{4}-\d{2}-\d{2} \d{2}:\d{2})\s+(tombstone_\d+)$")
+_PROCESS = re.compile(r"^Process: (\S+)|>>> (\S+) <<<", re.M)
+
+
+class DeviceCrash(BaseModel):
+    """A crash report a device wrote"""
+
+    device_id: str
+    platform: str
+    kind: str  # 'crash' | 'anr' | 'native_crash'
+    source: str  # 'dropbox' | 'tombstone' | 'ips'
+    name: str  # Dropbox tag, tombstone file or report file name
+    process: Optional[str] = None
+    timestamp: datetime  # UTC
+    content: str
+
+
+def _process(text: str) -> Optional[str]:
+    match = _PROCESS.search(text)
+    return (match.group(1) or match.group(2)) if match else None
+
+
+def _truncate(text: str) -> str:
+    if len(text) <= MAX_REPORT_CHARS:
+        return text
+    return text[:MAX_REPORT_CHARS] + "\n... (truncated)"
+
+
+# ============================================
+# Android
+# ============================================
+
+
+async def _device_utc_offset(device_id: str) -> timedelta:
+    """What to add to the device's local clock to get UTC, rounded to whole time zone steps"""
+    output = await run_adb_command(["shell", "date", "+%s %Y-%m-%d %H:%M:%S"], device_id)
+    epoch, local = output.strip().split(" ", 1)
+    difference = datetime.utcfromtimestamp(int(epoch)) - datetime.strptime(local, "%Y-%m-%d %H:%M:%S")
+    return timedelta(minutes=15 * round(difference.total_seconds() / 900))
+
+
+def parse_dropbox(output: str, device_id: str, utc_offset: timedelta) -> List[DeviceCrash]:
+    """Entries printed by `dumpsys dropbox --print <tag>`"""
+    crashes = []
+    for block in _DROPBOX_SEPARATOR.split(output):
+        block = block.strip("\n")
+        header = _DROPBOX_HEADER.match(block)
+        if not header or header.group(2) not in DROPBOX_TAGS:
+            continue
+        timestamp = datetime.strptime(header.group(1), "%Y-%m-%d %H:%M:%S") + utc_offset
+        crashes.append(DeviceCrash(
+            device_id=device_id,
+            platform="android",
+            kind=DROPBOX_TAGS[header.group(2)],
+            source="dropbox",
+            name=header.group(2),
+            process=_process(block),
+            timestamp=timestamp,
+            content=_truncate(block),
+        ))
+    return crashes
+
+
+async def _android_tombstones(device_id: str, since: datetime, utc_offset: timedelta) -> List[DeviceCrash]:
+    """Tombstone files newer than since, when the device lets us list them"""
+    try:
+        listing = await run_adb_command(["shell", "ls", "-l", TOMBSTONE_DIR], device_id)
+    except HTTPException:
+        return []  # Needs root on most devices; the dropbox has SYSTEM_TOMBSTONE entries instead
+    crashes = []
+    for line in listing.splitlines():
+        match = _TOMBSTONE_LINE.search(line.strip())
+        if not match:
+            continue
+        # ls only shows minutes, so keep files from the minute the run started
+        timestamp = datetime.strptime(match.group(1), "%Y-%m-%d %H:%M") + utc_offset
+        if timestamp < since.replace(second=0, microsecond=0):
+            continue
+        try:
+            content = await run_adb_command(["shell", "cat", f"{TOMBSTONE_DIR}/{match.group(2)}"], device_id)
+        except HTTPException:
+            continue
+        crashes.append(DeviceCrash(
+            device_id=device_id,
+            platform="android",
+            kind="native_crash",
+            source="tombstone",
+            name=match.group(2),
+            process=_process(content),
+            timestamp=timestamp,
+            content=_truncate(content),
+        ))
+    return crashes
+
+
+async def _android_crashes(device_id: str, since: datetime) -> List[DeviceCrash]:
+    utc_offset = await _device_utc_offset(device_id)
+    crashes: List[DeviceCrash] = []
+    for tag in DROPBOX_TAGS:
+        output = await run_adb_command(["shell", "dumpsys", "dropbox", "--print", tag], device_id)
+        crashes.extend(crash for crash in parse_dropbox(output, device_id, utc_offset) if crash.timestamp >= since)
+    crashes.extend(await _android_tombstones(device_id, since, utc_offset))
+    return crashes
+
+
+# ============================================
+# iOS
+# ============================================
+
+
+def _ips_process(text: str) -> Optional[str]:
+    """The bundle ID or process name from an .ips report's JSON header line"""
+    try:
+        header = json.loads(text.split("\n", 1)[0])
+    except ValueError:
+        return None
+    return header.get("bundleID") or header.get("app_name") or None
+
+
+def _ios_crashes(device_id: str, since: datetime) -> List[DeviceCrash]:
+    if not IOS_CRASH_REPORTS.is_dir():
+        return []
+    crashes = []
+    for path in sorted(IOS_CRASH_REPORTS.glob("*.ips")):
+        try:
+            timestamp = datetime.utcfromtimestamp(path.stat().st_mtime)
+            if timestamp < since:
+                continue
+            text = path.read_text(errors="replace")
+        except OSError:
+            continue
+        if device_id not in text:
+            continue  # Another simulator's or the Mac's own report
+        crashes.append(DeviceCrash(
+            device_id=device_id,
+            platform="ios",
+            kind="crash",
+            source="ips",
+            name=path.name,
+            process=_ips_process(text),
+            timestamp=timestamp,
+            content=_truncate(text),
+        ))
+    return crashes
+
+
+# ============================================
+# Collection
+# ============================================
+
+
+async def list_crashes_since(timestamp: datetime, device_id: str, platform: str = "android") -> List[DeviceCrash]:
+    """Crash reports the device wrote at or after a UTC time, oldest first"""
+    if platform == "ios":
+        if is_physical_ios(device_id):
+            return []  # Physical devices' reports only reach the Mac through Xcode's device sync
+        crashes = await asyncio.to_thread(_ios_crashes, device_id, timestamp)
+    else:
+        crashes = await _android_crashes(device_id, timestamp)
+    return sorted(crashes, key=lambda crash: crash.timestamp)
+
+
+async def collect_run_crashes(db: AsyncSession, test_run: TestRun, device_id: str, platform: str) -> int:
+    """
+    Attach the crash reports written while a run was going, returning how many were found
+
+    A device that can't be read only logs a warning, since the run's own
+    result is already recorded.
+    """
+    if not test_run.started_at or not await get_setting(db, "executor.collect_crash_logs"):
+        return 0
+    try:
+        crashes = await list_crashes_since(test_run.started_at, device_id, platform)
+    except HTTPException as e:
+        await add_run_logs(db, test_run.id, [RunLogCreate(
+            level="warning", source="crashes", message=f"Couldn't read crash logs: {e.detail}"
+        )])
+        return 0
+
+    for crash in crashes:
+        filename = crash.name if crash.source == "ips" else f"{crash.name}-{crash.timestamp:%Y%m%d-%H%M%S}.txt"
+        try:
+            await store_attachment(
+                db, test_run.id, filename, "text/plain", crash.content.encode("utf-8"), kind="crash"
+            )
+        except AttachmentError as e:
+            await add_run_logs(db, test_run.id, [RunLogCreate(
+                level="warning", source="crashes", message=f"Couldn't attach {crash.name}: {e}"
+            )])
+            continue
+        await add_run_logs(db, test_run.id, [RunLogCreate(
+            level="error", source="crashes",
+            message=f"{crash.kind.replace('_', ' ').capitalize()} in {crash.process or 'unknown process'} "
+                    f"at {crash.timestamp:%H:%M:%S} UTC ({crash.name})",
+        )])
+    return len(crashes)
@@ -0,0 +1,104 @@
+"""
+Cron - Parses five-field cron expressions and finds when they next fire
+"""
+from datetime import datetime, timedelta
+from typing import List, Optional, Set
+
+MACROS = {
+    "@yearly": "0 0 1 1 *",
+    "@annually": "0 0 1 1 *",
+    "@monthly": "0 0 1 * *",
+    "@weekly": "0 0 * * 0",
+    "@daily": "0 0 * * *",
+    "@midnight": "0 0 * * *",
+    "@hourly": "0 * * * *",
+}
+
+MONTH_NAMES = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"]
+DAY_NAMES = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"]
+
+# Expressions that can't fire within this many years, such as "0 0 30 2 *", are rejected
+SEARCH_YEARS = 5
+
+
+class CronError(Exception):
+    """Raised for expressions that can't be parsed or never fire"""
+
+
+def _value(token: str, low: int, names: Optional[List[str]]) -> int:
+    if names and token.upper() in names:
+        return names.index(token.upper()) + low
+    if not token.isdigit():
+        raise CronError(f"Invalid value: {token}")
+    return int(token)
+
+
+def _parse_field(field: str, low: int, high: int, names: Optional[List[str]] = None) -> Set[int]:
+    values: Set[int] = set()
+    for part in field.split(","):
+        base, _, step_text = part.partition("/")
+        step = int(step_text) if step_text.isdigit() and int(step_text) > 0 else None
+        if step_text and step is None:
+            raise CronError(f"Invalid step: {part}")
+        if base == "*":
+            start, end = low, high
+        elif "-" in base:
+            first, _, last = base.partition("-")
+            start, end = _value(first, low, names), _value(last, low, names)
+        else:
+            start = _value(base, low, names)
+            end = high if step else start
+        if not low <= start <= end <= high:
+            raise CronError(f"Out of range {low}-{high}: {part}")
+        values.update(range(start, end + 1, step or 1))
+    return values
+
+
+class CronExpression:
+    """
+    A standard five-field expression: minute, hour, day of month, month, day of week
+
+    Fields accept *, lists, ranges, steps and month or day names. Sunday is 0
+    or 7. As in cron, when both day fields are restricted a day matching
+    either one fires.
+    """
+
+    def __init__(self, expression: str):
+        self.expression = expression.strip()
+        fields = MACROS.get(self.expression.lower(), self.expression).split()
+        if len(fields) != 5:
+            raise CronError("Expected five fields: minute hour day-of-month month day-of-week")
+        self.minutes = _parse_field(fields[0], 0, 59)
+        self.hours = _parse_field(fields[1], 0, 23)
+        self.days = _parse_field(fields[2], 1, 31)
+        self.months = _parse_field(fields[3], 1, 12, MONTH_NAMES)
+        weekdays = _parse_field(fields[4], 0, 7, DAY_NAMES)
+        self.weekdays = {day % 7 for day in weekdays}
+        self._any_day = fields[2] == "*"
+        self._any_weekday = fields[4] == "*"
+        self.next_after(datetime(2000, 1, 1))
+
+    def _day_matches(self, moment: datetime) -> bool:
+        day = moment.day in self.days
+        weekday = (moment.isoweekday() % 7) in self.weekdays
+        if self._any_day or self._any_weekday:
+            return day and weekday
+        return day or weekday
+
+    def next_after(self, after: datetime) -> datetime:
+        """The first minute strictly after the given time that the expression fires"""
+        moment = after.replace(second=0, microsecond=0) + timedelta(minutes=1)
+        limit = moment + timedelta(days=366 * SEARCH_YEARS)
+        while moment < limit:
+            if moment.month not in self.months:
+                year, month = (moment.year + 1, 1) if moment.month == 12 else (moment.year, moment.month + 1)
+                moment = moment.replace(year=year, month=month, day=1, hour=0, minute=0)
+            elif not self._day_matches(moment):
+                moment = (moment + timedelta(days=1)).replace(hour=0, minute=0)
+            elif moment.hour not in self.hours:
+                moment = (moment + timedelta(hours=1)).replace(minute=0)
+            elif moment.minute not in self.minutes:
+                moment += timedelta(minutes=1)
+            else:
+                return moment
+        raise CronError(f"Expression never fires: {self.expression}")
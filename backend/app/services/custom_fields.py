@@ -0,0 +1,262 @@
+"""
+Custom Fields - Project-defined metadata on test cases and projects
+
+Values are stored as JSON so numbers, flags and multi-select lists keep
+their type. Anything a user types in, including CSV cells, is coerced to the
+field's type first, so filters can compare the stored JSON directly.
+"""
+import json
+from datetime import date
+from typing import Any, Dict, Iterable, List, Optional
+
+from sqlalchemy import delete, false, or_, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import CustomField, CustomFieldValue, TestCase
+
+ENTITIES = ("test_case", "project")
+FIELD_TYPES = ("text", "number", "boolean", "date", "select", "multi_select")
+CHOICE_TYPES = ("select", "multi_select")
+
+CUSTOM_PREFIX = "custom:"  # How CSV mappings and saved filters name a custom field, as in custom:Component
+
+TRUE_WORDS = {"true", "yes", "y", "1"}
+FALSE_WORDS = {"false", "no", "n", "0"}
+
+
+class CustomFieldError(Exception):
+    """Raised for invalid field definitions and values that don't fit a field"""
+
+
+def check_field(entity: str, field_type: str, allowed_values: List[str]) -> None:
+    if entity not in ENTITIES:
+        raise CustomFieldError(f"Entity must be one of: {', '.join(ENTITIES)}")
+    if field_type not in FIELD_TYPES:
+        raise CustomFieldError(f"Field type must be one of: {', '.join(FIELD_TYPES)}")
+    if field_type in CHOICE_TYPES and not allowed_values:
+        raise CustomFieldError(f"A {field_type} field needs its allowed values")
+    if len(set(allowed_values)) != len(allowed_values):
+        raise CustomFieldError("Allowed values must be unique")
+
+
+def _split(raw: Any) -> List[str]:
+    """Multi-select values from a list or a ';' or ',' separated string"""
+    if isinstance(raw, list):
+        return [str(item).strip() for item in raw if str(item).strip()]
+    separator = ";" if ";" in str(raw) else ","
+    return [item.strip() for item in str(raw).split(separator) if item.strip()]
+
+
+def coerce_value(field: CustomField, raw: Any) -> Any:
+    """A value converted to the field's type; raises CustomFieldError when it doesn't fit"""
+    allowed = json.loads(field.allowed_values or "[]")
+    if field.field_type == "number":
+        try:
+            number = float(raw)
+        except (TypeError, ValueError):
+            raise CustomFieldError(f"{field.name} must be a number")
+        return int(number) if number.is_integer() else number
+    if field.field_type == "boolean":
+        if isinstance(raw, bool):
+            return raw
+        word = str(raw).strip().lower()
+        if word not in TRUE_WORDS | FALSE_WORDS:
+            raise CustomFieldError(f"{field.name} must be true or false")
+        return word in TRUE_WORDS
+    if field.field_type == "date":
+        try:
+            return date.fromisoformat(str(raw).strip()).isoformat()
+        except ValueError:
+            raise CustomFieldError(f"{field.name} must be a YYYY-MM-DD date")
+    if field.field_type == "multi_select":
+        values = _split(raw)
+        unknown = [value for value in values if value not in allowed]
+        if unknown:
+            raise CustomFieldError(f"{field.name} doesn't allow: {', '.join(unknown)}")
+        return values
+    value = str(raw).strip()
+    if field.field_type == "select" and value not in allowed:
+        raise CustomFieldError(f"{field.name} must be one of: {', '.join(allowed)}")
+    return value
+
+
+def _is_empty(raw: Any) -> bool:
+    return raw is None or (isinstance(raw, (str, list)) and not raw)
+
+
+def format_value(value: Any) -> str:
+    """A value as text for CSV cells"""
+    if isinstance(value, bool):
+        return "true" if value else "false"
+    if isinstance(value, list):
+        return "; ".join(value)
+    return str(value)
+
+
+# ============================================
+# Values
+# ============================================
+
+
+async def list_fields(db: AsyncSession, project_id: str, entity: str = "test_case") -> List[CustomField]:
+    result = await db.execute(
+        select(CustomField)
+        .where(CustomField.project_id == project_id, CustomField.entity == entity)
+        .order_by(CustomField.created_at)
+    )
+    return list(result.scalars().all())
+
+
+async def get_values(
+    db: AsyncSession, project_id: str, entity: str, entity_ids: Iterable[str]
+) -> Dict[str, Dict[str, Any]]:
+    """Each entity's values by field name; entities without values map to an empty dict"""
+    values: Dict[str, Dict[str, Any]] = {entity_id: {} for entity_id in entity_ids}
+    if not values:
+        return values
+    result = await db.execute(
+        select(CustomField.name, CustomFieldValue.entity_id, CustomFieldValue.value)
+        .join(CustomField, CustomFieldValue.field_id == CustomField.id)
+        .where(
+            CustomField.project_id == project_id,
+            CustomField.entity == entity,
+            CustomFieldValue.entity_id.in_(list(values)),
+        )
+    )
+    for name, entity_id, value in result.all():
+        values[entity_id][name] = json.loads(value)
+    return values
+
+
+def coerce_values(fields: Dict[str, CustomField], values: Dict[str, Any]) -> Dict[str, Any]:
+    """Values by field name converted to their fields' types, with None for ones being cleared"""
+    unknown = [name for name in values if name not in fields]
+    if unknown:
+        raise CustomFieldError(f"Unknown custom fields: {', '.join(unknown)}")
+    coerced = {}
+    for name, raw in values.items():
+        if _is_empty(raw):
+            if fields[name].required:
+                raise CustomFieldError(f"{name} is required")
+            coerced[name] = None
+        else:
+            coerced[name] = coerce_value(fields[name], raw)
+    return coerced
+
+
+async def set_values(
+    db: AsyncSession, project_id: str, entity: str, entity_id: str, values: Dict[str, Any]
+) -> Dict[str, Any]:
+    """
+    Set some of an entity's values by field name without committing
+
+    Empty values clear a field, except required ones. Nothing is written
+    unless every value fits its field.
+    """
+    fields = {field.name: field for field in await list_fields(db, project_id, entity)}
+    coerced = coerce_values(fields, values)
+    result = await db.execute(
+        select(CustomFieldValue).where(
+            CustomFieldValue.entity_id == entity_id,
+            CustomFieldValue.field_id.in_([fields[name].id for name in coerced]),
+        )
+    )
+    existing = {row.field_id: row for row in result.scalars().all()}
+    for name, value in coerced.items():
+        row = existing.get(fields[name].id)
+        if value is None:
+            if row:
+                await db.delete(row)
+        elif row:
+            row.value = json.dumps(value)
+        else:
+            db.add(CustomFieldValue(field_id=fields[name].id, entity_id=entity_id, value=json.dumps(value)))
+    await db.flush()
+    return (await get_values(db, project_id, entity, [entity_id]))[entity_id]
+
+
+def _add_matching(db: AsyncSession, values: Dict[str, Any], fields: Dict[str, CustomField], entity_id: str) -> None:
+    """Add values for the fields named like theirs, dropping ones without a field or that don't fit it"""
+    for name, value in values.items():
+        if name not in fields:
+            continue
+        try:
+            value = coerce_value(fields[name], value)
+        except CustomFieldError:
+            continue
+        db.add(CustomFieldValue(field_id=fields[name].id, entity_id=entity_id, value=json.dumps(value)))
+
+
+async def copy_values(db: AsyncSession, source_project_id: str, source_id: str, project_id: str, target_id: str):
+    """Copy a test case's values onto another, matching the target project's fields by name, without committing"""
+    values = (await get_values(db, source_project_id, "test_case", [source_id]))[source_id]
+    fields = {field.name: field for field in await list_fields(db, project_id)}
+    _add_matching(db, values, fields, target_id)
+
+
+async def move_values(db: AsyncSession, test_cases: List[TestCase], project_id: str) -> None:
+    """Rebind test cases' values to a new project's fields of the same name, without committing"""
+    fields = {field.name: field for field in await list_fields(db, project_id)}
+    for test_case in test_cases:
+        if test_case.project_id == project_id:
+            continue
+        values = (await get_values(db, test_case.project_id, "test_case", [test_case.id]))[test_case.id]
+        await delete_values(db, [test_case.id])
+        _add_matching(db, values, fields, test_case.id)
+
+
+async def delete_values(db: AsyncSession, entity_ids: List[str]) -> None:
+    await db.execute(delete(CustomFieldValue).where(CustomFieldValue.entity_id.in_(entity_ids)))
+
+
+# ============================================
+# Filters
+# ============================================
+
+
+def parse_filters(filters: List[str]) -> Dict[str, str]:
+    """'Name=value' query parameters as a dict"""
+    parsed = {}
+    for item in filters:
+        name, separator, value = item.partition("=")
+        if not separator or not name.strip():
+            raise CustomFieldError(f"Custom field filters look like name=value, not {item}")
+        parsed[name.strip()] = value.strip()
+    return parsed
+
+
+async def filter_clauses(
+    db: AsyncSession, entity_column, entity: str, filters: Dict[str, str], project_id: Optional[str] = None
+) -> List[Any]:
+    """
+    Conditions matching entities whose fields hold the filtered values
+
+    Without a project, a name matches that field in every project. A
+    multi-select filter matches entities with any of its values among their choices.
+    """
+    clauses = []
+    for name, raw in filters.items():
+        query = select(CustomField).where(CustomField.entity == entity, CustomField.name == name)
+        if project_id:
+            query = query.where(CustomField.project_id == project_id)
+        fields = (await db.execute(query)).scalars().all()
+        if not fields:
+            raise CustomFieldError(f"Unknown custom field: {name}")
+        matches = []
+        for field in fields:
+            try:
+                value = coerce_value(field, raw)
+            except CustomFieldError:
+                continue
+            if field.field_type == "multi_select":
+                match = or_(false(), *(CustomFieldValue.value.like(f"%{json.dumps(item)}%") for item in value))
+            else:
+                match = CustomFieldValue.value == json.dumps(value)
+            matches.append((CustomFieldValue.field_id == field.id) & match)
+        clauses.append(entity_column.in_(select(CustomFieldValue.entity_id).where(or_(false(), *matches))))
+    return clauses
+
+
+def search_clause(entity_column, text: str):
+    """A condition matching entities with the text in any custom field value"""
+    return entity_column.in_(select(CustomFieldValue.entity_id).where(CustomFieldValue.value.ilike(f"%{text}%")))
@@ -0,0 +1,132 @@
+"""
+Database Encryption Service - Migrates the local database to and from SQLCipher encryption
+"""
+import asyncio
+import os
+from pathlib import Path
+from typing import Optional
+
+from pydantic import BaseModel
+
+from ..db import engine, init_db
+from ..db.database import IS_SQLITE, db_path, db_state
+from ..db.encryption import (
+    EncryptionError,
+    check_integrity,
+    connect,
+    encryption_available,
+    export_database,
+    has_key,
+    is_encrypted,
+    require_encryption,
+)
+from .backup import list_snapshots
+from .events import event_bus
+from .run_queue import run_queue
+
+
+class EncryptionStatus(BaseModel):
+    encrypted: bool
+    available: bool  # SQLCipher and keyring are installed
+    key_in_keychain: bool
+    # Snapshots taken before encrypting stay in plain text until rotated out or deleted
+    plain_snapshots: int
+
+
+def encryption_status() -> EncryptionStatus:
+    return EncryptionStatus(
+        encrypted=IS_SQLITE and is_encrypted(db_path),
+        available=IS_SQLITE and encryption_available(),
+        key_in_keychain=has_key(),
+        plain_snapshots=sum(1 for snapshot in list_snapshots() if not is_encrypted(snapshot)),
+    )
+
+
+def _checkpoint(path: Path) -> None:
+    """Move everything in the WAL into the database file, failing if another connection is in the way"""
+    connection = connect(path)
+    try:
+        busy, _, _ = connection.execute("PRAGMA wal_checkpoint(TRUNCATE)").fetchone()
+    finally:
+        connection.close()
+    if busy:
+        raise EncryptionError("The database is in use; try again once other programs using it have closed")
+
+
+def _rewrite(path: Path, encrypt: bool) -> None:
+    """Export the database to a new file and swap it in once it passes an integrity check"""
+    _checkpoint(path)
+    migrated = path.with_name(f"{path.name}.migrating")
+    migrated.unlink(missing_ok=True)
+    # Held until the swap so no other connection can write what the export would miss; readers still can
+    lock = connect(path)
+    try:
+        lock.execute("BEGIN IMMEDIATE")
+        export_database(path, migrated, encrypt)
+        problem = check_integrity(migrated)
+        if problem:
+            raise EncryptionError(f"The migrated database failed its integrity check: {problem}")
+        lock.rollback()
+        lock.close()
+        wal = path.with_name(path.name + "-wal")
+        if wal.exists() and wal.stat().st_size:
+            raise EncryptionError("The database was written to while migrating; try again")
+        for suffix in ("-wal", "-shm"):
+            path.with_name(path.name + suffix).unlink(missing_ok=True)
+        os.replace(migrated, path)
+    finally:
+        lock.close()
+        migrated.unlink(missing_ok=True)
+
+
+def _in_use() -> Optional[str]:
+    """Why the database can't be swapped out right now, or None"""
+    runs = run_queue.list()
+    if runs:
+        return f"Test runs are queued or running ({len(runs)}); wait for them or cancel them first"
+    if engine.pool.checkedout():
+        return "The database is in use by another request; try again in a moment"
+    if db_state.pending_writes:
+        return "Writes made while the database was busy are still being saved; try again in a moment"
+    return None
+
+
+async def _migrate(encrypt: bool) -> EncryptionStatus:
+    if not IS_SQLITE:
+        raise EncryptionError("Encryption is only for the SQLite database; encrypt a server database on the server")
+    if is_encrypted(db_path) == encrypt:
+        raise EncryptionError("The database is already encrypted" if encrypt else "The database isn't encrypted")
+    require_encryption()
+
+    # Requests get 503s from here, so no new sessions open while the file is swapped
+    was_available, previous_error = db_state.available, db_state.error
+    db_state.available = False
+    db_state.error = "Migrating database encryption"
+    problem = _in_use()
+    if problem:
+        db_state.available, db_state.error = was_available, previous_error
+        raise EncryptionError(problem)
+    try:
+        await engine.dispose()
+        await asyncio.to_thread(_rewrite, db_path, encrypt)
+    finally:
+        await engine.dispose()
+        await init_db()
+
+    status = encryption_status()
+    event_bus.publish("db:encryption_changed", status.model_dump())
+    return status
+
+
+async def migrate_to_encrypted() -> EncryptionStatus:
+    """Encrypt the database with a key generated into the OS keychain"""
+    return await _migrate(encrypt=True)
+
+
+async def migrate_to_plain() -> EncryptionStatus:
+    """
+    Decrypt the database
+
+    The key stays in the keychain so encrypted backups can still be restored.
+    """
+    return await _migrate(encrypt=False)
@@ -0,0 +1,93 @@
+"""
+DB Changes - Publishes a db:changed event for every committed write
+
+Session hooks collect the rows each flush inserts, updates and deletes, and
+publish them once the transaction commits, so clients never hear about
+writes that were rolled back. Events carry the table as their entity, the
+row's id and the operation, with the row's new values as data for inserts
+and updates. Bulk statements, such as updating the status of many test
+cases at once, publish one event with no id; clients refetch the entity.
+"""
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+
+from sqlalchemy import event, inspect
+from sqlalchemy.orm import ORMExecuteState, Session
+
+from .events import event_bus
+from .secret_masking import mask_secrets
+
+DB_CHANGED_EVENT = "db:changed"
+
+# Written too often to be worth pushing, or already streamed as events of their own
+QUIET_TABLES = {"ai_cache", "ai_usage", "run_logs", "stat_counters"}
+
+CHANGES_KEY = "db_changes"
+
+
+def _row_data(obj: Any) -> Dict[str, Any]:
+    """A row's loaded column values; expired ones are left out rather than loaded mid-flush"""
+    state = inspect(obj)
+    data = {}
+    for attribute in state.mapper.column_attrs:
+        if attribute.key in state.dict:
+            value = state.dict[attribute.key]
+            data[attribute.key] = value.isoformat() if isinstance(value, datetime) else mask_secrets(value)
+    return data
+
+
+def _row_id(obj: Any) -> str:
+    return ":".join(str(value) for value in inspect(obj).mapper.primary_key_from_instance(obj))
+
+
+def _change(entity: str, row_id: Optional[str], op: str, data: Optional[Dict[str, Any]] = None) -> Dict[str, Any]:
+    return {"entity": entity, "id": row_id, "op": op, "data": data}
+
+
+@event.listens_for(Session, "after_flush")
+def _collect_flush(session: Session, flush_context: Any) -> None:
+    changes: List[Dict[str, Any]] = session.info.setdefault(CHANGES_KEY, [])
+    for op, objects in (("insert", session.new), ("update", session.dirty), ("delete", session.deleted)):
+        for obj in objects:
+            entity = getattr(obj, "__tablename__", None)
+            if not entity or entity in QUIET_TABLES:
+                continue
+            if op == "update" and not session.is_modified(obj, include_collections=False):
+                continue
+            changes.append(_change(entity, _row_id(obj), op, None if op == "delete" else _row_data(obj)))
+
+
+@event.listens_for(Session, "do_orm_execute")
+def _collect_bulk(state: ORMExecuteState) -> None:
+    if not (state.is_insert or state.is_update or state.is_delete):
+        return
+    entity = getattr(getattr(state.statement, "table", None), "name", None)
+    if entity and entity not in QUIET_TABLES:
+        op = "insert" if state.is_insert else "update" if state.is_update else "delete"
+        state.session.info.setdefault(CHANGES_KEY, []).append(_change(entity, None, op))
+
+
+def _merged(changes: List[Dict[str, Any]]) -> List[Dict[str, Any]]:
+    """One change per row: an insert later updated stays an insert, and one later deleted is dropped"""
+    merged: Dict[Any, Dict[str, Any]] = {}
+    for index, change in enumerate(changes):
+        key = (change["entity"], change["id"]) if change["id"] else index
+        earlier = merged.get(key)
+        if earlier and earlier["op"] == "insert":
+            if change["op"] == "delete":
+                del merged[key]
+                continue
+            change = {**change, "op": "insert"}
+        merged[key] = change
+    return list(merged.values())
+
+
+@event.listens_for(Session, "after_commit")
+def _publish(session: Session) -> None:
+    for change in _merged(session.info.pop(CHANGES_KEY, [])):
+        event_bus.publish(DB_CHANGED_EVENT, change)
+
+
+@event.listens_for(Session, "after_rollback")
+def _discard(session: Session) -> None:
+    session.info.pop(CHANGES_KEY, None)
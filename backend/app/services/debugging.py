@@ -0,0 +1,136 @@
+"""
+Debugging - Pauses device runs at step breakpoints for interactive inspection
+
+A debug run pauses before every step with a breakpoint and publishes a
+"debug:paused" event. While it's paused the device can be inspected through
+the run's executor, with a live screenshot and UI dump, and steps can be
+tried out with edited configs. Resuming runs on to the next breakpoint,
+stepping over runs the paused step and pauses before the one after it, and
+either can replace the paused step's config for this run only. Aborting
+cancels the run. Web runs execute a whole spec at once, so only device runs
+can be debugged.
+"""
+import asyncio
+from dataclasses import dataclass, field
+from typing import Any, Dict, Optional, Tuple
+
+from pydantic import BaseModel
+
+from ..models import Step
+from .events import event_bus
+from .run_queue import run_queue
+
+
+class DebugError(Exception):
+    """Raised for commands a debug run can't take in its current state"""
+
+
+class DebugState(BaseModel):
+    """Schema for where a debug run is paused"""
+    test_run_id: str
+    paused: bool
+    step_id: Optional[str] = None
+    step_index: Optional[int] = None
+    step_type: Optional[str] = None
+    config: Optional[Dict[str, Any]] = None  # The paused step's config, with its element applied
+
+
+@dataclass
+class DebugSession:
+    test_run_id: str
+    executor: Any  # The ScenarioExecutor running the debug run
+    stepping: bool = False  # Pause before the next step whether or not it has a breakpoint
+    step_id: Optional[str] = None
+    step_index: Optional[int] = None
+    step_type: Optional[str] = None
+    config: Optional[Dict[str, Any]] = None
+    resumed: asyncio.Event = field(default_factory=asyncio.Event)
+
+    @property
+    def paused(self) -> bool:
+        return self.step_id is not None
+
+    def state(self) -> DebugState:
+        return DebugState(
+            test_run_id=self.test_run_id,
+            paused=self.paused,
+            step_id=self.step_id,
+            step_index=self.step_index,
+            step_type=self.step_type,
+            config=self.config,
+        )
+
+
+class DebugSessions:
+    """The debug runs in progress, keyed by test run id"""
+
+    def __init__(self):
+        self._sessions: Dict[str, DebugSession] = {}
+
+    def open(self, test_run_id: str, executor: Any) -> None:
+        self._sessions[test_run_id] = DebugSession(test_run_id, executor)
+
+    def close(self, test_run_id: str) -> None:
+        self._sessions.pop(test_run_id, None)
+
+    def get(self, test_run_id: str) -> DebugSession:
+        session = self._sessions.get(test_run_id)
+        if not session:
+            raise KeyError(test_run_id)
+        return session
+
+    def paused(self, test_run_id: str) -> DebugSession:
+        """The session of a debug run that's paused at a step"""
+        session = self.get(test_run_id)
+        if not session.paused:
+            raise DebugError("The debug run isn't paused")
+        return session
+
+    async def before_step(self, test_run_id: str, step: Step, index: int, config: Dict[str, Any]) -> Dict[str, Any]:
+        """Wait at a breakpoint, or after stepping over, until the run is resumed; returns the config to run"""
+        session = self._sessions.get(test_run_id)
+        if not session or not (step.breakpoint or session.stepping):
+            return config
+        session.step_id, session.step_index, session.step_type, session.config = step.id, index, step.step_type, config
+        session.resumed.clear()
+        event_bus.publish("debug:paused", session.state().model_dump())
+        try:
+            await session.resumed.wait()
+            return session.config
+        finally:
+            session.step_id = session.step_index = session.step_type = session.config = None
+
+    def _continue(self, test_run_id: str, stepping: bool, config: Optional[Dict[str, Any]]) -> None:
+        session = self.paused(test_run_id)
+        event_bus.publish("debug:resumed", {"test_run_id": test_run_id, "step_id": session.step_id})
+        if config is not None:
+            session.config = config
+        session.stepping = stepping
+        # No longer paused, so a second command before the run wakes up is refused
+        session.step_id = None
+        session.resumed.set()
+
+    def resume(self, test_run_id: str, config: Optional[Dict[str, Any]] = None) -> None:
+        """Run the paused step and carry on to the next breakpoint"""
+        self._continue(test_run_id, False, config)
+
+    def step_over(self, test_run_id: str, config: Optional[Dict[str, Any]] = None) -> None:
+        """Run the paused step and pause again before the next one"""
+        self._continue(test_run_id, True, config)
+
+    async def inspect(self, test_run_id: str) -> Tuple[str, str]:
+        """The paused device's screenshot, base64 PNG, and UI dump XML"""
+        return await self.paused(test_run_id).executor.inspect()
+
+    async def try_step(self, test_run_id: str, step_type: str, config: Dict[str, Any]) -> Any:
+        """Run a step on the paused device without recording a result, returning its StepOutcome"""
+        return await self.paused(test_run_id).executor.execute_step(step_type, config)
+
+    async def abort(self, test_run_id: str) -> None:
+        """Cancel a debug run, paused or not"""
+        self.get(test_run_id)
+        await run_queue.cancel(test_run_id)
+
+
+# Singleton instance
+debug_sessions = DebugSessions()
@@ -0,0 +1,90 @@
+"""
+Scenario Dependencies - Dependency graph validation and execution ordering
+"""
+from collections import defaultdict
+from typing import Dict, List, Set
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Scenario, ScenarioDependency
+
+
+class DependencyError(Exception):
+    """Raised when dependencies would form a cycle or reference unknown scenarios"""
+
+
+async def load_prerequisites(db: AsyncSession) -> Dict[str, List[str]]:
+    """Map each scenario ID to the IDs of the scenarios it depends on"""
+    result = await db.execute(select(ScenarioDependency))
+    graph: Dict[str, List[str]] = defaultdict(list)
+    for dependency in result.scalars().all():
+        graph[dependency.scenario_id].append(dependency.depends_on_id)
+    return graph
+
+
+def _reaches(graph: Dict[str, List[str]], start: str, target: str) -> bool:
+    stack, seen = [start], set()
+    while stack:
+        node = stack.pop()
+        if node == target:
+            return True
+        if node not in seen:
+            seen.add(node)
+            stack.extend(graph.get(node, []))
+    return False
+
+
+async def validate_dependency(db: AsyncSession, scenario_id: str, depends_on_id: str) -> None:
+    """Raise if making scenario_id depend on depends_on_id would create a cycle"""
+    if scenario_id == depends_on_id:
+        raise DependencyError("A scenario can't depend on itself")
+    graph = await load_prerequisites(db)
+    if _reaches(graph, depends_on_id, scenario_id):
+        raise DependencyError("Dependency would create a cycle")
+
+
+async def execution_order(db: AsyncSession, scenario_ids: List[str]) -> List[str]:
+    """
+    Order scenarios so every prerequisite runs before its dependents
+
+    Prerequisites that weren't requested are included. Scenarios without an
+    ordering constraint keep their test case order.
+    """
+    graph = await load_prerequisites(db)
+
+    included: Set[str] = set()
+    stack = list(scenario_ids)
+    while stack:
+        scenario_id = stack.pop()
+        if scenario_id not in included:
+            included.add(scenario_id)
+            stack.extend(graph.get(scenario_id, []))
+
+    result = await db.execute(
+        select(Scenario.id).where(Scenario.id.in_(included)).order_by(Scenario.position, Scenario.created_at)
+    )
+    natural_order = result.scalars().all()
+    missing = included - set(natural_order)
+    if missing:
+        raise DependencyError(f"Scenarios not found: {', '.join(sorted(missing))}")
+
+    ordered: List[str] = []
+    visiting: Set[str] = set()
+    done: Set[str] = set()
+
+    def visit(scenario_id: str) -> None:
+        if scenario_id in done:
+            return
+        if scenario_id in visiting:
+            raise DependencyError("Scenario dependencies contain a cycle")
+        visiting.add(scenario_id)
+        for prerequisite in graph.get(scenario_id, []):
+            visit(prerequisite)
+        visiting.discard(scenario_id)
+        done.add(scenario_id)
+        ordered.append(scenario_id)
+
+    for scenario_id in natural_order:
+        visit(scenario_id)
+    return ordered
@@ -0,0 +1,261 @@
+"""
+Device State - Commands for deep links, permissions, connectivity, locale and clock
+"""
+import json
+import shlex
+from dataclasses import dataclass
+from datetime import datetime
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional
+
+# iOS privacy services accepted by `simctl privacy`
+IOS_PRIVACY_SERVICES = (
+    "all", "calendar", "contacts-limited", "contacts", "location", "location-always",
+    "photos-add", "photos", "media-library", "microphone", "motion", "reminders", "siri",
+)
+
+
+class DeviceStateError(Exception):
+    """Raised when a state change is missing settings or unsupported on the platform"""
+
+
+@dataclass
+class DeviceCommand:
+    """adb arguments on Android or simctl arguments on iOS, with optional stdin"""
+
+    args: List[str]
+    input: Optional[bytes] = None
+    delay: float = 0.0  # Seconds to wait afterwards, for timed playback
+
+
+def _as_bool(value: Any) -> bool:
+    if isinstance(value, str):
+        return value.strip().lower() in ("1", "true", "on", "yes", "enable", "enabled")
+    return bool(value)
+
+
+def deep_link_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    url = config.get("url")
+    if not url:
+        raise DeviceStateError("Missing url for deep link")
+    if platform == "ios":
+        return [DeviceCommand(["openurl", device_id, url])]
+    command = ["shell", "am", "start", "-W", "-a", "android.intent.action.VIEW", "-d", url]
+    if config.get("packageName"):
+        command.append(config["packageName"])
+    return [DeviceCommand(command)]
+
+
+def permission_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    package = config.get("packageName")
+    permission = config.get("permission")
+    if not package or not permission:
+        raise DeviceStateError("Missing packageName or permission")
+    grant = _as_bool(config.get("grant", True))
+    if platform == "ios":
+        if permission not in IOS_PRIVACY_SERVICES:
+            raise DeviceStateError(f"iOS permission must be one of: {', '.join(IOS_PRIVACY_SERVICES)}")
+        return [DeviceCommand(["privacy", device_id, "grant" if grant else "revoke", permission, package])]
+    if "." not in permission:
+        permission = f"android.permission.{permission}"
+    return [DeviceCommand(["shell", "pm", "grant" if grant else "revoke", package, permission])]
+
+
+def network_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    if platform == "ios":
+        raise DeviceStateError("Simulators share the host's network, so it can't be toggled")
+    commands = []
+    for key, prefix in (
+        ("airplane_mode", ["shell", "cmd", "connectivity", "airplane-mode"]),
+        ("wifi", ["shell", "svc", "wifi"]),
+        ("mobile_data", ["shell", "svc", "data"]),
+    ):
+        if config.get(key) is not None:
+            commands.append(DeviceCommand(prefix + ["enable" if _as_bool(config[key]) else "disable"]))
+    if not commands:
+        raise DeviceStateError("Set airplane_mode, wifi or mobile_data")
+    return commands
+
+
+def locale_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """Change the system language; iOS apps see it after relaunch, Android restarts its UI"""
+    locale = (config.get("locale") or config.get("value") or "").replace("_", "-")
+    if not locale:
+        raise DeviceStateError("Missing locale, e.g. en-US")
+    if platform == "ios":
+        defaults = ["spawn", device_id, "defaults", "write", ".GlobalPreferences"]
+        return [
+            DeviceCommand(defaults + ["AppleLanguages", "-array", locale.split("-")[0]]),
+            DeviceCommand(defaults + ["AppleLocale", locale.replace("-", "_")]),
+        ]
+    # Needs an emulator image that allows adb root, such as google_apis
+    return [
+        DeviceCommand(["root"]),
+        DeviceCommand(["wait-for-device"]),
+        DeviceCommand(["shell", "setprop", "persist.sys.locale", locale]),
+        DeviceCommand(["shell", "setprop", "ctl.restart", "zygote"]),
+    ]
+
+
+def time_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """Set the device clock to an ISO time, or back to network time with auto"""
+    if platform == "ios":
+        raise DeviceStateError("Simulators use the host's clock; override the status bar time instead")
+    if _as_bool(config.get("auto")):
+        return [DeviceCommand(["shell", "settings", "put", "global", "auto_time", "1"])]
+    value = config.get("time") or config.get("value")
+    if not value:
+        raise DeviceStateError("Missing time, e.g. 2024-01-31T09:41:00")
+    try:
+        moment = datetime.fromisoformat(str(value))
+    except ValueError:
+        raise DeviceStateError(f"Invalid ISO time: {value}")
+    return [
+        DeviceCommand(["shell", "settings", "put", "global", "auto_time", "0"]),
+        DeviceCommand(["shell", "cmd", "alarm", "set-time", str(int(moment.timestamp() * 1000))]),
+    ]
+
+
+def push_notification_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """Deliver an APNs payload to a simulator app; Android pushes need a real FCM sender"""
+    if platform != "ios":
+        raise DeviceStateError("Simulated push notifications are only available on iOS simulators")
+    bundle_id = config.get("packageName") or config.get("bundle_id")
+    payload = config.get("payload") or config.get("value")
+    if not bundle_id or not payload:
+        raise DeviceStateError("Missing packageName or payload")
+    if isinstance(payload, str):
+        try:
+            payload = json.loads(payload)
+        except ValueError as e:
+            raise DeviceStateError(f"Payload is not valid JSON: {e}")
+    if not isinstance(payload, dict) or "aps" not in payload:
+        raise DeviceStateError("Payload must be a JSON object with an 'aps' key")
+    return [DeviceCommand(["push", device_id, bundle_id, "-"], input=json.dumps(payload).encode())]
+
+
+def biometric_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """Present a matching or non-matching Face ID, Touch ID or fingerprint"""
+    match = _as_bool(config.get("match", True))
+    if platform == "ios":
+        sensor = "fingerTouch" if config.get("kind") == "touch" else "pearl"
+        outcome = "match" if match else "nomatch"
+        notify = ["spawn", device_id, "notifyutil"]
+        return [
+            # Enroll first so the app sees biometrics as available
+            DeviceCommand(notify + ["-s", "com.apple.BiometricKit.enrollmentChanged", "1"]),
+            DeviceCommand(notify + ["-p", "com.apple.BiometricKit.enrollmentChanged"]),
+            DeviceCommand(notify + ["-p", f"com.apple.BiometricKit_Sim.{sensor}.{outcome}"]),
+        ]
+    # Emulators only accept fingers enrolled in Settings, so another ID fails to match
+    finger_id = int(config.get("finger_id") or 1)
+    return [DeviceCommand(["emu", "finger", "touch", str(finger_id if match else finger_id + 100)])]
+
+
+def _coordinate(point: Dict[str, Any]) -> tuple:
+    try:
+        latitude = float(point.get("latitude", point.get("lat")))
+        longitude = float(point.get("longitude", point.get("lng", point.get("lon"))))
+    except (TypeError, ValueError):
+        raise DeviceStateError("Locations need numeric latitude and longitude")
+    if not -90 <= latitude <= 90 or not -180 <= longitude <= 180:
+        raise DeviceStateError(f"Location out of range: {latitude}, {longitude}")
+    return latitude, longitude
+
+
+def location_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """
+    Set a mock GPS location, or play back a route of points
+
+    Route points may carry their own delay_ms; otherwise interval_ms (default
+    1000) separates them. The device stays at the last point afterwards.
+    """
+    route = config.get("route") or [config]
+    if not isinstance(route, list):
+        raise DeviceStateError("Route must be a list of points")
+    interval = float(config.get("interval_ms") or 1000) / 1000
+    commands = []
+    for index, point in enumerate(route):
+        if not isinstance(point, dict):
+            raise DeviceStateError("Route points must be objects with latitude and longitude")
+        latitude, longitude = _coordinate(point)
+        if platform == "ios":
+            args = ["location", device_id, "set", f"{latitude},{longitude}"]
+        else:
+            # The emulator console takes longitude first
+            args = ["emu", "geo", "fix", str(longitude), str(latitude)]
+            if point.get("altitude") is not None:
+                args.append(str(float(point["altitude"])))
+        last = index == len(route) - 1
+        delay = float(point["delay_ms"]) / 1000 if point.get("delay_ms") is not None else interval
+        commands.append(DeviceCommand(args, delay=0.0 if last else delay))
+    return commands
+
+
+def sensor_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """Set an emulator sensor such as acceleration, gyroscope or light to the given values"""
+    if platform == "ios":
+        raise DeviceStateError("Simulators don't expose sensors beyond location")
+    sensor = config.get("sensor")
+    values = config.get("values", config.get("value"))
+    if not sensor or values is None:
+        raise DeviceStateError("Missing sensor or values")
+    if isinstance(values, (list, tuple)):
+        values = ":".join(str(float(v)) for v in values)
+    return [DeviceCommand(["emu", "sensor", "set", sensor, str(values)])]
+
+
+# Where injected media lands on Android before the media scanner indexes it
+ANDROID_MEDIA_DIR = "/sdcard/Pictures/AutoTest"
+
+
+def media_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """Add photos or videos from this machine to the device's gallery"""
+    paths = config.get("paths") or ([config["path"]] if config.get("path") else [])
+    if not paths:
+        raise DeviceStateError("Missing path of the photo or video to add")
+    files = [Path(p).expanduser() for p in paths]
+    missing = [str(f) for f in files if not f.is_file()]
+    if missing:
+        raise DeviceStateError(f"Media file not found: {', '.join(missing)}")
+    if platform == "ios":
+        return [DeviceCommand(["addmedia", device_id] + [str(f) for f in files])]
+    commands = [DeviceCommand(["shell", "mkdir", "-p", ANDROID_MEDIA_DIR])]
+    for file in files:
+        remote = f"{ANDROID_MEDIA_DIR}/{file.name}"
+        commands.append(DeviceCommand(["push", str(file), remote]))
+        commands.append(DeviceCommand([
+            "shell", "am", "broadcast", "-a", "android.intent.action.MEDIA_SCANNER_SCAN_FILE", "-d", f"file://{remote}"
+        ]))
+    return commands
+
+
+def clipboard_commands(platform: str, device_id: str, config: Dict[str, Any]) -> List[DeviceCommand]:
+    """
+    Put text on the clipboard
+
+    Android has no shell clipboard command, so this goes through the Clipper
+    helper app (ca.zgrs.clipper), which must be installed and running.
+    """
+    text = config.get("text", config.get("value"))
+    if text is None:
+        raise DeviceStateError("Missing text for the clipboard")
+    if platform == "ios":
+        return [DeviceCommand(["pbcopy", device_id], input=str(text).encode())]
+    return [DeviceCommand(["shell", "am", "broadcast", "-a", "clipper.set", "-e", "text", shlex.quote(str(text))])]
+
+
+# Step types that change device state, mapped to the commands they run
+STATE_STEP_TYPES: Dict[str, Callable[[str, str, Dict[str, Any]], List[DeviceCommand]]] = {
+    "deeplink": deep_link_commands,
+    "permission": permission_commands,
+    "network": network_commands,
+    "locale": locale_commands,
+    "set_time": time_commands,
+    "push_notification": push_notification_commands,
+    "biometric": biometric_commands,
+    "set_location": location_commands,
+    "sensor": sensor_commands,
+    "add_media": media_commands,
+    "set_clipboard": clipboard_commands,
+}
@@ -0,0 +1,201 @@
+"""
+Environment Doctor - Checks the tools, keys, ports and storage a fresh machine needs
+"""
+import asyncio
+import os
+import platform
+import socket
+import sys
+import tempfile
+from typing import List, Optional
+from urllib.parse import urlsplit
+
+from pydantic import BaseModel
+
+from ..config import settings
+from ..db.database import IS_SQLITE, db_state, engine
+from ..routers.services import check_service
+from .ai_client import AiClientError, get_provider, load_ai_options
+from .service_urls import get_service_urls
+from .tools import find_tool, tool_command
+
+TOOL_TIMEOUT = 10.0
+
+
+class DoctorCheck(BaseModel):
+    id: str
+    name: str
+    status: str  # 'ok' | 'warning' | 'error' | 'skipped'
+    detail: str
+    hint: Optional[str] = None  # What to do about a warning or error
+
+
+class DoctorReport(BaseModel):
+    status: str  # The worst status among the checks
+    platform: str
+    checks: List[DoctorCheck]
+
+
+async def _version(command: List[str]) -> Optional[str]:
+    """First line a tool prints for its version, None if it can't run"""
+    try:
+        process = await asyncio.create_subprocess_exec(
+            *command, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.STDOUT
+        )
+        stdout, _ = await asyncio.wait_for(process.communicate(), TOOL_TIMEOUT)
+    except (OSError, asyncio.TimeoutError):
+        return None
+    if process.returncode != 0:
+        return None
+    lines = stdout.decode(errors="replace").strip().splitlines()
+    return lines[0] if lines else ""
+
+
+async def check_adb() -> DoctorCheck:
+    path = find_tool("adb")
+    version = await _version([path, "version"]) if path else None
+    if version is None:
+        return DoctorCheck(
+            id="adb", name="Android Debug Bridge", status="warning",
+            detail="adb was not found, so Android devices and emulators can't be used",
+            hint="Install the Android SDK platform-tools and set ANDROID_HOME, or set tools.adb_path",
+        )
+    return DoctorCheck(id="adb", name="Android Debug Bridge", status="ok", detail=f"{version} ({path})")
+
+
+async def check_simctl() -> DoctorCheck:
+    if platform.system() != "Darwin":
+        return DoctorCheck(
+            id="simctl", name="iOS Simulator tools", status="skipped", detail="iOS testing needs macOS"
+        )
+    if await _version([tool_command("xcrun"), "simctl", "help"]) is None:
+        return DoctorCheck(
+            id="simctl", name="iOS Simulator tools", status="warning",
+            detail="xcrun simctl isn't available, so iOS simulators can't be used",
+            hint="Install Xcode from the App Store, then run: sudo xcode-select -s /Applications/Xcode.app",
+        )
+    version = await _version(["xcodebuild", "-version"])
+    return DoctorCheck(id="simctl", name="iOS Simulator tools", status="ok", detail=version or "xcrun simctl")
+
+
+async def check_node() -> DoctorCheck:
+    version = await _version([tool_command("node"), "--version"])
+    if version is None:
+        return DoctorCheck(
+            id="node", name="Node.js", status="warning",
+            detail="node was not found, so Playwright and Cypress web runs can't start",
+            hint="Install Node.js 18 or newer from https://nodejs.org",
+        )
+    if not find_tool("npx"):
+        return DoctorCheck(
+            id="node", name="Node.js", status="warning", detail=f"node {version} is installed without npx",
+            hint="Reinstall Node.js with npm, which provides npx",
+        )
+    return DoctorCheck(id="node", name="Node.js", status="ok", detail=f"node {version}")
+
+
+def check_python() -> DoctorCheck:
+    version = platform.python_version()
+    if sys.version_info < (3, 9):
+        return DoctorCheck(
+            id="python", name="Python", status="error", detail=f"Python {version} is too old",
+            hint="Install Python 3.9 or newer and recreate the backend's virtualenv",
+        )
+    return DoctorCheck(id="python", name="Python", status="ok", detail=f"Python {version} ({sys.executable})")
+
+
+async def check_ai() -> DoctorCheck:
+    options = await load_ai_options()
+    try:
+        get_provider(options)
+    except AiClientError as e:
+        return DoctorCheck(
+            id="ai", name="AI provider", status="warning",
+            detail=f"{options.provider}: {e}. AI features and self-healing won't work",
+            hint="Add the provider's API key to the backend's .env file, or mark projects as AI-free",
+        )
+    return DoctorCheck(id="ai", name="AI provider", status="ok", detail=f"{options.provider} is configured")
+
+
+def _port_in_use(host: str, port: int) -> bool:
+    with socket.socket(socket.AF_INET, socket.SOCK_STREAM) as sock:
+        sock.settimeout(1.0)
+        return sock.connect_ex((host, port)) == 0
+
+
+async def check_service_ports() -> List[DoctorCheck]:
+    """Each local service should either be running or have its port free to start on"""
+    checks = []
+    for name, url in (await get_service_urls()).items():
+        parts = urlsplit(url)
+        host, port = parts.hostname or "127.0.0.1", parts.port or (443 if parts.scheme == "https" else 80)
+        check_id = f"port:{name}"
+        if host not in ("127.0.0.1", "localhost", "::1"):
+            health = await check_service(name, url)
+            status = "ok" if health.status == "Running" else "warning"
+            checks.append(DoctorCheck(
+                id=check_id, name=f"{name} service", status=status,
+                detail=f"Remote service at {url} is {health.status.lower()}",
+                hint=None if status == "ok" else f"Check that {url} is reachable from this machine",
+            ))
+            continue
+        if not await asyncio.to_thread(_port_in_use, host, port):
+            checks.append(DoctorCheck(
+                id=check_id, name=f"{name} service", status="ok", detail=f"Port {port} is free for {name}"
+            ))
+            continue
+        health = await check_service(name, url)
+        if health.status == "Running":
+            checks.append(DoctorCheck(
+                id=check_id, name=f"{name} service", status="ok", detail=f"{name} is running on port {port}"
+            ))
+        else:
+            checks.append(DoctorCheck(
+                id=check_id, name=f"{name} service", status="error",
+                detail=f"Port {port} is taken by something that isn't {name}",
+                hint=f"Stop the process using port {port}, or point services.{name}_url at another port",
+            ))
+    return checks
+
+
+def check_database() -> DoctorCheck:
+    if not IS_SQLITE:
+        url = engine.url.render_as_string(hide_password=True)
+        if not db_state.available:
+            return DoctorCheck(
+                id="database", name="Database", status="error",
+                detail=f"The database at {url} is unavailable: {db_state.error}",
+                hint="Check that the database server is running and DATABASE_URL's host and credentials",
+            )
+        return DoctorCheck(id="database", name="Database", status="ok", detail=f"Connected to {url}")
+
+    path = settings.get_database_path()
+    if not db_state.available:
+        return DoctorCheck(
+            id="database", name="Database", status="error",
+            detail=f"The database at {path} is unavailable: {db_state.error}",
+            hint="Check that the data directory exists and has free space, or set DATABASE_URL",
+        )
+    try:
+        with tempfile.NamedTemporaryFile(dir=path.parent):
+            pass
+        writable = not path.exists() or os.access(path, os.W_OK)
+    except OSError:
+        writable = False
+    if not writable:
+        return DoctorCheck(
+            id="database", name="Database", status="error", detail=f"{path} is not writable",
+            hint=f"Give your user write access to {path.parent}",
+        )
+    return DoctorCheck(id="database", name="Database", status="ok", detail=f"{path} is writable")
+
+
+async def run_environment_doctor() -> DoctorReport:
+    """Run every check, returning a report with a hint for each problem"""
+    adb, simctl, node, ai, ports = await asyncio.gather(
+        check_adb(), check_simctl(), check_node(), check_ai(), check_service_ports()
+    )
+    checks = [adb, simctl, node, check_python(), ai, *ports, check_database()]
+    statuses = {check.status for check in checks}
+    status = "error" if "error" in statuses else "warning" if "warning" in statuses else "ok"
+    return DoctorReport(status=status, platform=platform.system().lower(), checks=checks)
@@ -0,0 +1,114 @@
+"""
+Drafts - Autosaved, unsaved edits to scenarios, steps and test cases
+
+The editor saves its working copy of an entity here every few seconds and
+discards it once the edits are saved. A draft remembers the entity's
+updated_at when editing began; if the entity has been saved since, for
+example from another window, reading the draft reports a conflict so the
+editor can ask which copy to keep instead of overwriting the newer one.
+"""
+import json
+from datetime import datetime
+from typing import Dict, List, Optional, Type
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Draft, DraftResponse, DraftSave, Scenario, Step, TestCase
+
+DRAFT_ENTITIES: Dict[str, Type] = {"scenario": Scenario, "step": Step, "test_case": TestCase}
+
+# Drafts larger than this are refused, since they're written every few seconds
+MAX_DRAFT_BYTES = 2 * 1024 * 1024
+
+
+class DraftError(Exception):
+    """Raised for drafts of unknown entity types or ones that are too large"""
+
+
+class DraftNotFound(Exception):
+    """Raised when the draft or its entity doesn't exist"""
+
+
+async def entity_updated_at(db: AsyncSession, entity: str, entity_id: str) -> Optional[datetime]:
+    """The saved entity's updated_at; raises DraftNotFound when the entity doesn't exist"""
+    model = DRAFT_ENTITIES.get(entity)
+    if not model:
+        raise DraftError(f"Drafts are kept for: {', '.join(DRAFT_ENTITIES)}")
+    row = await db.get(model, entity_id)
+    if not row:
+        raise DraftNotFound(f"{entity.replace('_', ' ').capitalize()} not found")
+    return row.updated_at
+
+
+def _response(draft: Draft, current_updated_at: Optional[datetime]) -> DraftResponse:
+    response = DraftResponse.model_validate(draft)
+    response.current_updated_at = current_updated_at
+    response.conflict = bool(
+        draft.base_updated_at and current_updated_at and current_updated_at > draft.base_updated_at
+    )
+    return response
+
+
+async def _find(db: AsyncSession, entity: str, entity_id: str) -> Optional[Draft]:
+    result = await db.execute(select(Draft).where(Draft.entity == entity, Draft.entity_id == entity_id))
+    return result.scalar_one_or_none()
+
+
+async def save_draft(db: AsyncSession, entity: str, entity_id: str, data: DraftSave) -> DraftResponse:
+    """Create or replace an entity's draft, keeping the base version of the first save"""
+    current = await entity_updated_at(db, entity, entity_id)
+    content = json.dumps(data.content)
+    if len(content.encode()) > MAX_DRAFT_BYTES:
+        raise DraftError(f"Drafts can be at most {MAX_DRAFT_BYTES // (1024 * 1024)} MB")
+
+    draft = await _find(db, entity, entity_id)
+    if draft:
+        draft.content = content
+        if data.base_updated_at:
+            draft.base_updated_at = data.base_updated_at
+    else:
+        draft = Draft(
+            entity=entity, entity_id=entity_id, content=content, base_updated_at=data.base_updated_at or current
+        )
+        db.add(draft)
+    await db.commit()
+    await db.refresh(draft)
+    return _response(draft, current)
+
+
+async def get_draft(db: AsyncSession, entity: str, entity_id: str) -> DraftResponse:
+    """An entity's draft, with whether the entity was saved since the draft's base version"""
+    current = await entity_updated_at(db, entity, entity_id)
+    draft = await _find(db, entity, entity_id)
+    if not draft:
+        raise DraftNotFound("No draft for this entity")
+    return _response(draft, current)
+
+
+async def discard_draft(db: AsyncSession, entity: str, entity_id: str) -> bool:
+    """Delete an entity's draft, returning whether there was one"""
+    draft = await _find(db, entity, entity_id)
+    if not draft:
+        return False
+    await db.delete(draft)
+    await db.commit()
+    return True
+
+
+async def list_drafts(db: AsyncSession, entity: Optional[str] = None) -> List[DraftResponse]:
+    """Every draft to recover after a restart, newest first; drafts of deleted entities are discarded"""
+    query = select(Draft).order_by(Draft.updated_at.desc())
+    if entity:
+        query = query.where(Draft.entity == entity)
+    drafts = []
+    orphaned = False
+    for draft in (await db.execute(query)).scalars().all():
+        try:
+            drafts.append(_response(draft, await entity_updated_at(db, draft.entity, draft.entity_id)))
+        except (DraftError, DraftNotFound):
+            await db.delete(draft)
+            orphaned = True
+    if orphaned:
+        await db.commit()
+    return drafts
@@ -0,0 +1,120 @@
+"""
+Duplication Service - Deep copies of scenarios, test cases, and projects
+"""
+import uuid
+from typing import Dict, Optional
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Element, Project, Scenario, Step, StepResult, TestCase, TestRun
+
+# Columns that are regenerated rather than copied
+_GENERATED_COLUMNS = {"id", "created_at", "updated_at"}
+
+
+def _clone(row, **overrides):
+    """Copy a row's column values into a new instance with a fresh ID"""
+    values = {
+        column.key: getattr(row, column.key)
+        for column in row.__table__.columns
+        if column.key not in _GENERATED_COLUMNS
+    }
+    values.update(overrides)
+    return type(row)(id=str(uuid.uuid4()), **values)
+
+
+async def copy_scenario(
+    db: AsyncSession,
+    scenario: Scenario,
+    test_case_id: str,
+    name: Optional[str] = None,
+    step_ids: Optional[Dict[str, str]] = None,
+) -> Scenario:
+    """
+    Copy a scenario and its steps into a test case without committing
+
+    Old step IDs are mapped to the new ones in step_ids when it is given.
+    """
+    new_scenario = _clone(scenario, test_case_id=test_case_id, name=name or scenario.name)
+    db.add(new_scenario)
+    await db.flush()
+
+    steps_result = await db.execute(
+        select(Step).where(Step.scenario_id == scenario.id).order_by(Step.step_order)
+    )
+    for step in steps_result.scalars().all():
+        new_step = _clone(step, scenario_id=new_scenario.id)
+        db.add(new_step)
+        if step_ids is not None:
+            step_ids[step.id] = new_step.id
+    return new_scenario
+
+
+async def copy_test_case(
+    db: AsyncSession,
+    test_case: TestCase,
+    project_id: str,
+    name: Optional[str] = None,
+    step_ids: Optional[Dict[str, str]] = None,
+) -> TestCase:
+    """Copy a test case with all its scenarios and steps without committing"""
+    new_test_case = _clone(test_case, project_id=project_id, name=name or test_case.name)
+    db.add(new_test_case)
+    await db.flush()
+
+    scenarios_result = await db.execute(
+        select(Scenario).where(Scenario.test_case_id == test_case.id).order_by(Scenario.position, Scenario.created_at)
+    )
+    for scenario in scenarios_result.scalars().all():
+        await copy_scenario(db, scenario, new_test_case.id, step_ids=step_ids)
+    return new_test_case
+
+
+async def copy_project(
+    db: AsyncSession, project: Project, name: Optional[str] = None, include_runs: bool = False
+) -> Project:
+    """
+    Copy a project with all elements, test cases, scenarios, and steps without committing
+
+    With include_runs, test runs and their step results are copied too and
+    point at the copied test cases and steps.
+    """
+    new_project = _clone(project, name=name or project.name)
+    db.add(new_project)
+    await db.flush()
+
+    elements_result = await db.execute(select(Element).where(Element.project_id == project.id))
+    for element in elements_result.scalars().all():
+        db.add(_clone(element, project_id=new_project.id))
+
+    test_case_ids: Dict[str, str] = {}
+    step_ids: Dict[str, str] = {}
+    test_cases_result = await db.execute(
+        select(TestCase).where(TestCase.project_id == project.id).order_by(TestCase.created_at)
+    )
+    for test_case in test_cases_result.scalars().all():
+        new_test_case = await copy_test_case(db, test_case, new_project.id, step_ids=step_ids)
+        test_case_ids[test_case.id] = new_test_case.id
+
+    if include_runs:
+        runs_result = await db.execute(select(TestRun).where(TestRun.project_id == project.id))
+        for test_run in runs_result.scalars().all():
+            new_run = _clone(test_run, project_id=new_project.id)
+            db.add(new_run)
+            await db.flush()
+
+            results = await db.execute(select(StepResult).where(StepResult.test_run_id == test_run.id))
+            for step_result in results.scalars().all():
+                # Results of steps that were since deleted have nothing to point at
+                if step_result.step_id not in step_ids or step_result.test_case_id not in test_case_ids:
+                    continue
+                db.add(
+                    _clone(
+                        step_result,
+                        test_run_id=new_run.id,
+                        step_id=step_ids[step_result.step_id],
+                        test_case_id=test_case_ids[step_result.test_case_id],
+                    )
+                )
+    return new_project
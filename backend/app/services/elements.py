@@ -0,0 +1,80 @@
+"""
+Element Repository - Resolves named element references in step configs
+"""
+import json
+from typing import Any, Dict, List, Tuple
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Element, Scenario, Step, TestCase
+from .web_context import FRAME_KEY, SHADOW_KEY
+
+# Step config key holding the referenced element name
+ELEMENT_KEY = "element"
+
+
+async def load_elements(db: AsyncSession, project_id: str) -> Dict[str, Element]:
+    """Load a project's elements keyed by name"""
+    result = await db.execute(select(Element).where(Element.project_id == project_id))
+    return {element.name: element for element in result.scalars().all()}
+
+
+def apply_element(config: Dict[str, Any], elements: Dict[str, Element], platform: str) -> Dict[str, Any]:
+    """
+    Fill a step config's locator from the element it references
+
+    The element's locator for the platform becomes the step's selector, with its
+    coordinates as a fallback. Web elements also bring the frames and shadow
+    roots they sit in. Configs without a known element are returned as is.
+    """
+    element = elements.get(config.get(ELEMENT_KEY) or "")
+    if not element:
+        return config
+
+    if platform == "android":
+        selector = element.resource_id
+    elif platform == "ios":
+        selector = element.accessibility_id
+    else:
+        selector = element.css_selector
+
+    resolved = {**config, "selector": selector}
+    if platform == "web" and element.xpath:
+        resolved["xpath"] = element.xpath
+    if platform == "web":
+        for key in (FRAME_KEY, SHADOW_KEY):
+            path = json.loads(getattr(element, key) or "[]")
+            if path:
+                resolved[key] = path
+    if element.x is not None and element.y is not None:
+        resolved.update(x=element.x, y=element.y)
+    if element.description and not config.get("element_description"):
+        resolved["element_description"] = element.description
+    return resolved
+
+
+async def find_element_usages(db: AsyncSession, element: Element) -> List[Tuple[Step, Scenario]]:
+    """Find the steps in the element's project that reference it"""
+    result = await db.execute(
+        select(Step, Scenario)
+        .join(Scenario, Step.scenario_id == Scenario.id)
+        .join(TestCase, Scenario.test_case_id == TestCase.id)
+        .where(TestCase.project_id == element.project_id, Step.config.contains(element.name))
+        .order_by(Scenario.position, Scenario.name, Step.step_order)
+    )
+    return [
+        (step, scenario)
+        for step, scenario in result.all()
+        if json.loads(step.config or "{}").get(ELEMENT_KEY) == element.name
+    ]
+
+
+async def rename_element_references(db: AsyncSession, element: Element, new_name: str) -> int:
+    """Point every step that references the element at its new name, without committing"""
+    usages = await find_element_usages(db, element)
+    for step, _ in usages:
+        config = json.loads(step.config or "{}")
+        config[ELEMENT_KEY] = new_name
+        step.config = json.dumps(config)
+    return len(usages)
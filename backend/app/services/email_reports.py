@@ -0,0 +1,146 @@
+"""
+Email Reports - Sends run reports over SMTP
+
+The report is the HTML body, with the JUnit XML attached. Scheduled runs
+that fail are emailed to email.schedule_failure_recipients when it's set.
+"""
+import asyncio
+import smtplib
+import ssl
+from email.message import EmailMessage
+from email.utils import formatdate, make_msgid
+from typing import Any, Dict, List, Optional
+
+from sqlalchemy import select
+from sqlalchemy.exc import OperationalError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from ..models import RunSchedule, TestRun
+from .app_settings import get_all_settings
+from .events import event_bus
+from .run_reports import render_html_report, render_junit_xml
+
+SMTP_SECURITY = ("starttls", "ssl", "none")
+SMTP_TIMEOUT = 30.0
+
+
+class EmailError(Exception):
+    """Raised for missing SMTP settings and bad recipients"""
+
+
+class EmailDeliveryError(EmailError):
+    """Raised when the SMTP server can't be reached or refuses the message"""
+
+
+def parse_recipients(value: str) -> List[str]:
+    recipients = [address.strip() for address in value.replace(";", ",").split(",") if address.strip()]
+    for address in recipients:
+        if "@" not in address or any(c in address for c in " <>\r\n"):
+            raise EmailError(f"Invalid email address: {address}")
+    return recipients
+
+
+def _send(values: Dict[str, Any], message: EmailMessage) -> None:
+    host, port, security = values["email.smtp_host"], values["email.smtp_port"], values["email.smtp_security"]
+    context = ssl.create_default_context()
+    if security == "ssl":
+        smtp = smtplib.SMTP_SSL(host, port, timeout=SMTP_TIMEOUT, context=context)
+    else:
+        smtp = smtplib.SMTP(host, port, timeout=SMTP_TIMEOUT)
+    with smtp:
+        if security == "starttls":
+            smtp.starttls(context=context)
+        if values["email.smtp_username"]:
+            smtp.login(values["email.smtp_username"], settings.smtp_password)
+        smtp.send_message(message)
+
+
+async def email_run_report(db: AsyncSession, test_run_id: str, recipients: List[str]) -> None:
+    """Email a run's HTML report inline with its JUnit XML attached"""
+    values = await get_all_settings(db)
+    if not values["email.smtp_host"] or not values["email.from_address"]:
+        raise EmailError("Email needs email.smtp_host and email.from_address")
+    if values["email.smtp_security"] not in SMTP_SECURITY:
+        raise EmailError(f"email.smtp_security must be one of: {', '.join(SMTP_SECURITY)}")
+    if not recipients:
+        raise EmailError("No recipients")
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise EmailError("Test run not found")
+
+    message = EmailMessage()
+    message["Subject"] = f"[{settings.app_name}] {test_run.name}: {test_run.status}"
+    message["From"] = values["email.from_address"]
+    message["To"] = ", ".join(recipients)
+    message["Date"] = formatdate(localtime=True)
+    message["Message-ID"] = make_msgid()
+    message.set_content(
+        f"{test_run.name}: {test_run.status}\n"
+        f"{test_run.passed} passed, {test_run.failed} failed, {test_run.skipped} skipped\n\n"
+        "Open this email in an HTML-capable client for the full report; the JUnit XML is attached."
+    )
+    message.add_alternative(await render_html_report(db, test_run), subtype="html")
+    message.add_attachment(
+        (await render_junit_xml(db, test_run)).encode("utf-8"),
+        maintype="application", subtype="xml", filename=f"junit-{test_run.id}.xml",
+    )
+
+    try:
+        await asyncio.to_thread(_send, values, message)
+    except (smtplib.SMTPException, OSError) as e:
+        raise EmailDeliveryError(f"Couldn't send email: {e}")
+    event_bus.publish("email:sent", {"test_run_id": test_run.id, "recipients": len(recipients)})
+
+
+# ============================================
+# Failed Schedule Delivery
+# ============================================
+
+
+class ScheduleFailureMailer:
+    """Emails the report of each scheduled run that fails, watching run status events"""
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        queue = event_bus.subscribe()
+        try:
+            while True:
+                message = await queue.get()
+                payload = message["payload"] or {}
+                if message["event"] != "run:status_changed" or payload.get("to_status") != "failed":
+                    continue
+                try:
+                    await self._deliver(payload["test_run_id"])
+                except (EmailError, OperationalError) as e:
+                    print(f"Failed run email error: {e}")
+                    event_bus.publish("email:failed", {"test_run_id": payload["test_run_id"], "error": str(e)})
+        finally:
+            event_bus.unsubscribe(queue)
+
+    async def _deliver(self, test_run_id: str) -> None:
+        async with AsyncSessionLocal() as db:
+            recipients = parse_recipients((await get_all_settings(db))["email.schedule_failure_recipients"])
+            if not recipients:
+                return
+            result = await db.execute(select(RunSchedule.id).where(RunSchedule.last_test_run_id == test_run_id))
+            if result.first() is None:
+                return  # Not a scheduled run
+            await email_run_report(db, test_run_id, recipients)
+
+
+# Singleton instance
+schedule_failure_mailer = ScheduleFailureMailer()
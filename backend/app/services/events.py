@@ -0,0 +1,62 @@
+"""
+Event Bus - Pushes backend events to connected frontend clients
+
+Each desktop window subscribes with its own window id, so events can be sent
+to a single window, and with filters such as a test_run_id, so a detached
+run viewer only hears about its run.
+"""
+import asyncio
+import time
+from dataclasses import dataclass, field
+from typing import Any, Dict, List, Optional
+
+# Events queued for a slow client beyond this are dropped rather than blocking publishers
+MAX_QUEUED_EVENTS = 1000
+
+
+@dataclass
+class Subscription:
+    window_id: Optional[str] = None
+    filters: Dict[str, str] = field(default_factory=dict)
+
+    def wants(self, payload: Any, window_id: Optional[str]) -> bool:
+        """Whether an event is for this subscriber; events without a filtered key go to everyone"""
+        if window_id and window_id != self.window_id:
+            return False
+        if not isinstance(payload, dict):
+            return True
+        return all(payload.get(key) in (None, value) for key, value in self.filters.items())
+
+
+class EventBus:
+    """In-process publish/subscribe channel, delivered to clients over WebSocket"""
+
+    def __init__(self):
+        self._subscribers: Dict[asyncio.Queue, Subscription] = {}
+
+    def subscribe(self, window_id: Optional[str] = None, filters: Optional[Dict[str, str]] = None) -> asyncio.Queue:
+        queue: asyncio.Queue = asyncio.Queue(maxsize=MAX_QUEUED_EVENTS)
+        self._subscribers[queue] = Subscription(window_id, {k: v for k, v in (filters or {}).items() if v})
+        return queue
+
+    def unsubscribe(self, queue: asyncio.Queue) -> None:
+        self._subscribers.pop(queue, None)
+
+    def windows(self) -> List[Subscription]:
+        """Subscriptions of connected windows"""
+        return [subscription for subscription in self._subscribers.values() if subscription.window_id]
+
+    def publish(self, event: str, payload: Any = None, window_id: Optional[str] = None) -> None:
+        """Send an event to every subscriber that wants it, or only to one window's"""
+        message = {"event": event, "payload": payload, "timestamp": time.time()}
+        for queue, subscription in list(self._subscribers.items()):
+            if not subscription.wants(payload, window_id):
+                continue
+            try:
+                queue.put_nowait(message)
+            except asyncio.QueueFull:
+                pass
+
+
+# Singleton instance
+event_bus = EventBus()
@@ -0,0 +1,972 @@
+"""
+Scenario Executor - Runs mobile scenario steps against a connected device
+"""
+import asyncio
+import base64
+import hashlib
+import json
+import time
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional, Set, Tuple
+
+from fastapi import HTTPException
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal
+from ..models import Project, RunLogCreate, Scenario, Step, StepResultCreate, TestCase, TestRun
+from ..routers.mobile import (
+    AppRequest,
+    InputTextRequest,
+    SwipeRequest,
+    TapRequest,
+    android_screenshot,
+    android_type_text,
+    ios_input_text,
+    ios_launch_app,
+    ios_screenshot,
+    ios_swipe,
+    ios_tap,
+    is_physical_ios,
+    run_adb_command,
+    run_xcrun_command,
+)
+from .accessibility import ACCESSIBILITY_STEP_TYPE, audit_android, evaluate_violations, failure_message
+from .ai_client import AiClientError, ask_ai_json
+from .app_settings import SETTING_DEFAULTS, get_all_settings, get_setting
+from .assertions import (
+    VERIFY_STEP_TYPE,
+    AssertionTargetError,
+    DeviceValueReader,
+    run_assertions,
+    step_assertions,
+    summarize,
+)
+from .baselines import compare_with_baselines
+from .budgets import apply_step_budget, budget_message, check_scenario_budget
+from .crash_logs import collect_run_crashes
+from .debugging import debug_sessions
+from .coordinates import ABSOLUTE, DEVICE_PIXELS, NORMALIZED, png_size, to_device_point
+from .device_state import STATE_STEP_TYPES, DeviceStateError
+from .elements import apply_element, load_elements
+from .events import event_bus
+from .healing import heal_locator
+from .plugins import PluginError, plugin_registry
+from .result_writer import StepResultWriter, insert_step_results, step_result_row
+from .run_lifecycle import RunTransitionError, cancel_run, complete_run, fail_run, start_run
+from .run_logs import add_run_logs
+from .run_queue import run_queue
+from .screenshots import save_step_screenshot, should_capture
+from .secret_masking import redact_screenshot, secret_values
+from .shell_steps import SHELL_STEP_TYPE, ShellStepError, run_shell_step
+from .synonyms import load_synonyms
+from .ui_dump import (
+    UiNode,
+    element_descriptor,
+    find_element_from_ui_dump,
+    find_node_by_descriptor,
+    find_node_by_selector,
+    layout_fingerprint,
+    parse_ui_dump,
+)
+from .waits import (
+    DEFAULT_IDLE_MS,
+    IDLE_BYTES_THRESHOLD,
+    POLL_INTERVAL,
+    WAIT_STEP_TYPES,
+    network_bytes,
+    poll_until,
+    resumed_activity,
+    wait_timeout_ms,
+)
+
+# Step types whose target element is resolved to a point before acting
+TARGETED_STEP_TYPES = {"tap", "swipe"}
+
+# Step types that never act on an element, so there's nothing to heal
+UNTARGETED_STEP_TYPES = {SHELL_STEP_TYPE, *STATE_STEP_TYPES, *WAIT_STEP_TYPES}
+
+
+class StepExecutionError(Exception):
+    """Raised when a step cannot be performed on the device"""
+
+
+@dataclass
+class StepOutcome:
+    status: str
+    duration_ms: int
+    error_message: Optional[str] = None
+    healed: bool = False
+    healed_locator: Optional[Dict[str, Any]] = None
+    details: Optional[Dict[str, Any]] = None
+    soft: bool = False  # Only soft assertions failed, so the scenario carries on
+
+
+@dataclass
+class LocatorResolution:
+    """Outcome of resolving a scenario's element locators"""
+
+    resolved_step_ids: List[str]
+    unresolved_step_ids: List[str]
+    screens: int
+
+
+class ScenarioExecutor:
+    """Executes the steps of a scenario on an Android device or iOS simulator"""
+
+    log_source = "executor"
+
+    def __init__(
+        self, device_id: str, platform: str, self_heal: bool = True, ai_free: bool = False, debug: bool = False
+    ):
+        self.device_id = device_id
+        self.platform = platform
+        self.self_heal = self_heal
+        # Resolve elements from UI dumps only, never calling AI services
+        self.ai_free = ai_free
+        # Pause at step breakpoints for interactive debugging
+        self.debug = debug
+        # Set while a scenario runs so shell steps can log and see the project's env vars
+        self.test_run_id: Optional[str] = None
+        self.env: Dict[str, str] = {}
+        # How long tap and swipe targets may take to appear before the step fails
+        self.implicit_wait_ms: int = SETTING_DEFAULTS["executor.implicit_wait_ms"]
+        # The project's synonym dictionary for offline element lookups; None uses the defaults
+        self.synonyms: Optional[List[Set[str]]] = None
+        self._screen: Optional[Tuple[int, int]] = None
+
+    async def run_scenario(self, scenario_id: str, test_run_id: str) -> None:
+        """Run every step of a scenario and record the results on the test run"""
+        await self.run_scenarios([scenario_id], test_run_id)
+
+    async def run_scenarios(
+        self,
+        scenario_ids: List[str],
+        test_run_id: str,
+        prerequisites: Optional[Dict[str, List[str]]] = None,
+    ) -> Dict[str, str]:
+        """
+        Run scenarios in order as one test run, returning each scenario's outcome
+
+        A scenario whose prerequisites didn't all pass is skipped, with a skipped
+        result recorded for each of its steps.
+        """
+        prerequisites = prerequisites or {}
+        outcomes: Dict[str, str] = {}
+        if self.debug:
+            debug_sessions.open(test_run_id, self)
+        try:
+            return await self._run_scenarios(scenario_ids, test_run_id, prerequisites, outcomes)
+        finally:
+            debug_sessions.close(test_run_id)
+
+    async def _run_scenarios(
+        self,
+        scenario_ids: List[str],
+        test_run_id: str,
+        prerequisites: Dict[str, List[str]],
+        outcomes: Dict[str, str],
+    ) -> Dict[str, str]:
+        async with AsyncSessionLocal() as db:
+            test_run = await start_run(db, await db.get(TestRun, test_run_id))
+
+            passed = failed = skipped = 0
+            for scenario_id in scenario_ids:
+                scenario = await db.get(Scenario, scenario_id)
+                blocked_by = [p for p in prerequisites.get(scenario_id, []) if outcomes.get(p) != "passed"]
+                if blocked_by:
+                    names = [(await db.get(Scenario, p)).name for p in blocked_by]
+                    reason = f"Skipped: prerequisite did not pass ({', '.join(names)})"
+                    await self._log(db, test_run_id, "warning", f"{scenario.name}: {reason}")
+                    counts = await self._skip_steps(db, scenario, test_run_id, reason)
+                    outcomes[scenario_id] = "skipped"
+                else:
+                    await self._log(db, test_run_id, "info", f"Running scenario {scenario.name}")
+                    counts = await self._run_steps(db, scenario, test_run_id)
+                    counts = await self._check_budget(db, scenario, test_run_id, counts)
+                    outcomes[scenario_id] = "failed" if counts[1] else "passed"
+                passed += counts[0]
+                failed += counts[1]
+                skipped += counts[2]
+
+            await complete_run(db, test_run, passed, failed, skipped)
+            await self._log(
+                db, test_run_id, "error" if test_run.status == "failed" else "info",
+                f"Run {test_run.status}: {passed} passed, {failed} failed, {skipped} skipped",
+            )
+            await collect_run_crashes(db, test_run, self.device_id, self.platform)
+            await self._report_baseline_changes(db, test_run)
+        return outcomes
+
+    async def _check_budget(self, db: AsyncSession, scenario: Scenario, test_run_id: str, counts: tuple) -> tuple:
+        """Log a scenario's time budget overruns, moving steps failed for going over into the failed count"""
+        action = await get_setting(db, "executor.budget_action")
+        messages, over = await check_scenario_budget(db, scenario, test_run_id, action)
+        for message in messages:
+            await self._log(db, test_run_id, "error" if over else "warning", message)
+        await db.commit()
+        return counts[0] - over, counts[1] + over, counts[2]
+
+    async def _report_baseline_changes(self, db: AsyncSession, test_run: TestRun) -> None:
+        """Log how each scenario differs from its approved baseline run"""
+        for result in await compare_with_baselines(db, test_run):
+            comparison = result.comparison
+            counts = {
+                "newly_failing": comparison.newly_failing,
+                "newly_passing": comparison.newly_passing,
+                "duration_regressions": comparison.duration_regressions,
+                "changed_screenshots": comparison.changed_screenshots,
+            }
+            name = comparison.scenarios[0].name if comparison.scenarios else result.scenario_id
+            regressed = comparison.newly_failing or comparison.duration_regressions or comparison.changed_screenshots
+            await self._log(
+                db, test_run.id, "warning" if regressed else "info",
+                f"{name} vs baseline: {comparison.newly_failing} newly failing, "
+                f"{comparison.newly_passing} newly passing, {comparison.duration_regressions} slower, "
+                f"{comparison.changed_screenshots} changed screenshots",
+            )
+            event_bus.publish("baseline:compared", {
+                "test_run_id": test_run.id,
+                "scenario_id": result.scenario_id,
+                "baseline_run_id": result.baseline.test_run_id,
+                **counts,
+            })
+
+    async def _log(
+        self, db: AsyncSession, test_run_id: str, level: str, message: str, step_index: Optional[int] = None
+    ) -> None:
+        """Write a line to the run's console log"""
+        entry = RunLogCreate(level=level, source=self.log_source, step_index=step_index, message=message)
+        await add_run_logs(db, test_run_id, [entry])
+
+    async def _load_steps(self, db: AsyncSession, scenario_id: str) -> List[Step]:
+        steps_result = await db.execute(
+            select(Step).where(Step.scenario_id == scenario_id).order_by(Step.step_order)
+        )
+        return steps_result.scalars().all()
+
+    async def _run_steps(self, db: AsyncSession, scenario: Scenario, test_run_id: str) -> tuple:
+        """Run a scenario's steps, returning (passed, failed, skipped) counts"""
+        steps = await self._load_steps(db, scenario.id)
+        test_case = await db.get(TestCase, scenario.test_case_id)
+        elements = await load_elements(db, test_case.project_id)
+        project = await db.get(Project, test_case.project_id)
+        self.test_run_id = test_run_id
+        self.env = json.loads(project.env_vars or "{}") if project else {}
+        self.implicit_wait_ms = await get_setting(db, "executor.implicit_wait_ms")
+        self.synonyms = await load_synonyms(db, test_case.project_id)
+        budget_action = await get_setting(db, "executor.budget_action")
+        results = StepResultWriter(
+            db, await get_setting(db, "executor.result_batch_size"), await get_setting(db, "executor.result_flush_ms")
+        )
+        options = await get_all_settings(db)
+        screenshot_policy = scenario.screenshot_policy or options["executor.screenshot_policy"]
+
+        passed = failed = skipped = 0
+        stopped = False
+        for index, step in enumerate(steps):
+            if stopped:
+                skipped += 1
+                continue
+
+            config = apply_element(json.loads(step.config or "{}"), elements, self.platform)
+            if self.debug:
+                config = await debug_sessions.before_step(test_run_id, step, index, config)
+            await self._log(db, test_run_id, "info", f"Step {index + 1}: {step.step_type}", index)
+            outcome = await self.execute_step(step.step_type, config)
+            budget = apply_step_budget(outcome, config, budget_action)
+            if budget and budget["action"] == "warn":
+                await self._log(db, test_run_id, "warning", budget_message("Step", budget), index)
+            if outcome.healed:
+                await self._log(
+                    db, test_run_id, "warning", f"Healed locator: {json.dumps(outcome.healed_locator)}", index
+                )
+            if outcome.status != "passed":
+                await self._log(db, test_run_id, "error", outcome.error_message or "Step failed", index)
+            screenshot = {}
+            if should_capture(screenshot_policy, outcome.status):
+                try:
+                    png = base64.b64decode(await self._ai_screenshot())
+                    screenshot = await asyncio.to_thread(save_step_screenshot, test_run_id, step.id, png, options)
+                except (StepExecutionError, OSError, ValueError) as e:
+                    await self._log(db, test_run_id, "warning", f"Screenshot not saved: {e}", index)
+            await results.add(
+                StepResultCreate(
+                    test_run_id=test_run_id,
+                    step_id=step.id,
+                    test_case_id=scenario.test_case_id,
+                    status=outcome.status,
+                    duration_ms=outcome.duration_ms,
+                    error_message=outcome.error_message,
+                    healed=outcome.healed,
+                    healed_locator=outcome.healed_locator,
+                    details=outcome.details,
+                    **screenshot,
+                )
+            )
+            if outcome.status == "passed":
+                passed += 1
+            else:
+                failed += 1
+                stopped = not outcome.soft
+        await results.flush()
+        return passed, failed, skipped
+
+    async def _skip_steps(self, db: AsyncSession, scenario: Scenario, test_run_id: str, reason: str) -> tuple:
+        """Record every step of a scenario as skipped"""
+        steps = await self._load_steps(db, scenario.id)
+        await insert_step_results(db, [
+            step_result_row(StepResultCreate(
+                test_run_id=test_run_id,
+                step_id=step.id,
+                test_case_id=scenario.test_case_id,
+                status="skipped",
+                duration_ms=0,
+                error_message=reason,
+            ))
+            for step in steps
+        ])
+        await db.commit()
+        return 0, 0, len(steps)
+
+    async def resolve_locators(self, scenario_id: str) -> LocatorResolution:
+        """
+        Resolve every step that only has an element description, one AI call per screen
+
+        The scenario is walked on the device so later screens can be reached.
+        Each newly seen screen is captured once and a single prompt resolves all
+        remaining descriptions visible on it. Resolved configs are saved together.
+        """
+        async with AsyncSessionLocal() as db:
+            steps_result = await db.execute(
+                select(Step).where(Step.scenario_id == scenario_id).order_by(Step.step_order)
+            )
+            steps = steps_result.scalars().all()
+            configs = {step.id: json.loads(step.config or "{}") for step in steps}
+
+            pending = [
+                step for step in steps
+                if step.step_type in TARGETED_STEP_TYPES
+                and configs[step.id].get("element_description")
+                and not configs[step.id].get("selector")
+                and (configs[step.id].get("x") is None or configs[step.id].get("y") is None)
+            ]
+            resolved: Dict[str, Dict[str, Any]] = {}
+            seen_screens = set()
+
+            for step in steps:
+                config = configs[step.id]
+                if step in pending and step.id not in resolved:
+                    screen, screenshot, nodes = await self._capture_screen()
+                    if screen not in seen_screens:
+                        seen_screens.add(screen)
+                        remaining = [s for s in pending if s.id not in resolved]
+                        resolved.update(await self._resolve_on_screen(remaining, configs, screenshot, nodes))
+                    if step.id not in resolved:
+                        # The flow can't continue past an element that wasn't found
+                        break
+                if step.id in resolved:
+                    config = {**config, **resolved[step.id]}
+                try:
+                    await self._perform(step.step_type, config)
+                except StepExecutionError:
+                    break
+
+            for step in steps:
+                if step.id in resolved:
+                    step.config = json.dumps({**configs[step.id], **resolved[step.id]})
+            await db.commit()
+
+        return LocatorResolution(
+            resolved_step_ids=[step.id for step in pending if step.id in resolved],
+            unresolved_step_ids=[step.id for step in pending if step.id not in resolved],
+            screens=len(seen_screens),
+        )
+
+    async def _capture_screen(self) -> tuple:
+        """Capture the current screen as (fingerprint, screenshot, UI nodes)"""
+        screenshot = await self._screenshot()
+        nodes: List[UiNode] = []
+        if self.platform == "android":
+            nodes = parse_ui_dump(await self._dump_ui())
+            screenshot = redact_screenshot(screenshot, nodes)
+
+        if nodes:
+            return layout_fingerprint(nodes), screenshot, nodes
+        return hashlib.sha256(screenshot.encode()).hexdigest(), screenshot, nodes
+
+    async def _resolve_on_screen(
+        self,
+        steps: List[Step],
+        configs: Dict[str, Dict[str, Any]],
+        screenshot: str,
+        nodes: List[UiNode],
+    ) -> Dict[str, Dict[str, Any]]:
+        """Ask the AI once for every description that is visible on the current screen"""
+        element_lines = "\n".join(
+            f"{index}. {configs[step.id]['element_description']}" for index, step in enumerate(steps)
+        )
+        node_lines = "\n".join(
+            f"- id={node.resource_id!r} text={node.text!r} desc={node.content_desc!r} "
+            f"bounds=[{node.left},{node.top}][{node.right},{node.bottom}]"
+            for node in nodes
+            if node.resource_id or node.text or node.content_desc
+        )
+
+        prompt = f"""You are locating elements on a mobile app screenshot.
+
+Elements to find:
+{element_lines}
+
+{f"UI hierarchy:{chr(10)}{node_lines}{chr(10)}" if node_lines else ""}
+Only report elements that are visible on this screen. Coordinates are the element's
+center in screenshot pixels. Use the element's resource id as "selector" when it has one.
+
+Respond with JSON only:
+{{"elements": [{{"index": 0, "found": true, "x": 0, "y": 0, "selector": null}}]}}"""
+
+        try:
+            result = await ask_ai_json(
+                prompt, "resolve_scenario_locators", image_base64=screenshot, purpose="element"
+            )
+        except AiClientError:
+            return {}
+
+        # Record the screen size so the coordinates scale to other devices
+        size = png_size(screenshot)
+        resolved = {}
+        for element in result.get("elements", []) if isinstance(result, dict) else []:
+            index = element.get("index")
+            if not isinstance(index, int) or not 0 <= index < len(steps) or not element.get("found"):
+                continue
+            locator: Dict[str, Any] = {}
+            if element.get("x") is not None and element.get("y") is not None:
+                locator.update(x=int(element["x"]), y=int(element["y"]), **DEVICE_PIXELS)
+                if size:
+                    locator.update(reference_width=size[0], reference_height=size[1])
+            if element.get("selector") and self.platform == "android":
+                locator["selector"] = element["selector"]
+            if locator:
+                resolved[steps[index].id] = locator
+        return resolved
+
+    async def execute_step(self, step_type: str, config: Dict[str, Any]) -> StepOutcome:
+        """Execute a single step, healing its locator once if it fails"""
+        start_time = time.time()
+        if step_type == ACCESSIBILITY_STEP_TYPE:
+            return await self._accessibility_check(config, start_time)
+        if step_type == VERIFY_STEP_TYPE:
+            return await self._check_assertions(step_assertions(config), config, start_time)
+        try:
+            await self._perform(step_type, config)
+            return await self._check_assertions(config.get("assertions") or [], config, start_time)
+        except StepExecutionError as e:
+            error = str(e)
+
+        healed_locator = await self._heal(step_type, config) if self.self_heal else None
+        if healed_locator:
+            try:
+                await self._perform(step_type, {**config, **healed_locator, **DEVICE_PIXELS, "selector": None})
+                outcome = await self._check_assertions(config.get("assertions") or [], config, start_time)
+                outcome.healed = True
+                outcome.healed_locator = healed_locator
+                return outcome
+            except StepExecutionError as e:
+                error = f"{error} (healed locator also failed: {e})"
+
+        return StepOutcome(
+            status="failed",
+            duration_ms=int((time.time() - start_time) * 1000),
+            error_message=error,
+        )
+
+    async def _check_assertions(
+        self, assertions: List[Dict[str, Any]], config: Dict[str, Any], start_time: float
+    ) -> StepOutcome:
+        """Evaluate a step's assertions; failures of soft assertions don't stop the scenario"""
+        if not assertions:
+            return StepOutcome(status="passed", duration_ms=int((time.time() - start_time) * 1000))
+        reader = DeviceValueReader(
+            self.platform, self._dump_ui, self._screenshot, self.env, self.ai_free, self.synonyms
+        )
+        try:
+            results = await run_assertions(assertions, reader, bool(config.get("soft")))
+        except StepExecutionError as e:
+            return StepOutcome(
+                status="failed", duration_ms=int((time.time() - start_time) * 1000), error_message=str(e)
+            )
+        outcome, error, details = summarize(results)
+        return StepOutcome(
+            status="passed" if outcome == "passed" else "failed",
+            duration_ms=int((time.time() - start_time) * 1000),
+            error_message=error,
+            details=details,
+            soft=outcome == "soft_failed",
+        )
+
+    async def _accessibility_check(self, config: Dict[str, Any], start_time: float) -> StepOutcome:
+        """Audit the current screen, failing on violations at the step's fail_on severity"""
+        if self.platform != "android":
+            return StepOutcome(
+                status="failed",
+                duration_ms=int((time.time() - start_time) * 1000),
+                error_message="Accessibility checks need an Android UI dump; iOS isn't supported yet",
+            )
+        try:
+            nodes = parse_ui_dump(await self._dump_ui())
+            screenshot = base64.b64decode(await self._screenshot())
+            density = await self._device_command(["shell", "wm", "density"])
+        except StepExecutionError as e:
+            return StepOutcome(
+                status="failed", duration_ms=int((time.time() - start_time) * 1000), error_message=str(e)
+            )
+
+        # "Physical density: 420", plus "Override density: ..." when one is set
+        densities = [line.split(":", 1)[1].strip() for line in density.splitlines() if "density:" in line]
+        density_dpi = int(densities[-1]) if densities and densities[-1].isdigit() else 160
+        passed, details = evaluate_violations(audit_android(nodes, screenshot, density_dpi), config)
+        return StepOutcome(
+            status="passed" if passed else "failed",
+            duration_ms=int((time.time() - start_time) * 1000),
+            error_message=None if passed else failure_message(details),
+            details=details,
+        )
+
+    async def _heal(self, step_type: str, config: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+        """Find the step's element again from a fresh screenshot"""
+        if step_type in UNTARGETED_STEP_TYPES or plugin_registry.for_step_type(step_type):
+            return None  # Shell, device state and plugin steps have no locator to heal
+        if step_type == "tap_element":
+            return None  # Resolving the element already falls back to the AI
+        if self.ai_free:
+            return await self._heal_offline(config)
+        try:
+            screenshot = await self._ai_screenshot()
+        except StepExecutionError:
+            return None
+        return await heal_locator(step_type, config, screenshot)
+
+    async def _heal_offline(self, config: Dict[str, Any]) -> Optional[Dict[str, Any]]:
+        """Find the step's element again from a fresh UI dump"""
+        description = config.get("element_description")
+        if not description or self.platform != "android":
+            return None
+        try:
+            point = await self._find_offline(description)
+        except StepExecutionError:
+            return None
+        if not point:
+            return None
+        return {"x": point[0], "y": point[1]}
+
+    async def _find_offline(self, description: str) -> Optional[tuple]:
+        match = find_element_from_ui_dump(parse_ui_dump(await self._dump_ui()), description, synonyms=self.synonyms)
+        return match[0].center if match else None
+
+    async def _perform(self, step_type: str, config: Dict[str, Any]) -> None:
+        if step_type == "tap":
+            x, y = await self._resolve_point(config)
+            await self._tap(x, y)
+        elif step_type == "tap_element":
+            x, y = await self._resolve_element(config)
+            await self._tap(x, y)
+        elif step_type == "swipe":
+            x, y = await self._resolve_point(config)
+            if config.get("x2") is None or config.get("y2") is None:
+                raise StepExecutionError("Missing end coordinates for swipe step")
+            x2, y2 = await self._device_point(config, "x2", "y2")
+            await self._swipe(x, y, x2, y2, int(config.get("duration") or 300))
+        elif step_type == "input":
+            if not config.get("value"):
+                raise StepExecutionError("Missing value for input step")
+            await self._input_text(config["value"], config.get("input_method") or "auto")
+        elif step_type == "back":
+            # iOS has no hardware back button
+            if self.platform == "android":
+                await self._device_command(["shell", "input", "keyevent", "4"])
+        elif step_type == "home":
+            if self.platform == "android":
+                await self._device_command(["shell", "input", "keyevent", "3"])
+        elif step_type == "launch":
+            package = config.get("packageName")
+            if not package:
+                raise StepExecutionError("Missing package name for launch step")
+            if self.platform == "ios" and is_physical_ios(self.device_id):
+                try:
+                    await ios_launch_app(self.device_id, AppRequest(package=package))
+                except HTTPException as e:
+                    raise StepExecutionError(str(e.detail))
+            elif self.platform == "ios":
+                await self._device_command(["launch", self.device_id, package])
+            else:
+                await self._device_command(
+                    ["shell", "monkey", "-p", package, "-c", "android.intent.category.LAUNCHER", "1"]
+                )
+        elif step_type == "wait":
+            await asyncio.sleep((config.get("timeout") or config.get("duration") or 1000) / 1000)
+        elif step_type in WAIT_STEP_TYPES:
+            await self._wait(step_type, config)
+        elif step_type in STATE_STEP_TYPES:
+            try:
+                commands = STATE_STEP_TYPES[step_type](self.platform, self.device_id, config)
+            except DeviceStateError as e:
+                raise StepExecutionError(str(e))
+            for command in commands:
+                await self._device_command(command.args, command.input)
+                if command.delay:
+                    await asyncio.sleep(command.delay)
+        elif step_type == SHELL_STEP_TYPE:
+            try:
+                await run_shell_step(config, self.env, self.test_run_id)
+            except ShellStepError as e:
+                raise StepExecutionError(str(e))
+        elif plugin_registry.for_step_type(step_type):
+            context = {"device_id": self.device_id, "platform": self.platform}
+            try:
+                await plugin_registry.execute(step_type, config, context)
+            except (PluginError, asyncio.TimeoutError) as e:
+                raise StepExecutionError(str(e) or f"Plugin step {step_type} timed out")
+        else:
+            raise StepExecutionError(f"Unsupported step type for mobile execution: {step_type}")
+
+    async def _resolve_point(self, config: Dict[str, Any]) -> tuple:
+        """Resolve a step's target point from its selector, stored coordinates, or description"""
+        selector = config.get("selector")
+        if selector and self.platform == "android":
+            node = await self._await_node({"selector": selector}, self._implicit_wait(config))
+            if not node:
+                raise StepExecutionError(f"Element not found: {selector}")
+            return node.center
+
+        if config.get("x") is None or config.get("y") is None:
+            description = config.get("element_description")
+            if self.ai_free and description and self.platform == "android":
+                node = await self._await_node({"element_description": description}, self._implicit_wait(config))
+                if not node:
+                    raise StepExecutionError(f"Element not found: {description}")
+                return node.center
+            raise StepExecutionError("Missing coordinates for step")
+        return await self._device_point(config, "x", "y")
+
+    async def _resolve_element(self, config: Dict[str, Any]) -> tuple:
+        """
+        Find a tap_element step's element on the current screen
+
+        The descriptor is matched against the UI dump, waiting like other taps.
+        When it isn't found, or there's no UI dump on iOS, the AI looks for it
+        on a screenshot unless the project is AI-free.
+        """
+        descriptor = element_descriptor(config)
+        description = config.get("element_description")
+        if not descriptor and not description:
+            raise StepExecutionError("Missing resource_id, text, content_desc or element_description for step")
+        label = description or ", ".join(f"{key}={value!r}" for key, value in descriptor.items())
+
+        if self.platform == "android":
+            lookup = {"descriptor": descriptor, "index": int(config.get("index") or 0)}
+            if not descriptor:
+                lookup = {"element_description": description}
+            node = await self._await_node(lookup, self._implicit_wait(config))
+            if node:
+                return node.center
+        if self.ai_free:
+            raise StepExecutionError(f"Element not found: {label}")
+
+        details = ", ".join(f"{key.replace('_', ' ')} {value!r}" for key, value in descriptor.items())
+        located = await heal_locator(
+            "tap_element",
+            {"element_description": f"{description} ({details})" if description and details else label},
+            await self._ai_screenshot(),
+        )
+        if not located or located.get("x") is None or located.get("y") is None:
+            raise StepExecutionError(f"Element not found: {label}")
+        return await self._device_point({**located, **DEVICE_PIXELS}, "x", "y")
+
+    def _implicit_wait(self, config: Dict[str, Any]) -> int:
+        """A step's wait_timeout overrides the implicit wait setting; 0 looks once"""
+        if config.get("wait_timeout") is not None:
+            return int(config["wait_timeout"])
+        return self.implicit_wait_ms
+
+    async def _find_node(self, config: Dict[str, Any]) -> Optional[UiNode]:
+        nodes = parse_ui_dump(await self._dump_ui())
+        if config.get("selector"):
+            return find_node_by_selector(nodes, config["selector"])
+        if config.get("descriptor"):
+            return find_node_by_descriptor(nodes, config["descriptor"], config.get("index", 0))
+        match = find_element_from_ui_dump(nodes, config["element_description"], synonyms=self.synonyms)
+        return match[0] if match else None
+
+    async def _await_node(self, config: Dict[str, Any], timeout_ms: int) -> Optional[UiNode]:
+        """Poll UI dumps until the element appears, returning None if it never does"""
+        found: Optional[UiNode] = None
+
+        async def appeared() -> bool:
+            nonlocal found
+            found = await self._find_node(config)
+            return found is not None
+
+        await poll_until(appeared, timeout_ms)
+        return found
+
+    async def _wait(self, step_type: str, config: Dict[str, Any]) -> None:
+        """Poll until a wait step's condition holds, failing once its timeout passes"""
+        timeout_ms = wait_timeout_ms(config)
+        if step_type == "wait_for_url":
+            raise StepExecutionError("wait_for_url only applies to web scenarios")
+        if step_type == "wait_for_network_idle":
+            met = await self._wait_network_idle(config, timeout_ms)
+            waited_for = "network to go idle"
+        elif step_type == "wait_for_activity":
+            met, waited_for = await self._wait_activity(config, timeout_ms)
+        elif step_type == "wait_for_text":
+            met, waited_for = await self._wait_text(config, timeout_ms)
+        else:
+            if self.platform != "android":
+                raise StepExecutionError("Element waits need an Android UI dump; use wait_for_text on iOS")
+            target = config.get("selector") or config.get("element_description")
+            if not target:
+                raise StepExecutionError(f"Missing selector or element_description for {step_type} step")
+            if step_type == "wait_for_element":
+                met = await self._await_node(config, timeout_ms) is not None
+                waited_for = f"{target} to appear"
+            else:
+                async def gone() -> bool:
+                    return await self._find_node(config) is None
+
+                met = await poll_until(gone, timeout_ms)
+                waited_for = f"{target} to disappear"
+        if not met:
+            raise StepExecutionError(f"Timed out after {timeout_ms}ms waiting for {waited_for}")
+
+    async def _wait_text(self, config: Dict[str, Any], timeout_ms: int) -> Tuple[bool, str]:
+        text = config.get("text") or config.get("value")
+        if not text:
+            raise StepExecutionError("Missing text for wait_for_text step")
+
+        async def shown() -> bool:
+            reader = DeviceValueReader(
+                self.platform, self._dump_ui, self._screenshot, self.env, self.ai_free, self.synonyms
+            )
+            try:
+                return text in await reader.screen_text()
+            except AssertionTargetError as e:
+                raise StepExecutionError(str(e))
+
+        # Reading iOS screens goes through the AI, so poll less often there
+        met = await poll_until(shown, timeout_ms, POLL_INTERVAL if self.platform == "android" else 2.0)
+        return met, f"text '{text}'"
+
+    async def _wait_activity(self, config: Dict[str, Any], timeout_ms: int) -> Tuple[bool, str]:
+        activity = config.get("activity")
+        if not activity:
+            raise StepExecutionError("Missing activity for wait_for_activity step")
+        if self.platform != "android":
+            raise StepExecutionError("wait_for_activity is only supported on Android")
+
+        async def resumed() -> bool:
+            output = await self._device_command(["shell", "dumpsys", "activity", "activities"])
+            return activity in (resumed_activity(output) or "")
+
+        return await poll_until(resumed, timeout_ms), f"activity {activity}"
+
+    async def _wait_network_idle(self, config: Dict[str, Any], timeout_ms: int) -> bool:
+        """Wait until the device moves almost no traffic for idle_ms"""
+        if self.platform != "android":
+            raise StepExecutionError("wait_for_network_idle is only supported on Android")
+        idle_ms = int(config.get("idle_ms") or DEFAULT_IDLE_MS)
+        last = network_bytes(await self._device_command(["shell", "cat", "/proc/net/dev"]))
+
+        async def quiet() -> bool:
+            nonlocal last
+            await asyncio.sleep(idle_ms / 1000)
+            current = network_bytes(await self._device_command(["shell", "cat", "/proc/net/dev"]))
+            moved, last = current - last, current
+            return moved < IDLE_BYTES_THRESHOLD
+
+        return await poll_until(quiet, timeout_ms, interval=0)
+
+    async def _device_point(self, config: Dict[str, Any], x_key: str, y_key: str) -> tuple:
+        """Convert stored coordinates to pixels on this device's screen"""
+        mode = config.get("coordinate_mode") or ABSOLUTE
+        if mode not in (ABSOLUTE, NORMALIZED):
+            raise StepExecutionError(f"Unknown coordinate mode: {mode}")
+        needs_screen = mode == NORMALIZED or (config.get("reference_width") and config.get("reference_height"))
+        screen = await self._screen_size() if needs_screen else None
+        try:
+            return to_device_point(config, x_key, y_key, screen)
+        except ValueError as e:
+            raise StepExecutionError(str(e))
+
+    async def _screen_size(self) -> Optional[Tuple[int, int]]:
+        """The device's screen size in pixels, read once per executor"""
+        if self._screen:
+            return self._screen
+        if self.platform == "android":
+            output = await self._device_command(["shell", "wm", "size"])
+            # "Physical size: 1080x2400", plus "Override size: ..." when one is set
+            sizes = [line.split(":", 1)[1].strip() for line in output.splitlines() if "size:" in line]
+            if sizes:
+                width, height = sizes[-1].split("x")
+                self._screen = (int(width), int(height))
+        else:
+            self._screen = png_size(await self._screenshot())
+        return self._screen
+
+    async def _tap(self, x: int, y: int) -> None:
+        if self.platform == "ios":
+            await self._ios_input(ios_tap(self.device_id, TapRequest(x=x, y=y)))
+        else:
+            await self._device_command(["shell", "input", "tap", str(x), str(y)])
+
+    async def _swipe(self, x: int, y: int, x2: int, y2: int, duration_ms: int) -> None:
+        if self.platform == "ios":
+            request = SwipeRequest(start_x=x, start_y=y, end_x=x2, end_y=y2, duration_ms=duration_ms)
+            await self._ios_input(ios_swipe(self.device_id, request))
+        else:
+            await self._device_command(
+                ["shell", "input", "swipe", str(x), str(y), str(x2), str(y2), str(duration_ms)]
+            )
+
+    async def _input_text(self, text: str, method: str = "auto") -> None:
+        if self.platform == "ios":
+            await self._ios_input(ios_input_text(self.device_id, InputTextRequest(text=text)))
+            return
+        try:
+            await android_type_text(self.device_id, text, method)
+        except HTTPException as e:
+            raise StepExecutionError(str(e.detail))
+
+    async def _ios_input(self, action) -> None:
+        """Await an iOS input endpoint, which uses idb when available"""
+        try:
+            await action
+        except HTTPException as e:
+            raise StepExecutionError(str(e.detail))
+
+    async def _dump_ui(self) -> str:
+        await self._device_command(["shell", "uiautomator", "dump", "/sdcard/ui.xml"])
+        output = await self._device_command(["shell", "cat", "/sdcard/ui.xml"])
+        await self._device_command(["shell", "rm", "/sdcard/ui.xml"])
+        return output
+
+    async def _screenshot(self) -> str:
+        try:
+            if self.platform == "ios":
+                return (await ios_screenshot(self.device_id)).screenshot
+            return (await android_screenshot(self.device_id)).screenshot
+        except HTTPException as e:
+            raise StepExecutionError(str(e.detail))
+
+    async def screenshot(self) -> str:
+        """The device's current screen as base64 PNG"""
+        return await self._screenshot()
+
+    async def inspect(self) -> Tuple[str, str]:
+        """The device's current screenshot, base64 PNG, and UI dump XML"""
+        return await self._screenshot(), await self._dump_ui()
+
+    async def _ai_screenshot(self) -> str:
+        """A screenshot to send to the AI, with elements showing secret values blacked out on Android"""
+        screenshot = await self._screenshot()
+        if self.platform == "android" and secret_values():
+            screenshot = redact_screenshot(screenshot, parse_ui_dump(await self._dump_ui()))
+        return screenshot
+
+    async def _device_command(self, args: List[str], input: Optional[bytes] = None) -> str:
+        """Run an adb (Android) or simctl (iOS) command, mapping errors to step failures"""
+        try:
+            if self.platform == "ios":
+                return await run_xcrun_command(args, self.device_id, input=input)
+            return await run_adb_command(args, self.device_id, input=input)
+        except HTTPException as e:
+            raise StepExecutionError(str(e.detail))
+
+
+async def _run_safely(
+    executor: ScenarioExecutor,
+    scenario_ids: List[str],
+    test_run_id: str,
+    prerequisites: Optional[Dict[str, List[str]]] = None,
+) -> None:
+    """Run scenarios, marking the test run as failed if execution crashes"""
+    try:
+        await executor.run_scenarios(scenario_ids, test_run_id, prerequisites)
+    except asyncio.CancelledError:
+        async with AsyncSessionLocal() as db:
+            test_run = await db.get(TestRun, test_run_id)
+            if test_run:
+                try:
+                    await cancel_run(db, test_run)
+                except RunTransitionError:
+                    pass  # Finished before the cancel arrived
+        raise
+    except Exception:
+        async with AsyncSessionLocal() as db:
+            test_run = await db.get(TestRun, test_run_id)
+            if test_run and test_run.status == "running":
+                try:
+                    await fail_run(db, test_run, "Execution crashed")
+                except RunTransitionError:
+                    pass
+        raise
+
+
+def start_scenario_run(
+    scenario_id: str,
+    test_run_id: str,
+    device_id: str,
+    platform: str,
+    self_heal: bool = True,
+    ai_free: bool = False,
+    priority: str = "normal",
+    debug: bool = False,
+) -> None:
+    """Queue a scenario to run in the background"""
+    start_scenarios_run(
+        [scenario_id], test_run_id, device_id, platform,
+        self_heal=self_heal, ai_free=ai_free, priority=priority, debug=debug,
+    )
+
+
+def start_scenarios_run(
+    scenario_ids: List[str],
+    test_run_id: str,
+    device_id: str,
+    platform: str,
+    self_heal: bool = True,
+    ai_free: bool = False,
+    prerequisites: Optional[Dict[str, List[str]]] = None,
+    priority: str = "normal",
+    debug: bool = False,
+) -> None:
+    """Queue scenarios to run in order as one test run in the background"""
+    executor = ScenarioExecutor(device_id, platform, self_heal=self_heal, ai_free=ai_free, debug=debug)
+    start_executor_run(executor, scenario_ids, test_run_id, prerequisites, priority)
+
+
+def start_executor_run(
+    executor: ScenarioExecutor,
+    scenario_ids: List[str],
+    test_run_id: str,
+    prerequisites: Optional[Dict[str, List[str]]] = None,
+    priority: str = "normal",
+) -> None:
+    """Queue an executor's run; it starts once its device or the browser has capacity"""
+    run_queue.enqueue(
+        test_run_id,
+        executor.device_id,
+        executor.platform,
+        lambda: _run_safely(executor, scenario_ids, test_run_id, prerequisites),
+        priority,
+    )
+
+
+async def cancel_scenario_run(test_run_id: str) -> bool:
+    """Cancel a queued or running scenario run, returning False if there's none"""
+    return await run_queue.cancel(test_run_id)
+
+
+async def cancel_device_runs(device_id: str) -> int:
+    """Cancel every queued and running scenario run on a device, returning how many were cancelled"""
+    return await run_queue.cancel_device(device_id)
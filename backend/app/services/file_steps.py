@@ -0,0 +1,153 @@
+"""
+File Steps - Uploads fixture files to file inputs and checks what a page downloads
+
+Fixtures are looked up in the fixtures folder next to the database unless a
+step gives an absolute path. Downloads land in a folder of the run's own, so
+verify_download only ever sees files the scenario itself downloaded.
+"""
+import json
+import re
+from pathlib import Path
+from typing import Any, Dict, List
+
+from ..config import settings
+
+FILE_STEP_TYPES = ("upload_file", "verify_download")
+
+DEFAULT_DOWNLOAD_TIMEOUT_MS = 30000
+
+# Polls a folder for a finished download and returns its name, size and SHA-256, or null at the timeout
+FIND_DOWNLOAD_JS = (
+    "async ({ dir, filename, pattern, timeout }) => {"
+    " const fs = require('fs'), path = require('path'), crypto = require('crypto');"
+    " const deadline = Date.now() + timeout;"
+    " for (;;) {"
+    " const names = fs.existsSync(dir) ? fs.readdirSync(dir) : [];"
+    " const name = names.find((n) => !/\\.(crdownload|part|tmp)$/.test(n)"
+    " && (filename ? n === filename : new RegExp(pattern || '.').test(n)));"
+    " if (name) { const bytes = fs.readFileSync(path.join(dir, name));"
+    " return { name, size: bytes.length, sha256: crypto.createHash('sha256').update(bytes).digest('hex') }; }"
+    " if (Date.now() > deadline) return null;"
+    " await new Promise((resolve) => setTimeout(resolve, 250)); } }"
+)
+
+
+class FileStepError(Exception):
+    """Raised for file steps with missing settings or fixtures that don't exist"""
+
+
+def fixtures_dir() -> Path:
+    return settings.get_database_path().parent / "fixtures"
+
+
+def resolve_fixture(name: str) -> Path:
+    """A fixture's absolute path; relative names can't leave the fixtures folder"""
+    path = Path(name).expanduser()
+    if not path.is_absolute():
+        root = fixtures_dir().resolve()
+        path = (root / path).resolve()
+        if root not in path.parents:
+            raise FileStepError(f"{name} is outside the fixtures folder")
+    if not path.is_file():
+        raise FileStepError(f"Fixture not found: {path}")
+    return path
+
+
+def fixture_names(config: Dict[str, Any]) -> List[str]:
+    files = config.get("files") or config.get("file") or config.get("value")
+    return [files] if isinstance(files, str) else list(files or [])
+
+
+def check_file_step(step_type: str, config: Dict[str, Any]) -> List[Path]:
+    """Raise FileStepError when a step is missing what it needs; returns an upload's fixture paths"""
+    if step_type == "upload_file":
+        if not config.get("selector"):
+            raise FileStepError("Set the file input's selector")
+        names = fixture_names(config)
+        if not names:
+            raise FileStepError("Set the fixture file to upload")
+        return [resolve_fixture(name) for name in names]
+    if step_type == "verify_download":
+        if config.get("pattern"):
+            try:
+                re.compile(config["pattern"])
+            except re.error as e:
+                raise FileStepError(f"Invalid filename pattern: {e}")
+        for key in ("min_bytes", "max_bytes", "timeout"):
+            try:
+                if config.get(key) is not None and int(config[key]) < 0:
+                    raise FileStepError(f"{key} can't be negative")
+            except (TypeError, ValueError):
+                raise FileStepError(f"{key} must be a whole number")
+    return []
+
+
+def _download_match(config: Dict[str, Any]) -> Dict[str, Any]:
+    return {
+        "filename": config.get("filename") or None,
+        "pattern": config.get("pattern") or None,
+        "timeout": int(config.get("timeout") or DEFAULT_DOWNLOAD_TIMEOUT_MS),
+    }
+
+
+def _describe(config: Dict[str, Any]) -> str:
+    return config.get("filename") or (f"/{config['pattern']}/" if config.get("pattern") else "any file")
+
+
+# ============================================
+# Browser Specs
+# ============================================
+
+
+def playwright_download_prelude() -> List[str]:
+    """Save every tab's downloads under the spec's folder so verify_download can find them"""
+    return [
+        "const downloadsDir = require('path').join(__dirname, 'downloads');",
+        "const saveDownloads = (tab) => tab.on('download', (download) => "
+        "download.saveAs(require('path').join(downloadsDir, download.suggestedFilename())));",
+        "saveDownloads(page);",
+        "page.context().on('page', saveDownloads);",
+        f"const findDownload = {FIND_DOWNLOAD_JS};",
+    ]
+
+
+def playwright_file_lines(step_type: str, config: Dict[str, Any]) -> List[str]:
+    paths = check_file_step(step_type, config)
+    if step_type == "upload_file":
+        files = json.dumps([str(path) for path in paths])
+        return [f"await page.setInputFiles({json.dumps(config['selector'])}, {files});"]
+    match = _download_match(config)
+    lines = [
+        "{",
+        f"  const download = await findDownload({{ dir: downloadsDir, ...{json.dumps(match)} }});",
+        f"  expect(download, {json.dumps(f'No download matching {_describe(config)}')}).toBeTruthy();",
+    ]
+    if config.get("min_bytes") is not None:
+        lines.append(f"  expect(download.size).toBeGreaterThanOrEqual({int(config['min_bytes'])});")
+    if config.get("max_bytes") is not None:
+        lines.append(f"  expect(download.size).toBeLessThanOrEqual({int(config['max_bytes'])});")
+    if config.get("sha256"):
+        lines.append(f"  expect(download.sha256).toBe({json.dumps(config['sha256'].lower())});")
+    return lines + ["}"]
+
+
+def cypress_file_lines(step_type: str, config: Dict[str, Any]) -> List[str]:
+    paths = check_file_step(step_type, config)
+    if step_type == "upload_file":
+        files = json.dumps([str(path) for path in paths])
+        # Styled upload buttons often hide the real input
+        return [f"cy.get({json.dumps(config['selector'])}).selectFile({files}, {{ force: true }});"]
+    match = _download_match(config)
+    # The task polls until the download appears, so it gets longer than the step's timeout
+    task_timeout = match["timeout"] + 5000
+    lines = [
+        f"cy.task('findDownload', {json.dumps(match)}, {{ timeout: {task_timeout} }}).then((download) => {{",
+        f"  expect(download, {json.dumps(f'No download matching {_describe(config)}')}).to.be.ok;",
+    ]
+    if config.get("min_bytes") is not None:
+        lines.append(f"  expect(download.size).to.be.at.least({int(config['min_bytes'])});")
+    if config.get("max_bytes") is not None:
+        lines.append(f"  expect(download.size).to.be.at.most({int(config['max_bytes'])});")
+    if config.get("sha256"):
+        lines.append(f"  expect(download.sha256).to.equal({json.dumps(config['sha256'].lower())});")
+    return lines + ["});"]
@@ -0,0 +1,76 @@
+"""
+Generated Tests - Saves the AI agent's generated tests as test cases of a project
+
+Each generated test becomes a test case in the "AI-generated" category, with
+its code kept alongside in generated_test_code together with the source
+snippet and commit it was generated from. Regenerating a test with the same
+name adds the new code to the existing test case instead of duplicating it.
+"""
+from typing import List
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import GeneratedTestCode, SaveGeneratedTests, TestCase
+
+AI_GENERATED_CATEGORY = "AI-generated"
+
+
+class GeneratedTestsError(Exception):
+    """Raised when there are no tests to save"""
+
+
+async def save_generated_tests(db: AsyncSession, project_id: str, data: SaveGeneratedTests) -> List[TestCase]:
+    """Create or update a test case per generated test and attach its code, returning the test cases"""
+    if not data.response.tests:
+        raise GeneratedTestsError("The response has no tests to save")
+
+    existing = {
+        test_case.name: test_case
+        for test_case in (await db.execute(
+            select(TestCase).where(TestCase.project_id == project_id, TestCase.category == AI_GENERATED_CATEGORY)
+        )).scalars().all()
+    }
+
+    test_cases = []
+    for test in data.response.tests:
+        test_case = existing.get(test.name)
+        if test_case:
+            test_case.description = test.description
+        else:
+            test_case = TestCase(
+                project_id=project_id,
+                name=test.name,
+                description=test.description,
+                category=AI_GENERATED_CATEGORY,
+                test_type="Automated",
+            )
+            db.add(test_case)
+            await db.flush()
+            existing[test.name] = test_case
+        db.add(GeneratedTestCode(
+            test_case_id=test_case.id,
+            code=test.code,
+            test_type=test.test_type,
+            language=data.language,
+            framework=data.framework,
+            source_code=data.source_code,
+            source_ref=data.source_ref,
+        ))
+        if test_case not in test_cases:
+            test_cases.append(test_case)
+
+    await db.commit()
+    for test_case in test_cases:
+        await db.refresh(test_case)
+    return test_cases
+
+
+async def list_generated_code(db: AsyncSession, test_case_id: str) -> List[GeneratedTestCode]:
+    """A test case's generated code, newest first"""
+    result = await db.execute(
+        select(GeneratedTestCode)
+        .where(GeneratedTestCode.test_case_id == test_case_id)
+        .order_by(GeneratedTestCode.created_at.desc())
+    )
+    return list(result.scalars().all())
@@ -0,0 +1,311 @@
+"""
+GitHub Webhooks - Runs the tests affected by pushes and pull requests and reports back as checks
+
+A repository webhook posts push and pull_request events to /api/webhooks/github,
+signed with GITHUB_WEBHOOK_SECRET. For every project whose repo_url is that
+repository, change-based test selection picks the scenarios covering the
+diff, and they're queued as one test run on the target in the webhooks.*
+settings. A GitHub check run on the head commit follows the run and is
+completed with its outcome; creating check runs needs a GITHUB_TOKEN that
+may write checks, such as a GitHub App installation token.
+"""
+import asyncio
+import hashlib
+import hmac
+import uuid
+from dataclasses import dataclass
+from typing import Any, Dict, List, Optional
+
+import httpx
+from sqlalchemy import select
+from sqlalchemy.exc import OperationalError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from ..models import Project, TestRun, WebhookRun
+from .app_settings import get_all_settings
+from .dependencies import load_prerequisites
+from .events import event_bus
+from .executor import ScenarioExecutor, start_executor_run
+from .run_lifecycle import FINISHED_STATUSES
+from .test_runner import TestFramework
+from .test_selection import GITHUB_REPO, TestSelectionError, suggest_tests_for_diff
+from .web_executor import WebScenarioExecutor
+
+CHECK_NAME = "AutoTest AI"
+
+# Pull request actions that change the code under review
+PULL_REQUEST_ACTIONS = {"opened", "synchronize", "reopened"}
+
+NULL_SHA = "0" * 40
+
+CHECK_CONCLUSIONS = {
+    "passed": "success",
+    "passed_with_warnings": "success",
+    "failed": "failure",
+    "cancelled": "cancelled",
+    "aborted": "cancelled",
+}
+
+
+class WebhookError(Exception):
+    """Raised for deliveries without a valid signature and failed Checks API calls"""
+
+
+@dataclass
+class Change:
+    """The commits a push or pull request compares"""
+    event: str
+    owner: str
+    repo: str
+    base: str
+    head: str
+    label: str  # Names the test run, e.g. "PR #12" or "push to main"
+    pull_number: Optional[int] = None
+
+
+def verify_signature(body: bytes, signature: Optional[str]) -> None:
+    """Check a delivery's X-Hub-Signature-256 against GITHUB_WEBHOOK_SECRET"""
+    secret = settings.github_webhook_secret
+    if not secret:
+        raise WebhookError("GitHub webhooks are off; set GITHUB_WEBHOOK_SECRET to turn them on")
+    expected = "sha256=" + hmac.new(secret.encode(), body, hashlib.sha256).hexdigest()
+    if not signature or not hmac.compare_digest(expected, signature):
+        raise WebhookError("Invalid webhook signature")
+
+
+def parse_change(event: str, payload: Dict[str, Any]) -> Optional[Change]:
+    """The change a delivery describes, or None for events and actions that don't trigger runs"""
+    repository = payload.get("repository") or {}
+    owner, _, repo = (repository.get("full_name") or "").partition("/")
+    if not (owner and repo):
+        return None
+
+    if event == "push":
+        base, head = payload.get("before"), payload.get("after")
+        # New branches have nothing to compare against, and deleted ones nothing to test
+        if not base or not head or NULL_SHA in (base, head):
+            return None
+        branch = (payload.get("ref") or "").removeprefix("refs/heads/")
+        return Change(event, owner, repo, base, head, f"push to {branch}")
+
+    if event == "pull_request" and payload.get("action") in PULL_REQUEST_ACTIONS:
+        pull_request = payload.get("pull_request") or {}
+        base = (pull_request.get("base") or {}).get("sha")
+        head = (pull_request.get("head") or {}).get("sha")
+        if not base or not head:
+            return None
+        number = pull_request.get("number")
+        return Change(event, owner, repo, base, head, f"PR #{number}", number)
+
+    return None
+
+
+async def _linked_projects(db: AsyncSession, owner: str, repo: str) -> List[Project]:
+    result = await db.execute(select(Project).where(Project.repo_url.is_not(None)))
+    linked = []
+    for project in result.scalars().all():
+        match = GITHUB_REPO.search(project.repo_url)
+        if match and (match.group(1).lower(), match.group(2).lower()) == (owner.lower(), repo.lower()):
+            linked.append(project)
+    return linked
+
+
+# ============================================
+# Checks API
+# ============================================
+
+
+async def _checks_request(method: str, path: str, body: Dict[str, Any]) -> Dict[str, Any]:
+    if not settings.github_token:
+        raise WebhookError("GITHUB_TOKEN is not configured")
+    try:
+        async with httpx.AsyncClient(timeout=30.0) as client:
+            response = await client.request(
+                method,
+                f"https://api.github.com{path}",
+                json=body,
+                headers={
+                    "Authorization": f"token {settings.github_token}",
+                    "Accept": "application/vnd.github+json",
+                },
+            )
+            response.raise_for_status()
+            return response.json()
+    except httpx.HTTPError as e:
+        raise WebhookError(f"GitHub Checks API error: {str(e)}")
+
+
+async def _create_check(change: Change, **fields: Any) -> int:
+    data = await _checks_request(
+        "POST",
+        f"/repos/{change.owner}/{change.repo}/check-runs",
+        {"name": CHECK_NAME, "head_sha": change.head, **fields},
+    )
+    return data["id"]
+
+
+async def complete_check(webhook_run: WebhookRun, test_run: TestRun) -> None:
+    """Complete a webhook run's check run with the test run's outcome"""
+    summary = f"{test_run.passed} passed, {test_run.failed} failed, {test_run.skipped} skipped"
+    if test_run.expected_failed:
+        summary += f" ({test_run.expected_failed} expected failures)"
+    await _checks_request(
+        "PATCH",
+        f"/repos/{webhook_run.repository}/check-runs/{webhook_run.check_run_id}",
+        {
+            "status": "completed",
+            "conclusion": CHECK_CONCLUSIONS.get(test_run.status, "neutral"),
+            "output": {"title": f"{test_run.name}: {test_run.status}", "summary": summary},
+        },
+    )
+
+
+# ============================================
+# Triggering
+# ============================================
+
+
+async def _start_selected_run(
+    db: AsyncSession, project: Project, change: Change, scenario_ids: List[str], options: Dict[str, Any]
+) -> TestRun:
+    if options["webhooks.target"] == "device":
+        if not options["webhooks.device_id"]:
+            raise ValueError("Set webhooks.device_id to run webhook-triggered tests on a device")
+        executor = ScenarioExecutor(
+            options["webhooks.device_id"], options["webhooks.platform"], ai_free=project.ai_free
+        )
+    else:
+        executor = WebScenarioExecutor(TestFramework(options["webhooks.framework"]), options["webhooks.browser"], True)
+
+    test_run = TestRun(id=str(uuid.uuid4()), project_id=project.id, name=f"{change.label} ({change.head[:7]})")
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    prerequisites = await load_prerequisites(db)
+    planned = {
+        scenario_id: [p for p in prerequisites.get(scenario_id, []) if p in scenario_ids[:index]]
+        for index, scenario_id in enumerate(scenario_ids)
+    }
+    start_executor_run(executor, scenario_ids, test_run.id, planned)
+    return test_run
+
+
+async def _trigger_project(
+    db: AsyncSession, project: Project, change: Change, options: Dict[str, Any]
+) -> WebhookRun:
+    webhook_run = WebhookRun(
+        project_id=project.id,
+        event=change.event,
+        repository=f"{change.owner}/{change.repo}",
+        base_sha=change.base,
+        head_sha=change.head,
+        pull_number=change.pull_number,
+    )
+    db.add(webhook_run)
+    try:
+        selection = await suggest_tests_for_diff(db, project.id, change.base, change.head)
+        scenario_ids = list(dict.fromkeys(s.scenario_id for s in selection.scenarios))
+        if scenario_ids:
+            test_run = await _start_selected_run(db, project, change, scenario_ids, options)
+            webhook_run.test_run_id = test_run.id
+    except (TestSelectionError, ValueError) as e:
+        webhook_run.error = str(e)
+        scenario_ids = []
+
+    if options["webhooks.report_checks"]:
+        try:
+            if webhook_run.test_run_id:
+                webhook_run.check_run_id = await _create_check(
+                    change,
+                    status="in_progress",
+                    output={"title": "Running affected tests", "summary": f"{len(scenario_ids)} scenarios queued"},
+                )
+            else:
+                await _create_check(
+                    change,
+                    status="completed",
+                    conclusion="neutral",
+                    output={
+                        "title": "No affected tests",
+                        "summary": webhook_run.error or "No mapped test cases cover the changed files",
+                    },
+                )
+        except WebhookError as e:
+            webhook_run.error = str(e)
+
+    await db.commit()
+    await db.refresh(webhook_run)
+    event_bus.publish("webhook:triggered", {
+        "project_id": project.id,
+        "test_run_id": webhook_run.test_run_id,
+        "head_sha": change.head,
+        "error": webhook_run.error,
+    })
+    return webhook_run
+
+
+async def handle_github_event(db: AsyncSession, event: str, payload: Dict[str, Any]) -> List[WebhookRun]:
+    """Start a run of the affected scenarios for every project linked to the delivery's repository"""
+    change = parse_change(event, payload)
+    if not change:
+        return []
+    options = await get_all_settings(db)
+    return [
+        await _trigger_project(db, project, change, options)
+        for project in await _linked_projects(db, change.owner, change.repo)
+    ]
+
+
+class CheckReporter:
+    """Completes the check runs of webhook-triggered runs as they finish, watching run status events"""
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        queue = event_bus.subscribe()
+        try:
+            while True:
+                message = await queue.get()
+                payload = message["payload"] or {}
+                if message["event"] != "run:status_changed" or payload.get("to_status") not in FINISHED_STATUSES:
+                    continue
+                try:
+                    await self._report(payload["test_run_id"])
+                except (WebhookError, OperationalError) as e:
+                    print(f"Check run update error: {e}")
+        finally:
+            event_bus.unsubscribe(queue)
+
+    async def _report(self, test_run_id: str) -> None:
+        async with AsyncSessionLocal() as db:
+            result = await db.execute(
+                select(WebhookRun).where(WebhookRun.test_run_id == test_run_id, WebhookRun.check_run_id.is_not(None))
+            )
+            webhook_run = result.scalar_one_or_none()
+            if not webhook_run:
+                return
+            test_run = await db.get(TestRun, test_run_id)
+            try:
+                await complete_check(webhook_run, test_run)
+            except WebhookError as e:
+                webhook_run.error = str(e)
+                await db.commit()
+                raise
+
+
+# Singleton instance
+check_reporter = CheckReporter()
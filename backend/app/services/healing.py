@@ -0,0 +1,85 @@
+"""
+Locator Healing Service - Repairs broken step locators using the AI agent
+"""
+from typing import Any, Dict, Optional
+
+from fastapi import HTTPException
+
+from ..routers.ai import FindWebElementRequest, find_web_element
+from .ai_client import AiClientError, ask_ai_json
+
+# Suggestions below this confidence are not trusted enough to retry with
+MIN_HEALING_CONFIDENCE = 0.5
+
+MOBILE_STEP_TYPES = {"tap", "tap_element", "swipe", "input", "back", "home", "launch"}
+
+
+async def heal_locator(
+    step_type: str,
+    config: Dict[str, Any],
+    screenshot_base64: str,
+    page_html: Optional[str] = None,
+) -> Optional[Dict[str, Any]]:
+    """
+    Ask the AI to locate the step's element again
+
+    Web steps get a selector from the AI agent's element finder; mobile steps
+    get the element's x/y coordinates on the screenshot from the AI provider.
+    Returns the config fields to patch into the step, or None if the element
+    can't be found with enough confidence.
+    """
+    description = config.get("element_description")
+    if not description:
+        return None
+    if step_type in MOBILE_STEP_TYPES:
+        return await _locate_on_screen(description, screenshot_base64)
+
+    try:
+        result = await find_web_element(
+            FindWebElementRequest(
+                screenshot_base64=screenshot_base64,
+                element_description=description,
+                page_html=page_html,
+            )
+        )
+    except HTTPException:
+        return None
+
+    if not result.get("found") or result.get("confidence", 0) < MIN_HEALING_CONFIDENCE:
+        return None
+    if not result.get("selector"):
+        return None
+    locator = {"selector": result["selector"]}
+    if result.get("xpath"):
+        locator["xpath"] = result["xpath"]
+    for key in ("frame_path", "shadow_path"):
+        if result.get(key):
+            locator[key] = result[key]
+    return locator
+
+
+async def _locate_on_screen(description: str, screenshot_base64: str) -> Optional[Dict[str, Any]]:
+    """Ask the AI for the center of a described element on a mobile screenshot, in screenshot pixels"""
+    prompt = f"""You are locating an element on a mobile app screenshot.
+
+Element to find: {description}
+
+Only report the element if it is visible on this screen. Coordinates are the element's
+center in screenshot pixels, and confidence is between 0 and 1.
+
+Respond with JSON only:
+{{"found": true, "x": 0, "y": 0, "confidence": 0.0}}"""
+
+    try:
+        result = await ask_ai_json(prompt, "heal_locator", image_base64=screenshot_base64, purpose="element")
+    except AiClientError:
+        return None
+
+    if not isinstance(result, dict) or not result.get("found"):
+        return None
+    if result.get("confidence", 0) < MIN_HEALING_CONFIDENCE:
+        return None
+    try:
+        return {"x": int(result["x"]), "y": int(result["y"])}
+    except (KeyError, TypeError, ValueError):
+        return None
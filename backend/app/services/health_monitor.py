@@ -0,0 +1,86 @@
+"""
+Health Monitor - Polls service health in the background and reports status changes
+"""
+import asyncio
+from typing import Dict, Optional
+
+from ..db import AsyncSessionLocal
+from ..routers.services import ServiceHealth, check_service
+from .app_settings import get_all_settings
+from .events import event_bus
+from .service_manager import service_manager
+from .service_urls import get_service_urls
+
+DEFAULT_INTERVAL_SECONDS = 15
+MIN_INTERVAL_SECONDS = 1
+
+
+class HealthMonitor:
+    """Checks every registered service on an interval and publishes 'service:status_changed' events"""
+
+    def __init__(self):
+        self.statuses: Dict[str, str] = {}
+        self.last_checks: Dict[str, ServiceHealth] = {}
+        self._failures: Dict[str, int] = {}
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        interval = DEFAULT_INTERVAL_SECONDS
+        while True:
+            try:
+                async with AsyncSessionLocal() as db:
+                    values = await get_all_settings(db)
+                interval = max(values["services.health_check_interval_seconds"], MIN_INTERVAL_SECONDS)
+                threshold = max(values["services.health_failure_threshold"], 1)
+
+                urls = await get_service_urls()
+                await asyncio.gather(
+                    *(self.check(service.name, urls[service.name], threshold) for service in service_manager.all())
+                )
+            except Exception as e:
+                # Keep monitoring through transient errors such as a locked database
+                print(f"Health monitor error: {e}")
+            await asyncio.sleep(interval)
+
+    async def check(self, name: str, url: str, threshold: int) -> None:
+        health = await check_service(name, url)
+        self.last_checks[name] = health
+
+        if health.status == "Running":
+            self._failures[name] = 0
+            status = "Running"
+        else:
+            self._failures[name] = self._failures.get(name, 0) + 1
+            # Ride out transient failures until the threshold is reached
+            if self._failures[name] < threshold and name in self.statuses:
+                return
+            status = health.status
+
+        service = service_manager.get(name)
+        if service.status in ("Running", "Unhealthy"):
+            # A live process that fails its checks is unhealthy rather than stopped
+            if status != "Running":
+                status = "Unhealthy"
+            service.status = status
+
+        previous = self.statuses.get(name)
+        self.statuses[name] = status
+        if previous is not None and previous != status:
+            event_bus.publish(
+                "service:status_changed",
+                {"name": name, "previous": previous, "status": status, "error": health.error},
+            )
+
+
+# Singleton instance
+health_monitor = HealthMonitor()
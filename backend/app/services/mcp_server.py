@@ -0,0 +1,313 @@
+"""
+MCP Server - Exposes device control and scenario editing as Model Context Protocol tools
+"""
+import json
+from dataclasses import dataclass
+from typing import Any, Awaitable, Callable, Dict, List, Optional
+
+from fastapi import HTTPException
+from pydantic import BaseModel
+from sqlalchemy import func, select
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from ..models import (
+    Project,
+    ProjectResponse,
+    Scenario,
+    ScenarioCreate,
+    ScenarioResponse,
+    ScenarioUpdate,
+    Step,
+    StepConfig,
+    StepCreate,
+    StepResponse,
+    TestCase,
+    TestCaseResponse,
+)
+
+SUPPORTED_PROTOCOL_VERSIONS = ("2025-06-18", "2025-03-26", "2024-11-05")
+
+# JSON-RPC error codes
+PARSE_ERROR = -32700
+INVALID_REQUEST = -32600
+METHOD_NOT_FOUND = -32601
+INVALID_PARAMS = -32602
+
+
+@dataclass
+class McpTool:
+    name: str
+    description: str
+    properties: Dict[str, Any]
+    required: List[str]
+    handler: Callable[..., Awaitable[Any]]
+
+    def describe(self) -> Dict[str, Any]:
+        return {
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": {"type": "object", "properties": self.properties, "required": self.required},
+        }
+
+
+_tools: Dict[str, McpTool] = {}
+
+
+def tool(name: str, description: str, required: Optional[List[str]] = None, **properties: Dict[str, Any]):
+    """Register a coroutine as an MCP tool; its keyword arguments are the tool's input"""
+    def register(handler: Callable[..., Awaitable[Any]]):
+        _tools[name] = McpTool(name, description, properties, required or [], handler)
+        return handler
+    return register
+
+
+STRING = {"type": "string"}
+INTEGER = {"type": "integer"}
+PLATFORM = {"type": "string", "enum": ["android", "ios"]}
+
+
+def _dump(value: Any) -> Any:
+    if isinstance(value, BaseModel):
+        return value.model_dump(mode="json")
+    if isinstance(value, list):
+        return [_dump(item) for item in value]
+    return value
+
+
+# ============================================
+# Device Tools
+# ============================================
+
+def _mobile():
+    # Imported lazily since the mobile router imports the services package
+    from ..routers import mobile
+    return mobile
+
+
+@tool("list_devices", "List connected Android devices and iOS simulators")
+async def list_devices() -> Any:
+    mobile = _mobile()
+    devices = []
+    for lister in (mobile.list_android_devices, mobile.list_ios_devices):
+        try:
+            devices.extend(await lister())
+        except HTTPException:
+            pass  # adb or xcrun isn't installed
+    return devices
+
+
+@tool("screenshot", "Capture the device screen as a PNG image", ["device_id", "platform"],
+      device_id=STRING, platform=PLATFORM)
+async def screenshot(device_id: str, platform: str) -> Any:
+    mobile = _mobile()
+    capture = mobile.ios_screenshot if platform == "ios" else mobile.android_screenshot
+    return {"image": (await capture(device_id)).screenshot}
+
+
+@tool("ui_dump", "Get the Android UI hierarchy as uiautomator XML", ["device_id"], device_id=STRING)
+async def ui_dump(device_id: str) -> Any:
+    return (await _mobile().android_dump_ui(device_id))["xml"]
+
+
+@tool("tap", "Tap a screen coordinate", ["device_id", "platform", "x", "y"],
+      device_id=STRING, platform=PLATFORM, x=INTEGER, y=INTEGER)
+async def tap(device_id: str, platform: str, x: int, y: int) -> Any:
+    mobile = _mobile()
+    handler = mobile.ios_tap if platform == "ios" else mobile.android_tap
+    return await handler(device_id, mobile.TapRequest(x=x, y=y))
+
+
+@tool("swipe", "Swipe between two coordinates on an Android device",
+      ["device_id", "start_x", "start_y", "end_x", "end_y"],
+      device_id=STRING, start_x=INTEGER, start_y=INTEGER, end_x=INTEGER, end_y=INTEGER, duration_ms=INTEGER)
+async def swipe(device_id: str, start_x: int, start_y: int, end_x: int, end_y: int, duration_ms: int = 300) -> Any:
+    mobile = _mobile()
+    request = mobile.SwipeRequest(
+        start_x=start_x, start_y=start_y, end_x=end_x, end_y=end_y, duration_ms=duration_ms
+    )
+    return await mobile.android_swipe(device_id, request)
+
+
+@tool("input_text", "Type text into the focused field", ["device_id", "platform", "text"],
+      device_id=STRING, platform=PLATFORM, text=STRING)
+async def input_text(device_id: str, platform: str, text: str) -> Any:
+    mobile = _mobile()
+    handler = mobile.ios_input_text if platform == "ios" else mobile.android_input_text
+    return await handler(device_id, mobile.InputTextRequest(text=text))
+
+
+@tool("press_key", "Send an Android key event, such as 3 for home or 4 for back", ["device_id", "keycode"],
+      device_id=STRING, keycode=INTEGER)
+async def press_key(device_id: str, keycode: int) -> Any:
+    mobile = _mobile()
+    return await mobile.android_keyevent(device_id, mobile.KeyEventRequest(keycode=keycode))
+
+
+@tool("launch_app", "Launch an app by Android package name or iOS bundle ID", ["device_id", "platform", "package"],
+      device_id=STRING, platform=PLATFORM, package=STRING)
+async def launch_app(device_id: str, platform: str, package: str) -> Any:
+    mobile = _mobile()
+    handler = mobile.ios_launch_app if platform == "ios" else mobile.android_launch_app
+    return await handler(device_id, mobile.AppRequest(package=package))
+
+
+@tool("stop_app", "Stop an app by Android package name or iOS bundle ID", ["device_id", "platform", "package"],
+      device_id=STRING, platform=PLATFORM, package=STRING)
+async def stop_app(device_id: str, platform: str, package: str) -> Any:
+    mobile = _mobile()
+    handler = mobile.ios_terminate_app if platform == "ios" else mobile.android_stop_app
+    return await handler(device_id, mobile.AppRequest(package=package))
+
+
+# ============================================
+# Scenario Tools
+# ============================================
+
+@tool("list_projects", "List projects")
+async def list_projects() -> Any:
+    async with AsyncSessionLocal() as db:
+        result = await db.execute(select(Project).order_by(Project.name))
+        return [ProjectResponse.model_validate(p) for p in result.scalars().all()]
+
+
+@tool("list_test_cases", "List a project's test cases", ["project_id"], project_id=STRING)
+async def list_test_cases(project_id: str) -> Any:
+    async with AsyncSessionLocal() as db:
+        result = await db.execute(
+            select(TestCase).where(TestCase.project_id == project_id).order_by(TestCase.name)
+        )
+        return [TestCaseResponse.model_validate(t) for t in result.scalars().all()]
+
+
+@tool("list_scenarios", "List a test case's scenarios", ["test_case_id"], test_case_id=STRING)
+async def list_scenarios(test_case_id: str) -> Any:
+    from ..routers.scenarios import list_scenarios_by_test_case
+
+    async with AsyncSessionLocal() as db:
+        return [ScenarioResponse.model_validate(s) for s in await list_scenarios_by_test_case(test_case_id, db)]
+
+
+@tool("get_scenario", "Get a scenario with its steps", ["scenario_id"], scenario_id=STRING)
+async def get_scenario(scenario_id: str) -> Any:
+    from ..routers.scenarios import get_scenario_with_steps
+
+    async with AsyncSessionLocal() as db:
+        return await get_scenario_with_steps(scenario_id, db)
+
+
+@tool("create_scenario", "Create a scenario in a test case", ["test_case_id", "name"],
+      test_case_id=STRING, name=STRING, description=STRING, target_url=STRING)
+async def create_scenario(
+    test_case_id: str, name: str, description: Optional[str] = None, target_url: Optional[str] = None
+) -> Any:
+    from ..routers.scenarios import create_scenario as create
+
+    async with AsyncSessionLocal() as db:
+        if not await db.get(TestCase, test_case_id):
+            raise HTTPException(status_code=404, detail="Test case not found")
+        data = ScenarioCreate(test_case_id=test_case_id, name=name, description=description, target_url=target_url)
+        return ScenarioResponse.model_validate(await create(data, db))
+
+
+@tool("update_scenario", "Rename or describe a scenario", ["scenario_id"],
+      scenario_id=STRING, name=STRING, description=STRING, target_url=STRING)
+async def update_scenario(scenario_id: str, **changes: Any) -> Any:
+    from ..routers.scenarios import update_scenario as update
+
+    async with AsyncSessionLocal() as db:
+        return ScenarioResponse.model_validate(await update(scenario_id, ScenarioUpdate(**changes), db))
+
+
+@tool("delete_scenario", "Delete a scenario and its steps", ["scenario_id"], scenario_id=STRING)
+async def delete_scenario(scenario_id: str) -> Any:
+    from ..routers.scenarios import delete_scenario as delete
+
+    async with AsyncSessionLocal() as db:
+        return await delete(scenario_id, db)
+
+
+@tool("add_step", "Append a step to a scenario; config holds selector, value, url, x, y and so on",
+      ["scenario_id", "step_type", "label"],
+      scenario_id=STRING, step_type=STRING, label=STRING, config={"type": "object"})
+async def add_step(scenario_id: str, step_type: str, label: str, config: Optional[Dict[str, Any]] = None) -> Any:
+    from ..routers.steps import create_step
+
+    async with AsyncSessionLocal() as db:
+        if not await db.get(Scenario, scenario_id):
+            raise HTTPException(status_code=404, detail="Scenario not found")
+        last = await db.execute(select(func.max(Step.step_order)).where(Step.scenario_id == scenario_id))
+        data = StepCreate(
+            scenario_id=scenario_id,
+            step_order=(last.scalar() or 0.0) + 1.0,
+            step_type=step_type,
+            label=label,
+            config=StepConfig(**(config or {})),
+        )
+        return StepResponse.model_validate(await create_step(data, db))
+
+
+@tool("delete_step", "Delete a step", ["step_id"], step_id=STRING)
+async def delete_step(step_id: str) -> Any:
+    from ..routers.steps import delete_step as delete
+
+    async with AsyncSessionLocal() as db:
+        return await delete(step_id, db)
+
+
+# ============================================
+# JSON-RPC
+# ============================================
+
+def _error(message_id: Any, code: int, message: str) -> Dict[str, Any]:
+    return {"jsonrpc": "2.0", "id": message_id, "error": {"code": code, "message": message}}
+
+
+def _result(message_id: Any, result: Any) -> Dict[str, Any]:
+    return {"jsonrpc": "2.0", "id": message_id, "result": result}
+
+
+async def _call_tool(params: Dict[str, Any]) -> Dict[str, Any]:
+    """Run a tool, reporting its failures as tool errors the agent can read"""
+    entry = _tools[params["name"]]
+    try:
+        value = _dump(await entry.handler(**(params.get("arguments") or {})))
+    except HTTPException as e:
+        return {"content": [{"type": "text", "text": str(e.detail)}], "isError": True}
+    except (TypeError, ValueError) as e:
+        return {"content": [{"type": "text", "text": f"Invalid arguments: {e}"}], "isError": True}
+
+    if isinstance(value, dict) and set(value) == {"image"}:
+        return {"content": [{"type": "image", "data": value["image"], "mimeType": "image/png"}]}
+    text = value if isinstance(value, str) else json.dumps(value, indent=2)
+    return {"content": [{"type": "text", "text": text}]}
+
+
+async def handle_message(message: Any) -> Optional[Dict[str, Any]]:
+    """Handle one JSON-RPC message, returning the response or None for notifications"""
+    if not isinstance(message, dict) or message.get("jsonrpc") != "2.0" or "method" not in message:
+        return _error(message.get("id") if isinstance(message, dict) else None, INVALID_REQUEST, "Invalid request")
+
+    method = message["method"]
+    params = message.get("params") or {}
+    if "id" not in message:
+        return None  # Notifications such as notifications/initialized need no reply
+    message_id = message["id"]
+
+    if method == "initialize":
+        requested = params.get("protocolVersion")
+        version = requested if requested in SUPPORTED_PROTOCOL_VERSIONS else SUPPORTED_PROTOCOL_VERSIONS[0]
+        return _result(message_id, {
+            "protocolVersion": version,
+            "capabilities": {"tools": {"listChanged": False}},
+            "serverInfo": {"name": "autotest-ai", "version": settings.app_version},
+        })
+    if method == "ping":
+        return _result(message_id, {})
+    if method == "tools/list":
+        return _result(message_id, {"tools": [entry.describe() for entry in _tools.values()]})
+    if method == "tools/call":
+        if params.get("name") not in _tools:
+            return _error(message_id, INVALID_PARAMS, f"Unknown tool: {params.get('name')}")
+        return _result(message_id, await _call_tool(params))
+    return _error(message_id, METHOD_NOT_FOUND, f"Method not found: {method}")
@@ -0,0 +1,156 @@
+"""
+Ordering Service - Fractional positions so moving a row only rewrites that row
+
+Each move halves the gap it lands in, so a background rebalancer renumbers
+any scope whose positions have crept too close together or are tied.
+"""
+import asyncio
+from collections import defaultdict
+from typing import Any, Dict, List, Optional
+
+from sqlalchemy import func, select, update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal
+from ..models import Scenario, Step, SuiteScenario
+from .events import event_bus
+
+# Neighbouring positions closer than this are spread out again before inserting between them
+MIN_POSITION_GAP = 1e-9
+
+# Scopes with neighbours closer than this are renumbered by the rebalancer, long before moves run out of room
+REBALANCE_GAP = 1e-6
+
+REBALANCE_INTERVAL_SECONDS = 6 * 60 * 60
+
+# Ordered tables as (order column, the column rows are ordered within)
+ORDERED_COLUMNS = (
+    (Step.step_order, Step.scenario_id),
+    (Scenario.position, Scenario.test_case_id),
+    (SuiteScenario.position, SuiteScenario.suite_id),
+)
+
+
+def position_between(before: Optional[float], after: Optional[float]) -> float:
+    """Pick a position strictly between two neighbours, either of which may be missing"""
+    if before is None and after is None:
+        return 1.0
+    if before is None:
+        return after - 1.0
+    if after is None:
+        return before + 1.0
+    return (before + after) / 2
+
+
+async def rebalance(db: AsyncSession, order_column: Any, scope: Any) -> None:
+    """Renumber the rows in a scope to 1, 2, 3, ... keeping their current order"""
+    model = order_column.class_
+    # Rows that share a position (such as ones created before positions existed) keep creation order
+    result = await db.execute(
+        select(model.id).where(scope).order_by(order_column, model.created_at, model.id)
+    )
+    for position, row_id in enumerate(result.scalars().all(), start=1):
+        await db.execute(
+            update(model)
+            .where(model.id == row_id)
+            .values({order_column.key: float(position)})
+            .execution_options(synchronize_session=False)
+        )
+
+
+async def move_after(
+    db: AsyncSession, order_column: Any, scope: Any, row: Any, after_id: Optional[str]
+) -> float:
+    """
+    Move a row directly after another row in the same scope, or first when after_id is None
+
+    Only the moved row is rewritten unless its new neighbours are too close
+    together or the target shares its position with other rows, in which case
+    the scope is rebalanced first. Returns the new position.
+    """
+    model = order_column.class_
+    others = scope & (model.id != row.id)
+
+    for attempt in range(2):
+        tied = False
+        if after_id:
+            before = (
+                await db.execute(select(order_column).where(others, model.id == after_id))
+            ).scalar_one_or_none()
+            if before is None:
+                raise ValueError("Target row not found in the same scope")
+            # Rows sharing the target's position have no gap after it to move into
+            tied = bool((await db.execute(
+                select(func.count()).select_from(model).where(others, model.id != after_id, order_column == before)
+            )).scalar())
+            after = (
+                await db.execute(
+                    select(order_column).where(others, order_column > before).order_by(order_column).limit(1)
+                )
+            ).scalar_one_or_none()
+        else:
+            before = None
+            after = (
+                await db.execute(select(order_column).where(others).order_by(order_column).limit(1))
+            ).scalar_one_or_none()
+
+        crowded = before is not None and after is not None and after - before < MIN_POSITION_GAP
+        if (tied or crowded) and attempt == 0:
+            await rebalance(db, order_column, others)
+            continue
+        break
+
+    position = position_between(before, after)
+    setattr(row, order_column.key, position)
+    return position
+
+
+def needs_rebalance(positions: List[float]) -> bool:
+    """Whether sorted positions have ties or neighbours too close to keep inserting between"""
+    return any(b - a < REBALANCE_GAP for a, b in zip(positions, positions[1:]))
+
+
+async def rebalance_crowded(db: AsyncSession) -> int:
+    """Renumber every scope that needs it across the ordered tables, returning how many were"""
+    rebalanced = 0
+    for order_column, scope_column in ORDERED_COLUMNS:
+        result = await db.execute(select(scope_column, order_column).order_by(scope_column, order_column))
+        positions: Dict[Any, List[float]] = defaultdict(list)
+        for scope_id, position in result.all():
+            positions[scope_id].append(position)
+        for scope_id, scoped in positions.items():
+            if needs_rebalance(scoped):
+                await rebalance(db, order_column, scope_column == scope_id)
+                rebalanced += 1
+    await db.commit()
+    return rebalanced
+
+
+class OrderRebalancer:
+    """Renumbers crowded or tied scopes on startup and every REBALANCE_INTERVAL_SECONDS after"""
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            try:
+                async with AsyncSessionLocal() as db:
+                    await rebalance_crowded(db)
+            except Exception as e:
+                event_bus.publish("ordering:rebalance_failed", {"error": str(e)})
+            await asyncio.sleep(REBALANCE_INTERVAL_SECONDS)
+
+
+# Singleton instance
+order_rebalancer = OrderRebalancer()
@@ -0,0 +1,192 @@
+"""
+Plugin Registry - Custom step types provided by external processes or WASM modules
+"""
+import asyncio
+import json
+import os
+import shlex
+import tempfile
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from ..config import settings
+
+MANIFEST_NAME = "plugin.json"
+PLUGIN_KINDS = ("process", "wasm")
+DEFAULT_PLUGIN_TIMEOUT = 60.0
+
+
+class PluginError(Exception):
+    """Raised when a plugin can't be run or reports a failed step"""
+
+
+@dataclass
+class Plugin:
+    """
+    A plugin directory described by its plugin.json manifest
+
+    Plugins receive {"step_type", "config", "context"} as JSON on stdin and
+    answer with {"status": "passed" | "failed", "error", "output"} on stdout.
+    """
+
+    name: str
+    path: Path
+    kind: str
+    step_types: List[str]
+    version: str = "0.0.0"
+    description: str = ""
+    command: str = ""  # process plugins
+    module: str = ""  # wasm plugins, relative to the plugin directory
+    timeout: float = DEFAULT_PLUGIN_TIMEOUT
+    error: Optional[str] = None  # Why the manifest couldn't be loaded
+
+
+def plugins_dir() -> Path:
+    path = settings.get_database_path().parent / "plugins"
+    path.mkdir(parents=True, exist_ok=True)
+    return path
+
+
+def load_manifest(path: Path) -> Plugin:
+    """Read a plugin directory's manifest, recording problems on the plugin instead of raising"""
+    try:
+        manifest = json.loads((path / MANIFEST_NAME).read_text(encoding="utf-8"))
+    except (OSError, ValueError) as e:
+        return Plugin(name=path.name, path=path, kind="", step_types=[], error=f"Invalid manifest: {e}")
+
+    plugin = Plugin(
+        name=manifest.get("name") or path.name,
+        path=path,
+        kind=manifest.get("type", "process"),
+        step_types=list(manifest.get("step_types") or []),
+        version=str(manifest.get("version", "0.0.0")),
+        description=manifest.get("description", ""),
+        command=manifest.get("command", ""),
+        module=manifest.get("module", ""),
+        timeout=float(manifest.get("timeout_seconds") or DEFAULT_PLUGIN_TIMEOUT),
+    )
+    if plugin.kind not in PLUGIN_KINDS:
+        plugin.error = f"Unknown plugin type: {plugin.kind}"
+    elif not plugin.step_types:
+        plugin.error = "Manifest declares no step_types"
+    elif plugin.kind == "process" and not plugin.command:
+        plugin.error = "Process plugins need a command"
+    elif plugin.kind == "wasm" and not (path / plugin.module).is_file():
+        plugin.error = f"WASM module not found: {plugin.module}"
+    return plugin
+
+
+async def _run_process(plugin: Plugin, payload: bytes) -> bytes:
+    try:
+        process = await asyncio.create_subprocess_exec(
+            *shlex.split(plugin.command, posix=os.name != "nt"),
+            cwd=str(plugin.path),
+            stdin=asyncio.subprocess.PIPE,
+            stdout=asyncio.subprocess.PIPE,
+            stderr=asyncio.subprocess.PIPE,
+        )
+    except OSError as e:
+        raise PluginError(f"Failed to start plugin {plugin.name}: {e}")
+    try:
+        stdout, stderr = await asyncio.wait_for(process.communicate(payload), plugin.timeout)
+    except asyncio.TimeoutError:
+        process.kill()
+        await process.wait()
+        raise PluginError(f"Plugin {plugin.name} timed out after {plugin.timeout:g}s")
+    if process.returncode != 0:
+        message = stderr.decode(errors="replace").strip() or f"exit code {process.returncode}"
+        raise PluginError(f"Plugin {plugin.name} failed: {message}")
+    return stdout
+
+
+def _run_wasm_sync(plugin: Plugin, payload: bytes) -> bytes:
+    """Run a WASI module with the payload as stdin, returning its stdout"""
+    try:
+        import wasmtime
+    except ImportError:
+        raise PluginError("WASM plugins need the wasmtime package (pip install wasmtime)")
+
+    with tempfile.TemporaryDirectory() as tmp:
+        stdin_path = Path(tmp) / "stdin.json"
+        stdout_path = Path(tmp) / "stdout.json"
+        stdin_path.write_bytes(payload)
+
+        engine = wasmtime.Engine()
+        linker = wasmtime.Linker(engine)
+        linker.define_wasi()
+        store = wasmtime.Store(engine)
+        wasi = wasmtime.WasiConfig()
+        wasi.stdin_file = str(stdin_path)
+        wasi.stdout_file = str(stdout_path)
+        store.set_wasi(wasi)
+
+        module = wasmtime.Module.from_file(engine, str(plugin.path / plugin.module))
+        instance = linker.instantiate(store, module)
+        try:
+            instance.exports(store)["_start"](store)
+        except wasmtime.ExitTrap as e:
+            if e.code != 0:
+                raise PluginError(f"Plugin {plugin.name} exited with code {e.code}")
+        except wasmtime.Trap as e:
+            raise PluginError(f"Plugin {plugin.name} trapped: {e}")
+        return stdout_path.read_bytes()
+
+
+class PluginRegistry:
+    """Plugins discovered from the plugins directory, keyed by the step types they handle"""
+
+    def __init__(self):
+        self._plugins: List[Plugin] = []
+        self._by_step_type: Dict[str, Plugin] = {}
+        self._loaded = False
+
+    def discover(self) -> List[Plugin]:
+        """Rescan the plugins directory; the first plugin to claim a step type wins"""
+        plugins = [load_manifest(p) for p in sorted(plugins_dir().iterdir()) if (p / MANIFEST_NAME).exists()]
+        by_step_type: Dict[str, Plugin] = {}
+        for plugin in plugins:
+            if plugin.error:
+                continue
+            for step_type in plugin.step_types:
+                by_step_type.setdefault(step_type, plugin)
+        self._plugins = plugins
+        self._by_step_type = by_step_type
+        self._loaded = True
+        return plugins
+
+    def list(self) -> List[Plugin]:
+        if not self._loaded:
+            self.discover()
+        return list(self._plugins)
+
+    def for_step_type(self, step_type: str) -> Optional[Plugin]:
+        if not self._loaded:
+            self.discover()
+        return self._by_step_type.get(step_type)
+
+    async def execute(self, step_type: str, config: Dict[str, Any], context: Dict[str, Any]) -> Dict[str, Any]:
+        """Run the plugin for a step type, raising PluginError unless the step passed"""
+        plugin = self.for_step_type(step_type)
+        if not plugin:
+            raise PluginError(f"No plugin handles step type: {step_type}")
+
+        payload = json.dumps({"step_type": step_type, "config": config, "context": context}).encode()
+        if plugin.kind == "wasm":
+            output = await asyncio.wait_for(asyncio.to_thread(_run_wasm_sync, plugin, payload), plugin.timeout)
+        else:
+            output = await _run_process(plugin, payload)
+
+        try:
+            result = json.loads(output.decode(errors="replace") or "{}")
+        except ValueError:
+            raise PluginError(f"Plugin {plugin.name} returned invalid JSON")
+        if not isinstance(result, dict):
+            raise PluginError(f"Plugin {plugin.name} returned invalid JSON")
+        if result.get("status", "passed") != "passed":
+            raise PluginError(result.get("error") or f"Plugin {plugin.name} reported a failure")
+        return result
+
+
+# Singleton instance
+plugin_registry = PluginRegistry()
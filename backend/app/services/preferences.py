@@ -0,0 +1,160 @@
+"""
+Preferences - Desktop app preferences kept in a JSON file in the app data directory
+
+Preferences live outside the database so that data_dir can move the database.
+Runtime behaviour such as the AI provider is configured through app settings.
+"""
+import json
+import os
+from pathlib import Path
+from typing import Any, Callable, Dict, Optional
+
+from pydantic import BaseModel, ValidationError, field_validator
+
+from ..config import settings
+from .events import event_bus
+
+PREFERENCES_FILE = "preferences.json"
+
+# Bump when the file format changes, adding a migration from the previous version
+PREFERENCES_VERSION = 1
+
+THEMES = ("system", "light", "dark")
+
+
+class PreferencesError(Exception):
+    """Raised for invalid preference values"""
+
+
+class Preferences(BaseModel):
+    """User preferences for the desktop app"""
+
+    version: int = PREFERENCES_VERSION
+    theme: str = "system"  # 'system' | 'light' | 'dark'
+    default_platform: Optional[str] = None  # 'android' | 'ios' | 'web'
+    default_device_id: Optional[str] = None
+    screenshot_quality: int = 80  # JPEG quality for captured evidence, 1-100
+    data_dir: Optional[str] = None  # Where the database and artifacts live; applies after a restart
+
+    @field_validator("theme")
+    @classmethod
+    def check_theme(cls, v):
+        if v not in THEMES:
+            raise ValueError(f"Theme must be one of: {', '.join(THEMES)}")
+        return v
+
+    @field_validator("default_platform")
+    @classmethod
+    def check_platform(cls, v):
+        if v is not None and v not in ("android", "ios", "web"):
+            raise ValueError("Default platform must be android, ios or web")
+        return v
+
+    @field_validator("screenshot_quality")
+    @classmethod
+    def check_quality(cls, v):
+        if not 1 <= v <= 100:
+            raise ValueError("Screenshot quality must be between 1 and 100")
+        return v
+
+    @field_validator("data_dir")
+    @classmethod
+    def check_data_dir(cls, v):
+        if v and not Path(v).expanduser().is_absolute():
+            raise ValueError("Data directory must be an absolute path")
+        return v or None
+
+
+class PreferencesUpdate(BaseModel):
+    """Schema for updating preferences; unset fields are left unchanged"""
+
+    theme: Optional[str] = None
+    default_platform: Optional[str] = None
+    default_device_id: Optional[str] = None
+    screenshot_quality: Optional[int] = None
+    data_dir: Optional[str] = None
+
+
+# From each version, the function that upgrades a file's contents to the next version
+MIGRATIONS: Dict[int, Callable[[Dict[str, Any]], Dict[str, Any]]] = {}
+
+
+def preferences_path() -> Path:
+    # Always the default data directory, since data_dir itself is a preference
+    return settings.get_default_data_dir() / PREFERENCES_FILE
+
+
+def migrate(data: Dict[str, Any]) -> Dict[str, Any]:
+    """Upgrade stored preferences from older versions, step by step"""
+    version = data.get("version", 1)
+    while version < PREFERENCES_VERSION:
+        data = MIGRATIONS[version](data)
+        version += 1
+    data["version"] = PREFERENCES_VERSION
+    return data
+
+
+def load_preferences() -> Preferences:
+    """Read preferences, falling back to defaults for a missing or unreadable file"""
+    path = preferences_path()
+    if not path.exists():
+        return Preferences()
+    try:
+        data = json.loads(path.read_text(encoding="utf-8"))
+        if not isinstance(data, dict):
+            raise ValueError("Preferences must be a JSON object")
+        if data.get("version", 1) > PREFERENCES_VERSION:
+            # Written by a newer version of the app; keep what this version understands
+            data["version"] = PREFERENCES_VERSION
+        return Preferences.model_validate(migrate(data))
+    except (OSError, ValueError, ValidationError) as e:
+        # Keep the broken file for inspection rather than silently overwriting it later
+        path.replace(path.with_suffix(".json.bak"))
+        event_bus.publish("preferences:reset", {"error": str(e)})
+        return Preferences()
+
+
+def save_preferences(preferences: Preferences) -> None:
+    path = preferences_path()
+    temp = path.with_suffix(".json.tmp")
+    temp.write_text(json.dumps(preferences.model_dump(), indent=2), encoding="utf-8")
+    os.replace(temp, path)
+
+
+def update_preferences(data: PreferencesUpdate) -> Preferences:
+    """Validate and save changed preferences, publishing which ones changed"""
+    current = load_preferences()
+    values = current.model_dump()
+    values.update(data.model_dump(exclude_unset=True))
+    try:
+        updated = Preferences.model_validate(values)
+    except ValidationError as e:
+        raise PreferencesError("; ".join(error["msg"] for error in e.errors()))
+    if updated.data_dir:
+        try:
+            Path(updated.data_dir).expanduser().mkdir(parents=True, exist_ok=True)
+        except OSError as e:
+            raise PreferencesError(f"Can't use data directory: {e}")
+
+    changed = [key for key, value in updated.model_dump().items() if getattr(current, key) != value]
+    save_preferences(updated)
+    if changed:
+        event_bus.publish("preferences:changed", {
+            "changed": changed,
+            "preferences": updated.model_dump(),
+            "restart_required": "data_dir" in changed,
+        })
+    return updated
+
+
+def reset_preferences() -> Preferences:
+    """Restore the default preferences"""
+    current = load_preferences()
+    preferences = Preferences()
+    save_preferences(preferences)
+    event_bus.publish("preferences:changed", {
+        "changed": [key for key, value in preferences.model_dump().items() if getattr(current, key) != value],
+        "preferences": preferences.model_dump(),
+        "restart_required": current.data_dir is not None,
+    })
+    return preferences
@@ -0,0 +1,199 @@
+"""
+Quick Actions - Commands the palette and global shortcuts can run without the mouse
+
+Each action has an id, a title to search by and a default shortcut, which the
+desktop app registers system-wide when shortcuts.global is on. Running one
+returns a message, and an app route or backend path for the client to open.
+Actions reuse the routers' handlers, like the MCP tools, so they behave as
+the equivalent buttons do.
+"""
+from dataclasses import dataclass
+from typing import Any, Awaitable, Callable, Dict, List, Optional, Tuple
+
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Project, Scenario, Step, StepResult, TestCase, TestRun
+from .preferences import load_preferences
+
+
+class QuickActionError(Exception):
+    """Raised when an action can't run, such as when there's no default device"""
+
+
+class QuickActionResponse(BaseModel):
+    """Schema for an action in the palette"""
+    id: str
+    title: str
+    description: str
+    shortcut: Optional[str] = None  # Electron accelerator, e.g. CommandOrControl+Alt+R
+    args: Dict[str, str] = {}  # Optional arguments with their descriptions
+
+
+class QuickActionResult(BaseModel):
+    """Schema for the outcome of running an action"""
+    message: str
+    route: Optional[str] = None  # App page to show, e.g. /runs/<id>
+    open_path: Optional[str] = None  # Backend path to open in the browser, e.g. a report
+    data: Any = None
+
+
+@dataclass
+class QuickAction:
+    id: str
+    title: str
+    description: str
+    shortcut: Optional[str]
+    args: Dict[str, str]
+    handler: Callable[..., Awaitable[QuickActionResult]]
+
+    def describe(self) -> QuickActionResponse:
+        return QuickActionResponse(
+            id=self.id, title=self.title, description=self.description, shortcut=self.shortcut, args=self.args
+        )
+
+
+_actions: Dict[str, QuickAction] = {}
+
+
+def quick_action(action_id: str, title: str, description: str, shortcut: Optional[str] = None, **args: str):
+    """Register a coroutine taking a session and the action's arguments as a quick action"""
+    def register(handler: Callable[..., Awaitable[QuickActionResult]]):
+        _actions[action_id] = QuickAction(action_id, title, description, shortcut, args, handler)
+        return handler
+    return register
+
+
+def list_quick_actions() -> List[QuickActionResponse]:
+    return [action.describe() for action in _actions.values()]
+
+
+async def invoke_quick_action(db: AsyncSession, action_id: str, args: Dict[str, Any]) -> QuickActionResult:
+    """Run an action; raises KeyError for unknown actions and QuickActionError for ones that can't run"""
+    action = _actions[action_id]
+    unknown = set(args) - set(action.args)
+    if unknown:
+        raise QuickActionError(f"{action.title} doesn't take: {', '.join(sorted(unknown))}")
+    return await action.handler(db, **args)
+
+
+def _default_device(device_id: Optional[str], platform: Optional[str]) -> Tuple[str, str]:
+    preferences = load_preferences()
+    device_id = device_id or preferences.default_device_id
+    platform = platform or preferences.default_platform
+    if not device_id or platform not in ("android", "ios"):
+        raise QuickActionError("Choose a default device and platform in preferences, or pass device_id and platform")
+    return device_id, platform
+
+
+async def _latest_run(db: AsyncSession, project_id: Optional[str]) -> TestRun:
+    query = select(TestRun).order_by(TestRun.created_at.desc()).limit(1)
+    if project_id:
+        query = query.where(TestRun.project_id == project_id)
+    test_run = (await db.execute(query)).scalar_one_or_none()
+    if not test_run:
+        raise QuickActionError("There are no test runs yet")
+    return test_run
+
+
+# ============================================
+# Actions
+# ============================================
+
+
+@quick_action(
+    "run_last_scenario",
+    "Run last scenario",
+    "Run the most recently run scenario again, in a browser for web projects or on the default device",
+    "CommandOrControl+Alt+R",
+    scenario_id="Scenario to run instead of the last one",
+    device_id="Device to use instead of the default",
+    platform="android or ios, for the device",
+)
+async def run_last_scenario(
+    db: AsyncSession,
+    scenario_id: Optional[str] = None,
+    device_id: Optional[str] = None,
+    platform: Optional[str] = None,
+) -> QuickActionResult:
+    from ..routers.executions import (
+        ExecuteScenarioRequest,
+        ExecuteWebScenarioRequest,
+        execute_scenario,
+        execute_web_scenario,
+    )
+
+    if not scenario_id:
+        scenario_id = (await db.execute(
+            select(Step.scenario_id)
+            .join(StepResult, StepResult.step_id == Step.id)
+            .order_by(StepResult.created_at.desc())
+            .limit(1)
+        )).scalar_one_or_none()
+        if not scenario_id:
+            raise QuickActionError("No scenario has run yet")
+    scenario = await db.get(Scenario, scenario_id)
+    if not scenario:
+        raise QuickActionError("Scenario not found")
+    test_case = await db.get(TestCase, scenario.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+
+    if project.project_type == "web" and not device_id:
+        test_run = await execute_web_scenario(scenario_id, ExecuteWebScenarioRequest(), db)
+    else:
+        device_id, platform = _default_device(device_id, platform)
+        test_run = await execute_scenario(
+            scenario_id, ExecuteScenarioRequest(device_id=device_id, platform=platform), db
+        )
+    return QuickActionResult(message=f"Running {scenario.name}", route=f"/runs/{test_run.id}")
+
+
+@quick_action(
+    "screenshot_default_device",
+    "Screenshot default device",
+    "Capture the default device's screen",
+    "CommandOrControl+Alt+S",
+    device_id="Device to use instead of the default",
+    platform="android or ios, for the device",
+)
+async def screenshot_default_device(
+    db: AsyncSession, device_id: Optional[str] = None, platform: Optional[str] = None
+) -> QuickActionResult:
+    from ..routers import mobile
+
+    device_id, platform = _default_device(device_id, platform)
+    capture = mobile.ios_screenshot if platform == "ios" else mobile.android_screenshot
+    screenshot = (await capture(device_id)).screenshot
+    return QuickActionResult(message=f"Captured {device_id}", data={"image": screenshot})
+
+
+@quick_action(
+    "open_last_run",
+    "Open last run",
+    "Show the most recent test run",
+    "CommandOrControl+Alt+L",
+    project_id="Only look at this project's runs",
+)
+async def open_last_run(db: AsyncSession, project_id: Optional[str] = None) -> QuickActionResult:
+    test_run = await _latest_run(db, project_id)
+    return QuickActionResult(message=f"Opening {test_run.name}", route=f"/runs/{test_run.id}")
+
+
+@quick_action(
+    "open_report",
+    "Open report",
+    "Open the HTML report of the most recent test run, or of a given one",
+    None,
+    test_run_id="Run to open the report of",
+    project_id="Only look at this project's runs",
+)
+async def open_report(
+    db: AsyncSession, test_run_id: Optional[str] = None, project_id: Optional[str] = None
+) -> QuickActionResult:
+    test_run = await db.get(TestRun, test_run_id) if test_run_id else await _latest_run(db, project_id)
+    if not test_run:
+        raise QuickActionError("Test run not found")
+    return QuickActionResult(
+        message=f"Opening the report of {test_run.name}", open_path=f"/test-runs/{test_run.id}/report"
+    )
@@ -0,0 +1,392 @@
+"""
+Repo Analysis - Finds the testable surfaces of a project's source repository
+
+The project's repo_url is shallow-cloned into a temporary directory and its
+source files are scanned with lightweight, regex-based parsing for:
+
+- routes: HTTP handlers (FastAPI/Flask, Express) and Next.js pages
+- components: React, Vue and Angular components
+- screens: React Native, Android, iOS and Flutter screens
+
+Files with surfaces are sent to the AI agent's code analysis in chunks, and a
+test case skeleton is proposed for every surface found. Nothing is saved;
+the proposals are for the user to pick from.
+"""
+import asyncio
+import os
+import re
+import shutil
+import tempfile
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Callable, Dict, Iterator, List, Optional, Pattern, Tuple
+
+from pydantic import BaseModel
+
+from .tools import tool_command
+
+CLONE_TIMEOUT = 300.0
+
+# Directories that hold dependencies or build output rather than the project's source
+SKIPPED_DIRS = {
+    ".git", "node_modules", "vendor", "dist", "build", "out", ".next", "target",
+    "__pycache__", ".venv", "venv", "Pods", ".gradle", "coverage",
+}
+
+MAX_FILES = 5000
+MAX_FILE_BYTES = 512 * 1024
+
+# Code sent to AI analysis per request, and the most requests made for one analysis
+CHUNK_CHARS = 12000
+MAX_AI_REQUESTS = 20
+
+LANGUAGES = {
+    ".py": "python",
+    ".js": "javascript",
+    ".jsx": "javascript",
+    ".ts": "typescript",
+    ".tsx": "typescript",
+    ".vue": "vue",
+    ".kt": "kotlin",
+    ".java": "java",
+    ".swift": "swift",
+    ".dart": "dart",
+}
+
+HTTP_METHODS = "get|post|put|patch|delete"
+
+
+class RepoAnalysisError(Exception):
+    """Raised when the repository can't be cloned or has nothing to analyze"""
+
+
+class Surface(BaseModel):
+    """Schema for a testable surface found in the repository"""
+    kind: str  # 'route' | 'component' | 'screen'
+    name: str  # e.g. "GET /users/{id}", "LoginForm", "CheckoutScreen"
+    path: str  # File, relative to the repository root
+    line: int
+    language: str
+
+
+class ProposedTestCase(BaseModel):
+    """Schema for a test case skeleton proposed for a surface"""
+    name: str
+    description: str
+    category: str
+    priority: str
+    surface: Surface
+    suggestions: List[str] = []  # From AI analysis of the surface's file
+
+
+class RepoAnalysis(BaseModel):
+    """Schema for the result of analyzing a repository"""
+    repo_url: str
+    commit: Optional[str] = None
+    files_scanned: int
+    surfaces: List[Surface]
+    proposals: List[ProposedTestCase]
+    warnings: List[str] = []
+
+
+# ============================================
+# Cloning
+# ============================================
+
+
+async def run_git(*args: str, timeout: float = CLONE_TIMEOUT) -> str:
+    try:
+        process = await asyncio.create_subprocess_exec(
+            tool_command("git"), *args, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.PIPE
+        )
+    except FileNotFoundError:
+        raise RepoAnalysisError("git was not found; install it or set tools.git_path")
+    try:
+        stdout, stderr = await asyncio.wait_for(process.communicate(), timeout)
+    except asyncio.TimeoutError:
+        process.kill()
+        raise RepoAnalysisError(f"git {args[0]} timed out after {timeout:g}s")
+    if process.returncode != 0:
+        raise RepoAnalysisError(f"git {args[0]} failed: {stderr.decode(errors='replace').strip()}")
+    return stdout.decode(errors="replace").strip()
+
+
+async def shallow_clone(repo_url: str, destination: str, branch: Optional[str] = None) -> str:
+    """Clone the latest commit of a repository (or one of its branches), returning the commit SHA"""
+    args = ["clone", "--depth", "1", "--single-branch"]
+    if branch:
+        args += ["--branch", branch]
+    await run_git(*args, "--", repo_url, destination)
+    return await run_git("-C", destination, "rev-parse", "HEAD", timeout=10.0)
+
+
+# ============================================
+# Surface Discovery
+# ============================================
+
+
+@dataclass
+class SurfacePattern:
+    kind: str
+    extensions: Tuple[str, ...]
+    pattern: Pattern[str]
+    # Builds the surface name from the match groups; the single group by default
+    name: Callable[[Tuple[str, ...]], str] = lambda groups: groups[0]
+
+
+def _http_route(groups: Tuple[str, ...]) -> str:
+    return f"{groups[0].upper()} {groups[1] or '/'}"
+
+
+SURFACE_PATTERNS = [
+    # @app.get("/users"), @router.post('/items/{id}')
+    SurfacePattern(
+        "route", (".py",), re.compile(rf"@\w+\.({HTTP_METHODS})\(\s*[\"']([^\"']*)[\"']"), _http_route
+    ),
+    # Flask: @app.route("/login", methods=[...])
+    SurfacePattern(
+        "route", (".py",), re.compile(r"@\w+\.route\(\s*[\"']([^\"']+)[\"']"), lambda groups: f"ANY {groups[0]}"
+    ),
+    # Express: app.get('/users', ...), router.delete("/items/:id", ...)
+    SurfacePattern(
+        "route",
+        (".js", ".ts"),
+        re.compile(rf"\b(?:app|router)\.({HTTP_METHODS})\(\s*[\"'`]([^\"'`]+)[\"'`]"),
+        _http_route,
+    ),
+    # React Native screens by naming convention
+    SurfacePattern(
+        "screen",
+        (".js", ".jsx", ".ts", ".tsx"),
+        re.compile(r"(?:export\s+default\s+)?(?:function|const|class)\s+([A-Z]\w*Screen)\b"),
+    ),
+    # Android activities and fragments
+    SurfacePattern(
+        "screen",
+        (".kt", ".java"),
+        re.compile(r"class\s+(\w+)\s*(?:\([^)]*\))?\s*(?::|extends)\s*\w*(?:Activity|Fragment)\b"),
+    ),
+    # UIKit view controllers and SwiftUI views
+    SurfacePattern(
+        "screen", (".swift",), re.compile(r"(?:class|struct)\s+(\w+)\s*:\s*(?:UIViewController|View)\b")
+    ),
+    # Flutter pages and screens
+    SurfacePattern(
+        "screen", (".dart",), re.compile(r"class\s+(\w+(?:Screen|Page))\s+extends\s+State(?:less|ful)Widget")
+    ),
+    # React components: capitalized functions or consts in JSX files
+    SurfacePattern(
+        "component",
+        (".jsx", ".tsx"),
+        re.compile(r"export\s+(?:default\s+)?(?:function|const|class)\s+([A-Z]\w*)"),
+    ),
+    # Angular components
+    SurfacePattern(
+        "component", (".ts",), re.compile(r"@Component\([\s\S]*?\)\s*export\s+class\s+(\w+)")
+    ),
+]
+
+
+def _source_files(root: Path) -> Iterator[Path]:
+    count = 0
+    for directory, subdirs, files in os.walk(root):
+        subdirs[:] = sorted(d for d in subdirs if d not in SKIPPED_DIRS and not d.startswith("."))
+        for filename in sorted(files):
+            path = Path(directory) / filename
+            if path.suffix not in LANGUAGES:
+                continue
+            try:
+                if path.stat().st_size > MAX_FILE_BYTES:
+                    continue
+            except OSError:
+                continue
+            yield path
+            count += 1
+            if count >= MAX_FILES:
+                return
+
+
+def _next_route(relative: Path) -> Optional[str]:
+    """The route a Next.js pages/ or app/ file serves, e.g. app/users/[id]/page.tsx -> /users/[id]"""
+    parts = list(relative.with_suffix("").parts)
+    for marker in ("pages", "app"):
+        if marker in parts:
+            route = parts[parts.index(marker) + 1:]
+            break
+    else:
+        return None
+    if marker == "app":
+        if not route or route[-1] != "page":
+            return None
+        route = route[:-1]
+    elif route and route[-1] == "index":
+        route = route[:-1]
+    if route and (route[0] == "api" or route[-1].startswith("_")):
+        return None
+    # Route groups such as (marketing) don't appear in the URL
+    return "/" + "/".join(part for part in route if not part.startswith("("))
+
+
+def _line_of(content: str, offset: int) -> int:
+    return content.count("\n", 0, offset) + 1
+
+
+def scan_file(root: Path, path: Path) -> List[Surface]:
+    """Every surface a source file defines"""
+    try:
+        content = path.read_text(errors="replace")
+    except OSError:
+        return []
+    relative = path.relative_to(root)
+    language = LANGUAGES[path.suffix]
+    surfaces = []
+    seen = set()
+
+    route = _next_route(relative) if path.suffix in (".js", ".jsx", ".ts", ".tsx") else None
+    if route:
+        surfaces.append(Surface(kind="route", name=f"PAGE {route}", path=str(relative), line=1, language=language))
+    if path.suffix == ".vue":
+        surfaces.append(Surface(kind="component", name=path.stem, path=str(relative), line=1, language=language))
+
+    for surface_pattern in SURFACE_PATTERNS:
+        # A page's component is the page itself
+        if path.suffix not in surface_pattern.extensions or (route and surface_pattern.kind == "component"):
+            continue
+        for match in surface_pattern.pattern.finditer(content):
+            name = surface_pattern.name(match.groups())
+            # A screen also matches as a component; keep the more specific kind
+            if name in seen:
+                continue
+            seen.add(name)
+            surfaces.append(Surface(
+                kind=surface_pattern.kind,
+                name=name,
+                path=str(relative),
+                line=_line_of(content, match.start()),
+                language=language,
+            ))
+    return surfaces
+
+
+def discover_surfaces(root: Path) -> Tuple[List[Surface], int]:
+    """Scan a checked-out repository, returning its surfaces and the number of files scanned"""
+    surfaces = []
+    files = 0
+    for path in _source_files(root):
+        files += 1
+        surfaces.extend(scan_file(root, path))
+    return surfaces, files
+
+
+# ============================================
+# Proposals
+# ============================================
+
+
+def propose_test_case(surface: Surface) -> ProposedTestCase:
+    """A test case skeleton for a surface"""
+    if surface.kind == "route":
+        method, _, route = surface.name.partition(" ")
+        if method == "PAGE":
+            return ProposedTestCase(
+                name=f"Page {route} loads",
+                description=f"Open {route} and verify it renders without errors ({surface.path})",
+                category="Pages",
+                priority="High" if route == "/" else "Medium",
+                surface=surface,
+            )
+        return ProposedTestCase(
+            name=f"{surface.name} responds correctly",
+            description=(
+                f"Call {surface.name} with valid and invalid input and verify the status codes "
+                f"and response body ({surface.path}:{surface.line})"
+            ),
+            category="API",
+            priority="High" if method in ("POST", "PUT", "PATCH", "DELETE") else "Medium",
+            surface=surface,
+        )
+    if surface.kind == "screen":
+        return ProposedTestCase(
+            name=f"{surface.name} works end to end",
+            description=f"Navigate to {surface.name}, use its main actions and verify the results ({surface.path})",
+            category="Screens",
+            priority="High",
+            surface=surface,
+        )
+    return ProposedTestCase(
+        name=f"{surface.name} renders and responds to input",
+        description=f"Render {surface.name} and check its states and interactions ({surface.path}:{surface.line})",
+        category="Components",
+        priority="Low",
+        surface=surface,
+    )
+
+
+def _chunks(content: str) -> List[str]:
+    return [content[i:i + CHUNK_CHARS] for i in range(0, len(content), CHUNK_CHARS)] or [""]
+
+
+async def ai_suggestions(root: Path, surfaces: List[Surface], warnings: List[str]) -> Dict[str, List[str]]:
+    """AI code analysis suggestions for each file with surfaces, keyed by file path"""
+    from ..routers.ai import AnalyzeCodeRequest, analyze_code
+
+    by_file: Dict[str, List[Surface]] = {}
+    for surface in surfaces:
+        by_file.setdefault(surface.path, []).append(surface)
+
+    suggestions: Dict[str, List[str]] = {}
+    requests = 0
+    for path, file_surfaces in by_file.items():
+        content = (root / path).read_text(errors="replace")
+        summary = "; ".join(f"{s.kind} {s.name} (line {s.line})" for s in file_surfaces)
+        for index, chunk in enumerate(_chunks(content)):
+            if requests >= MAX_AI_REQUESTS:
+                warnings.append(f"AI analysis stopped after {MAX_AI_REQUESTS} requests; later files have none")
+                return suggestions
+            requests += 1
+            try:
+                result = await analyze_code(AnalyzeCodeRequest(
+                    code=chunk,
+                    language=file_surfaces[0].language,
+                    context=f"File {path}, part {index + 1}. Testable surfaces: {summary}",
+                ))
+            except Exception as e:
+                warnings.append(f"AI analysis of {path} failed: {getattr(e, 'detail', e)}")
+                break
+            suggestions.setdefault(path, []).extend(result.get("suggestions", []) if isinstance(result, dict) else [])
+    return suggestions
+
+
+async def analyze_repo(
+    repo_url: str, branch: Optional[str] = None, use_ai: bool = True, max_surfaces: int = 200
+) -> RepoAnalysis:
+    """Clone a repository, find its surfaces and propose a test case for each"""
+    workdir = tempfile.mkdtemp(prefix="autotest-repo-")
+    try:
+        checkout = os.path.join(workdir, "repo")
+        commit = await shallow_clone(repo_url, checkout, branch)
+        root = Path(checkout)
+        surfaces, files = await asyncio.to_thread(discover_surfaces, root)
+        warnings = []
+        if not surfaces:
+            warnings.append("No routes, components or screens were found")
+        if len(surfaces) > max_surfaces:
+            warnings.append(f"Found {len(surfaces)} surfaces; only the first {max_surfaces} are proposed")
+            surfaces = surfaces[:max_surfaces]
+
+        suggestions = await ai_suggestions(root, surfaces, warnings) if use_ai and surfaces else {}
+        proposals = []
+        for surface in surfaces:
+            proposal = propose_test_case(surface)
+            proposal.suggestions = suggestions.get(surface.path, [])
+            proposals.append(proposal)
+        return RepoAnalysis(
+            repo_url=repo_url,
+            commit=commit,
+            files_scanned=files,
+            surfaces=surfaces,
+            proposals=proposals,
+            warnings=warnings,
+        )
+    finally:
+        await asyncio.to_thread(shutil.rmtree, workdir, True)
@@ -0,0 +1,296 @@
+"""
+Report Import - Loads Playwright JSON and Cypress mochawesome reports as test runs
+"""
+import json
+from dataclasses import dataclass, field
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+
+from sqlalchemy import func, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import RunLogCreate, Scenario, Step, StepResult, TestCase, TestRun
+from .run_logs import add_run_logs
+from .run_status import apply_run_status
+
+REPORT_FORMATS = ("playwright", "cypress")
+
+IMPORTED_CATEGORY = "Imported"
+IMPORTED_STEP_TYPE = "external"
+
+
+class ReportImportError(Exception):
+    """Raised when a report file can't be read or isn't in the expected format"""
+
+
+@dataclass
+class ImportedTest:
+    """One test outcome from an external report"""
+
+    file: str
+    title: str
+    status: str  # 'passed' | 'failed' | 'skipped'
+    duration_ms: int = 0
+    error: Optional[str] = None
+    # (name, path) pairs for screenshots, videos and traces
+    attachments: List[Tuple[str, str]] = field(default_factory=list)
+
+
+@dataclass
+class ImportedReport:
+    tests: List[ImportedTest]
+    started_at: Optional[datetime] = None
+    duration_ms: Optional[int] = None
+
+
+def _parse_time(value: Any) -> Optional[datetime]:
+    if not isinstance(value, str):
+        return None
+    try:
+        parsed = datetime.fromisoformat(value.replace("Z", "+00:00"))
+    except ValueError:
+        return None
+    if parsed.tzinfo:
+        parsed = (parsed - parsed.utcoffset()).replace(tzinfo=None)
+    return parsed
+
+
+def parse_playwright_report(report: Dict[str, Any]) -> ImportedReport:
+    """Flatten a Playwright JSON reporter file into one result per test and browser project"""
+    tests: List[ImportedTest] = []
+
+    def walk(suite: Dict[str, Any], file: str, titles: List[str]) -> None:
+        file = suite.get("file") or file
+        # The top-level suite is the spec file itself
+        if suite.get("title") and suite.get("title") != file:
+            titles = titles + [suite["title"]]
+        for spec in suite.get("specs", []):
+            spec_tests = spec.get("tests", [])
+            for test in spec_tests:
+                results = test.get("results") or [{}]
+                last = results[-1]  # Retries append results; the last one decides
+                if test.get("status") == "skipped" or last.get("status") == "skipped":
+                    status = "skipped"
+                elif last.get("status") == "passed":
+                    status = "passed"
+                else:
+                    status = "failed"
+
+                title = " › ".join(titles + [spec.get("title", "")])
+                if len(spec_tests) > 1 and test.get("projectName"):
+                    title = f"{title} [{test['projectName']}]"
+                error = last.get("error") or next(iter(last.get("errors") or []), None)
+                tests.append(
+                    ImportedTest(
+                        file=file or spec.get("file", ""),
+                        title=title,
+                        status=status,
+                        duration_ms=int(sum(r.get("duration", 0) for r in results)),
+                        error=error.get("message") if isinstance(error, dict) else None,
+                        attachments=[
+                            (a.get("name", "attachment"), a["path"])
+                            for a in last.get("attachments", [])
+                            if a.get("path")
+                        ],
+                    )
+                )
+        for child in suite.get("suites", []):
+            walk(child, file, titles)
+
+    for suite in report.get("suites", []):
+        walk(suite, "", [])
+
+    stats = report.get("stats", {})
+    return ImportedReport(
+        tests=tests,
+        started_at=_parse_time(stats.get("startTime")),
+        duration_ms=int(stats["duration"]) if stats.get("duration") is not None else None,
+    )
+
+
+def _mochawesome_screenshots(context: Any) -> List[Tuple[str, str]]:
+    """Pull screenshot paths out of a mochawesome test context"""
+    if isinstance(context, str):
+        try:
+            context = json.loads(context)
+        except ValueError:
+            pass
+    items = context if isinstance(context, list) else [context]
+    attachments = []
+    for item in items:
+        title, value = ("screenshot", item)
+        if isinstance(item, dict):
+            title, value = item.get("title", "screenshot"), item.get("value")
+        if isinstance(value, str) and value.lower().endswith((".png", ".jpg", ".jpeg")):
+            attachments.append((title, value))
+    return attachments
+
+
+def parse_mochawesome_report(report: Dict[str, Any]) -> ImportedReport:
+    """Flatten a Cypress mochawesome report into one result per test"""
+    tests: List[ImportedTest] = []
+
+    def walk(suite: Dict[str, Any], file: str, titles: List[str]) -> None:
+        file = suite.get("file") or suite.get("fullFile") or file
+        if suite.get("title"):
+            titles = titles + [suite["title"]]
+        for test in suite.get("tests", []):
+            if test.get("pass") or test.get("state") == "passed":
+                status = "passed"
+            elif test.get("fail") or test.get("state") == "failed":
+                status = "failed"
+            else:
+                status = "skipped"
+            err = test.get("err") or {}
+            tests.append(
+                ImportedTest(
+                    file=file,
+                    title=" › ".join(titles + [test.get("title", "")]),
+                    status=status,
+                    duration_ms=int(test.get("duration") or 0),
+                    error=err.get("message"),
+                    attachments=_mochawesome_screenshots(test.get("context")),
+                )
+            )
+        for child in suite.get("suites", []):
+            walk(child, file, titles)
+
+    for result in report.get("results", []):
+        walk(result, "", [])
+
+    stats = report.get("stats", {})
+    return ImportedReport(
+        tests=tests,
+        started_at=_parse_time(stats.get("start")),
+        duration_ms=int(stats["duration"]) if stats.get("duration") is not None else None,
+    )
+
+
+def load_report(report_format: str, path: str) -> ImportedReport:
+    """Read and parse a report file"""
+    if report_format not in REPORT_FORMATS:
+        raise ReportImportError(f"Format must be one of: {', '.join(REPORT_FORMATS)}")
+    try:
+        report = json.loads(Path(path).read_text(encoding="utf-8"))
+    except OSError as e:
+        raise ReportImportError(f"Can't read report: {e}")
+    except ValueError as e:
+        raise ReportImportError(f"Report is not valid JSON: {e}")
+    if not isinstance(report, dict):
+        raise ReportImportError("Report is not a JSON object")
+
+    if report_format == "playwright":
+        if "suites" not in report:
+            raise ReportImportError("Not a Playwright JSON report: missing 'suites'")
+        return parse_playwright_report(report)
+    if "results" not in report:
+        raise ReportImportError("Not a mochawesome report: missing 'results'")
+    return parse_mochawesome_report(report)
+
+
+async def _imported_step(
+    db: AsyncSession, project_id: str, report_format: str, test: ImportedTest, cache: Dict[str, Any]
+) -> Step:
+    """Find or create the test case, scenario and step an imported test maps to"""
+    test_case = cache.get(test.file)
+    if not test_case:
+        result = await db.execute(
+            select(TestCase).where(TestCase.project_id == project_id, TestCase.name == test.file)
+        )
+        test_case = result.scalars().first()
+        if not test_case:
+            test_case = TestCase(project_id=project_id, name=test.file, category=IMPORTED_CATEGORY)
+            db.add(test_case)
+            await db.flush()
+        cache[test.file] = test_case
+
+    result = await db.execute(
+        select(Scenario).where(Scenario.test_case_id == test_case.id, Scenario.name == test.title)
+    )
+    scenario = result.scalars().first()
+    if not scenario:
+        last = await db.execute(select(func.max(Scenario.position)).where(Scenario.test_case_id == test_case.id))
+        scenario = Scenario(test_case_id=test_case.id, name=test.title, position=(last.scalar() or 0.0) + 1.0)
+        db.add(scenario)
+        await db.flush()
+
+    result = await db.execute(
+        select(Step).where(Step.scenario_id == scenario.id, Step.step_type == IMPORTED_STEP_TYPE)
+    )
+    step = result.scalars().first()
+    if not step:
+        step = Step(
+            scenario_id=scenario.id,
+            step_order=1.0,
+            step_type=IMPORTED_STEP_TYPE,
+            label=f"Run in {report_format.capitalize()}",
+            config=json.dumps({"framework": report_format, "file": test.file, "title": test.title}),
+        )
+        db.add(step)
+        await db.flush()
+    return step
+
+
+async def import_external_results(
+    db: AsyncSession, project_id: str, run_name: str, report_format: str, path: str
+) -> TestRun:
+    """
+    Create a completed test run from a Playwright or Cypress report
+
+    Each spec file maps to a test case and each test to a scenario with a single
+    external step, matched by name so repeated imports line up over time.
+    Screenshots are attached to results; other artifacts are written to the run log.
+    """
+    report = load_report(report_format, path)
+    if not report.tests:
+        raise ReportImportError("Report contains no tests")
+
+    started_at = report.started_at or datetime.utcnow()
+    duration_ms = report.duration_ms
+    if duration_ms is None:
+        duration_ms = sum(test.duration_ms for test in report.tests)
+
+    test_run = TestRun(
+        project_id=project_id,
+        name=run_name,
+        status="running",
+        started_at=started_at,
+        completed_at=started_at + timedelta(milliseconds=duration_ms),
+        duration_ms=duration_ms,
+    )
+    db.add(test_run)
+    await db.flush()
+
+    cache: Dict[str, Any] = {}
+    logs: List[RunLogCreate] = []
+    for test in report.tests:
+        step = await _imported_step(db, project_id, report_format, test, cache)
+        screenshots = [p for name, p in test.attachments if p.lower().endswith((".png", ".jpg", ".jpeg"))]
+        db.add(
+            StepResult(
+                test_run_id=test_run.id,
+                step_id=step.id,
+                test_case_id=cache[test.file].id,
+                status=test.status,
+                duration_ms=test.duration_ms,
+                error_message=test.error,
+                screenshot_path=screenshots[0] if screenshots else None,
+            )
+        )
+        if test.error:
+            logs.append(RunLogCreate(level="error", source=report_format, message=f"{test.title}: {test.error}"))
+        for name, artifact in test.attachments:
+            logs.append(RunLogCreate(level="info", source=report_format, message=f"{test.title}: {name} {artifact}"))
+
+    test_run.passed = sum(1 for test in report.tests if test.status == "passed")
+    test_run.failed = sum(1 for test in report.tests if test.status == "failed")
+    test_run.skipped = sum(1 for test in report.tests if test.status == "skipped")
+    await db.flush()
+    await apply_run_status(db, test_run)
+    await db.commit()
+
+    if logs:
+        await add_run_logs(db, test_run.id, logs)
+    await db.refresh(test_run)
+    return test_run
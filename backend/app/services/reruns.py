@@ -0,0 +1,163 @@
+"""
+Reruns - Retries the scenarios that failed in a test run and merges the attempts
+
+A rerun is a new test run, linked to the one it retries through rerun_of,
+holding only the scenarios that failed there. Reruns can be rerun in turn,
+so a run and its reruns form a chain; the latest status of each scenario is
+its outcome in the newest run of the chain that ran it.
+"""
+import uuid
+from typing import Dict, List, Optional, Tuple
+
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Project, Scenario, Step, StepResult, TestRun
+from .dependencies import load_prerequisites
+from .executor import ScenarioExecutor, start_executor_run
+from .run_lifecycle import FINISHED_STATUSES
+from .test_runner import TestFramework
+from .web_executor import WebScenarioExecutor
+
+
+class RerunError(Exception):
+    """Raised when a run can't be rerun, such as while it's still running or when nothing failed"""
+
+
+class RerunRequest(BaseModel):
+    """Schema for where to rerun a test run's failed scenarios"""
+    target: str = "device"  # 'device' | 'browser'
+    device_id: Optional[str] = None
+    platform: Optional[str] = None  # 'android' | 'ios'
+    framework: TestFramework = TestFramework.PLAYWRIGHT
+    browser: Optional[str] = None  # Empty uses the project's default browser
+    headless: Optional[bool] = None
+    self_heal: bool = True
+    priority: str = "normal"  # 'high' | 'normal' | 'low'
+
+
+class LatestScenarioStatus(BaseModel):
+    """Schema for a scenario's outcome across a run and its reruns"""
+    scenario_id: str
+    name: str
+    status: str  # 'passed' | 'failed' | 'skipped', from the newest run that ran it
+    test_run_id: str  # The run the status comes from
+    first_status: str  # Its outcome in the original run
+    attempts: int
+    passed_on_rerun: bool  # Failed at first and passed on the latest attempt
+
+
+async def scenario_statuses(db: AsyncSession, test_run_ids: List[str]) -> Dict[Tuple[str, str], str]:
+    """Each scenario's outcome by (test run id, scenario id), judged as the run's scenario outcomes are"""
+    result = await db.execute(
+        select(StepResult.test_run_id, Step.scenario_id, StepResult.status)
+        .join(Step, StepResult.step_id == Step.id)
+        .where(StepResult.test_run_id.in_(test_run_ids))
+        .order_by(StepResult.created_at)
+    )
+    counts: Dict[Tuple[str, str], Dict[str, int]] = {}
+    for test_run_id, scenario_id, status in result.all():
+        tally = counts.setdefault((test_run_id, scenario_id), {"passed": 0, "failed": 0, "skipped": 0})
+        tally[status if status in ("passed", "skipped") else "failed"] += 1
+    return {
+        key: "failed" if tally["failed"] else "skipped" if tally["skipped"] and not tally["passed"] else "passed"
+        for key, tally in counts.items()
+    }
+
+
+async def rerun_failures(db: AsyncSession, test_run_id: str, options: RerunRequest) -> TestRun:
+    """Start a test run of only the scenarios that failed in a finished run, linked to it by rerun_of"""
+    original = await db.get(TestRun, test_run_id)
+    if original.status not in FINISHED_STATUSES:
+        raise RerunError("Wait for the test run to finish before rerunning its failures")
+    statuses = await scenario_statuses(db, [test_run_id])
+    # The dict keeps the order the scenarios first reported results in
+    scenario_ids = [scenario_id for (_, scenario_id), status in statuses.items() if status == "failed"]
+    if not scenario_ids:
+        raise RerunError("The test run has no failed scenarios")
+
+    project = await db.get(Project, original.project_id)
+    if options.target == "browser":
+        executor = WebScenarioExecutor(options.framework, options.browser, options.headless)
+    elif options.device_id and options.platform:
+        executor = ScenarioExecutor(
+            options.device_id, options.platform, self_heal=options.self_heal, ai_free=project.ai_free
+        )
+    else:
+        raise RerunError("device_id and platform are required to rerun on a device")
+
+    test_run = TestRun(
+        id=str(uuid.uuid4()),
+        project_id=original.project_id,
+        name=f"{original.name} (rerun of failures)",
+        rerun_of=original.id,
+    )
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    prerequisites = await load_prerequisites(db)
+    planned = {
+        scenario_id: [p for p in prerequisites.get(scenario_id, []) if p in scenario_ids[:index]]
+        for index, scenario_id in enumerate(scenario_ids)
+    }
+    start_executor_run(executor, scenario_ids, test_run.id, planned, options.priority)
+    return test_run
+
+
+async def rerun_chain(db: AsyncSession, test_run_id: str) -> List[TestRun]:
+    """The original run of any run in a chain, followed by its reruns, oldest first"""
+    root = await db.get(TestRun, test_run_id)
+    while root.rerun_of:
+        parent = await db.get(TestRun, root.rerun_of)
+        if not parent:
+            break
+        root = parent
+
+    chain = [root]
+    frontier = [root.id]
+    while frontier:
+        result = await db.execute(select(TestRun).where(TestRun.rerun_of.in_(frontier)))
+        reruns = list(result.scalars().all())
+        chain.extend(reruns)
+        frontier = [run.id for run in reruns]
+    chain.sort(key=lambda run: run.created_at)
+    return chain
+
+
+async def latest_status(db: AsyncSession, test_run_id: str) -> List[LatestScenarioStatus]:
+    """Each scenario's latest outcome across a run and its reruns, in the order the original ran them"""
+    chain = await rerun_chain(db, test_run_id)
+    statuses = await scenario_statuses(db, [run.id for run in chain])
+    by_run: Dict[str, List[Tuple[str, str]]] = {}
+    for (run_id, scenario_id), status in statuses.items():
+        by_run.setdefault(run_id, []).append((scenario_id, status))
+
+    merged: Dict[str, LatestScenarioStatus] = {}
+    for run in chain:
+        for scenario_id, status in by_run.get(run.id, []):
+            current = merged.get(scenario_id)
+            if current:
+                current.status = status
+                current.test_run_id = run.id
+                current.attempts += 1
+                current.passed_on_rerun = status == "passed" and current.first_status == "failed"
+            else:
+                merged[scenario_id] = LatestScenarioStatus(
+                    scenario_id=scenario_id,
+                    name="",
+                    status=status,
+                    test_run_id=run.id,
+                    first_status=status,
+                    attempts=1,
+                    passed_on_rerun=False,
+                )
+
+    if merged:
+        names = dict((await db.execute(
+            select(Scenario.id, Scenario.name).where(Scenario.id.in_(merged))
+        )).all())
+        for scenario_id, outcome in merged.items():
+            outcome.name = names.get(scenario_id, "")
+    return list(merged.values())
@@ -0,0 +1,84 @@
+"""
+Result Writer - Stores step results in batches
+
+Runs record a result per step, so fast runs would otherwise write and commit
+row by row. The writer buffers results and inserts them with one multi-row
+INSERT when enough have built up or the oldest has waited long enough. Flushes
+happen as results are added, on the runner's own session, so no second
+connection competes with the runner's for SQLite's write lock.
+"""
+import json
+import time
+import uuid
+from datetime import datetime
+from typing import Any, Dict, List, Optional
+
+from sqlalchemy import insert
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import StepResult, StepResultCreate
+from .secret_masking import mask_data, mask_secrets
+
+
+def step_result_row(data: StepResultCreate) -> Dict[str, Any]:
+    """
+    A step result's column values, timestamped now so batched rows keep their order
+
+    Bulk inserts skip the ORM's insert events, so secrets are masked here.
+    """
+    return {
+        "id": str(uuid.uuid4()),
+        "test_run_id": data.test_run_id,
+        "step_id": data.step_id,
+        "test_case_id": data.test_case_id,
+        "status": data.status,
+        "duration_ms": data.duration_ms,
+        "error_message": mask_secrets(data.error_message),
+        "screenshot_path": data.screenshot_path,
+        "screenshot_bytes": data.screenshot_bytes,
+        "screenshot_original_bytes": data.screenshot_original_bytes,
+        "healed": data.healed,
+        "healed_locator": json.dumps(data.healed_locator) if data.healed_locator else None,
+        "details": json.dumps(mask_data(data.details)) if data.details else None,
+        "viewport": data.viewport,
+        "created_at": datetime.utcnow(),
+    }
+
+
+async def insert_step_results(db: AsyncSession, rows: List[Dict[str, Any]]) -> None:
+    """Insert step results in one statement without committing"""
+    if rows:
+        await db.execute(insert(StepResult), rows)
+
+
+class StepResultWriter:
+    """Buffers a run's step results, flushing every batch_size results or once the oldest is flush_interval_ms old"""
+
+    def __init__(self, db: AsyncSession, batch_size: int = 20, flush_interval_ms: int = 1000):
+        self.db = db
+        self.batch_size = max(batch_size, 1)
+        self.flush_interval_ms = flush_interval_ms
+        self._pending: List[Dict[str, Any]] = []
+        self._oldest: Optional[float] = None
+
+    async def add(self, data: StepResultCreate) -> None:
+        if not self._pending:
+            self._oldest = time.monotonic()
+        self._pending.append(step_result_row(data))
+        waited_ms = (time.monotonic() - self._oldest) * 1000
+        if len(self._pending) >= self.batch_size or waited_ms >= self.flush_interval_ms:
+            await self.flush()
+
+    async def flush(self) -> None:
+        """Write everything buffered so far; call before reading a run's results back"""
+        rows, self._pending = self._pending, []
+        if not rows:
+            return
+        try:
+            await insert_step_results(self.db, rows)
+            await self.db.commit()
+        except Exception:
+            await self.db.rollback()
+            self._pending = rows + self._pending  # Kept for the next flush to retry
+            raise
+        self._oldest = None
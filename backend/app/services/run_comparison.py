@@ -0,0 +1,193 @@
+"""
+Run Comparison - Diffs two test runs by scenario and step
+"""
+import asyncio
+import hashlib
+from pathlib import Path
+from typing import Dict, List, Optional, Set, Tuple
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import RunComparison, Scenario, ScenarioDelta, Step, StepDelta, StepResult, TestRun, TestRunResponse
+
+# A run is slower when it takes this much longer, both relatively and absolutely
+DEFAULT_DURATION_THRESHOLD_PCT = 20.0
+DEFAULT_MIN_DURATION_DELTA_MS = 200
+
+# Screenshots differing in more than this fraction of pixels count as changed
+DEFAULT_SCREENSHOT_THRESHOLD = 0.01
+
+
+def classify_change(status_a: Optional[str], status_b: Optional[str]) -> str:
+    if status_a is None:
+        return "added"
+    if status_b is None:
+        return "removed"
+    if status_a == status_b:
+        return "unchanged"
+    if status_b == "failed":
+        return "newly_failing"
+    if status_a == "failed" and status_b == "passed":
+        return "newly_passing"
+    return "status_changed"
+
+
+def is_duration_regression(
+    duration_a: Optional[int], duration_b: Optional[int], threshold_pct: float, min_delta_ms: int
+) -> bool:
+    if not duration_a or duration_b is None:
+        return False
+    delta = duration_b - duration_a
+    return delta >= min_delta_ms and delta / duration_a * 100 >= threshold_pct
+
+
+def _file_digest(path: Path) -> str:
+    return hashlib.sha256(path.read_bytes()).hexdigest()
+
+
+def screenshot_difference(path_a: Optional[str], path_b: Optional[str]) -> Optional[float]:
+    """
+    Fraction of pixels that differ between two screenshots, None if either is missing
+
+    Screenshots of different sizes count as entirely different. Without Pillow,
+    only identical files are recognised, and anything else counts as different.
+    """
+    if not path_a or not path_b:
+        return None
+    file_a, file_b = Path(path_a), Path(path_b)
+    if not file_a.is_file() or not file_b.is_file():
+        return None
+    if _file_digest(file_a) == _file_digest(file_b):
+        return 0.0
+    try:
+        from PIL import Image, ImageChops
+    except ImportError:
+        return 1.0
+
+    with Image.open(file_a) as image_a, Image.open(file_b) as image_b:
+        if image_a.size != image_b.size:
+            return 1.0
+        diff = ImageChops.difference(image_a.convert("RGB"), image_b.convert("RGB")).convert("L")
+        changed = sum(count for value, count in enumerate(diff.histogram()) if value > 16)
+        return changed / (image_a.width * image_a.height)
+
+
+def scenario_status(statuses: List[str]) -> Optional[str]:
+    if not statuses:
+        return None
+    if "failed" in statuses:
+        return "failed"
+    if "passed" in statuses:
+        return "passed"
+    return statuses[0]
+
+
+async def _results_by_step(db: AsyncSession, test_run_id: str) -> Dict[str, StepResult]:
+    result = await db.execute(
+        select(StepResult).where(StepResult.test_run_id == test_run_id).order_by(StepResult.created_at)
+    )
+    # A step that ran more than once in a run, such as on retry, is judged by its last result
+    return {step_result.step_id: step_result for step_result in result.scalars().all()}
+
+
+async def compare_runs(
+    db: AsyncSession,
+    run_a: TestRun,
+    run_b: TestRun,
+    duration_threshold_pct: float = DEFAULT_DURATION_THRESHOLD_PCT,
+    min_duration_delta_ms: int = DEFAULT_MIN_DURATION_DELTA_MS,
+    screenshot_threshold: float = DEFAULT_SCREENSHOT_THRESHOLD,
+    scenario_ids: Optional[Set[str]] = None,
+) -> RunComparison:
+    """
+    Compare run b against an earlier run a, step by step
+
+    Steps are matched by ID, so a step that only one run reached shows up as
+    added or removed. Scenarios that changed are listed first. Pass
+    scenario_ids to compare only those scenarios.
+    """
+    results_a = await _results_by_step(db, run_a.id)
+    results_b = await _results_by_step(db, run_b.id)
+    step_ids = set(results_a) | set(results_b)
+    steps = (
+        (await db.execute(select(Step).where(Step.id.in_(step_ids)).order_by(Step.step_order))).scalars().all()
+        if step_ids else []
+    )
+    if scenario_ids is not None:
+        steps = [step for step in steps if step.scenario_id in scenario_ids]
+    scenario_ids = {step.scenario_id for step in steps}
+    scenarios = {
+        scenario.id: scenario
+        for scenario in (await db.execute(select(Scenario).where(Scenario.id.in_(scenario_ids)))).scalars().all()
+    } if scenario_ids else {}
+
+    step_deltas: Dict[str, List[StepDelta]] = {}
+    for step in steps:
+        a, b = results_a.get(step.id), results_b.get(step.id)
+        screenshot_a = a.screenshot_path if a else None
+        screenshot_b = b.screenshot_path if b else None
+        difference = await asyncio.to_thread(screenshot_difference, screenshot_a, screenshot_b)
+        delta = StepDelta(
+            step_id=step.id,
+            label=step.label,
+            step_type=step.step_type,
+            status_a=a.status if a else None,
+            status_b=b.status if b else None,
+            change=classify_change(a.status if a else None, b.status if b else None),
+            duration_a_ms=a.duration_ms if a else None,
+            duration_b_ms=b.duration_ms if b else None,
+            screenshot_a=screenshot_a,
+            screenshot_b=screenshot_b,
+            screenshot_diff=difference,
+            screenshot_changed=difference is not None and difference > screenshot_threshold,
+        )
+        delta.duration_regression = is_duration_regression(
+            delta.duration_a_ms, delta.duration_b_ms, duration_threshold_pct, min_duration_delta_ms
+        )
+        step_deltas.setdefault(step.scenario_id, []).append(delta)
+
+    scenario_deltas = []
+    for scenario_id, deltas in step_deltas.items():
+        status_a = scenario_status([d.status_a for d in deltas if d.status_a])
+        status_b = scenario_status([d.status_b for d in deltas if d.status_b])
+        duration_a = _total_duration([d.duration_a_ms for d in deltas if d.status_a])
+        duration_b = _total_duration([d.duration_b_ms for d in deltas if d.status_b])
+        scenario = scenarios.get(scenario_id)
+        scenario_deltas.append(ScenarioDelta(
+            scenario_id=scenario_id,
+            name=scenario.name if scenario else scenario_id,
+            status_a=status_a,
+            status_b=status_b,
+            change=classify_change(status_a, status_b),
+            duration_a_ms=duration_a,
+            duration_b_ms=duration_b,
+            duration_regression=is_duration_regression(
+                duration_a, duration_b, duration_threshold_pct, min_duration_delta_ms
+            ),
+            steps=deltas,
+        ))
+    scenario_deltas.sort(key=_scenario_sort_key)
+
+    all_steps = [d for s in scenario_deltas for d in s.steps]
+    return RunComparison(
+        run_a=TestRunResponse.model_validate(run_a),
+        run_b=TestRunResponse.model_validate(run_b),
+        newly_failing=sum(1 for d in all_steps if d.change == "newly_failing"),
+        newly_passing=sum(1 for d in all_steps if d.change == "newly_passing"),
+        duration_regressions=sum(1 for d in all_steps if d.duration_regression),
+        changed_screenshots=sum(1 for d in all_steps if d.screenshot_changed),
+        scenarios=scenario_deltas,
+    )
+
+
+def _total_duration(durations: List[Optional[int]]) -> Optional[int]:
+    known = [d for d in durations if d is not None]
+    return sum(known) if known else None
+
+
+def _scenario_sort_key(delta: ScenarioDelta) -> Tuple[int, str]:
+    changed = delta.change != "unchanged" or any(
+        s.change != "unchanged" or s.duration_regression or s.screenshot_changed for s in delta.steps
+    )
+    return (0 if delta.change == "newly_failing" else 1 if changed else 2, delta.name)
@@ -0,0 +1,163 @@
+"""
+Run Lifecycle - Moves test runs between states, rejecting transitions the lifecycle doesn't allow
+
+Each transition is a compare-and-swap on the run's status, so two callers racing
+to finish the same run can't both succeed, and every transition is recorded
+with its time.
+"""
+from datetime import datetime
+from typing import Any, Optional
+
+from sqlalchemy import or_, select, update
+from sqlalchemy.exc import OperationalError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from ..models import RunTransition, TestRun
+from .events import event_bus
+from .run_status import run_outcome
+
+# 'aborted' runs were interrupted by the app stopping rather than by a user or a failure
+FINISHED_STATUSES = {"passed", "passed_with_warnings", "failed", "cancelled", "aborted"}
+
+TRANSITIONS = {
+    "pending": {"running", "cancelled", "aborted"},
+    "running": FINISHED_STATUSES,
+}
+
+
+class RunTransitionError(Exception):
+    """Raised when a test run can't move from its current state to the one asked for"""
+
+    def __init__(self, current: str, target: str):
+        self.current = current
+        self.target = target
+        super().__init__(f"Can't move a {current} run to {target}")
+
+
+class RunFinishedError(RunTransitionError):
+    """Raised when a test run has already finished"""
+
+    def __init__(self, current: str, target: str):
+        super().__init__(current, target)
+        self.args = (f"The run already finished as {current}",)
+
+
+def check_transition(current: str, target: str) -> None:
+    if target in TRANSITIONS.get(current, ()):
+        return
+    if current in FINISHED_STATUSES:
+        raise RunFinishedError(current, target)
+    raise RunTransitionError(current, target)
+
+
+async def transition_run(
+    db: AsyncSession,
+    test_run: TestRun,
+    target: str,
+    reason: Optional[str] = None,
+    **values: Any,
+) -> TestRun:
+    """
+    Move a run to a new state, setting any other columns given, and commit
+
+    Fails if the run's stored status changed since it was loaded, for example
+    because another request completed it first.
+    """
+    current = test_run.status
+    check_transition(current, target)
+    result = await db.execute(
+        update(TestRun)
+        .where(TestRun.id == test_run.id, TestRun.status == current)
+        .values(status=target, **values)
+    )
+    if result.rowcount != 1:
+        await db.refresh(test_run, ["status"])
+        check_transition(test_run.status, target)
+        raise RunTransitionError(test_run.status, target)
+
+    db.add(RunTransition(test_run_id=test_run.id, from_status=current, to_status=target, reason=reason))
+    await db.commit()
+    await db.refresh(test_run)
+    event_bus.publish("run:status_changed", {
+        "test_run_id": test_run.id,
+        "from_status": current,
+        "to_status": target,
+        "reason": reason,
+    })
+    return test_run
+
+
+async def start_run(db: AsyncSession, test_run: TestRun) -> TestRun:
+    return await transition_run(db, test_run, "running", started_at=datetime.utcnow())
+
+
+async def complete_run(
+    db: AsyncSession,
+    test_run: TestRun,
+    passed: int,
+    failed: int,
+    skipped: int,
+    reason: Optional[str] = None,
+    force_failed: bool = False,
+) -> TestRun:
+    """Finish a running run with its step counts, deriving its status from its step results"""
+    check_transition(test_run.status, "passed")
+    completed_at = datetime.utcnow()
+    test_run.passed, test_run.failed, test_run.skipped = passed, failed, skipped
+    status, expected_failed = await run_outcome(db, test_run)
+    return await transition_run(
+        db, test_run, "failed" if force_failed else status, reason,
+        passed=passed,
+        failed=failed,
+        skipped=skipped,
+        expected_failed=expected_failed,
+        completed_at=completed_at,
+        duration_ms=(
+            int((completed_at - test_run.started_at).total_seconds() * 1000) if test_run.started_at else None
+        ),
+    )
+
+
+async def cancel_run(db: AsyncSession, test_run: TestRun, reason: Optional[str] = None) -> TestRun:
+    return await transition_run(db, test_run, "cancelled", reason, completed_at=datetime.utcnow())
+
+
+async def fail_run(db: AsyncSession, test_run: TestRun, reason: Optional[str] = None) -> TestRun:
+    return await transition_run(db, test_run, "failed", reason, completed_at=datetime.utcnow())
+
+
+async def recover_orphaned_runs() -> int:
+    """
+    Abort runs left pending or running by a previous process
+
+    The run queue and agent jobs only live in memory, so nothing will ever
+    finish these runs. Runs of other instances sharing the database are left
+    alone; runs from before instances were recorded have no owner.
+    """
+    aborted = 0
+    try:
+        async with AsyncSessionLocal() as db:
+            result = await db.execute(
+                select(TestRun).where(
+                    TestRun.status.in_(TRANSITIONS),
+                    or_(TestRun.owner_instance == settings.get_instance_id(), TestRun.owner_instance.is_(None)),
+                )
+            )
+            for test_run in result.scalars().all():
+                await transition_run(
+                    db, test_run, "aborted", "The app stopped before the run finished",
+                    completed_at=datetime.utcnow(),
+                )
+                aborted += 1
+    except OperationalError:
+        pass  # Database not available; the runs are aborted on the next start
+    return aborted
+
+
+async def list_transitions(db: AsyncSession, test_run_id: str) -> list:
+    result = await db.execute(
+        select(RunTransition).where(RunTransition.test_run_id == test_run_id).order_by(RunTransition.created_at)
+    )
+    return list(result.scalars().all())
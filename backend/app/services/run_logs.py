@@ -0,0 +1,66 @@
+"""
+Run Logs - Persists structured console output for test runs and streams it as events
+"""
+from typing import List, Optional
+
+from sqlalchemy import func, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import RunLog, RunLogCreate, RunLogPage, RunLogResponse
+from .events import event_bus
+
+# Ordered from least to most severe; filtering by a level includes everything more severe
+LOG_LEVELS = ("debug", "info", "warning", "error")
+
+
+async def add_run_logs(db: AsyncSession, test_run_id: str, entries: List[RunLogCreate]) -> List[RunLog]:
+    """Store log lines for a run and publish each as a 'run:log' event"""
+    for entry in entries:
+        if entry.level not in LOG_LEVELS:
+            raise ValueError(f"Level must be one of: {', '.join(LOG_LEVELS)}")
+
+    logs = [RunLog(test_run_id=test_run_id, **entry.model_dump()) for entry in entries]
+    db.add_all(logs)
+    await db.commit()
+    for log in logs:
+        event_bus.publish("run:log", RunLogResponse.model_validate(log).model_dump(mode="json"))
+    return logs
+
+
+async def query_run_logs(
+    db: AsyncSession,
+    test_run_id: str,
+    level: Optional[str] = None,
+    source: Optional[str] = None,
+    step_index: Optional[int] = None,
+    search: Optional[str] = None,
+    offset: int = 0,
+    limit: int = 200,
+) -> RunLogPage:
+    """Page through a run's log lines, filtered by minimum level, source, step or text"""
+    conditions = [RunLog.test_run_id == test_run_id]
+    if level:
+        if level not in LOG_LEVELS:
+            raise ValueError(f"Level must be one of: {', '.join(LOG_LEVELS)}")
+        conditions.append(RunLog.level.in_(LOG_LEVELS[LOG_LEVELS.index(level):]))
+    if source:
+        conditions.append(RunLog.source == source)
+    if step_index is not None:
+        conditions.append(RunLog.step_index == step_index)
+    if search:
+        conditions.append(RunLog.message.ilike(f"%{search}%"))
+
+    total = (await db.execute(select(func.count()).select_from(RunLog).where(*conditions))).scalar() or 0
+    result = await db.execute(
+        select(RunLog)
+        .where(*conditions)
+        .order_by(RunLog.created_at, RunLog.id)
+        .offset(offset)
+        .limit(limit)
+    )
+    return RunLogPage(
+        items=[RunLogResponse.model_validate(log) for log in result.scalars().all()],
+        total=total,
+        offset=offset,
+        limit=limit,
+    )
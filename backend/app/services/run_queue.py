@@ -0,0 +1,176 @@
+"""
+Run Queue - Starts queued test runs as device and browser capacity frees up
+"""
+import asyncio
+from dataclasses import dataclass, field
+from datetime import datetime
+from typing import Awaitable, Callable, Dict, List, Optional
+
+from ..db import AsyncSessionLocal
+from ..models import TestRun
+from .app_settings import get_setting
+from .events import event_bus
+from .run_lifecycle import cancel_run
+
+# Lower values start first
+PRIORITIES = {"high": 0, "normal": 1, "low": 2}
+
+
+class QueueError(Exception):
+    """Raised for unknown queued runs or invalid queue changes"""
+
+
+@dataclass
+class QueuedRun:
+    test_run_id: str
+    device_id: str
+    platform: str  # 'android' | 'ios' | 'web'
+    priority: str
+    start: Callable[[], Awaitable[None]]
+    queued_at: datetime = field(default_factory=datetime.utcnow)
+    started_at: Optional[datetime] = None
+    task: Optional[asyncio.Task] = None
+
+
+class RunQueue:
+    """
+    Queued and running test runs, in the order queued runs will be considered
+
+    Runs are inserted after others of the same or higher priority. A run that
+    can't start yet doesn't hold up later runs on other devices or in the browser.
+    """
+
+    def __init__(self):
+        self._queued: List[QueuedRun] = []
+        self._running: Dict[str, QueuedRun] = {}
+        self._lock = asyncio.Lock()
+
+    def enqueue(
+        self,
+        test_run_id: str,
+        device_id: str,
+        platform: str,
+        start: Callable[[], Awaitable[None]],
+        priority: str = "normal",
+    ) -> QueuedRun:
+        if priority not in PRIORITIES:
+            raise QueueError(f"Priority must be one of: {', '.join(PRIORITIES)}")
+        run = QueuedRun(test_run_id, device_id, platform, priority, start)
+        self._insert(run)
+        self._changed()
+        self._schedule_dispatch()
+        return run
+
+    def _insert(self, run: QueuedRun) -> None:
+        rank = PRIORITIES[run.priority]
+        index = len(self._queued)
+        while index > 0 and PRIORITIES[self._queued[index - 1].priority] > rank:
+            index -= 1
+        self._queued.insert(index, run)
+
+    def list(self) -> List[QueuedRun]:
+        """Running runs first, then queued runs in the order they'll be considered"""
+        return [*self._running.values(), *self._queued]
+
+    def running(self, test_run_id: str) -> Optional[QueuedRun]:
+        return self._running.get(test_run_id)
+
+    def _find_queued(self, test_run_id: str) -> QueuedRun:
+        for run in self._queued:
+            if run.test_run_id == test_run_id:
+                return run
+        raise QueueError("Test run is not queued")
+
+    def move(self, test_run_id: str, position: int) -> None:
+        """Move a queued run to a position among the queued runs, ignoring priorities"""
+        run = self._find_queued(test_run_id)
+        self._queued.remove(run)
+        self._queued.insert(max(0, min(position, len(self._queued))), run)
+        self._changed()
+        self._schedule_dispatch()
+
+    def set_priority(self, test_run_id: str, priority: str) -> None:
+        if priority not in PRIORITIES:
+            raise QueueError(f"Priority must be one of: {', '.join(PRIORITIES)}")
+        run = self._find_queued(test_run_id)
+        self._queued.remove(run)
+        run.priority = priority
+        self._insert(run)
+        self._changed()
+        self._schedule_dispatch()
+
+    async def cancel(self, test_run_id: str) -> bool:
+        """Cancel a running run, or drop a queued one and mark it cancelled"""
+        running = self._running.get(test_run_id)
+        if running and running.task:
+            running.task.cancel()
+            return True
+        try:
+            run = self._find_queued(test_run_id)
+        except QueueError:
+            return False
+        self._queued.remove(run)
+        async with AsyncSessionLocal() as db:
+            test_run = await db.get(TestRun, test_run_id)
+            if test_run and test_run.status == "pending":
+                await cancel_run(db, test_run, "Removed from the queue")
+        self._changed()
+        return True
+
+    async def cancel_device(self, device_id: str) -> int:
+        """Cancel every queued and running run on a device"""
+        run_ids = [run.test_run_id for run in self.list() if run.device_id == device_id]
+        return sum([await self.cancel(run_id) for run_id in run_ids])
+
+    def _schedule_dispatch(self) -> None:
+        asyncio.get_running_loop().create_task(self._dispatch())
+
+    async def _limits(self) -> Dict[str, int]:
+        async with AsyncSessionLocal() as db:
+            return {
+                "per_device": await get_setting(db, "queue.max_runs_per_device"),
+                "web": await get_setting(db, "queue.max_web_runs"),
+                "mobile": await get_setting(db, "queue.max_mobile_runs"),
+            }
+
+    def _can_start(self, run: QueuedRun, limits: Dict[str, int]) -> bool:
+        """Limits of 0 mean unlimited"""
+        running = list(self._running.values())
+        if run.platform == "web":
+            web = sum(1 for r in running if r.platform == "web")
+            return not limits["web"] or web < limits["web"]
+        mobile = [r for r in running if r.platform != "web"]
+        if limits["mobile"] and len(mobile) >= limits["mobile"]:
+            return False
+        on_device = sum(1 for r in mobile if r.device_id == run.device_id)
+        return not limits["per_device"] or on_device < limits["per_device"]
+
+    async def _dispatch(self) -> None:
+        async with self._lock:
+            if not self._queued:
+                return
+            limits = await self._limits()
+            started = False
+            for run in list(self._queued):
+                if not self._can_start(run, limits):
+                    continue
+                self._queued.remove(run)
+                run.started_at = datetime.utcnow()
+                run.task = asyncio.create_task(run.start())
+                run.task.add_done_callback(lambda _, run_id=run.test_run_id: self._finished(run_id))
+                self._running[run.test_run_id] = run
+                started = True
+            if started:
+                self._changed()
+
+    def _finished(self, test_run_id: str) -> None:
+        self._running.pop(test_run_id, None)
+        self._changed()
+        self._schedule_dispatch()
+
+    def _changed(self) -> None:
+        event_bus.publish("queue:changed", {"queued": len(self._queued), "running": len(self._running)})
+
+
+# Singleton instance
+run_queue = RunQueue()
@@ -0,0 +1,186 @@
+"""
+Run Reports - Renders a test run as a standalone HTML report, JUnit XML or PDF
+
+The PDF is the HTML report printed by a headless Chromium-based browser,
+so it reads the same as the report people get by email.
+"""
+import asyncio
+import tempfile
+from html import escape
+from pathlib import Path
+from typing import List, Tuple, Union
+from xml.etree import ElementTree
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..models import Step, StepResult, TestCase, TestRun
+from .browsers import chromium_executable
+from .run_status import get_run_breakdown
+
+STATUS_COLORS = {
+    "passed": "#15803d",
+    "failed": "#b91c1c",
+    "expected_failure": "#b45309",
+    "skipped": "#6b7280",
+    "cancelled": "#6b7280",
+    "aborted": "#6b7280",
+}
+
+PRINT_TIMEOUT = 60.0
+
+
+class ReportExportError(Exception):
+    """Raised when a report can't be exported, such as when no browser can print it"""
+
+
+async def _step_results(db: AsyncSession, test_run_id: str) -> List[Tuple[StepResult, Step, TestCase]]:
+    result = await db.execute(
+        select(StepResult, Step, TestCase)
+        .join(Step, StepResult.step_id == Step.id)
+        .join(TestCase, StepResult.test_case_id == TestCase.id)
+        .where(StepResult.test_run_id == test_run_id)
+        .order_by(StepResult.created_at)
+    )
+    return [tuple(row) for row in result.all()]
+
+
+def _seconds(duration_ms) -> str:
+    return f"{(duration_ms or 0) / 1000:.3f}"
+
+
+async def render_junit_xml(db: AsyncSession, test_run: TestRun) -> str:
+    """JUnit XML with a test suite per test case and a test case per step"""
+    by_case = {}
+    for step_result, step, test_case in await _step_results(db, test_run.id):
+        by_case.setdefault(test_case.id, (test_case, []))[1].append((step_result, step))
+
+    root = ElementTree.Element("testsuites", {
+        "name": test_run.name,
+        "tests": str(test_run.passed + test_run.failed + test_run.skipped),
+        "failures": str(test_run.failed),
+        "skipped": str(test_run.skipped),
+        "time": _seconds(test_run.duration_ms),
+    })
+    for test_case, results in by_case.values():
+        suite = ElementTree.SubElement(root, "testsuite", {
+            "name": test_case.name,
+            "tests": str(len(results)),
+            "failures": str(sum(1 for result, _ in results if result.status == "failed")),
+            "skipped": str(sum(1 for result, _ in results if result.status == "skipped")),
+            "time": _seconds(sum(result.duration_ms or 0 for result, _ in results)),
+        })
+        for step_result, step in results:
+            case = ElementTree.SubElement(suite, "testcase", {
+                "classname": test_case.name,
+                "name": f"{step.label} [{step_result.viewport}]" if step_result.viewport else step.label,
+                "time": _seconds(step_result.duration_ms),
+            })
+            if step_result.status == "failed":
+                failure = ElementTree.SubElement(case, "failure", {"message": step_result.error_message or "Failed"})
+                failure.text = step_result.error_message or ""
+            elif step_result.status == "skipped":
+                ElementTree.SubElement(case, "skipped", {"message": step_result.error_message or ""})
+    ElementTree.indent(root)
+    return '<?xml version="1.0" encoding="UTF-8"?>\n' + ElementTree.tostring(root, encoding="unicode") + "\n"
+
+
+def _badge(status: str) -> str:
+    color = STATUS_COLORS.get(status, "#374151")
+    return (
+        f'<span style="background:{color};color:#fff;border-radius:4px;padding:2px 8px;'
+        f'font-size:12px;text-transform:uppercase">{escape(status.replace("_", " "))}</span>'
+    )
+
+
+async def render_html_report(db: AsyncSession, test_run: TestRun) -> str:
+    """A self-contained HTML report with inline styles, so it also renders in email clients"""
+    breakdown = await get_run_breakdown(db, test_run)
+    failures = [
+        (step_result, step, test_case)
+        for step_result, step, test_case in await _step_results(db, test_run.id)
+        if step_result.status == "failed"
+    ]
+    totals = breakdown.totals
+    cell = 'style="padding:6px 10px;border-bottom:1px solid #e5e7eb;text-align:left"'
+
+    parts = [
+        '<!DOCTYPE html><html><head><meta charset="utf-8">',
+        f"<title>{escape(test_run.name)}</title></head>",
+        '<body style="font-family:-apple-system,Segoe UI,Helvetica,Arial,sans-serif;color:#111827;margin:24px">',
+        f'<h2 style="margin:0 0 8px">{escape(test_run.name)} {_badge(test_run.status)}</h2>',
+        '<p style="color:#4b5563;margin:0 0 16px">',
+        f"{totals.passed} passed, {totals.failed} failed",
+        f" ({totals.expected_failed} expected)" if totals.expected_failed else "",
+        f", {totals.skipped} skipped",
+        f", {breakdown.healed_steps} healed" if breakdown.healed_steps else "",
+        f" &middot; {_seconds(test_run.duration_ms)}s" if test_run.duration_ms else "",
+        f" &middot; started {test_run.started_at:%Y-%m-%d %H:%M} UTC" if test_run.started_at else "",
+        "</p>",
+        '<table style="border-collapse:collapse;width:100%;font-size:14px">',
+        f"<tr><th {cell}>Test case</th><th {cell}>Category</th><th {cell}>Status</th>"
+        f"<th {cell}>Passed</th><th {cell}>Failed</th><th {cell}>Skipped</th></tr>",
+    ]
+    for outcome in breakdown.test_cases:
+        issue = ""
+        if outcome.issue_url:
+            issue = f' <a href="{escape(outcome.issue_url)}">{escape(outcome.issue_key or "")}</a>'
+        parts.append(
+            f"<tr><td {cell}>{escape(outcome.name)}{issue}</td><td {cell}>{escape(outcome.category or '')}</td>"
+            f"<td {cell}>{_badge(outcome.status)}</td><td {cell}>{outcome.passed}</td>"
+            f"<td {cell}>{outcome.failed}</td><td {cell}>{outcome.skipped}</td></tr>"
+        )
+    parts.append("</table>")
+
+    if failures:
+        parts.append('<h3 style="margin:24px 0 8px">Failures</h3>')
+        for step_result, step, test_case in failures:
+            parts.append(
+                f'<div style="margin:0 0 12px"><strong>{escape(test_case.name)}</strong> &rsaquo; '
+                f"{escape(step.label)}"
+                '<pre style="background:#f3f4f6;padding:8px;border-radius:4px;white-space:pre-wrap;margin:4px 0 0">'
+                f"{escape(step_result.error_message or 'Failed')}</pre></div>"
+            )
+    parts.append(
+        f'<p style="color:#9ca3af;font-size:12px;margin-top:24px">{escape(settings.app_name)} &middot; '
+        f"run {escape(test_run.id)}</p></body></html>"
+    )
+    return "".join(parts)
+
+
+# ============================================
+# PDF Export
+# ============================================
+
+
+async def export_run_pdf(db: AsyncSession, test_run_id: str, path: Union[str, Path]) -> Path:
+    """Print a test run's HTML report to a PDF file"""
+    test_run = await db.get(TestRun, test_run_id)
+    if not test_run:
+        raise ReportExportError("Test run not found")
+    browser = chromium_executable()
+    if not browser:
+        raise ReportExportError("Printing a PDF needs Chrome, Chromium or Edge; install one or the managed Chromium")
+
+    path = Path(path).expanduser().resolve()
+    path.parent.mkdir(parents=True, exist_ok=True)
+    with tempfile.TemporaryDirectory() as tmp:
+        html = Path(tmp) / "report.html"
+        html.write_text(await render_html_report(db, test_run), encoding="utf-8")
+        try:
+            process = await asyncio.create_subprocess_exec(
+                browser, "--headless", "--disable-gpu", "--no-pdf-header-footer",
+                f"--user-data-dir={Path(tmp) / 'profile'}", f"--print-to-pdf={path}", html.as_uri(),
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.STDOUT,
+            )
+            stdout, _ = await asyncio.wait_for(process.communicate(), PRINT_TIMEOUT)
+        except OSError as e:
+            raise ReportExportError(f"Couldn't start {browser}: {e}")
+        except asyncio.TimeoutError:
+            process.kill()
+            raise ReportExportError("Printing the PDF timed out")
+    if process.returncode != 0 or not path.is_file():
+        raise ReportExportError(f"Printing the PDF failed: {stdout.decode(errors='replace')[-500:]}")
+    return path
@@ -0,0 +1,150 @@
+"""
+Run Status - Derives test run outcomes, treating failures covered by known issues as expected
+"""
+from typing import Dict, List, Tuple
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import (
+    Attachment,
+    CategoryBreakdown,
+    KnownIssue,
+    RunBreakdown,
+    StatusCounts,
+    StepResult,
+    TestCase,
+    TestCaseOutcome,
+    TestRun,
+    TestRunResponse,
+    ViewportBreakdown,
+)
+
+UNCATEGORIZED = "Uncategorized"
+
+
+async def active_known_issues(db: AsyncSession, project_id: str) -> Dict[str, KnownIssue]:
+    """Map each quarantined test case ID to its active known issue"""
+    result = await db.execute(
+        select(KnownIssue)
+        .where(KnownIssue.project_id == project_id, KnownIssue.active.is_(True))
+        .order_by(KnownIssue.created_at)
+    )
+    return {issue.test_case_id: issue for issue in result.scalars().all()}
+
+
+async def run_outcome(db: AsyncSession, test_run: TestRun) -> Tuple[str, int]:
+    """
+    A finished run's status and expected failure count from its step results
+
+    Runs whose failures all belong to test cases with an active known issue, or
+    that needed self-healing, pass with warnings. Runs completed without
+    recorded step results fall back to the reported counts.
+    """
+    result = await db.execute(
+        select(StepResult.test_case_id, StepResult.status, StepResult.healed).where(
+            StepResult.test_run_id == test_run.id
+        )
+    )
+    rows = result.all()
+    known = await active_known_issues(db, test_run.project_id)
+
+    expected = sum(1 for test_case_id, status, _ in rows if status == "failed" and test_case_id in known)
+    unexpected = sum(1 for test_case_id, status, _ in rows if status == "failed" and test_case_id not in known)
+    healed = any(healed for _, _, healed in rows)
+    if not rows:
+        unexpected = test_run.failed
+
+    if unexpected:
+        return "failed", expected
+    if expected or healed:
+        return "passed_with_warnings", expected
+    return "passed", expected
+
+
+async def apply_run_status(db: AsyncSession, test_run: TestRun) -> str:
+    """Set a finished run's status and expected failure count; cancelled runs stay cancelled"""
+    status, test_run.expected_failed = await run_outcome(db, test_run)
+    if test_run.status != "cancelled":
+        test_run.status = status
+    return test_run.status
+
+
+def _count(counts: StatusCounts, status: str, expected: bool) -> None:
+    if status == "passed":
+        counts.passed += 1
+    elif status == "failed":
+        counts.failed += 1
+        if expected:
+            counts.expected_failed += 1
+    elif status == "skipped":
+        counts.skipped += 1
+
+
+async def get_run_breakdown(db: AsyncSession, test_run: TestRun) -> RunBreakdown:
+    """Break a run's step results down by category and test case"""
+    result = await db.execute(
+        select(StepResult, TestCase)
+        .join(TestCase, StepResult.test_case_id == TestCase.id)
+        .where(StepResult.test_run_id == test_run.id)
+        .order_by(StepResult.created_at)
+    )
+    known = await active_known_issues(db, test_run.project_id)
+
+    totals = StatusCounts()
+    healed_steps = 0
+    categories: Dict[str, CategoryBreakdown] = {}
+    outcomes: Dict[str, TestCaseOutcome] = {}
+    viewports: Dict[str, ViewportBreakdown] = {}
+    viewport_of: Dict[str, str] = {}
+    for step_result, test_case in result.all():
+        issue = known.get(test_case.id)
+        category = test_case.category or UNCATEGORIZED
+        if category not in categories:
+            categories[category] = CategoryBreakdown(category=category)
+        if test_case.id not in outcomes:
+            outcomes[test_case.id] = TestCaseOutcome(
+                test_case_id=test_case.id,
+                name=test_case.name,
+                category=test_case.category,
+                status="passed",
+                known_issue_id=issue.id if issue else None,
+                issue_key=issue.issue_key if issue else None,
+                issue_url=issue.issue_url if issue else None,
+            )
+        for counts in (totals, categories[category], outcomes[test_case.id]):
+            _count(counts, step_result.status, issue is not None)
+        if step_result.viewport:
+            if step_result.viewport not in viewports:
+                viewports[step_result.viewport] = ViewportBreakdown(viewport=step_result.viewport)
+            _count(viewports[step_result.viewport], step_result.status, issue is not None)
+            viewport_of[step_result.id] = step_result.viewport
+        healed_steps += int(step_result.healed)
+
+    if viewports:
+        screenshots = await db.execute(
+            select(Attachment.id, Attachment.step_result_id)
+            .where(Attachment.test_run_id == test_run.id, Attachment.kind == "screenshot")
+            .order_by(Attachment.created_at)
+        )
+        for attachment_id, step_result_id in screenshots.all():
+            if step_result_id in viewport_of:
+                viewports[viewport_of[step_result_id]].screenshot_ids.append(attachment_id)
+
+    test_cases: List[TestCaseOutcome] = list(outcomes.values())
+    for outcome in test_cases:
+        if outcome.failed > outcome.expected_failed:
+            outcome.status = "failed"
+        elif outcome.expected_failed:
+            outcome.status = "expected_failure"
+        elif outcome.passed == 0 and outcome.skipped:
+            outcome.status = "skipped"
+
+    return RunBreakdown(
+        run=TestRunResponse.model_validate(test_run),
+        totals=totals,
+        healed_steps=healed_steps,
+        by_category=sorted(categories.values(), key=lambda c: c.category),
+        test_cases=test_cases,
+        by_viewport=list(viewports.values()),
+    )
@@ -0,0 +1,144 @@
+"""
+Runner Events Bridge - Relays test runner execution events to the event bus over one shared connection
+"""
+import asyncio
+import json
+from typing import Any, Dict, Optional, Set
+
+import websockets
+
+from .events import event_bus
+from .service_urls import get_service_url
+
+# Reconnect delays grow from the initial to the max backoff, in seconds
+INITIAL_BACKOFF = 1.0
+MAX_BACKOFF = 30.0
+
+# Keepalive ping interval and how long to wait for its pong, in seconds
+HEARTBEAT_INTERVAL = 15.0
+HEARTBEAT_TIMEOUT = 10.0
+
+
+async def _events_url() -> str:
+    base = await get_service_url("test_runner")
+    if base.startswith("https://"):
+        return "wss://" + base[len("https://"):] + "/ws/executions"
+    if base.startswith("http://"):
+        return "ws://" + base[len("http://"):] + "/ws/executions"
+    return base + "/ws/executions"
+
+
+class RunnerEventBridge:
+    """
+    Single managed WebSocket to the test runner
+
+    Execution subscriptions are multiplexed over the connection and replayed
+    after a reconnect. The connection is closed once nothing is subscribed.
+    """
+
+    def __init__(self):
+        self._executions: Set[str] = set()
+        self._websocket: Optional[Any] = None
+        self._task: Optional[asyncio.Task] = None
+        self.connected = False
+        self.reconnects = 0
+
+    @property
+    def executions(self) -> Set[str]:
+        return set(self._executions)
+
+    async def subscribe(self, execution_id: str) -> None:
+        """Start relaying events for an execution, opening the connection if needed"""
+        if execution_id in self._executions:
+            return
+        self._executions.add(execution_id)
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+        else:
+            await self._send({"type": "subscribe", "execution_id": execution_id})
+
+    async def unsubscribe(self, execution_id: str) -> bool:
+        """Stop relaying events for an execution, closing the connection if it was the last"""
+        if execution_id not in self._executions:
+            return False
+        self._executions.discard(execution_id)
+        await self._send({"type": "unsubscribe", "execution_id": execution_id})
+        if not self._executions:
+            await self.close()
+        return True
+
+    async def close(self) -> None:
+        """Drop all subscriptions and close the connection"""
+        self._executions.clear()
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except (asyncio.CancelledError, Exception):
+                pass
+            self._task = None
+
+    async def _send(self, message: Dict[str, Any]) -> None:
+        if self._websocket is None:
+            return  # Replayed on (re)connect
+        try:
+            await self._websocket.send(json.dumps(message))
+        except websockets.ConnectionClosed:
+            pass
+
+    async def _run(self) -> None:
+        backoff = INITIAL_BACKOFF
+        while self._executions:
+            try:
+                async with websockets.connect(
+                    await _events_url(), ping_interval=HEARTBEAT_INTERVAL, ping_timeout=HEARTBEAT_TIMEOUT
+                ) as websocket:
+                    self._websocket = websocket
+                    self.connected = True
+                    backoff = INITIAL_BACKOFF
+                    event_bus.publish("test_runner:connected", {"executions": sorted(self._executions)})
+                    for execution_id in list(self._executions):
+                        await self._send({"type": "subscribe", "execution_id": execution_id})
+
+                    async for raw in websocket:
+                        await self._handle(raw)
+                        if not self._executions:
+                            break
+            except asyncio.CancelledError:
+                raise
+            except (OSError, websockets.WebSocketException) as e:
+                event_bus.publish("test_runner:disconnected", {"error": str(e), "retry_in": backoff})
+            finally:
+                self._websocket = None
+                self.connected = False
+
+            if not self._executions:
+                break
+            await asyncio.sleep(backoff)
+            backoff = min(backoff * 2, MAX_BACKOFF)
+            self.reconnects += 1
+
+    async def _handle(self, raw: Any) -> None:
+        try:
+            message = json.loads(raw)
+        except (TypeError, ValueError):
+            return
+        if not isinstance(message, dict):
+            return
+
+        # Application-level heartbeat from the runner
+        if message.get("type") == "ping":
+            await self._send({"type": "pong"})
+            return
+
+        execution_id = message.get("execution_id")
+        if execution_id in self._executions:
+            event_bus.publish("test_runner:event", message)
+            if message.get("type") in ("completed", "failed", "cancelled"):
+                # The read loop closes the connection once nothing is subscribed
+                self._executions.discard(execution_id)
+                await self._send({"type": "unsubscribe", "execution_id": execution_id})
+
+
+# Singleton instance
+runner_events = RunnerEventBridge()
@@ -0,0 +1,92 @@
+"""
+Saved Filters - Compiles stored test case filters into SQL
+
+A filter is a list of conditions on test case columns or custom fields,
+joined with AND when it matches all of them or OR when it matches any.
+Filters are compiled each time they're used, so they follow renamed values
+and newly added test cases.
+"""
+from typing import Any, List
+
+from sqlalchemy import Select, and_, false, not_, or_, select, true
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import CustomField, CustomFieldValue, FilterCondition, TestCase
+from .custom_fields import CUSTOM_PREFIX, CustomFieldError, filter_clauses
+
+FILTER_COLUMNS = ("status", "priority", "category", "test_type", "name", "description")
+OPERATORS = ("eq", "ne", "in", "contains")
+MATCHES = ("all", "any")
+
+
+class SavedFilterError(Exception):
+    """Raised for filters with unknown fields or operators, or values that don't fit them"""
+
+
+def check_condition(condition: FilterCondition) -> None:
+    if condition.field not in FILTER_COLUMNS and not condition.field.startswith(CUSTOM_PREFIX):
+        raise SavedFilterError(
+            f"Filter fields must be one of {', '.join(FILTER_COLUMNS)} or {CUSTOM_PREFIX}<field name>"
+        )
+    if condition.op not in OPERATORS:
+        raise SavedFilterError(f"Operators must be one of: {', '.join(OPERATORS)}")
+    if condition.op == "in" and not isinstance(condition.value, list):
+        raise SavedFilterError(f"{condition.field} in needs a list of values")
+    if condition.op != "in" and (condition.value is None or isinstance(condition.value, (list, dict))):
+        raise SavedFilterError(f"{condition.field} {condition.op} needs a single value")
+
+
+def _column_clause(condition: FilterCondition) -> Any:
+    column = getattr(TestCase, condition.field)
+    if condition.op == "eq":
+        return column == str(condition.value)
+    if condition.op == "ne":
+        return or_(column != str(condition.value), column.is_(None))
+    if condition.op == "in":
+        return column.in_([str(value) for value in condition.value])
+    return column.ilike(f"%{condition.value}%")
+
+
+async def _custom_clause(db: AsyncSession, project_id: str, condition: FilterCondition) -> Any:
+    name = condition.field.removeprefix(CUSTOM_PREFIX)
+    if condition.op == "contains":
+        return TestCase.id.in_(
+            select(CustomFieldValue.entity_id)
+            .join(CustomField, CustomFieldValue.field_id == CustomField.id)
+            .where(
+                CustomField.project_id == project_id,
+                CustomField.entity == "test_case",
+                CustomField.name == name,
+                CustomFieldValue.value.ilike(f"%{condition.value}%"),
+            )
+        )
+    values = condition.value if condition.op == "in" else [condition.value]
+    try:
+        clauses = [
+            (await filter_clauses(db, TestCase.id, "test_case", {name: str(value)}, project_id))[0]
+            for value in values
+        ]
+    except CustomFieldError as e:
+        raise SavedFilterError(str(e))
+    clause = or_(false(), *clauses)
+    return not_(clause) if condition.op == "ne" else clause
+
+
+async def filter_query(
+    db: AsyncSession, project_id: str, conditions: List[FilterCondition], match: str = "all"
+) -> Select:
+    """A query for a project's test cases matching a filter; raises SavedFilterError for invalid filters"""
+    if match not in MATCHES:
+        raise SavedFilterError(f"match must be one of: {', '.join(MATCHES)}")
+    clauses = []
+    for condition in conditions:
+        check_condition(condition)
+        if condition.field.startswith(CUSTOM_PREFIX):
+            clauses.append(await _custom_clause(db, project_id, condition))
+        else:
+            clauses.append(_column_clause(condition))
+    if not clauses:
+        combined = true()  # An empty filter lists every test case
+    else:
+        combined = and_(*clauses) if match == "all" else or_(*clauses)
+    return select(TestCase).where(TestCase.project_id == project_id, combined).order_by(TestCase.updated_at.desc())
@@ -0,0 +1,232 @@
+"""
+Scenario Validation - Checks a scenario's steps for problems without running them
+"""
+import json
+from collections import Counter
+from typing import Any, Dict, List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Project, Scenario, Step, TestCase
+from .accessibility import ACCESSIBILITY_STEP_TYPE
+from .browser_state import BROWSER_STATE_STEP_TYPES, BrowserStateError, check_state_step
+from .browser_tabs import TAB_STEP_TYPES, BrowserTabError, check_tab_step
+from .budgets import BudgetError, check_budget
+from .assertions import VERIFY_STEP_TYPE, step_assertions
+from .device_state import STATE_STEP_TYPES, DeviceStateError
+from .elements import ELEMENT_KEY, apply_element, load_elements
+from .file_steps import FILE_STEP_TYPES, FileStepError, check_file_step
+from .plugins import plugin_registry
+from .shell_steps import SHELL_STEP_TYPE
+from .ui_dump import element_descriptor
+from .waits import WAIT_STEP_TYPES
+
+# Step types each kind of project can run; plugin step types are added for mobile
+WEB_STEP_TYPES = {
+    "navigate", "click", "type", VERIFY_STEP_TYPE, "wait", ACCESSIBILITY_STEP_TYPE, SHELL_STEP_TYPE,
+    *BROWSER_STATE_STEP_TYPES, *FILE_STEP_TYPES, *TAB_STEP_TYPES,
+    *(step_type for step_type in WAIT_STEP_TYPES if step_type != "wait_for_activity"),
+}
+MOBILE_STEP_TYPES = {
+    "tap", "tap_element", "swipe", "input", "back", "home", "launch", "wait", VERIFY_STEP_TYPE,
+    ACCESSIBILITY_STEP_TYPE, SHELL_STEP_TYPE, *STATE_STEP_TYPES,
+    *(step_type for step_type in WAIT_STEP_TYPES if step_type != "wait_for_url"),
+}
+
+# Steps that need an Android UI dump to find their element
+ANDROID_ONLY_STEP_TYPES = {ACCESSIBILITY_STEP_TYPE, "wait_for_element", "wait_for_element_gone", "wait_for_activity"}
+
+
+class ScenarioValidationError(Exception):
+    """Raised when a scenario can't be validated"""
+
+
+class ValidationIssue(BaseModel):
+    step_id: Optional[str] = None  # None for problems with the scenario as a whole
+    step_order: Optional[float] = None
+    severity: str  # 'error' | 'warning'
+    code: str
+    message: str
+    field: Optional[str] = None  # The config field the editor should highlight
+
+
+class ScenarioValidation(BaseModel):
+    scenario_id: str
+    platform: str  # 'web' | 'android' | 'ios' | 'mobile' when the device decides
+    valid: bool  # No errors; warnings don't stop a run
+    issues: List[ValidationIssue]
+
+
+def _has_point(config: Dict[str, Any], x_key: str = "x", y_key: str = "y") -> bool:
+    return config.get(x_key) is not None and config.get(y_key) is not None
+
+
+def _target_issues(step_type: str, config: Dict[str, Any], platform: str, ai_free: bool) -> List[tuple]:
+    """Check that a tap or swipe can find where to act, as (severity, code, field, message)"""
+    issues = []
+    selector_works = config.get("selector") and platform != "ios"
+    if not selector_works and not _has_point(config):
+        if config.get("element_description") and ai_free and platform != "ios":
+            pass  # Found in the UI dump when the step runs
+        elif config.get("element_description"):
+            issues.append((
+                "warning", "unresolved_locator", "element_description",
+                "Only an element description is set; resolve the scenario's locators before running it",
+            ))
+        else:
+            issues.append(("error", "missing_target", "selector", "Set a selector or coordinates"))
+    if step_type == "swipe" and not _has_point(config, "x2", "y2"):
+        issues.append(("error", "missing_coordinates", "x2", "Set the swipe's end coordinates"))
+    return issues
+
+
+def check_step(step_type: str, config: Dict[str, Any], platform: str, ai_free: bool) -> List[tuple]:
+    """Problems with one step, as (severity, code, field, message)"""
+    plugin = platform != "web" and plugin_registry.for_step_type(step_type)
+    supported = WEB_STEP_TYPES if platform == "web" else MOBILE_STEP_TYPES
+    if step_type not in supported and not plugin:
+        target = "web" if platform == "web" else "mobile"
+        return [("error", "unsupported_step_type", None, f"{step_type} steps can't run in {target} scenarios")]
+    if platform == "ios" and step_type in ANDROID_ONLY_STEP_TYPES:
+        return [("error", "unsupported_step_type", None, f"{step_type} steps only run on Android")]
+
+    issues = []
+    if step_type in ("tap", "swipe"):
+        issues += _target_issues(step_type, config, platform, ai_free)
+    elif step_type == "tap_element":
+        if not element_descriptor(config) and not config.get("element_description"):
+            issues.append((
+                "error", "missing_target", "resource_id",
+                "Set a resource_id, text, content_desc or element_description",
+            ))
+        elif platform == "ios" and ai_free:
+            issues.append((
+                "error", "missing_target", "element_description",
+                "iOS has no UI dump, so tap_element needs the AI, which this project turns off",
+            ))
+    elif step_type in ("click", "type") and not config.get("selector"):
+        issues.append(("error", "missing_target", "selector", f"Set a selector for the {step_type} step"))
+
+    if step_type in ("input", "type") and not config.get("value"):
+        issues.append(("error", "missing_value", "value", "Set the text to type"))
+    elif step_type == "navigate" and not config.get("url"):
+        issues.append(("warning", "missing_url", "url", "No URL is set, so the project's app URL is opened"))
+    elif step_type == "launch" and not config.get("packageName"):
+        issues.append(("error", "missing_value", "packageName", "Set the package or bundle ID to launch"))
+    elif step_type == SHELL_STEP_TYPE and not (config.get("command") or "").strip():
+        issues.append(("error", "missing_value", "command", "Set the command to run"))
+    elif step_type == VERIFY_STEP_TYPE and not step_assertions(config):
+        if platform != "web":
+            issues.append(("warning", "no_assertions", "assertions", "The step doesn't check anything"))
+        elif not config.get("selector"):
+            issues.append(("error", "missing_target", "selector", "Set a selector or assertions to verify"))
+    elif step_type in ("wait_for_element", "wait_for_element_gone"):
+        if not config.get("selector") and not config.get("element_description"):
+            issues.append(("error", "missing_target", "selector", "Set a selector or element_description"))
+    elif step_type == "wait_for_text" and not (config.get("text") or config.get("value")):
+        issues.append(("error", "missing_value", "text", "Set the text to wait for"))
+    elif step_type == "wait_for_activity" and not config.get("activity"):
+        issues.append(("error", "missing_value", "activity", "Set the activity to wait for"))
+    elif step_type == "wait_for_url" and not config.get("url"):
+        issues.append(("error", "missing_value", "url", "Set the URL to wait for"))
+    elif step_type in STATE_STEP_TYPES:
+        try:
+            STATE_STEP_TYPES[step_type]("ios" if platform == "ios" else "android", "device", config)
+        except DeviceStateError as e:
+            issues.append(("error", "invalid_config", None, str(e)))
+    elif step_type in BROWSER_STATE_STEP_TYPES:
+        try:
+            check_state_step(step_type, config)
+        except BrowserStateError as e:
+            issues.append(("error", "invalid_config", None, str(e)))
+    elif step_type in FILE_STEP_TYPES:
+        try:
+            check_file_step(step_type, config)
+        except FileStepError as e:
+            issues.append(("error", "invalid_config", None, str(e)))
+    elif step_type in TAB_STEP_TYPES:
+        try:
+            check_tab_step(step_type, config)
+        except BrowserTabError as e:
+            issues.append(("error", "invalid_config", None, str(e)))
+
+    if config.get("max_duration_ms") is not None or config.get("budget_action"):
+        try:
+            check_budget(config.get("max_duration_ms"), config.get("budget_action"))
+        except BudgetError as e:
+            issues.append(("error", "invalid_config", "max_duration_ms", str(e)))
+        if platform == "web":
+            issues.append((
+                "warning", "step_budget_ignored", "max_duration_ms",
+                "Web specs are timed as a whole, so set a budget on the scenario instead",
+            ))
+    return issues
+
+
+def validate_steps(
+    steps: List[Step],
+    platform: str,
+    ai_free: bool = False,
+    elements: Optional[Dict[str, Any]] = None,
+) -> List[ValidationIssue]:
+    """Check steps in run order, as the executor would see them"""
+    elements = elements or {}
+    issues: List[ValidationIssue] = []
+
+    def add(step: Optional[Step], severity: str, code: str, field: Optional[str], message: str) -> None:
+        issues.append(ValidationIssue(
+            step_id=step.id if step else None,
+            step_order=step.step_order if step else None,
+            severity=severity, code=code, field=field, message=message,
+        ))
+
+    if not steps:
+        add(None, "warning", "no_steps", None, "The scenario has no steps")
+
+    counts = Counter(step.step_order for step in steps)
+    for step in steps:
+        if counts[step.step_order] > 1:
+            add(step, "warning", "duplicate_order", "step_order",
+                f"{counts[step.step_order]} steps share order {step.step_order:g}, so they may run in any order")
+
+    # Shell steps in web scenarios only run as setup before or teardown after the browser steps
+    body = [index for index, step in enumerate(steps) if step.step_type != SHELL_STEP_TYPE]
+    for index, step in enumerate(steps):
+        try:
+            config = json.loads(step.config or "{}")
+        except ValueError:
+            add(step, "error", "invalid_config", None, "The step's config isn't valid JSON")
+            continue
+        if config.get(ELEMENT_KEY) and config[ELEMENT_KEY] not in elements:
+            add(step, "error", "unknown_element", ELEMENT_KEY, f"No element is named {config[ELEMENT_KEY]!r}")
+        config = apply_element(config, elements, "android" if platform == "mobile" else platform)
+
+        if platform == "web" and step.step_type == SHELL_STEP_TYPE and body and body[0] < index < body[-1]:
+            add(step, "error", "shell_step_position", None,
+                "Shell steps can only run before or after a scenario's browser steps")
+        for severity, code, field, message in check_step(step.step_type, config, platform, ai_free):
+            add(step, severity, code, field, message)
+    return issues
+
+
+async def validate_scenario(db: AsyncSession, scenario_id: str) -> ScenarioValidation:
+    """Check a scenario's steps without running them"""
+    scenario = await db.get(Scenario, scenario_id)
+    if not scenario:
+        raise ScenarioValidationError("Scenario not found")
+    test_case = await db.get(TestCase, scenario.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+    platform = project.project_type if project.project_type in ("web", "android", "ios") else "mobile"
+
+    result = await db.execute(select(Step).where(Step.scenario_id == scenario_id).order_by(Step.step_order))
+    issues = validate_steps(
+        list(result.scalars().all()), platform, project.ai_free, await load_elements(db, project.id)
+    )
+    return ScenarioValidation(
+        scenario_id=scenario_id,
+        platform=platform,
+        valid=not any(issue.severity == "error" for issue in issues),
+        issues=issues,
+    )
@@ -0,0 +1,246 @@
+"""
+Schedules - Starts suite runs on cron schedules, locally or on runner agents
+"""
+import asyncio
+import uuid
+from datetime import datetime, timezone
+from typing import Dict, List, Optional, Tuple
+from zoneinfo import ZoneInfo, ZoneInfoNotFoundError
+
+from sqlalchemy import select, update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal
+from ..models import Project, RunSchedule, Suite, SuiteScenario, TestRun, UpcomingRun
+from .agents import AgentError, choose_agent, dispatch_run
+from .cron import CronError, CronExpression
+from .dependencies import load_prerequisites
+from .events import event_bus
+from .executor import ScenarioExecutor, start_executor_run
+from .run_queue import PRIORITIES
+from .test_runner import TestFramework
+from .web_executor import WebScenarioExecutor
+
+# How often the scheduler looks for due schedules, in seconds
+SCHEDULER_POLL_SECONDS = 30
+
+
+class ScheduleError(Exception):
+    """Raised for invalid schedules and runs that can't be started"""
+
+
+def _zone(name: str) -> ZoneInfo:
+    try:
+        return ZoneInfo(name)
+    except (ZoneInfoNotFoundError, ValueError):
+        raise ScheduleError(f"Unknown timezone: {name}")
+
+
+def next_run_time(cron: str, timezone_name: str, after: datetime) -> datetime:
+    """When a cron expression in a timezone next fires after a UTC time, as naive UTC"""
+    zone = _zone(timezone_name)
+    local = after.replace(tzinfo=timezone.utc).astimezone(zone).replace(tzinfo=None)
+    try:
+        fires = CronExpression(cron).next_after(local)
+    except CronError as e:
+        raise ScheduleError(str(e))
+    return fires.replace(tzinfo=zone).astimezone(timezone.utc).replace(tzinfo=None)
+
+
+def validate_schedule(schedule: RunSchedule) -> None:
+    next_run_time(schedule.cron, schedule.timezone, datetime.utcnow())
+    if schedule.target not in ("device", "browser"):
+        raise ScheduleError(f"Unknown target: {schedule.target}")
+    if schedule.target == "device" and schedule.platform not in ("android", "ios"):
+        raise ScheduleError("Platform must be android or ios for device runs")
+    if schedule.target == "device" and not schedule.on_agent and not schedule.device_id:
+        raise ScheduleError("device_id is required for device runs on this machine")
+    if schedule.priority not in PRIORITIES:
+        raise ScheduleError(f"Priority must be one of: {', '.join(PRIORITIES)}")
+    if schedule.framework not in {framework.value for framework in TestFramework}:
+        raise ScheduleError(f"Unknown framework: {schedule.framework}")
+
+
+def upcoming_runs(schedules: List[RunSchedule], count: int, after: Optional[datetime] = None) -> List[UpcomingRun]:
+    """The next runs across enabled schedules, soonest first"""
+    after = after or datetime.utcnow()
+    runs = []
+    for schedule in schedules:
+        if not schedule.enabled:
+            continue
+        moment = after
+        for _ in range(count):
+            try:
+                moment = next_run_time(schedule.cron, schedule.timezone, moment)
+            except ScheduleError:
+                break
+            runs.append(UpcomingRun(
+                schedule_id=schedule.id, name=schedule.name, suite_id=schedule.suite_id, run_at=moment
+            ))
+    runs.sort(key=lambda run: run.run_at)
+    return runs[:count]
+
+
+async def _suite_plan(db: AsyncSession, suite_id: str) -> Tuple[List[str], Dict[str, List[str]]]:
+    """A suite's scenarios in run order, with the prerequisites that run earlier in it"""
+    result = await db.execute(
+        select(SuiteScenario.scenario_id)
+        .where(SuiteScenario.suite_id == suite_id)
+        .order_by(SuiteScenario.position, SuiteScenario.created_at)
+    )
+    scenario_ids = list(result.scalars().all())
+    prerequisites = await load_prerequisites(db)
+    return scenario_ids, {
+        scenario_id: [p for p in prerequisites.get(scenario_id, []) if p in scenario_ids[:index]]
+        for index, scenario_id in enumerate(scenario_ids)
+    }
+
+
+async def trigger_schedule(db: AsyncSession, schedule: RunSchedule) -> TestRun:
+    """Start a schedule's suite now, recording the outcome on the schedule"""
+    try:
+        test_run = await _start_run(db, schedule)
+    except ScheduleError as e:
+        schedule.last_run_at = datetime.utcnow()
+        schedule.last_error = str(e)
+        await db.commit()
+        event_bus.publish("schedule:failed", {"schedule_id": schedule.id, "error": str(e)})
+        raise
+    schedule.last_run_at = datetime.utcnow()
+    schedule.last_test_run_id = test_run.id
+    schedule.last_error = None
+    await db.commit()
+    event_bus.publish("schedule:triggered", {"schedule_id": schedule.id, "test_run_id": test_run.id})
+    return test_run
+
+
+async def _start_run(db: AsyncSession, schedule: RunSchedule) -> TestRun:
+    suite = await db.get(Suite, schedule.suite_id)
+    if not suite:
+        raise ScheduleError("Suite not found")
+    scenario_ids, prerequisites = await _suite_plan(db, suite.id)
+    if not scenario_ids:
+        raise ScheduleError("Suite has no scenarios")
+    project = await db.get(Project, suite.project_id)
+
+    agent = None
+    if schedule.on_agent:
+        try:
+            agent = await choose_agent(
+                db,
+                platform=schedule.platform,
+                device_id=schedule.device_id,
+                browser=schedule.browser if schedule.target == "browser" else None,
+                agent_id=schedule.agent_id,
+            )
+        except AgentError as e:
+            raise ScheduleError(str(e))
+
+    test_run = TestRun(id=str(uuid.uuid4()), project_id=suite.project_id, name=f"{suite.name} ({schedule.name})")
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+
+    if agent:
+        options = {
+            "target": schedule.target,
+            "platform": schedule.platform,
+            "device_id": schedule.device_id,
+            "framework": schedule.framework,
+            "browser": schedule.browser,
+            "headless": schedule.headless,
+            "self_heal": schedule.self_heal,
+        }
+        try:
+            await dispatch_run(db, agent, test_run, scenario_ids, options, prerequisites)
+        except AgentError as e:
+            await db.delete(test_run)
+            await db.commit()
+            raise ScheduleError(str(e))
+        return test_run
+
+    if schedule.target == "browser":
+        executor = WebScenarioExecutor(TestFramework(schedule.framework), schedule.browser, schedule.headless)
+    else:
+        executor = ScenarioExecutor(
+            schedule.device_id, schedule.platform, self_heal=schedule.self_heal, ai_free=project.ai_free
+        )
+    start_executor_run(executor, scenario_ids, test_run.id, prerequisites, schedule.priority)
+    return test_run
+
+
+class RunScheduler:
+    """
+    Starts runs for enabled schedules once their next run time passes
+
+    Runs missed while the app was closed start once on the next check,
+    not once per missed time.
+    """
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            try:
+                await self._check()
+            except Exception as e:
+                print(f"Scheduler error: {e}")
+            await asyncio.sleep(SCHEDULER_POLL_SECONDS)
+
+    async def _check(self) -> None:
+        now = datetime.utcnow()
+        async with AsyncSessionLocal() as db:
+            result = await db.execute(select(RunSchedule).where(RunSchedule.enabled.is_(True)))
+            for schedule in result.scalars().all():
+                due = schedule.next_run_at is not None and schedule.next_run_at <= now
+                if not due and schedule.next_run_at is not None:
+                    continue
+                try:
+                    upcoming = next_run_time(schedule.cron, schedule.timezone, now)
+                except ScheduleError as e:
+                    schedule.enabled = False
+                    schedule.last_error = str(e)
+                    await db.commit()
+                    continue
+                if await _claim(db, schedule, upcoming) and due:
+                    try:
+                        await trigger_schedule(db, schedule)
+                    except ScheduleError:
+                        pass  # Recorded on the schedule and published
+
+
+async def _claim(db: AsyncSession, schedule: RunSchedule, upcoming: datetime) -> bool:
+    """
+    Move a schedule on to its next run time, returning False if another instance already did
+
+    Instances sharing a database all check every schedule, so only the one
+    whose update still finds the old run time starts the run.
+    """
+    previous = schedule.next_run_at
+    result = await db.execute(
+        update(RunSchedule)
+        .where(
+            RunSchedule.id == schedule.id,
+            RunSchedule.next_run_at.is_(None) if previous is None else RunSchedule.next_run_at == previous,
+        )
+        .values(next_run_at=upcoming)
+        .execution_options(synchronize_session=False)
+    )
+    await db.commit()
+    await db.refresh(schedule)
+    return result.rowcount == 1
+
+
+# Singleton instance
+run_scheduler = RunScheduler()
@@ -0,0 +1,141 @@
+"""
+Screen Stream Service - Streams device screenshots as events for the live device mirror
+"""
+import asyncio
+import base64
+import struct
+import time
+from typing import Dict, Optional
+
+from fastapi import HTTPException
+
+from ..routers.mobile import ios_screenshot
+from .events import event_bus
+from .tools import tool_command
+
+PNG_SIGNATURE = b"\x89PNG\r\n\x1a\n"
+
+MAX_FPS = 30.0
+
+
+class ScreenStream:
+    """Captures frames from one device at a fixed rate and publishes them as 'device:frame' events"""
+
+    def __init__(self, device_id: str, platform: str, fps: float):
+        self.device_id = device_id
+        self.platform = platform
+        self.fps = fps
+        self.frames = 0
+        self.started_at = time.time()
+        self._process: Optional[asyncio.subprocess.Process] = None
+        self._task: Optional[asyncio.Task] = None
+
+    def start(self) -> None:
+        self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._process and self._process.returncode is None:
+            try:
+                self._process.kill()
+            except ProcessLookupError:
+                pass
+        if self._task:
+            self._task.cancel()
+            try:
+                await self._task
+            except (asyncio.CancelledError, Exception):
+                pass
+
+    async def _run(self) -> None:
+        try:
+            if self.platform == "ios":
+                await self._stream_ios()
+            else:
+                await self._stream_android()
+        except asyncio.CancelledError:
+            raise
+        except Exception as e:
+            event_bus.publish("device:stream_error", {"device_id": self.device_id, "error": str(e)})
+        finally:
+            if _streams.get(self.device_id) is self:
+                del _streams[self.device_id]
+            event_bus.publish("device:stream_stopped", {"device_id": self.device_id, "frames": self.frames})
+
+    async def _stream_android(self) -> None:
+        """Read PNG frames from one long-lived `adb exec-out` screencap loop"""
+        interval = 1.0 / self.fps
+        self._process = await asyncio.create_subprocess_exec(
+            tool_command("adb"), "-s", self.device_id, "exec-out",
+            f"while true; do screencap -p; sleep {interval:.3f}; done",
+            stdout=asyncio.subprocess.PIPE,
+            stderr=asyncio.subprocess.DEVNULL,
+        )
+        reader = self._process.stdout
+        try:
+            while True:
+                self._publish(await _read_png(reader))
+        except asyncio.IncompleteReadError:
+            raise RuntimeError("Device screen stream ended")
+
+    async def _stream_ios(self) -> None:
+        """simctl has no streaming screenshot mode, so poll at the requested rate"""
+        interval = 1.0 / self.fps
+        while True:
+            started = time.time()
+            try:
+                frame = (await ios_screenshot(self.device_id)).screenshot
+            except HTTPException as e:
+                raise RuntimeError(str(e.detail))
+            self._publish(frame)
+            await asyncio.sleep(max(0.0, interval - (time.time() - started)))
+
+    def _publish(self, frame) -> None:
+        if isinstance(frame, bytes):
+            frame = base64.b64encode(frame).decode()
+        self.frames += 1
+        event_bus.publish("device:frame", {"device_id": self.device_id, "frame": frame, "index": self.frames})
+
+
+async def _read_png(reader: asyncio.StreamReader) -> bytes:
+    """Read one complete PNG image from a stream of back-to-back PNGs"""
+    # Skip anything (such as shell warnings) before the next PNG signature
+    buffer = await reader.readexactly(len(PNG_SIGNATURE))
+    while buffer != PNG_SIGNATURE:
+        buffer = buffer[1:] + await reader.readexactly(1)
+
+    chunks = [buffer]
+    while True:
+        header = await reader.readexactly(8)
+        length, chunk_type = struct.unpack(">I4s", header)
+        chunks.append(header)
+        chunks.append(await reader.readexactly(length + 4))  # data + CRC
+        if chunk_type == b"IEND":
+            return b"".join(chunks)
+
+
+# Active streams keyed by device ID
+_streams: Dict[str, ScreenStream] = {}
+
+
+async def start_stream(device_id: str, platform: str, fps: float) -> ScreenStream:
+    """Start streaming a device's screen, replacing any stream already running for it"""
+    if not 0 < fps <= MAX_FPS:
+        raise ValueError(f"fps must be between 0 and {MAX_FPS:g}")
+    await stop_stream(device_id)
+    stream = ScreenStream(device_id, platform, fps)
+    _streams[device_id] = stream
+    stream.start()
+    return stream
+
+
+async def stop_stream(device_id: str) -> bool:
+    """Stop a device's screen stream, returning False if none was running"""
+    stream = _streams.pop(device_id, None)
+    if not stream:
+        return False
+    await stream.stop()
+    return True
+
+
+def get_stream(device_id: str) -> Optional[ScreenStream]:
+    return _streams.get(device_id)
@@ -0,0 +1,127 @@
+"""
+Screenshots - Decides which steps get a screenshot and shrinks them before they're stored
+
+A scenario's screenshot_policy, or the executor.screenshot_policy setting,
+captures a screenshot after every step, only after failed steps, or never.
+Captured PNGs are downscaled to screenshots.max_width and converted to the
+screenshots.format at screenshots.quality; a conversion that doesn't make the
+file smaller keeps the original. The stored and original sizes are recorded
+on the step result so the space saved can be reported.
+"""
+import io
+import shutil
+from pathlib import Path
+from typing import Any, Dict, Optional, Tuple
+
+from pydantic import BaseModel
+from sqlalchemy import func, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..models import StepResult, TestRun
+
+SCREENSHOT_POLICIES = ("always", "on_failure", "never")
+
+FORMATS = {"webp": ("WEBP", "webp"), "jpeg": ("JPEG", "jpg"), "png": ("PNG", "png")}
+
+MEDIA_TYPES = {".webp": "image/webp", ".jpg": "image/jpeg", ".png": "image/png"}
+
+
+class ScreenshotStats(BaseModel):
+    """Schema for the space used and saved by a project's step screenshots"""
+    screenshots: int
+    original_bytes: int
+    stored_bytes: int
+    saved_bytes: int
+    saved_percent: float
+
+
+def check_policy(policy: Optional[str]) -> None:
+    if policy and policy not in SCREENSHOT_POLICIES:
+        raise ValueError(f"screenshot_policy must be one of: {', '.join(SCREENSHOT_POLICIES)}")
+
+
+def should_capture(policy: str, status: str) -> bool:
+    return policy == "always" or (policy == "on_failure" and status == "failed")
+
+
+def compress(png: bytes, values: Dict[str, Any]) -> Tuple[bytes, str]:
+    """A PNG downscaled and converted as the screenshots.* settings say, with its file extension"""
+    image_format, extension = FORMATS.get(values["screenshots.format"], FORMATS["png"])
+    max_width = int(values["screenshots.max_width"] or 0)
+    try:
+        from PIL import Image
+    except ImportError:
+        return png, "png"
+
+    image = Image.open(io.BytesIO(png))
+    if max_width and image.width > max_width:
+        image = image.resize((max_width, round(image.height * max_width / image.width)), Image.LANCZOS)
+    elif image_format == "PNG":
+        return png, "png"
+    if image_format == "JPEG":
+        image = image.convert("RGB")
+    output = io.BytesIO()
+    image.save(output, format=image_format, quality=max(1, min(int(values["screenshots.quality"]), 100)))
+    data = output.getvalue()
+    if len(data) >= len(png):
+        return png, "png"
+    return data, extension
+
+
+def _screenshots_root() -> Path:
+    return settings.get_database_path().parent / "screenshots"
+
+
+def screenshot_dir(test_run_id: str) -> Path:
+    directory = _screenshots_root() / test_run_id
+    directory.mkdir(parents=True, exist_ok=True)
+    return directory
+
+
+def remove_run_screenshots(test_run_id: str) -> None:
+    shutil.rmtree(_screenshots_root() / test_run_id, ignore_errors=True)
+
+
+def save_step_screenshot(test_run_id: str, name: str, png: bytes, values: Dict[str, Any]) -> Dict[str, Any]:
+    """Compress and write a step's screenshot, returning the step result fields describing it"""
+    data, extension = compress(png, values)
+    path = screenshot_dir(test_run_id) / f"{name}.{extension}"
+    path.write_bytes(data)
+    return {"screenshot_path": str(path), "screenshot_bytes": len(data), "screenshot_original_bytes": len(png)}
+
+
+def media_type(path: str) -> str:
+    return MEDIA_TYPES.get(Path(path).suffix, "application/octet-stream")
+
+
+def stored_screenshot(path: Optional[str]) -> Optional[Path]:
+    """A step result's screenshot file, if it exists and was saved by a run"""
+    if not path:
+        return None
+    resolved = Path(path).resolve()
+    if _screenshots_root().resolve() not in resolved.parents or not resolved.is_file():
+        return None
+    return resolved
+
+
+async def screenshot_stats(db: AsyncSession, project_id: str) -> ScreenshotStats:
+    """How much space a project's step screenshots take, and how much compressing them saved"""
+    count, original, stored = (await db.execute(
+        select(
+            func.count(),
+            func.coalesce(func.sum(StepResult.screenshot_original_bytes), 0),
+            func.coalesce(func.sum(StepResult.screenshot_bytes), 0),
+        )
+        .select_from(StepResult)
+        .join(TestRun, StepResult.test_run_id == TestRun.id)
+        .where(TestRun.project_id == project_id, StepResult.screenshot_bytes.is_not(None))
+    )).one()
+    saved = max(original - stored, 0)
+    return ScreenshotStats(
+        screenshots=count,
+        original_bytes=original,
+        stored_bytes=stored,
+        saved_bytes=saved,
+        saved_percent=round(saved * 100 / original, 1) if original else 0.0,
+    )
@@ -0,0 +1,154 @@
+"""
+Secret Masking - Keeps secret variable values out of run logs, results and AI requests
+
+Secret values are cached in memory so masking can run anywhere without a database
+session, including while SQLAlchemy flushes step results and run logs.
+"""
+import base64
+import io
+import json
+from contextlib import contextmanager
+from typing import Any, Dict, Iterable, Iterator, List, Set
+
+from sqlalchemy import event, select
+from sqlalchemy.exc import OperationalError
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal
+from ..models import Project, RunLog, StepResult
+from .ui_dump import UiNode
+
+MASK = "******"
+
+# Shorter values would mask ordinary text such as single digits
+MIN_SECRET_LENGTH = 3
+
+# Secret values from every project's variables, refreshed whenever projects change
+_project_secrets: Set[str] = set()
+
+# Secrets of jobs running on this machine for another orchestrator, as runner agents do
+_job_secrets: List[Set[str]] = []
+
+
+class SecretError(Exception):
+    """Raised when a variable can't be marked secret"""
+
+
+def project_secret_values(project: Project) -> Set[str]:
+    env = json.loads(project.env_vars or "{}")
+    return {
+        str(env[name]) for name in json.loads(project.secret_env_vars or "[]")
+        if name in env and len(str(env[name])) >= MIN_SECRET_LENGTH
+    }
+
+
+async def refresh_secrets() -> None:
+    """Reload secret values from all projects"""
+    try:
+        async with AsyncSessionLocal() as db:
+            projects = (await db.execute(select(Project))).scalars().all()
+    except OperationalError:
+        return  # Database not ready yet; refreshed again once projects change
+    values = set()
+    for project in projects:
+        values |= project_secret_values(project)
+    _project_secrets.clear()
+    _project_secrets.update(values)
+
+
+@contextmanager
+def job_secrets(values: Iterable[str]) -> Iterator[None]:
+    """Mask a job's secrets while it runs"""
+    secrets = {str(value) for value in values if len(str(value)) >= MIN_SECRET_LENGTH}
+    _job_secrets.append(secrets)
+    try:
+        yield
+    finally:
+        _job_secrets.remove(secrets)
+
+
+def secret_values() -> List[str]:
+    """Every secret, longest first so a secret containing another is masked whole"""
+    values = set(_project_secrets)
+    for secrets in _job_secrets:
+        values |= secrets
+    return sorted(values, key=len, reverse=True)
+
+
+def mask_secrets(text: Any) -> Any:
+    """Replace secret values in text, including their JSON-escaped form"""
+    if not isinstance(text, str) or not text:
+        return text
+    for value in secret_values():
+        text = text.replace(value, MASK)
+        escaped = json.dumps(value)[1:-1]
+        if escaped != value:
+            text = text.replace(escaped, MASK)
+    return text
+
+
+def mask_data(data: Any) -> Any:
+    """Mask every string inside a JSON-like value, leaving base64 images to redact_screenshot"""
+    if isinstance(data, str):
+        return mask_secrets(data)
+    if isinstance(data, dict):
+        return {key: value if str(key).endswith("base64") else mask_data(value) for key, value in data.items()}
+    if isinstance(data, (list, tuple)):
+        return [mask_data(value) for value in data]
+    return data
+
+
+def nodes_showing_secrets(nodes: List[UiNode]) -> List[UiNode]:
+    values = secret_values()
+    return [
+        node for node in nodes
+        if any(value in node.text or value in node.content_desc for value in values)
+    ]
+
+
+def redact_screenshot(screenshot_base64: str, nodes: List[UiNode]) -> str:
+    """Black out the elements that show a secret, so the screenshot can go to the AI"""
+    showing = nodes_showing_secrets(nodes)
+    if not showing:
+        return screenshot_base64
+    from PIL import Image, ImageDraw
+
+    image = Image.open(io.BytesIO(base64.b64decode(screenshot_base64))).convert("RGB")
+    draw = ImageDraw.Draw(image)
+    for node in showing:
+        draw.rectangle([node.left, node.top, node.right, node.bottom], fill="black")
+    output = io.BytesIO()
+    image.save(output, format="PNG")
+    return base64.b64encode(output.getvalue()).decode()
+
+
+async def mark_variable_secret(db: AsyncSession, project: Project, name: str, secret: bool = True) -> Project:
+    """Mark one of a project's variables as secret, or clear the mark"""
+    env: Dict[str, str] = json.loads(project.env_vars or "{}")
+    if name not in env:
+        raise SecretError(f"Project has no variable named {name}")
+    names = [n for n in json.loads(project.secret_env_vars or "[]") if n != name]
+    if secret:
+        names.append(name)
+    project.secret_env_vars = json.dumps(sorted(names))
+    await db.commit()
+    await db.refresh(project)
+    await refresh_secrets()
+    return project
+
+
+# ============================================
+# Masking on write
+# ============================================
+
+
+@event.listens_for(RunLog, "before_insert")
+def _mask_run_log(mapper, connection, log: RunLog) -> None:
+    log.message = mask_secrets(log.message)
+
+
+@event.listens_for(StepResult, "before_insert")
+@event.listens_for(StepResult, "before_update")
+def _mask_step_result(mapper, connection, result: StepResult) -> None:
+    result.error_message = mask_secrets(result.error_message)
+    result.details = mask_secrets(result.details)
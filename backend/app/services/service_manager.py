@@ -0,0 +1,194 @@
+"""
+Service Manager - Starts, stops, and supervises the AI agent and test runner processes
+"""
+import asyncio
+import logging
+import os
+import shlex
+import time
+from logging.handlers import RotatingFileHandler
+from pathlib import Path
+from typing import Dict, List, Optional
+
+from ..config import settings
+
+# Restart delays after a crash grow from the initial to the max backoff, in seconds
+INITIAL_RESTART_BACKOFF = 1.0
+MAX_RESTART_BACKOFF = 60.0
+
+# A service that stays up this long is considered stable and its backoff resets
+STABLE_UPTIME = 60.0
+
+# Seconds to wait for a graceful exit before killing the process
+STOP_TIMEOUT = 10.0
+
+LOG_MAX_BYTES = 5 * 1024 * 1024
+LOG_BACKUP_COUNT = 3
+
+
+class ServiceError(Exception):
+    """Raised for unknown services or services that can't be launched"""
+
+
+class ManagedService:
+    """A sidecar service run as a child process"""
+
+    def __init__(self, name: str, command: str, cwd: str):
+        self.name = name
+        self.command = command
+        self.cwd = cwd
+        self.status = "Stopped"
+        self.pid: Optional[int] = None
+        self.started_at: Optional[float] = None
+        self.restarts = 0
+        self.last_exit_code: Optional[int] = None
+        self.error: Optional[str] = None
+        self.auto_restart = True
+        self._process: Optional[asyncio.subprocess.Process] = None
+        self._watcher: Optional[asyncio.Task] = None
+        self._backoff = INITIAL_RESTART_BACKOFF
+        self._logger = self._create_logger()
+
+    @property
+    def log_path(self) -> Path:
+        return _log_dir() / f"{self.name}.log"
+
+    @property
+    def uptime_seconds(self) -> Optional[float]:
+        if self.status not in ("Running", "Unhealthy") or self.started_at is None:
+            return None
+        return time.time() - self.started_at
+
+    def _create_logger(self) -> logging.Logger:
+        logger = logging.getLogger(f"autotest.services.{self.name}")
+        logger.propagate = False
+        if not logger.handlers:
+            handler = RotatingFileHandler(
+                self.log_path, maxBytes=LOG_MAX_BYTES, backupCount=LOG_BACKUP_COUNT, encoding="utf-8"
+            )
+            handler.setFormatter(logging.Formatter("%(asctime)s %(message)s"))
+            logger.addHandler(handler)
+            logger.setLevel(logging.INFO)
+        return logger
+
+    async def start(self) -> None:
+        if self._process and self._process.returncode is None:
+            return
+        if not self.command:
+            raise ServiceError(f"No command configured for {self.name}")
+
+        self.status = "Starting"
+        self.error = None
+        self.auto_restart = True
+        try:
+            self._process = await asyncio.create_subprocess_exec(
+                *shlex.split(self.command, posix=os.name != "nt"),
+                cwd=self.cwd or None,
+                stdout=asyncio.subprocess.PIPE,
+                stderr=asyncio.subprocess.PIPE,
+            )
+        except OSError as e:
+            self.status = "Error"
+            self.error = str(e)
+            raise ServiceError(f"Failed to start {self.name}: {e}")
+
+        self.pid = self._process.pid
+        self.started_at = time.time()
+        self.status = "Running"
+        self._logger.info(f"[manager] started pid {self.pid}: {self.command}")
+        self._watcher = asyncio.create_task(self._watch(self._process))
+
+    async def stop(self) -> None:
+        self.auto_restart = False
+        process = self._process
+        if not process or process.returncode is not None:
+            self.status = "Stopped"
+            return
+
+        self.status = "Stopping"
+        process.terminate()
+        try:
+            await asyncio.wait_for(process.wait(), STOP_TIMEOUT)
+        except asyncio.TimeoutError:
+            process.kill()
+            await process.wait()
+        if self._watcher:
+            await asyncio.gather(self._watcher, return_exceptions=True)
+        self.status = "Stopped"
+
+    async def restart(self) -> None:
+        await self.stop()
+        await self.start()
+
+    async def _pipe_to_log(self, stream: asyncio.StreamReader, label: str) -> None:
+        async for line in stream:
+            self._logger.info(f"[{label}] {line.decode(errors='replace').rstrip()}")
+
+    async def _watch(self, process: asyncio.subprocess.Process) -> None:
+        """Log the process output and restart it with backoff if it crashes"""
+        await asyncio.gather(
+            self._pipe_to_log(process.stdout, "stdout"),
+            self._pipe_to_log(process.stderr, "stderr"),
+        )
+        exit_code = await process.wait()
+        self.last_exit_code = exit_code
+        self.pid = None
+        self._logger.info(f"[manager] exited with code {exit_code}")
+
+        if not self.auto_restart:
+            return
+
+        self.status = "Error"
+        self.error = f"Exited unexpectedly with code {exit_code}"
+        if self.started_at and time.time() - self.started_at >= STABLE_UPTIME:
+            self._backoff = INITIAL_RESTART_BACKOFF
+        await asyncio.sleep(self._backoff)
+        self._backoff = min(self._backoff * 2, MAX_RESTART_BACKOFF)
+
+        if self.auto_restart:
+            self.restarts += 1
+            try:
+                await self.start()
+            except ServiceError:
+                pass
+
+
+def _log_dir() -> Path:
+    log_dir = settings.get_database_path().parent / "logs"
+    log_dir.mkdir(parents=True, exist_ok=True)
+    return log_dir
+
+
+class ServiceManager:
+    """Registry of the managed sidecar services"""
+
+    def __init__(self):
+        self._services: Dict[str, ManagedService] = {}
+
+    def _ensure_registered(self) -> None:
+        if self._services:
+            return
+        self._services = {
+            "ai_agent": ManagedService("ai_agent", settings.ai_agent_command, settings.ai_agent_dir),
+            "test_runner": ManagedService(
+                "test_runner", settings.test_runner_command, settings.test_runner_dir
+            ),
+        }
+
+    def get(self, name: str) -> ManagedService:
+        self._ensure_registered()
+        if name not in self._services:
+            raise ServiceError(f"Unknown service: {name}")
+        return self._services[name]
+
+    def all(self) -> List[ManagedService]:
+        self._ensure_registered()
+        return list(self._services.values())
+
+    async def stop_all(self) -> None:
+        for service in self._services.values():
+            await service.stop()
+
+
+# Singleton instance
+service_manager = ServiceManager()
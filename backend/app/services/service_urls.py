@@ -0,0 +1,39 @@
+"""
+Service URLs - Resolves the AI agent and test runner base URLs
+"""
+from typing import Dict
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from .app_settings import get_all_settings
+
+# Service name -> (app setting key, config field)
+SERVICE_URL_SOURCES = {
+    "ai_agent": ("services.ai_agent_url", "ai_agent_url"),
+    "test_runner": ("services.test_runner_url", "test_runner_url"),
+}
+
+
+async def get_service_urls() -> Dict[str, str]:
+    """
+    Get the base URL of every service
+
+    An environment variable (or .env entry) wins, then the stored app setting,
+    then the built-in localhost default.
+    """
+    async with AsyncSessionLocal() as db:
+        values = await get_all_settings(db)
+
+    urls = {}
+    for name, (setting_key, config_field) in SERVICE_URL_SOURCES.items():
+        if config_field in settings.model_fields_set or not values[setting_key]:
+            urls[name] = getattr(settings, config_field)
+        else:
+            urls[name] = values[setting_key]
+        urls[name] = urls[name].rstrip("/")
+    return urls
+
+
+async def get_service_url(name: str) -> str:
+    """Get the base URL of a single service"""
+    return (await get_service_urls())[name]
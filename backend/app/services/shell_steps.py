@@ -0,0 +1,204 @@
+"""
+Shell Steps - Runs setup and teardown commands from scenarios behind an allowlist
+"""
+import asyncio
+import os
+import shlex
+import time
+import uuid
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Set
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..db import AsyncSessionLocal, queue_write
+from ..models import RunLogCreate
+from .app_settings import get_setting
+from .events import event_bus
+from .run_logs import add_run_logs
+from .secret_masking import mask_secrets
+
+SHELL_STEP_TYPE = "shell"
+
+DEFAULT_SHELL_TIMEOUT_MS = 60000
+
+# Output kept in the run log; the artifact file holds everything
+LOGGED_OUTPUT_CHARS = 4000
+
+# How long an unlisted command waits for the user to approve it, in seconds
+CONFIRMATION_TIMEOUT = 120.0
+
+
+class ShellStepError(Exception):
+    """Raised when a shell step is refused, times out, or exits with an error"""
+
+
+@dataclass
+class ShellResult:
+    exit_code: int
+    output: str
+    duration_ms: int
+    artifact_path: str
+
+
+@dataclass
+class PendingConfirmation:
+    id: str
+    command: str
+    test_run_id: Optional[str]
+    future: asyncio.Future
+
+
+_confirmations: Dict[str, PendingConfirmation] = {}
+
+
+def list_confirmations() -> List[PendingConfirmation]:
+    return list(_confirmations.values())
+
+
+def resolve_confirmation(confirmation_id: str, approved: bool) -> bool:
+    """Approve or deny a waiting command, returning False if it's no longer waiting"""
+    pending = _confirmations.get(confirmation_id)
+    if not pending or pending.future.done():
+        return False
+    pending.future.set_result(approved)
+    return True
+
+
+def _is_listed(executable: str, allowlist: Set[str]) -> bool:
+    """
+    Whether the allowlist covers an executable
+
+    A bare name like "npm" is run from PATH and matches a listed name. A path
+    matches only when that exact path is listed, or for absolute paths one
+    that resolves to the same file, so listing "npm" doesn't allow ./npm or
+    /tmp/npm.
+    """
+    if executable in allowlist:
+        return True
+    if not Path(executable).is_absolute():
+        return False
+    resolved = Path(executable).resolve()
+    return any(Path(name).is_absolute() and Path(name).resolve() == resolved for name in allowlist)
+
+
+async def _authorize(argv: List[str], command: str, test_run_id: Optional[str]) -> None:
+    async with AsyncSessionLocal() as db:
+        enabled = await get_setting(db, "shell.enabled")
+        allowed = await get_setting(db, "shell.allowed_commands")
+        confirm_unlisted = await get_setting(db, "shell.confirm_unlisted")
+    if not enabled:
+        raise ShellStepError("Shell steps are disabled; turn on the shell.enabled setting")
+
+    allowlist = {name.strip() for name in allowed.split(",") if name.strip()}
+    if "*" in allowlist or _is_listed(argv[0], allowlist):
+        return
+    if not confirm_unlisted:
+        raise ShellStepError(f"Command '{argv[0]}' is not in shell.allowed_commands")
+
+    pending = PendingConfirmation(
+        id=str(uuid.uuid4()),
+        command=command,
+        test_run_id=test_run_id,
+        future=asyncio.get_running_loop().create_future(),
+    )
+    _confirmations[pending.id] = pending
+    event_bus.publish(
+        "shell:confirmation_required",
+        {"confirmation_id": pending.id, "command": command, "test_run_id": test_run_id},
+    )
+    try:
+        approved = await asyncio.wait_for(pending.future, CONFIRMATION_TIMEOUT)
+    except asyncio.TimeoutError:
+        approved = False
+    finally:
+        _confirmations.pop(pending.id, None)
+    if not approved:
+        raise ShellStepError(f"Command was not approved: {command}")
+
+
+def _artifact_path(test_run_id: Optional[str]) -> Path:
+    directory = settings.get_database_path().parent / "artifacts" / (test_run_id or "adhoc")
+    directory.mkdir(parents=True, exist_ok=True)
+    return directory / f"shell-{uuid.uuid4().hex[:12]}.log"
+
+
+async def run_shell_step(
+    config: Dict[str, Any],
+    env: Optional[Dict[str, str]] = None,
+    test_run_id: Optional[str] = None,
+) -> ShellResult:
+    """
+    Run a shell step's command without a shell, so no interpolation or pipes
+
+    The project's environment variables and the step's own `env` are added to
+    the backend's environment. Output is saved as an artifact and logged to the run.
+    """
+    command = (config.get("command") or "").strip()
+    if not command:
+        raise ShellStepError("Missing command for shell step")
+    try:
+        argv = shlex.split(command, posix=os.name != "nt")
+    except ValueError as e:
+        raise ShellStepError(f"Invalid command: {e}")
+    await _authorize(argv, command, test_run_id)
+
+    timeout = (config.get("timeout") or DEFAULT_SHELL_TIMEOUT_MS) / 1000
+    process_env = {**os.environ, **(env or {}), **{k: str(v) for k, v in (config.get("env") or {}).items()}}
+    start_time = time.time()
+    try:
+        process = await asyncio.create_subprocess_exec(
+            *argv,
+            cwd=config.get("cwd") or None,
+            env=process_env,
+            stdout=asyncio.subprocess.PIPE,
+            stderr=asyncio.subprocess.STDOUT,
+        )
+    except OSError as e:
+        raise ShellStepError(f"Failed to start command: {e}")
+
+    timed_out = False
+    try:
+        stdout, _ = await asyncio.wait_for(process.communicate(), timeout)
+    except asyncio.TimeoutError:
+        timed_out = True
+        process.kill()
+        stdout, _ = await process.communicate()
+    except BaseException:
+        # A cancelled run mustn't leave the command running behind it
+        if process.returncode is None:
+            process.kill()
+            await asyncio.shield(process.wait())
+        raise
+
+    output = stdout.decode(errors="replace")
+    artifact = _artifact_path(test_run_id)
+    artifact.write_text(mask_secrets(f"$ {command}\n{output}"), encoding="utf-8")
+    result = ShellResult(
+        exit_code=-1 if timed_out else process.returncode,
+        output=output,
+        duration_ms=int((time.time() - start_time) * 1000),
+        artifact_path=str(artifact),
+    )
+
+    if test_run_id:
+        level = "error" if result.exit_code != 0 else "info"
+        logs = [
+            RunLogCreate(level=level, source="shell", message=f"$ {command} (exit {result.exit_code})"),
+            RunLogCreate(level="debug", source="shell", message=output[-LOGGED_OUTPUT_CHARS:] or "(no output)"),
+            RunLogCreate(level="info", source="shell", message=f"Output saved to {artifact}"),
+        ]
+
+        async def write(db: AsyncSession) -> None:
+            await add_run_logs(db, test_run_id, logs)
+
+        # Queued while the database is busy, so logging never fails a command that ran
+        await queue_write(write)
+
+    if timed_out:
+        raise ShellStepError(f"Command timed out after {timeout:g}s: {command}")
+    if result.exit_code != 0 and not config.get("allow_failure"):
+        raise ShellStepError(f"Command exited with code {result.exit_code}: {output[-500:].strip()}")
+    return result
@@ -0,0 +1,128 @@
+"""
+Single Step - Runs one step from the editor without creating a test run
+
+A device step runs against whatever screen the device is on, so authors can
+iterate on a locator by trying it again after each edit. Browser steps run
+in a fresh browser opened on the scenario's page, since specs don't share a
+browser between runs. Either way the outcome comes back with a screenshot
+taken after the step, and nothing is recorded.
+"""
+import base64
+import json
+import time
+from typing import Any, Dict, Optional
+
+from pydantic import BaseModel
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Project, Scenario, Step, TestCase
+from .app_settings import get_setting
+from .browser_state import BrowserStateError
+from .browser_tabs import BrowserTabError
+from .browsers import BrowserError, ensure_browser, resolve_browser
+from .elements import apply_element, load_elements
+from .executor import ScenarioExecutor, StepExecutionError
+from .file_steps import FileStepError
+from .shell_steps import SHELL_STEP_TYPE
+from .synonyms import load_synonyms
+from .test_runner import TestFramework, test_runner
+from .web_executor import WebScenarioExecutor, judge_spec, runner_step
+
+
+class SingleStepRequest(BaseModel):
+    """Schema for where to try a step, and optionally an unsaved config to try instead of its own"""
+    target: str = "device"  # 'device' | 'browser'
+    device_id: Optional[str] = None
+    platform: Optional[str] = None  # 'android' | 'ios'
+    framework: TestFramework = TestFramework.PLAYWRIGHT
+    browser: Optional[str] = None  # Empty uses the project's default browser
+    headless: Optional[bool] = None
+    url: Optional[str] = None  # Browser page to run the step on; empty uses the scenario's or project's URL
+    self_heal: bool = False
+    config: Optional[Dict[str, Any]] = None
+
+
+class SingleStepResult(BaseModel):
+    """Schema for the outcome of a step tried from the editor"""
+    status: str
+    duration_ms: int
+    error_message: Optional[str] = None
+    healed_locator: Optional[Dict[str, Any]] = None
+    details: Optional[Dict[str, Any]] = None
+    screenshot: Optional[str] = None  # Base64 PNG taken after the step
+
+
+async def _run_on_device(
+    db: AsyncSession, step: Step, config: Dict[str, Any], project: Project, request: SingleStepRequest
+) -> SingleStepResult:
+    if not request.device_id or not request.platform:
+        raise ValueError("device_id and platform are required to run a step on a device")
+    executor = ScenarioExecutor(
+        request.device_id, request.platform, self_heal=request.self_heal, ai_free=project.ai_free
+    )
+    executor.env = json.loads(project.env_vars or "{}")
+    executor.implicit_wait_ms = await get_setting(db, "executor.implicit_wait_ms")
+    executor.synonyms = await load_synonyms(db, project.id)
+    outcome = await executor.execute_step(step.step_type, config)
+    try:
+        screenshot = await executor.screenshot()
+    except StepExecutionError:
+        screenshot = None
+    return SingleStepResult(
+        status=outcome.status,
+        duration_ms=outcome.duration_ms,
+        error_message=outcome.error_message,
+        healed_locator=outcome.healed_locator,
+        details=outcome.details,
+        screenshot=screenshot,
+    )
+
+
+async def _run_in_browser(
+    step: Step, config: Dict[str, Any], project: Project, scenario: Scenario, request: SingleStepRequest
+) -> SingleStepResult:
+    if step.step_type == SHELL_STEP_TYPE:
+        executor = WebScenarioExecutor(request.framework, request.browser, request.headless)
+        executor.env = json.loads(project.env_vars or "{}")
+        outcome = await executor.execute_step(step.step_type, config)
+        return SingleStepResult(
+            status=outcome.status, duration_ms=outcome.duration_ms, error_message=outcome.error_message
+        )
+
+    base_url = request.url or scenario.target_url or project.app_url
+    browser, headless = resolve_browser(project, request.browser, request.headless)
+    if request.framework == TestFramework.CYPRESS:
+        run_steps = test_runner.run_steps_as_cypress
+    else:
+        run_steps = test_runner.run_steps_as_playwright
+    started = time.time()
+    try:
+        await ensure_browser(browser)
+        result = await run_steps(
+            [runner_step(step.step_type, config)], base_url, browser, headless, capture_screenshot=True
+        )
+    except (BrowserError, BrowserStateError, BrowserTabError, FileStepError) as e:
+        result = {"success": False, "error": str(e)}
+    status, error, details = judge_spec(result, [(step.step_type, config)])[0]
+    return SingleStepResult(
+        status=status,
+        duration_ms=int((time.time() - started) * 1000),
+        error_message=error,
+        details=details,
+        screenshot=base64.b64encode(result["screenshot"]).decode() if result.get("screenshot") else None,
+    )
+
+
+async def execute_single_step(db: AsyncSession, step_id: str, request: SingleStepRequest) -> SingleStepResult:
+    """Run one step against the device's current screen or a fresh browser, returning its outcome"""
+    step = await db.get(Step, step_id)
+    scenario = await db.get(Scenario, step.scenario_id)
+    test_case = await db.get(TestCase, scenario.test_case_id)
+    project = await db.get(Project, test_case.project_id)
+    raw = request.config if request.config is not None else json.loads(step.config or "{}")
+    platform = "web" if request.target == "browser" else request.platform
+    config = apply_element(raw, await load_elements(db, project.id), platform)
+
+    if request.target == "browser":
+        return await _run_in_browser(step, config, project, scenario, request)
+    return await _run_on_device(db, step, config, project, request)
@@ -0,0 +1,177 @@
+"""
+Stat Counters - Per project counts kept up to date as rows are written
+
+Test case and test run stats are read from the stat_counters table instead
+of being recomputed on every request. Triggers on test_cases and
+test_runs adjust the counters in the same transaction as each insert, update
+and delete, so bulk updates and raw SQL writes are counted too. The triggers
+are created, and every counter rebuilt, when the database is initialized
+without them; rebuild_stats repairs a single project's counters.
+"""
+from collections import defaultdict
+from typing import Dict, List, Optional, Set, Tuple
+
+from sqlalchemy import event, select, text
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db.database import Base
+from ..models import CategoryCount, PriorityCount, StatCounter, TestCaseStats, TestRunSummary
+
+# (metric, table, counted column, summed column); runs are counted by duration only when they have one
+COUNTED: Tuple[Tuple[str, str, Optional[str], Optional[str]], ...] = (
+    ("test_case_status", "test_cases", "status", None),
+    ("test_case_category", "test_cases", "category", None),
+    ("test_case_priority", "test_cases", "priority", None),
+    ("test_run_status", "test_runs", "status", None),
+    ("test_run_duration", "test_runs", None, "duration_ms"),
+)
+
+
+def _parts(row: str, column: Optional[str], summed: Optional[str]) -> Tuple[str, str, str]:
+    """A counted row's key, summed value and the condition for counting it, as SQL over row"""
+    key = f"{row}.{column}" if column else "''"
+    total = f"{row}.{summed}" if summed else "0"
+    conditions = [f"{row}.{name} IS NOT NULL" for name in (column, summed) if name]
+    return key, total, " AND ".join(conditions) or "1 = 1"
+
+
+def _add_sql(metric: str, column: Optional[str], summed: Optional[str]) -> str:
+    key, total, condition = _parts("NEW", column, summed)
+    return (
+        f"INSERT INTO stat_counters (project_id, metric, key, count, total) "
+        f"SELECT NEW.project_id, '{metric}', {key}, 1, {total} WHERE {condition} "
+        f"ON CONFLICT (project_id, metric, key) "
+        f"DO UPDATE SET count = stat_counters.count + 1, total = stat_counters.total + excluded.total;"
+    )
+
+
+def _remove_sql(metric: str, column: Optional[str], summed: Optional[str]) -> str:
+    key, total, condition = _parts("OLD", column, summed)
+    return (
+        f"UPDATE stat_counters SET count = count - 1, total = total - {total} "
+        f"WHERE project_id = OLD.project_id AND metric = '{metric}' AND key = {key} AND {condition};"
+    )
+
+
+def _triggers() -> Dict[str, Tuple[str, str, str]]:
+    """Each trigger's table, timing and body by trigger name"""
+    tables: Dict[str, List[Tuple[str, Optional[str], Optional[str]]]] = defaultdict(list)
+    for metric, table, column, summed in COUNTED:
+        tables[table].append((metric, column, summed))
+
+    triggers = {}
+    for table, metrics in tables.items():
+        adds = " ".join(_add_sql(*metric) for metric in metrics)
+        removes = " ".join(_remove_sql(*metric) for metric in metrics)
+        columns = ", ".join(["project_id", *sorted({name for _, *names in metrics for name in names if name})])
+        for name, timing, body in (
+            (f"{table}_counted_insert", "AFTER INSERT", adds),
+            (f"{table}_counted_delete", "AFTER DELETE", removes),
+            (f"{table}_counted_update", f"AFTER UPDATE OF {columns}", f"{removes} {adds}"),
+        ):
+            triggers[name] = (table, timing, body)
+    triggers["projects_counted_delete"] = (
+        "projects", "AFTER DELETE", "DELETE FROM stat_counters WHERE project_id = OLD.id;"
+    )
+    return triggers
+
+
+def _create_trigger_sql(dialect: str, name: str, table: str, timing: str, body: str) -> List[str]:
+    """Statements creating a trigger; PostgreSQL runs trigger bodies as functions"""
+    if dialect != "postgresql":
+        return [f"CREATE TRIGGER IF NOT EXISTS {name} {timing} ON {table} BEGIN {body} END"]
+    return [
+        f"CREATE OR REPLACE FUNCTION {name}() RETURNS trigger AS $$ BEGIN {body} RETURN NULL; END $$ LANGUAGE plpgsql",
+        f"DROP TRIGGER IF EXISTS {name} ON {table}",
+        f"CREATE TRIGGER {name} {timing} ON {table} FOR EACH ROW EXECUTE FUNCTION {name}()",
+    ]
+
+
+def _existing_triggers(connection) -> Set[str]:
+    if connection.dialect.name == "postgresql":
+        sql = "SELECT tgname FROM pg_trigger WHERE NOT tgisinternal"
+    else:
+        sql = "SELECT name FROM sqlite_master WHERE type = 'trigger'"
+    return set(connection.exec_driver_sql(sql).scalars())
+
+
+def _rebuild_sql(project_id: Optional[str]) -> List[str]:
+    """Statements recounting one project's counters, or every project's"""
+    scope = "project_id = :project_id" if project_id else "1 = 1"
+    statements = [f"DELETE FROM stat_counters WHERE {scope}"]
+    for metric, table, column, summed in COUNTED:
+        _, _, condition = _parts(table, column, summed)
+        key = column or "''"
+        statements.append(
+            f"INSERT INTO stat_counters (project_id, metric, key, count, total) "
+            f"SELECT project_id, '{metric}', {key}, COUNT(*), {f'COALESCE(SUM({summed}), 0)' if summed else '0'} "
+            f"FROM {table} WHERE {condition} AND {scope} GROUP BY project_id, {key}"
+        )
+    return statements
+
+
+@event.listens_for(Base.metadata, "after_create")
+def _create_triggers(metadata, connection, **kwargs) -> None:
+    """Create missing counter triggers, recounting everything since writes made without them weren't counted"""
+    triggers = _triggers()
+    if _existing_triggers(connection).issuperset(triggers):
+        return
+    for name, trigger in triggers.items():
+        for sql in _create_trigger_sql(connection.dialect.name, name, *trigger):
+            connection.exec_driver_sql(sql)
+    for sql in _rebuild_sql(None):
+        connection.execute(text(sql))
+
+
+async def rebuild_stats(db: AsyncSession, project_id: str) -> None:
+    """Recount a project's counters from its test cases and runs, without committing"""
+    for sql in _rebuild_sql(project_id):
+        await db.execute(text(sql), {"project_id": project_id})
+
+
+async def project_counters(db: AsyncSession, project_id: str) -> Dict[str, Dict[str, StatCounter]]:
+    """A project's non-zero counters by metric and key"""
+    result = await db.execute(
+        select(StatCounter)
+        .where(StatCounter.project_id == project_id, StatCounter.count > 0)
+        .order_by(StatCounter.key)
+    )
+    counters: Dict[str, Dict[str, StatCounter]] = defaultdict(dict)
+    for counter in result.scalars().all():
+        counters[counter.metric][counter.key] = counter
+    return counters
+
+
+def _count(counters: Dict[str, StatCounter], *keys: str) -> int:
+    return sum(counters[key].count for key in keys if key in counters)
+
+
+async def test_case_stats(db: AsyncSession, project_id: str) -> TestCaseStats:
+    counters = await project_counters(db, project_id)
+    statuses = counters["test_case_status"]
+    categories: Dict[str, int] = defaultdict(int)
+    for category, counter in counters["test_case_category"].items():
+        categories[category or "Uncategorized"] += counter.count
+    return TestCaseStats(
+        total=sum(counter.count for counter in statuses.values()),
+        passed=_count(statuses, "success"),
+        failed=_count(statuses, "failed"),
+        pending=_count(statuses, "pending"),
+        by_category=[CategoryCount(category=category, count=count) for category, count in categories.items()],
+        by_priority=[
+            PriorityCount(priority=priority, count=counter.count)
+            for priority, counter in counters["test_case_priority"].items()
+        ],
+    )
+
+
+async def test_run_summary(db: AsyncSession, project_id: str) -> TestRunSummary:
+    counters = await project_counters(db, project_id)
+    statuses = counters["test_run_status"]
+    duration = counters["test_run_duration"].get("")
+    return TestRunSummary(
+        total_runs=sum(counter.count for counter in statuses.values()),
+        passed_runs=_count(statuses, "passed", "passed_with_warnings"),
+        failed_runs=_count(statuses, "failed"),
+        avg_duration_ms=duration.total / duration.count if duration else None,
+    )
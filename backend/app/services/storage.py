@@ -0,0 +1,310 @@
+"""
+Storage Manager - Disk usage of test runs and the retention rules that clean them up
+
+A run's artifacts are its attachments, in local or S3 storage, and the step
+screenshots saved under the data directory. Retention keeps the newest
+retention.keep_runs runs of each project; older runs are deleted with their
+artifacts, except failed runs younger than retention.keep_failed_days, runs
+that haven't finished, and runs approved as a scenario's baseline. Cleanup
+can be previewed as a dry run, and runs daily while retention.auto_cleanup
+is on.
+"""
+import asyncio
+import time
+from collections import defaultdict
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import delete, func, select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal
+from ..db.database import IS_SQLITE, db_path
+from ..models import (
+    Attachment,
+    Project,
+    RunLog,
+    RunTransition,
+    ScenarioBaseline,
+    StepResult,
+    StressCrash,
+    TestRun,
+    WebhookRun,
+)
+from .app_settings import get_all_settings
+from .attachments import AttachmentError, get_storage
+from .backup import list_snapshots
+from .events import event_bus
+from .run_lifecycle import FINISHED_STATUSES
+from .screenshots import remove_run_screenshots
+
+CLEANUP_INTERVAL_SECONDS = 24 * 60 * 60
+
+# How often the cleaner checks whether a cleanup is due, in seconds
+CLEANER_POLL_SECONDS = 60 * 60
+
+# Tables whose rows belong to a single test run
+RUN_TABLES = (StepResult, RunLog, RunTransition, StressCrash, Attachment, ScenarioBaseline, WebhookRun)
+
+
+class RunStorage(BaseModel):
+    """Schema for the space one test run's artifacts take"""
+    test_run_id: str
+    name: str
+    status: str
+    created_at: datetime
+    attachment_bytes: int
+    screenshot_bytes: int
+    total_bytes: int
+
+
+class ProjectStorage(BaseModel):
+    """Schema for the space a project's test runs take"""
+    project_id: str
+    name: str
+    runs: int
+    attachment_bytes: int
+    screenshot_bytes: int
+    total_bytes: int
+
+
+class StorageUsage(BaseModel):
+    """Schema for disk usage across projects, with per run usage when one project is asked for"""
+    database_bytes: int
+    snapshot_bytes: int
+    artifact_bytes: int
+    projects: List[ProjectStorage]
+    runs: List[RunStorage] = []
+
+
+class CleanupCandidate(BaseModel):
+    """Schema for a test run the retention rules delete"""
+    test_run_id: str
+    project_id: str
+    name: str
+    status: str
+    created_at: datetime
+    total_bytes: int
+
+
+class CleanupResult(BaseModel):
+    """Schema for what a cleanup deleted, or would delete on a dry run"""
+    dry_run: bool
+    runs: List[CleanupCandidate]
+    freed_bytes: int
+    errors: List[str] = []  # Runs kept because their attachments couldn't be deleted
+
+
+# ============================================
+# Usage
+# ============================================
+
+
+async def _run_bytes(db: AsyncSession, project_id: Optional[str] = None) -> Dict[str, List[int]]:
+    """[attachment bytes, screenshot bytes] by test run id"""
+    sizes: Dict[str, List[int]] = defaultdict(lambda: [0, 0])
+    attachments = select(Attachment.test_run_id, func.sum(Attachment.size_bytes)).group_by(Attachment.test_run_id)
+    # Web screenshots are attachments; only the ones saved by device runs have a path
+    screenshots = (
+        select(StepResult.test_run_id, func.sum(StepResult.screenshot_bytes))
+        .where(StepResult.screenshot_path.is_not(None), StepResult.screenshot_bytes.is_not(None))
+        .group_by(StepResult.test_run_id)
+    )
+    if project_id:
+        attachments = attachments.join(TestRun, Attachment.test_run_id == TestRun.id).where(
+            TestRun.project_id == project_id
+        )
+        screenshots = screenshots.join(TestRun, StepResult.test_run_id == TestRun.id).where(
+            TestRun.project_id == project_id
+        )
+    for index, query in enumerate((attachments, screenshots)):
+        for test_run_id, total in (await db.execute(query)).all():
+            sizes[test_run_id][index] = total or 0
+    return sizes
+
+
+def _file_size(path: Path) -> int:
+    return path.stat().st_size if path.exists() else 0
+
+
+async def get_storage_usage(db: AsyncSession, project_id: Optional[str] = None) -> StorageUsage:
+    """Disk usage per project, and per run of the given project"""
+    sizes = await _run_bytes(db, project_id)
+    query = select(Project.id, Project.name, func.count(TestRun.id)).outerjoin(
+        TestRun, TestRun.project_id == Project.id
+    ).group_by(Project.id)
+    if project_id:
+        query = query.where(Project.id == project_id)
+    run_projects = dict((await db.execute(
+        select(TestRun.id, TestRun.project_id).where(TestRun.id.in_(sizes))
+    )).all()) if sizes else {}
+
+    totals: Dict[str, List[int]] = defaultdict(lambda: [0, 0])
+    for test_run_id, (attachment_bytes, screenshot_bytes) in sizes.items():
+        owner = run_projects.get(test_run_id)
+        if owner:
+            totals[owner][0] += attachment_bytes
+            totals[owner][1] += screenshot_bytes
+
+    projects = [
+        ProjectStorage(
+            project_id=owner,
+            name=name,
+            runs=runs,
+            attachment_bytes=totals[owner][0],
+            screenshot_bytes=totals[owner][1],
+            total_bytes=sum(totals[owner]),
+        )
+        for owner, name, runs in (await db.execute(query)).all()
+    ]
+    projects.sort(key=lambda p: p.total_bytes, reverse=True)
+
+    runs = []
+    if project_id:
+        result = await db.execute(
+            select(TestRun).where(TestRun.project_id == project_id).order_by(TestRun.created_at.desc())
+        )
+        runs = [
+            RunStorage(
+                test_run_id=run.id,
+                name=run.name,
+                status=run.status,
+                created_at=run.created_at,
+                attachment_bytes=sizes[run.id][0],
+                screenshot_bytes=sizes[run.id][1],
+                total_bytes=sum(sizes[run.id]),
+            )
+            for run in result.scalars().all()
+        ]
+
+    database_bytes = 0  # A server database's size is the server's to report
+    if IS_SQLITE:
+        database_bytes = sum(_file_size(db_path.with_name(db_path.name + suffix)) for suffix in ("", "-wal"))
+    return StorageUsage(
+        database_bytes=database_bytes,
+        snapshot_bytes=sum(_file_size(path) for path in list_snapshots()),
+        artifact_bytes=sum(p.total_bytes for p in projects),
+        projects=projects,
+        runs=runs,
+    )
+
+
+# ============================================
+# Retention
+# ============================================
+
+
+async def expired_runs(db: AsyncSession, values: Dict[str, Any]) -> List[TestRun]:
+    """Runs outside the retention rules, oldest first"""
+    keep_runs = values["retention.keep_runs"]
+    if keep_runs <= 0:
+        return []
+    failed_cutoff = datetime.utcnow() - timedelta(days=values["retention.keep_failed_days"])
+    baselines = set((await db.execute(
+        select(ScenarioBaseline.test_run_id).where(ScenarioBaseline.superseded_at.is_(None))
+    )).scalars().all())
+
+    result = await db.execute(select(TestRun).order_by(TestRun.project_id, TestRun.created_at.desc()))
+    by_project: Dict[str, List[TestRun]] = defaultdict(list)
+    for run in result.scalars().all():
+        by_project[run.project_id].append(run)
+
+    expired = []
+    for runs in by_project.values():
+        for run in runs[keep_runs:]:
+            if run.status not in FINISHED_STATUSES or run.id in baselines:
+                continue
+            if run.status == "failed" and run.created_at > failed_cutoff:
+                continue
+            expired.append(run)
+    expired.sort(key=lambda run: run.created_at)
+    return expired
+
+
+async def _delete_run(db: AsyncSession, run: TestRun, values: Dict[str, Any]) -> None:
+    attachments = (await db.execute(
+        select(Attachment).where(Attachment.test_run_id == run.id)
+    )).scalars().all()
+    for attachment in attachments:
+        await get_storage(values, attachment.storage).delete(attachment.storage_key)
+    await asyncio.to_thread(remove_run_screenshots, run.id)
+    for table in RUN_TABLES:
+        await db.execute(delete(table).where(table.test_run_id == run.id))
+    await db.delete(run)
+    await db.commit()
+
+
+async def apply_retention(db: AsyncSession, dry_run: bool = True) -> CleanupResult:
+    """Delete the runs outside the retention rules with their artifacts, or list them on a dry run"""
+    values = await get_all_settings(db)
+    runs = await expired_runs(db, values)
+    sizes = await _run_bytes(db)
+    deleted = []
+    errors = []
+    for run in runs:
+        candidate = CleanupCandidate(
+            test_run_id=run.id,
+            project_id=run.project_id,
+            name=run.name,
+            status=run.status,
+            created_at=run.created_at,
+            total_bytes=sum(sizes[run.id]),
+        )
+        if not dry_run:
+            try:
+                await _delete_run(db, run, values)
+            except AttachmentError as e:
+                await db.rollback()
+                errors.append(f"{run.name}: {e}")
+                continue
+        deleted.append(candidate)
+
+    cleanup = CleanupResult(
+        dry_run=dry_run, runs=deleted, freed_bytes=sum(c.total_bytes for c in deleted), errors=errors
+    )
+    if not dry_run:
+        event_bus.publish("storage:cleanup_completed", {
+            "deleted_runs": len(deleted),
+            "freed_bytes": cleanup.freed_bytes,
+            "errors": len(errors),
+        })
+    return cleanup
+
+
+class RetentionCleaner:
+    """Applies the retention rules once a day while the retention.auto_cleanup setting is on"""
+
+    def __init__(self):
+        self._task: Optional[asyncio.Task] = None
+        self._last_cleanup: Optional[float] = None
+
+    def start(self) -> None:
+        if not self._task or self._task.done():
+            self._task = asyncio.create_task(self._run())
+
+    async def stop(self) -> None:
+        if self._task:
+            self._task.cancel()
+            await asyncio.gather(self._task, return_exceptions=True)
+            self._task = None
+
+    async def _run(self) -> None:
+        while True:
+            try:
+                async with AsyncSessionLocal() as db:
+                    enabled = (await get_all_settings(db))["retention.auto_cleanup"]
+                    if enabled and self._cleanup_due():
+                        self._last_cleanup = time.time()
+                        await apply_retention(db, dry_run=False)
+            except Exception as e:
+                event_bus.publish("storage:cleanup_failed", {"error": str(e)})
+            await asyncio.sleep(CLEANER_POLL_SECONDS)
+
+    def _cleanup_due(self) -> bool:
+        return self._last_cleanup is None or time.time() - self._last_cleanup >= CLEANUP_INTERVAL_SECONDS
+
+
+# Singleton instance
+retention_cleaner = RetentionCleaner()
@@ -0,0 +1,278 @@
+"""
+Stress Testing - Sends random input to an app and records the crashes and ANRs it causes
+
+Android runs `adb shell monkey` and reads the crash log buffer afterwards. iOS
+simulators get seeded random taps and swipes, with the app's process and new
+crash reports checked between events. Each stress test is a test run that
+fails when anything crashed; every crash keeps the seed that reproduces it.
+"""
+import asyncio
+import random
+import re
+import shlex
+from dataclasses import dataclass
+from datetime import datetime
+from typing import List, Optional
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..db import AsyncSessionLocal
+from ..models import Project, RunLogCreate, StressCrash, StressTestRequest, TestRun
+from ..routers.mobile import is_physical_ios, run_adb_command, run_device_process, run_xcrun_command
+from .coordinates import png_size
+from .crash_logs import collect_run_crashes, list_crashes_since
+from .events import event_bus
+from .executor import ScenarioExecutor, StepExecutionError
+from .run_lifecycle import RunTransitionError, cancel_run, complete_run, fail_run, start_run
+from .run_logs import add_run_logs
+from .run_queue import QueueError, run_queue
+from .tools import tool_command
+
+MAX_EVENTS = 100000
+MAX_THROTTLE_MS = 10000
+
+# How often the iOS generator checks that the app is still running, in events
+IOS_CHECK_INTERVAL = 10
+
+_MONKEY_EVENTS = re.compile(r"Events injected:\s*(\d+)")
+_MONKEY_SENDING = re.compile(r":Sending \w+")
+
+
+class StressTestError(Exception):
+    """Raised when a stress test can't be started"""
+
+
+@dataclass
+class FoundCrash:
+    kind: str  # 'crash' | 'anr'
+    process: Optional[str]
+    message: str
+    stack_trace: Optional[str] = None
+    event_index: Optional[int] = None
+
+
+# ============================================
+# Output parsing
+# ============================================
+
+
+def parse_monkey_output(output: str) -> List[FoundCrash]:
+    """Crashes and ANRs monkey reported, with how many events it had sent before each"""
+    crashes: List[FoundCrash] = []
+    current: Optional[FoundCrash] = None
+    events = 0
+    for line in output.splitlines():
+        if _MONKEY_SENDING.search(line):
+            events += 1
+        match = re.match(r"// (CRASH|NOT RESPONDING): (\S+)", line)
+        if match:
+            current = FoundCrash(
+                "crash" if match.group(1) == "CRASH" else "anr", match.group(2), match.group(1).title(),
+                stack_trace="", event_index=events,
+            )
+            crashes.append(current)
+            continue
+        if current and line.startswith("// "):
+            text = line[3:]
+            if text.startswith("Short Msg: ") and current.kind == "crash":
+                current.message = text[len("Short Msg: "):]
+            elif text.startswith("Reason: ") and current.kind == "anr":
+                current.message = text[len("Reason: "):]
+            current.stack_trace += text + "\n"
+        elif current and not line.startswith("//"):
+            current = None
+    for crash in crashes:
+        crash.stack_trace = crash.stack_trace.strip() or None
+    return crashes
+
+
+def parse_crash_buffer(logcat: str, package: str) -> List[FoundCrash]:
+    """Fatal exceptions and ANRs for a package from `logcat -b crash` or the main buffer"""
+    crashes: List[FoundCrash] = []
+    lines = logcat.splitlines()
+    for index, line in enumerate(lines):
+        if "FATAL EXCEPTION" in line:
+            block = [line]
+            for following in lines[index + 1:]:
+                if "AndroidRuntime" not in following or "FATAL EXCEPTION" in following:
+                    break
+                block.append(following)
+            text = "\n".join(block)
+            if f"Process: {package}" not in text:
+                continue
+            message = next((b.split(": ", 1)[-1] for b in block[2:3]), "Fatal exception")
+            crashes.append(FoundCrash("crash", package, message.strip(), text))
+        elif f"ANR in {package}" in line:
+            crashes.append(FoundCrash("anr", package, "Application not responding", line))
+    return crashes
+
+
+# ============================================
+# Android
+# ============================================
+
+
+def monkey_command(package: str, seed: int, events: int, throttle_ms: int) -> List[str]:
+    return [
+        "shell", "monkey", "-p", package, "-s", str(seed), "--throttle", str(throttle_ms),
+        "--pct-syskeys", "0", "-v", "-v", str(events),
+    ]
+
+
+async def _stress_android(device_id: str, package: str, seed: int, events: int, throttle_ms: int) -> tuple:
+    """Run monkey, returning (events sent, crashes found)"""
+    await run_adb_command(["logcat", "-c"], device_id)
+    timeout = events * (throttle_ms + 50) / 1000 + 60
+    # monkey exits with an error when the app crashes, so read its output either way
+    output = await _run_monkey(device_id, monkey_command(package, seed, events, throttle_ms), timeout)
+    crashes = parse_monkey_output(output)
+    match = _MONKEY_EVENTS.search(output)
+    sent = int(match.group(1)) if match else None
+
+    logcat = await run_adb_command(["logcat", "-d", "-b", "crash", "-b", "main", "-v", "brief"], device_id)
+    from_log = parse_crash_buffer(logcat, package)
+    if from_log and not crashes:
+        crashes = from_log  # Native crashes and ANRs monkey didn't catch
+    elif from_log:
+        # The crash log has the full stack trace
+        logged = [c for c in from_log if c.kind == "crash"]
+        for crash, logged_crash in zip([c for c in crashes if c.kind == "crash"], logged):
+            crash.stack_trace = logged_crash.stack_trace
+    return sent if sent is not None else events, crashes
+
+
+async def _run_monkey(device_id: str, args: List[str], timeout: float) -> str:
+    _, stdout, stderr = await run_device_process(
+        [tool_command("adb"), "-s", device_id] + args, device_id, timeout
+    )
+    return stdout.decode(errors="replace") + stderr.decode(errors="replace")
+
+
+# ============================================
+# iOS
+# ============================================
+
+
+async def _ios_app_running(device_id: str, package: str) -> bool:
+    output = await run_xcrun_command(["spawn", device_id, "launchctl", "list"], device_id)
+    return f"UIKitApplication:{package}" in output
+
+
+async def _stress_ios(device_id: str, package: str, seed: int, events: int, throttle_ms: int) -> tuple:
+    """Send seeded random taps and swipes, returning (events sent, crashes found)"""
+    if is_physical_ios(device_id):
+        raise StressTestError("Stress tests need an iOS simulator")
+    executor = ScenarioExecutor(device_id, "ios", self_heal=False, ai_free=True)
+    started = datetime.utcnow()
+    await run_xcrun_command(["launch", device_id, package], device_id)
+    size = png_size(await executor._screenshot())
+    if not size:
+        raise StressTestError("Couldn't read the simulator's screen size")
+    width, height = size
+
+    rng = random.Random(seed)
+    for sent in range(events):
+        x, y = rng.randrange(width), rng.randrange(height)
+        try:
+            if rng.random() < 0.8:
+                await executor._tap(x, y)
+            else:
+                await executor._swipe(x, y, rng.randrange(width), rng.randrange(height), rng.randint(100, 600))
+        except StepExecutionError:
+            pass  # A tap outside the app's window isn't a crash
+        await asyncio.sleep(throttle_ms / 1000)
+
+        if (sent + 1) % IOS_CHECK_INTERVAL == 0 or sent + 1 == events:
+            if not await _ios_app_running(device_id, package):
+                reports = [c for c in await list_crashes_since(started, device_id, "ios") if package in c.content]
+                stack = reports[-1].content if reports else None
+                message = "The app stopped running" + (f" ({reports[-1].name})" if reports else "")
+                return sent + 1, [FoundCrash("crash", package, message, stack, sent + 1)]
+    return events, []
+
+
+# ============================================
+# Runs
+# ============================================
+
+
+def reproduce_command(request: StressTestRequest, seed: int) -> str:
+    if request.platform == "android":
+        args = monkey_command(request.package_name, seed, request.events, request.throttle_ms)
+        return shlex.join(["adb", "-s", request.device_id] + args)
+    return f"Run a stress test on {request.package_name} with seed {seed} and {request.events} events"
+
+
+async def _log(db: AsyncSession, test_run_id: str, level: str, message: str) -> None:
+    await add_run_logs(db, test_run_id, [RunLogCreate(level=level, source="stress", message=message)])
+
+
+async def run_stress_test(test_run_id: str, request: StressTestRequest, seed: int) -> None:
+    async with AsyncSessionLocal() as db:
+        test_run = await start_run(db, await db.get(TestRun, test_run_id))
+        await _log(db, test_run_id, "info", f"Stress testing {request.package_name} with seed {seed}")
+        try:
+            stress = _stress_android if request.platform == "android" else _stress_ios
+            sent, crashes = await stress(
+                request.device_id, request.package_name, seed, request.events, request.throttle_ms
+            )
+        except asyncio.CancelledError:
+            try:
+                await cancel_run(db, test_run)
+            except RunTransitionError:
+                pass
+            raise
+        except Exception as e:
+            await _log(db, test_run_id, "error", f"Stress test crashed: {e}")
+            await fail_run(db, test_run, "Stress test crashed")
+            return
+
+        command = reproduce_command(request, seed)
+        for crash in crashes:
+            db.add(StressCrash(
+                test_run_id=test_run_id,
+                kind=crash.kind,
+                process=crash.process,
+                message=crash.message,
+                stack_trace=crash.stack_trace,
+                seed=seed,
+                event_index=crash.event_index,
+                reproduce_command=command,
+            ))
+            label = "ANR" if crash.kind == "anr" else "Crash"
+            await _log(db, test_run_id, "error", f"{label} after {crash.event_index or sent} events: {crash.message}")
+        await _log(
+            db, test_run_id, "error" if crashes else "info",
+            f"Sent {sent} events, {len(crashes)} crashes. Reproduce with: {command}",
+        )
+        await complete_run(db, test_run, 0 if crashes else 1, len(crashes), 0, force_failed=bool(crashes))
+        await collect_run_crashes(db, test_run, request.device_id, request.platform)
+        event_bus.publish("stress:finished", {"test_run_id": test_run_id, "seed": seed, "crashes": len(crashes)})
+
+
+async def start_stress_test(db: AsyncSession, request: StressTestRequest) -> TestRun:
+    """Queue a stress test as a test run on its device"""
+    if request.platform not in ("android", "ios"):
+        raise StressTestError("Platform must be android or ios")
+    if not 1 <= request.events <= MAX_EVENTS:
+        raise StressTestError(f"events must be between 1 and {MAX_EVENTS}")
+    if not 0 <= request.throttle_ms <= MAX_THROTTLE_MS:
+        raise StressTestError(f"throttle_ms must be between 0 and {MAX_THROTTLE_MS}")
+    if not await db.get(Project, request.project_id):
+        raise StressTestError("Project not found")
+
+    seed = request.seed if request.seed is not None else random.randrange(2 ** 31)
+    test_run = TestRun(project_id=request.project_id, name=f"Stress test: {request.package_name} (seed {seed})")
+    db.add(test_run)
+    await db.commit()
+    await db.refresh(test_run)
+    try:
+        run_queue.enqueue(
+            test_run.id, request.device_id, request.platform,
+            lambda: run_stress_test(test_run.id, request, seed), request.priority,
+        )
+    except QueueError as e:
+        await db.delete(test_run)
+        await db.commit()
+        raise StressTestError(str(e))
+    return test_run
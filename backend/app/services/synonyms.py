@@ -0,0 +1,79 @@
+"""
+Synonyms - Each project's dictionary of words the offline element matcher treats as equal
+
+A project's dictionary starts with the default English groups and can be
+extended with groups in any language, so "Anmelden" can find a login button
+on a German build. The matcher loads the dictionary when it looks an element
+up. Runs use every language in it, since a locale step can switch the app's
+language partway through a scenario; lookups can also be narrowed to one
+language, where a regional language like pt-BR also uses its base pt groups.
+"""
+import json
+import re
+from typing import List, Optional, Set
+
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Project, SynonymGroup
+from .ui_dump import DEFAULT_SYNONYM_GROUPS
+
+_LANGUAGE_PATTERN = re.compile(r"^[a-z]{2,3}(-[A-Za-z0-9]{2,8})*$")
+
+
+class SynonymError(Exception):
+    """Raised for synonym groups that can't be saved, such as ones with fewer than two terms"""
+
+
+def check_language(language: str) -> None:
+    if not _LANGUAGE_PATTERN.match(language):
+        raise SynonymError(f"Invalid language '{language}', use a code like en or pt-BR")
+
+
+def clean_terms(terms: List[str]) -> List[str]:
+    """Lowercase, trim and de-duplicate a group's terms, keeping their order"""
+    cleaned = list(dict.fromkeys(" ".join(term.lower().split()) for term in terms))
+    cleaned = [term for term in cleaned if term]
+    if len(cleaned) < 2:
+        raise SynonymError("A synonym group needs at least two different terms")
+    return cleaned
+
+
+def _base_language(language: str) -> str:
+    return language.split("-")[0]
+
+
+async def seed_defaults(db: AsyncSession, project: Project) -> int:
+    """Add the default groups the project's dictionary doesn't have yet, without committing"""
+    result = await db.execute(select(SynonymGroup.terms).where(SynonymGroup.project_id == project.id))
+    existing = {frozenset(json.loads(terms)) for terms in result.scalars().all()}
+    added = 0
+    for group in DEFAULT_SYNONYM_GROUPS:
+        if frozenset(group) not in existing:
+            db.add(SynonymGroup(project_id=project.id, language="en", terms=json.dumps(sorted(group))))
+            added += 1
+    project.synonyms_seeded = True
+    return added
+
+
+async def ensure_seeded(db: AsyncSession, project_id: str) -> None:
+    """Seed a project's dictionary the first time it's used, so deleted default groups stay deleted"""
+    project = await db.get(Project, project_id)
+    if project and not project.synonyms_seeded:
+        await seed_defaults(db, project)
+        await db.commit()
+
+
+async def list_groups(db: AsyncSession, project_id: str, language: Optional[str] = None) -> List[SynonymGroup]:
+    """A project's synonym groups, of one language and its base language when given"""
+    await ensure_seeded(db, project_id)
+    query = select(SynonymGroup).where(SynonymGroup.project_id == project_id)
+    if language:
+        query = query.where(SynonymGroup.language.in_({language, _base_language(language)}))
+    result = await db.execute(query.order_by(SynonymGroup.language, SynonymGroup.created_at))
+    return list(result.scalars().all())
+
+
+async def load_synonyms(db: AsyncSession, project_id: str, language: Optional[str] = None) -> List[Set[str]]:
+    """A project's dictionary in the form the offline matcher takes"""
+    return [set(json.loads(group.terms)) for group in await list_groups(db, project_id, language)]
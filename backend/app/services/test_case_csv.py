@@ -0,0 +1,126 @@
+"""
+Test Case CSV - Exports a project's test cases to CSV and imports them back
+
+Exports have a column per test case attribute and per custom field. Imports
+map each CSV header to an attribute or custom field by name, case
+insensitively, unless a mapping names the target for it. Rows update the
+project's test case with the same name, or create one.
+"""
+import csv
+import io
+from typing import Any, Dict, List, Optional
+
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import TestCase
+from .custom_fields import (
+    CUSTOM_PREFIX,
+    CustomFieldError,
+    coerce_values,
+    format_value,
+    get_values,
+    list_fields,
+    set_values,
+)
+
+CSV_COLUMNS = ("name", "description", "category", "priority", "test_type", "status")
+
+
+class CsvImportError(Exception):
+    """Raised for CSV files that can't be read or mapped"""
+
+
+class CsvImportResult(BaseModel):
+    created: int = 0
+    updated: int = 0
+    errors: List[str] = []  # One per skipped row
+
+
+async def export_test_cases_csv(db: AsyncSession, project_id: str) -> str:
+    result = await db.execute(
+        select(TestCase).where(TestCase.project_id == project_id).order_by(TestCase.created_at)
+    )
+    test_cases = result.scalars().all()
+    field_names = [field.name for field in await list_fields(db, project_id)]
+    values = await get_values(db, project_id, "test_case", [test_case.id for test_case in test_cases])
+
+    output = io.StringIO()
+    writer = csv.writer(output)
+    writer.writerow([*CSV_COLUMNS, *field_names])
+    for test_case in test_cases:
+        custom = values[test_case.id]
+        writer.writerow([
+            *(getattr(test_case, column) or "" for column in CSV_COLUMNS),
+            *(format_value(custom[name]) if name in custom else "" for name in field_names),
+        ])
+    return output.getvalue()
+
+
+def _targets(headers: List[str], field_names: List[str], mapping: Dict[str, str]) -> Dict[str, str]:
+    """Each mapped header's target: a column name or custom:<field name>"""
+    known = {column.lower(): column for column in CSV_COLUMNS}
+    known.update({name.lower(): f"{CUSTOM_PREFIX}{name}" for name in field_names})
+    targets = {}
+    for header in headers:
+        target = mapping.get(header, header)
+        if not target:
+            continue  # Mapped to nothing to skip the column
+        resolved = known.get(target.lower()) or known.get(target.lower().removeprefix(CUSTOM_PREFIX))
+        if resolved:
+            targets[header] = resolved
+        elif header in mapping:
+            raise CsvImportError(f"{header} is mapped to {target}, which isn't a column or custom field")
+    if "name" not in targets.values():
+        raise CsvImportError("The CSV needs a name column")
+    return targets
+
+
+async def import_test_cases_csv(
+    db: AsyncSession, project_id: str, content: str, mapping: Optional[Dict[str, str]] = None
+) -> CsvImportResult:
+    """Create or update a project's test cases from CSV rows, skipping rows whose values don't fit"""
+    reader = csv.DictReader(io.StringIO(content.lstrip("\ufeff")))
+    if not reader.fieldnames:
+        raise CsvImportError("The CSV is empty")
+    fields = {field.name: field for field in await list_fields(db, project_id)}
+    targets = _targets(list(reader.fieldnames), list(fields), mapping or {})
+
+    existing_result = await db.execute(select(TestCase).where(TestCase.project_id == project_id))
+    existing = {test_case.name: test_case for test_case in existing_result.scalars().all()}
+    imported = CsvImportResult()
+    for line, row in enumerate(reader, start=2):
+        columns: Dict[str, Any] = {}
+        custom: Dict[str, Any] = {}
+        for header, target in targets.items():
+            cell = (row.get(header) or "").strip()
+            if target.startswith(CUSTOM_PREFIX):
+                custom[target.removeprefix(CUSTOM_PREFIX)] = cell
+            elif cell:
+                columns[target] = cell
+        name = columns.get("name")
+        if not name:
+            imported.errors.append(f"Row {line}: no name")
+            continue
+
+        try:
+            coerce_values(fields, custom)  # Check the row before writing any of it
+        except CustomFieldError as e:
+            imported.errors.append(f"Row {line}: {e}")
+            continue
+
+        test_case = existing.get(name)
+        if test_case:
+            for column, value in columns.items():
+                setattr(test_case, column, value)
+            imported.updated += 1
+        else:
+            test_case = TestCase(project_id=project_id, **columns)
+            db.add(test_case)
+            await db.flush()
+            existing[name] = test_case
+            imported.created += 1
+        await set_values(db, project_id, "test_case", test_case.id, custom)
+    await db.commit()
+    return imported
@@ -10,12 +10,51 @@ from pathlib import Path
 from typing import Optional, Dict, Any, List
 from enum import Enum
 
+from .accessibility import ACCESSIBILITY_STEP_TYPE, axe_cypress_lines, axe_playwright_lines
+from .assertions import cypress_assertion_lines, playwright_assertion_lines, step_assertions
+from .browser_state import BROWSER_STATE_STEP_TYPES, cypress_state_lines, playwright_state_lines
+from .browser_tabs import TAB_STEP_TYPES, cypress_tab_lines, playwright_tab_lines, playwright_tab_prelude
+from .browsers import BrowserError, browser_env, cypress_browser, playwright_browser
+from .file_steps import (
+    FILE_STEP_TYPES,
+    FIND_DOWNLOAD_JS,
+    cypress_file_lines,
+    playwright_download_prelude,
+    playwright_file_lines,
+)
+from .tools import tool_command
+from .waits import WAIT_STEP_TYPES, cypress_wait_lines, playwright_wait_lines
+from .web_context import cypress_context_lines, in_context, playwright_context_lines
+
 
 class TestFramework(str, Enum):
     CYPRESS = "cypress"
     PLAYWRIGHT = "playwright"
 
 
+def _last_png(directory: Path) -> Optional[bytes]:
+    """The newest screenshot a run left in a directory"""
+    found = sorted(directory.rglob("*.png"), key=lambda path: path.stat().st_mtime) if directory.is_dir() else []
+    return found[-1].read_bytes() if found else None
+
+
+def playwright_viewport_options(viewport: Dict[str, Any]) -> List[str]:
+    """Lines for a Playwright config's `use` block that emulate a viewport"""
+    lines = []
+    if viewport.get("device"):
+        lines.append(f"...devices[{json.dumps(viewport['device'])}],")
+    lines.append(f"viewport: {{ width: {int(viewport['width'])}, height: {int(viewport['height'])} }},")
+    if viewport.get("device_scale_factor", 1) != 1:  # Leave a device profile's own scale alone
+        lines.append(f"deviceScaleFactor: {float(viewport['device_scale_factor'])},")
+    if viewport.get("is_mobile"):
+        lines.append("isMobile: true,")
+    if viewport.get("has_touch"):
+        lines.append("hasTouch: true,")
+    if viewport.get("user_agent"):
+        lines.append(f"userAgent: {json.dumps(viewport['user_agent'])},")
+    return lines
+
+
 class TestRunner:
     """Service to run Cypress and Playwright tests"""
 
@@ -28,7 +67,9 @@ class TestRunner:
         base_url: str,
         browser: str = "chrome",
         headless: bool = True,
-        timeout: int = 60000
+        timeout: int = 60000,
+        viewport: Optional[Dict[str, Any]] = None,
+        capture_screenshot: bool = False
     ) -> Dict[str, Any]:
         """
         Run a Cypress test from spec content
@@ -36,10 +77,17 @@ class TestRunner:
         Args:
             spec_content: The Cypress test spec content (JavaScript)
             base_url: The base URL for the test
-            browser: Browser to use (chrome, firefox, electron)
+            browser: Browser to use (chromium, chrome, edge, firefox, electron)
             headless: Run in headless mode
             timeout: Test timeout in ms
+            viewport: Width, height and user agent to emulate
+            capture_screenshot: Return the page as it was when the test ended, as PNG bytes
         """
+        try:
+            browser = cypress_browser(browser)
+        except BrowserError as e:
+            return {"success": False, "error": str(e), "stdout": "", "stderr": "", "exit_code": -1}
+
         # Create temp directory for the test
         with tempfile.TemporaryDirectory() as temp_dir:
             temp_path = Path(temp_dir)
@@ -56,7 +104,10 @@ class TestRunner:
 
             # Write support file
             support_file = cypress_dir / "support" / "e2e.js"
-            support_file.write_text("// Cypress support file\n")
+            support = "// Cypress support file\n"
+            if capture_screenshot:
+                support += "afterEach(() => { cy.screenshot('final', { capture: 'viewport', overwrite: true }); });\n"
+            support_file.write_text(support)
 
             # Write cypress config
             config = {
@@ -69,12 +120,32 @@ class TestRunner:
                     "defaultCommandTimeout": timeout
                 }
             }
+            if viewport:
+                config["e2e"]["viewportWidth"] = int(viewport["width"])
+                config["e2e"]["viewportHeight"] = int(viewport["height"])
+                if viewport.get("user_agent"):
+                    config["e2e"]["userAgent"] = viewport["user_agent"]
+            if capture_screenshot:
+                config["e2e"]["screenshotsFolder"] = str(temp_path / "screenshots")
+            config["e2e"]["downloadsFolder"] = str(temp_path / "downloads")
             config_file = temp_path / "cypress.config.js"
-            config_file.write_text(f"module.exports = {json.dumps(config, indent=2)}")
+            # The log task lets specs print to stdout, which accessibility checks rely on;
+            # findDownload looks for the files verify_download steps expect
+            config_file.write_text(
+                f"const config = {json.dumps(config, indent=2)};\n"
+                f"const findDownload = {FIND_DOWNLOAD_JS};\n"
+                "config.e2e.setupNodeEvents = (on) => {\n"
+                "  on('task', {\n"
+                "    log(message) { console.log(message); return null; },\n"
+                "    findDownload(match) { return findDownload({ ...match, dir: config.e2e.downloadsFolder }); },\n"
+                "  });\n"
+                "};\n"
+                "module.exports = config;\n"
+            )
 
             # Build command
             cmd = [
-                "npx", "cypress", "run",
+                tool_command("npx"), "cypress", "run",
                 "--browser", browser,
                 "--spec", str(spec_file)
             ]
@@ -83,6 +154,8 @@ class TestRunner:
 
             # Run cypress
             result = await self._run_process(cmd, temp_path, timeout // 1000 + 30)
+            if capture_screenshot:
+                result["screenshot"] = _last_png(temp_path / "screenshots")
 
             return result
 
@@ -92,7 +165,9 @@ class TestRunner:
         base_url: str,
         browser: str = "chromium",
         headless: bool = True,
-        timeout: int = 60000
+        timeout: int = 60000,
+        viewport: Optional[Dict[str, Any]] = None,
+        capture_screenshot: bool = False
     ) -> Dict[str, Any]:
         """
         Run a Playwright test from spec content
@@ -100,10 +175,20 @@ class TestRunner:
         Args:
             spec_content: The Playwright test spec content (JavaScript/TypeScript)
             base_url: The base URL for the test
-            browser: Browser to use (chromium, firefox, webkit)
+            browser: Browser to use (chromium, chrome, edge, firefox, safari or webkit)
             headless: Run in headless mode
             timeout: Test timeout in ms
+            viewport: Size and Playwright device profile to emulate
+            capture_screenshot: Return the page as it was when the test ended, as PNG bytes
         """
+        browser_name, channel = playwright_browser(browser)
+        # Chrome and Edge are Chromium channels
+        options = [f"channel: '{channel}'," if channel else ""]
+        if viewport:
+            options += playwright_viewport_options(viewport)
+        if capture_screenshot:
+            options.append("screenshot: 'on',")
+        extra_options = "".join(f"\n    {option}" for option in options if option)
         with tempfile.TemporaryDirectory() as temp_dir:
             temp_path = Path(temp_dir)
 
@@ -113,15 +198,16 @@ class TestRunner:
 
             # Write playwright config
             config_content = f"""
-const {{ defineConfig }} = require('@playwright/test');
+const {{ defineConfig, devices }} = require('@playwright/test');
 
 module.exports = defineConfig({{
   testDir: '.',
+  outputDir: './test-results',
   timeout: {timeout},
   use: {{
     baseURL: '{base_url}',
     headless: {str(headless).lower()},
-    browserName: '{browser}',
+    browserName: '{browser_name}',{extra_options}
   }},
 }});
 """
@@ -129,10 +215,12 @@ module.exports = defineConfig({{
             config_file.write_text(config_content)
 
             # Build command
-            cmd = ["npx", "playwright", "test", str(spec_file)]
+            cmd = [tool_command("npx"), "playwright", "test", str(spec_file)]
 
             # Run playwright
-            result = await self._run_process(cmd, temp_path, timeout // 1000 + 30)
+            result = await self._run_process(cmd, temp_path, timeout // 1000 + 30, browser_env(browser))
+            if capture_screenshot:
+                result["screenshot"] = _last_png(temp_path / "test-results")
 
             return result
 
@@ -141,7 +229,9 @@ module.exports = defineConfig({{
         steps: List[Dict[str, Any]],
         base_url: str,
         browser: str = "chrome",
-        headless: bool = True
+        headless: bool = True,
+        viewport: Optional[Dict[str, Any]] = None,
+        capture_screenshot: bool = False
     ) -> Dict[str, Any]:
         """
         Convert test steps to Cypress spec and run
@@ -151,22 +241,30 @@ module.exports = defineConfig({{
             base_url: The base URL for the test
             browser: Browser to use
             headless: Run in headless mode
+            viewport: Width, height and user agent to emulate
+            capture_screenshot: Return the final page as PNG bytes
         """
         spec_content = self._steps_to_cypress(steps, base_url)
-        return await self.run_cypress(spec_content, base_url, browser, headless)
+        return await self.run_cypress(
+            spec_content, base_url, browser, headless, viewport=viewport, capture_screenshot=capture_screenshot
+        )
 
     async def run_steps_as_playwright(
         self,
         steps: List[Dict[str, Any]],
         base_url: str,
         browser: str = "chromium",
-        headless: bool = True
+        headless: bool = True,
+        viewport: Optional[Dict[str, Any]] = None,
+        capture_screenshot: bool = False
     ) -> Dict[str, Any]:
         """
         Convert test steps to Playwright spec and run
         """
         spec_content = self._steps_to_playwright(steps, base_url)
-        return await self.run_playwright(spec_content, base_url, browser, headless)
+        return await self.run_playwright(
+            spec_content, base_url, browser, headless, viewport=viewport, capture_screenshot=capture_screenshot
+        )
 
     def _steps_to_cypress(self, steps: List[Dict[str, Any]], base_url: str) -> str:
         """Convert test steps to Cypress spec content"""
@@ -179,19 +277,38 @@ module.exports = defineConfig({{
             step_type = step.get("type", "")
             selector = step.get("selector", "")
             value = step.get("value", "")
+            config = step.get("config") or step  # Full step config, from scenario runs
 
-            if step_type == "navigate":
+            if in_context(step_type, config):
+                lines.extend(f"    {line}" for line in cypress_context_lines(step_type, config))
+            elif step_type == "navigate":
                 url = step.get("url", base_url)
                 lines.append(f"    cy.visit('{url}');")
             elif step_type == "click":
                 lines.append(f"    cy.get('{selector}').click();")
             elif step_type == "type":
                 lines.append(f"    cy.get('{selector}').type('{value}');")
+            elif step_type == "verify" and step_assertions(step):
+                for assertion in step_assertions(step):
+                    lines.extend(cypress_assertion_lines(assertion))
             elif step_type == "verify":
                 lines.append(f"    cy.get('{selector}').should('exist');")
             elif step_type == "wait":
                 wait_time = step.get("duration", 1000)
                 lines.append(f"    cy.wait({wait_time});")
+            elif step_type == ACCESSIBILITY_STEP_TYPE:
+                lines.extend(axe_cypress_lines())
+            elif step_type in WAIT_STEP_TYPES:
+                lines.extend(cypress_wait_lines(step))
+            elif step_type in BROWSER_STATE_STEP_TYPES:
+                lines.extend(f"    {line}" for line in cypress_state_lines(step_type, config, base_url))
+            elif step_type in FILE_STEP_TYPES:
+                lines.extend(f"    {line}" for line in cypress_file_lines(step_type, config))
+            elif step_type in TAB_STEP_TYPES:
+                lines.extend(f"    {line}" for line in cypress_tab_lines(step_type, config))
+            if step_type != "verify":
+                for assertion in step.get("assertions") or []:
+                    lines.extend(cypress_assertion_lines(assertion))
 
         lines.extend([
             "  });",
@@ -207,24 +324,47 @@ module.exports = defineConfig({{
             "",
             "test('runs the test steps', async ({ page }) => {"
         ]
+        if any(step.get("type") == "verify_download" for step in steps):
+            lines.extend(f"  {line}" for line in playwright_download_prelude())
+        if any(step.get("type") == "switch_tab" for step in steps):
+            lines.extend(f"  {line}" for line in playwright_tab_prelude())
 
         for step in steps:
             step_type = step.get("type", "")
             selector = step.get("selector", "")
             value = step.get("value", "")
+            config = step.get("config") or step  # Full step config, from scenario runs
 
-            if step_type == "navigate":
+            if in_context(step_type, config):
+                lines.extend(f"  {line}" for line in playwright_context_lines(step_type, config))
+            elif step_type == "navigate":
                 url = step.get("url", base_url)
                 lines.append(f"  await page.goto('{url}');")
             elif step_type == "click":
                 lines.append(f"  await page.click('{selector}');")
             elif step_type == "type":
                 lines.append(f"  await page.fill('{selector}', '{value}');")
+            elif step_type == "verify" and step_assertions(step):
+                for assertion in step_assertions(step):
+                    lines.extend(playwright_assertion_lines(assertion, bool(step.get("soft"))))
             elif step_type == "verify":
                 lines.append(f"  await expect(page.locator('{selector}')).toBeVisible();")
             elif step_type == "wait":
                 wait_time = step.get("duration", 1000)
                 lines.append(f"  await page.waitForTimeout({wait_time});")
+            elif step_type == ACCESSIBILITY_STEP_TYPE:
+                lines.extend(axe_playwright_lines())
+            elif step_type in WAIT_STEP_TYPES:
+                lines.extend(playwright_wait_lines(step))
+            elif step_type in BROWSER_STATE_STEP_TYPES:
+                lines.extend(f"  {line}" for line in playwright_state_lines(step_type, config, base_url))
+            elif step_type in FILE_STEP_TYPES:
+                lines.extend(f"  {line}" for line in playwright_file_lines(step_type, config))
+            elif step_type in TAB_STEP_TYPES:
+                lines.extend(f"  {line}" for line in playwright_tab_lines(step_type, config))
+            if step_type != "verify":
+                for assertion in step.get("assertions") or []:
+                    lines.extend(playwright_assertion_lines(assertion, bool(step.get("soft"))))
 
         lines.append("});")
 
@@ -234,7 +374,8 @@ module.exports = defineConfig({{
         self,
         cmd: List[str],
         cwd: Path,
-        timeout: int
+        timeout: int,
+        env: Optional[Dict[str, str]] = None
     ) -> Dict[str, Any]:
         """Run a subprocess and capture output"""
         try:
@@ -243,7 +384,7 @@ module.exports = defineConfig({{
                 cwd=str(cwd),
                 stdout=asyncio.subprocess.PIPE,
                 stderr=asyncio.subprocess.PIPE,
-                env={**os.environ, "CI": "true"}
+                env={**os.environ, "CI": "true", **(env or {})}
             )
 
             try:
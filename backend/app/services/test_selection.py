@@ -0,0 +1,216 @@
+"""
+Test Selection - Picks the scenarios to run for the changes between two commits
+
+Changed files come from the GitHub compare API when the project's repo_url is
+on GitHub and a token is available, or from git otherwise: directly for a
+local checkout, or from a blobless clone of the remote. Each changed file is
+matched against the project's file to test mappings, entered by hand or
+suggested by AI, and the mapped test cases' scenarios are returned ranked by
+how many changed files they cover and their test case's priority.
+"""
+import fnmatch
+import os
+import re
+import shutil
+import tempfile
+from typing import Dict, List, Optional, Set, Tuple
+
+import httpx
+from pydantic import BaseModel
+from sqlalchemy import select
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..config import settings
+from ..models import FileTestMapping, Project, Scenario, TestCase
+from .ai_client import AiClientError, ask_ai_json
+from .repo_analysis import RepoAnalysisError, run_git
+
+GITHUB_REPO = re.compile(r"github\.com[:/]([^/]+)/([^/]+?)(?:\.git)?/?$")
+
+PRIORITY_WEIGHTS = {"Critical": 4, "High": 3, "Medium": 2, "Low": 1}
+
+# Changed files listed in one AI mapping prompt
+MAX_AI_FILES = 200
+
+
+class TestSelectionError(Exception):
+    """Raised when the diff can't be fetched or AI can't suggest mappings"""
+
+
+class SelectedScenario(BaseModel):
+    """Schema for a scenario picked for a diff"""
+    scenario_id: str
+    scenario_name: str
+    test_case_id: str
+    test_case_name: str
+    score: int
+    matched_files: List[str]
+
+
+class TestSelection(BaseModel):
+    """Schema for the scenarios to run for a diff"""
+    base: str
+    head: str
+    changed_files: List[str]
+    unmapped_files: List[str]  # Changed files no mapping covers
+    scenarios: List[SelectedScenario]
+
+
+# ============================================
+# Diffs
+# ============================================
+
+
+async def _github_changed_files(owner: str, repo: str, base: str, head: str, token: str) -> List[str]:
+    try:
+        async with httpx.AsyncClient(timeout=30.0) as client:
+            response = await client.get(
+                f"https://api.github.com/repos/{owner}/{repo}/compare/{base}...{head}",
+                headers={"Authorization": f"token {token}", "Accept": "application/vnd.github.v3+json"},
+            )
+            response.raise_for_status()
+            data = response.json()
+    except httpx.HTTPError as e:
+        raise TestSelectionError(f"GitHub API error: {str(e)}")
+    return [f["filename"] for f in data.get("files", [])]
+
+
+async def _git_changed_files(repo_url: str, base: str, head: str) -> List[str]:
+    try:
+        if os.path.isdir(repo_url):
+            output = await run_git("-C", repo_url, "diff", "--name-only", f"{base}...{head}")
+        else:
+            workdir = tempfile.mkdtemp(prefix="autotest-diff-")
+            try:
+                # Commits and trees only; file contents aren't needed for the names of changed files
+                await run_git("clone", "--bare", "--filter=blob:none", "--", repo_url, workdir)
+                output = await run_git("-C", workdir, "diff", "--name-only", f"{base}...{head}")
+            finally:
+                shutil.rmtree(workdir, ignore_errors=True)
+    except RepoAnalysisError as e:
+        raise TestSelectionError(str(e))
+    return [line for line in output.splitlines() if line]
+
+
+async def changed_files(repo_url: str, base: str, head: str, github_token: Optional[str] = None) -> List[str]:
+    """Paths changed between two commits, branches or tags of a repository"""
+    if base.startswith("-") or head.startswith("-"):
+        raise TestSelectionError("base and head must be commits, branches or tags")
+    token = github_token or settings.github_token
+    github = GITHUB_REPO.search(repo_url)
+    if github and token:
+        return await _github_changed_files(github.group(1), github.group(2), base, head, token)
+    return await _git_changed_files(repo_url, base, head)
+
+
+# ============================================
+# Matching
+# ============================================
+
+
+def matches(path: str, pattern: str) -> bool:
+    """Whether a path is covered by a mapping: the same file, a file in its directory, or a glob match"""
+    pattern = pattern.strip().lstrip("/")
+    if any(c in pattern for c in "*?["):
+        # fnmatch's * already crosses directories, so ** behaves the same
+        return fnmatch.fnmatchcase(path, pattern.replace("**", "*"))
+    return path == pattern or path.startswith(pattern.rstrip("/") + "/")
+
+
+async def suggest_tests_for_diff(
+    db: AsyncSession, project_id: str, base: str, head: str, github_token: Optional[str] = None
+) -> TestSelection:
+    """The scenarios covering a diff's changed files, highest scoring first"""
+    project = await db.get(Project, project_id)
+    if not project.repo_url:
+        raise TestSelectionError("Set the project's repository URL first")
+    files = await changed_files(project.repo_url, base, head, github_token)
+
+    mappings = (await db.execute(
+        select(FileTestMapping).where(FileTestMapping.project_id == project_id)
+    )).scalars().all()
+    covered: Dict[str, List[str]] = {}
+    unmapped = []
+    for path in files:
+        test_case_ids = {m.test_case_id for m in mappings if matches(path, m.path_pattern)}
+        if not test_case_ids:
+            unmapped.append(path)
+        for test_case_id in test_case_ids:
+            covered.setdefault(test_case_id, []).append(path)
+
+    selected = []
+    if covered:
+        rows = (await db.execute(
+            select(Scenario, TestCase)
+            .join(TestCase, Scenario.test_case_id == TestCase.id)
+            .where(TestCase.id.in_(covered))
+            .order_by(Scenario.position)
+        )).all()
+        for scenario, test_case in rows:
+            matched = covered[test_case.id]
+            selected.append(SelectedScenario(
+                scenario_id=scenario.id,
+                scenario_name=scenario.name,
+                test_case_id=test_case.id,
+                test_case_name=test_case.name,
+                score=len(matched) * PRIORITY_WEIGHTS.get(test_case.priority, 1),
+                matched_files=matched,
+            ))
+    selected.sort(key=lambda s: s.score, reverse=True)
+    return TestSelection(base=base, head=head, changed_files=files, unmapped_files=unmapped, scenarios=selected)
+
+
+# ============================================
+# AI Mappings
+# ============================================
+
+
+async def suggest_mappings(db: AsyncSession, project_id: str, files: List[str]) -> List[FileTestMapping]:
+    """
+    Ask AI which test cases cover each file and save its answers as 'ai' mappings
+
+    Only test cases of the project are accepted, and mappings that already
+    exist are skipped; the new mappings are returned.
+    """
+    test_cases = (await db.execute(select(TestCase).where(TestCase.project_id == project_id))).scalars().all()
+    if not test_cases or not files:
+        return []
+    catalog = "\n".join(
+        f"- {tc.id}: {tc.name}" + (f" ({tc.description})" if tc.description else "") for tc in test_cases
+    )
+    listing = "\n".join(f"- {path}" for path in files[:MAX_AI_FILES])
+    prompt = f"""These are the test cases of an application:
+{catalog}
+
+For each of these source files, list the test cases that exercise the code in it:
+{listing}
+
+Only map a file when it's clearly covered. Return a JSON array:
+[{{"path": "<file from the list>", "test_case_id": "<id from the test cases>"}}]"""
+    try:
+        answer = await ask_ai_json(prompt, "suggest_file_mappings")
+    except AiClientError as e:
+        raise TestSelectionError(str(e))
+
+    known_ids = {tc.id for tc in test_cases}
+    existing: Set[Tuple[str, str]] = {
+        (m.test_case_id, m.path_pattern)
+        for m in (await db.execute(
+            select(FileTestMapping).where(FileTestMapping.project_id == project_id)
+        )).scalars().all()
+    }
+    created = []
+    for item in answer if isinstance(answer, list) else []:
+        if not isinstance(item, dict):
+            continue
+        key = (item.get("test_case_id"), item.get("path"))
+        if key[0] not in known_ids or key[1] not in files or key in existing:
+            continue
+        existing.add(key)
+        mapping = FileTestMapping(project_id=project_id, test_case_id=key[0], path_pattern=key[1], source="ai")
+        db.add(mapping)
+        created.append(mapping)
+    await db.commit()
+    for mapping in created:
+        await db.refresh(mapping)
+    return created
@@ -0,0 +1,148 @@
+"""
+Tools - Finds the command-line tools the app drives, such as adb, xcrun, npx and git
+"""
+import asyncio
+import os
+import platform
+import shutil
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+
+from pydantic import BaseModel
+from sqlalchemy.exc import OperationalError
+
+from ..db import AsyncSessionLocal
+from .app_settings import get_all_settings
+
+VERSION_TIMEOUT = 10.0
+
+
+@dataclass
+class ToolSpec:
+    setting: str  # App setting holding a user-chosen path
+    version_args: List[str]
+    sdk_dirs: List[str] = field(default_factory=list)  # Where the tool lives inside the Android SDK
+    macos_only: bool = False
+
+
+TOOLS: Dict[str, ToolSpec] = {
+    "adb": ToolSpec("tools.adb_path", ["version"], ["platform-tools"]),
+    "emulator": ToolSpec("tools.emulator_path", ["-version"], ["emulator"]),
+    "avdmanager": ToolSpec("tools.avdmanager_path", ["--help"], ["cmdline-tools/latest/bin", "tools/bin"]),
+    "xcrun": ToolSpec("tools.xcrun_path", ["--version"], macos_only=True),
+    "idb": ToolSpec("tools.idb_path", ["--help"], macos_only=True),
+    "node": ToolSpec("tools.node_path", ["--version"]),
+    "npx": ToolSpec("tools.npx_path", ["--version"]),
+    "git": ToolSpec("tools.git_path", ["--version"]),
+}
+
+# Paths chosen in settings, refreshed at startup and whenever settings change
+_overrides: Dict[str, str] = {}
+
+
+class ToolInfo(BaseModel):
+    name: str
+    path: Optional[str] = None
+    source: Optional[str] = None  # 'setting' | 'path' | 'sdk'
+    version: Optional[str] = None
+    error: Optional[str] = None
+
+
+def set_tool_overrides(values: Dict[str, Any]) -> None:
+    for name, spec in TOOLS.items():
+        _overrides[name] = (values.get(spec.setting) or "").strip()
+
+
+async def refresh_tool_overrides() -> None:
+    try:
+        async with AsyncSessionLocal() as db:
+            set_tool_overrides(await get_all_settings(db))
+    except OperationalError:
+        pass  # Database not ready yet; tools are found on PATH until settings can be read
+
+
+def sdk_roots() -> List[Path]:
+    """Android SDK locations: ANDROID_HOME, ANDROID_SDK_ROOT, then each platform's default install"""
+    roots = [os.environ.get(name) for name in ("ANDROID_HOME", "ANDROID_SDK_ROOT")]
+    home = Path.home()
+    defaults = [home / "Library" / "Android" / "sdk", home / "Android" / "Sdk"]
+    if os.environ.get("LOCALAPPDATA"):
+        defaults.append(Path(os.environ["LOCALAPPDATA"]) / "Android" / "Sdk")
+    if platform.system() == "Linux":
+        defaults += [Path("/usr/lib/android-sdk"), Path("/opt/android-sdk")]
+    return [Path(root) for root in roots if root] + defaults
+
+
+def _find_in_sdk(name: str, subdirs: List[str]) -> Optional[str]:
+    suffixes = [".exe", ".bat", ""] if os.name == "nt" else [""]
+    for root in sdk_roots():
+        candidates = [root / subdir for subdir in subdirs]
+        if name == "avdmanager" and (root / "cmdline-tools").is_dir():
+            # Versioned installs such as cmdline-tools/12.0/bin
+            candidates += sorted((root / "cmdline-tools").glob("*/bin"), reverse=True)
+        for directory in candidates:
+            for suffix in suffixes:
+                path = directory / f"{name}{suffix}"
+                if path.is_file():
+                    return str(path)
+    return None
+
+
+def locate_tool(name: str) -> Tuple[Optional[str], Optional[str]]:
+    """Find a tool as (path, source): the path from settings, then PATH, then the Android SDK"""
+    override = _overrides.get(name)
+    if override:
+        path = Path(override).expanduser()
+        return (str(path), "setting") if path.is_file() else (None, None)
+    found = shutil.which(name)
+    if found:
+        return found, "path"
+    spec = TOOLS.get(name)
+    if spec and spec.sdk_dirs:
+        found = _find_in_sdk(name, spec.sdk_dirs)
+        if found:
+            return found, "sdk"
+    return None, None
+
+
+def find_tool(name: str) -> Optional[str]:
+    return locate_tool(name)[0]
+
+
+def tool_command(name: str) -> str:
+    """A tool's path, or its bare name so running it fails with FileNotFoundError as before"""
+    return find_tool(name) or name
+
+
+async def _tool_version(path: str, args: List[str]) -> Optional[str]:
+    try:
+        process = await asyncio.create_subprocess_exec(
+            path, *args, stdout=asyncio.subprocess.PIPE, stderr=asyncio.subprocess.STDOUT
+        )
+        stdout, _ = await asyncio.wait_for(process.communicate(), VERSION_TIMEOUT)
+    except (OSError, asyncio.TimeoutError):
+        return None
+    lines = stdout.decode(errors="replace").strip().splitlines()
+    return lines[0] if lines else None
+
+
+async def detect_tool(name: str) -> ToolInfo:
+    spec = TOOLS[name]
+    if spec.macos_only and platform.system() != "Darwin":
+        return ToolInfo(name=name, error="Only available on macOS")
+    path, source = locate_tool(name)
+    if not path:
+        override = _overrides.get(name)
+        error = f"{spec.setting} points to a missing file: {override}" if override else "Not found"
+        return ToolInfo(name=name, error=error)
+    version = await _tool_version(path, spec.version_args)
+    return ToolInfo(
+        name=name, path=path, source=source, version=version,
+        error=None if version is not None else "Found but could not be run",
+    )
+
+
+async def detect_tools() -> List[ToolInfo]:
+    """Report where each tool was found and its version"""
+    return list(await asyncio.gather(*(detect_tool(name) for name in TOOLS)))
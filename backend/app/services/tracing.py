@@ -0,0 +1,261 @@
+"""
+Tracing Service - Opt-in timing spans for API requests, device commands and AI calls
+
+Spans nest through a context variable, so device and AI spans started while a
+request is handled share its trace. Finished spans are kept in memory for the
+diagnostics view, appended to a rotating JSON lines file in the data directory
+and, when an OTLP endpoint is set, exported to it as OTLP/HTTP JSON.
+"""
+import asyncio
+import logging
+import secrets
+import time
+from collections import deque
+from contextlib import contextmanager
+from contextvars import ContextVar
+from logging.handlers import RotatingFileHandler
+from typing import Any, Deque, Dict, Iterator, List, Optional
+
+import httpx
+from pydantic import BaseModel
+from sqlalchemy.exc import OperationalError
+
+from ..config import settings
+from ..db import AsyncSessionLocal
+from .app_settings import get_all_settings
+
+RECENT_SPANS = 2000
+TRACE_FILE_BACKUPS = 3
+EXPORT_INTERVAL_SECONDS = 5.0
+MAX_EXPORT_BATCH = 512
+
+# OTLP span kinds
+OTLP_KINDS = {"request": 2, "device": 3, "ai": 3}  # server, client, client
+
+
+class TraceSpan(BaseModel):
+    trace_id: str
+    span_id: str
+    parent_id: Optional[str] = None
+    name: str
+    kind: str  # 'request' | 'device' | 'ai'
+    started_at: float  # Unix time in seconds
+    duration_ms: float
+    status: str  # 'ok' | 'error'
+    error: Optional[str] = None
+    attributes: Dict[str, Any] = {}
+
+
+class _ActiveSpan:
+    def __init__(self, trace_id: str, name: str, kind: str, parent_id: Optional[str], attributes: Dict[str, Any]):
+        self.trace_id = trace_id
+        self.span_id = secrets.token_hex(8)
+        self.parent_id = parent_id
+        self.name = name
+        self.kind = kind
+        self.attributes = attributes
+        self.error: Optional[str] = None
+
+    def set(self, **attributes: Any) -> None:
+        self.attributes.update(attributes)
+
+    def fail(self, error: str) -> None:
+        self.error = error
+
+
+_current: ContextVar[Optional[_ActiveSpan]] = ContextVar("trace_span", default=None)
+
+
+class Tracer:
+    """Records spans while tracing is turned on in settings"""
+
+    def __init__(self):
+        self.enabled = False
+        self.otlp_url = ""
+        self.max_file_mb = 10
+        self._recent: Deque[TraceSpan] = deque(maxlen=RECENT_SPANS)
+        self._pending_export: Deque[TraceSpan] = deque(maxlen=RECENT_SPANS)
+        self._file_logger: Optional[logging.Logger] = None
+        self._export_task: Optional[asyncio.Task] = None
+
+    def configure(self, values: Dict[str, Any]) -> None:
+        self.enabled = bool(values.get("tracing.enabled"))
+        self.otlp_url = (values.get("tracing.otlp_url") or "").rstrip("/")
+        max_file_mb = values.get("tracing.max_file_mb") or 10
+        if self._file_logger and max_file_mb != self.max_file_mb:
+            self._close_file()
+        self.max_file_mb = max_file_mb
+        if not self.enabled:
+            self._close_file()
+            self._pending_export.clear()
+
+    @contextmanager
+    def span(self, name: str, kind: str, **attributes: Any) -> Iterator[Optional[_ActiveSpan]]:
+        """Time a block as a span, yielding None when tracing is off"""
+        if not self.enabled:
+            yield None
+            return
+        parent = _current.get()
+        active = _ActiveSpan(
+            parent.trace_id if parent else secrets.token_hex(16), name, kind, parent.span_id if parent else None,
+            attributes,
+        )
+        token = _current.set(active)
+        started_at = time.time()
+        start = time.perf_counter()
+        try:
+            yield active
+        except BaseException as e:
+            active.fail(str(e) or type(e).__name__)
+            raise
+        finally:
+            _current.reset(token)
+            self._record(TraceSpan(
+                trace_id=active.trace_id,
+                span_id=active.span_id,
+                parent_id=active.parent_id,
+                name=active.name,
+                kind=kind,
+                started_at=started_at,
+                duration_ms=round((time.perf_counter() - start) * 1000, 3),
+                status="error" if active.error else "ok",
+                error=active.error,
+                attributes=active.attributes,
+            ))
+
+    def _record(self, span: TraceSpan) -> None:
+        self._recent.append(span)
+        try:
+            self._file().info(span.model_dump_json())
+        except OSError:
+            pass  # The trace file is best effort; spans stay available in memory
+        if self.otlp_url:
+            self._pending_export.append(span)
+
+    def recent(self, limit: int = 200, kind: Optional[str] = None, min_duration_ms: float = 0) -> List[TraceSpan]:
+        """The newest finished spans, newest first"""
+        spans = [
+            span for span in reversed(self._recent)
+            if (kind is None or span.kind == kind) and span.duration_ms >= min_duration_ms
+        ]
+        return spans[:limit]
+
+    def clear(self) -> None:
+        self._recent.clear()
+
+    # ============================================
+    # Trace file
+    # ============================================
+
+    def _file(self) -> logging.Logger:
+        if self._file_logger is None:
+            directory = settings.get_data_dir() / "traces"
+            directory.mkdir(parents=True, exist_ok=True)
+            handler = RotatingFileHandler(
+                directory / "traces.jsonl",
+                maxBytes=self.max_file_mb * 1024 * 1024,
+                backupCount=TRACE_FILE_BACKUPS,
+                encoding="utf-8",
+            )
+            handler.setFormatter(logging.Formatter("%(message)s"))
+            logger = logging.getLogger("autotest.traces")
+            logger.setLevel(logging.INFO)
+            logger.propagate = False
+            logger.addHandler(handler)
+            self._file_logger = logger
+        return self._file_logger
+
+    def _close_file(self) -> None:
+        if self._file_logger:
+            for handler in list(self._file_logger.handlers):
+                self._file_logger.removeHandler(handler)
+                handler.close()
+            self._file_logger = None
+
+    # ============================================
+    # OTLP export
+    # ============================================
+
+    def start(self) -> None:
+        if self._export_task is None:
+            self._export_task = asyncio.create_task(self._export_loop())
+
+    async def stop(self) -> None:
+        if self._export_task:
+            self._export_task.cancel()
+            try:
+                await self._export_task
+            except asyncio.CancelledError:
+                pass
+            self._export_task = None
+        await self._export()
+        self._close_file()
+
+    async def _export_loop(self) -> None:
+        while True:
+            await asyncio.sleep(EXPORT_INTERVAL_SECONDS)
+            await self._export()
+
+    async def _export(self) -> None:
+        if not self.otlp_url or not self._pending_export:
+            return
+        batch = [self._pending_export.popleft() for _ in range(min(MAX_EXPORT_BATCH, len(self._pending_export)))]
+        try:
+            async with httpx.AsyncClient(timeout=10.0) as client:
+                response = await client.post(f"{self.otlp_url}/v1/traces", json=otlp_payload(batch))
+                response.raise_for_status()
+        except httpx.HTTPError as e:
+            # Dropped rather than retried, so a missing collector can't grow memory
+            print(f"Trace export to {self.otlp_url} failed: {e}")
+
+
+def _otlp_value(value: Any) -> Dict[str, Any]:
+    if isinstance(value, bool):
+        return {"boolValue": value}
+    if isinstance(value, int):
+        return {"intValue": str(value)}
+    if isinstance(value, float):
+        return {"doubleValue": value}
+    return {"stringValue": str(value)}
+
+
+def otlp_payload(spans: List[TraceSpan]) -> Dict[str, Any]:
+    """Spans as an OTLP/HTTP JSON export request"""
+    otlp_spans = []
+    for span in spans:
+        start = int(span.started_at * 1e9)
+        otlp_span = {
+            "traceId": span.trace_id,
+            "spanId": span.span_id,
+            "name": span.name,
+            "kind": OTLP_KINDS.get(span.kind, 1),
+            "startTimeUnixNano": str(start),
+            "endTimeUnixNano": str(start + int(span.duration_ms * 1e6)),
+            "attributes": [
+                {"key": key, "value": _otlp_value(value)}
+                for key, value in {"autotest.kind": span.kind, **span.attributes}.items() if value is not None
+            ],
+            "status": {"code": 2, "message": span.error} if span.error else {"code": 1},
+        }
+        if span.parent_id:
+            otlp_span["parentSpanId"] = span.parent_id
+        otlp_spans.append(otlp_span)
+    return {"resourceSpans": [{
+        "resource": {"attributes": [
+            {"key": "service.name", "value": {"stringValue": "autotest-backend"}},
+            {"key": "service.version", "value": {"stringValue": settings.app_version}},
+        ]},
+        "scopeSpans": [{"scope": {"name": "autotest.tracing"}, "spans": otlp_spans}],
+    }]}
+
+
+async def refresh_tracing() -> None:
+    try:
+        async with AsyncSessionLocal() as db:
+            tracer.configure(await get_all_settings(db))
+    except OperationalError:
+        pass  # Database not ready yet; tracing stays off until settings can be read
+
+
+# Singleton instance
+tracer = Tracer()
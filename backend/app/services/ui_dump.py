@@ -0,0 +1,255 @@
+"""
+UI Dump Service - Parses Android uiautomator dumps and locates elements
+"""
+import hashlib
+import re
+import xml.etree.ElementTree as ET
+from dataclasses import dataclass, field
+from difflib import SequenceMatcher
+from typing import Any, Dict, List, Optional, Set, Tuple
+
+
+_BOUNDS_PATTERN = re.compile(r"\[(\d+),(\d+)\]\[(\d+),(\d+)\]")
+
+
+@dataclass
+class UiNode:
+    """A single node from a uiautomator hierarchy dump"""
+
+    resource_id: str
+    text: str
+    content_desc: str
+    class_name: str
+    clickable: bool
+    left: int
+    top: int
+    right: int
+    bottom: int
+    # Every attribute from the dump, such as checked, enabled and package
+    attributes: Dict[str, str] = field(default_factory=dict)
+
+    @property
+    def center(self) -> tuple:
+        return ((self.left + self.right) // 2, (self.top + self.bottom) // 2)
+
+
+def parse_ui_dump(xml: str) -> List[UiNode]:
+    """Parse a uiautomator XML dump into a flat list of nodes"""
+    # `adb shell cat` can prepend status lines before the XML document
+    start = xml.find("<?xml")
+    if start == -1:
+        start = xml.find("<hierarchy")
+    if start == -1:
+        return []
+
+    try:
+        root = ET.fromstring(xml[start:])
+    except ET.ParseError:
+        return []
+
+    nodes = []
+    for element in root.iter("node"):
+        match = _BOUNDS_PATTERN.match(element.get("bounds", ""))
+        if not match:
+            continue
+        left, top, right, bottom = (int(v) for v in match.groups())
+        nodes.append(
+            UiNode(
+                resource_id=element.get("resource-id", ""),
+                text=element.get("text", ""),
+                content_desc=element.get("content-desc", ""),
+                class_name=element.get("class", ""),
+                clickable=element.get("clickable") == "true",
+                left=left,
+                top=top,
+                right=right,
+                bottom=bottom,
+                attributes=dict(element.attrib),
+            )
+        )
+    return nodes
+
+
+def layout_fingerprint(nodes: List[UiNode]) -> str:
+    """Identify a screen by its layout only, so typed text doesn't make it look new"""
+    layout = "|".join(f"{node.class_name}#{node.resource_id}" for node in nodes)
+    return hashlib.sha256(layout.encode()).hexdigest()
+
+
+def find_node_by_selector(nodes: List[UiNode], selector: str) -> Optional[UiNode]:
+    """
+    Find a node by an exact selector
+
+    The selector may be a full resource-id, the id part after ":id/",
+    the node text, or the content description.
+    """
+    for node in nodes:
+        if node.resource_id and (
+            node.resource_id == selector or node.resource_id.endswith(f":id/{selector}")
+        ):
+            return node
+    for node in nodes:
+        if selector in (node.text, node.content_desc):
+            return node
+    return None
+
+
+# Step config keys that describe an element by its UI hierarchy attributes
+ELEMENT_DESCRIPTOR_KEYS = ("resource_id", "text", "content_desc")
+
+
+def element_descriptor(config: Dict[str, Any]) -> Dict[str, str]:
+    """The descriptor fields set in a step config"""
+    return {key: config[key] for key in (*ELEMENT_DESCRIPTOR_KEYS, "class_name") if config.get(key)}
+
+
+def find_node_by_descriptor(nodes: List[UiNode], descriptor: Dict[str, Any], index: int = 0) -> Optional[UiNode]:
+    """
+    Find the node matching every field of an element descriptor
+
+    resource_id may omit the package, as in find_node_by_selector. When several
+    nodes match, index picks one in hierarchy order.
+    """
+    matches = []
+    for node in nodes:
+        resource_id = descriptor.get("resource_id")
+        if resource_id and not (
+            node.resource_id == resource_id or node.resource_id.endswith(f":id/{resource_id}")
+        ):
+            continue
+        if any(
+            descriptor.get(key) and getattr(node, key) != descriptor[key]
+            for key in ("text", "content_desc", "class_name")
+        ):
+            continue
+        matches.append(node)
+    return matches[index] if 0 <= index < len(matches) else None
+
+
+def node_at(nodes: List[UiNode], x: int, y: int) -> Optional[UiNode]:
+    """The deepest node whose bounds contain a point: the smallest, and the later in hierarchy order on ties"""
+    containing = [
+        node for node in nodes if node.left <= x < node.right and node.top <= y < node.bottom
+    ]
+    if not containing:
+        return None
+    return min(reversed(containing), key=lambda node: (node.right - node.left) * (node.bottom - node.top))
+
+
+def suggest_locator(nodes: List[UiNode], node: UiNode) -> Dict[str, Any]:
+    """
+    The fewest element descriptor fields that find a node again
+
+    A resource id, text or content description that only this node has is
+    enough on its own; otherwise every field is used, with the index among
+    the nodes that share them.
+    """
+    for key in ELEMENT_DESCRIPTOR_KEYS:
+        value = getattr(node, key)
+        if value and find_node_by_descriptor(nodes, {key: value}, 1) is None:
+            return {key: value}
+
+    locator: Dict[str, Any] = {
+        key: getattr(node, key) for key in (*ELEMENT_DESCRIPTOR_KEYS, "class_name") if getattr(node, key)
+    }
+    index = 0
+    while (match := find_node_by_descriptor(nodes, locator, index)) is not None and match is not node:
+        index += 1
+    if index:
+        locator["index"] = index
+    return locator
+
+
+# Common English UI vocabulary, seeded into each project's synonym dictionary
+DEFAULT_SYNONYM_GROUPS = [
+    {"login", "log in", "sign in", "signin", "logon"},
+    {"logout", "log out", "sign out", "signout"},
+    {"signup", "sign up", "register", "create account"},
+    {"submit", "send", "confirm", "ok", "done", "continue", "next"},
+    {"cancel", "close", "dismiss", "back"},
+    {"search", "find", "lookup"},
+    {"settings", "preferences", "options", "gear"},
+    {"menu", "hamburger", "navigation", "drawer"},
+    {"cart", "basket", "bag"},
+    {"checkout", "pay", "purchase", "buy"},
+    {"add", "plus", "new", "create"},
+    {"delete", "remove", "trash"},
+    {"edit", "modify", "change"},
+    {"save", "apply", "update"},
+    {"profile", "account", "user", "avatar"},
+    {"home", "main", "dashboard"},
+    {"email", "e-mail", "mail"},
+    {"password", "passcode", "pin"},
+    {"username", "user name", "login id"},
+]
+
+_STOP_WORDS = {"the", "a", "an", "button", "field", "input", "icon", "link", "tab", "on", "to", "of"}
+
+
+def get_synonyms(term: str, groups: Optional[List[Set[str]]] = None) -> Set[str]:
+    """Get all synonyms of a term in the given groups, or the default groups, including the term itself"""
+    term = term.lower()
+    synonyms = {term}
+    for group in DEFAULT_SYNONYM_GROUPS if groups is None else groups:
+        if term in group:
+            synonyms |= group
+    return synonyms
+
+
+def _normalize(value: str) -> str:
+    value = value.split(":id/")[-1]
+    value = re.sub(r"([a-z])([A-Z])", r"\1 \2", value)
+    return re.sub(r"[_\-./]+", " ", value).lower().strip()
+
+
+def _node_labels(node: UiNode) -> List[str]:
+    return [_normalize(v) for v in (node.text, node.content_desc, node.resource_id) if v]
+
+
+def _contains_phrase(label: str, phrase: str) -> bool:
+    return re.search(rf"\b{re.escape(phrase)}\b", label) is not None
+
+
+def _score_label(description: str, terms: List[str], label: str, groups: Optional[List[Set[str]]]) -> float:
+    if label == description:
+        return 1.0
+    if description in label or label in description:
+        return 0.9
+
+    if terms and all(
+        any(_contains_phrase(label, synonym) for synonym in get_synonyms(term, groups)) for term in terms
+    ):
+        return 0.85
+
+    return SequenceMatcher(None, description, label).ratio() * 0.8
+
+
+def find_element_from_ui_dump(
+    nodes: List[UiNode],
+    description: str,
+    min_score: float = 0.6,
+    synonyms: Optional[List[Set[str]]] = None,
+) -> Optional[Tuple[UiNode, float]]:
+    """
+    Find the node best matching a natural-language element description
+
+    Matches against text, content description, and resource-id using exact,
+    synonym, and fuzzy comparisons. Clickable nodes win ties. Synonyms come
+    from the given groups, such as a project's dictionary, or the defaults.
+    """
+    normalized = _normalize(description)
+    terms = [word for word in normalized.split() if word not in _STOP_WORDS]
+    if terms:
+        normalized = " ".join(terms)
+
+    best: Optional[Tuple[UiNode, float]] = None
+    for node in nodes:
+        labels = _node_labels(node)
+        if not labels:
+            continue
+        score = max(_score_label(normalized, terms, label, synonyms) for label in labels)
+        if node.clickable:
+            score = min(score + 0.05, 1.0)
+        if score >= min_score and (not best or score > best[1]):
+            best = (node, score)
+    return best
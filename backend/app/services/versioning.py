@@ -0,0 +1,45 @@
+"""
+Versioning - Optimistic concurrency for edits to test cases, scenarios and steps
+
+An update may carry the updated_at of the copy it was based on. The row is
+claimed with a conditional UPDATE on that timestamp before the edit is
+applied, so of two edits based on the same copy only the first is written;
+the second gets a conflict and the current copy to merge with.
+"""
+from datetime import datetime, timezone
+from typing import Any, Optional
+
+from sqlalchemy import update
+from sqlalchemy.ext.asyncio import AsyncSession
+
+
+class StaleWriteError(Exception):
+    """Raised when a row changed after the copy an edit was based on"""
+
+
+def _naive_utc(value: datetime) -> datetime:
+    """Timestamps are stored as naive UTC, so aware ones from clients are converted"""
+    if value.tzinfo:
+        return value.astimezone(timezone.utc).replace(tzinfo=None)
+    return value
+
+
+async def claim_version(db: AsyncSession, row: Any, expected_updated_at: Optional[datetime]) -> None:
+    """
+    Bump a row's updated_at if it's still at expected_updated_at, without committing
+
+    Raises StaleWriteError, with the row refreshed to its current copy, when
+    it has changed since. Edits with no expected_updated_at always apply.
+    """
+    if expected_updated_at is None:
+        return
+    model = type(row)
+    result = await db.execute(
+        update(model)
+        .where(model.id == row.id, model.updated_at == _naive_utc(expected_updated_at))
+        .values(updated_at=datetime.utcnow())
+        .execution_options(synchronize_session=False)
+    )
+    if result.rowcount != 1:
+        await db.refresh(row)
+        raise StaleWriteError(f"{model.__name__} {row.id} changed since {expected_updated_at.isoformat()}")
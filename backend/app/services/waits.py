@@ -0,0 +1,104 @@
+"""
+Wait Strategies - Polls for screen conditions instead of sleeping for fixed times
+"""
+import asyncio
+import json
+import re
+import time
+from typing import Any, Awaitable, Callable, Dict, List, Optional
+
+WAIT_STEP_TYPES = (
+    "wait_for_element",
+    "wait_for_element_gone",
+    "wait_for_text",
+    "wait_for_activity",
+    "wait_for_url",
+    "wait_for_network_idle",
+)
+
+DEFAULT_WAIT_TIMEOUT_MS = 10000
+POLL_INTERVAL = 0.5
+
+# Network counts as idle once this little traffic moves over idle_ms
+DEFAULT_IDLE_MS = 1000
+IDLE_BYTES_THRESHOLD = 2048
+
+
+def wait_timeout_ms(config: Dict[str, Any]) -> int:
+    return int(config.get("timeout") or DEFAULT_WAIT_TIMEOUT_MS)
+
+
+async def poll_until(
+    check: Callable[[], Awaitable[bool]], timeout_ms: int, interval: float = POLL_INTERVAL
+) -> bool:
+    """Run check until it returns True or the timeout passes; the check always runs at least once"""
+    deadline = time.monotonic() + timeout_ms / 1000
+    while True:
+        if await check():
+            return True
+        if time.monotonic() + interval > deadline:
+            return False
+        await asyncio.sleep(interval)
+
+
+def resumed_activity(dumpsys_output: str) -> Optional[str]:
+    """The foreground activity from `dumpsys activity activities`, as package/.Activity"""
+    match = re.search(r"(?:mResumedActivity|topResumedActivity|ResumedActivity)[:=].*?\s(\S+/\S+)", dumpsys_output)
+    return match.group(1).rstrip("}") if match else None
+
+
+def network_bytes(proc_net_dev: str) -> int:
+    """Total bytes received and sent on every interface except loopback, from /proc/net/dev"""
+    total = 0
+    for line in proc_net_dev.splitlines():
+        if ":" not in line:
+            continue
+        name, counters = line.split(":", 1)
+        fields = counters.split()
+        if name.strip() == "lo" or len(fields) < 9 or not fields[0].isdigit():
+            continue
+        total += int(fields[0]) + int(fields[8])
+    return total
+
+
+# ============================================
+# Browser Specs
+# ============================================
+
+def playwright_wait_lines(step: Dict[str, Any]) -> List[str]:
+    step_type = step.get("type")
+    timeout = int(step.get("duration") or DEFAULT_WAIT_TIMEOUT_MS)
+    selector = json.dumps(step.get("selector") or "")
+    if step_type == "wait_for_element":
+        return [f"  await page.locator({selector}).first().waitFor({{ state: 'visible', timeout: {timeout} }});"]
+    if step_type == "wait_for_element_gone":
+        return [f"  await page.locator({selector}).first().waitFor({{ state: 'hidden', timeout: {timeout} }});"]
+    if step_type == "wait_for_text":
+        text = json.dumps(step.get("text") or step.get("value") or "")
+        return [f"  await page.getByText({text}).first().waitFor({{ state: 'visible', timeout: {timeout} }});"]
+    if step_type == "wait_for_url":
+        url = json.dumps(step.get("url") or "")
+        return [f"  await page.waitForURL((url) => url.href.includes({url}), {{ timeout: {timeout} }});"]
+    if step_type == "wait_for_network_idle":
+        return [f"  await page.waitForLoadState('networkidle', {{ timeout: {timeout} }});"]
+    return [f"  // {step_type} isn't available in browser runs"]
+
+
+def cypress_wait_lines(step: Dict[str, Any]) -> List[str]:
+    step_type = step.get("type")
+    timeout = int(step.get("duration") or DEFAULT_WAIT_TIMEOUT_MS)
+    selector = json.dumps(step.get("selector") or "")
+    if step_type == "wait_for_element":
+        return [f"    cy.get({selector}, {{ timeout: {timeout} }}).should('be.visible');"]
+    if step_type == "wait_for_element_gone":
+        return [f"    cy.get({selector}, {{ timeout: {timeout} }}).should('not.exist');"]
+    if step_type == "wait_for_text":
+        text = json.dumps(step.get("text") or step.get("value") or "")
+        return [f"    cy.contains({text}, {{ timeout: {timeout} }}).should('be.visible');"]
+    if step_type == "wait_for_url":
+        url = json.dumps(step.get("url") or "")
+        return [f"    cy.url({{ timeout: {timeout} }}).should('include', {url});"]
+    if step_type == "wait_for_network_idle":
+        # Cypress has no network idle signal, so wait for the page to finish loading
+        return [f"    cy.document({{ timeout: {timeout} }}).its('readyState').should('eq', 'complete');"]
+    return [f"    // {step_type} isn't available in browser runs"]
@@ -0,0 +1,106 @@
+"""
+Web Context - Locates web elements inside iframes and shadow roots
+
+A step's frame_path lists the selectors of the iframes to enter, outermost
+first, and its shadow_path the shadow hosts to pierce inside the innermost
+frame; the step's selector is then matched in that context. Cypress can only
+enter same-origin frames unless the browser runs with chromeWebSecurity off.
+"""
+import json
+from typing import Any, Dict, List
+
+from .assertions import step_assertions
+from .file_steps import check_file_step
+
+FRAME_KEY = "frame_path"
+SHADOW_KEY = "shadow_path"
+
+# Steps that act on one element and so can look for it in a frame or shadow root
+CONTEXT_STEP_TYPES = ("click", "type", "verify", "wait_for_element", "wait_for_element_gone", "upload_file")
+
+DEFAULT_WAIT_TIMEOUT_MS = 10000
+
+
+def _path(config: Dict[str, Any], key: str) -> List[str]:
+    value = config.get(key) or []
+    return [value] if isinstance(value, str) else [selector for selector in value if selector]
+
+
+def frame_path(config: Dict[str, Any]) -> List[str]:
+    return _path(config, FRAME_KEY)
+
+
+def shadow_path(config: Dict[str, Any]) -> List[str]:
+    return _path(config, SHADOW_KEY)
+
+
+def in_context(step_type: str, config: Dict[str, Any]) -> bool:
+    """Whether a step's element is inside a frame or shadow root"""
+    if step_type not in CONTEXT_STEP_TYPES or not config.get("selector"):
+        return False
+    if step_type == "verify" and step_assertions(config):
+        return False  # Assertions name their own targets
+    return bool(frame_path(config) or shadow_path(config))
+
+
+# ============================================
+# Browser Specs
+# ============================================
+
+
+def playwright_locator(config: Dict[str, Any]) -> str:
+    """A Locator expression; Playwright's CSS engine pierces open shadow roots, so hosts are chained"""
+    expression = "page" + "".join(f".frameLocator({json.dumps(frame)})" for frame in frame_path(config))
+    for host in shadow_path(config):
+        expression += f".locator({json.dumps(host)})"
+    return expression + f".locator({json.dumps(config['selector'])})"
+
+
+def cypress_chain(config: Dict[str, Any], options: str = "") -> str:
+    """A Cypress chain that enters each frame's body and each shadow root before finding the element"""
+    chain = ""
+    for frame in frame_path(config):
+        chain += f"cy.get({json.dumps(frame)})" if not chain else f".find({json.dumps(frame)})"
+        chain += ".its('0.contentDocument.body').should('not.be.empty').then(cy.wrap)"
+    for host in shadow_path(config):
+        chain += f"cy.get({json.dumps(host)})" if not chain else f".find({json.dumps(host)})"
+        chain += ".shadow()"
+    options = f", {options}" if options else ""
+    return chain + f".find({json.dumps(config['selector'])}{options})"
+
+
+def _timeout(config: Dict[str, Any]) -> int:
+    return int(config.get("timeout") or config.get("duration") or DEFAULT_WAIT_TIMEOUT_MS)
+
+
+def playwright_context_lines(step_type: str, config: Dict[str, Any]) -> List[str]:
+    locator = playwright_locator(config)
+    if step_type == "click":
+        return [f"await {locator}.click();"]
+    if step_type == "type":
+        return [f"await {locator}.fill({json.dumps(str(config.get('value') or ''))});"]
+    if step_type == "verify":
+        return [f"await expect({locator}).toBeVisible();"]
+    if step_type in ("wait_for_element", "wait_for_element_gone"):
+        state = "visible" if step_type == "wait_for_element" else "hidden"
+        return [f"await {locator}.first().waitFor({{ state: '{state}', timeout: {_timeout(config)} }});"]
+    if step_type == "upload_file":
+        files = json.dumps([str(path) for path in check_file_step(step_type, config)])
+        return [f"await {locator}.setInputFiles({files});"]
+    return [f"// {step_type} can't run inside a frame or shadow root"]
+
+
+def cypress_context_lines(step_type: str, config: Dict[str, Any]) -> List[str]:
+    if step_type == "click":
+        return [f"{cypress_chain(config)}.click();"]
+    if step_type == "type":
+        return [f"{cypress_chain(config)}.type({json.dumps(str(config.get('value') or ''))});"]
+    if step_type == "verify":
+        return [f"{cypress_chain(config)}.should('exist');"]
+    if step_type in ("wait_for_element", "wait_for_element_gone"):
+        assertion = "be.visible" if step_type == "wait_for_element" else "not.exist"
+        return [f"{cypress_chain(config, f'{{ timeout: {_timeout(config)} }}')}.should('{assertion}');"]
+    if step_type == "upload_file":
+        files = json.dumps([str(path) for path in check_file_step(step_type, config)])
+        return [f"{cypress_chain(config)}.selectFile({files}, {{ force: true }});"]
+    return [f"// {step_type} can't run inside a frame or shadow root"]
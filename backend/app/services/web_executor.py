@@ -0,0 +1,293 @@
+"""
+Web Executor - Runs web scenarios in a browser through Cypress or Playwright
+"""
+import asyncio
+import json
+import time
+from typing import Any, Dict, List, Optional, Tuple
+
+from sqlalchemy.ext.asyncio import AsyncSession
+
+from ..models import Project, Scenario, Step, StepResult, TestCase, Viewport
+from .accessibility import ACCESSIBILITY_STEP_TYPE, evaluate_violations, failure_message, parse_axe_output
+from .app_settings import get_all_settings
+from .attachments import AttachmentError, store_attachment
+from .browser_state import BrowserStateError
+from .browser_tabs import TAB_STEP_TYPES, BrowserTabError, parse_tab_output
+from .browsers import BrowserError, ensure_browser, resolve_browser, select_viewports
+from .elements import apply_element, load_elements
+from .executor import ScenarioExecutor
+from .file_steps import FileStepError
+from .screenshots import compress, media_type, should_capture
+from .shell_steps import SHELL_STEP_TYPE
+from .test_runner import TestFramework, test_runner
+
+# Trailing output kept in a failed step's error message
+MAX_ERROR_OUTPUT = 2000
+
+
+def runner_step(step_type: str, config: Dict[str, Any]) -> Dict[str, Any]:
+    """A step in the shape the spec generators expect"""
+    return {
+        "type": step_type,
+        "selector": config.get("selector"),
+        "value": config.get("value"),
+        "url": config.get("url"),
+        "duration": config.get("timeout") or config.get("duration"),
+        "expected": config.get("expected"),
+        "operator": config.get("operator"),
+        "assertions": config.get("assertions"),
+        "soft": config.get("soft"),
+        "text": config.get("text"),
+        "config": config,
+    }
+
+
+def judge_spec(
+    result: Dict[str, Any], steps: List[Tuple[str, Dict[str, Any]]]
+) -> List[Tuple[str, Optional[str], Optional[Dict[str, Any]]]]:
+    """
+    Judge each step of a finished spec, as (status, error, details)
+
+    A failed spec fails its first step and skips the rest. Accessibility checks
+    don't stop a spec, so they're judged here from its output. Tab steps record
+    the tab they left active.
+    """
+    if not result.get("success"):
+        output = result.get("error") or result.get("stderr") or result.get("stdout") or "Spec failed"
+        failure = ("failed", f"Scenario spec failed: {output[-MAX_ERROR_OUTPUT:]}", None)
+        return [failure] + [("skipped", "Skipped: scenario spec failed", None)] * (len(steps) - 1)
+
+    checks = iter(parse_axe_output(result.get("stdout") or ""))
+    tabs = iter(parse_tab_output(result.get("stdout") or ""))
+    judged = []
+    for step_type, config in steps:
+        if step_type in TAB_STEP_TYPES:
+            tab = next(tabs, None)
+            judged.append(("passed", None, {"tab": tab} if tab else None))
+            continue
+        if step_type != ACCESSIBILITY_STEP_TYPE:
+            judged.append(("passed", None, None))
+            continue
+        passed, details = evaluate_violations(next(checks, []), config)
+        judged.append(("passed" if passed else "failed", None if passed else failure_message(details), details))
+    return judged
+
+
+class WebScenarioExecutor(ScenarioExecutor):
+    """
+    Executes each scenario as one generated browser spec
+
+    The spec passes or fails as a whole, so a failure is recorded on the first
+    step and the remaining steps are recorded as skipped. Leading and trailing
+    shell steps run on the host before and after the spec. Without a browser
+    or headless flag, each scenario uses its project's defaults.
+
+    With viewports, the spec runs once per project viewport and each run's
+    results and final screenshot are tagged with the viewport's name.
+    """
+
+    log_source = "browser"
+
+    def __init__(
+        self,
+        framework: TestFramework,
+        browser: Optional[str] = None,
+        headless: Optional[bool] = None,
+        viewports: Optional[List[str]] = None,
+    ):
+        super().__init__(f"browser:{browser or 'default'}", "web", self_heal=False)
+        self.framework = framework
+        self.browser = browser
+        self.headless = headless
+        self.viewports = viewports
+
+    async def _run_steps(self, db: AsyncSession, scenario: Scenario, test_run_id: str) -> tuple:
+        steps = await self._load_steps(db, scenario.id)
+        if not steps:
+            return 0, 0, 0
+        test_case = await db.get(TestCase, scenario.test_case_id)
+        project = await db.get(Project, test_case.project_id)
+        elements = await load_elements(db, project.id)
+        self.test_run_id = test_run_id
+        self.env = json.loads(project.env_vars or "{}")
+
+        # Shell steps at either end run as setup and teardown hooks around the spec
+        start = 0
+        while start < len(steps) and steps[start].step_type == SHELL_STEP_TYPE:
+            start += 1
+        end = len(steps)
+        while end > start and steps[end - 1].step_type == SHELL_STEP_TYPE:
+            end -= 1
+        pre_hooks, body, post_hooks = steps[:start], steps[start:end], steps[end:]
+
+        passed = failed = skipped = 0
+        for step in pre_hooks:
+            if failed:
+                db.add(self._result(step, scenario, test_run_id, "skipped", 0, "Skipped: setup step failed"))
+                skipped += 1
+            elif await self._run_hook(db, scenario, test_run_id, step):
+                passed += 1
+            else:
+                failed += 1
+
+        if failed:
+            for step in body:
+                db.add(self._result(step, scenario, test_run_id, "skipped", 0, "Skipped: setup step failed"))
+            skipped += len(body)
+        elif body:
+            counts = await self._run_spec(db, scenario, test_run_id, project, body, elements)
+            passed, failed, skipped = passed + counts[0], failed + counts[1], skipped + counts[2]
+
+        # Teardown hooks always run so environments get cleaned up after failures
+        for step in post_hooks:
+            if await self._run_hook(db, scenario, test_run_id, step):
+                passed += 1
+            else:
+                failed += 1
+        await db.commit()
+        return passed, failed, skipped
+
+    async def _run_hook(self, db: AsyncSession, scenario: Scenario, test_run_id: str, step: Step) -> bool:
+        config = json.loads(step.config or "{}")
+        await self._log(db, test_run_id, "info", f"Shell step: {config.get('command', '')}")
+        outcome = await self.execute_step(SHELL_STEP_TYPE, config)
+        if outcome.status != "passed":
+            await self._log(db, test_run_id, "error", outcome.error_message or "Shell step failed")
+        db.add(self._result(step, scenario, test_run_id, outcome.status, outcome.duration_ms, outcome.error_message))
+        await db.commit()
+        return outcome.status == "passed"
+
+    async def _run_spec(
+        self,
+        db: AsyncSession,
+        scenario: Scenario,
+        test_run_id: str,
+        project: Project,
+        steps: List[Step],
+        elements: Dict[str, Any],
+    ) -> tuple:
+        shell_step = next((step for step in steps if step.step_type == SHELL_STEP_TYPE), None)
+        if shell_step:
+            error = "Shell steps can only run before or after a scenario's browser steps"
+            await self._log(db, test_run_id, "error", error)
+            for step in steps:
+                if step is shell_step:
+                    db.add(self._result(step, scenario, test_run_id, "failed", 0, error))
+                else:
+                    db.add(self._result(step, scenario, test_run_id, "skipped", 0, f"Skipped: {error.lower()}"))
+            await db.commit()
+            return 0, 1, len(steps) - 1
+
+        configs = [apply_element(json.loads(step.config or "{}"), elements, "web") for step in steps]
+        runner_steps = [runner_step(step.step_type, config) for step, config in zip(steps, configs)]
+        base_url = scenario.target_url or project.app_url
+        browser, headless = resolve_browser(project, self.browser, self.headless)
+        values = await get_all_settings(db)
+        policy = scenario.screenshot_policy or values["executor.screenshot_policy"]
+        try:
+            viewports = select_viewports(project, self.viewports)
+            await ensure_browser(browser)
+        except BrowserError as e:
+            failure = {"success": False, "error": str(e)}
+            return await self._record_spec(db, scenario, test_run_id, steps, configs, failure)
+
+        totals = (0, 0, 0)
+        for viewport in viewports:
+            size = f" at {viewport.name} ({viewport.width}x{viewport.height})" if viewport else ""
+            await self._log(
+                db, test_run_id, "info", f"Running {len(steps)} steps with {self.framework.value} in {browser}{size}"
+            )
+            options = {"viewport": viewport.model_dump()} if viewport else {}
+            # Whether an on_failure screenshot is kept is only known once the spec has run
+            options["capture_screenshot"] = policy != "never"
+            started = time.time()
+            try:
+                if self.framework == TestFramework.CYPRESS:
+                    run_steps = test_runner.run_steps_as_cypress
+                else:
+                    run_steps = test_runner.run_steps_as_playwright
+                result = await run_steps(runner_steps, base_url, browser, headless, **options)
+            except (BrowserError, BrowserStateError, BrowserTabError, FileStepError) as e:
+                result = {"success": False, "error": str(e)}
+            # Spread the spec's duration evenly since per-step timings aren't reported
+            duration_ms = int((time.time() - started) * 1000) // len(steps)
+            counts = await self._record_spec(
+                db, scenario, test_run_id, steps, configs, result, duration_ms, viewport, policy, values
+            )
+            totals = tuple(total + count for total, count in zip(totals, counts))
+        return totals
+
+    async def _record_spec(
+        self,
+        db: AsyncSession,
+        scenario: Scenario,
+        test_run_id: str,
+        steps: List[Step],
+        configs: List[Dict[str, Any]],
+        result: Dict[str, Any],
+        duration_ms: int = 0,
+        viewport: Optional[Viewport] = None,
+        policy: str = "never",
+        values: Optional[Dict[str, Any]] = None,
+    ) -> tuple:
+        """Record the step results of one spec run, attaching its final screenshot to the last step"""
+        viewport_name = viewport.name if viewport else None
+        if not result.get("success"):
+            output = result.get("error") or result.get("stderr") or result.get("stdout") or "Spec failed"
+            await self._log(db, test_run_id, "error", output[-MAX_ERROR_OUTPUT:], 0)
+            if result.get("stdout"):
+                await self._log(db, test_run_id, "debug", result["stdout"][-MAX_ERROR_OUTPUT:])
+
+        counts = {"passed": 0, "failed": 0, "skipped": 0}
+        judged = judge_spec(result, [(step.step_type, config) for step, config in zip(steps, configs)])
+        step_result = None
+        for step, (status, error, details) in zip(steps, judged):
+            counts[status] += 1
+            if error and result.get("success"):
+                await self._log(db, test_run_id, "error", error)
+            step_duration = 0 if status == "skipped" else duration_ms
+            step_result = self._result(
+                step, scenario, test_run_id, status, step_duration, error, details, viewport_name
+            )
+            db.add(step_result)
+        await db.commit()
+
+        spec_status = "failed" if counts["failed"] else "passed"
+        if result.get("screenshot") and step_result and should_capture(policy, spec_status):
+            try:
+                data, extension = await asyncio.to_thread(compress, result["screenshot"], values)
+                filename = f"{scenario.name}-{viewport_name or 'default'}.{extension}"
+                await store_attachment(
+                    db, test_run_id, filename, media_type(filename), data,
+                    kind="screenshot", step_result_id=step_result.id,
+                )
+                step_result.screenshot_bytes = len(data)
+                step_result.screenshot_original_bytes = len(result["screenshot"])
+                await db.commit()
+            except (AttachmentError, OSError) as e:
+                await self._log(db, test_run_id, "warning", f"Couldn't attach the {viewport_name} screenshot: {e}")
+        return counts["passed"], counts["failed"], counts["skipped"]
+
+    def _result(
+        self,
+        step: Step,
+        scenario: Scenario,
+        test_run_id: str,
+        status: str,
+        duration_ms: int,
+        error: str = None,
+        details: Dict[str, Any] = None,
+        viewport: Optional[str] = None,
+    ) -> StepResult:
+        return StepResult(
+            test_run_id=test_run_id,
+            step_id=step.id,
+            test_case_id=scenario.test_case_id,
+            status=status,
+            duration_ms=duration_ms,
+            error_message=error,
+            details=json.dumps(details) if details else None,
+            viewport=viewport,
+        )
+
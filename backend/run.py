@@ -1,10 +1,60 @@
 #!/usr/bin/env python3
 """Run the AutoTest AI backend server"""
 
+import argparse
+import asyncio
+import json
+import sys
+import warnings
+from pathlib import Path
+
 import uvicorn
 from app.config import settings
 
+
+def migrate() -> int:
+    """Apply pending schema migrations and exit, without starting the server"""
+    import app.models  # noqa: F401 - registers the tables to create and check
+    from app.db.database import migrate_only
+    from app.db.migrations import SchemaDriftError, latest_version
+
+    try:
+        applied = asyncio.run(migrate_only())
+    except SchemaDriftError as e:
+        print(e, file=sys.stderr)
+        return 1
+    for migration in applied:
+        print(f"Applied migration {migration.version}: {migration.description}")
+    print(f"Database schema is at version {latest_version()}")
+    return 0
+
+
+def export_openapi(path: str) -> int:
+    """Write the API's OpenAPI schema to path, failing on duplicate operation ids that clients would collide on"""
+    from app.main import app
+
+    with warnings.catch_warnings():
+        warnings.filterwarnings("error", message="Duplicate Operation ID")
+        try:
+            schema = app.openapi()
+        except UserWarning as e:
+            print(e, file=sys.stderr)
+            return 1
+    Path(path).write_text(json.dumps(schema, indent=2, sort_keys=True) + "\n", encoding="utf-8")
+    print(f"Wrote the OpenAPI schema to {path}")
+    return 0
+
+
 if __name__ == "__main__":
+    parser = argparse.ArgumentParser(description="Run the AutoTest AI backend server")
+    parser.add_argument("--migrate-only", action="store_true", help="apply schema migrations and exit")
+    parser.add_argument("--export-openapi", metavar="PATH", help="write the OpenAPI schema to PATH and exit")
+    args = parser.parse_args()
+    if args.migrate_only:
+        sys.exit(migrate())
+    if args.export_openapi:
+        sys.exit(export_openapi(args.export_openapi))
+
     uvicorn.run(
         "app.main:app",
         host=settings.host,
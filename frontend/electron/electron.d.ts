@@ -1,4 +1,16 @@
 // Type definitions for Electron API exposed to renderer
+interface QuickActionResult {
+  message: string;
+  route?: string | null;
+  open_path?: string | null;
+  data?: unknown;
+}
+
+interface ApiToken {
+  enabled: boolean;
+  token: string;
+}
+
 interface ElectronAPI {
   getAppInfo: () => Promise<{
     name: string;
@@ -8,6 +20,17 @@ interface ElectronAPI {
   }>;
   getBackendUrl: () => Promise<string>;
   openExternal: (url: string) => Promise<void>;
+  openRunWindow: (runId: string) => Promise<string>;
+  openWindow: (windowId: string, route: string, title?: string) => Promise<string>;
+  getWindowId: () => Promise<string>;
+  refreshShortcuts: () => Promise<{ registered: number; failed: string[] }>;
+  onQuickActionResult: (
+    callback: (message: { actionId: string; result?: QuickActionResult; error?: string }) => void
+  ) => () => void;
+  getControlToken: () => Promise<ApiToken>;
+  rotateControlToken: () => Promise<ApiToken>;
+  getAgentToken: () => Promise<ApiToken>;
+  rotateAgentToken: () => Promise<ApiToken>;
   platform: string;
   arch: string;
   isElectron: boolean;
@@ -1,18 +1,26 @@
-const { app, BrowserWindow, ipcMain, shell } = require('electron');
+const { app, BrowserWindow, globalShortcut, ipcMain, shell } = require('electron');
 const path = require('path');
 const { spawn, execSync } = require('child_process');
 const http = require('http');
 const fs = require('fs');
+const crypto = require('crypto');
 
 // Keep a global reference of the window object
 let mainWindow = null;
 let pythonProcess = null;
 
+// Secondary windows, such as detached run viewers, by window id
+const secondaryWindows = new Map();
+
 // Detect dev mode: check if running from source (not packaged)
 const isDev = !app.isPackaged;
 const BACKEND_PORT = 8000;
 const BACKEND_URL = `http://127.0.0.1:${BACKEND_PORT}`;
 
+// Passed to the backend at launch and sent with main process requests, which lets only
+// this process read the control API token; the renderer gets the token through IPC
+const MAIN_PROCESS_SECRET = crypto.randomBytes(32).toString('hex');
+
 /**
  * Wait for the backend server to be ready
  */
@@ -131,6 +139,7 @@ function startBackend() {
         ...process.env,
         PYTHONUNBUFFERED: '1',
         PATH: process.env.PATH,
+        MAIN_PROCESS_SECRET,
       },
     });
 
@@ -216,6 +225,124 @@ function createWindow() {
   });
 }
 
+/**
+ * Load an app route into a window; the window id is passed so the page can target its events
+ */
+function loadRoute(window, route, windowId) {
+  if (isDev) {
+    const separator = route.includes('?') ? '&' : '?';
+    window.loadURL(`http://localhost:3000${route}${separator}window=${encodeURIComponent(windowId)}`);
+  } else {
+    window.loadFile(path.join(__dirname, '..', 'out', 'index.html'), {
+      query: { route, window: windowId },
+    });
+  }
+}
+
+/**
+ * Open a secondary window on an app route, focusing it instead when it's already open
+ */
+function openSecondaryWindow(windowId, route, title) {
+  const existing = secondaryWindows.get(windowId);
+  if (existing) {
+    existing.focus();
+    return windowId;
+  }
+
+  const window = new BrowserWindow({
+    width: 1000,
+    height: 760,
+    minWidth: 640,
+    minHeight: 480,
+    title,
+    webPreferences: {
+      nodeIntegration: false,
+      contextIsolation: true,
+      preload: path.join(__dirname, 'preload.js'),
+    },
+    show: false,
+  });
+  secondaryWindows.set(windowId, window);
+  loadRoute(window, route, windowId);
+
+  window.once('ready-to-show', () => {
+    window.show();
+  });
+  window.webContents.setWindowOpenHandler(({ url }) => {
+    shell.openExternal(url);
+    return { action: 'deny' };
+  });
+  window.on('closed', () => {
+    secondaryWindows.delete(windowId);
+  });
+  return windowId;
+}
+
+/**
+ * Call the backend's JSON API from the main process
+ */
+function requestBackend(method, endpoint, body) {
+  return new Promise((resolve, reject) => {
+    const payload = body === undefined ? undefined : JSON.stringify(body);
+    const headers = { 'X-Main-Process-Secret': MAIN_PROCESS_SECRET };
+    if (payload) {
+      headers['Content-Type'] = 'application/json';
+      headers['Content-Length'] = Buffer.byteLength(payload);
+    }
+    const req = http.request(`${BACKEND_URL}/api${endpoint}`, { method, headers }, (res) => {
+      let data = '';
+      res.on('data', (chunk) => { data += chunk; });
+      res.on('end', () => {
+        const parsed = data ? JSON.parse(data) : null;
+        if (res.statusCode >= 400) {
+          reject(new Error((parsed && parsed.detail) || `Request failed with ${res.statusCode}`));
+        } else {
+          resolve(parsed);
+        }
+      });
+    });
+    req.on('error', reject);
+    if (payload) {
+      req.write(payload);
+    }
+    req.end();
+  });
+}
+
+/**
+ * Run a quick action and hand its result to the main window, which shows its route or message
+ */
+async function runQuickAction(actionId) {
+  let message;
+  try {
+    message = { actionId, result: await requestBackend('POST', `/quick-actions/${actionId}`, {}) };
+  } catch (error) {
+    message = { actionId, error: error.message };
+  }
+  if (mainWindow) {
+    if (message.result && message.result.route) {
+      mainWindow.show();
+      mainWindow.focus();
+    }
+    mainWindow.webContents.send('quick-action-result', message);
+  }
+}
+
+/**
+ * Register the backend's global quick action shortcuts, replacing any registered before
+ */
+async function registerShortcuts() {
+  globalShortcut.unregisterAll();
+  const shortcuts = await requestBackend('GET', '/quick-actions/shortcuts');
+  const failed = [];
+  for (const [actionId, accelerator] of Object.entries(shortcuts)) {
+    if (!globalShortcut.register(accelerator, () => runQuickAction(actionId))) {
+      failed.push(accelerator);  // Taken by another app
+    }
+  }
+  return { registered: Object.keys(shortcuts).length - failed.length, failed };
+}
+
 // IPC handlers
 ipcMain.handle('get-app-info', () => {
   return {
@@ -234,6 +361,46 @@ ipcMain.handle('open-external', (_, url) => {
   shell.openExternal(url);
 });
 
+ipcMain.handle('open-run-window', (_, runId) => {
+  return openSecondaryWindow(`run-${runId}`, `/runs/${encodeURIComponent(runId)}`, 'Test Run');
+});
+
+ipcMain.handle('open-window', (_, windowId, route, title) => {
+  if (!route.startsWith('/')) {
+    throw new Error('Window routes must start with /');
+  }
+  return openSecondaryWindow(windowId, route, title || 'AutoTest AI');
+});
+
+ipcMain.handle('refresh-shortcuts', () => {
+  return registerShortcuts();
+});
+
+ipcMain.handle('get-control-token', () => {
+  return requestBackend('GET', '/control/token');
+});
+
+ipcMain.handle('rotate-control-token', () => {
+  return requestBackend('POST', '/control/token/rotate');
+});
+
+ipcMain.handle('get-agent-token', () => {
+  return requestBackend('GET', '/agents/token');
+});
+
+ipcMain.handle('rotate-agent-token', () => {
+  return requestBackend('POST', '/agents/token/rotate');
+});
+
+ipcMain.handle('get-window-id', (event) => {
+  for (const [windowId, window] of secondaryWindows) {
+    if (window.webContents === event.sender) {
+      return windowId;
+    }
+  }
+  return 'main';
+});
+
 // App lifecycle
 app.whenReady().then(async () => {
   try {
@@ -244,6 +411,9 @@ app.whenReady().then(async () => {
 
     // Create window
     createWindow();
+    registerShortcuts().catch((error) => {
+      console.error('Failed to register shortcuts:', error);
+    });
 
     app.on('activate', () => {
       if (BrowserWindow.getAllWindows().length === 0) {
@@ -262,6 +432,10 @@ app.on('window-all-closed', () => {
   }
 });
 
+app.on('will-quit', () => {
+  globalShortcut.unregisterAll();
+});
+
 app.on('before-quit', () => {
   stopBackend();
 });
@@ -11,6 +11,27 @@ contextBridge.exposeInMainWorld('electronAPI', {
   // Open external URL
   openExternal: (url) => ipcRenderer.invoke('open-external', url),
 
+  // Secondary windows
+  openRunWindow: (runId) => ipcRenderer.invoke('open-run-window', runId),
+  openWindow: (windowId, route, title) => ipcRenderer.invoke('open-window', windowId, route, title),
+  getWindowId: () => ipcRenderer.invoke('get-window-id'),
+
+  // Global quick action shortcuts
+  refreshShortcuts: () => ipcRenderer.invoke('refresh-shortcuts'),
+  onQuickActionResult: (callback) => {
+    const listener = (_, message) => callback(message);
+    ipcRenderer.on('quick-action-result', listener);
+    return () => ipcRenderer.removeListener('quick-action-result', listener);
+  },
+
+  // Control API token for external tools, which only the main process can read
+  getControlToken: () => ipcRenderer.invoke('get-control-token'),
+  rotateControlToken: () => ipcRenderer.invoke('rotate-control-token'),
+
+  // Token runner agents register with, also only readable by the main process
+  getAgentToken: () => ipcRenderer.invoke('get-agent-token'),
+  rotateAgentToken: () => ipcRenderer.invoke('rotate-agent-token'),
+
   // Platform info
   platform: process.platform,
   arch: process.arch,
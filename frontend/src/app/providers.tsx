@@ -1,11 +1,46 @@
 'use client';
 
 import { QueryClient, QueryClientProvider } from '@tanstack/react-query';
-import { useState, type ReactNode } from 'react';
+import { useEffect, useState, type ReactNode } from 'react';
+import { useRouter } from 'next/navigation';
 import { ProjectProvider } from '@/contexts/project-context';
-import { ToastProvider, ErrorBoundary } from '@/components/ui';
+import { ToastProvider, ErrorBoundary, useToast } from '@/components/ui';
+import { quickActionApi } from '@/lib/api';
+
+// Shows what global shortcuts did: the page they opened, or their message
+function QuickActionResults() {
+  const router = useRouter();
+  const { success, error: showError } = useToast();
+
+  useEffect(() => {
+    if (!window.electronAPI) return;
+    return window.electronAPI.onQuickActionResult(({ result, error }) => {
+      if (error || !result) {
+        showError(error || 'Quick action failed');
+        return;
+      }
+      if (result.route) router.push(result.route);
+      if (result.open_path) quickActionApi.openPath(result.open_path);
+      success(result.message);
+    });
+  }, [router, success, showError]);
+
+  return null;
+}
 
 export function Providers({ children }: { children: ReactNode }) {
+  const router = useRouter();
+
+  // Packaged secondary windows load the app's index with the route to show
+  useEffect(() => {
+    const params = new URLSearchParams(window.location.search);
+    const route = params.get('route');
+    if (route?.startsWith('/')) {
+      const windowId = params.get('window');
+      router.replace(windowId ? `${route}?window=${encodeURIComponent(windowId)}` : route);
+    }
+  }, [router]);
+
   const [queryClient] = useState(
     () =>
       new QueryClient({
@@ -26,7 +61,10 @@ export function Providers({ children }: { children: ReactNode }) {
     <ErrorBoundary>
       <QueryClientProvider client={queryClient}>
         <ProjectProvider>
-          <ToastProvider>{children}</ToastProvider>
+          <ToastProvider>
+            <QuickActionResults />
+            {children}
+          </ToastProvider>
         </ProjectProvider>
       </QueryClientProvider>
     </ErrorBoundary>
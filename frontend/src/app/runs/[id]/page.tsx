@@ -17,10 +17,11 @@ import {
   faImage,
   faVideo,
   faFileAlt,
+  faUpRightFromSquare,
 } from '@fortawesome/free-solid-svg-icons';
 import { cn } from '@/lib/utils';
 import { useProject } from '@/contexts/project-context';
-import { executionsApi, ExecutionDetails } from '@/lib/api';
+import { executionsApi, ExecutionDetails, openRunWindow, subscribeEvents } from '@/lib/api';
 
 interface RunDetails {
   id: string;
@@ -159,6 +160,15 @@ export default function RunDetailsPage() {
     return () => clearInterval(interval);
   }, [currentProject, router, fetchRunDetails, run?.status]);
 
+  // Events about this run refresh it right away; its logs are left to polling
+  useEffect(() => {
+    return subscribeEvents({ testRunId: id }, (message) => {
+      if (message.payload?.test_run_id === id && message.event !== 'run:log') {
+        fetchRunDetails();
+      }
+    });
+  }, [id, fetchRunDetails]);
+
   const handleRerun = async () => {
     if (!run) return;
     setIsRerunning(true);
@@ -279,20 +289,30 @@ export default function RunDetailsPage() {
                 <p className="text-sm text-text-secondary font-mono">{run.id}</p>
               </div>
             </div>
-            <button
-              onClick={handleRerun}
-              disabled={isRerunning || run.status === 'running' || run.status === 'queued'}
-              className={cn(
-                'px-4 py-2 rounded-lg bg-primary text-white',
-                'hover:bg-primary-dark transition-colors',
-                'flex items-center gap-2',
-                (isRerunning || run.status === 'running' || run.status === 'queued') &&
-                  'opacity-50 cursor-not-allowed'
-              )}
-            >
-              <FontAwesomeIcon icon={faRedo} className={cn(isRerunning && 'animate-spin')} />
-              Re-run Test
-            </button>
+            <div className="flex items-center gap-2">
+              <button
+                onClick={() => openRunWindow(run.id)}
+                className="px-4 py-2 rounded-lg border border-border text-text-secondary hover:text-text-primary transition-colors flex items-center gap-2"
+                title="Watch this run in its own window"
+              >
+                <FontAwesomeIcon icon={faUpRightFromSquare} />
+                Open in Window
+              </button>
+              <button
+                onClick={handleRerun}
+                disabled={isRerunning || run.status === 'running' || run.status === 'queued'}
+                className={cn(
+                  'px-4 py-2 rounded-lg bg-primary text-white',
+                  'hover:bg-primary-dark transition-colors',
+                  'flex items-center gap-2',
+                  (isRerunning || run.status === 'running' || run.status === 'queued') &&
+                    'opacity-50 cursor-not-allowed'
+                )}
+              >
+                <FontAwesomeIcon icon={faRedo} className={cn(isRerunning && 'animate-spin')} />
+                Re-run Test
+              </button>
+            </div>
           </div>
         </div>
 
@@ -29,11 +29,25 @@ import {
 } from '@fortawesome/free-solid-svg-icons';
 import { useProject } from '@/contexts/project-context';
 import MobilePreview from '@/components/mobile-preview';
-import { mobileApi, aiWebApi, AiWebSuggestedStep, AiWebAnalysisResult, AiWebElementLocation } from '@/lib/api';
+import {
+  mobileApi,
+  aiWebApi,
+  draftApi,
+  scenarioApi,
+  stepApi,
+  AiWebSuggestedStep,
+  AiWebAnalysisResult,
+  AiWebElementLocation,
+} from '@/lib/api';
+import { serializePageHtml } from '@/lib/page-html';
+import type { StepWithConfig } from '@/types';
 
 // Storage key for test runs (same as runs page)
 const RUNS_STORAGE_KEY = 'test-runs-history';
 
+// How long edits settle before they're autosaved as a draft
+const DRAFT_AUTOSAVE_MS = 3000;
+
 // Device types for preview
 type DeviceType = 'desktop' | 'tablet' | 'mobile';
 
@@ -59,6 +73,9 @@ interface TestStep {
     value?: string;
     timeout?: number;
     expected?: string;
+    // Web: iframes to enter and shadow hosts to pierce before matching the selector
+    frame_path?: string[];
+    shadow_path?: string[];
     // Mobile-specific
     x?: number;
     y?: number;
@@ -203,6 +220,15 @@ const getMobileInitialSteps = (packageName: string): TestStep[] => {
   ];
 };
 
+// A step saved on the scenario, as the builder edits it
+const toTestStep = (step: StepWithConfig): TestStep => ({
+  id: step.id,
+  type: step.step_type as StepType,
+  label: step.label,
+  config: step.config as TestStep['config'],
+  status: 'pending',
+});
+
 export default function ScenarioBuilderPage() {
   const router = useRouter();
   const params = useParams();
@@ -255,6 +281,16 @@ export default function ScenarioBuilderPage() {
   const bridgeReadyRef = useRef(false);
   const hasInitialized = useRef(false);
 
+  // Draft autosave: the scenario's updated_at the edits started from
+  const [draftReady, setDraftReady] = useState(false);
+  const draftBaseRef = useRef<string | null>(null);
+  // Only the user's own edits are autosaved, not the template or steps loaded on open
+  const [hasEdits, setHasEdits] = useState(false);
+  // Steps came from the scenario or a draft, so the template mustn't replace them
+  const stepsRestoredRef = useRef(false);
+  const savedStepsRef = useRef<TestStep[]>([]);
+  const [saving, setSaving] = useState(false);
+
   // AI Suggestion state
   const [showAiModal, setShowAiModal] = useState(false);
   const [aiLoading, setAiLoading] = useState(false);
@@ -276,6 +312,12 @@ export default function ScenarioBuilderPage() {
   const [showWebAiModal, setShowWebAiModal] = useState(false);
   const [findingSelector, setFindingSelector] = useState(false);
 
+  // Apply a user edit to the steps; run status updates go through setSteps directly
+  const editSteps = (update: (prev: TestStep[]) => TestStep[]) => {
+    setSteps(update);
+    setHasEdits(true);
+  };
+
   // Show toast notification
   const showToast = useCallback((message: string, type: 'success' | 'error' | 'info' = 'info') => {
     setToast({ message, type });
@@ -343,7 +385,7 @@ export default function ScenarioBuilderPage() {
       // For now, we'll use the iframe's document HTML if accessible
       let pageHtml: string | undefined;
       try {
-        pageHtml = iframe.contentDocument?.documentElement?.outerHTML;
+        pageHtml = serializePageHtml(iframe.contentDocument);
       } catch {
         // Cross-origin restriction - HTML not accessible
         addLog('Note: Page HTML not accessible due to cross-origin restrictions');
@@ -419,7 +461,7 @@ export default function ScenarioBuilderPage() {
 
       let pageHtml: string | undefined;
       try {
-        pageHtml = iframe.contentDocument?.documentElement?.outerHTML;
+        pageHtml = serializePageHtml(iframe.contentDocument);
       } catch {
         // Cross-origin restriction
       }
@@ -445,8 +487,10 @@ export default function ScenarioBuilderPage() {
       );
 
       if (result.found && result.confidence > 0.5) {
-        // Update step selector
+        // Update step selector, along with the frames and shadow roots it sits in
         updateStepConfig(stepId, 'selector', result.selector);
+        updateStepConfig(stepId, 'frame_path', result.frame_path ?? []);
+        updateStepConfig(stepId, 'shadow_path', result.shadow_path ?? []);
         addLog(`AI found element: ${result.selector} - ${result.description}`);
         showToast(`Selector updated: ${result.selector}`, 'success');
       } else {
@@ -477,7 +521,7 @@ export default function ScenarioBuilderPage() {
       },
       status: 'pending',
     };
-    setSteps(prev => [...prev, newStep]);
+    editSteps(prev => [...prev, newStep]);
     setActiveStepId(newStep.id);
     addLog(`Added AI suggested step: ${suggestion.label}`);
   };
@@ -514,13 +558,14 @@ export default function ScenarioBuilderPage() {
     setExecutionLogs(prev => [...prev, `[${timestamp}] ${message}`]);
   };
 
+  const templateSteps = () => (isMobileMode ? getMobileInitialSteps(packageName) : getInitialSteps(appBaseUrl));
+
   // Initialize steps only once when the component mounts or when project changes
   useEffect(() => {
+    if (stepsRestoredRef.current) return;
     if (!hasInitialized.current || appBaseUrl !== '') {
-      if (isMobileMode) {
-        setSteps(getMobileInitialSteps(packageName));
-      } else {
-        setSteps(getInitialSteps(appBaseUrl));
+      setSteps(templateSteps());
+      if (!isMobileMode) {
         setPreviewUrl(getProxyUrl(testCaseInfo.defaultUrl));
       }
       setActiveStepId('step-1');
@@ -528,6 +573,112 @@ export default function ScenarioBuilderPage() {
     }
   }, [appBaseUrl, testCaseInfo.defaultUrl, isMobileMode, packageName]);
 
+  // Load the scenario's saved steps, or edits autosaved before a crash or reload, warning when the scenario
+  // was saved elsewhere since
+  useEffect(() => {
+    let cancelled = false;
+    (async () => {
+      const scenario = await scenarioApi.get(scenarioId);
+      const [saved, draft] = scenario
+        ? await Promise.all([
+            stepApi.listByScenario(scenarioId).catch(() => []),
+            draftApi.get<{ steps: TestStep[] }>('scenario', scenarioId),
+          ])
+        : [[], null];
+      if (cancelled) return;
+      draftBaseRef.current = scenario?.updated_at ?? null;
+      savedStepsRef.current = saved.map(toTestStep);
+      const restored = draft?.content.steps?.length
+        ? draft.content.steps.map((step) => ({ ...step, status: 'pending' as const }))
+        : savedStepsRef.current;
+      if (restored.length) {
+        stepsRestoredRef.current = true;
+        setSteps(restored);
+        setActiveStepId(restored[0].id);
+      }
+      if (draft?.content.steps?.length) {
+        setHasEdits(true);
+        if (draft.conflict) {
+          showToast('Restored unsaved edits, but this scenario was changed elsewhere since', 'error');
+        } else {
+          showToast('Restored unsaved edits', 'info');
+        }
+      }
+      setDraftReady(Boolean(scenario));
+    })();
+    return () => {
+      cancelled = true;
+    };
+  }, [scenarioId, showToast]);
+
+  // Autosave edits once they settle
+  useEffect(() => {
+    if (!draftReady || !hasEdits) return;
+    const timer = setTimeout(() => {
+      draftApi.save('scenario', scenarioId, { steps }, draftBaseRef.current).catch((e) => {
+        console.error('Failed to autosave draft:', e);
+      });
+    }, DRAFT_AUTOSAVE_MS);
+    return () => clearTimeout(timer);
+  }, [steps, draftReady, hasEdits, scenarioId]);
+
+  // The edits are saved or abandoned, so their draft is no longer needed; there's none if no autosave ran yet
+  const discardDraft = () => {
+    setHasEdits(false);
+    draftApi.discard('scenario', scenarioId).catch(() => undefined);
+  };
+
+  // Save the steps to the scenario, updating the ones it already has so their results are kept
+  const handleSave = async () => {
+    setSaving(true);
+    try {
+      const saved = await stepApi.listByScenario(scenarioId);
+      const savedIds = new Set(saved.map((step) => step.id));
+      const keptIds = new Set(steps.map((step) => step.id));
+      const removed = saved.filter((step) => !keptIds.has(step.id)).map((step) => step.id);
+      if (removed.length) {
+        await stepApi.bulkDelete(removed);
+      }
+
+      const fields = steps.map((step, index) => ({
+        step_order: index + 1,
+        step_type: step.type,
+        label: step.label,
+        config: step.config,
+      }));
+      await Promise.all(steps.map((step, index) =>
+        savedIds.has(step.id) ? stepApi.update(step.id, fields[index]) : null
+      ));
+      await stepApi.bulkCreate(steps.flatMap((step, index) =>
+        savedIds.has(step.id) ? [] : [{ scenario_id: scenarioId, ...fields[index] }]
+      ));
+
+      // New steps get their ids from the backend
+      const persisted = (await stepApi.listByScenario(scenarioId)).map(toTestStep);
+      const activeIndex = steps.findIndex((step) => step.id === activeStepId);
+      savedStepsRef.current = persisted;
+      stepsRestoredRef.current = true;
+      setSteps(persisted);
+      setActiveStepId(persisted[Math.max(activeIndex, 0)]?.id ?? '');
+      discardDraft();
+      showToast('Scenario saved', 'success');
+    } catch (error) {
+      const errorMsg = error instanceof Error ? error.message : String(error);
+      showToast(`Failed to save scenario: ${errorMsg}`, 'error');
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  // Drop unsaved edits, going back to the saved steps or the template
+  const handleRevert = () => {
+    const reverted = savedStepsRef.current.length ? savedStepsRef.current : templateSteps();
+    setSteps(reverted);
+    setActiveStepId(reverted[0]?.id ?? '');
+    discardDraft();
+    addLog('Reverted unsaved edits');
+  };
+
   useEffect(() => {
     const handleMessage = (event: MessageEvent) => {
       if (event.data?.type === 'AUTOTEST_BRIDGE_READY') {
@@ -548,14 +699,14 @@ export default function ScenarioBuilderPage() {
 
   const activeStep = steps.find(s => s.id === activeStepId);
 
-  const updateStepConfig = (stepId: string, field: string, value: string | number) => {
-    setSteps(prev => prev.map(step =>
+  const updateStepConfig = (stepId: string, field: string, value: string | number | string[]) => {
+    editSteps(prev => prev.map(step =>
       step.id === stepId ? { ...step, config: { ...step.config, [field]: value } } : step
     ));
   };
 
   const updateStepLabel = (stepId: string, label: string) => {
-    setSteps(prev => prev.map(step => step.id === stepId ? { ...step, label } : step));
+    editSteps(prev => prev.map(step => step.id === stepId ? { ...step, label } : step));
   };
 
   const addStep = (type: StepType) => {
@@ -592,7 +743,7 @@ export default function ScenarioBuilderPage() {
       config: configs[type] || {},
       status: 'pending',
     };
-    setSteps(prev => [...prev, newStep]);
+    editSteps(prev => [...prev, newStep]);
     setActiveStepId(newStep.id);
   };
 
@@ -674,7 +825,7 @@ export default function ScenarioBuilderPage() {
       },
       status: 'pending',
     };
-    setSteps(prev => [...prev, newStep]);
+    editSteps(prev => [...prev, newStep]);
     setActiveStepId(newStep.id);
     addLog(`➕ Added AI suggested step: ${suggestion.label}`);
   };
@@ -691,7 +842,7 @@ export default function ScenarioBuilderPage() {
   };
 
   const deleteStep = (stepId: string) => {
-    setSteps(prev => prev.filter(s => s.id !== stepId));
+    editSteps(prev => prev.filter(s => s.id !== stepId));
     if (activeStepId === stepId && steps.length > 1) {
       setActiveStepId(steps[0].id);
     }
@@ -1212,7 +1363,15 @@ export default function ScenarioBuilderPage() {
             <FontAwesomeIcon icon={isRunning ? faSpinner : faPlay} className={`mr-2 text-sm ${isRunning ? 'animate-spin' : ''}`} />
             {isRunning ? 'Running...' : 'Run Test'}
           </Button>
-          <Button className="!px-4 !py-2">Save</Button>
+          {hasEdits && (
+            <Button variant="secondary" onClick={handleRevert} disabled={saving || isRunning} className="!px-4 !py-2">
+              Revert
+            </Button>
+          )}
+          <Button onClick={handleSave} disabled={saving || isRunning} className="!px-4 !py-2">
+            {saving && <FontAwesomeIcon icon={faSpinner} className="mr-2 text-sm animate-spin" />}
+            Save
+          </Button>
         </div>
       </header>
 
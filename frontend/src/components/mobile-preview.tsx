@@ -1,7 +1,7 @@
 'use client';
 
 import { useState, useEffect, useCallback, useRef } from 'react';
-import { mobileApi } from '@/lib/api';
+import { mobileApi, subscribeEvents } from '@/lib/api';
 import { FontAwesomeIcon } from '@fortawesome/react-fontawesome';
 import {
   faSync,
@@ -60,6 +60,7 @@ export default function MobilePreview({
   const [tapping, setTapping] = useState(false);
   const containerRef = useRef<HTMLDivElement>(null);
   const intervalRef = useRef<NodeJS.Timeout | null>(null);
+  const streamingRef = useRef(false);
   const tapIdCounter = useRef(0);
 
   // Capture screenshot from device
@@ -103,9 +104,11 @@ export default function MobilePreview({
     getScreenSize();
     captureScreenshot();
 
-    // Set up interval for continuous refresh
+    // Set up interval for continuous refresh, paused while the device's screen stream sends frames
     intervalRef.current = setInterval(() => {
-      captureScreenshot();
+      if (!streamingRef.current) {
+        captureScreenshot();
+      }
     }, refreshInterval);
 
     return () => {
@@ -115,6 +118,23 @@ export default function MobilePreview({
     };
   }, [captureScreenshot, getScreenSize, refreshInterval]);
 
+  // Show frames from the device's screen stream when one is running
+  useEffect(() => {
+    if (!deviceId) return;
+    return subscribeEvents({ deviceId }, (message) => {
+      const frame = message.payload?.frame;
+      if (message.event === 'device:frame' && typeof frame === 'string') {
+        streamingRef.current = true;
+        setScreenshot(`data:image/png;base64,${frame}`);
+        setConnected(true);
+        setError(null);
+        setLoading(false);
+      } else if (message.event === 'device:stream_stopped' || message.event === 'device:stream_error') {
+        streamingRef.current = false;
+      }
+    });
+  }, [deviceId]);
+
   // Add a tap indicator with animation
   const addTapIndicator = useCallback((relX: number, relY: number, success: boolean) => {
     const id = tapIdCounter.current++;
@@ -48,6 +48,34 @@ export async function initializeApi(): Promise<void> {
   }
 }
 
+// Error codes the backend attaches to error responses
+export type ApiErrorCode =
+  | 'not_found'
+  | 'validation_error'
+  | 'conflict'
+  | 'unauthorized'
+  | 'forbidden'
+  | 'device_unavailable'
+  | 'device_error'
+  | 'tool_not_found'
+  | 'ai_provider_error'
+  | 'integration_error'
+  | 'db_error'
+  | 'timeout'
+  | 'internal_error';
+
+export class ApiError extends Error {
+  constructor(
+    message: string,
+    public status: number,
+    public code: ApiErrorCode,
+    public details: Record<string, unknown> | null = null
+  ) {
+    super(message);
+    this.name = 'ApiError';
+  }
+}
+
 // Generic fetch wrapper with error handling
 async function fetchApi<T>(
   endpoint: string,
@@ -65,7 +93,8 @@ async function fetchApi<T>(
 
   if (!response.ok) {
     const error = await response.json().catch(() => ({ detail: 'Unknown error' }));
-    throw new Error(error.detail || `HTTP ${response.status}`);
+    const message = typeof error.detail === 'string' ? error.detail : `HTTP ${response.status}`;
+    throw new ApiError(message, response.status, error.code || 'internal_error', error.details || null);
   }
 
   return response.json();
@@ -112,6 +141,108 @@ export async function openUrl(url: string): Promise<void> {
   }
 }
 
+// ============================================
+// Windows
+// ============================================
+
+// Opens a detached run viewer, in a desktop window when running in Electron
+export async function openRunWindow(runId: string): Promise<void> {
+  if (isElectron()) {
+    // @ts-ignore
+    await window.electronAPI.openRunWindow(runId);
+  } else {
+    window.open(`/runs/${encodeURIComponent(runId)}?window=run-${encodeURIComponent(runId)}`, `run-${runId}`);
+  }
+}
+
+// The id this window subscribes to events with; the main window is 'main'
+export async function getWindowId(): Promise<string> {
+  if (isElectron()) {
+    // @ts-ignore
+    return window.electronAPI.getWindowId();
+  }
+  return new URLSearchParams(window.location.search).get('window') || 'main';
+}
+
+export interface EventFilters {
+  windowId?: string;
+  testRunId?: string;
+  deviceId?: string;
+  dbData?: boolean;
+}
+
+// WebSocket URL of the event stream, narrowed to one window, run or device
+export function eventsUrl(filters: EventFilters = {}): string {
+  const params = new URLSearchParams();
+  if (filters.windowId) params.set('window_id', filters.windowId);
+  if (filters.testRunId) params.set('test_run_id', filters.testRunId);
+  if (filters.deviceId) params.set('device_id', filters.deviceId);
+  if (filters.dbData) params.set('db_data', 'true');
+  const query = params.toString();
+  return `${BACKEND_URL.replace(/^http/, 'ws')}/api/events/ws${query ? `?${query}` : ''}`;
+}
+
+export interface BackendEvent<T = Record<string, unknown>> {
+  event: string;
+  payload: T;
+  timestamp: number;
+}
+
+// Listen to the event stream as this window, returning a function that stops listening
+export function subscribeEvents(
+  filters: Omit<EventFilters, 'windowId'>,
+  onEvent: (message: BackendEvent) => void
+): () => void {
+  let socket: WebSocket | null = null;
+  let closed = false;
+  getWindowId().then((windowId) => {
+    if (closed) return;
+    socket = new WebSocket(eventsUrl({ ...filters, windowId }));
+    socket.onmessage = (message) => onEvent(JSON.parse(message.data));
+  });
+  return () => {
+    closed = true;
+    socket?.close();
+  };
+}
+
+// ============================================
+// Quick Actions
+// ============================================
+
+export interface QuickAction {
+  id: string;
+  title: string;
+  description: string;
+  shortcut: string | null;
+  args: Record<string, string>;
+}
+
+export interface QuickActionResult {
+  message: string;
+  route: string | null;
+  open_path: string | null;
+  data: unknown;
+}
+
+export const quickActionApi = {
+  async list(): Promise<QuickAction[]> {
+    return fetchApi<QuickAction[]>('/quick-actions');
+  },
+
+  async invoke(id: string, args: Record<string, unknown> = {}): Promise<QuickActionResult> {
+    return fetchApi<QuickActionResult>(`/quick-actions/${id}`, {
+      method: 'POST',
+      body: JSON.stringify(args),
+    });
+  },
+
+  // Opens a backend page an action returned, such as a run report
+  async openPath(path: string): Promise<void> {
+    await openUrl(`${BACKEND_URL}/api${path}`);
+  },
+};
+
 // ============================================
 // Project Commands
 // ============================================
@@ -128,6 +259,32 @@ export interface ConnectResponse {
   error?: string;
 }
 
+export interface RepoSurface {
+  kind: 'route' | 'component' | 'screen';
+  name: string;
+  path: string;
+  line: number;
+  language: string;
+}
+
+export interface ProposedTestCase {
+  name: string;
+  description: string;
+  category: string;
+  priority: string;
+  surface: RepoSurface;
+  suggestions: string[];
+}
+
+export interface RepoAnalysis {
+  repo_url: string;
+  commit: string | null;
+  files_scanned: number;
+  surfaces: RepoSurface[];
+  proposals: ProposedTestCase[];
+  warnings: string[];
+}
+
 export const projectApi = {
   async connect(data: ConnectRequest): Promise<ConnectResponse> {
     return fetchApi<ConnectResponse>('/projects/connect', {
@@ -169,6 +326,20 @@ export const projectApi = {
   async search(query: string): Promise<Project[]> {
     return fetchApi<Project[]>(`/projects/search/${encodeURIComponent(query)}`);
   },
+
+  async rebuildStats(id: string): Promise<{ test_cases: TestCaseStats; test_runs: TestRunSummary }> {
+    return fetchApi(`/projects/${id}/stats/rebuild`, { method: 'POST' });
+  },
+
+  async analyzeRepo(
+    id: string,
+    options: { branch?: string; use_ai?: boolean; max_surfaces?: number } = {}
+  ): Promise<RepoAnalysis> {
+    return fetchApi<RepoAnalysis>(`/projects/${id}/repo-analysis`, {
+      method: 'POST',
+      body: JSON.stringify(options),
+    });
+  },
 };
 
 // ============================================
@@ -227,6 +398,17 @@ export const testCaseApi = {
   async getStats(projectId: string): Promise<TestCaseStats> {
     return fetchApi<TestCaseStats>(`/test-cases/stats/${projectId}`);
   },
+
+  async saveGenerated(projectId: string, data: SaveGeneratedTests): Promise<TestCase[]> {
+    return fetchApi<TestCase[]>(`/test-cases/project/${projectId}/generated`, {
+      method: 'POST',
+      body: JSON.stringify(data),
+    });
+  },
+
+  async getGeneratedCode(id: string): Promise<GeneratedTestCode[]> {
+    return fetchApi<GeneratedTestCode[]>(`/test-cases/${id}/generated-code`);
+  },
 };
 
 // ============================================
@@ -284,6 +466,28 @@ export const scenarioApi = {
 // Step Commands
 // ============================================
 
+// Where to try a single step from the editor; config tries unsaved edits
+export interface TryStepOptions {
+  target: 'device' | 'browser';
+  device_id?: string;
+  platform?: 'android' | 'ios';
+  framework?: 'playwright' | 'cypress';
+  browser?: string;
+  headless?: boolean;
+  url?: string;
+  self_heal?: boolean;
+  config?: StepConfig;
+}
+
+export interface TryStepOutcome {
+  status: string;
+  duration_ms: number;
+  error_message: string | null;
+  healed_locator: Record<string, unknown> | null;
+  details: Record<string, unknown> | null;
+  screenshot: string | null; // Base64 PNG taken after the step
+}
+
 export const stepApi = {
   async create(data: CreateStep): Promise<Step> {
     return fetchApi<Step>('/steps', {
@@ -311,8 +515,9 @@ export const stepApi = {
     });
   },
 
-  async updateConfig(id: string, config: StepConfig): Promise<Step> {
-    return fetchApi<Step>(`/steps/${id}/config`, {
+  async updateConfig(id: string, config: StepConfig, expectedUpdatedAt?: string): Promise<Step> {
+    const query = expectedUpdatedAt ? `?expected_updated_at=${encodeURIComponent(expectedUpdatedAt)}` : '';
+    return fetchApi<Step>(`/steps/${id}/config${query}`, {
       method: 'PUT',
       body: JSON.stringify(config),
     });
@@ -343,12 +548,239 @@ export const stepApi = {
     });
     return result.deleted;
   },
+
+  async tryStep(id: string, options: TryStepOptions): Promise<TryStepOutcome> {
+    return fetchApi<TryStepOutcome>(`/steps/${id}/try`, {
+      method: 'POST',
+      body: JSON.stringify(options),
+    });
+  },
+
+  async setBreakpoint(id: string, enabled: boolean): Promise<Step> {
+    return fetchApi<Step>(`/steps/${id}/breakpoint`, {
+      method: 'PUT',
+      body: JSON.stringify({ enabled }),
+    });
+  },
+};
+
+// ============================================
+// Debug Commands
+// ============================================
+
+// Where a debug run is paused; 'debug:paused' and 'debug:resumed' events follow it live
+export interface DebugState {
+  test_run_id: string;
+  paused: boolean;
+  step_id: string | null;
+  step_index: number | null;
+  step_type: string | null;
+  config: Record<string, unknown> | null;
+}
+
+export interface TryStepResult {
+  status: string;
+  duration_ms: number;
+  error_message: string | null;
+  details: Record<string, unknown> | null;
+}
+
+export const debugApi = {
+  async getState(testRunId: string): Promise<DebugState> {
+    return fetchApi<DebugState>(`/debug/${testRunId}`);
+  },
+
+  async inspectDevice(testRunId: string): Promise<{ screenshot: string; ui_dump: string }> {
+    return fetchApi(`/debug/${testRunId}/device`);
+  },
+
+  async tryStep(testRunId: string, stepType: string, config: Record<string, unknown>): Promise<TryStepResult> {
+    return fetchApi<TryStepResult>(`/debug/${testRunId}/try-step`, {
+      method: 'POST',
+      body: JSON.stringify({ step_type: stepType, config }),
+    });
+  },
+
+  async resume(testRunId: string, config?: Record<string, unknown>): Promise<DebugState> {
+    return fetchApi<DebugState>(`/debug/${testRunId}/resume`, {
+      method: 'POST',
+      body: JSON.stringify({ config }),
+    });
+  },
+
+  async stepOver(testRunId: string, config?: Record<string, unknown>): Promise<DebugState> {
+    return fetchApi<DebugState>(`/debug/${testRunId}/step-over`, {
+      method: 'POST',
+      body: JSON.stringify({ config }),
+    });
+  },
+
+  async abort(testRunId: string): Promise<void> {
+    await fetchApi(`/debug/${testRunId}/abort`, { method: 'POST' });
+  },
+};
+
+// ============================================
+// Draft Commands
+// ============================================
+
+export type DraftEntity = 'scenario' | 'step' | 'test_case';
+
+export interface Draft<T = Record<string, unknown>> {
+  id: string;
+  entity: DraftEntity;
+  entity_id: string;
+  content: T;
+  base_updated_at: string | null;
+  created_at: string;
+  updated_at: string;
+  conflict: boolean;
+  current_updated_at: string | null;
+}
+
+export const draftApi = {
+  async list(entity?: DraftEntity): Promise<Draft[]> {
+    return fetchApi<Draft[]>(`/drafts${entity ? `?entity=${entity}` : ''}`);
+  },
+
+  async get<T = Record<string, unknown>>(entity: DraftEntity, entityId: string): Promise<Draft<T> | null> {
+    try {
+      return await fetchApi<Draft<T>>(`/drafts/${entity}/${entityId}`);
+    } catch {
+      return null;
+    }
+  },
+
+  async save<T extends object>(
+    entity: DraftEntity,
+    entityId: string,
+    content: T,
+    baseUpdatedAt?: string | null
+  ): Promise<Draft<T>> {
+    return fetchApi<Draft<T>>(`/drafts/${entity}/${entityId}`, {
+      method: 'PUT',
+      body: JSON.stringify({ content, base_updated_at: baseUpdatedAt ?? null }),
+    });
+  },
+
+  async discard(entity: DraftEntity, entityId: string): Promise<void> {
+    await fetchApi(`/drafts/${entity}/${entityId}`, { method: 'DELETE' });
+  },
+};
+
+// ============================================
+// Test Selection Commands
+// ============================================
+
+export interface FileTestMapping {
+  id: string;
+  project_id: string;
+  test_case_id: string;
+  path_pattern: string; // A file, a directory such as src/auth/, or a glob such as src/**/*.tsx
+  source: 'manual' | 'ai';
+  created_at: string;
+  updated_at: string;
+}
+
+export interface SelectedScenario {
+  scenario_id: string;
+  scenario_name: string;
+  test_case_id: string;
+  test_case_name: string;
+  score: number;
+  matched_files: string[];
+}
+
+export interface TestSelection {
+  base: string;
+  head: string;
+  changed_files: string[];
+  unmapped_files: string[];
+  scenarios: SelectedScenario[];
+}
+
+export const testSelectionApi = {
+  async forDiff(projectId: string, base: string, head: string, githubToken?: string): Promise<TestSelection> {
+    return fetchApi<TestSelection>('/test-selection/diff', {
+      method: 'POST',
+      body: JSON.stringify({ project_id: projectId, base, head, github_token: githubToken }),
+    });
+  },
+
+  async listMappings(projectId: string, testCaseId?: string): Promise<FileTestMapping[]> {
+    const params = new URLSearchParams({ project_id: projectId });
+    if (testCaseId) params.set('test_case_id', testCaseId);
+    return fetchApi<FileTestMapping[]>(`/test-selection/mappings?${params}`);
+  },
+
+  async createMapping(testCaseId: string, pathPattern: string): Promise<FileTestMapping> {
+    return fetchApi<FileTestMapping>('/test-selection/mappings', {
+      method: 'POST',
+      body: JSON.stringify({ test_case_id: testCaseId, path_pattern: pathPattern }),
+    });
+  },
+
+  async suggestMappings(projectId: string, files: string[]): Promise<FileTestMapping[]> {
+    return fetchApi<FileTestMapping[]>('/test-selection/mappings/suggest', {
+      method: 'POST',
+      body: JSON.stringify({ project_id: projectId, files }),
+    });
+  },
+
+  async deleteMapping(id: string): Promise<void> {
+    await fetchApi(`/test-selection/mappings/${id}`, { method: 'DELETE' });
+  },
+};
+
+// A run started by a GitHub push or pull request webhook
+export interface WebhookRun {
+  id: string;
+  project_id: string;
+  test_run_id: string | null;
+  event: 'push' | 'pull_request';
+  repository: string;
+  base_sha: string;
+  head_sha: string;
+  pull_number: number | null;
+  check_run_id: number | null;
+  error: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export const webhookApi = {
+  async listRuns(projectId: string, limit = 50): Promise<WebhookRun[]> {
+    return fetchApi<WebhookRun[]>(`/webhooks/runs?project_id=${projectId}&limit=${limit}`);
+  },
 };
 
 // ============================================
 // Test Run Commands
 // ============================================
 
+// Where a rerun of a run's failed scenarios executes
+export interface RerunOptions {
+  target: 'device' | 'browser';
+  device_id?: string;
+  platform?: 'android' | 'ios';
+  framework?: 'playwright' | 'cypress';
+  browser?: string;
+  headless?: boolean;
+  self_heal?: boolean;
+  priority?: 'high' | 'normal' | 'low';
+}
+
+// A scenario's outcome across a run and its reruns
+export interface LatestScenarioStatus {
+  scenario_id: string;
+  name: string;
+  status: 'passed' | 'failed' | 'skipped';
+  test_run_id: string;
+  first_status: 'passed' | 'failed' | 'skipped';
+  attempts: number;
+  passed_on_rerun: boolean;
+}
+
 export const testRunApi = {
   async create(data: CreateTestRun): Promise<TestRun> {
     return fetchApi<TestRun>('/test-runs', {
@@ -402,12 +834,31 @@ export const testRunApi = {
   async getSummary(projectId: string): Promise<TestRunSummary> {
     return fetchApi<TestRunSummary>(`/test-runs/summary/${projectId}`);
   },
+
+  async rerunFailures(id: string, options: RerunOptions): Promise<TestRun> {
+    return fetchApi<TestRun>(`/executions/${id}/rerun-failures`, {
+      method: 'POST',
+      body: JSON.stringify(options),
+    });
+  },
+
+  async getLatestStatus(id: string): Promise<LatestScenarioStatus[]> {
+    return fetchApi<LatestScenarioStatus[]>(`/executions/${id}/latest-status`);
+  },
 };
 
 // ============================================
 // Step Result Commands
 // ============================================
 
+export interface ScreenshotStats {
+  screenshots: number;
+  original_bytes: number;
+  stored_bytes: number;
+  saved_bytes: number;
+  saved_percent: number;
+}
+
 export const stepResultApi = {
   async create(data: CreateStepResult): Promise<StepResult> {
     return fetchApi<StepResult>('/step-results', {
@@ -416,9 +867,129 @@ export const stepResultApi = {
     });
   },
 
+  async createMany(results: CreateStepResult[]): Promise<StepResult[]> {
+    return fetchApi<StepResult[]>('/step-results/bulk', {
+      method: 'POST',
+      body: JSON.stringify(results),
+    });
+  },
+
   async list(testRunId: string): Promise<StepResult[]> {
     return fetchApi<StepResult[]>(`/step-results/test-run/${testRunId}`);
   },
+
+  screenshotUrl(stepResultId: string): string {
+    return `${BACKEND_URL}/api/step-results/${stepResultId}/screenshot`;
+  },
+
+  async getScreenshotStats(projectId: string): Promise<ScreenshotStats> {
+    return fetchApi<ScreenshotStats>(`/step-results/project/${projectId}/screenshot-stats`);
+  },
+};
+
+// ============================================
+// Storage Commands
+// ============================================
+
+export interface RunStorage {
+  test_run_id: string;
+  name: string;
+  status: string;
+  created_at: string;
+  attachment_bytes: number;
+  screenshot_bytes: number;
+  total_bytes: number;
+}
+
+export interface ProjectStorage {
+  project_id: string;
+  name: string;
+  runs: number;
+  attachment_bytes: number;
+  screenshot_bytes: number;
+  total_bytes: number;
+}
+
+export interface StorageUsage {
+  database_bytes: number;
+  snapshot_bytes: number;
+  artifact_bytes: number;
+  projects: ProjectStorage[];
+  runs: RunStorage[];
+}
+
+export interface CleanupCandidate {
+  test_run_id: string;
+  project_id: string;
+  name: string;
+  status: string;
+  created_at: string;
+  total_bytes: number;
+}
+
+export interface CleanupResult {
+  dry_run: boolean;
+  runs: CleanupCandidate[];
+  freed_bytes: number;
+  errors: string[];
+}
+
+export const storageApi = {
+  async getUsage(projectId?: string): Promise<StorageUsage> {
+    const query = projectId ? `?project_id=${encodeURIComponent(projectId)}` : '';
+    return fetchApi<StorageUsage>(`/storage/usage${query}`);
+  },
+
+  async previewCleanup(): Promise<CleanupResult> {
+    return fetchApi<CleanupResult>('/storage/cleanup/preview');
+  },
+
+  async runCleanup(): Promise<CleanupResult> {
+    return fetchApi<CleanupResult>('/storage/cleanup', { method: 'POST' });
+  },
+};
+
+// ============================================
+// Synonym Dictionary Commands
+// ============================================
+
+// Terms the offline element matcher treats as equal within a project
+export interface SynonymGroup {
+  id: string;
+  project_id: string;
+  language: string;
+  terms: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export const synonymApi = {
+  async list(projectId: string, language?: string): Promise<SynonymGroup[]> {
+    const query = language ? `?language=${encodeURIComponent(language)}` : '';
+    return fetchApi<SynonymGroup[]>(`/synonyms/project/${projectId}${query}`);
+  },
+
+  async create(projectId: string, terms: string[], language: string = 'en'): Promise<SynonymGroup> {
+    return fetchApi<SynonymGroup>('/synonyms', {
+      method: 'POST',
+      body: JSON.stringify({ project_id: projectId, language, terms }),
+    });
+  },
+
+  async update(id: string, data: { language?: string; terms?: string[] }): Promise<SynonymGroup> {
+    return fetchApi<SynonymGroup>(`/synonyms/${id}`, {
+      method: 'PUT',
+      body: JSON.stringify(data),
+    });
+  },
+
+  async delete(id: string): Promise<void> {
+    await fetchApi(`/synonyms/${id}`, { method: 'DELETE' });
+  },
+
+  async restoreDefaults(projectId: string): Promise<SynonymGroup[]> {
+    return fetchApi<SynonymGroup[]>(`/synonyms/project/${projectId}/defaults`, { method: 'POST' });
+  },
 };
 
 // ============================================
@@ -504,6 +1075,38 @@ export interface GenerateTestsResponse {
   coverage_estimate: string;
 }
 
+export interface SaveGeneratedTests {
+  response: GenerateTestsResponse;
+  language?: string;
+  framework?: string;
+  source_code?: string;
+  source_ref?: string; // e.g. a commit SHA or path@commit
+}
+
+export interface GeneratedTestCode {
+  id: string;
+  test_case_id: string;
+  code: string;
+  test_type: string | null;
+  language: string | null;
+  framework: string | null;
+  source_code: string | null;
+  source_ref: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+// A long-running AI agent request; progress also arrives as 'ai:job_progress' events
+export interface AiJob {
+  job_id: string;
+  command: string;
+  status: 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+  progress: number;
+  message: string | null;
+  error: string | null;
+  submitted_at: number;
+}
+
 export interface ParseRequirementsRequest {
   requirements: string;
   format?: string;
@@ -539,6 +1142,25 @@ export const aiAgentApi = {
     });
   },
 
+  async submitGenerationJob(request: GenerateTestsRequest): Promise<AiJob> {
+    return fetchApi<AiJob>('/ai/generate-tests/jobs', {
+      method: 'POST',
+      body: JSON.stringify(request),
+    });
+  },
+
+  async getJob(jobId: string): Promise<AiJob> {
+    return fetchApi<AiJob>(`/ai/jobs/${encodeURIComponent(jobId)}`);
+  },
+
+  async getJobResult(jobId: string): Promise<GenerateTestsResponse> {
+    return fetchApi<GenerateTestsResponse>(`/ai/jobs/${encodeURIComponent(jobId)}/result`);
+  },
+
+  async cancelJob(jobId: string): Promise<AiJob> {
+    return fetchApi<AiJob>(`/ai/jobs/${encodeURIComponent(jobId)}`, { method: 'DELETE' });
+  },
+
   async parseRequirements(request: ParseRequirementsRequest): Promise<ParseRequirementsResponse> {
     return fetchApi<ParseRequirementsResponse>('/ai/parse-requirements', {
       method: 'POST',
@@ -599,6 +1221,8 @@ export interface AiWebElementLocation {
   found: boolean;
   selector: string;
   xpath?: string;
+  frame_path?: string[];
+  shadow_path?: string[];
   element_type: string;
   confidence: number;
   description: string;
@@ -899,6 +1523,17 @@ export interface DeviceInfo {
   platform: 'android' | 'ios';
 }
 
+export interface InspectedElement {
+  found: boolean;
+  resource_id: string | null;
+  text: string | null;
+  content_desc: string | null;
+  class_name: string | null;
+  clickable: boolean;
+  bounds: [number, number, number, number] | null;
+  locator: Record<string, unknown>;
+}
+
 export const mobileApi = {
   // Android
   async listAndroidDevices(): Promise<DeviceInfo[]> {
@@ -977,6 +1612,10 @@ export const mobileApi = {
     return result.xml;
   },
 
+  async androidInspectAt(deviceId: string, x: number, y: number): Promise<InspectedElement> {
+    return fetchApi<InspectedElement>(`/mobile/android/${deviceId}/inspect?x=${x}&y=${y}`);
+  },
+
   // iOS
   async listIosDevices(): Promise<DeviceInfo[]> {
     return fetchApi<DeviceInfo[]>('/mobile/ios/devices');
@@ -0,0 +1,69 @@
+// Serializes a page for AI element lookups, including what outerHTML leaves out:
+// the documents of same-origin iframes and the contents of open shadow roots.
+// Frames are inlined inside their <iframe> tag and shadow roots as declarative
+// <template shadowrootmode="open"> blocks, so the AI can report the frame and
+// shadow host path to an element. Script and style bodies are dropped to keep
+// the request small.
+
+const VOID_TAGS = new Set([
+  'area', 'base', 'br', 'col', 'embed', 'hr', 'img', 'input', 'link', 'meta', 'source', 'track', 'wbr',
+]);
+const EMPTIED_TAGS = new Set(['script', 'style', 'noscript']);
+
+function escapeText(text: string): string {
+  return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;');
+}
+
+function escapeAttribute(value: string): string {
+  return escapeText(value).replace(/"/g, '&quot;');
+}
+
+function frameDocument(element: Element): Document | null {
+  try {
+    return (element as HTMLIFrameElement).contentDocument;
+  } catch {
+    return null; // Cross-origin frames can't be read
+  }
+}
+
+function serializeChildren(parent: Node): string {
+  return Array.from(parent.childNodes).map(serializeNode).join('');
+}
+
+function serializeNode(node: Node): string {
+  if (node.nodeType === Node.TEXT_NODE) {
+    return escapeText(node.textContent || '');
+  }
+  if (node.nodeType !== Node.ELEMENT_NODE) {
+    return '';
+  }
+
+  const element = node as Element;
+  const tag = element.tagName.toLowerCase();
+  const attributes = Array.from(element.attributes)
+    .map((attribute) => ` ${attribute.name}="${escapeAttribute(attribute.value)}"`)
+    .join('');
+  if (VOID_TAGS.has(tag)) {
+    return `<${tag}${attributes}>`;
+  }
+  if (EMPTIED_TAGS.has(tag)) {
+    return `<${tag}${attributes}></${tag}>`;
+  }
+
+  let inner = '';
+  if (element.shadowRoot) {
+    inner += `<template shadowrootmode="open">${serializeChildren(element.shadowRoot)}</template>`;
+  }
+  if (tag === 'iframe' || tag === 'frame') {
+    const frame = frameDocument(element);
+    if (frame?.documentElement) {
+      inner += serializeNode(frame.documentElement);
+    }
+  }
+  inner += serializeChildren(element);
+  return `<${tag}${attributes}>${inner}</${tag}>`;
+}
+
+export function serializePageHtml(doc: Document | null | undefined): string | undefined {
+  return doc?.documentElement ? serializeNode(doc.documentElement) : undefined;
+}
@@ -69,6 +69,8 @@ export interface UpdateTestCase {
   priority?: string;
   test_type?: string;
   status?: string;
+  // The updated_at the edit was based on; stale edits fail with a conflict carrying the current copy
+  expected_updated_at?: string;
 }
 
 export interface TestCaseFilter {
@@ -108,21 +110,27 @@ export interface Scenario {
   name: string;
   description: string | null;
   target_url: string | null;
+  screenshot_policy: ScreenshotPolicy | null;
   created_at: string;
   updated_at: string;
 }
 
+export type ScreenshotPolicy = 'always' | 'on_failure' | 'never';
+
 export interface CreateScenario {
   test_case_id: string;
   name: string;
   description?: string;
   target_url?: string;
+  screenshot_policy?: ScreenshotPolicy;
 }
 
 export interface UpdateScenario {
   name?: string;
   description?: string;
   target_url?: string;
+  screenshot_policy?: ScreenshotPolicy | null;
+  expected_updated_at?: string;
 }
 
 export interface ScenarioWithSteps extends Scenario {
@@ -152,6 +160,7 @@ export interface Step {
   step_type: string;
   label: string;
   config: string; // JSON string
+  breakpoint: boolean;
   created_at: string;
   updated_at: string;
 }
@@ -163,6 +172,7 @@ export interface StepWithConfig {
   step_type: string;
   label: string;
   config: StepConfig;
+  breakpoint: boolean;
   created_at: string;
   updated_at: string;
 }
@@ -180,6 +190,7 @@ export interface UpdateStep {
   step_type?: string;
   label?: string;
   config?: StepConfig;
+  expected_updated_at?: string;
 }
 
 // ============================================
@@ -199,6 +210,7 @@ export interface TestRun {
   skipped: number;
   started_at: string | null;
   completed_at: string | null;
+  rerun_of: string | null;
   created_at: string;
 }
 
@@ -233,6 +245,7 @@ export interface StepResult {
   duration_ms: number | null;
   error_message: string | null;
   screenshot_path: string | null;
+  screenshot_bytes: number | null;
   created_at: string;
 }
 